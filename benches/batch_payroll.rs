@@ -0,0 +1,69 @@
+use employee_management::{
+    AllowancePeriod, ContractEmployee, EmployeeData, FulltimeEmployee, PayrollData,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const EMPLOYEES: usize = 50_000;
+const ROUNDS: u32 = 10;
+
+fn employees() -> Vec<EmployeeData> {
+    (0..EMPLOYEES)
+        .map(|i| {
+            let employee_id = format!("E{:05}", i);
+            if i % 4 == 0 {
+                EmployeeData::Contract(ContractEmployee::new(
+                    employee_id,
+                    150.0 + (i % 40) as f64,
+                    250_000.0,
+                    AllowancePeriod::Monthly,
+                    75_000.0 + (i % 10) as f64 * 5_000.0,
+                ))
+            } else {
+                EmployeeData::Fulltime(FulltimeEmployee::new(
+                    employee_id,
+                    160.0 + (i % 30) as f64,
+                    1_000_000.0,
+                    AllowancePeriod::Monthly,
+                    5_000_000.0 + (i % 50) as f64 * 250_000.0,
+                ))
+            }
+        })
+        .collect()
+}
+
+fn measure(label: &str, f: impl Fn(Vec<EmployeeData>) -> Vec<PayrollData>) -> Duration {
+    let input = employees();
+    let mut total = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let batch = input.clone();
+        let start = Instant::now();
+        black_box(f(batch));
+        total += start.elapsed();
+    }
+    let average = total / ROUNDS;
+    println!("{:<12} {:>10.2?} per {} employees", label, average, EMPLOYEES);
+    average
+}
+
+fn main() {
+    let sequential = measure("sequential", |batch| {
+        batch
+            .into_iter()
+            .map(|employee| PayrollData::new(employee, "September 2024".to_string()))
+            .collect()
+    });
+    let batched = measure("batch", |batch| {
+        PayrollData::compute_batch(batch, "September 2024")
+    });
+
+    println!(
+        "speedup      {:>10.2}x{}",
+        sequential.as_secs_f64() / batched.as_secs_f64(),
+        if cfg!(feature = "parallel") {
+            ""
+        } else {
+            " (build with --features parallel to use rayon)"
+        }
+    );
+}
@@ -0,0 +1,31 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/payroll.proto"], &["proto"])
+            .expect("proto/payroll.proto compiles");
+    }
+
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+        let config = cbindgen::Config {
+            language: cbindgen::Language::C,
+            include_guard: Some("EMPLOYEE_MANAGEMENT_H".to_string()),
+            autogen_warning: Some(
+                "/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */".to_string(),
+            ),
+            ..cbindgen::Config::default()
+        };
+        cbindgen::Builder::new()
+            .with_src(std::path::Path::new(&crate_dir).join("src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("C header generates")
+            .write_to_file(std::path::Path::new(&crate_dir).join("include/employee_management.h"));
+    }
+}
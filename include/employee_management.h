@@ -0,0 +1,90 @@
+#ifndef EMPLOYEE_MANAGEMENT_H
+#define EMPLOYEE_MANAGEMENT_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define EMP_OK 0
+
+#define EMP_NULL_POINTER 1
+
+#define EMP_INVALID_ARGUMENT 2
+
+#define EMP_FULLTIME 0
+
+#define EMP_CONTRACT 1
+
+#define EMP_ALLOWANCE_MONTHLY 0
+
+#define EMP_ALLOWANCE_YEARLY 1
+
+#define EMP_ALLOWANCE_PER_PROJECT 2
+
+#define EMP_PTKP_NONE -1
+
+#define EMP_PTKP_TK0 0
+
+#define EMP_PTKP_TK1 1
+
+#define EMP_PTKP_TK2 2
+
+#define EMP_PTKP_TK3 3
+
+#define EMP_PTKP_K0 4
+
+#define EMP_PTKP_K1 5
+
+#define EMP_PTKP_K2 6
+
+#define EMP_PTKP_K3 7
+
+typedef struct EmpEmployee {
+  int kind;
+  double work_hours;
+  double allowance;
+  int allowance_period;
+  double base_salary;
+  double hourly_rate;
+  int ptkp_status;
+} EmpEmployee;
+
+/**
+ * # Safety
+ *
+ * `employee` must point to a valid `EmpEmployee` and `gross` to writable memory for one `double`.
+ */
+int emp_calculate_gross(const struct EmpEmployee *employee, double *gross);
+
+/**
+ * # Safety
+ *
+ * `employee` must point to a valid `EmpEmployee` and `tax` to writable memory for one `double`.
+ */
+int emp_calculate_tax(const struct EmpEmployee *employee, double *tax);
+
+/**
+ * # Safety
+ *
+ * `employee_json` and `pay_period` must be valid NUL-terminated strings. The returned string
+ * must be released with `emp_string_free`.
+ */
+char *emp_process_record(const char *employee_json, const char *pay_period);
+
+/**
+ * # Safety
+ *
+ * `value` must be null or a string returned by this library that has not been freed yet.
+ */
+void emp_string_free(char *value);
+
+/**
+ * Returns the message of the last failed call on this thread, or null. The pointer stays valid
+ * until the next failing call on the same thread.
+ */
+const char *emp_last_error(void);
+
+#endif  /* EMPLOYEE_MANAGEMENT_H */
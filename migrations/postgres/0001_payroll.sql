@@ -0,0 +1,31 @@
+-- The payroll document without its employees and records, which have tables of their own. There is
+-- one row; revision 0 means nothing has been saved yet.
+CREATE TABLE payroll_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    revision BIGINT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    document JSONB NOT NULL,
+    saved_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+INSERT INTO payroll_state (revision, schema_version, document) VALUES (0, 0, '{}');
+
+CREATE TABLE employees (
+    position INTEGER PRIMARY KEY,
+    employee_id TEXT NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX employees_employee_id ON employees (employee_id);
+
+CREATE TABLE payroll_records (
+    position INTEGER PRIMARY KEY,
+    record_id TEXT NOT NULL,
+    employee_id TEXT NOT NULL,
+    pay_period TEXT NOT NULL,
+    voided BOOLEAN NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX payroll_records_record_id ON payroll_records (record_id);
+CREATE INDEX payroll_records_employee_id ON payroll_records (employee_id, pay_period);
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One API request: who made it, whose data it concerned and how it ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AccessLogEntry {
+    pub timestamp: DateTime<Utc>,
+    /// The API key's name or token subject, `employee:ID` for self-service sign-ins, `anonymous`
+    /// while authentication is off, or `unauthenticated` when the credential was refused.
+    pub client: String,
+    pub remote_addr: Option<String>,
+    pub method: String,
+    /// Path and query string as requested.
+    pub path: String,
+    /// The employee whose data was read or changed, when the request concerns one; requests
+    /// listing records or employees without a filter touch everyone's.
+    pub employee_id: Option<String>,
+    pub status: u16,
+    pub duration_ms: u64,
+}
+
+/// Appends an [`AccessLogEntry`] per request to a JSON Lines file, which is only ever added to.
+#[derive(Debug)]
+pub struct AccessLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl AccessLog {
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &AccessLogEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        file.write_all(&line)
+    }
+}
@@ -0,0 +1,487 @@
+use crate::equity::EQUITY_SETTLED;
+use crate::export::write_csv_row;
+use crate::insurance::InsurancePremium;
+use crate::natura::NATURA_PROVIDED;
+use crate::payroll::{PayrollData, PayrollRun};
+use crate::remittance::EXTRA_TAX_LINES;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountCodes {
+    pub salary_expense: String,
+    pub bpjs_payable: String,
+    pub pph21_payable: String,
+    pub cash_bank: String,
+    /// Premiums owed to private insurers, the employee's and the company's share.
+    #[serde(default = "default_insurance_payable")]
+    pub insurance_payable: String,
+    /// The company's share of private insurance premiums.
+    #[serde(default = "default_insurance_expense")]
+    pub insurance_expense: String,
+    /// Other amounts withheld from pay: penalties, adjustments, rule deductions and the like.
+    #[serde(default = "default_deductions_payable")]
+    pub deductions_payable: String,
+    /// Pay settled in shares or in kind rather than from the bank.
+    #[serde(default = "default_settled_in_kind")]
+    pub settled_in_kind: String,
+    /// Accounts for particular deductions by name, ahead of the accounts above.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deduction_accounts: BTreeMap<String, String>,
+}
+
+fn default_insurance_payable() -> String {
+    "2150".to_string()
+}
+
+fn default_insurance_expense() -> String {
+    "6150".to_string()
+}
+
+fn default_deductions_payable() -> String {
+    "2190".to_string()
+}
+
+fn default_settled_in_kind() -> String {
+    "2180".to_string()
+}
+
+impl AccountCodes {
+    /// The account a deduction taken from pay is credited to.
+    pub fn for_deduction(&self, name: &str) -> &str {
+        if let Some(account) = self.deduction_accounts.get(name) {
+            return account;
+        }
+        if EXTRA_TAX_LINES.contains(&name) {
+            &self.pph21_payable
+        } else if name == EQUITY_SETTLED || name == NATURA_PROVIDED {
+            &self.settled_in_kind
+        } else {
+            &self.deductions_payable
+        }
+    }
+}
+
+impl Default for AccountCodes {
+    fn default() -> Self {
+        Self {
+            salary_expense: "6100".to_string(),
+            bpjs_payable: "2130".to_string(),
+            pph21_payable: "2140".to_string(),
+            cash_bank: "1110".to_string(),
+            insurance_payable: default_insurance_payable(),
+            insurance_expense: default_insurance_expense(),
+            deductions_payable: default_deductions_payable(),
+            settled_in_kind: default_settled_in_kind(),
+            deduction_accounts: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JournalLine {
+    pub date: NaiveDate,
+    pub reference: String,
+    pub account_code: String,
+    pub description: String,
+    pub debit: f64,
+    pub credit: f64,
+}
+
+/// The run's salary expense against what was withheld from it and the net pay: PPh 21, BPJS, the
+/// premiums owed to each insurer (with the company's share as insurance expense), and each other
+/// deduction on the account [`AccountCodes::for_deduction`] gives it.
+pub fn journal_entries(run: &PayrollRun, records: &[&PayrollData], accounts: &AccountCodes) -> Vec<JournalLine> {
+    let mut gross = 0.0;
+    let mut tax = 0.0;
+    let mut bpjs = 0.0;
+    let mut net = 0.0;
+    let mut deductions: Vec<(&str, f64)> = Vec::new();
+    let mut insurers: Vec<(&str, f64, f64)> = Vec::new();
+    for record in records {
+        let breakdown = record.breakdown();
+        gross += record.gross_salary;
+        tax += breakdown.tax;
+        bpjs += breakdown.bpjs_kesehatan + breakdown.bpjs_ketenagakerjaan;
+        net += record.net_salary;
+        for premium in &record.insurance {
+            match insurers.iter_mut().find(|(insurer, _, _)| *insurer == premium.insurer) {
+                Some((_, employee_share, employer_share)) => {
+                    *employee_share += premium.employee_share;
+                    *employer_share += premium.employer_share;
+                }
+                None => insurers.push((&premium.insurer, premium.employee_share, premium.employer_share)),
+            }
+        }
+        let premiums: Vec<String> = record
+            .insurance
+            .iter()
+            .map(|premium| InsurancePremium::deduction_name(&premium.plan))
+            .collect();
+        for deduction in record.extra_deductions.iter().filter(|deduction| !premiums.contains(&deduction.name)) {
+            match deductions.iter_mut().find(|(name, _)| *name == deduction.name) {
+                Some((_, amount)) => *amount += deduction.amount,
+                None => deductions.push((&deduction.name, deduction.amount)),
+            }
+        }
+    }
+
+    let date = run
+        .approved_date
+        .unwrap_or(run.created_date)
+        .date_naive();
+    let reference = format!("PAYRUN-{}", run.run_id);
+    let line = |account_code: &str, description: String, debit: f64, credit: f64| JournalLine {
+        date,
+        reference: reference.clone(),
+        account_code: account_code.to_string(),
+        description,
+        debit,
+        credit,
+    };
+
+    let mut lines = vec![line(
+        &accounts.salary_expense,
+        format!("Salary expense {}", run.pay_period),
+        gross,
+        0.0,
+    )];
+    let employer_premiums: f64 = insurers.iter().map(|(_, _, employer_share)| employer_share).sum();
+    if employer_premiums > 0.0 {
+        lines.push(line(
+            &accounts.insurance_expense,
+            format!("Insurance premiums, company share {}", run.pay_period),
+            employer_premiums,
+            0.0,
+        ));
+    }
+    if tax > 0.0 {
+        lines.push(line(
+            &accounts.pph21_payable,
+            format!("PPh 21 payable {}", run.pay_period),
+            0.0,
+            tax,
+        ));
+    }
+    if bpjs > 0.0 {
+        lines.push(line(
+            &accounts.bpjs_payable,
+            format!("BPJS payable {}", run.pay_period),
+            0.0,
+            bpjs,
+        ));
+    }
+    for (insurer, employee_share, employer_share) in insurers {
+        if employee_share + employer_share > 0.0 {
+            lines.push(line(
+                &accounts.insurance_payable,
+                format!("Insurance premiums payable to {} {}", insurer, run.pay_period),
+                0.0,
+                employee_share + employer_share,
+            ));
+        }
+    }
+    for (name, amount) in deductions {
+        let description = format!("{} {}", name, run.pay_period);
+        if amount > 0.0 {
+            lines.push(line(accounts.for_deduction(name), description, 0.0, amount));
+        } else if amount < 0.0 {
+            lines.push(line(accounts.for_deduction(name), description, -amount, 0.0));
+        }
+    }
+    lines.push(line(
+        &accounts.cash_bank,
+        format!("Net salary paid {}", run.pay_period),
+        0.0,
+        net,
+    ));
+    lines
+}
+
+pub fn write_journal_csv<W: Write>(lines: &[JournalLine], writer: &mut W) -> io::Result<()> {
+    write_csv_row(
+        writer,
+        &[
+            "Date".to_string(),
+            "Reference".to_string(),
+            "Account".to_string(),
+            "Description".to_string(),
+            "Debit".to_string(),
+            "Credit".to_string(),
+        ],
+    )?;
+    for (line, (debit, credit)) in lines.iter().zip(balanced_cents(lines)?) {
+        write_csv_row(
+            writer,
+            &[
+                line.date.format("%Y-%m-%d").to_string(),
+                line.reference.clone(),
+                line.account_code.clone(),
+                line.description.clone(),
+                format_cents(debit),
+                format_cents(credit),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// The layout a journal is exported in: this crate's own CSV, or the journal import templates of
+/// QuickBooks Online and Xero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JournalFormat {
+    #[default]
+    Generic,
+    QuickBooks,
+    Xero,
+}
+
+impl JournalFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalFormat::Generic => "generic",
+            JournalFormat::QuickBooks => "quickbooks",
+            JournalFormat::Xero => "xero",
+        }
+    }
+
+    pub fn write<W: Write>(&self, lines: &[JournalLine], writer: &mut W) -> io::Result<()> {
+        match self {
+            JournalFormat::Generic => write_journal_csv(lines, writer),
+            JournalFormat::QuickBooks => write_quickbooks_journal_csv(lines, writer),
+            JournalFormat::Xero => write_xero_journal_csv(lines, writer),
+        }
+    }
+}
+
+impl fmt::Display for JournalFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JournalFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "generic" | "csv" => Ok(JournalFormat::Generic),
+            "quickbooks" | "qbo" => Ok(JournalFormat::QuickBooks),
+            "xero" => Ok(JournalFormat::Xero),
+            _ => Err(format!(
+                "Unknown journal format '{}' (expected generic, quickbooks or xero)",
+                s
+            )),
+        }
+    }
+}
+
+/// Each line's debit and credit in cents. Both importers refuse a journal whose rounded lines do not
+/// balance, so the rounding difference goes to the last line, the net pay credited to the bank. A
+/// journal whose amounts differ by a cent or more before rounding is refused instead.
+fn balanced_cents(lines: &[JournalLine]) -> io::Result<Vec<(i64, i64)>> {
+    let debits: f64 = lines.iter().map(|line| line.debit).sum();
+    let credits: f64 = lines.iter().map(|line| line.credit).sum();
+    if (debits - credits).abs() >= 0.01 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("journal does not balance: debits {:.2}, credits {:.2}", debits, credits),
+        ));
+    }
+    let mut cents: Vec<(i64, i64)> = lines
+        .iter()
+        .map(|line| ((line.debit * 100.0).round() as i64, (line.credit * 100.0).round() as i64))
+        .collect();
+    let difference: i64 = cents.iter().map(|(debit, credit)| debit - credit).sum();
+    if let Some((debit, credit)) = cents.last_mut() {
+        match *credit > 0 || *debit == 0 {
+            true => *credit += difference,
+            false => *debit -= difference,
+        }
+    }
+    Ok(cents)
+}
+
+fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+/// QuickBooks Online's journal entry import: lines sharing a journal number form one entry, and
+/// each line has either a debit or a credit. Dates are day first, as in a non-US company file.
+pub fn write_quickbooks_journal_csv<W: Write>(lines: &[JournalLine], writer: &mut W) -> io::Result<()> {
+    let amount = |cents: i64| if cents == 0 { String::new() } else { format_cents(cents) };
+    write_csv_row(
+        writer,
+        &[
+            "Journal No".to_string(),
+            "Journal Date".to_string(),
+            "Account".to_string(),
+            "Debits".to_string(),
+            "Credits".to_string(),
+            "Description".to_string(),
+        ],
+    )?;
+    for (line, (debit, credit)) in lines.iter().zip(balanced_cents(lines)?) {
+        write_csv_row(
+            writer,
+            &[
+                line.reference.clone(),
+                line.date.format("%d/%m/%Y").to_string(),
+                line.account_code.clone(),
+                amount(debit),
+                amount(credit),
+                line.description.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Xero's manual journal import: lines sharing a narration and date form one journal, debits are
+/// positive amounts and credits negative. Payroll lines carry no VAT, so they are "Tax Exempt".
+pub fn write_xero_journal_csv<W: Write>(lines: &[JournalLine], writer: &mut W) -> io::Result<()> {
+    write_csv_row(
+        writer,
+        &[
+            "*Narration".to_string(),
+            "*Date".to_string(),
+            "Description".to_string(),
+            "*AccountCode".to_string(),
+            "*TaxRate".to_string(),
+            "*Amount".to_string(),
+        ],
+    )?;
+    for (line, (debit, credit)) in lines.iter().zip(balanced_cents(lines)?) {
+        write_csv_row(
+            writer,
+            &[
+                line.reference.clone(),
+                line.date.format("%d/%m/%Y").to_string(),
+                line.description.clone(),
+                line.account_code.clone(),
+                "Tax Exempt".to_string(),
+                format_cents(debit - credit),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::{AllowancePeriod, PtkpStatus};
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::{EmployeeData, RunStatus};
+    use crate::rules::PayrollComponent;
+    use crate::travel::PER_DIEM_TAX;
+    use chrono::{TimeZone, Utc};
+
+    fn record(employee_id: &str, base_salary: f64) -> PayrollData {
+        let mut employee =
+            FulltimeEmployee::new(employee_id.to_string(), 173.0, 0.0, AllowancePeriod::Monthly, base_salary);
+        employee.ptkp_status = Some(PtkpStatus::TK0);
+        PayrollData::new(EmployeeData::Fulltime(employee), "2026-01".to_string())
+    }
+
+    fn deduct(record: &mut PayrollData, name: &str, amount: f64) {
+        record.extra_deductions.push(PayrollComponent {
+            name: name.to_string(),
+            amount,
+        });
+        record.deductions += amount;
+        record.net_salary -= amount;
+    }
+
+    fn run() -> PayrollRun {
+        PayrollRun {
+            run_id: 3,
+            pay_period: "2026-01".to_string(),
+            record_indices: vec![0, 1],
+            status: RunStatus::Approved,
+            created_date: Utc.with_ymd_and_hms(2026, 1, 31, 9, 0, 0).unwrap(),
+            approved_date: None,
+            deliveries: Vec::new(),
+            disbursement: None,
+            held: Vec::new(),
+        }
+    }
+
+    fn totals(lines: &[JournalLine]) -> (f64, f64) {
+        (lines.iter().map(|line| line.debit).sum(), lines.iter().map(|line| line.credit).sum())
+    }
+
+    #[test]
+    fn every_deduction_is_credited_so_the_journal_balances() {
+        let mut first = record("E1", 12_345_678.91);
+        deduct(&mut first, "Late Penalty", 150_000.0);
+        deduct(&mut first, PER_DIEM_TAX, 12_500.0);
+        first.insurance.push(InsurancePremium {
+            plan: "HEALTH".to_string(),
+            insurer: "Insurer".to_string(),
+            tier: "employee".to_string(),
+            employee_share: 200_000.0,
+            employer_share: 600_000.0,
+        });
+        deduct(&mut first, &InsurancePremium::deduction_name("HEALTH"), 200_000.0);
+        let second = record("E2", 7_000_000.0);
+        let accounts = AccountCodes::default();
+
+        let lines = journal_entries(&run(), &[&first, &second], &accounts);
+        let (debits, credits) = totals(&lines);
+        assert!((debits - credits).abs() < 0.005, "debits {} credits {}", debits, credits);
+        assert!(lines.iter().all(|line| line.reference == "PAYRUN-3"));
+        let credited = |account: &str| -> f64 {
+            lines.iter().filter(|line| line.account_code == account).map(|line| line.credit).sum()
+        };
+        assert_eq!(credited(&accounts.deductions_payable), 150_000.0);
+        assert_eq!(credited(&accounts.insurance_payable), 800_000.0);
+        assert_eq!(
+            credited(&accounts.pph21_payable),
+            first.breakdown().tax + second.breakdown().tax + 12_500.0
+        );
+        assert_eq!(credited(&accounts.cash_bank), first.net_salary + second.net_salary);
+    }
+
+    #[test]
+    fn exported_cents_balance_with_the_rounding_on_the_net_pay_line() {
+        let first = record("E1", 10_000_000.004);
+        let second = record("E2", 10_000_000.004);
+        let lines = journal_entries(&run(), &[&first, &second], &AccountCodes::default());
+        let cents = balanced_cents(&lines).unwrap();
+        assert_eq!(cents.iter().map(|(debit, credit)| debit - credit).sum::<i64>(), 0);
+
+        let mut csv = Vec::new();
+        write_journal_csv(&lines, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("Date,Reference,Account,Description,Debit,Credit\n"));
+        assert!(csv.contains("2026-01-31,PAYRUN-3,6100,Salary expense 2026-01,20000000.01,0.00\n"));
+    }
+
+    #[test]
+    fn an_unbalanced_journal_is_refused_in_every_format() {
+        let mut lines = journal_entries(&run(), &[&record("E1", 9_000_000.0)], &AccountCodes::default());
+        lines[0].debit += 0.5;
+        for format in [JournalFormat::Generic, JournalFormat::QuickBooks, JournalFormat::Xero] {
+            let err = format.write(&lines, &mut Vec::new()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().starts_with("journal does not balance"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn named_deduction_accounts_come_first() {
+        let mut accounts = AccountCodes::default();
+        accounts.deduction_accounts.insert("Late Penalty".to_string(), "4900".to_string());
+        assert_eq!(accounts.for_deduction("Late Penalty"), "4900");
+        assert_eq!(accounts.for_deduction(EQUITY_SETTLED), accounts.settled_in_kind);
+        assert_eq!(accounts.for_deduction("Loan Repayment"), accounts.deductions_payable);
+    }
+}
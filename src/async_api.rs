@@ -0,0 +1,138 @@
+use crate::delivery::{PayslipDelivery, PayslipOptions, PayslipSender};
+use crate::error::{PayrollError, StorageError};
+use crate::payroll::{EmployeeData, Payroll, PayrollData, PayrollRun};
+use crate::shared::SharedPayroll;
+use crate::storage::Storage;
+use std::future::Future;
+use std::io;
+use std::sync::Arc;
+use tokio::task;
+
+pub trait AsyncStorage {
+    fn load(&self) -> impl Future<Output = Result<Payroll, StorageError>> + Send;
+    fn save(&self, payroll: &SharedPayroll) -> impl Future<Output = Result<(), StorageError>> + Send;
+}
+
+#[derive(Debug, Clone)]
+pub struct AsyncStorageAdapter<S> {
+    inner: Arc<S>,
+}
+
+impl<S> AsyncStorageAdapter<S>
+where
+    S: Storage + Send + Sync + 'static,
+{
+    pub fn new(storage: S) -> Self {
+        Self {
+            inner: Arc::new(storage),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S> AsyncStorage for AsyncStorageAdapter<S>
+where
+    S: Storage + Send + Sync + 'static,
+{
+    async fn load(&self) -> Result<Payroll, StorageError> {
+        let storage = Arc::clone(&self.inner);
+        task::spawn_blocking(move || storage.load())
+            .await
+            .map_err(join_error)?
+    }
+
+    async fn save(&self, payroll: &SharedPayroll) -> Result<(), StorageError> {
+        let storage = Arc::clone(&self.inner);
+        let payroll = payroll.clone();
+        task::spawn_blocking(move || payroll.read(|payroll| storage.save(payroll)))
+            .await
+            .map_err(join_error)?
+    }
+}
+
+fn join_error(err: task::JoinError) -> StorageError {
+    StorageError::Io(io::Error::other(err))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AsyncPayroll {
+    shared: SharedPayroll,
+}
+
+impl AsyncPayroll {
+    pub fn new(payroll: Payroll) -> Self {
+        Self {
+            shared: SharedPayroll::new(payroll),
+        }
+    }
+
+    pub async fn load<S: AsyncStorage>(storage: &S) -> Result<Self, StorageError> {
+        Ok(Self::new(storage.load().await?))
+    }
+
+    pub async fn save<S: AsyncStorage>(&self, storage: &S) -> Result<(), StorageError> {
+        storage.save(&self.shared).await
+    }
+
+    pub fn shared(&self) -> &SharedPayroll {
+        &self.shared
+    }
+
+    pub async fn add_employee(&self, employee: EmployeeData) {
+        self.blocking(move |shared| shared.add_employee(employee)).await
+    }
+
+    pub async fn process_payroll(
+        &self,
+        employee: EmployeeData,
+        pay_period: String,
+    ) -> Result<PayrollData, PayrollError> {
+        self.blocking(move |shared| shared.process_payroll(employee, pay_period))
+            .await
+    }
+
+    pub async fn create_run(&self, pay_period: String) -> Result<PayrollRun, PayrollError> {
+        self.blocking(move |shared| shared.create_run(&pay_period)).await
+    }
+
+    pub async fn approve_run(&self, run_id: u32) -> Result<PayrollRun, PayrollError> {
+        self.blocking(move |shared| shared.approve_run(run_id)).await
+    }
+
+    pub async fn deliver_payslips(
+        &self,
+        run_id: u32,
+        sender: Arc<dyn PayslipSender + Send + Sync>,
+        options: PayslipOptions,
+    ) -> Result<Vec<PayslipDelivery>, PayrollError> {
+        self.blocking(move |shared| {
+            shared.write(|payroll| {
+                payroll
+                    .deliver_payslips(run_id, sender.as_ref(), &options)
+                    .map(|deliveries| deliveries.to_vec())
+            })
+        })
+        .await
+    }
+
+    async fn blocking<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(SharedPayroll) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let shared = self.shared.clone();
+        match task::spawn_blocking(move || f(shared)).await {
+            Ok(result) => result,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+impl From<SharedPayroll> for AsyncPayroll {
+    fn from(shared: SharedPayroll) -> Self {
+        Self { shared }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::batch::{split_csv_line, RejectedRow};
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+use std::ops::Deref;
+
+pub const LATE_PENALTY: &str = "Late Arrival Penalty";
+pub const EARLY_LEAVE_PENALTY: &str = "Early Leave Penalty";
+pub const MEAL_ALLOWANCE: &str = "Meal Allowance";
+pub const TRANSPORT_ALLOWANCE: &str = "Transport Allowance";
+/// The deduction line withholding PPh 21 on the taxable attendance allowances.
+pub const ALLOWANCE_TAX: &str = "PPh 21 on Attendance Allowances";
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// One attended day, with how late the employee clocked in and how early they left.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceEntry {
+    pub employee_id: String,
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub late_minutes: u32,
+    #[serde(default)]
+    pub early_leave_minutes: u32,
+}
+
+impl AttendanceEntry {
+    pub fn new(
+        employee_id: &str,
+        date: NaiveDate,
+        late_minutes: u32,
+        early_leave_minutes: u32,
+    ) -> Result<Self, String> {
+        if late_minutes.saturating_add(early_leave_minutes) > MINUTES_PER_DAY {
+            return Err("late and early-leave minutes cannot exceed a day".to_string());
+        }
+        Ok(Self {
+            employee_id: employee_id.trim().to_string(),
+            date,
+            late_minutes,
+            early_leave_minutes,
+        })
+    }
+
+    pub fn period(&self) -> PayPeriod {
+        PayPeriod::from_date(self.date)
+    }
+}
+
+/// Allowances paid per attended day and penalties deducted for lateness and early leave
+/// (`[attendance]` in the configuration). Only events longer than the grace period count; an
+/// amount of 0 turns that allowance or penalty off.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttendancePolicy {
+    pub grace_minutes: u32,
+    /// Deducted per late arrival beyond the grace period.
+    pub late_penalty: f64,
+    /// Deducted per early leave beyond the grace period.
+    pub early_leave_penalty: f64,
+    /// Paid per attended day (uang makan).
+    pub meal_allowance: f64,
+    /// Paid per attended day (uang transport).
+    pub transport_allowance: f64,
+    /// Whether the meal allowance counts as income PPh 21 is withheld on.
+    pub meal_allowance_taxable: bool,
+    pub transport_allowance_taxable: bool,
+}
+
+impl Default for AttendancePolicy {
+    fn default() -> Self {
+        Self {
+            grace_minutes: 0,
+            late_penalty: 0.0,
+            early_leave_penalty: 0.0,
+            meal_allowance: 0.0,
+            transport_allowance: 0.0,
+            meal_allowance_taxable: true,
+            transport_allowance_taxable: true,
+        }
+    }
+}
+
+impl AttendancePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, amount) in [
+            ("attendance.late_penalty", self.late_penalty),
+            ("attendance.early_leave_penalty", self.early_leave_penalty),
+            ("attendance.meal_allowance", self.meal_allowance),
+            ("attendance.transport_allowance", self.transport_allowance),
+        ] {
+            if !amount.is_finite() || amount < 0.0 {
+                return Err(format!("{} must not be negative", name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn summarize<'a>(&self, entries: impl IntoIterator<Item = &'a AttendanceEntry>) -> AttendanceSummary {
+        entries.into_iter().fold(AttendanceSummary::default(), |mut summary, entry| {
+            summary.days += 1;
+            if entry.late_minutes > self.grace_minutes {
+                summary.late += 1;
+            }
+            if entry.early_leave_minutes > self.grace_minutes {
+                summary.early_leaves += 1;
+            }
+            summary
+        })
+    }
+
+    /// The penalty deductions a month's attendance earns.
+    pub fn penalties(&self, summary: &AttendanceSummary) -> Vec<PayrollComponent> {
+        [
+            (LATE_PENALTY, summary.late as f64 * self.late_penalty),
+            (EARLY_LEAVE_PENALTY, summary.early_leaves as f64 * self.early_leave_penalty),
+        ]
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0.0)
+        .map(|(name, amount)| PayrollComponent {
+            name: name.to_string(),
+            amount,
+        })
+        .collect()
+    }
+
+    /// The per-day allowances a month's attendance earns.
+    pub fn allowances(&self, summary: &AttendanceSummary) -> Vec<PayrollComponent> {
+        self.daily_allowances()
+            .into_iter()
+            .map(|(name, rate, _)| PayrollComponent {
+                name: name.to_string(),
+                amount: summary.days as f64 * rate,
+            })
+            .filter(|allowance| allowance.amount > 0.0)
+            .collect()
+    }
+
+    /// The part of [`allowances`](Self::allowances) that is taxable.
+    pub fn taxable_allowances(&self, summary: &AttendanceSummary) -> f64 {
+        self.daily_allowances()
+            .into_iter()
+            .filter(|(_, _, taxable)| *taxable)
+            .map(|(_, rate, _)| summary.days as f64 * rate)
+            .sum()
+    }
+
+    fn daily_allowances(&self) -> [(&'static str, f64, bool); 2] {
+        [
+            (MEAL_ALLOWANCE, self.meal_allowance, self.meal_allowance_taxable),
+            (TRANSPORT_ALLOWANCE, self.transport_allowance, self.transport_allowance_taxable),
+        ]
+    }
+}
+
+/// A month of attendance: days attended and the late arrivals and early leaves beyond the grace period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AttendanceSummary {
+    pub days: u32,
+    pub late: u32,
+    pub early_leaves: u32,
+}
+
+/// Every attended day recorded, at most one per employee and date, with the policy that turns a
+/// month of them into allowances and penalties.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AttendanceLog {
+    entries: Vec<AttendanceEntry>,
+    #[serde(skip)]
+    policy: AttendancePolicy,
+}
+
+impl AttendanceLog {
+    pub fn policy(&self) -> &AttendancePolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: AttendancePolicy) {
+        self.policy = policy;
+    }
+
+    /// Stores a day, replacing an earlier entry for the same employee and date.
+    pub(crate) fn record(&mut self, entry: AttendanceEntry) {
+        self.entries
+            .retain(|existing| existing.employee_id != entry.employee_id || existing.date != entry.date);
+        self.entries.push(entry);
+    }
+
+    /// An employee's attendance in a pay period, by date.
+    pub fn for_employee(&self, employee_id: &str, period: &PayPeriod) -> Vec<&AttendanceEntry> {
+        let mut entries: Vec<&AttendanceEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.employee_id == employee_id && entry.period() == *period)
+            .collect();
+        entries.sort_by_key(|entry| entry.date);
+        entries
+    }
+
+    pub fn summary(&self, employee_id: &str, period: &PayPeriod) -> AttendanceSummary {
+        self.policy.summarize(self.for_employee(employee_id, period))
+    }
+
+    /// Adds the per-day allowances of the record's period to its earnings, with PPh 21 on the
+    /// taxable ones, and the lateness and early-leave penalties to its deductions.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let summary = self.summary(record.employee.as_employee().employee_id(), &period);
+        for allowance in self.policy.allowances(&summary) {
+            record.gross_salary += allowance.amount;
+            record.extra_earnings.push(allowance);
+        }
+        let tax = record.add_taxable_income(self.policy.taxable_allowances(&summary));
+        if tax > 0.0 {
+            record.deductions += tax;
+            record.extra_deductions.push(PayrollComponent {
+                name: ALLOWANCE_TAX.to_string(),
+                amount: tax,
+            });
+        }
+        for penalty in self.policy.penalties(&summary) {
+            record.deductions += penalty.amount;
+            record.extra_deductions.push(penalty);
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+
+    /// Drops the days of periods before `cutoff`.
+    pub(crate) fn purge_before(&mut self, cutoff: PayPeriod) {
+        self.entries.retain(|entry| entry.period() >= cutoff);
+    }
+
+    /// Moves the employee's days to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.entries.iter_mut().map(|entry| renaming.id(&mut entry.employee_id)).sum()
+    }
+}
+
+impl Deref for AttendanceLog {
+    type Target = [AttendanceEntry];
+
+    fn deref(&self) -> &[AttendanceEntry] {
+        &self.entries
+    }
+}
+
+/// An entry read from an attendance CSV, with the line it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttendanceRow {
+    pub line: usize,
+    pub entry: AttendanceEntry,
+}
+
+/// Reads `employee_id,date[,late_minutes][,early_leave_minutes]` rows (header required, columns in
+/// any order, dates as YYYY-MM-DD), as exported by a time clock. Bad rows are returned as
+/// rejected.
+pub fn read_attendance_csv<R: BufRead>(reader: R) -> io::Result<(Vec<AttendanceRow>, Vec<RejectedRow>)> {
+    let mut lines = reader.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, header)) => split_csv_line(header?.trim_start_matches('\u{feff}')),
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let find = |names: &[&str]| {
+        header.iter().position(|name| {
+            let name = name.to_lowercase().replace([' ', '-'], "_");
+            names.contains(&name.as_str())
+        })
+    };
+    let missing = |name: &str| io::Error::new(io::ErrorKind::InvalidData, format!("missing {} column", name));
+    let employee_column = find(&["employee_id", "id"]).ok_or_else(|| missing("employee_id"))?;
+    let date_column = find(&["date"]).ok_or_else(|| missing("date"))?;
+    let late_column = find(&["late_minutes", "late"]);
+    let early_column = find(&["early_leave_minutes", "early_leave", "early"]);
+
+    let mut entries = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(&line);
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| fields.get(column))
+                .filter(|value| !value.is_empty())
+        };
+        let minutes = |name: &str, column: Option<usize>| match field(column) {
+            None => Ok(0),
+            Some(value) => value.parse::<u32>().map_err(|_| format!("{} must be a whole number of minutes", name)),
+        };
+        let parsed = field(Some(employee_column))
+            .ok_or_else(|| "missing employee_id".to_string())
+            .and_then(|employee_id| {
+                let date = field(Some(date_column))
+                    .ok_or("missing date")?
+                    .parse::<NaiveDate>()
+                    .map_err(|_| "date must be YYYY-MM-DD".to_string())?;
+                let late = minutes("late_minutes", late_column)?;
+                let early = minutes("early_leave_minutes", early_column)?;
+                AttendanceEntry::new(employee_id, date, late, early)
+            });
+        match parsed {
+            Ok(entry) => entries.push(AttendanceRow { line: index + 1, entry }),
+            Err(reason) => rejected.push(RejectedRow {
+                line: index + 1,
+                employee_id: fields.get(employee_column).cloned().unwrap_or_default(),
+                reason,
+            }),
+        }
+    }
+    Ok((entries, rejected))
+}
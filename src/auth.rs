@@ -0,0 +1,418 @@
+use crate::error::AuthError;
+use crate::payslip::to_hex;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Tokens are still accepted this many seconds past their `exp` and before their `nbf`, for clocks
+/// that are slightly off.
+const CLOCK_LEEWAY_SECONDS: i64 = 60;
+
+/// Shorter JWT secrets are refused; HS256 wants at least as many bytes as the hash.
+pub const MIN_JWT_SECRET_BYTES: usize = 32;
+
+/// What an API key or token may do. Each scope includes the ones before it, so `payroll-process`
+/// can read as well and `admin` can do everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// Employees, payroll records, reports, runs and metrics.
+    #[serde(alias = "read-only")]
+    Read,
+    /// Processing payroll and creating runs.
+    PayrollProcess,
+    /// Changing employees, approving runs and managing webhooks.
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::PayrollProcess => "payroll-process",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "read" | "read-only" => Ok(Scope::Read),
+            "payroll-process" | "payroll:process" => Ok(Scope::PayrollProcess),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!("unknown scope '{}' (expected read, payroll-process or admin)", other)),
+        }
+    }
+}
+
+/// An API key of a client, stored as its SHA-256 so the configuration does not give the key away.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub name: String,
+    /// Lowercase hex, e.g. from `printf %s "$KEY" | sha256sum`.
+    pub key_sha256: String,
+    pub scope: Scope,
+}
+
+/// Bearer tokens signed with HS256 by an identity provider sharing `secret`. The scope comes from
+/// the token's `scope` (space-separated) or `scopes` claim, and the highest one listed applies.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub secret: String,
+    /// When set, tokens must carry this `iss`.
+    pub issuer: Option<String>,
+    /// When set, tokens must carry this `aud`, or list it.
+    pub audience: Option<String>,
+}
+
+/// Who the server accepts API requests from. With no keys and no JWT secret, every request is
+/// let through, as before authentication existed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiAuthConfig {
+    pub keys: Vec<ApiKey>,
+    pub jwt: Option<JwtConfig>,
+}
+
+/// The client a request was authenticated as. Only authentication hands one out, so a scope it
+/// carries was granted by the API configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    name: String,
+    scope: Scope,
+}
+
+impl Principal {
+    #[cfg(feature = "server")]
+    pub(crate) fn new(name: String, scope: Scope) -> Self {
+        Self { name, scope }
+    }
+
+    /// The principal of every request while the server runs without authentication.
+    #[cfg(feature = "server")]
+    pub(crate) fn anonymous() -> Self {
+        Self::new("anonymous".to_string(), Scope::Admin)
+    }
+
+    /// The key's name, or the token's `sub`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    pub fn require(&self, scope: Scope) -> Result<(), AuthError> {
+        match self.scope >= scope {
+            true => Ok(()),
+            false => Err(AuthError::InsufficientScope(scope)),
+        }
+    }
+}
+
+pub fn key_sha256(key: &str) -> String {
+    to_hex(&Sha256::digest(key.as_bytes()))
+}
+
+/// Whether `a` and `b` are equal, taking as long whichever byte they differ at.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl ApiAuthConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty() || self.jwt.is_some()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, key) in self.keys.iter().enumerate() {
+            if key.name.trim().is_empty() {
+                return Err("api.keys entries need a name".to_string());
+            }
+            if self.keys[..index].iter().any(|other| other.name == key.name) {
+                return Err(format!("API key {} is defined twice", key.name));
+            }
+            if key.key_sha256.len() != 64 || !key.key_sha256.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+                return Err(format!("API key {}: key_sha256 must be 64 lowercase hex digits", key.name));
+            }
+        }
+        if let Some(jwt) = &self.jwt {
+            if jwt.secret.len() < MIN_JWT_SECRET_BYTES {
+                return Err(format!("api.jwt.secret must be at least {} bytes", MIN_JWT_SECRET_BYTES));
+            }
+        }
+        Ok(())
+    }
+
+    /// The client `credential` belongs to: an API key, or a JWT (three dot-separated parts) when a
+    /// JWT secret is configured.
+    pub fn authenticate(&self, credential: &str, now: DateTime<Utc>) -> Result<Principal, AuthError> {
+        let credential = credential.trim();
+        if credential.is_empty() {
+            return Err(AuthError::MissingCredentials);
+        }
+        if let Some(jwt) = &self.jwt {
+            if credential.split('.').count() == 3 {
+                return jwt.verify(credential, now);
+            }
+        }
+        let hash = key_sha256(credential);
+        // Every key is compared, so the time taken does not tell which one came close.
+        self.keys
+            .iter()
+            .fold(None, |found, key| {
+                let matches = constant_time_eq(hash.as_bytes(), key.key_sha256.as_bytes());
+                found.or(matches.then_some(key))
+            })
+            .map(|key| Principal {
+                name: key.name.clone(),
+                scope: key.scope,
+            })
+            .ok_or(AuthError::UnknownApiKey)
+    }
+}
+
+impl JwtConfig {
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Principal, AuthError> {
+        let invalid = |reason: &str| AuthError::InvalidToken(reason.to_string());
+        let mut parts = token.split('.');
+        let (header, payload, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(payload), Some(signature)) => (header, payload, signature),
+            _ => return Err(invalid("not a JWT")),
+        };
+        let decode_json = |part: &str| -> Result<Value, AuthError> {
+            let bytes = base64url_decode(part).ok_or_else(|| invalid("bad encoding"))?;
+            serde_json::from_slice(&bytes).map_err(|_| invalid("bad JSON"))
+        };
+        if decode_json(header)?.get("alg").and_then(Value::as_str) != Some("HS256") {
+            return Err(invalid("only HS256 tokens are accepted"));
+        }
+        let signature = base64url_decode(signature).ok_or_else(|| invalid("bad encoding"))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(&token.as_bytes()[..header.len() + 1 + payload.len()]);
+        mac.verify_slice(&signature).map_err(|_| invalid("bad signature"))?;
+
+        let claims = decode_json(payload)?;
+        let timestamp = |name: &str| claims.get(name).and_then(Value::as_i64);
+        let now = now.timestamp();
+        match timestamp("exp") {
+            Some(exp) if exp + CLOCK_LEEWAY_SECONDS <= now => return Err(AuthError::TokenExpired),
+            Some(_) => {}
+            None => return Err(invalid("no exp claim")),
+        }
+        if timestamp("nbf").is_some_and(|nbf| nbf - CLOCK_LEEWAY_SECONDS > now) {
+            return Err(invalid("not valid yet"));
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err(invalid("wrong issuer"));
+            }
+        }
+        if let Some(audience) = &self.audience {
+            let listed = match claims.get("aud") {
+                Some(Value::String(aud)) => aud == audience,
+                Some(Value::Array(auds)) => auds.iter().any(|aud| aud.as_str() == Some(audience.as_str())),
+                _ => false,
+            };
+            if !listed {
+                return Err(invalid("wrong audience"));
+            }
+        }
+        let scopes: Vec<&str> = match (claims.get("scope"), claims.get("scopes")) {
+            (Some(Value::String(scope)), _) => scope.split_whitespace().collect(),
+            (_, Some(Value::Array(scopes))) => scopes.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let scope = scopes
+            .into_iter()
+            .filter_map(|scope| scope.parse::<Scope>().ok())
+            .max()
+            .ok_or_else(|| invalid("no read, payroll-process or admin scope"))?;
+        let name = claims.get("sub").and_then(Value::as_str).unwrap_or("jwt").to_string();
+        Ok(Principal { name, scope })
+    }
+}
+
+/// Decodes unpadded base64url, as JWTs use.
+fn base64url_decode(input: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'-' => Some(62),
+        b'_' => Some(63),
+        _ => None,
+    };
+    let input = input.trim_end_matches('=').as_bytes();
+    if input.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut buffer = 0u32;
+        for (index, c) in chunk.iter().enumerate() {
+            buffer |= u32::from(value(*c)?) << (18 - 6 * index);
+        }
+        bytes.extend_from_slice(&buffer.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    const SECRET: &str = "a shared secret of thirty-two bytes or more";
+
+    fn base64url_encode(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        let mut encoded = String::new();
+        for chunk in bytes.chunks(3) {
+            let buffer = chunk.iter().enumerate().fold(0u32, |acc, (index, b)| acc | u32::from(*b) << (16 - 8 * index));
+            for index in 0..=chunk.len() {
+                encoded.push(ALPHABET[(buffer >> (18 - 6 * index) & 63) as usize] as char);
+            }
+        }
+        encoded
+    }
+
+    fn token(alg: &str, secret: &str, claims: Value) -> String {
+        let header = base64url_encode(json!({ "alg": alg, "typ": "JWT" }).to_string().as_bytes());
+        let payload = base64url_encode(claims.to_string().as_bytes());
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.{}", header, payload).as_bytes());
+        format!("{}.{}.{}", header, payload, base64url_encode(&mac.finalize().into_bytes()))
+    }
+
+    fn jwt() -> JwtConfig {
+        JwtConfig {
+            secret: SECRET.to_string(),
+            issuer: Some("idp".to_string()),
+            audience: Some("payroll".to_string()),
+        }
+    }
+
+    fn claims(now: DateTime<Utc>) -> Value {
+        json!({
+            "sub": "ci",
+            "iss": "idp",
+            "aud": ["other", "payroll"],
+            "exp": (now + Duration::minutes(5)).timestamp(),
+            "scope": "read payroll-process",
+        })
+    }
+
+    fn config() -> ApiAuthConfig {
+        ApiAuthConfig {
+            keys: vec![ApiKey {
+                name: "reports".to_string(),
+                key_sha256: key_sha256("report-key"),
+                scope: Scope::Read,
+            }],
+            jwt: Some(jwt()),
+        }
+    }
+
+    #[test]
+    fn each_scope_includes_the_ones_before_it() {
+        let principal = Principal {
+            name: "ci".to_string(),
+            scope: Scope::PayrollProcess,
+        };
+        assert!(principal.require(Scope::Read).is_ok());
+        assert!(principal.require(Scope::PayrollProcess).is_ok());
+        assert!(matches!(principal.require(Scope::Admin), Err(AuthError::InsufficientScope(Scope::Admin))));
+        assert_eq!("read-only".parse::<Scope>(), Ok(Scope::Read));
+        assert_eq!("payroll:process".parse::<Scope>(), Ok(Scope::PayrollProcess));
+        assert!("root".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn api_keys_are_matched_by_their_hash() {
+        let config = config();
+        let principal = config.authenticate(" report-key ", Utc::now()).unwrap();
+        assert_eq!((principal.name(), principal.scope()), ("reports", Scope::Read));
+        assert!(matches!(config.authenticate("guess", Utc::now()), Err(AuthError::UnknownApiKey)));
+        assert!(matches!(config.authenticate("  ", Utc::now()), Err(AuthError::MissingCredentials)));
+        assert!(!ApiAuthConfig::default().is_enabled());
+        assert!(config.is_enabled());
+    }
+
+    #[test]
+    fn the_configuration_is_validated() {
+        assert!(config().validate().is_ok());
+        let mut twice = config();
+        twice.keys.push(twice.keys[0].clone());
+        assert!(twice.validate().is_err());
+        let mut unhashed = config();
+        unhashed.keys[0].key_sha256 = "report-key".to_string();
+        assert!(unhashed.validate().is_err());
+        let mut short = config();
+        short.jwt.as_mut().unwrap().secret = "short".to_string();
+        assert!(short.validate().is_err());
+    }
+
+    #[test]
+    fn a_signed_token_grants_its_highest_scope() {
+        let now = Utc::now();
+        let principal = config().authenticate(&token("HS256", SECRET, claims(now)), now).unwrap();
+        assert_eq!((principal.name(), principal.scope()), ("ci", Scope::PayrollProcess));
+
+        let mut listed = claims(now);
+        listed["scope"].take();
+        listed["scopes"] = json!(["admin", "unknown"]);
+        assert_eq!(jwt().verify(&token("HS256", SECRET, listed), now).unwrap().scope(), Scope::Admin);
+    }
+
+    #[test]
+    fn forged_and_foreign_tokens_are_refused() {
+        let now = Utc::now();
+        let invalid = |token: String| matches!(jwt().verify(&token, now), Err(AuthError::InvalidToken(_)));
+        assert!(invalid(token("HS256", "another secret of thirty-two bytes or more", claims(now))));
+        assert!(invalid(token("none", SECRET, claims(now))));
+        assert!(invalid(token("HS256", SECRET, json!({ "exp": claims(now)["exp"], "scope": "admin" }))));
+        let mut unscoped = claims(now);
+        unscoped["scope"] = json!("write");
+        assert!(invalid(token("HS256", SECRET, unscoped)));
+        let mut foreign = claims(now);
+        foreign["aud"] = json!("billing");
+        assert!(invalid(token("HS256", SECRET, foreign)));
+
+        let signed = token("HS256", SECRET, claims(now));
+        let (unsigned, _) = signed.rsplit_once('.').unwrap();
+        assert!(invalid(format!("{}.", unsigned)));
+    }
+
+    #[test]
+    fn tokens_are_valid_between_nbf_and_exp_give_or_take_a_minute() {
+        let now = Utc::now();
+        let mut early = claims(now);
+        early["nbf"] = json!((now + Duration::seconds(CLOCK_LEEWAY_SECONDS - 5)).timestamp());
+        assert!(jwt().verify(&token("HS256", SECRET, early.clone()), now).is_ok());
+        early["nbf"] = json!((now + Duration::minutes(5)).timestamp());
+        assert!(matches!(jwt().verify(&token("HS256", SECRET, early), now), Err(AuthError::InvalidToken(_))));
+
+        let mut expired = claims(now);
+        expired["exp"] = json!((now - Duration::seconds(CLOCK_LEEWAY_SECONDS - 5)).timestamp());
+        assert!(jwt().verify(&token("HS256", SECRET, expired.clone()), now).is_ok());
+        expired["exp"] = json!((now - Duration::minutes(5)).timestamp());
+        assert!(matches!(jwt().verify(&token("HS256", SECRET, expired), now), Err(AuthError::TokenExpired)));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::config::parse_number;
+use crate::error::PayrollError;
+use crate::export::write_csv_row;
+use crate::payroll::{EmployeeData, Payroll, PayrollData};
+use crate::rules::PayrollComponent;
+use std::io::{self, BufRead, Write};
+
+const ADJUSTMENT: &str = "Adjustment";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoursEntry {
+    pub line: usize,
+    pub employee_id: String,
+    pub work_hours: f64,
+    pub allowance: Option<f64>,
+    pub bonus: f64,
+    pub deduction: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedRow {
+    pub line: usize,
+    pub employee_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchOutcome {
+    pub records: Vec<PayrollData>,
+    /// Records already stored for rows with the same hours and adjustments, e.g. when an import is
+    /// run again.
+    pub unchanged: Vec<PayrollData>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+pub(crate) fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|field| field.trim().to_string()).collect()
+}
+
+struct Columns {
+    employee_id: usize,
+    work_hours: usize,
+    allowance: Option<usize>,
+    bonus: Option<usize>,
+    deduction: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> io::Result<Self> {
+        let find = |names: &[&str]| {
+            header.iter().position(|name| {
+                let name = name.to_lowercase().replace([' ', '-'], "_");
+                names.contains(&name.as_str())
+            })
+        };
+        let missing = |name: &str| io::Error::new(io::ErrorKind::InvalidData, format!("missing {} column", name));
+        Ok(Self {
+            employee_id: find(&["employee_id", "id"]).ok_or_else(|| missing("employee_id"))?,
+            work_hours: find(&["work_hours", "hours"]).ok_or_else(|| missing("work_hours"))?,
+            allowance: find(&["allowance", "tunjangan"]),
+            bonus: find(&["bonus", "adjustment"]),
+            deduction: find(&["deduction"]),
+        })
+    }
+
+    fn parse(&self, line: usize, fields: &[String], locale: &str) -> Result<HoursEntry, String> {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| fields.get(column))
+                .filter(|value| !value.is_empty())
+        };
+        let amount = |name: &str, column: Option<usize>| match field(column) {
+            None => Ok(None),
+            Some(value) => match parse_number(value, locale) {
+                Ok(amount) if amount >= 0.0 => Ok(Some(amount)),
+                Ok(_) => Err(format!("{} must not be negative", name)),
+                Err(err) => Err(format!("{}: {}", name, err)),
+            },
+        };
+
+        Ok(HoursEntry {
+            line,
+            employee_id: field(Some(self.employee_id)).cloned().ok_or("missing employee_id")?,
+            work_hours: amount("work_hours", Some(self.work_hours))?.ok_or("missing work_hours")?,
+            allowance: amount("allowance", self.allowance)?,
+            bonus: amount("bonus", self.bonus)?.unwrap_or(0.0),
+            deduction: amount("deduction", self.deduction)?.unwrap_or(0.0),
+        })
+    }
+}
+
+/// Reads `employee_id,work_hours[,allowance][,bonus][,deduction]` rows (header required, columns in
+/// any order). Amounts follow `locale`; rows that cannot be parsed are returned as rejected.
+pub fn read_hours_csv<R: BufRead>(reader: R, locale: &str) -> io::Result<(Vec<HoursEntry>, Vec<RejectedRow>)> {
+    let mut lines = reader.lines().enumerate();
+    let columns = match lines.next() {
+        Some((_, header)) => Columns::from_header(&split_csv_line(header?.trim_start_matches('\u{feff}')))?,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+
+    let mut entries = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(&line);
+        match columns.parse(index + 1, &fields, locale) {
+            Ok(entry) => entries.push(entry),
+            Err(reason) => rejected.push(RejectedRow {
+                line: index + 1,
+                employee_id: fields.get(columns.employee_id).cloned().unwrap_or_default(),
+                reason,
+            }),
+        }
+    }
+    Ok((entries, rejected))
+}
+
+fn with_hours(employee: &EmployeeData, entry: &HoursEntry) -> EmployeeData {
+    let mut employee = employee.clone();
+    match &mut employee {
+        EmployeeData::Fulltime(emp) => {
+            emp.work_hour = entry.work_hours;
+            emp.tunjangan = entry.allowance.unwrap_or(emp.tunjangan);
+        }
+        EmployeeData::Contract(emp) => {
+            emp.work_hour = entry.work_hours;
+            emp.tunjangan = entry.allowance.unwrap_or(emp.tunjangan);
+        }
+    }
+    employee
+}
+
+fn apply_adjustments(record: &mut PayrollData, entry: &HoursEntry) {
+    if entry.bonus > 0.0 {
+        record.extra_earnings.push(PayrollComponent {
+            name: ADJUSTMENT.to_string(),
+            amount: entry.bonus,
+        });
+        record.gross_salary += entry.bonus;
+    }
+    if entry.deduction > 0.0 {
+        record.extra_deductions.push(PayrollComponent {
+            name: ADJUSTMENT.to_string(),
+            amount: entry.deduction,
+        });
+        record.deductions += entry.deduction;
+    }
+    record.net_salary = record.gross_salary - record.deductions;
+}
+
+/// Whether `record` was processed from the same hours, allowance and adjustments as `entry`.
+fn processed_from(record: &PayrollData, entry: &HoursEntry) -> bool {
+    let employee = record.employee.as_employee();
+    let adjustment = |lines: &[PayrollComponent]| -> f64 {
+        lines.iter().filter(|line| line.name == ADJUSTMENT).map(|line| line.amount).sum()
+    };
+    employee.work_hour() == entry.work_hours
+        && entry.allowance.is_none_or(|allowance| employee.tunjangan() == allowance)
+        && adjustment(&record.extra_earnings) == entry.bonus
+        && adjustment(&record.extra_deductions) == entry.deduction
+}
+
+/// Processes registered employees with the hours and adjustments in `entries`. Rows for employees
+/// already paid for `pay_period` with the same input are returned as unchanged, so importing a file
+/// twice is harmless; unknown employees, duplicate rows and employees paid with other input are
+/// rejected. The rest are stored.
+pub fn process_hours(
+    payroll: &mut Payroll,
+    entries: Vec<HoursEntry>,
+    pay_period: &str,
+) -> Result<BatchOutcome, PayrollError> {
+    if payroll.is_period_closed(pay_period) {
+        return Err(PayrollError::PeriodClosed(pay_period.trim().to_string()));
+    }
+
+    let mut accepted: Vec<HoursEntry> = Vec::new();
+    let mut unchanged: Vec<PayrollData> = Vec::new();
+    let mut employees = Vec::new();
+    let mut rejected = Vec::new();
+    for entry in entries {
+        let reason = if let Some(first) = accepted.iter().find(|other| other.employee_id == entry.employee_id) {
+            Some(format!("duplicate row (first on line {})", first.line))
+        } else if let Some(index) = payroll.find_record_index(&entry.employee_id, pay_period) {
+            let record = &payroll.get_payroll_records()[index];
+            if processed_from(record, &entry) {
+                unchanged.push(record.clone());
+                continue;
+            }
+            Some(format!("already processed for {} with other input; void that record first", pay_period))
+        } else {
+            match payroll.get_employee(&entry.employee_id) {
+                Some(employee) => {
+                    employees.push(with_hours(employee, &entry));
+                    None
+                }
+                None => Some("employee not registered".to_string()),
+            }
+        };
+        match reason {
+            Some(reason) => rejected.push(RejectedRow {
+                line: entry.line,
+                employee_id: entry.employee_id,
+                reason,
+            }),
+            None => accepted.push(entry),
+        }
+    }
+
+    let count = payroll.process_batch(employees, pay_period)?.len();
+    let start = payroll.get_payroll_records().len() - count;
+    for (index, entry) in (start..).zip(&accepted) {
+        payroll.update_record(index, |record| apply_adjustments(record, entry))?;
+    }
+    Ok(BatchOutcome {
+        records: payroll.get_payroll_records()[start..].to_vec(),
+        unchanged,
+        rejected,
+    })
+}
+
+pub fn write_rejected_csv<W: Write>(rejected: &[RejectedRow], writer: &mut W) -> io::Result<()> {
+    write_csv_row(writer, &["Line", "Employee ID", "Reason"].map(String::from))?;
+    for row in rejected {
+        write_csv_row(writer, &[row.line.to_string(), row.employee_id.clone(), row.reason.clone()])?;
+    }
+    Ok(())
+}
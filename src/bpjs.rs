@@ -0,0 +1,95 @@
+use crate::payroll::{EmployeeData, PayrollData};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BpjsRates {
+    pub jht_employee: f64,
+    pub jht_employer: f64,
+    pub jp_employee: f64,
+    pub jp_employer: f64,
+    pub jp_wage_cap: f64,
+    pub jkk_employer: f64,
+    pub jkm_employer: f64,
+    pub kesehatan_employer: f64,
+    pub kesehatan_wage_cap: f64,
+}
+
+impl Default for BpjsRates {
+    fn default() -> Self {
+        Self {
+            jht_employee: 0.02,
+            jht_employer: 0.037,
+            jp_employee: 0.01,
+            jp_employer: 0.02,
+            jp_wage_cap: 10_042_300.0,
+            jkk_employer: 0.0024,
+            jkm_employer: 0.003,
+            kesehatan_employer: 0.04,
+            kesehatan_wage_cap: 12_000_000.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BpjsContribution {
+    pub wage_base: f64,
+    pub jht_employee: f64,
+    pub jht_employer: f64,
+    pub jp_employee: f64,
+    pub jp_employer: f64,
+    pub jkk: f64,
+    pub jkm: f64,
+}
+
+impl BpjsContribution {
+    pub fn calculate(wage_base: f64, rates: &BpjsRates) -> Self {
+        let jp_base = wage_base.min(rates.jp_wage_cap);
+        Self {
+            wage_base,
+            jht_employee: wage_base * rates.jht_employee,
+            jht_employer: wage_base * rates.jht_employer,
+            jp_employee: jp_base * rates.jp_employee,
+            jp_employer: jp_base * rates.jp_employer,
+            jkk: wage_base * rates.jkk_employer,
+            jkm: wage_base * rates.jkm_employer,
+        }
+    }
+
+    pub fn for_record(record: &PayrollData, rates: &BpjsRates) -> Option<Self> {
+        match record.employee {
+            EmployeeData::Fulltime(_) if !record.voided => {
+                Some(Self::calculate(record.gross_salary, rates))
+            }
+            _ => None,
+        }
+    }
+
+    /// The employer's BPJS Kesehatan share for a fulltime record, on gross up to the wage cap.
+    pub fn kesehatan_employer(record: &PayrollData, rates: &BpjsRates) -> f64 {
+        match record.employee {
+            EmployeeData::Fulltime(_) if !record.voided => {
+                record.gross_salary.min(rates.kesehatan_wage_cap) * rates.kesehatan_employer
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Gross pay plus every employer contribution on top of it, insurance premiums included.
+    pub fn employer_cost(record: &PayrollData, rates: &BpjsRates) -> f64 {
+        let programs = Self::for_record(record, rates).map_or(0.0, |contribution| contribution.employer_total());
+        let insurance: f64 = record.insurance.iter().map(|premium| premium.employer_share).sum();
+        record.gross_salary + programs + Self::kesehatan_employer(record, rates) + insurance
+    }
+
+    pub fn employee_total(&self) -> f64 {
+        self.jht_employee + self.jp_employee
+    }
+
+    pub fn employer_total(&self) -> f64 {
+        self.jht_employer + self.jp_employer + self.jkk + self.jkm
+    }
+
+    pub fn total(&self) -> f64 {
+        self.employee_total() + self.employer_total()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::batch::split_csv_line;
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::config::parse_number;
+use crate::export::write_csv_row;
+use crate::payroll::Payroll;
+use crate::period::PayPeriod;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+pub const UNASSIGNED: &str = "Unassigned";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetLine {
+    pub department: String,
+    pub period: PayPeriod,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    pub lines: Vec<BudgetLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetVariance {
+    pub department: String,
+    pub period: PayPeriod,
+    pub budget: f64,
+    pub actual: f64,
+}
+
+impl BudgetVariance {
+    /// Actual minus budget; positive when the department spent more than planned.
+    pub fn variance(&self) -> f64 {
+        self.actual - self.budget
+    }
+
+    pub fn variance_percent(&self) -> Option<f64> {
+        (self.budget != 0.0).then(|| self.variance() / self.budget * 100.0)
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.actual > self.budget
+    }
+}
+
+fn invalid(line: usize, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, message))
+}
+
+/// Reads `department,period,amount` rows (header required, columns in any order). Amounts follow
+/// `locale`; rows for the same department and month are added together.
+pub fn read_budget_csv<R: BufRead>(reader: R, locale: &str) -> io::Result<Budget> {
+    let mut lines = reader.lines().enumerate();
+    let Some((_, header)) = lines.next() else {
+        return Ok(Budget::default());
+    };
+    let header = split_csv_line(header?.trim_start_matches('\u{feff}'));
+    let find = |names: &[&str], missing: &str| {
+        header
+            .iter()
+            .position(|name| names.contains(&name.to_lowercase().replace([' ', '-'], "_").as_str()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("missing {} column", missing)))
+    };
+    let department = find(&["department", "dept"], "department")?;
+    let period = find(&["period", "pay_period", "month"], "period")?;
+    let amount = find(&["amount", "budget"], "amount")?;
+
+    let mut budget = Budget::default();
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(&line);
+        let field = |column: usize| fields.get(column).map(String::as_str).unwrap_or("");
+        let entry = BudgetLine {
+            department: match field(department) {
+                "" => return Err(invalid(index + 1, "missing department")),
+                name => name.to_string(),
+            },
+            period: field(period).parse().map_err(|err| invalid(index + 1, err))?,
+            amount: parse_number(field(amount), locale).map_err(|err| invalid(index + 1, err))?,
+        };
+        match budget
+            .lines
+            .iter_mut()
+            .find(|line| line.period == entry.period && line.department.eq_ignore_ascii_case(&entry.department))
+        {
+            Some(existing) => existing.amount += entry.amount,
+            None => budget.lines.push(entry),
+        }
+    }
+    Ok(budget)
+}
+
+impl Budget {
+    /// Compares the budget with the employer cost (gross plus employer BPJS contributions) of the
+    /// non-voided records in the budgeted months, per department. Records use the department stored
+    /// on them, then the employee's current one; departments without a budget line are included
+    /// with a budget of zero.
+    pub fn compare(&self, payroll: &Payroll, rates: &BpjsRates) -> Vec<BudgetVariance> {
+        let mut rows: BTreeMap<(PayPeriod, String), BudgetVariance> = BTreeMap::new();
+        for line in &self.lines {
+            rows.insert(
+                (line.period, line.department.to_lowercase()),
+                BudgetVariance {
+                    department: line.department.clone(),
+                    period: line.period,
+                    budget: line.amount,
+                    actual: 0.0,
+                },
+            );
+        }
+
+        for record in payroll.get_payroll_records().iter().filter(|record| !record.voided) {
+            let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+                continue;
+            };
+            if !self.lines.iter().any(|line| line.period == period) {
+                continue;
+            }
+            let employee = record.employee.as_employee();
+            let department = employee
+                .profile()
+                .department
+                .clone()
+                .or_else(|| {
+                    let current = payroll.get_employee(employee.employee_id())?;
+                    current.as_employee().profile().department.clone()
+                })
+                .unwrap_or_else(|| UNASSIGNED.to_string());
+            rows.entry((period, department.to_lowercase()))
+                .or_insert_with(|| BudgetVariance {
+                    department,
+                    period,
+                    budget: 0.0,
+                    actual: 0.0,
+                })
+                .actual += BpjsContribution::employer_cost(record, rates);
+        }
+        rows.into_values().collect()
+    }
+}
+
+pub fn write_variance_csv<W: Write>(rows: &[BudgetVariance], mut writer: W) -> io::Result<()> {
+    write_csv_row(
+        &mut writer,
+        &["Department", "Period", "Budget", "Actual", "Variance", "Variance %", "Status"].map(String::from),
+    )?;
+    for row in rows {
+        write_csv_row(
+            &mut writer,
+            &[
+                row.department.clone(),
+                format!("{}-{:02}", row.period.year, row.period.month),
+                format!("{:.2}", row.budget),
+                format!("{:.2}", row.actual),
+                format!("{:.2}", row.variance()),
+                row.variance_percent().map(|percent| format!("{:.1}", percent)).unwrap_or_default(),
+                if row.over_budget() { "OVER" } else { "OK" }.to_string(),
+            ],
+        )?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,129 @@
+use crate::period::PayPeriod;
+#[cfg(feature = "redis")]
+use crate::records::RecordKey;
+#[cfg(feature = "redis")]
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+#[cfg(feature = "redis")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "redis")]
+use serde::Serialize;
+#[cfg(feature = "redis")]
+use std::collections::BTreeSet;
+#[cfg(feature = "redis")]
+use std::time::Duration;
+
+/// A query result the server caches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheKey {
+    YearToDate { employee_id: String, year: i32 },
+    PeriodReport(PayPeriod),
+}
+
+impl CacheKey {
+    /// The entries that change when a record of `employee_id` for `pay_period` is added, changed or
+    /// removed.
+    pub fn affected_by(employee_id: &str, pay_period: PayPeriod) -> [CacheKey; 2] {
+        [
+            CacheKey::YearToDate {
+                employee_id: employee_id.to_string(),
+                year: pay_period.year,
+            },
+            CacheKey::PeriodReport(pay_period),
+        ]
+    }
+
+    /// The Redis hash holding the entry, one per employee or period.
+    #[cfg(feature = "redis")]
+    fn hash(&self) -> String {
+        match self {
+            CacheKey::YearToDate { employee_id, .. } => format!("{}:ytd:{}", KEY_PREFIX, employee_id),
+            CacheKey::PeriodReport(period) => {
+                format!("{}:report:{}-{:02}", KEY_PREFIX, period.year, period.month)
+            }
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    fn field(&self, revision: u64) -> String {
+        match self {
+            CacheKey::YearToDate { year, .. } => format!("{}@{}", year, revision),
+            CacheKey::PeriodReport(_) => revision.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+const KEY_PREFIX: &str = "employee-management";
+
+/// Entries expire this long after their hash was last written.
+#[cfg(feature = "redis")]
+const TTL_SECONDS: i64 = 3600;
+
+/// A slow or unreachable cache gives up after this, and the result is computed instead.
+#[cfg(feature = "redis")]
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Caches year-to-date totals and period reports in Redis, where several servers can share them.
+/// Entries are stored under the payroll revision they were computed at, so a server that has not
+/// loaded the latest save neither serves nor overwrites the newer answers. The entries of the
+/// records a change touched are also dropped (see [`records_changed`](Self::records_changed)),
+/// which covers servers running without storage, whose revision never moves; the payroll notes
+/// those records the same way its own read models learn of changes.
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisCache {
+    connection: ConnectionManager,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCache {
+    /// Connects to Redis at `url`, e.g. "redis://127.0.0.1/".
+    pub async fn connect(url: &str) -> redis::RedisResult<Self> {
+        let config = ConnectionManagerConfig::new()
+            .set_connection_timeout(TIMEOUT)
+            .set_response_timeout(TIMEOUT)
+            .set_number_of_retries(1);
+        let connection = redis::Client::open(url)?
+            .get_connection_manager_with_config(config)
+            .await?;
+        Ok(Self { connection })
+    }
+
+    /// The entry computed at `revision`, if any. An entry that no longer parses counts as missing.
+    pub async fn get<T: DeserializeOwned>(&self, key: &CacheKey, revision: u64) -> redis::RedisResult<Option<T>> {
+        let value: Option<String> = redis::cmd("HGET")
+            .arg(key.hash())
+            .arg(key.field(revision))
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(value.and_then(|value| serde_json::from_str(&value).ok()))
+    }
+
+    pub async fn put<T: Serialize>(&self, key: &CacheKey, revision: u64, value: &T) -> redis::RedisResult<()> {
+        let value = serde_json::to_string(value).map_err(|err| {
+            redis::RedisError::from((redis::ErrorKind::TypeError, "unserializable value", err.to_string()))
+        })?;
+        let hash = key.hash();
+        redis::pipe()
+            .atomic()
+            .hset(&hash, key.field(revision), value)
+            .ignore()
+            .expire(&hash, TTL_SECONDS)
+            .ignore()
+            .query_async(&mut self.connection.clone())
+            .await
+    }
+
+    /// Drops every cached result the records of `changed`, by employee and period, count towards.
+    pub async fn records_changed(&self, changed: &BTreeSet<RecordKey>) -> redis::RedisResult<()> {
+        let hashes: BTreeSet<String> = changed
+            .iter()
+            .flat_map(|(employee_id, period)| CacheKey::affected_by(employee_id, *period))
+            .map(|key| key.hash())
+            .collect();
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        redis::cmd("DEL").arg(hashes.into_iter().collect::<Vec<_>>()).query_async(&mut self.connection.clone()).await
+    }
+}
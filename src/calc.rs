@@ -0,0 +1,428 @@
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub const STANDARD_MONTHLY_HOURS: f64 = 173.0;
+pub const OVERTIME_MULTIPLIER: f64 = 1.5;
+pub const BPJS_KESEHATAN_RATE: f64 = 0.01;
+pub const BPJS_KETENAGAKERJAAN_RATE: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TaxBracket {
+    pub up_to: Option<f64>,
+    pub rate: f64,
+}
+
+pub const FULLTIME_TAX_TABLE_ID: &str = "pph21-fulltime-v1";
+pub const FULLTIME_TAX_BRACKETS: [TaxBracket; 4] = [
+    TaxBracket { up_to: Some(54_000_000.0), rate: 0.05 },
+    TaxBracket { up_to: Some(250_000_000.0), rate: 0.15 },
+    TaxBracket { up_to: Some(500_000_000.0), rate: 0.25 },
+    TaxBracket { up_to: None, rate: 0.30 },
+];
+
+pub const CONTRACT_TAX_TABLE_ID: &str = "pph21-contract-v1";
+pub const CONTRACT_TAX_RATE: f64 = 0.025;
+
+pub const FULLTIME_TAX_TABLES: [(&str, &[TaxBracket]); 1] = [(FULLTIME_TAX_TABLE_ID, &FULLTIME_TAX_BRACKETS)];
+
+pub fn fulltime_tax_table(table_id: &str) -> Option<TaxTable> {
+    FULLTIME_TAX_TABLES
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(table_id.trim()))
+        .and_then(|(id, brackets)| TaxTable::new(id, brackets))
+}
+
+/// Longest tax table id, in bytes.
+pub const MAX_TAX_TABLE_ID_LEN: usize = 32;
+/// Most brackets a tax table can have.
+pub const MAX_TAX_BRACKETS: usize = 8;
+
+/// A tax table's id and brackets, held by value so settings stay `Copy` without an allocator or a
+/// registry of tables to point into.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TaxTable {
+    id: [u8; MAX_TAX_TABLE_ID_LEN],
+    id_len: usize,
+    brackets: [TaxBracket; MAX_TAX_BRACKETS],
+    len: usize,
+}
+
+impl TaxTable {
+    pub const FULLTIME: Self = match Self::new(FULLTIME_TAX_TABLE_ID, &FULLTIME_TAX_BRACKETS) {
+        Some(table) => table,
+        None => panic!("the built-in tax table does not fit"),
+    };
+
+    /// `None` when the id is longer than [`MAX_TAX_TABLE_ID_LEN`] or there are more than
+    /// [`MAX_TAX_BRACKETS`] brackets.
+    pub const fn new(id: &str, brackets: &[TaxBracket]) -> Option<Self> {
+        let bytes = id.as_bytes();
+        if bytes.len() > MAX_TAX_TABLE_ID_LEN || brackets.len() > MAX_TAX_BRACKETS {
+            return None;
+        }
+        let mut table = Self {
+            id: [0; MAX_TAX_TABLE_ID_LEN],
+            id_len: bytes.len(),
+            brackets: [TaxBracket { up_to: None, rate: 0.0 }; MAX_TAX_BRACKETS],
+            len: brackets.len(),
+        };
+        let mut i = 0;
+        while i < bytes.len() {
+            table.id[i] = bytes[i];
+            i += 1;
+        }
+        let mut i = 0;
+        while i < brackets.len() {
+            table.brackets[i] = brackets[i];
+            i += 1;
+        }
+        Some(table)
+    }
+
+    pub fn id(&self) -> &str {
+        core::str::from_utf8(&self.id[..self.id_len]).unwrap_or_default()
+    }
+
+    pub fn brackets(&self) -> &[TaxBracket] {
+        &self.brackets[..self.len]
+    }
+}
+
+impl core::fmt::Debug for TaxTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaxTable")
+            .field("id", &self.id())
+            .field("brackets", &self.brackets())
+            .finish()
+    }
+}
+
+/// How tax amounts are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum TaxRounding {
+    /// Amounts as calculated, to the cent. The default.
+    Exact,
+    /// DJP conventions: annual taxable income rounded down to the thousand rupiah and tax down to
+    /// the whole rupiah.
+    Djp,
+}
+
+impl TaxRounding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxRounding::Exact => "exact",
+            TaxRounding::Djp => "djp",
+        }
+    }
+
+    pub fn taxable(&self, amount: f64) -> f64 {
+        match self {
+            TaxRounding::Exact => amount,
+            TaxRounding::Djp => round_down(amount, 1_000.0),
+        }
+    }
+
+    pub fn tax(&self, amount: f64) -> f64 {
+        match self {
+            TaxRounding::Exact => amount,
+            TaxRounding::Djp => round_down(amount, 1.0),
+        }
+    }
+}
+
+/// `amount` rounded down to a multiple of `unit`; negative amounts are left as they are.
+fn round_down(amount: f64, unit: f64) -> f64 {
+    if amount <= 0.0 {
+        return amount;
+    }
+    ((amount / unit) as u64) as f64 * unit
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalculationSettings {
+    pub standard_monthly_hours: f64,
+    pub overtime_multiplier: f64,
+    pub bpjs_kesehatan_rate: f64,
+    pub bpjs_ketenagakerjaan_rate: f64,
+    pub tax_table: TaxTable,
+    pub tax_rounding: TaxRounding,
+}
+
+impl CalculationSettings {
+    pub const DEFAULT: Self = Self {
+        standard_monthly_hours: STANDARD_MONTHLY_HOURS,
+        overtime_multiplier: OVERTIME_MULTIPLIER,
+        bpjs_kesehatan_rate: BPJS_KESEHATAN_RATE,
+        bpjs_ketenagakerjaan_rate: BPJS_KETENAGAKERJAAN_RATE,
+        tax_table: TaxTable::FULLTIME,
+        tax_rounding: TaxRounding::Exact,
+    };
+}
+
+impl Default for CalculationSettings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum AllowancePeriod {
+    Monthly,
+    Yearly,
+    PerProject,
+}
+
+impl AllowancePeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AllowancePeriod::Monthly => "monthly",
+            AllowancePeriod::Yearly => "yearly",
+            AllowancePeriod::PerProject => "per_project",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum PtkpStatus {
+    TK0,
+    TK1,
+    TK2,
+    TK3,
+    K0,
+    K1,
+    K2,
+    K3,
+}
+
+impl PtkpStatus {
+    pub fn annual_allowance(&self) -> f64 {
+        match self {
+            PtkpStatus::TK0 => 54_000_000.0,
+            PtkpStatus::TK1 | PtkpStatus::K0 => 58_500_000.0,
+            PtkpStatus::TK2 | PtkpStatus::K1 => 63_000_000.0,
+            PtkpStatus::TK3 | PtkpStatus::K2 => 67_500_000.0,
+            PtkpStatus::K3 => 72_000_000.0,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            PtkpStatus::TK0 => "TK/0",
+            PtkpStatus::TK1 => "TK/1",
+            PtkpStatus::TK2 => "TK/2",
+            PtkpStatus::TK3 => "TK/3",
+            PtkpStatus::K0 => "K/0",
+            PtkpStatus::K1 => "K/1",
+            PtkpStatus::K2 => "K/2",
+            PtkpStatus::K3 => "K/3",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct DeductionBreakdown {
+    pub tax: f64,
+    pub bpjs_kesehatan: f64,
+    pub bpjs_ketenagakerjaan: f64,
+}
+
+impl DeductionBreakdown {
+    pub fn total(&self) -> f64 {
+        self.tax + self.bpjs_kesehatan + self.bpjs_ketenagakerjaan
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct EarningBreakdown {
+    pub base: f64,
+    pub overtime: f64,
+    pub allowance: f64,
+}
+
+impl EarningBreakdown {
+    pub fn total(&self) -> f64 {
+        self.base + self.overtime + self.allowance
+    }
+}
+
+pub fn fulltime_gross(
+    base_salary: f64,
+    work_hours: f64,
+    allowance: f64,
+    period: AllowancePeriod,
+    settings: &CalculationSettings,
+) -> f64 {
+    fulltime_earnings(base_salary, work_hours, allowance, period, settings).total()
+}
+
+pub fn fulltime_earnings(
+    base_salary: f64,
+    work_hours: f64,
+    allowance: f64,
+    period: AllowancePeriod,
+    settings: &CalculationSettings,
+) -> EarningBreakdown {
+    let standard_hours = settings.standard_monthly_hours;
+    let overtime_rate = base_salary / standard_hours;
+    let overtime_hours = if work_hours > standard_hours {
+        work_hours - standard_hours
+    } else {
+        0.0
+    };
+    let overtime_pay = overtime_hours * overtime_rate * settings.overtime_multiplier;
+
+    let monthly_allowance = match period {
+        AllowancePeriod::Monthly => allowance,
+        AllowancePeriod::Yearly => allowance / 12.0,
+        AllowancePeriod::PerProject => 0.0,
+    };
+
+    EarningBreakdown {
+        base: base_salary,
+        overtime: overtime_pay,
+        allowance: monthly_allowance,
+    }
+}
+
+pub fn contract_gross(hourly_rate: f64, work_hours: f64, allowance: f64, period: AllowancePeriod) -> f64 {
+    contract_earnings(hourly_rate, work_hours, allowance, period).total()
+}
+
+pub fn contract_earnings(hourly_rate: f64, work_hours: f64, allowance: f64, period: AllowancePeriod) -> EarningBreakdown {
+    let monthly_allowance = match period {
+        AllowancePeriod::Monthly => allowance,
+        AllowancePeriod::Yearly => allowance / 12.0,
+        AllowancePeriod::PerProject => allowance,
+    };
+
+    EarningBreakdown {
+        base: work_hours * hourly_rate,
+        overtime: 0.0,
+        allowance: monthly_allowance,
+    }
+}
+
+pub fn bracket_tax(taxable: f64, brackets: &[TaxBracket]) -> f64 {
+    brackets
+        .iter()
+        .find(|bracket| bracket.up_to.is_none_or(|limit| taxable <= limit))
+        .map(|bracket| taxable * bracket.rate)
+        .unwrap_or(0.0)
+}
+
+pub fn annual_taxable(monthly_gross: f64, ptkp_status: Option<PtkpStatus>) -> f64 {
+    let annual_gross = monthly_gross * 12.0;
+    match ptkp_status {
+        Some(status) => (annual_gross - status.annual_allowance()).max(0.0),
+        None => annual_gross,
+    }
+}
+
+pub fn fulltime_tax(monthly_gross: f64, ptkp_status: Option<PtkpStatus>, settings: &CalculationSettings) -> f64 {
+    let taxable = settings.tax_rounding.taxable(annual_taxable(monthly_gross, ptkp_status));
+    settings.tax_rounding.tax(bracket_tax(taxable, settings.tax_table.brackets()) / 12.0)
+}
+
+pub fn contract_tax(gross: f64) -> f64 {
+    gross * CONTRACT_TAX_RATE
+}
+
+pub fn fulltime_deductions(
+    gross: f64,
+    ptkp_status: Option<PtkpStatus>,
+    settings: &CalculationSettings,
+) -> DeductionBreakdown {
+    DeductionBreakdown {
+        tax: fulltime_tax(gross, ptkp_status, settings),
+        bpjs_kesehatan: gross * settings.bpjs_kesehatan_rate,
+        bpjs_ketenagakerjaan: gross * settings.bpjs_ketenagakerjaan_rate,
+    }
+}
+
+pub fn contract_deductions(gross: f64) -> DeductionBreakdown {
+    DeductionBreakdown {
+        tax: contract_tax(gross),
+        ..DeductionBreakdown::default()
+    }
+}
+
+pub fn net(gross: f64, deductions: &DeductionBreakdown) -> f64 {
+    gross - deductions.total()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bracket_tax_charges_the_rate_of_the_bracket_the_income_falls_in() {
+        assert_eq!(bracket_tax(0.0, &FULLTIME_TAX_BRACKETS), 0.0);
+        assert_eq!(bracket_tax(54_000_000.0, &FULLTIME_TAX_BRACKETS), 2_700_000.0);
+        assert_eq!(bracket_tax(54_000_100.0, &FULLTIME_TAX_BRACKETS), 8_100_015.0);
+        assert_eq!(bracket_tax(250_000_000.0, &FULLTIME_TAX_BRACKETS), 37_500_000.0);
+        assert_eq!(bracket_tax(500_000_000.0, &FULLTIME_TAX_BRACKETS), 125_000_000.0);
+        assert_eq!(bracket_tax(600_000_000.0, &FULLTIME_TAX_BRACKETS), 180_000_000.0);
+    }
+
+    #[test]
+    fn annual_taxable_takes_off_the_ptkp_allowance_but_never_goes_negative() {
+        assert_eq!(annual_taxable(10_000_000.0, None), 120_000_000.0);
+        assert_eq!(annual_taxable(10_000_000.0, Some(PtkpStatus::TK0)), 66_000_000.0);
+        assert_eq!(annual_taxable(10_000_000.0, Some(PtkpStatus::K3)), 48_000_000.0);
+        assert_eq!(annual_taxable(4_000_000.0, Some(PtkpStatus::K1)), 0.0);
+    }
+
+    #[test]
+    fn exact_rounding_is_the_default_and_keeps_fractions() {
+        assert_eq!(CalculationSettings::default().tax_rounding, TaxRounding::Exact);
+        let tax = fulltime_tax(10_000_050.0, Some(PtkpStatus::TK0), &CalculationSettings::DEFAULT);
+        assert_eq!(tax, 825_007.5);
+    }
+
+    #[test]
+    fn djp_rounding_rounds_taxable_income_to_the_thousand_and_tax_to_the_rupiah() {
+        let djp = TaxRounding::Djp;
+        assert_eq!(djp.taxable(66_000_999.0), 66_000_000.0);
+        assert_eq!(djp.taxable(999.0), 0.0);
+        assert_eq!(djp.tax(825_012.5), 825_012.0);
+        assert_eq!(djp.tax(-10.5), -10.5);
+
+        let settings = CalculationSettings {
+            tax_rounding: TaxRounding::Djp,
+            ..CalculationSettings::DEFAULT
+        };
+        assert_eq!(fulltime_tax(10_000_050.0, Some(PtkpStatus::TK0), &settings), 825_000.0);
+        // 66,001,000 taxable: 9,900,150 a year is 825,012.50 a month.
+        assert_eq!(fulltime_tax(10_000_090.0, Some(PtkpStatus::TK0), &settings), 825_012.0);
+    }
+
+    #[test]
+    fn contract_tax_is_a_flat_rate_and_net_is_gross_less_deductions() {
+        let deductions = contract_deductions(4_000_000.0);
+        assert_eq!(deductions.tax, 100_000.0);
+        assert_eq!(net(4_000_000.0, &deductions), 3_900_000.0);
+    }
+
+    #[test]
+    fn tax_tables_are_found_by_id_ignoring_case() {
+        let table = fulltime_tax_table(" PPH21-FULLTIME-V1 ").expect("the built-in table");
+        assert_eq!(table.id(), FULLTIME_TAX_TABLE_ID);
+        assert_eq!(table.brackets(), &FULLTIME_TAX_BRACKETS);
+        assert!(fulltime_tax_table("pph21-fulltime-v2").is_none());
+        assert!(TaxTable::new(&"x".repeat(MAX_TAX_TABLE_ID_LEN + 1), &FULLTIME_TAX_BRACKETS).is_none());
+    }
+}
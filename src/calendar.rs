@@ -0,0 +1,178 @@
+use crate::period::PayPeriod;
+use crate::remittance::{
+    due_date, BPJS_KESEHATAN, BPJS_KESEHATAN_DUE_DAY, BPJS_KETENAGAKERJAAN, BPJS_KETENAGAKERJAAN_DUE_DAY, DJP,
+    TAX_DUE_DAY,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use std::io::{self, Write};
+
+/// Pay day used when none is configured.
+pub const DEFAULT_PAY_DAY: u32 = 25;
+
+/// Content lines longer than this many bytes are folded, as RFC 5545 requires.
+const MAX_LINE_BYTES: usize = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlineKind {
+    PayDate,
+    TaxRemittance,
+    BpjsKesehatan,
+    BpjsKetenagakerjaan,
+}
+
+impl DeadlineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadlineKind::PayDate => "pay-date",
+            DeadlineKind::TaxRemittance => "pph21",
+            DeadlineKind::BpjsKesehatan => "bpjs-kesehatan",
+            DeadlineKind::BpjsKetenagakerjaan => "bpjs-ketenagakerjaan",
+        }
+    }
+}
+
+/// An all-day event of the payroll calendar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub kind: DeadlineKind,
+    pub pay_period: PayPeriod,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+}
+
+impl CalendarEvent {
+    /// Stays the same for a period's event across exports, so a subscribed calendar updates the
+    /// event instead of adding it again when the pay day changes.
+    pub fn uid(&self) -> String {
+        format!(
+            "{}-{}-{:02}@employee-management",
+            self.kind.as_str(),
+            self.pay_period.year,
+            self.pay_period.month
+        )
+    }
+}
+
+/// `pay_day` of `period` (the last day in shorter months), moved back to the Friday before when it
+/// falls on a weekend.
+pub fn pay_date(period: PayPeriod, pay_day: u32) -> NaiveDate {
+    let last_day = period.next().first_day().pred_opt().unwrap_or_else(|| period.first_day());
+    let date = NaiveDate::from_ymd_opt(period.year, period.month, pay_day).unwrap_or(last_day);
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date - Duration::days(2),
+        _ => date,
+    }
+}
+
+/// The periods a calendar covers when no range is given: the month before `today`'s and the eleven
+/// after it.
+pub fn default_range(today: NaiveDate) -> (PayPeriod, PayPeriod) {
+    let current = PayPeriod::from_date(today);
+    let to = (0..11).fold(current, |period, _| period.next());
+    (current.previous(), to)
+}
+
+/// The pay date of every period from `from` to `to`, and when its PPh 21 and BPJS contributions are
+/// due, on the same dates as the remittance summary. Sorted by date.
+pub fn payroll_calendar(from: PayPeriod, to: PayPeriod, pay_day: u32) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut period = from;
+    while period <= to {
+        let event = |kind, date, summary: String, description: String| CalendarEvent {
+            kind,
+            pay_period: period,
+            date,
+            summary,
+            description,
+        };
+        events.push(event(
+            DeadlineKind::PayDate,
+            pay_date(period, pay_day),
+            format!("Pay date: {}", period),
+            format!("Salaries for {} are paid.", period),
+        ));
+        events.push(event(
+            DeadlineKind::TaxRemittance,
+            due_date(period, TAX_DUE_DAY),
+            format!("PPh 21 due: {}", period),
+            format!("PPh 21 and PPh 26 withheld from {} pay are due to {}.", period, DJP),
+        ));
+        events.push(event(
+            DeadlineKind::BpjsKesehatan,
+            due_date(period, BPJS_KESEHATAN_DUE_DAY),
+            format!("BPJS Kesehatan due: {}", period),
+            format!("{} contributions for {} are due.", BPJS_KESEHATAN, period),
+        ));
+        events.push(event(
+            DeadlineKind::BpjsKetenagakerjaan,
+            due_date(period, BPJS_KETENAGAKERJAAN_DUE_DAY),
+            format!("BPJS Ketenagakerjaan due: {}", period),
+            format!("{} contributions (JHT, JP, JKK, JKM) for {} are due.", BPJS_KETENAGAKERJAAN, period),
+        ));
+        period = period.next();
+    }
+    events.sort_by_key(|event| event.date);
+    events
+}
+
+/// Writes `events` as an iCalendar (.ics) file of all-day events, stamped with `now`.
+pub fn write_ics<W: Write>(events: &[CalendarEvent], now: DateTime<Utc>, writer: &mut W) -> io::Result<()> {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    write_line(writer, "BEGIN:VCALENDAR")?;
+    write_line(writer, "VERSION:2.0")?;
+    write_line(writer, "PRODID:-//employee-management//Payroll Calendar//EN")?;
+    write_line(writer, "CALSCALE:GREGORIAN")?;
+    write_line(writer, "METHOD:PUBLISH")?;
+    write_line(writer, "X-WR-CALNAME:Payroll")?;
+    for event in events {
+        write_line(writer, "BEGIN:VEVENT")?;
+        write_line(writer, &format!("UID:{}", event.uid()))?;
+        write_line(writer, &format!("DTSTAMP:{}", stamp))?;
+        write_line(writer, &format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")))?;
+        let end = event.date + Duration::days(1);
+        write_line(writer, &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")))?;
+        write_line(writer, &format!("SUMMARY:{}", escape(&event.summary)))?;
+        write_line(writer, &format!("DESCRIPTION:{}", escape(&event.description)))?;
+        write_line(writer, &format!("CATEGORIES:{}", event.kind.as_str()))?;
+        write_line(writer, "TRANSP:TRANSPARENT")?;
+        write_line(writer, "END:VEVENT")?;
+    }
+    write_line(writer, "END:VCALENDAR")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a content line ending in CRLF, folded onto continuation lines starting with a space when
+/// it is too long; folds never split a UTF-8 character.
+fn write_line<W: Write>(writer: &mut W, line: &str) -> io::Result<()> {
+    let mut rest = line;
+    let mut limit = MAX_LINE_BYTES;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        writer.write_all(&rest.as_bytes()[..split])?;
+        writer.write_all(b"\r\n ")?;
+        rest = &rest[split..];
+        limit = MAX_LINE_BYTES - 1;
+    }
+    writer.write_all(rest.as_bytes())?;
+    writer.write_all(b"\r\n")
+}
@@ -1,198 +1,781 @@
-use crate::employee::{AllowancePeriod, ContractEmployee, FulltimeEmployee};
-use crate::payroll::{EmployeeData, Payroll, PayrollPresentation};
-use std::io::{self, Write};
+use crate::accounting::{AccountCodes, JournalFormat};
+use crate::attendance::AttendanceEntry;
+use crate::bpjs::BpjsRates;
+use crate::budget::{read_budget_csv, write_variance_csv};
+use crate::commands::storage_at;
+use crate::config::Config;
+use crate::credentials::MAX_FAILED_ATTEMPTS;
+use crate::disbursement::{parse_salary_split, EWallet};
+use crate::equity::{EquityKind, VestingSchedule};
+use crate::error::{PayrollError, StorageError};
+use crate::employee::{
+    AllowancePeriod, ContractEmployee, EmployeeProfile, EmployeeStatus, FulltimeEmployee, PROBATION_REVIEW_DAYS,
+};
+use crate::export::register::RegisterCsvWriter;
+use crate::export::{audit_bundle, ebupot, org_chart, sipp, write_cost_trend_csv};
+use crate::ledger::{LedgerVerification, PayrollLedger};
+use crate::natura::{NaturaKind, NaturaValuation};
+use crate::notify::RunSummary;
+use crate::onboarding::OnboardingTask;
+use crate::payroll::{EmployeeData, Payroll};
+use crate::presentation::PayrollPresentation;
+use crate::review::validate_score;
+use crate::period::PayPeriod;
+use crate::query::{EmployeeKind, EmployeeQuery};
+use crate::simulation::{SalaryScenario, SalarySimulator};
+use crate::report::PayrollTotals;
+use crate::search::EmployeeIdMatch;
+use crate::self_service::SelfService;
+use crate::storage::{JsonFileStorage, Storage};
+use crate::tax::{NonResidency, PtkpStatus};
+use crate::term;
+use crate::timesheet::{validate_hours, Timesheet};
+use anstream::{print, println};
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+/// Returned by prompts when input ends (Ctrl-D or a closed pipe); the current form is abandoned.
+struct Cancelled;
+
+type Prompted<T> = Result<T, Cancelled>;
+
+fn read_input(prompt: &str) -> Prompted<String> {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => {
+            println!();
+            Err(Cancelled)
+        }
+        Ok(_) => Ok(input),
+        Err(err) => {
+            println!("\n{}", term::error(format_args!("Could not read input: {}", err)));
+            Err(Cancelled)
+        }
+    }
+}
+
+/// Reads a PIN without echoing it when typed at a terminal; piped input is read like any other line.
+fn read_pin_input(prompt: &str) -> Prompted<String> {
+    if !io::stdin().is_terminal() {
+        return read_input(prompt);
+    }
+    match rpassword::prompt_password(prompt) {
+        Ok(pin) => Ok(pin),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            println!();
+            Err(Cancelled)
+        }
+        Err(err) => {
+            println!("\n{}", term::error(format_args!("Could not read input: {}", err)));
+            Err(Cancelled)
+        }
+    }
+}
+
+enum UndoAction {
+    VoidRecord { index: usize },
+    ReplaceEmployee { previous: Box<EmployeeData> },
+    RemoveEmployee { previous: Box<EmployeeData> },
+}
+
+fn describe_employee(employee: &EmployeeData) -> String {
+    let employee = employee.as_employee();
+    match employee.profile().name.as_str() {
+        "" => employee.employee_id().to_string(),
+        name => format!("{} ({})", employee.employee_id(), name),
+    }
+}
+
+fn describe_employees(employees: &[&EmployeeData]) -> String {
+    let mut described: Vec<String> = employees.iter().take(8).map(|employee| describe_employee(employee)).collect();
+    if employees.len() > 8 {
+        described.push(format!("... {} more", employees.len() - 8));
+    }
+    described.join(", ")
+}
+
+impl UndoAction {
+    fn describe(&self, payroll: &Payroll) -> String {
+        match self {
+            UndoAction::VoidRecord { index } => match payroll.get_payroll_records().get(*index) {
+                Some(record) => format!(
+                    "void of {} for {}",
+                    record.employee.as_employee().employee_id(),
+                    record.pay_period
+                ),
+                None => format!("void of record #{}", index),
+            },
+            UndoAction::ReplaceEmployee { previous } => {
+                format!("replacement of employee {}", previous.as_employee().employee_id())
+            }
+            UndoAction::RemoveEmployee { previous } => {
+                format!("removal of employee {}", previous.as_employee().employee_id())
+            }
+        }
+    }
+}
 
 pub struct CLI {
     payroll: Payroll,
+    account_codes: AccountCodes,
+    bpjs_rates: BpjsRates,
+    ledger: PayrollLedger,
+    config: Config,
+    undo_stack: Vec<UndoAction>,
+}
+
+impl Default for CLI {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CLI {
     pub fn new() -> Self {
+        let config = Config::load_default().unwrap_or_else(|err| {
+            println!("{}\n", term::warning(format_args!("Warning: {}; using defaults.", err)));
+            Config::default()
+        });
+        let mut payroll = Payroll::new();
+        payroll.configure(&config);
         Self {
-            payroll: Payroll::new(),
+            payroll,
+            account_codes: AccountCodes::default(),
+            bpjs_rates: BpjsRates::default(),
+            ledger: PayrollLedger::beside(Path::new(&config.storage_path)),
+            config,
+            undo_stack: Vec::new(),
         }
     }
 
     pub fn run(&mut self) {
-        println!("=== Employee Management System ===\n");
+        term::status!("{}\n", term::heading("=== Employee Management System ==="));
+
+        if !Config::path().exists() {
+            let setup = self.confirm("No configuration file found. Run the setup wizard now?").and_then(|run| {
+                if run {
+                    self.setup_wizard()
+                } else {
+                    println!();
+                    Ok(())
+                }
+            });
+            if setup.is_err() {
+                println!("{}\n", term::warning("Setup cancelled."));
+            }
+        }
         
         loop {
             self.show_menu();
-            let choice = self.get_input("Enter your choice: ");
+            let Ok(choice) = self.get_input("Enter your choice: ") else {
+                term::status!("Thank you for using Employee Management System!");
+                break;
+            };
             
-            match choice.trim() {
+            let result = match choice.trim() {
                 "1" => self.add_fulltime_employee(),
                 "2" => self.add_contract_employee(),
                 "3" => self.process_payroll(),
-                "4" => self.show_all_payrolls(),
+                "4" => {
+                    self.show_all_payrolls();
+                    Ok(())
+                }
                 "5" => self.show_employee_payroll(),
-                "6" => {
-                    println!("Thank you for using Employee Management System!");
+                "6" => self.simulate_salary(),
+                "7" => self.void_payroll_record(),
+                "8" => self.close_pay_period(),
+                "9" => self.reopen_pay_period(),
+                "10" => self.compare_pay_periods(),
+                "11" => self.export_payroll_xlsx(),
+                "12" => self.create_payroll_run(),
+                "13" => self.approve_payroll_run(),
+                "14" => self.export_journal_entries(),
+                "15" => self.export_ebupot(),
+                "16" => self.export_bpjs_sipp(),
+                "17" => self.email_payslips(),
+                "18" => self.save_data(),
+                "19" => self.load_data(),
+                "20" => self.verify_ledger(),
+                "21" => self.export_audit_bundle(),
+                "22" => self.export_payroll_register(),
+                "23" => self.search(),
+                "24" => self.setup_wizard(),
+                "25" => {
+                    self.undo_last_action();
+                    Ok(())
+                }
+                "26" => self.list_employees(),
+                "27" => self.remove_employee(),
+                "28" => self.show_employee(),
+                "29" => self.show_payroll_register(),
+                "30" => self.show_remittance_summary(),
+                "31" => self.show_cost_trend(),
+                "32" => self.compare_budget(),
+                "33" => self.show_statistics(),
+                "34" => self.manage_pins(),
+                "35" => self.export_bank_transfers(),
+                "36" => self.export_ewallet_payouts(),
+                "37" => self.manage_onboarding(),
+                "38" => self.review_probation(),
+                "39" => self.manage_salary_changes(),
+                "40" => self.manage_reviews(),
+                "41" => self.show_org_chart(),
+                "42" => self.manage_timesheets(),
+                "43" => self.manage_overtime(),
+                "44" => self.manage_attendance(),
+                "45" => self.manage_travel(),
+                "46" => self.manage_expenses(),
+                "47" => self.manage_insurance(),
+                "48" => self.manage_equity(),
+                "49" => self.manage_natura(),
+                "50" => {
+                    term::status!("Thank you for using Employee Management System!");
                     break;
                 }
-                _ => println!("Invalid choice. Please try again.\n"),
+                _ => {
+                    println!("{}\n", term::warning("Invalid choice. Please try again."));
+                    Ok(())
+                }
+            };
+            if result.is_err() {
+                println!("{}\n", term::warning("Cancelled."));
             }
         }
     }
 
     fn show_menu(&self) {
-        println!("=== MAIN MENU ===");
+        if term::is_quiet() {
+            return;
+        }
+        println!("{}", term::heading("=== MAIN MENU ==="));
         println!("1. Add Fulltime Employee");
         println!("2. Add Contract Employee");
         println!("3. Process Payroll");
         println!("4. Show All Payrolls");
         println!("5. Show Employee Payroll");
-        println!("6. Exit");
+        println!("6. Salary Simulation");
+        println!("7. Void Payroll Record");
+        println!("8. Close Pay Period");
+        println!("9. Reopen Pay Period");
+        println!("10. Compare Pay Periods");
+        println!("11. Export Payroll to Excel");
+        println!("12. Create Payroll Run");
+        println!("13. Approve Payroll Run");
+        println!("14. Export Journal Entries");
+        println!("15. Export e-Bupot 21/26");
+        println!("16. Export BPJS SIPP Report");
+        println!("17. Email Payslips");
+        println!("18. Save Data");
+        println!("19. Load Data");
+        println!("20. Verify Payroll Ledger");
+        println!("21. Export Auditor Bundle");
+        println!("22. Export Payroll Register (CSV)");
+        println!("23. Search Employees and Records");
+        println!("24. Setup Wizard");
+        println!("25. Undo Last Action");
+        println!("26. List Employees");
+        println!("27. Remove Employee");
+        println!("28. Show Employee");
+        println!("29. Payroll Run Register");
+        println!("30. Remittance Summary");
+        println!("31. Headcount and Cost Trend");
+        println!("32. Budget vs Actual");
+        println!("33. Payroll Statistics");
+        println!("34. Employee PINs");
+        println!("35. Export Bank Transfer File");
+        println!("36. Export E-Wallet Payouts");
+        println!("37. Onboarding Checklists");
+        println!("38. Probation Reviews");
+        println!("39. Salary Changes");
+        println!("40. Performance Reviews");
+        println!("41. Organization Chart");
+        println!("42. Timesheets");
+        println!("43. Overtime");
+        println!("44. Attendance");
+        println!("45. Travel");
+        println!("46. Expenses");
+        println!("47. Insurance");
+        println!("48. Equity");
+        println!("49. Benefits in Kind");
+        println!("50. Exit");
         println!();
     }
 
-    fn get_input(&self, prompt: &str) -> String {
-        print!("{}", prompt);
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        input
+    fn get_input(&self, prompt: &str) -> Prompted<String> {
+        read_input(prompt)
     }
 
-    fn get_number_input(&self, prompt: &str) -> Result<f64, std::num::ParseFloatError> {
-        let input = self.get_input(prompt);
-        input.trim().parse::<f64>()
+    fn confirm(&self, prompt: &str) -> Prompted<bool> {
+        let answer = self.get_input(&format!("{} (y/N): ", prompt))?;
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
     }
 
-    fn add_fulltime_employee(&mut self) {
-        println!("\n=== Add Fulltime Employee ===");
-        
-        let employee_id = self.get_input("Employee ID: ").trim().to_string();
-        
-        let work_hour = loop {
-            match self.get_number_input("Work Hours: ") {
-                Ok(hours) if hours >= 0.0 => break hours,
-                _ => println!("Please enter a valid positive number for work hours."),
+    fn confirm_replace(&self, employee_id: &str) -> Prompted<bool> {
+        match self.payroll.get_employee(employee_id) {
+            Some(existing) => self.confirm(&format!(
+                "Employee {} ({}) already exists. Replace it?",
+                employee_id,
+                existing.as_employee().profile().name
+            )),
+            None => Ok(true),
+        }
+    }
+
+    fn read_employee_id(&self, prompt: &str, allow_unregistered: bool) -> Prompted<String> {
+        if self.payroll.known_employees().is_empty() {
+            return Ok(self.get_input(prompt)?.trim().to_string());
+        }
+        loop {
+            let input = self.get_input(prompt)?;
+            let input = input.trim();
+            let suggestions = match self.payroll.match_employee_id(input) {
+                EmployeeIdMatch::Exact(employee) => return Ok(employee.as_employee().employee_id().to_string()),
+                EmployeeIdMatch::Completed(employee) => {
+                    println!("  -> {}", describe_employee(employee));
+                    return Ok(employee.as_employee().employee_id().to_string());
+                }
+                EmployeeIdMatch::Ambiguous(matches) => {
+                    println!("Matching employees: {}", describe_employees(&matches));
+                    continue;
+                }
+                EmployeeIdMatch::Unknown(suggestions) => suggestions,
+            };
+            if input.is_empty() {
+                println!("{}", term::warning("Please enter an employee ID."));
+                continue;
             }
-        };
-        
-        let tunjangan = loop {
-            match self.get_number_input("Allowance (Tunjangan): ") {
-                Ok(amount) if amount >= 0.0 => break amount,
-                _ => println!("Please enter a valid positive number for allowance."),
+            println!("{}", term::warning(format_args!("No employee found with ID: {}", input)));
+            if !suggestions.is_empty() {
+                println!("Did you mean: {}", describe_employees(&suggestions));
+            }
+            if allow_unregistered && self.confirm(&format!("Use unregistered employee ID {}?", input))? {
+                return Ok(input.to_string());
+            }
+        }
+    }
+
+    fn register_employee(&mut self, employee: EmployeeData) {
+        let employee_id = employee.as_employee().employee_id().to_string();
+        if let Some(previous) = self.payroll.get_employee(&employee_id) {
+            self.undo_stack.push(UndoAction::ReplaceEmployee {
+                previous: Box::new(previous.clone()),
+            });
+        }
+        self.payroll.add_employee(employee);
+        if let Some(checklist) = self.payroll.onboarding(&employee_id).filter(|checklist| !checklist.is_complete()) {
+            let pending: Vec<String> = checklist.pending_mandatory().map(|task| task.to_string()).collect();
+            println!(
+                "{}",
+                term::warning(format_args!(
+                    "Onboarding open: {}. Payroll runs hold {} until these are done.",
+                    pending.join(", "),
+                    employee_id
+                ))
+            );
+        }
+    }
+
+    fn get_number_input(&self, prompt: &str) -> Prompted<Result<f64, String>> {
+        let input = self.get_input(prompt)?;
+        Ok(self.config.parse_number(&input))
+    }
+
+    fn get_optional_number_input(&self, prompt: &str) -> Prompted<Option<Result<f64, String>>> {
+        let input = self.get_input(prompt)?;
+        let input = input.trim();
+        if input.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(self.config.parse_number(input)))
+        }
+    }
+
+    fn get_optional_input(&self, prompt: &str) -> Prompted<Option<String>> {
+        let input = self.get_input(prompt)?.trim().to_string();
+        if input.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(input))
+        }
+    }
+
+    fn read_number(
+        &self,
+        label: &str,
+        default: Option<f64>,
+        valid: impl Fn(f64) -> bool,
+        warning: &str,
+    ) -> Prompted<f64> {
+        loop {
+            let input = match default {
+                Some(default) => self
+                    .get_optional_number_input(&format!("{} (default: {}): ", label, default))?
+                    .unwrap_or(Ok(default)),
+                None => self.get_number_input(&format!("{}: ", label))?,
+            };
+            match input {
+                Ok(value) if valid(value) => break Ok(value),
+                _ => println!("{}", term::warning(warning)),
             }
+        }
+    }
+
+    fn read_optional_field(&self, label: &str, current: Option<&str>) -> Prompted<Option<String>> {
+        let Some(current) = current else {
+            return self.get_optional_input(&format!("{} (leave blank if unknown): ", label));
         };
-        
+        match self.get_optional_input(&format!("{} (default: {}, '-' to clear): ", label, current))? {
+            None => Ok(Some(current.to_string())),
+            Some(input) if input == "-" => Ok(None),
+            input => Ok(input),
+        }
+    }
+
+    fn read_allowance(&self, default: (f64, AllowancePeriod)) -> Prompted<(f64, AllowancePeriod)> {
+        let (default_amount, default_period) = default;
+        let tunjangan = self.read_number(
+            "Allowance (Tunjangan)",
+            Some(default_amount),
+            |amount| amount >= 0.0,
+            "Please enter a valid positive number for allowance.",
+        )?;
+
         let periode_tunjangan = loop {
-            let period = self.get_input("Allowance Period (monthly/yearly/per_project): ");
-            match AllowancePeriod::from_str(period.trim()) {
-                Some(period) => break period,
-                None => println!("Please enter 'monthly', 'yearly', or 'per_project'."),
+            let prompt = format!(
+                "Allowance Period (monthly/yearly/per_project) (default: {}): ",
+                default_period.as_str()
+            );
+            match self.get_optional_input(&prompt)? {
+                None => break default_period,
+                Some(period) => match period.parse::<AllowancePeriod>() {
+                    Ok(period) => break period,
+                    Err(_) => println!("{}", term::warning("Please enter 'monthly', 'yearly', or 'per_project'.")),
+                },
             }
         };
-        
-        let base_salary = loop {
-            match self.get_number_input("Base Salary: ") {
-                Ok(salary) if salary > 0.0 => break salary,
-                _ => println!("Please enter a valid positive number for base salary."),
+
+        Ok((tunjangan, periode_tunjangan))
+    }
+
+    fn default_allowance(&self, current: Option<&EmployeeData>) -> (f64, AllowancePeriod) {
+        match current {
+            Some(employee) => (employee.as_employee().tunjangan(), *employee.as_employee().periode_tunjangan()),
+            None => (self.config.allowance.amount, self.config.allowance.period),
+        }
+    }
+
+    fn read_profile(&self, employee_id: &str, current: Option<&EmployeeProfile>) -> Prompted<EmployeeProfile> {
+        let blank = EmployeeProfile::default();
+        let current = current.unwrap_or(&blank);
+        let name = match current.name.as_str() {
+            "" => self.get_input("Name: ")?.trim().to_string(),
+            name => self.prompt_with_default("Name", name)?,
+        };
+        let probation_end = self.read_optional_date("Probation End", current.probation_end)?;
+        Ok(EmployeeProfile {
+            name,
+            nik: self.read_optional_field("NIK", current.nik.as_deref())?,
+            npwp: self.read_optional_field("NPWP", current.npwp.as_deref())?,
+            bpjs_tk_number: self.read_optional_field("BPJS Ketenagakerjaan No.", current.bpjs_tk_number.as_deref())?,
+            email: self.read_optional_field("Email", current.email.as_deref())?,
+            department: self.read_optional_field("Department", current.department.as_deref())?,
+            grade: self.read_optional_field("Grade", current.grade.as_deref())?,
+            manager_id: loop {
+                match self.read_optional_field("Manager ID", current.manager_id.as_deref())? {
+                    None => break None,
+                    Some(manager_id) => match self.payroll.check_manager(employee_id, &manager_id) {
+                        Ok(()) => break Some(manager_id),
+                        Err(err) => println!("{}", term::warning(err)),
+                    },
+                }
+            },
+            date_of_birth: self.read_optional_date("Date of Birth", current.date_of_birth)?,
+            salary_split: loop {
+                let current_split = current.salary_split.iter().map(ToString::to_string).collect::<Vec<_>>();
+                let current_split = (!current_split.is_empty()).then(|| current_split.join(" "));
+                let label = "Bank Accounts (BANK:ACCOUNT[=AMOUNT|PERCENT%], space-separated)";
+                match self.read_optional_field(label, current_split.as_deref())? {
+                    None => break Vec::new(),
+                    Some(input) => match parse_salary_split(&input) {
+                        Ok(splits) => break splits,
+                        Err(err) => println!("{}", term::warning(err)),
+                    },
+                }
+            },
+            probation_end,
+            status: match current.name.as_str() {
+                "" if probation_end.is_some() => EmployeeStatus::Probation,
+                "" => EmployeeStatus::Active,
+                _ => loop {
+                    let status = self.prompt_with_default(
+                        "Status (active/probation/archived/terminated)",
+                        current.status.as_str(),
+                    )?;
+                    match status.parse() {
+                        Ok(status) => break status,
+                        Err(err) => println!("{}", term::warning(err)),
+                    }
+                },
+            },
+            non_resident: None,
+            tax_regime: None,
+        })
+    }
+
+    /// Asks which tax regime a full-time employee is charged under, when the configuration defines
+    /// regimes besides the built-in tables; blank keeps them on the company's.
+    fn read_tax_regime(&self, current: Option<&str>) -> Prompted<Option<String>> {
+        if self.config.tax.regimes.is_empty() {
+            return Ok(None);
+        }
+        println!("Available tax regimes: {}", self.config.tax.regime_ids().join(", "));
+        loop {
+            let label = format!("Tax Regime (blank for the company's {})", self.config.tax.table);
+            match self.read_optional_field(&label, current)? {
+                None => break Ok(None),
+                Some(id) => match self.config.tax.regime(&id) {
+                    Some(table) => break Ok(Some(table.id().to_string())),
+                    None => println!("{}", term::warning(format_args!("Unknown tax regime '{}'.", id))),
+                },
+            }
+        }
+    }
+
+    /// Asks where a contractor is resident for tax, keeping `current` as the default. A country
+    /// other than Indonesia gets the PPh 26 rate configured for it.
+    fn read_residency(&self, current: Option<&NonResidency>) -> Prompted<Option<NonResidency>> {
+        loop {
+            let label = "Tax Residence Country (two-letter code, blank for Indonesia)";
+            let country = match self.read_optional_field(label, current.map(|residency| residency.country.as_str()))? {
+                None => return Ok(None),
+                Some(country) => country.trim().to_uppercase(),
+            };
+            if country == "ID" {
+                return Ok(None);
+            }
+            let residency = match current.filter(|residency| residency.country == country) {
+                Some(residency) => residency.clone(),
+                None => NonResidency {
+                    rate: self.config.tax.pph26_rate_for(&country),
+                    country,
+                },
+            };
+            match residency.validate() {
+                Ok(()) => return Ok(Some(residency)),
+                Err(err) => println!("{}", term::warning(err)),
+            }
+        }
+    }
+
+    fn read_optional_date(&self, label: &str, current: Option<NaiveDate>) -> Prompted<Option<NaiveDate>> {
+        let current = current.map(|date| date.format("%Y-%m-%d").to_string());
+        loop {
+            match self.read_optional_field(&format!("{} (YYYY-MM-DD)", label), current.as_deref())? {
+                None => break Ok(None),
+                Some(input) => match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                    Ok(date) => break Ok(Some(date)),
+                    Err(_) => println!("{}", term::warning("Please enter the date as YYYY-MM-DD.")),
+                },
+            }
+        }
+    }
+
+    fn read_ptkp_status(&self, current: Option<PtkpStatus>) -> Prompted<Option<PtkpStatus>> {
+        loop {
+            match self.read_optional_field("PTKP Status (e.g. TK/0, K/1)", current.map(|status| status.code()))? {
+                None => break Ok(None),
+                Some(input) => match input.parse::<PtkpStatus>() {
+                    Ok(status) => break Ok(Some(status)),
+                    Err(_) => println!("{}", term::warning("Please enter a status from TK/0-TK/3 or K/0-K/3.")),
+                },
             }
+        }
+    }
+
+    fn apply_registered_details(&self, employee_data: &mut EmployeeData) {
+        let registered = match self.payroll.get_employee(employee_data.as_employee().employee_id()) {
+            Some(registered) => registered,
+            None => return,
+        };
+        *employee_data.profile_mut() = registered.as_employee().profile().clone();
+        if let (EmployeeData::Fulltime(employee), EmployeeData::Fulltime(registered)) =
+            (employee_data, registered)
+        {
+            employee.ptkp_status = registered.ptkp_status;
+        }
+    }
+
+    fn add_fulltime_employee(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Add Fulltime Employee ==="));
+        
+        let employee_id = self.get_input("Employee ID: ")?.trim().to_string();
+        if !self.confirm_replace(&employee_id)? {
+            println!("Employee not changed.\n");
+            return Ok(());
+        }
+        let current = self.payroll.get_employee(&employee_id);
+        let profile = self.read_profile(&employee_id, current.map(|employee| employee.as_employee().profile()))?;
+        
+        let work_hour = self.read_number(
+            "Work Hours",
+            current.map(|employee| employee.as_employee().work_hour()),
+            |hours| hours >= 0.0,
+            "Please enter a valid positive number for work hours.",
+        )?;
+        
+        let (tunjangan, periode_tunjangan) = self.read_allowance(self.default_allowance(current))?;
+        
+        let (base_salary, ptkp_status) = match current {
+            Some(EmployeeData::Fulltime(employee)) => (Some(employee.base_salary), employee.ptkp_status),
+            _ => (None, None),
         };
+        let base_salary = self.read_number(
+            "Base Salary",
+            base_salary,
+            |salary| salary > 0.0,
+            "Please enter a valid positive number for base salary.",
+        )?;
+        let ptkp_status = self.read_ptkp_status(ptkp_status)?;
+        let tax_regime = current.and_then(|employee| employee.as_employee().profile().tax_regime.as_deref());
+        let tax_regime = self.read_tax_regime(tax_regime)?;
 
-        let employee = FulltimeEmployee::new(
+        let mut employee = FulltimeEmployee::new(
             employee_id,
             work_hour,
             tunjangan,
             periode_tunjangan,
             base_salary,
         );
+        employee.ptkp_status = ptkp_status;
+        employee.profile = EmployeeProfile { tax_regime, ..profile };
+        self.register_employee(EmployeeData::Fulltime(employee));
 
-        println!("Fulltime employee added successfully!\n");
+        term::status!("{}\n", term::success("Fulltime employee added successfully!"));
+        Ok(())
     }
 
-    fn add_contract_employee(&mut self) {
-        println!("\n=== Add Contract Employee ===");
+    fn add_contract_employee(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Add Contract Employee ==="));
         
-        let employee_id = self.get_input("Employee ID: ").trim().to_string();
-        
-        let work_hour = loop {
-            match self.get_number_input("Work Hours: ") {
-                Ok(hours) if hours >= 0.0 => break hours,
-                _ => println!("Please enter a valid positive number for work hours."),
-            }
-        };
+        let employee_id = self.get_input("Employee ID: ")?.trim().to_string();
+        if !self.confirm_replace(&employee_id)? {
+            println!("Employee not changed.\n");
+            return Ok(());
+        }
+        let current = self.payroll.get_employee(&employee_id);
+        let profile = self.read_profile(&employee_id, current.map(|employee| employee.as_employee().profile()))?;
         
-        let tunjangan = loop {
-            match self.get_number_input("Allowance (Tunjangan): ") {
-                Ok(amount) if amount >= 0.0 => break amount,
-                _ => println!("Please enter a valid positive number for allowance."),
-            }
-        };
+        let work_hour = self.read_number(
+            "Work Hours",
+            current.map(|employee| employee.as_employee().work_hour()),
+            |hours| hours >= 0.0,
+            "Please enter a valid positive number for work hours.",
+        )?;
         
-        let periode_tunjangan = loop {
-            let period = self.get_input("Allowance Period (monthly/yearly/per_project): ");
-            match AllowancePeriod::from_str(period.trim()) {
-                Some(period) => break period,
-                None => println!("Please enter 'monthly', 'yearly', or 'per_project'."),
-            }
-        };
+        let (tunjangan, periode_tunjangan) = self.read_allowance(self.default_allowance(current))?;
         
-        let hourly_rate = loop {
-            match self.get_number_input("Hourly Rate: ") {
-                Ok(rate) if rate > 0.0 => break rate,
-                _ => println!("Please enter a valid positive number for hourly rate."),
-            }
+        let hourly_rate = match current {
+            Some(EmployeeData::Contract(employee)) => Some(employee.hourly_rate),
+            _ => None,
         };
+        let hourly_rate = self.read_number(
+            "Hourly Rate",
+            hourly_rate,
+            |rate| rate > 0.0,
+            "Please enter a valid positive number for hourly rate.",
+        )?;
+        let residency = current.and_then(|employee| employee.as_employee().profile().non_resident.as_ref());
+        let non_resident = self.read_residency(residency)?;
 
-        let employee = ContractEmployee::new(
+        let mut employee = ContractEmployee::new(
             employee_id,
             work_hour,
             tunjangan,
             periode_tunjangan,
             hourly_rate,
         );
+        employee.profile = EmployeeProfile { non_resident, ..profile };
+        self.register_employee(EmployeeData::Contract(employee));
 
-        println!("Contract employee added successfully!\n");
+        term::status!("{}\n", term::success("Contract employee added successfully!"));
+        Ok(())
     }
 
-    fn process_payroll(&mut self) {
-        println!("\n=== Process Payroll ===");
-        
+    fn process_payroll(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Process Payroll ==="));
+
+        let employee_id = self.read_employee_id("Employee ID: ", true)?;
+        let previous = self
+            .payroll
+            .get_employee_payroll(&employee_id)
+            .last()
+            .map(|record| &record.employee)
+            .or_else(|| self.payroll.get_employee(&employee_id))
+            .cloned();
+        let previous = previous.as_ref();
+
+        let default_type = previous.map(|employee| match employee {
+            EmployeeData::Fulltime(_) => "fulltime",
+            EmployeeData::Contract(_) => "contract",
+        });
+        let type_prompt = match default_type {
+            Some(default_type) => format!("Employee Type (fulltime/contract) (default: {}): ", default_type),
+            None => "Employee Type (fulltime/contract): ".to_string(),
+        };
         let employee_type = loop {
-            let input = self.get_input("Employee Type (fulltime/contract): ");
-            match input.trim().to_lowercase().as_str() {
+            let input = self.get_input(&type_prompt)?.trim().to_lowercase();
+            let input = if input.is_empty() { default_type.unwrap_or_default() } else { input.as_str() };
+            match input {
                 "fulltime" | "ft" => break "fulltime",
                 "contract" | "ct" => break "contract",
-                _ => println!("Please enter 'fulltime' or 'contract'."),
+                _ => println!("{}", term::warning("Please enter 'fulltime' or 'contract'.")),
             }
         };
 
-        let employee_id = self.get_input("Employee ID: ").trim().to_string();
-        let work_hour = loop {
-            match self.get_number_input("Work Hours: ") {
-                Ok(hours) if hours >= 0.0 => break hours,
-                _ => println!("Please enter a valid positive number for work hours."),
-            }
-        };
-        
-        let tunjangan = loop {
-            match self.get_number_input("Allowance (Tunjangan): ") {
-                Ok(amount) if amount >= 0.0 => break amount,
-                _ => println!("Please enter a valid positive number for allowance."),
-            }
-        };
+        let work_hour = self.read_number(
+            "Work Hours",
+            previous.map(|employee| employee.as_employee().work_hour()),
+            |hours| hours >= 0.0,
+            "Please enter a valid positive number for work hours.",
+        )?;
         
-        let periode_tunjangan = loop {
-            let period = self.get_input("Allowance Period (monthly/yearly/per_project): ");
-            match AllowancePeriod::from_str(period.trim()) {
-                Some(period) => break period,
-                None => println!("Please enter 'monthly', 'yearly', or 'per_project'."),
-            }
-        };
+        let (tunjangan, periode_tunjangan) = self.read_allowance(self.default_allowance(previous))?;
+
+        let pay_period = self.get_input("Pay Period (e.g., 'September 2024'): ")?.trim().to_string();
+        if self.payroll.is_period_closed(&pay_period) {
+            println!(
+                "{}\n",
+                term::warning(format_args!("Pay period '{}' is closed. Ask an admin to reopen it first.", pay_period))
+            );
+            return Ok(());
+        }
 
-        let pay_period = self.get_input("Pay Period (e.g., 'September 2024'): ").trim().to_string();
+        let approved_hours = pay_period
+            .parse::<PayPeriod>()
+            .ok()
+            .and_then(|period| self.payroll.timesheet(&employee_id, &period))
+            .filter(|timesheet| employee_type == "contract" && timesheet.is_approved())
+            .map(|timesheet| timesheet.hours);
+        if let Some(hours) = approved_hours {
+            term::status!("Paying the {} hours of the approved timesheet.", hours);
+        }
 
-        let employee_data = if employee_type == "fulltime" {
-            let base_salary = loop {
-                match self.get_number_input("Base Salary: ") {
-                    Ok(salary) if salary > 0.0 => break salary,
-                    _ => println!("Please enter a valid positive number for base salary."),
-                }
+        let mut employee_data = if employee_type == "fulltime" {
+            let base_salary = match previous {
+                Some(EmployeeData::Fulltime(employee)) => Some(employee.base_salary),
+                _ => None,
             };
+            let base_salary = self.read_number(
+                "Base Salary",
+                base_salary,
+                |salary| salary > 0.0,
+                "Please enter a valid positive number for base salary.",
+            )?;
 
             let employee = FulltimeEmployee::new(
                 employee_id,
@@ -203,12 +786,16 @@ impl CLI {
             );
             EmployeeData::Fulltime(employee)
         } else {
-            let hourly_rate = loop {
-                match self.get_number_input("Hourly Rate: ") {
-                    Ok(rate) if rate > 0.0 => break rate,
-                    _ => println!("Please enter a valid positive number for hourly rate."),
-                }
+            let hourly_rate = match previous {
+                Some(EmployeeData::Contract(employee)) => Some(employee.hourly_rate),
+                _ => None,
             };
+            let hourly_rate = self.read_number(
+                "Hourly Rate",
+                hourly_rate,
+                |rate| rate > 0.0,
+                "Please enter a valid positive number for hourly rate.",
+            )?;
 
             let employee = ContractEmployee::new(
                 employee_id,
@@ -219,46 +806,2181 @@ impl CLI {
             );
             EmployeeData::Contract(employee)
         };
+        self.apply_registered_details(&mut employee_data);
 
-        let payroll_data = self.payroll.process_payroll(employee_data, pay_period);
-        
-        println!("\nPayroll processed successfully!");
-        PayrollPresentation::print_payroll_summary(&payroll_data);
-        println!();
+        match self.payroll.process_payroll(employee_data, pay_period) {
+            Ok(payroll_data) => {
+                term::status!("\n{}", term::success("Payroll processed successfully!"));
+                PayrollPresentation::print_payroll_summary(&payroll_data);
+                println!();
+            }
+            Err(err) => println!("\n{}\n", term::error(format_args!("Payroll not processed: {}", err))),
+        }
+        Ok(())
     }
 
     fn show_all_payrolls(&self) {
-        println!("\n=== All Payroll Records ===");
+        term::status!("\n{}", term::heading("=== All Payroll Records ==="));
         
-        if self.payroll.payroll_records.is_empty() {
+        if self.payroll.get_payroll_records().is_empty() {
             println!("No payroll records found.\n");
             return;
         }
 
-        PayrollPresentation::print_all_payrolls(&self.payroll.payroll_records);
+        PayrollPresentation::print_all_payrolls(self.payroll.get_payroll_records());
         println!();
     }
 
-    fn show_employee_payroll(&self) {
-        println!("\n=== Employee Payroll History ===");
+    fn show_employee_payroll(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Employee Payroll History ==="));
         
-        if self.payroll.payroll_records.is_empty() {
+        if self.payroll.get_payroll_records().is_empty() {
             println!("No payroll records found.\n");
-            return;
+            return Ok(());
         }
 
-        let employee_id = self.get_input("Enter Employee ID: ").trim().to_string();
+        let employee_id = self.read_employee_id("Enter Employee ID: ", false)?;
         let records = self.payroll.get_employee_payroll(&employee_id);
         
         if records.is_empty() {
             println!("No payroll records found for employee ID: {}\n", employee_id);
-            return;
+            return Ok(());
         }
 
-        println!("Payroll records for employee {}:\n", employee_id);
-        for record in records {
-            PayrollPresentation::print_payroll_summary(record);
+        PayrollPresentation::print_employee_history(&employee_id, &records);
+        println!();
+        if self.confirm("Show the full summary of each record?")? {
             println!();
+            for record in records {
+                PayrollPresentation::print_payroll_summary(record);
+                println!();
+            }
+        }
+        Ok(())
+    }
+    fn simulate_salary(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Salary Simulation ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered. Add an employee first.\n");
+            return Ok(());
+        }
+
+        let employee_id = self.read_employee_id("Employee ID: ", false)?;
+        let employee = match self.payroll.get_employee(&employee_id) {
+            Some(employee) => employee,
+            None => {
+                println!("No employee found with ID: {}\n", employee_id);
+                return Ok(());
+            }
+        };
+
+        let raise_prompt = match employee {
+            EmployeeData::Fulltime(_) => "New Base Salary (leave blank to keep): ",
+            EmployeeData::Contract(_) => "New Hourly Rate (leave blank to keep): ",
+        };
+        let raise_to = loop {
+            match self.get_optional_number_input(raise_prompt)? {
+                None => break None,
+                Some(Ok(amount)) if amount > 0.0 => break Some(amount),
+                _ => println!("{}", term::warning("Please enter a valid positive number or leave blank.")),
+            }
+        };
+
+        let additional_allowance = loop {
+            match self.get_optional_number_input("Additional Allowance (leave blank for none): ")? {
+                None => break None,
+                Some(Ok(amount)) if amount >= 0.0 => break Some(amount),
+                _ => println!("{}", term::warning("Please enter a valid positive number or leave blank.")),
+            }
+        };
+
+        let ptkp_status = match employee {
+            EmployeeData::Fulltime(_) => loop {
+                let input = self.get_input("PTKP Status (e.g. TK/0, K/1; leave blank to keep): ")?;
+                let input = input.trim();
+                if input.is_empty() {
+                    break None;
+                }
+                match input.parse::<PtkpStatus>() {
+                    Ok(status) => break Some(status),
+                    Err(_) => println!("{}", term::warning("Please enter a status from TK/0-TK/3 or K/0-K/3.")),
+                }
+            },
+            EmployeeData::Contract(_) => None,
+        };
+
+        let scenario = SalaryScenario {
+            raise_to,
+            additional_allowance,
+            ptkp_status,
+        };
+        let result = SalarySimulator::simulate(employee, &scenario);
+
+        println!();
+        PayrollPresentation::print_simulation(&result);
+        println!();
+        Ok(())
+    }
+    fn void_payroll_record(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Void Payroll Record ==="));
+
+        let employee_id = self.read_employee_id("Employee ID: ", false)?;
+        let pay_period = self.get_input("Pay Period: ")?.trim().to_string();
+
+        let index = match self.payroll.find_record_index(&employee_id, &pay_period) {
+            Some(index) => index,
+            None => {
+                println!("No active payroll record found for {} in {}\n", employee_id, pay_period);
+                return Ok(());
+            }
+        };
+
+        let record = &self.payroll.get_payroll_records()[index];
+        println!(
+            "Net salary {} processed {}",
+            self.config.currency.format(record.net_salary),
+            record.processed_date.format("%Y-%m-%d")
+        );
+        if !self.confirm(&format!("Void the payroll record of {} for {}?", employee_id, pay_period))? {
+            println!("Record not voided.\n");
+            return Ok(());
+        }
+
+        match self.payroll.void_record(index) {
+            Ok(()) => {
+                self.undo_stack.push(UndoAction::VoidRecord { index });
+                term::status!("{}\n", term::success("Payroll record voided successfully!"));
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Record not voided: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn undo_last_action(&mut self) {
+        term::status!("\n{}", term::heading("=== Undo Last Action ==="));
+
+        let Some(action) = self.undo_stack.pop() else {
+            println!("Nothing to undo.\n");
+            return;
+        };
+        let description = action.describe(&self.payroll);
+        match action {
+            UndoAction::VoidRecord { index } => match self.payroll.restore_record(index) {
+                Ok(()) => {}
+                Err(err) => {
+                    println!("{}\n", term::error(format_args!("Could not undo {}: {}", description, err)));
+                    return;
+                }
+            },
+            UndoAction::ReplaceEmployee { previous } | UndoAction::RemoveEmployee { previous } => {
+                self.payroll.add_employee(*previous)
+            }
+        }
+        term::status!("{}\n", term::success(format_args!("Undid {}.", description)));
+    }
+
+    fn close_pay_period(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Close Pay Period ==="));
+
+        let pay_period = self.get_input("Pay Period to close: ")?.trim().to_string();
+        if self.payroll.is_period_closed(&pay_period) {
+            println!("{}\n", term::warning(format_args!("Pay period '{}' is already closed.", pay_period)));
+            return Ok(());
+        }
+
+        self.payroll.close_period(&pay_period);
+        println!("Pay period '{}' closed. Its records can no longer be changed.\n", pay_period);
+        Ok(())
+    }
+
+    fn reopen_pay_period(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Reopen Pay Period ==="));
+
+        let pay_period = self.get_input("Pay Period to reopen: ")?.trim().to_string();
+        if !self.config.api.is_enabled() {
+            println!(
+                "{}\n",
+                term::error("Pay period not reopened: configure an admin API key or JWT secret ([api]) first")
+            );
+            return Ok(());
+        }
+        let credential = self.get_input("Admin API key or token: ")?;
+        let principal = match self.config.api.authenticate(&credential, Utc::now()) {
+            Ok(principal) => principal,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Pay period not reopened: {}", err)));
+                return Ok(());
+            }
+        };
+
+        match self.payroll.reopen_period(&pay_period, &principal) {
+            Ok(()) => println!("Pay period '{}' reopened.\n", pay_period),
+            Err(err) => println!("{}\n", term::error(format_args!("Pay period not reopened: {}", err))),
+        }
+        Ok(())
+    }
+    fn compare_pay_periods(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Compare Pay Periods ==="));
+
+        if self.payroll.get_payroll_records().is_empty() {
+            println!("No payroll records found.\n");
+            return Ok(());
+        }
+
+        let current_period = self.get_input("Current Pay Period: ")?.trim().to_string();
+        let previous_period = self
+            .get_input("Previous Pay Period (leave blank for the month before): ")?
+            .trim()
+            .to_string();
+        let previous_period = if previous_period.is_empty() {
+            match current_period.parse::<PayPeriod>() {
+                Ok(period) => period.previous().to_string(),
+                Err(_) => {
+                    println!(
+                        "{}\n",
+                        term::error(format_args!("Could not determine the previous period of '{}'.", current_period))
+                    );
+                    return Ok(());
+                }
+            }
+        } else {
+            previous_period
+        };
+
+        let comparison = self.payroll.compare_periods(&previous_period, &current_period);
+        if comparison.employees.is_empty() {
+            println!("No payroll records found for either period.\n");
+            return Ok(());
+        }
+
+        println!();
+        PayrollPresentation::print_period_comparison(&comparison);
+        println!();
+        Ok(())
+    }
+    fn export_payroll_xlsx(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export Payroll to Excel ==="));
+
+        if self.payroll.get_payroll_records().is_empty() {
+            println!("No payroll records found.\n");
+            return Ok(());
+        }
+
+        #[cfg(feature = "xlsx")]
+        {
+            let path = self.get_input("Output file (default: payroll.xlsx): ")?.trim().to_string();
+            let path = if path.is_empty() { "payroll.xlsx".to_string() } else { path };
+            match crate::export::xlsx::write_payroll_workbook(self.payroll.get_payroll_records(), &path) {
+                Ok(()) => println!("Payroll exported to {}\n", path),
+                Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+            }
         }
+
+        #[cfg(not(feature = "xlsx"))]
+        println!("{}\n", term::warning("Excel export is not available in this build. Rebuild with `--features xlsx`."));
+        Ok(())
     }
-}
\ No newline at end of file
+    fn get_run_id_input(&self) -> Prompted<Option<u32>> {
+        if self.payroll.runs.is_empty() {
+            println!("No payroll runs found.\n");
+            return Ok(None);
+        }
+
+        for run in &self.payroll.runs {
+            println!(
+                "Run #{} - {} ({} records, {:?})",
+                run.run_id,
+                run.pay_period,
+                run.record_indices.len(),
+                run.status
+            );
+        }
+
+        match self.get_input("Run ID: ")?.trim().parse::<u32>() {
+            Ok(run_id) => Ok(Some(run_id)),
+            Err(_) => {
+                println!("{}\n", term::warning("Please enter a valid run ID."));
+                Ok(None)
+            }
+        }
+    }
+
+    fn create_payroll_run(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Create Payroll Run ==="));
+
+        let pay_period = self.get_input("Pay Period: ")?.trim().to_string();
+        match self.payroll.create_run(&pay_period) {
+            Ok(run) => {
+                println!(
+                    "Draft payroll run #{} created with {} records.\n",
+                    run.run_id,
+                    run.record_indices.len()
+                );
+                if !run.held.is_empty() {
+                    println!(
+                        "{}\n",
+                        term::warning(format_args!("Held until onboarding is finished: {}", run.held.join(", ")))
+                    );
+                }
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Run not created: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn approve_payroll_run(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Approve Payroll Run ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        match self.payroll.approve_run(run_id) {
+            Ok(run) => println!("Payroll run #{} approved.\n", run.run_id),
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Run not approved: {}", err)));
+                return Ok(());
+            }
+        }
+        if let Ok(summary) = self.payroll.remittance_for_run(run_id, &self.bpjs_rates) {
+            PayrollPresentation::print_remittance_summary(&summary);
+            println!();
+        }
+        self.notify_run_completed(run_id);
+        Ok(())
+    }
+
+    fn notify_run_completed(&self, run_id: u32) {
+        if self.config.notifications.is_empty() {
+            return;
+        }
+        let summary = match RunSummary::for_run(&self.payroll, run_id) {
+            Ok(summary) => summary,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Run summary not sent: {}", err)));
+                return;
+            }
+        };
+
+        #[cfg(feature = "notifications")]
+        {
+            for (channel, result) in crate::notify::notify_all(&self.config.notifications, &summary) {
+                match result {
+                    Ok(()) => println!("Run summary posted to {}.", channel),
+                    Err(err) => println!(
+                        "{}",
+                        term::error(format_args!("Run summary not posted to {}: {}", channel, err))
+                    ),
+                }
+            }
+            println!();
+        }
+
+        #[cfg(not(feature = "notifications"))]
+        {
+            let _ = summary;
+            println!(
+                "{}\n",
+                term::warning("Notifications are not available in this build. Rebuild with `--features notifications`.")
+            );
+        }
+    }
+
+    fn export_journal_entries(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export Journal Entries ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        let lines = match self.payroll.journal_for_run(run_id, &self.account_codes) {
+            Ok(lines) => lines,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Journal not generated: {}", err)));
+                return Ok(());
+            }
+        };
+
+        let format = loop {
+            let input = self.get_input("Format (generic, quickbooks or xero; default: generic): ")?;
+            if input.trim().is_empty() {
+                break JournalFormat::Generic;
+            }
+            match input.parse::<JournalFormat>() {
+                Ok(format) => break format,
+                Err(err) => println!("{}", term::error(err)),
+            }
+        };
+
+        let default_path = format!("journal_run_{}.csv", run_id);
+        let path = self
+            .get_input(&format!("Output file (default: {}): ", default_path))?
+            .trim()
+            .to_string();
+        let path = if path.is_empty() { default_path } else { path };
+
+        let result = std::fs::File::create(&path).and_then(|mut file| format.write(&lines, &mut file));
+        match result {
+            Ok(()) => println!("{} journal lines exported to {}\n", lines.len(), path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+    fn export_ebupot(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export e-Bupot 21/26 ==="));
+
+        let pay_period = self.get_input("Pay Period: ")?.trim().to_string();
+        let period = match pay_period.parse::<PayPeriod>() {
+            Ok(period) => period,
+            Err(err) => {
+                println!("{}\n", term::error(err));
+                return Ok(());
+            }
+        };
+
+        let records = self.payroll.get_period_records(&pay_period);
+        if records.is_empty() {
+            println!("No payroll records found for {}.\n", period);
+            return Ok(());
+        }
+
+        let missing = ebupot::missing_tax_ids(&records);
+        if !missing.is_empty() {
+            println!("{}", term::warning(format_args!("Warning: no NPWP or NIK recorded for: {}", missing.join(", "))));
+        }
+
+        let default_path = format!("ebupot_{}_{:02}.csv", period.year, period.month);
+        let path = self
+            .get_input(&format!("Output file (default: {}): ", default_path))?
+            .trim()
+            .to_string();
+        let path = if path.is_empty() { default_path } else { path };
+
+        let result = std::fs::File::create(&path).and_then(|mut file| ebupot::write_ebupot_csv(records.iter().copied(), &mut file));
+        match result {
+            Ok(rows) => println!("{} withholding rows exported to {}\n", rows, path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+    fn export_bpjs_sipp(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export BPJS SIPP Report ==="));
+
+        let pay_period = self.get_input("Pay Period: ")?.trim().to_string();
+        let period = match pay_period.parse::<PayPeriod>() {
+            Ok(period) => period,
+            Err(err) => {
+                println!("{}\n", term::error(err));
+                return Ok(());
+            }
+        };
+
+        let records = self.payroll.get_period_records(&pay_period);
+        if records.is_empty() {
+            println!("No payroll records found for {}.\n", period);
+            return Ok(());
+        }
+
+        let default_path = format!("bpjs_sipp_{}_{:02}.csv", period.year, period.month);
+        let path = self
+            .get_input(&format!("Output file (default: {}): ", default_path))?
+            .trim()
+            .to_string();
+        let path = if path.is_empty() { default_path } else { path };
+
+        let result = std::fs::File::create(&path)
+            .and_then(|mut file| sipp::write_sipp_csv(records.iter().copied(), &self.bpjs_rates, &mut file));
+        match result {
+            Ok(rows) => println!("{} contribution rows exported to {}\n", rows, path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+    #[cfg(feature = "email")]
+    fn email_payslips(&mut self) -> Prompted<()> {
+        use crate::delivery::{DeliveryStatus, PayslipOptions};
+        use crate::email::{SmtpPayslipSender, SmtpSettings};
+
+        term::status!("\n{}", term::heading("=== Email Payslips ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+
+        let settings = SmtpSettings {
+            host: self.get_input("SMTP Host: ")?.trim().to_string(),
+            port: self.get_input("SMTP Port (default: 587): ")?.trim().parse().unwrap_or(587),
+            username: self.get_input("SMTP Username: ")?.trim().to_string(),
+            password: self.get_input("SMTP Password: ")?.trim().to_string(),
+            from: self.get_input("From Address: ")?.trim().to_string(),
+        };
+        let sender = match SmtpPayslipSender::new(&settings) {
+            Ok(sender) => sender,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Could not configure SMTP: {}", err)));
+                return Ok(());
+            }
+        };
+
+        let options = if cfg!(feature = "pdf") {
+            let owner_password = self.get_optional_input("PDF owner password (leave blank to generate one): ")?;
+            let signing_key = self.get_optional_input("Payslip signing key (leave blank for none): ")?;
+            match PayslipOptions::pdf(owner_password, signing_key) {
+                Ok(options) => options,
+                Err(err) => {
+                    println!("{}\n", term::error(err));
+                    return Ok(());
+                }
+            }
+        } else {
+            PayslipOptions::html(self.get_optional_input("Payslip signing key (leave blank for none): ")?)
+        };
+
+        match self.payroll.deliver_payslips(run_id, &sender, &options) {
+            Ok(deliveries) => {
+                for delivery in deliveries {
+                    let status = match &delivery.status {
+                        DeliveryStatus::Sent(at) => format!("sent {}", at.format("%Y-%m-%d %H:%M:%S")),
+                        DeliveryStatus::Failed(err) => format!("failed: {}", err),
+                        DeliveryStatus::NoEmailAddress => "no email address".to_string(),
+                    };
+                    println!("{:<12} {}", delivery.employee_id, status);
+                }
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Payslips not sent: {}", err))),
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "email"))]
+    fn email_payslips(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Email Payslips ==="));
+        println!(
+            "{}\n",
+            term::warning("Email delivery is not available in this build. Rebuild with `--features email`.")
+        );
+        Ok(())
+    }
+    fn prompt_with_default(&self, label: &str, default: &str) -> Prompted<String> {
+        Ok(self
+            .get_optional_input(&format!("{} (default: {}): ", label, default))?
+            .unwrap_or_else(|| default.to_string()))
+    }
+
+    fn prompt_number_with_default(&self, label: &str, default: f64, valid: impl Fn(f64) -> bool) -> Prompted<f64> {
+        loop {
+            match self.get_optional_number_input(&format!("{} (default: {}): ", label, default))? {
+                None => break Ok(default),
+                Some(Ok(value)) if valid(value) => break Ok(value),
+                _ => println!("{}", term::warning("Please enter a valid number.")),
+            }
+        }
+    }
+
+    fn setup_wizard(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Setup Wizard ==="));
+        println!("Press Enter to keep the value shown in brackets.\n");
+
+        let mut config = self.config.clone();
+
+        term::status!("{}", term::heading("-- Company --"));
+        config.company.name = self.prompt_with_default("Company name", &config.company.name)?;
+        config.company.npwp = self.get_optional_input(&format!(
+            "Company NPWP (default: {}): ",
+            config.company.npwp.as_deref().unwrap_or("none")
+        ))?
+        .or(config.company.npwp);
+        config.company.address = self.get_optional_input(&format!(
+            "Company address (default: {}): ",
+            config.company.address.as_deref().unwrap_or("none")
+        ))?
+        .or(config.company.address);
+
+        term::status!("\n{}", term::heading("-- Tax --"));
+        println!("Available tax tables: {}", config.tax.regime_ids().join(", "));
+        config.tax.table = loop {
+            let table = self.prompt_with_default("Tax table", &config.tax.table)?;
+            match config.tax.regime(&table) {
+                Some(table) => break table.id().to_string(),
+                None => println!("{}", term::warning(format_args!("Unknown tax table '{}'.", table))),
+            }
+        };
+        config.tax.rounding = loop {
+            let rounding = self.prompt_with_default("Tax rounding (exact/djp)", config.tax.rounding.as_str())?;
+            match rounding.parse() {
+                Ok(rounding) => break rounding,
+                Err(err) => println!("{}", term::warning(err)),
+            }
+        };
+        let is_rate = |rate: f64| (0.0..=1.0).contains(&rate);
+        config.bpjs.kesehatan_rate =
+            self.prompt_number_with_default("BPJS Kesehatan rate", config.bpjs.kesehatan_rate, is_rate)?;
+        config.bpjs.ketenagakerjaan_rate = self.prompt_number_with_default(
+            "BPJS Ketenagakerjaan rate",
+            config.bpjs.ketenagakerjaan_rate,
+            is_rate,
+        )?;
+
+        term::status!("\n{}", term::heading("-- Working Hours --"));
+        config.overtime.standard_monthly_hours = self.prompt_number_with_default(
+            "Standard monthly hours",
+            config.overtime.standard_monthly_hours,
+            |hours| hours > 0.0,
+        )?;
+        config.overtime.multiplier =
+            self.prompt_number_with_default("Overtime multiplier", config.overtime.multiplier, |rate| rate >= 0.0)?;
+
+        term::status!("\n{}", term::heading("-- Storage --"));
+        config.storage_path = self.prompt_with_default("Data file", &config.storage_path)?;
+
+        let path = Config::path();
+        match config.save(&path) {
+            Ok(()) => {
+                self.payroll.configure(&config);
+                self.ledger = PayrollLedger::beside(Path::new(&config.storage_path));
+                self.config = config;
+                println!("Configuration written to {}\n", path.display());
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Configuration not saved: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn get_storage(&self) -> Prompted<JsonFileStorage> {
+        let prompt = format!("Data file (default: {}): ", self.config.storage_path);
+        let path = self
+            .get_optional_input(&prompt)?
+            .unwrap_or_else(|| self.config.storage_path.clone());
+        let storage = storage_at(path);
+
+        #[cfg(feature = "encryption")]
+        {
+            use crate::encryption::EncryptionKey;
+
+            let storage = match self.get_optional_input("Passphrase (leave blank for none): ")? {
+                Some(passphrase) => storage.with_encryption(EncryptionKey::Passphrase(passphrase)),
+                None => storage,
+            };
+            match self.get_optional_input("Key file (leave blank for none): ")? {
+                Some(key_file) => Ok(storage.with_encryption(EncryptionKey::KeyFile(key_file.into()))),
+                None => Ok(storage),
+            }
+        }
+
+        #[cfg(not(feature = "encryption"))]
+        Ok(storage)
+    }
+
+    fn save_data(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Save Data ==="));
+
+        let storage = self.get_storage()?;
+        let mut saved = storage.save(&self.payroll);
+        if let Err(StorageError::Conflict(stored, _)) = saved {
+            let path = storage.path().display();
+            println!("{}", term::warning(format_args!("{} was saved elsewhere since it was loaded.", path)));
+            if self.confirm("Overwrite those changes with this session?")? {
+                self.payroll.revision.store(stored, Ordering::SeqCst);
+                saved = storage.save(&self.payroll);
+            }
+        }
+        match saved {
+            Ok(()) => println!("Data saved to {}\n", storage.path().display()),
+            Err(err) => println!("{}\n", term::error(format_args!("Data not saved: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn load_data(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Load Data ==="));
+
+        let storage = self.get_storage()?;
+        match storage.load() {
+            Ok(payroll) => {
+                self.payroll = payroll;
+                self.payroll.configure(&self.config);
+                self.ledger = PayrollLedger::beside(storage.path());
+                self.undo_stack.clear();
+                println!(
+                    "Loaded {} employees and {} payroll records from {}\n",
+                    self.payroll.employees.len(),
+                    self.payroll.get_payroll_records().len(),
+                    storage.path().display()
+                );
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Data not loaded: {}", err))),
+        }
+        Ok(())
+    }
+    fn verify_ledger(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Verify Payroll Ledger ==="));
+
+        let storage = self.get_storage()?;
+        let Some(ledger) = storage.ledger() else {
+            return Ok(());
+        };
+        let verified = storage.load().and_then(|payroll| ledger.verify_against(&payroll));
+        let path = ledger.path().display();
+        match verified {
+            Ok(LedgerVerification::Valid { entries }) => {
+                println!("Ledger {} is intact and matches the data file ({} entries).\n", path, entries)
+            }
+            Ok(LedgerVerification::Tampered { sequence, reason }) => println!(
+                "{}\n",
+                term::error(format_args!("Ledger {} has been modified at entry #{}: {}", path, sequence, reason))
+            ),
+            Ok(LedgerVerification::KeyRequired { sequence }) => println!(
+                "{}\n",
+                term::error(format_args!(
+                    "Ledger {} is signed from entry #{}; set PAYROLL_LEDGER_KEY to verify it",
+                    path, sequence
+                ))
+            ),
+            Ok(LedgerVerification::DataMismatch { differences }) => {
+                println!(
+                    "{}",
+                    term::error(format_args!("Ledger {} does not match {}:", path, storage.path().display()))
+                );
+                for difference in differences {
+                    println!("  {}", difference);
+                }
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Ledger could not be verified: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn get_period_input(&self, prompt: &str) -> Prompted<Option<PayPeriod>> {
+        match self.get_input(prompt)?.trim().parse::<PayPeriod>() {
+            Ok(period) => Ok(Some(period)),
+            Err(err) => {
+                println!("{}\n", term::error(err));
+                Ok(None)
+            }
+        }
+    }
+
+    fn export_audit_bundle(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export Auditor Bundle ==="));
+
+        let from = match self.get_period_input("From Pay Period: ")? {
+            Some(period) => period,
+            None => return Ok(()),
+        };
+        let to = match self.get_period_input("To Pay Period: ")? {
+            Some(period) => period,
+            None => return Ok(()),
+        };
+        if from > to {
+            println!("The start period must not be after the end period.\n");
+            return Ok(());
+        }
+
+        let default_dir = format!(
+            "audit_bundle_{}{:02}_{}{:02}",
+            from.year, from.month, to.year, to.month
+        );
+        let dir = self
+            .get_input(&format!("Output directory (default: {}): ", default_dir))?
+            .trim()
+            .to_string();
+        let dir = if dir.is_empty() { default_dir } else { dir };
+
+        match audit_bundle::write_audit_bundle(
+            &self.payroll,
+            Some(&self.ledger),
+            &self.bpjs_rates,
+            from,
+            to,
+            std::path::Path::new(&dir),
+        ) {
+            Ok(manifest) => {
+                println!("Auditor bundle written to {} ({} records)", dir, manifest.records);
+                for file in &manifest.files {
+                    println!("  {:<22} sha256 {}", file.name, file.sha256);
+                }
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Bundle not written: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn export_payroll_register(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export Payroll Register (CSV) ==="));
+
+        let storage = self.get_storage()?;
+        let path = self
+            .get_input("Output file (default: payroll_register.csv): ")?
+            .trim()
+            .to_string();
+        let path = if path.is_empty() { "payroll_register.csv".to_string() } else { path };
+
+        let file = match std::fs::File::create(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Export failed: {}", err)));
+                return Ok(());
+            }
+        };
+        let mut register = match RegisterCsvWriter::new(io::BufWriter::new(file)) {
+            Ok(register) => register,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Export failed: {}", err)));
+                return Ok(());
+            }
+        };
+
+        let mut totals = PayrollTotals::default();
+        let result = storage.for_each_record(&mut |record| {
+            totals.add(&record);
+            register.write_record(&record)?;
+            Ok(())
+        });
+        match result.and_then(|_| Ok(register.finish()?)) {
+            Ok((_, rows)) => {
+                println!("{} records exported to {}", rows, path);
+                PayrollPresentation::print_total_summary(&totals);
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn show_payroll_register(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Payroll Run Register ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        let register = match self.payroll.register_for_run(run_id) {
+            Ok(register) => register,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Register not generated: {}", err)));
+                return Ok(());
+            }
+        };
+        PayrollPresentation::print_payroll_register(&register);
+        println!();
+
+        let Some(path) = self.get_optional_input("Export to file (.csv or .xlsx, blank to skip): ")? else {
+            return Ok(());
+        };
+        match register.save(&path) {
+            Ok(()) => println!("{} rows exported to {}\n", register.rows.len(), path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn export_bank_transfers(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export Bank Transfer File ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        let batch = match self.payroll.bank_transfers_for_run(run_id) {
+            Ok(batch) => batch,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Transfers not generated: {}", err)));
+                return Ok(());
+            }
+        };
+        PayrollPresentation::print_bank_transfers(&batch);
+        println!();
+        if !batch.problems.is_empty() {
+            println!(
+                "{}\n",
+                term::warning("Fix the salary split of the employees listed above and export again to pay them.")
+            );
+        }
+
+        let Some(path) = self.get_optional_input("Export to CSV file (blank to skip): ")? else {
+            return Ok(());
+        };
+        match std::fs::File::create(&path).and_then(|file| batch.write_csv(io::BufWriter::new(file))) {
+            Ok(()) => println!("Bank transfers exported to {}\n", path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn export_ewallet_payouts(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Export E-Wallet Payouts ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        let Some(wallet) = self.read_filter::<EWallet>("E-Wallet (gopay/ovo/dana): ")? else {
+            return Ok(());
+        };
+        let batch = match self.payroll.bank_transfers_for_run(run_id) {
+            Ok(batch) => batch,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Payouts not generated: {}", err)));
+                return Ok(());
+            }
+        };
+        PayrollPresentation::print_ewallet_transfers(&batch, wallet);
+        println!();
+
+        let default_path = format!("{}_run_{}.csv", wallet.as_str().to_lowercase(), run_id);
+        let path = self.prompt_with_default("Output file", &default_path)?;
+        match std::fs::File::create(&path).and_then(|file| batch.write_ewallet_csv(wallet, io::BufWriter::new(file))) {
+            Ok(()) => println!("{} payouts exported to {}\n", wallet.as_str(), path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn show_remittance_summary(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Remittance Summary ==="));
+
+        let run_id = match self.get_run_id_input()? {
+            Some(run_id) => run_id,
+            None => return Ok(()),
+        };
+        let summary = match self.payroll.remittance_for_run(run_id, &self.bpjs_rates) {
+            Ok(summary) => summary,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Summary not generated: {}", err)));
+                return Ok(());
+            }
+        };
+        PayrollPresentation::print_remittance_summary(&summary);
+        println!();
+
+        let Some(path) = self.get_optional_input("Export to CSV file (blank to skip): ")? else {
+            return Ok(());
+        };
+        match std::fs::File::create(&path).and_then(|file| summary.write_csv(io::BufWriter::new(file))) {
+            Ok(()) => println!("Remittance summary exported to {}\n", path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn show_cost_trend(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Headcount and Cost Trend ==="));
+
+        let from = match self.get_period_input("From Pay Period: ")? {
+            Some(period) => period,
+            None => return Ok(()),
+        };
+        let to = match self.get_period_input("To Pay Period: ")? {
+            Some(period) => period,
+            None => return Ok(()),
+        };
+        if from > to {
+            println!("The start period must not be after the end period.\n");
+            return Ok(());
+        }
+
+        let months = self.payroll.cost_trend(from, to, &self.bpjs_rates);
+        PayrollPresentation::print_cost_trend(&months);
+        println!();
+
+        let Some(path) = self.get_optional_input("Export to CSV file (blank to skip): ")? else {
+            return Ok(());
+        };
+        match std::fs::File::create(&path).and_then(|file| write_cost_trend_csv(&months, io::BufWriter::new(file))) {
+            Ok(()) => println!("{} months exported to {}\n", months.len(), path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn compare_budget(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Budget vs Actual ==="));
+
+        let path = self.get_input("Budget file (CSV with department, period, amount): ")?.trim().to_string();
+        let budget = match std::fs::File::open(&path)
+            .and_then(|file| read_budget_csv(io::BufReader::new(file), &self.config.locale))
+        {
+            Ok(budget) => budget,
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Budget not loaded: {}", err)));
+                return Ok(());
+            }
+        };
+        if budget.lines.is_empty() {
+            println!("The budget file has no rows.\n");
+            return Ok(());
+        }
+
+        let rows = budget.compare(&self.payroll, &self.bpjs_rates);
+        PayrollPresentation::print_budget_variance(&rows);
+        println!();
+
+        let Some(path) = self.get_optional_input("Export to CSV file (blank to skip): ")? else {
+            return Ok(());
+        };
+        match std::fs::File::create(&path).and_then(|file| write_variance_csv(&rows, io::BufWriter::new(file))) {
+            Ok(()) => println!("{} rows exported to {}\n", rows.len(), path),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn show_statistics(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Payroll Statistics ==="));
+
+        let mut query = self.payroll.query();
+        let mut scope = Vec::new();
+        if let Some(period) = self.read_filter::<PayPeriod>("Pay Period (Enter for all): ")? {
+            query = query.period(&period.to_string());
+            scope.push(period.to_string());
+        }
+        if let Some(kind) = self.read_filter::<EmployeeKind>("Type (fulltime/contract, Enter for all): ")? {
+            query = query.employee_kind(kind);
+            scope.push(kind.as_str().to_string());
+        }
+
+        let scope = if scope.is_empty() { "all records".to_string() } else { scope.join(", ") };
+        PayrollPresentation::print_statistics(&scope, &self.payroll.statistics(&query));
+        println!();
+        Ok(())
+    }
+
+    fn manage_onboarding(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Onboarding Checklists ==="));
+
+        let pending = self.payroll.pending_onboarding();
+        if pending.is_empty() {
+            println!("No open onboarding tasks.\n");
+            return Ok(());
+        }
+        PayrollPresentation::print_onboarding(&pending);
+        println!();
+
+        let Some(employee_id) = self.get_optional_input("Employee ID (Enter to go back): ")? else {
+            return Ok(());
+        };
+        let Some(task) = self.read_filter::<OnboardingTask>("Task done (bpjs/bank/npwp/contract): ")? else {
+            return Ok(());
+        };
+        match self.payroll.complete_onboarding_task(&employee_id, task) {
+            Ok(true) if self.payroll.is_onboarded(&employee_id) => term::status!(
+                "{}\n",
+                term::success(format_args!("{} done; {} can now be included in payroll runs.", task, employee_id))
+            ),
+            Ok(true) => term::status!("{}\n", term::success(format_args!("{} done for {}.", task, employee_id))),
+            Ok(false) => println!("{} was already done for {}.\n", task, employee_id),
+            Err(err) => println!("{}\n", term::error(format_args!("Task not updated: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn review_probation(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Probation Reviews ==="));
+
+        let today = Local::now().date_naive();
+        let due = self.payroll.probation_reviews_due(today + chrono::Duration::days(PROBATION_REVIEW_DAYS));
+        if due.is_empty() {
+            println!("No probation reviews due in the next {} days.\n", PROBATION_REVIEW_DAYS);
+            return Ok(());
+        }
+        PayrollPresentation::print_probation_reviews(&due, today);
+        println!();
+
+        let Some(employee_id) = self.get_optional_input("Employee ID to confirm as permanent (Enter to go back): ")?
+        else {
+            return Ok(());
+        };
+        let Some(employee) = self.payroll.get_employee(&employee_id).cloned() else {
+            println!("{}\n", term::error(format_args!("Employee {} is not registered.", employee_id)));
+            return Ok(());
+        };
+        let current = employee.base_rate();
+        let label = match employee {
+            EmployeeData::Fulltime(_) => "Base Salary after probation",
+            EmployeeData::Contract(_) => "Hourly Rate after probation",
+        };
+        let rate = self.read_number(label, Some(current), |rate| rate > 0.0, "Please enter a positive amount.")?;
+        let revision = (rate != current).then_some(rate);
+        match self.payroll.confirm_employee(&employee_id, revision) {
+            Ok(_) => {
+                self.undo_stack.push(UndoAction::ReplaceEmployee {
+                    previous: Box::new(employee),
+                });
+                term::status!("{}", term::success(format_args!("{} is now a permanent employee.", employee_id)));
+                if let Some(rate) = revision {
+                    let revised = format!("Base rate revised: {:.2} -> {:.2}", current, rate);
+                    term::status!("{}", term::success(revised));
+                }
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Employee not confirmed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn manage_salary_changes(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Salary Changes ==="));
+
+        let pending = self.payroll.pending_salary_changes();
+        if pending.is_empty() {
+            println!("No salary changes are waiting for approval.");
+        } else {
+            PayrollPresentation::print_salary_changes(&pending);
+        }
+        let action = self.get_input("[R]equest change, [A]pprove, re[J]ect, [H]istory, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "r" | "request" => self.request_salary_change()?,
+            "a" | "approve" => {
+                let id = self.read_change_request_id()?;
+                let approver = self.get_input("Your name (the approver named on the request): ")?;
+                match self.payroll.approve_salary_change(id, &approver) {
+                    Ok(entry) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Change #{} approved: {} goes from {:.2} to {:.2} from {}.",
+                            id, entry.employee_id, entry.previous_rate, entry.rate, entry.effective
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Change not approved: {}", err))),
+                }
+            }
+            "j" | "reject" => {
+                let id = self.read_change_request_id()?;
+                let approver = self.get_input("Your name (the approver named on the request): ")?;
+                let reason = self.get_input("Reason: ")?;
+                match self.payroll.reject_salary_change(id, &approver, &reason) {
+                    Ok(()) => term::status!("{}\n", term::success(format_args!("Change #{} rejected.", id))),
+                    Err(err) => println!("{}\n", term::error(format_args!("Change not rejected: {}", err))),
+                }
+            }
+            "h" | "history" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let history = self.payroll.salary_history_for(&employee_id);
+                if history.is_empty() {
+                    println!("No approved salary changes for {}.\n", employee_id);
+                } else {
+                    PayrollPresentation::print_salary_history(&employee_id, &history);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn request_salary_change(&mut self) -> Prompted<()> {
+        let employee_id = self.read_employee_id("Employee ID: ", false)?;
+        let Some(employee) = self.payroll.get_employee(&employee_id) else {
+            println!("{}\n", term::error(format_args!("Employee {} is not registered.", employee_id)));
+            return Ok(());
+        };
+        let current_grade = employee.as_employee().profile().grade.clone();
+        let label = match employee {
+            EmployeeData::Fulltime(_) => "New Base Salary",
+            EmployeeData::Contract(_) => "New Hourly Rate",
+        };
+        let rate = self.read_number(label, None, |rate| rate > 0.0, "Please enter a positive amount.")?;
+        let grade = self.read_optional_field("New Grade", current_grade.as_deref())?;
+        let effective = self.read_pay_period("Effective Pay Period (e.g., 'January 2027'): ")?;
+        let approver = self.get_input("Approver: ")?;
+        match self.payroll.request_salary_change(&employee_id, rate, grade, effective, &approver) {
+            Ok(request) => term::status!(
+                "{}\n",
+                term::success(format_args!(
+                    "Change request #{} submitted; waiting for {} to approve.",
+                    request.id, request.approver
+                ))
+            ),
+            Err(err) => println!("{}\n", term::error(format_args!("Change not requested: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn manage_reviews(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Performance Reviews ==="));
+
+        if self.payroll.review_cycles.is_empty() {
+            println!("No review cycles yet.");
+        } else {
+            PayrollPresentation::print_review_cycles(&self.payroll.review_cycles);
+        }
+        let action = self.get_input("[N]ew cycle, [S]core, [B]onus run, [C]lose cycle, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "n" | "new" => {
+                let name = self.get_input("Cycle Name (e.g., '2026 Annual'): ")?;
+                if name.trim().is_empty() {
+                    println!("No cycle started.\n");
+                    return Ok(());
+                }
+                match self.payroll.start_review_cycle(&name) {
+                    Ok(cycle) => {
+                        term::status!("{}\n", term::success(format_args!("Review cycle '{}' started.", cycle.name)))
+                    }
+                    Err(err) => println!("{}\n", term::error(format_args!("Cycle not started: {}", err))),
+                }
+            }
+            "s" | "score" => {
+                let cycle = self.get_input("Cycle: ")?;
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let score = self.read_number(
+                    "Score (1-5)",
+                    None,
+                    |score| validate_score(score).is_ok(),
+                    "Please enter a score from 1 to 5.",
+                )?;
+                let reviewer = self.get_optional_input("Reviewer (optional): ")?;
+                match self.payroll.record_review(&cycle, &employee_id, score, reviewer) {
+                    Ok(()) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Score {} recorded for {} (bonus multiplier {:.2}x).",
+                            score,
+                            employee_id,
+                            self.config.bonus.multiplier(score)
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Score not recorded: {}", err))),
+                }
+            }
+            "b" | "bonus" => {
+                let cycle = self.get_input("Cycle: ")?;
+                match self.payroll.bonus_run(&cycle, &self.config.bonus) {
+                    Ok(run) => {
+                        PayrollPresentation::print_bonus_run(&run);
+                        if let Some(path) = self.get_optional_input("Export to CSV (path, Enter to skip): ")? {
+                            match std::fs::File::create(&path).and_then(|file| run.write_csv(io::BufWriter::new(file))) {
+                                Ok(()) => term::status!(
+                                    "{}",
+                                    term::success(format_args!("Bonuses exported to {}", path))
+                                ),
+                                Err(err) => println!("{}", term::error(format_args!("Export failed: {}", err))),
+                            }
+                        }
+                        println!();
+                    }
+                    Err(err) => println!("{}\n", term::error(format_args!("Bonus run failed: {}", err))),
+                }
+            }
+            "c" | "close" => {
+                let cycle = self.get_input("Cycle: ")?;
+                match self.payroll.close_review_cycle(&cycle) {
+                    Ok(()) => term::status!("{}\n", term::success(format_args!("Review cycle '{}' closed.", cycle))),
+                    Err(err) => println!("{}\n", term::error(format_args!("Cycle not closed: {}", err))),
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn show_org_chart(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Organization Chart ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered.\n");
+            return Ok(());
+        }
+        PayrollPresentation::print_org_chart(&self.payroll);
+        let Some(path) = self.get_optional_input("Export to file (.dot for Graphviz, .csv; Enter to skip): ")? else {
+            println!();
+            return Ok(());
+        };
+        let result = std::fs::File::create(&path).and_then(|file| match path.to_lowercase().ends_with(".dot") {
+            true => org_chart::write_org_chart_dot(&self.payroll, io::BufWriter::new(file)),
+            false => org_chart::write_org_chart_csv(&self.payroll, io::BufWriter::new(file)),
+        });
+        match result {
+            Ok(()) => term::status!("{}\n", term::success(format_args!("Organization chart exported to {}", path))),
+            Err(err) => println!("{}\n", term::error(format_args!("Export failed: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn manage_timesheets(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Timesheets ==="));
+
+        let pending = self.payroll.pending_timesheets();
+        if pending.is_empty() {
+            println!("No timesheets are waiting for approval.");
+        } else {
+            PayrollPresentation::print_timesheets(&pending);
+        }
+        let action = self.get_input("[S]ubmit, [A]pprove, re[J]ect, [P]eriod list, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "s" | "submit" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let period = self.read_pay_period("Pay Period (e.g., 'September 2024'): ")?;
+                let hours = self.read_number(
+                    "Hours Worked",
+                    None,
+                    |hours| validate_hours(hours).is_ok(),
+                    "Please enter the hours worked in the period.",
+                )?;
+                match self.payroll.submit_timesheet(&employee_id, period, hours) {
+                    Ok(_) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Timesheet of {} for {} submitted.", employee_id, period))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Timesheet not submitted: {}", err))),
+                }
+            }
+            "a" | "approve" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let period = self.read_pay_period("Pay Period: ")?;
+                let approver = self.read_employee_id("Your Employee ID (a manager above them): ", false)?;
+                match self.payroll.approve_timesheet(&employee_id, &period, &approver) {
+                    Ok(timesheet) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Timesheet of {} for {} approved: {} hours.",
+                            employee_id, period, timesheet.hours
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Timesheet not approved: {}", err))),
+                }
+            }
+            "j" | "reject" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let period = self.read_pay_period("Pay Period: ")?;
+                let approver = self.read_employee_id("Your Employee ID (a manager above them): ", false)?;
+                let reason = self.get_input("Reason: ")?;
+                match self.payroll.reject_timesheet(&employee_id, &period, &approver, &reason) {
+                    Ok(()) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Timesheet of {} for {} rejected.", employee_id, period))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Timesheet not rejected: {}", err))),
+                }
+            }
+            "p" | "period" => {
+                let period = self.read_pay_period("Pay Period: ")?;
+                let timesheets: Vec<&Timesheet> =
+                    self.payroll.timesheets.iter().filter(|timesheet| timesheet.period == period).collect();
+                if timesheets.is_empty() {
+                    println!("No timesheets for {}.\n", period);
+                } else {
+                    PayrollPresentation::print_timesheets(&timesheets);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_overtime(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Overtime ==="));
+
+        let pending = self.payroll.pending_overtime_requests();
+        if pending.is_empty() {
+            println!("No overtime requests are waiting for a decision.");
+        } else {
+            PayrollPresentation::print_overtime_requests(&pending);
+        }
+        let action = self.get_input("[R]equest, [A]pprove, re[J]ect, [E]xceptions, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "r" | "request" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let period = self.read_pay_period("Pay Period (e.g., 'September 2024'): ")?;
+                let hours =
+                    self.read_number("Overtime Hours", None, |hours| hours > 0.0, "Please enter a positive number.")?;
+                let reason = self.get_input("Reason: ")?;
+                match self.payroll.request_overtime(&employee_id, period, hours, &reason) {
+                    Ok(request) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Overtime request #{} submitted.", request.id))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Overtime not requested: {}", err))),
+                }
+            }
+            "a" | "approve" => {
+                let id = self.read_overtime_request_id()?;
+                let approver = self.read_employee_id("Your Employee ID (a manager above them): ", false)?;
+                match self.payroll.approve_overtime(id, &approver) {
+                    Ok(request) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Overtime request #{} approved: {} hours for {} in {}.",
+                            id, request.hours, request.employee_id, request.period
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Overtime not approved: {}", err))),
+                }
+            }
+            "j" | "reject" => {
+                let id = self.read_overtime_request_id()?;
+                let approver = self.read_employee_id("Your Employee ID (a manager above them): ", false)?;
+                let reason = self.get_input("Reason: ")?;
+                match self.payroll.reject_overtime(id, &approver, &reason) {
+                    Ok(()) => {
+                        term::status!("{}\n", term::success(format_args!("Overtime request #{} rejected.", id)))
+                    }
+                    Err(err) => println!("{}\n", term::error(format_args!("Overtime not rejected: {}", err))),
+                }
+            }
+            "e" | "exceptions" => {
+                let period = self.get_input("Pay Period: ")?.trim().to_string();
+                let records = self.payroll.overtime_exceptions(&period);
+                if records.is_empty() {
+                    println!("No overtime beyond policy in {}.\n", period);
+                } else {
+                    PayrollPresentation::print_overtime_exceptions(&records);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_attendance(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Attendance ==="));
+
+        let action = self.get_input("[R]ecord a day, [S]ummary of a period, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "r" | "record" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let date = self.read_date("Date (YYYY-MM-DD): ")?;
+                let is_minutes = |minutes: f64| minutes >= 0.0 && minutes.fract() == 0.0;
+                let late = self.read_number("Minutes Late", Some(0.0), is_minutes, "Please enter whole minutes.")?;
+                let early =
+                    self.read_number("Minutes Left Early", Some(0.0), is_minutes, "Please enter whole minutes.")?;
+                let result = AttendanceEntry::new(&employee_id, date, late as u32, early as u32)
+                    .map_err(PayrollError::InvalidAttendance)
+                    .and_then(|entry| self.payroll.record_attendance(entry));
+                match result {
+                    Ok(()) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Attendance of {} on {} recorded.", employee_id, date))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Attendance not recorded: {}", err))),
+                }
+            }
+            "s" | "summary" => {
+                let period = self.read_pay_period("Pay Period: ")?;
+                if self.payroll.attendance.iter().any(|entry| entry.period() == period) {
+                    PayrollPresentation::print_attendance_summary(&self.payroll, &period);
+                    println!();
+                } else {
+                    println!("No attendance recorded for {}.\n", period);
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_travel(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Travel ==="));
+
+        let action = self.get_input("[R]ecord a trip, [C]ancel a trip, [L]ist a period, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "r" | "record" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let destination = self.get_input("Destination: ")?;
+                let tier = loop {
+                    match self.get_input("Destination Tier (local/domestic/international): ")?.parse() {
+                        Ok(tier) => break tier,
+                        Err(err) => println!("{}", term::warning(err)),
+                    }
+                };
+                let start = self.read_date("First Day (YYYY-MM-DD): ")?;
+                let is_days = |days: f64| days >= 1.0 && days.fract() == 0.0;
+                let days = self.read_number("Days", None, is_days, "Please enter whole days.")?;
+                let period = self.read_pay_period("Pay Period to pay it in: ")?;
+                match self.payroll.record_travel(&employee_id, &destination, tier, start, days as u32, Some(period)) {
+                    Ok(record) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Travel #{} recorded: per diem {} ({} taxable).",
+                            record.id,
+                            self.config.currency.format(record.amount()),
+                            self.config.currency.format(record.taxable_amount())
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Travel not recorded: {}", err))),
+                }
+            }
+            "c" | "cancel" => {
+                let id = loop {
+                    match self.get_input("Travel #: ")?.trim().trim_start_matches('#').parse() {
+                        Ok(id) => break id,
+                        Err(_) => println!("{}", term::warning("Please enter a travel record number.")),
+                    }
+                };
+                match self.payroll.cancel_travel(id) {
+                    Ok(_) => term::status!("{}\n", term::success(format_args!("Travel #{} cancelled.", id))),
+                    Err(err) => println!("{}\n", term::error(format_args!("Travel not cancelled: {}", err))),
+                }
+            }
+            "l" | "list" => {
+                let period = self.read_pay_period("Pay Period: ")?;
+                let records = self.payroll.travel_records(Some(&period));
+                if records.is_empty() {
+                    println!("No travel paid in {}.\n", period);
+                } else {
+                    PayrollPresentation::print_travel(&records);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_expenses(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Expenses ==="));
+
+        let action = self.get_input("[S]ubmit a claim, [C]ancel a claim, [L]ist a period, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "s" | "submit" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let category = self.get_input("Category (e.g. communication, medical): ")?;
+                let amount =
+                    self.read_number("Amount", None, |amount| amount > 0.0, "Please enter a positive amount.")?;
+                let period = self.read_pay_period("Pay Period to pay it in: ")?;
+                let description = self.get_input("Description: ")?;
+                match self.payroll.submit_expense(&employee_id, &category, period, amount, &description) {
+                    Ok(claim) => match &claim.note {
+                        Some(note) => println!(
+                            "{}\n",
+                            term::warning(format_args!(
+                                "Expense claim #{} {}; {} will be paid.",
+                                claim.id,
+                                note,
+                                self.config.currency.format(claim.paid)
+                            ))
+                        ),
+                        None => term::status!(
+                            "{}\n",
+                            term::success(format_args!("Expense claim #{} submitted.", claim.id))
+                        ),
+                    },
+                    Err(err) => println!("{}\n", term::error(format_args!("Claim not submitted: {}", err))),
+                }
+            }
+            "c" | "cancel" => {
+                let id = loop {
+                    match self.get_input("Claim #: ")?.trim().trim_start_matches('#').parse() {
+                        Ok(id) => break id,
+                        Err(_) => println!("{}", term::warning("Please enter a claim number.")),
+                    }
+                };
+                match self.payroll.cancel_expense(id) {
+                    Ok(_) => term::status!("{}\n", term::success(format_args!("Expense claim #{} withdrawn.", id))),
+                    Err(err) => println!("{}\n", term::error(format_args!("Claim not withdrawn: {}", err))),
+                }
+            }
+            "l" | "list" => {
+                let period = self.read_pay_period("Pay Period: ")?;
+                let claims = self.payroll.expense_claims_in(Some(&period));
+                if claims.is_empty() {
+                    println!("No expense claims paid in {}.\n", period);
+                } else {
+                    PayrollPresentation::print_expense_claims(&claims);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_insurance(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Insurance ==="));
+
+        if self.payroll.insurance_enrollments.plans().is_empty() {
+            println!("No insurance plans configured; add [[insurance]] plans to employee.toml.\n");
+            return Ok(());
+        }
+        PayrollPresentation::print_insurance_plans(self.payroll.insurance_enrollments.plans());
+        let action = self.get_input("[E]nroll, e[N]d cover, [L]ist an employee's enrollments, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "e" | "enroll" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let plan = self.get_input("Plan Code: ")?;
+                let tier = self.get_input("Tier: ")?;
+                let start = self.read_pay_period("From Pay Period: ")?;
+                match self.payroll.enroll_insurance(&employee_id, plan.trim(), tier.trim(), start) {
+                    Ok(enrollment) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "{} enrolled in {} ({}) from {}.",
+                            employee_id, enrollment.plan, enrollment.tier, start
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Not enrolled: {}", err))),
+                }
+            }
+            "n" | "end" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let plan = self.get_input("Plan Code: ")?;
+                let last = self.read_pay_period("Last Pay Period Covered: ")?;
+                match self.payroll.end_insurance(&employee_id, plan.trim(), last) {
+                    Ok(()) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Cover of {} ends after {}.", employee_id, last))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Cover not ended: {}", err))),
+                }
+            }
+            "l" | "list" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let enrollments = self.payroll.insurance_enrollments_of(Some(&employee_id));
+                if enrollments.is_empty() {
+                    println!("{} is not enrolled in any plan.\n", employee_id);
+                } else {
+                    PayrollPresentation::print_insurance_enrollments(&enrollments);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_equity(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Equity ==="));
+
+        let action = self.get_input("[G]rant, [L]ist an employee's grants, [V]esting in a period, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "g" | "grant" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let kind = loop {
+                    match self.get_input("Kind (rsu/option): ")?.parse::<EquityKind>() {
+                        Ok(kind) => break kind,
+                        Err(err) => println!("{}", term::warning(err)),
+                    }
+                };
+                let whole = |value: f64| value >= 0.0 && value.fract() == 0.0;
+                let months = "Please enter whole months.";
+                let units =
+                    self.read_number("Units", None, |units| units >= 1.0 && whole(units), "Please enter whole units.")?;
+                let strike = match kind {
+                    EquityKind::Option => {
+                        self.read_number("Strike Price", None, |price| price >= 0.0, "Please enter a price.")?
+                    }
+                    EquityKind::Rsu => 0.0,
+                };
+                let fair_value =
+                    self.read_number("Fair Value per Share", None, |price| price >= 0.0, "Please enter a price.")?;
+                let granted = self.read_date("Grant Date (YYYY-MM-DD): ")?;
+                let schedule = VestingSchedule {
+                    cliff_months: self.read_number("Cliff (months)", Some(12.0), whole, months)? as u32,
+                    every_months: self.read_number("Vests Every (months)", Some(3.0), whole, months)? as u32,
+                    total_months: self.read_number("Fully Vested After (months)", Some(48.0), whole, months)? as u32,
+                };
+                let units = units as u32;
+                match self.payroll.grant_equity(&employee_id, kind, units, strike, fair_value, granted, schedule) {
+                    Ok(grant) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "{} grant #{} recorded: {} vestings.",
+                            kind,
+                            grant.id,
+                            grant.vesting_events().len()
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Grant not recorded: {}", err))),
+                }
+            }
+            "l" | "list" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let grants = self.payroll.equity_grants_of(Some(&employee_id));
+                if grants.is_empty() {
+                    println!("{} has no equity grants.\n", employee_id);
+                } else {
+                    PayrollPresentation::print_equity_grants(&grants, chrono::Local::now().date_naive());
+                    println!();
+                }
+            }
+            "v" | "vesting" => {
+                let period = self.read_pay_period("Pay Period: ")?;
+                let events = self.payroll.vesting_in(&period);
+                if events.is_empty() {
+                    println!("Nothing vests in {}.\n", period);
+                } else {
+                    PayrollPresentation::print_vesting(&events);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn manage_natura(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Benefits in Kind ==="));
+
+        let action = self.get_input("[P]rovide, [E]nd, [L]ist an employee's benefits, Enter to go back: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "p" | "provide" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let kind = loop {
+                    match self.get_input("Kind (vehicle/housing/other): ")?.parse::<NaturaKind>() {
+                        Ok(kind) => break kind,
+                        Err(err) => println!("{}", term::warning(err)),
+                    }
+                };
+                let description = self.get_input("Description: ")?;
+                let by_cost = self.get_input("Value by [C]ost or [M]onthly value? [M]: ")?;
+                let positive = "Please enter a positive amount.";
+                let valuation = if by_cost.trim().eq_ignore_ascii_case("c") {
+                    NaturaValuation::Cost(self.read_number("Cost", None, |cost| cost > 0.0, positive)?)
+                } else {
+                    NaturaValuation::Monthly(self.read_number("Monthly Value", None, |value| value > 0.0, positive)?)
+                };
+                let start = self.read_pay_period("From Pay Period: ")?;
+                match self.payroll.provide_natura(&employee_id, kind, &description, valuation, start) {
+                    Ok(item) => term::status!(
+                        "{}\n",
+                        term::success(format_args!(
+                            "Benefit #{} recorded: {} a month from {}.",
+                            item.id,
+                            self.config.currency.format(item.monthly_value),
+                            start
+                        ))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Benefit not recorded: {}", err))),
+                }
+            }
+            "e" | "end" => {
+                let id = loop {
+                    match self.get_input("Benefit #: ")?.trim().trim_start_matches('#').parse() {
+                        Ok(id) => break id,
+                        Err(_) => println!("{}", term::warning("Please enter a benefit number.")),
+                    }
+                };
+                let last = self.read_pay_period("Last Pay Period: ")?;
+                match self.payroll.end_natura(id, last) {
+                    Ok(item) => term::status!(
+                        "{}\n",
+                        term::success(format_args!("Benefit #{} ends after {}.", item.id, last))
+                    ),
+                    Err(err) => println!("{}\n", term::error(format_args!("Benefit not ended: {}", err))),
+                }
+            }
+            "l" | "list" => {
+                let employee_id = self.read_employee_id("Employee ID: ", false)?;
+                let items = self.payroll.natura_of(Some(&employee_id));
+                if items.is_empty() {
+                    println!("{} has no benefits in kind.\n", employee_id);
+                } else {
+                    PayrollPresentation::print_natura(&items);
+                    println!();
+                }
+            }
+            _ => println!(),
+        }
+        Ok(())
+    }
+
+    fn read_date(&self, prompt: &str) -> Prompted<NaiveDate> {
+        loop {
+            match NaiveDate::parse_from_str(self.get_input(prompt)?.trim(), "%Y-%m-%d") {
+                Ok(date) => break Ok(date),
+                Err(_) => println!("{}", term::warning("Please enter the date as YYYY-MM-DD.")),
+            }
+        }
+    }
+
+    fn read_overtime_request_id(&self) -> Prompted<u32> {
+        loop {
+            match self.get_input("Overtime Request #: ")?.trim().trim_start_matches('#').parse() {
+                Ok(id) => break Ok(id),
+                Err(_) => println!("{}", term::warning("Please enter a request number.")),
+            }
+        }
+    }
+
+    fn read_pay_period(&self, prompt: &str) -> Prompted<PayPeriod> {
+        loop {
+            match self.get_input(prompt)?.parse::<PayPeriod>() {
+                Ok(period) => break Ok(period),
+                Err(err) => println!("{}", term::warning(err)),
+            }
+        }
+    }
+
+    fn read_change_request_id(&self) -> Prompted<u32> {
+        loop {
+            match self.get_input("Change Request #: ")?.trim().trim_start_matches('#').parse() {
+                Ok(id) => break Ok(id),
+                Err(_) => println!("{}", term::warning("Please enter a request number.")),
+            }
+        }
+    }
+
+    fn manage_pins(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Employee PINs ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered.\n");
+            return Ok(());
+        }
+        PayrollPresentation::print_pin_statuses(&self.payroll, chrono::Utc::now());
+
+        let Some(employee_id) = self.get_optional_input("Employee ID (Enter to go back): ")? else {
+            println!();
+            return Ok(());
+        };
+        if self.payroll.get_employee(&employee_id).is_none() {
+            println!("{}\n", term::error(format_args!("Employee {} is not registered.", employee_id)));
+            return Ok(());
+        }
+        let action = self.get_input("[I]ssue temporary PIN, [U]nlock, [R]evoke, Enter to cancel: ")?;
+        match action.trim().to_lowercase().as_str() {
+            "i" | "issue" => match self.payroll.issue_pin(&employee_id) {
+                Ok(pin) => term::status!(
+                    "{}\n",
+                    term::success(format_args!(
+                        "Temporary PIN for {}: {} (must be changed at first sign-in)",
+                        employee_id, pin
+                    ))
+                ),
+                Err(err) => println!("{}\n", term::error(format_args!("Error issuing PIN: {}", err))),
+            },
+            "u" | "unlock" => match self.payroll.unlock_pin(&employee_id) {
+                Ok(_) => term::status!("{}\n", term::success(format_args!("Sign-in unlocked for {}.", employee_id))),
+                Err(err) => println!("{}\n", term::error(format_args!("Error unlocking: {}", err))),
+            },
+            "r" | "revoke" => {
+                if self.payroll.revoke_pin(&employee_id) {
+                    term::status!("{}\n", term::success(format_args!("PIN revoked for {}.", employee_id)));
+                } else {
+                    println!("Employee {} has no PIN.\n", employee_id);
+                }
+            }
+            _ => println!("No changes made.\n"),
+        }
+        Ok(())
+    }
+
+    fn read_filter<T: FromStr<Err = String>>(&self, prompt: &str) -> Prompted<Option<T>> {
+        loop {
+            match self.get_optional_input(prompt)? {
+                None => break Ok(None),
+                Some(input) => match input.parse() {
+                    Ok(value) => break Ok(Some(value)),
+                    Err(err) => println!("{}", term::warning(err)),
+                },
+            }
+        }
+    }
+
+    fn read_salary_filter(&self, prompt: &str) -> Prompted<Option<f64>> {
+        loop {
+            match self.get_optional_number_input(prompt)? {
+                None => break Ok(None),
+                Some(Ok(amount)) if amount >= 0.0 => break Ok(Some(amount)),
+                _ => println!("{}", term::warning("Please enter a valid positive number.")),
+            }
+        }
+    }
+
+    fn list_employees(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Employees ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered.\n");
+            return Ok(());
+        }
+
+        let mut query = EmployeeQuery::new(&self.payroll);
+        if let Some(kind) = self.read_filter::<EmployeeKind>("Type (fulltime/contract, Enter for all): ")? {
+            query = query.employee_kind(kind);
+        }
+        let status = self.read_filter::<EmployeeStatus>("Status (active/archived/terminated, Enter for all): ")?;
+        if let Some(status) = status {
+            query = query.status(status);
+        }
+        if let Some(department) = self.get_optional_input("Department (Enter for all): ")? {
+            query = query.department(&department);
+        }
+        if let Some(amount) = self.read_salary_filter("Minimum monthly gross (Enter for no limit): ")? {
+            query = query.min_salary(amount);
+        }
+        if let Some(amount) = self.read_salary_filter("Maximum monthly gross (Enter for no limit): ")? {
+            query = query.max_salary(amount);
+        }
+
+        let employees = query.run();
+        if employees.is_empty() {
+            println!("No employees match the filters.\n");
+            return Ok(());
+        }
+        println!();
+        PayrollPresentation::print_employee_list(&employees, &self.payroll.settings);
+        println!();
+        Ok(())
+    }
+
+    fn show_employee(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Show Employee ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered.\n");
+            return Ok(());
+        }
+
+        let employee_id = self.read_employee_id("Enter Employee ID: ", false)?;
+        let Some(employee) = self.payroll.get_employee(&employee_id) else {
+            println!("{}\n", term::error(format_args!("Employee {} is not registered.", employee_id)));
+            return Ok(());
+        };
+        let pay_period = PayPeriod::from_date(Local::now().date_naive()).to_string();
+        match self.payroll.preview_payroll(&employee_id, &pay_period) {
+            Ok(preview) => {
+                PayrollPresentation::print_employee_detail(employee, &preview);
+                println!();
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Error calculating preview: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn remove_employee(&mut self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Remove Employee ==="));
+
+        if self.payroll.employees.is_empty() {
+            println!("No employees registered.\n");
+            return Ok(());
+        }
+
+        let employee_id = self.read_employee_id("Enter Employee ID: ", false)?;
+        let Some(employee) = self.payroll.get_employee(&employee_id).cloned() else {
+            println!("{}\n", term::error(format_args!("Employee {} is not registered.", employee_id)));
+            return Ok(());
+        };
+        let records = self.payroll.get_employee_payroll(&employee_id).len();
+        let force = if records == 0 {
+            if !self.confirm(&format!("Remove employee {}?", describe_employee(&employee)))? {
+                println!("Employee not removed.\n");
+                return Ok(());
+            }
+            false
+        } else {
+            println!(
+                "{}",
+                term::warning(format_args!("{} has {} payroll records.", describe_employee(&employee), records))
+            );
+            let archived = employee.as_employee().profile().status == EmployeeStatus::Archived;
+            if !archived && self.confirm("Archive the employee instead, keeping the history linked?")? {
+                if let Err(err) = self.payroll.archive_employee(&employee_id) {
+                    println!("{}\n", term::error(format_args!("Error archiving employee: {}", err)));
+                    return Ok(());
+                }
+                self.undo_stack.push(UndoAction::ReplaceEmployee {
+                    previous: Box::new(employee),
+                });
+                term::status!("{}\n", term::success(format_args!("Employee {} archived.", employee_id)));
+                return Ok(());
+            }
+            if !self.confirm("Remove anyway? Existing payroll records keep their own copy of the employee")? {
+                println!("Employee not removed.\n");
+                return Ok(());
+            }
+            true
+        };
+
+        match self.payroll.remove_employee(&employee_id, force) {
+            Ok(previous) => {
+                self.undo_stack.push(UndoAction::RemoveEmployee {
+                    previous: Box::new(previous),
+                });
+                term::status!("{}\n", term::success(format_args!("Employee {} removed.", employee_id)));
+            }
+            Err(err) => println!("{}\n", term::error(format_args!("Error removing employee: {}", err))),
+        }
+        Ok(())
+    }
+
+    fn search(&self) -> Prompted<()> {
+        term::status!("\n{}", term::heading("=== Search Employees and Records ==="));
+
+        let query = self.get_input("Search (ID, name or period, e.g. \"E00\" or \"sept 24\"): ")?;
+        let results = self.payroll.search(&query);
+        if results.is_empty() {
+            println!("No matches found.\n");
+            return Ok(());
+        }
+
+        if !results.employees.is_empty() {
+            println!("\nEmployees:");
+            for found in results.employees.iter().take(10) {
+                let employee = found.employee.as_employee();
+                println!(
+                    "  {:<10} {:<24} {}",
+                    employee.employee_id(),
+                    employee.profile().name,
+                    employee.employee_type()
+                );
+            }
+        }
+
+        if !results.records.is_empty() {
+            println!("\nPayroll Records:");
+            for found in results.records.iter().take(20) {
+                let employee = found.record.employee.as_employee();
+                println!(
+                    "  {:<10} {:<24} {:<16} {:>18}{}",
+                    employee.employee_id(),
+                    employee.profile().name,
+                    found.record.pay_period,
+                    self.config.currency.format(found.record.net_salary),
+                    if found.record.voided { " (VOID)" } else { "" }
+                );
+            }
+            if results.records.len() > 20 {
+                println!("  ... {} more", results.records.len() - 20);
+            }
+        }
+        println!();
+        Ok(())
+    }
+}
+/// Restricted session for one employee. After signing in with their ID and PIN they can only see
+/// their own payslips and year-to-date totals; the session ends after too many failed sign-ins.
+pub fn run_self_service(payroll: &mut Payroll) -> Result<(), PayrollError> {
+    term::status!("{}\n", term::heading("=== Employee Self-Service ==="));
+
+    let mut attempts = 0;
+    let (employee_id, pin, must_change) = loop {
+        let Ok(employee_id) = read_input("Employee ID: ") else {
+            return Ok(());
+        };
+        let Ok(pin) = read_pin_input("PIN: ") else {
+            return Ok(());
+        };
+        match payroll.sign_in(&employee_id, pin.trim()) {
+            Ok(session) => break (session.employee_id().to_string(), pin.trim().to_string(), session.must_change_pin()),
+            Err(err @ PayrollError::AccountLocked(_)) => return Err(err),
+            Err(err) => {
+                attempts += 1;
+                if attempts >= MAX_FAILED_ATTEMPTS {
+                    return Err(err);
+                }
+                println!("{}\n", term::warning(format_args!("Sign-in failed: {}.", err)));
+            }
+        }
+    };
+    if must_change {
+        term::status!("\nYour PIN was issued by an administrator. Choose your own PIN to continue.");
+        if change_own_pin(payroll, &employee_id, Some(&pin)).is_err() {
+            return Ok(());
+        }
+    }
+    let name = payroll
+        .get_employee(&employee_id)
+        .map(|employee| employee.as_employee().profile().name.clone())
+        .unwrap_or_default();
+    term::status!("\nSigned in as {} {}\n", employee_id, name);
+
+    loop {
+        if !term::is_quiet() {
+            println!("{}", term::heading("=== MY PAYROLL ==="));
+            println!("1. My Payslips");
+            println!("2. View Payslip");
+            println!("3. Year-to-Date Summary");
+            println!("4. Change PIN");
+            println!("5. Sign Out");
+            println!();
+        }
+        let Ok(choice) = read_input("Enter your choice: ") else {
+            break;
+        };
+        let result = match choice.trim() {
+            "1" => {
+                show_own_payslips(&SelfService::new(payroll, &employee_id));
+                Ok(())
+            }
+            "2" => view_own_payslip(&SelfService::new(payroll, &employee_id)),
+            "3" => show_own_year_to_date(&SelfService::new(payroll, &employee_id)),
+            "4" => change_own_pin(payroll, &employee_id, None),
+            "5" => break,
+            _ => {
+                println!("{}\n", term::warning("Invalid choice. Please try again."));
+                Ok(())
+            }
+        };
+        if result.is_err() {
+            break;
+        }
+    }
+    term::status!("Signed out.");
+    Ok(())
+}
+
+/// Asks for a new PIN (and the current one unless it is already known) until the change succeeds.
+/// Too many wrong current PINs lock the account and end the session.
+fn change_own_pin(payroll: &mut Payroll, employee_id: &str, current: Option<&str>) -> Prompted<()> {
+    let current = match current {
+        Some(current) => current.to_string(),
+        None => read_pin_input("Current PIN: ")?.trim().to_string(),
+    };
+    loop {
+        let new_pin = read_pin_input("New PIN: ")?.trim().to_string();
+        if read_pin_input("Repeat PIN: ")?.trim() != new_pin {
+            println!("{}\n", term::warning("The PINs do not match."));
+            continue;
+        }
+        match payroll.change_pin(employee_id, &current, &new_pin) {
+            Ok(()) => {
+                term::status!("{}\n", term::success("PIN changed."));
+                return Ok(());
+            }
+            Err(err @ PayrollError::InvalidPin(_)) => println!("{}\n", term::warning(format_args!("{}.", err))),
+            Err(err) => {
+                println!("{}\n", term::error(format_args!("Could not change PIN: {}", err)));
+                return match err {
+                    PayrollError::AccountLocked(_) => Err(Cancelled),
+                    _ => Ok(()),
+                };
+            }
+        }
+    }
+}
+
+fn show_own_payslips(session: &SelfService) {
+    let records = session.payslips();
+    if records.is_empty() {
+        println!("No payslips yet.\n");
+        return;
+    }
+    PayrollPresentation::print_employee_history(session.employee_id(), &records);
+    println!();
+}
+
+fn view_own_payslip(session: &SelfService) -> Prompted<()> {
+    let pay_period = read_input("Pay Period: ")?.trim().to_string();
+    match session.payslip(&pay_period) {
+        Some(record) => {
+            PayrollPresentation::print_payroll_summary(record);
+            println!();
+        }
+        None => println!("No payslip for {}.\n", pay_period),
+    }
+    Ok(())
+}
+
+fn show_own_year_to_date(session: &SelfService) -> Prompted<()> {
+    let current = Local::now().year();
+    let year = match read_input(&format!("Year (default: {}): ", current))?.trim() {
+        "" => current,
+        year => match year.parse::<i32>() {
+            Ok(year) => year,
+            Err(_) => {
+                println!("{}\n", term::warning("Please enter a valid year."));
+                return Ok(());
+            }
+        },
+    };
+    PayrollPresentation::print_year_to_date(&session.year_to_date(year));
+    println!();
+    Ok(())
+}
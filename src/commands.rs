@@ -0,0 +1,3630 @@
+use crate::accounting::{AccountCodes, JournalFormat};
+use crate::attendance::{read_attendance_csv, AttendanceEntry, AttendanceRow};
+use crate::batch::{self, RejectedRow};
+use crate::bpjs::BpjsRates;
+use crate::budget::{read_budget_csv, write_variance_csv};
+use crate::calendar;
+use crate::cli::CLI;
+use crate::config::Config;
+use crate::disbursement::EWallet;
+use crate::equity::{EquityKind, VestingSchedule};
+use crate::error::{ConfigError, PayrollError};
+use crate::demo::{self, DemoOptions};
+use crate::employee::{EmployeeStatus, PROBATION_REVIEW_DAYS};
+use crate::export::register::RegisterCsvWriter;
+use crate::export::{org_chart, write_cost_trend_csv, write_department_totals_csv};
+use crate::ledger::PayrollLedger;
+use crate::natura::{NaturaKind, NaturaValuation};
+use crate::onboarding::OnboardingTask;
+use crate::payroll::{EmployeeData, Payroll};
+use crate::presentation::PayrollPresentation;
+use crate::payslip::{self, payslip_file_name, render_html, PayslipSeal, VerificationCode};
+use crate::period::PayPeriod;
+use crate::query::{EmployeeKind, EmployeeQuery};
+use crate::search::EmployeeIdMatch;
+use crate::severance;
+use crate::storage::{JsonFileStorage, Storage};
+use crate::tax::NonResidency;
+use crate::term::{self, Verbosity};
+use crate::travel::DestinationTier;
+use anstream::{eprintln, println};
+use chrono::NaiveDate;
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::env::{CompleteEnv, Shells};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const BIN_NAME: &str = "employee-management";
+
+#[derive(Debug, Parser)]
+#[command(name = BIN_NAME, version, about = "Employee management and payroll processing")]
+pub struct Cli {
+    /// Disable colored output
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    /// Print only results and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Show calculation traces and storage operations (-vv for more detail)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// Print errors as text or as one JSON object per line on stderr
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the REST API server
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(default_value = "127.0.0.1:8080")]
+        addr: String,
+        data_file: Option<PathBuf>,
+        /// Keep the payroll in this PostgreSQL database instead, e.g. postgres://payroll@db/payroll
+        #[arg(long, conflicts_with = "data_file")]
+        database: Option<String>,
+        /// Cache year-to-date totals and period reports in this Redis, e.g. redis://127.0.0.1/
+        #[arg(long)]
+        cache: Option<String>,
+    },
+    /// Generate demo employees and payroll history
+    #[command(long_flag = "seed-demo")]
+    SeedDemo {
+        #[arg(default_value_t = 25)]
+        employees: usize,
+        #[arg(default_value_t = 6)]
+        months: u32,
+        file: Option<PathBuf>,
+    },
+    /// Convert a data file between JSON and the compressed binary snapshot format
+    #[cfg(feature = "snapshot")]
+    Convert {
+        input: PathBuf,
+        /// File to write (default: convert the input in place)
+        output: Option<PathBuf>,
+        /// json or snapshot
+        #[arg(long)]
+        to: crate::storage::DataFormat,
+    },
+    /// Copy a data file into a PostgreSQL database that holds no payroll yet
+    #[cfg(feature = "postgres")]
+    DbImport {
+        file: PathBuf,
+        /// e.g. postgres://payroll@db/payroll
+        #[arg(long)]
+        database: String,
+    },
+    /// Print the JSON Schema of a file or API payload (employee, payroll-record, ...), list them, or
+    /// write them all to a directory
+    Schema {
+        name: Option<String>,
+        /// Write NAME.schema.json files to this directory instead
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Employee commands
+    #[command(subcommand)]
+    Employee(EmployeeCommand),
+    /// Payroll commands
+    #[command(subcommand)]
+    Payroll(PayrollCommand),
+    /// Performance review cycles and the bonuses they earn
+    #[command(subcommand)]
+    Review(ReviewCommand),
+    /// Contract employees' monthly timesheets and their approval
+    #[command(subcommand)]
+    Timesheet(TimesheetCommand),
+    /// Overtime requests and the records whose overtime went beyond policy
+    #[command(subcommand)]
+    Overtime(OvertimeCommand),
+    /// Daily attendance and the allowances and penalties it earns
+    #[command(subcommand)]
+    Attendance(AttendanceCommand),
+    /// Business trips and the per diems paid for them
+    #[command(subcommand)]
+    Travel(TravelCommand),
+    /// Reimbursement claims, capped per grade and category
+    #[command(subcommand)]
+    Expense(ExpenseCommand),
+    /// Private insurance plans, enrollments and the premiums deducted for them
+    #[command(subcommand)]
+    Insurance(InsuranceCommand),
+    /// Stock option and RSU grants, taxed as income when they vest
+    #[command(subcommand)]
+    Equity(EquityCommand),
+    /// Benefits in kind (natura) such as company cars and housing, taxed above their exemption
+    #[command(subcommand)]
+    Natura(NaturaCommand),
+    /// Check a payslip's QR verification code (or its verification hash) against the payroll history;
+    /// signatures are checked with the key in PAYSLIP_SIGNING_KEY
+    VerifyPayslip {
+        code: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Let an employee sign in with their ID and PIN to view their own payslips
+    SelfService {
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Print a shell completion script
+    Completions { shell: CompletionShell },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EmployeeCommand {
+    /// List registered employees
+    List {
+        #[command(flatten)]
+        filter: EmployeeFilter,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show an employee's profile and a preview of this month's pay
+    Show {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Pay period to preview (default: the current month)
+        #[arg(long)]
+        period: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Remove an employee, or archive one that has payroll history
+    Remove {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Mark the employee archived instead of removing them
+        #[arg(long, conflicts_with = "force")]
+        archive: bool,
+        /// Remove even when payroll records exist for the employee
+        #[arg(long)]
+        force: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Terminate every employee listed in a CSV at once (e.g. a layoff), paying their final month with
+    /// severance and marking them terminated; nothing is changed if any row is rejected
+    Terminate {
+        /// CSV with employee_id, hired, last_day and reason columns, plus optional work_hours and compensation
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long)]
+        data: Option<PathBuf>,
+        /// Final payroll records (default: <input>_results.csv)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Rejected rows report (default: <input>_errors.csv)
+        #[arg(long)]
+        errors: Option<PathBuf>,
+        /// Directory the termination payslips are written to (default: <input>_payslips)
+        #[arg(long)]
+        payslips: Option<PathBuf>,
+    },
+    /// Sync names, departments and status from an HR system's REST endpoint (a bearer token can be
+    /// given in HRIS_TOKEN), creating employees it lists with a base salary or hourly rate
+    Sync {
+        /// URL returning the employees as JSON
+        #[arg(long)]
+        url: String,
+        /// Archive active employees the HR system does not list
+        #[arg(long)]
+        deactivate_missing: bool,
+        /// Report the changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Set an employee's self-service PIN (prompted, or read from stdin)
+    SetPin {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Issue a temporary self-service PIN (also resets a forgotten one); it must be changed at first sign-in
+    IssuePin {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Clear failed sign-ins and lift a self-service lockout
+    UnlockPin {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Remove an employee's self-service PIN
+    RevokePin {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List each employee's self-service PIN status
+    Pins {
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show onboarding checklists: one employee's, or every one with open tasks
+    Onboarding {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Mark an onboarding task (bpjs, bank, npwp or contract) done
+    CompleteTask {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        task: OnboardingTask,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Set who an employee reports to, or clear it when no manager is given
+    SetManager {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        manager_id: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Mark a contractor as resident abroad for tax, withheld PPh 26 at the default or treaty rate
+    SetResidency {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Two-letter code of the country they are resident in
+        #[arg(long, required_unless_present = "resident")]
+        country: Option<String>,
+        /// PPh 26 rate as a fraction (default: the country's treaty rate, or tax.pph26_rate)
+        #[arg(long, requires = "country")]
+        rate: Option<f64>,
+        /// Mark them resident in Indonesia again
+        #[arg(long, conflicts_with = "country")]
+        resident: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Tax a full-time employee under a configured tax regime (omit the regime for the company's)
+    SetTaxRegime {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        regime: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show an employee's reporting chain and everyone reporting to them, directly or indirectly
+    Reports {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Print the organization chart, or export it (.dot for Graphviz, CSV otherwise)
+    OrgChart {
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Put an employee on probation until a date (YYYY-MM-DD)
+    StartProbation {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        until: NaiveDate,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Convert an employee on probation to permanent, optionally revising their base salary or hourly rate
+    Confirm {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        salary: Option<f64>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List employees whose probation ends within the given number of days (or already has)
+    ProbationDue {
+        #[arg(long, default_value_t = PROBATION_REVIEW_DAYS)]
+        within: i64,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Propose a new base salary (hourly rate for contract employees) and grade from a pay period on
+    SalaryChange {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        rate: f64,
+        #[arg(long)]
+        grade: Option<String>,
+        /// First pay period the change applies to (e.g. 2027-01)
+        #[arg(long)]
+        effective: PayPeriod,
+        /// Who has to approve the change
+        #[arg(long)]
+        approver: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Approve a salary change request, writing it into the salary history
+    ApproveChange {
+        request_id: u32,
+        #[arg(long)]
+        approver: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Reject a salary change request
+    RejectChange {
+        request_id: u32,
+        #[arg(long)]
+        approver: String,
+        #[arg(long)]
+        reason: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List salary change requests waiting for approval
+    SalaryChanges {
+        /// Include approved and rejected requests
+        #[arg(long)]
+        all: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show an employee's approved salary changes
+    SalaryHistory {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show an employee's payroll history
+    History {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Print the full summary of each record after the table
+        #[arg(long)]
+        full: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Export everything held on an employee as JSON, to answer a data-subject access request
+    ExportData {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Where to write the export (default: <employee_id>_data.json)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Pseudonymize a terminated employee once retention.pseudonymize_after_years have passed since
+    /// their last day, keeping their pay history under a random ID
+    Pseudonymize {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Check the employee can be pseudonymized without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReviewCommand {
+    /// Start a review cycle, e.g. "2026 Annual"
+    Start {
+        name: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Record an employee's score (1 to 5) in an open cycle, replacing any earlier one
+    Score {
+        cycle: String,
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        score: f64,
+        #[arg(long)]
+        reviewer: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Close a cycle so its scores can no longer change
+    Close {
+        cycle: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List review cycles with their number of reviews and average score
+    List {
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Compute each reviewed employee's bonus from their score and the [bonus] configuration
+    Bonus {
+        cycle: String,
+        /// Also write the bonuses to a CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TimesheetCommand {
+    /// Submit a contract employee's hours for a pay period, replacing an unapproved submission
+    Submit {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Pay period the hours were worked in (e.g. 2026-10)
+        #[arg(long)]
+        period: PayPeriod,
+        #[arg(long)]
+        hours: f64,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Approve a submitted timesheet; its hours are then paid when the period is processed
+    Approve {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        period: PayPeriod,
+        /// Employee ID of a manager above the employee in the reporting chain
+        #[arg(long, add = ArgValueCompleter::new(complete_employee_id))]
+        approver: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Reject a submitted timesheet so the employee resubmits it
+    Reject {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        period: PayPeriod,
+        #[arg(long, add = ArgValueCompleter::new(complete_employee_id))]
+        approver: String,
+        #[arg(long)]
+        reason: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List timesheets waiting for approval, or every timesheet of a period
+    List {
+        #[arg(long)]
+        period: Option<PayPeriod>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OvertimeCommand {
+    /// Ask for overtime hours in a pay period for a full-time employee (prints the request number)
+    Request {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        period: PayPeriod,
+        #[arg(long)]
+        hours: f64,
+        #[arg(long, default_value = "")]
+        reason: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Approve an overtime request
+    Approve {
+        request_id: u32,
+        /// Employee ID of a manager above the employee in the reporting chain
+        #[arg(long, add = ArgValueCompleter::new(complete_employee_id))]
+        approver: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Reject an overtime request
+    Reject {
+        request_id: u32,
+        #[arg(long, add = ArgValueCompleter::new(complete_employee_id))]
+        approver: String,
+        #[arg(long)]
+        reason: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List overtime requests waiting for a decision
+    List {
+        /// Include approved and rejected requests
+        #[arg(long)]
+        all: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List a period's records with overtime beyond the monthly cap or the approved hours
+    Exceptions {
+        period: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AttendanceCommand {
+    /// Record a day of attendance, replacing any earlier entry for that date
+    Record {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// YYYY-MM-DD
+        #[arg(long)]
+        date: NaiveDate,
+        /// Minutes late clocking in
+        #[arg(long, default_value_t = 0)]
+        late: u32,
+        /// Minutes left before the end of the shift
+        #[arg(long, default_value_t = 0)]
+        early: u32,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Import a time-clock CSV with employee_id and date columns, plus optional late_minutes and
+    /// early_leave_minutes
+    Import {
+        input: PathBuf,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show each employee's days, late arrivals, early leaves, allowances and penalties in a pay period
+    Summary {
+        period: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TravelCommand {
+    /// Record a business trip, paid at the current per-diem rate of its tier (prints the record number)
+    Record {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        destination: String,
+        /// local, domestic or international
+        #[arg(long)]
+        tier: DestinationTier,
+        /// First day of the trip (YYYY-MM-DD)
+        #[arg(long)]
+        start: NaiveDate,
+        #[arg(long)]
+        days: u32,
+        /// Pay period to pay the per diem in (default: the month the trip starts)
+        #[arg(long)]
+        period: Option<PayPeriod>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Remove a travel record whose pay period is still open
+    Cancel {
+        record_id: u32,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List travel records with their per diem and its taxable part
+    List {
+        #[arg(long)]
+        period: Option<PayPeriod>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExpenseCommand {
+    /// Submit a reimbursement claim to be paid in a pay period (prints the claim number)
+    Submit {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// e.g. communication, medical, fuel
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        amount: f64,
+        #[arg(long)]
+        period: PayPeriod,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Withdraw a claim whose pay period is still open
+    Cancel {
+        claim_id: u32,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List claims with the amount claimed and paid
+    List {
+        #[arg(long)]
+        period: Option<PayPeriod>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum InsuranceCommand {
+    /// List the configured plans with each tier's employee and employer premium
+    Plans,
+    /// Enroll an employee in a plan tier from a pay period on, ending an earlier tier of the same plan
+    Enroll {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// Plan code
+        #[arg(long)]
+        plan: String,
+        #[arg(long)]
+        tier: String,
+        /// First pay period the premium is deducted in
+        #[arg(long)]
+        from: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// End an employee's cover under a plan after a pay period
+    End {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        #[arg(long)]
+        plan: String,
+        /// Last pay period the premium is deducted in
+        #[arg(long)]
+        last: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List enrollments, of one employee or of everyone
+    List {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EquityCommand {
+    /// Record a stock option or RSU grant with its vesting schedule (prints the grant number)
+    Grant {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// rsu or option
+        #[arg(long)]
+        kind: EquityKind,
+        #[arg(long)]
+        units: u32,
+        /// Price per share an option holder pays
+        #[arg(long, default_value_t = 0.0)]
+        strike: f64,
+        /// Value of one share, used for the income when units vest
+        #[arg(long)]
+        fair_value: f64,
+        /// Grant date (YYYY-MM-DD); vesting is counted in months from it
+        #[arg(long)]
+        granted: NaiveDate,
+        /// Months before anything vests
+        #[arg(long, default_value_t = 12)]
+        cliff: u32,
+        /// Months between vestings after the cliff
+        #[arg(long, default_value_t = 3)]
+        every: u32,
+        /// Months until the grant is fully vested
+        #[arg(long, default_value_t = 48)]
+        over: u32,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Update the share value later vestings are taxed at
+    Revalue {
+        grant_id: u32,
+        #[arg(long)]
+        fair_value: f64,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Forfeit the units of a grant not vested by a date
+    Forfeit {
+        grant_id: u32,
+        /// YYYY-MM-DD
+        #[arg(long)]
+        on: NaiveDate,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List grants with the units vested so far, of one employee or of everyone
+    List {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List the vestings of a pay period and the income they create
+    Vesting {
+        period: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NaturaCommand {
+    /// Record a benefit in kind provided from a pay period on (prints the benefit number)
+    Provide {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: String,
+        /// vehicle, housing or other
+        #[arg(long)]
+        kind: NaturaKind,
+        #[arg(long)]
+        description: String,
+        /// What the benefit is worth a month, e.g. the rent or lease paid for it
+        #[arg(long, required_unless_present = "cost")]
+        value: Option<f64>,
+        /// What the company paid for the item, valued with the kind's annual rate
+        #[arg(long, conflicts_with = "value")]
+        cost: Option<f64>,
+        /// First pay period the benefit is taxed in
+        #[arg(long)]
+        from: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Stop a benefit in kind after a pay period
+    End {
+        item_id: u32,
+        /// Last pay period the benefit is taxed in
+        #[arg(long)]
+        last: PayPeriod,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// List benefits in kind, of one employee or of everyone
+    List {
+        #[arg(add = ArgValueCompleter::new(complete_employee_id))]
+        employee_id: Option<String>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct EmployeeFilter {
+    /// fulltime or contract
+    #[arg(long = "type")]
+    pub kind: Option<EmployeeKind>,
+    /// active, probation, archived or terminated
+    #[arg(long)]
+    pub status: Option<EmployeeStatus>,
+    #[arg(long)]
+    pub department: Option<String>,
+    /// Only the direct reports of this employee
+    #[arg(long, add = ArgValueCompleter::new(complete_employee_id))]
+    pub manager: Option<String>,
+    /// Minimum monthly gross
+    #[arg(long)]
+    pub min_salary: Option<f64>,
+    /// Maximum monthly gross
+    #[arg(long)]
+    pub max_salary: Option<f64>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PayrollCommand {
+    /// Process a period for every employee listed in an hours CSV
+    ProcessBatch {
+        /// CSV with employee_id and work_hours columns, plus optional allowance, bonus and deduction
+        #[arg(long)]
+        input: PathBuf,
+        /// Pay period to process, e.g. "2024-09" or "September 2024"
+        #[arg(long)]
+        period: String,
+        #[arg(long)]
+        data: Option<PathBuf>,
+        /// Results file (default: <input>_results.csv)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Rejected rows report (default: <input>_errors.csv)
+        #[arg(long)]
+        errors: Option<PathBuf>,
+    },
+    /// Print a run's payroll register, or export it to CSV or XLSX
+    Register {
+        run_id: u32,
+        /// Write the register to this file instead (.xlsx for Excel, CSV otherwise)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Export a run's journal entries as CSV, in this program's layout or for import into QuickBooks
+    /// Online or Xero
+    Journal {
+        run_id: u32,
+        /// generic, quickbooks or xero
+        #[arg(long, default_value_t = JournalFormat::Generic)]
+        format: JournalFormat,
+        /// Output file (default: journal_run_<run_id>.csv)
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Bank transfers paying out an approved run, following each employee's salary split
+    BankFile {
+        run_id: u32,
+        /// Also write the transfers to this CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// E-wallet payouts of an approved run in the provider's bulk-payment template
+    EwalletFile {
+        run_id: u32,
+        /// gopay, ovo or dana
+        #[arg(long)]
+        provider: EWallet,
+        /// Write the bulk-payment CSV to this file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Show the payment batch a run was submitted as, with the result of each transfer
+    Disbursement {
+        run_id: u32,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Summarize the PPh 21, BPJS and insurance amounts a run leaves to remit, with due dates
+    Remittance {
+        run_id: u32,
+        /// Also write the summary to this CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Write an iCalendar file with each month's pay date and PPh 21 and BPJS deadlines
+    Calendar {
+        /// First pay period, e.g. "2026-01" (default: last month)
+        #[arg(long)]
+        from: Option<PayPeriod>,
+        /// Last pay period (default: eleven months after the current one)
+        #[arg(long)]
+        to: Option<PayPeriod>,
+        /// Output file (default: payroll_calendar.ics)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Headcount, gross, employer cost and average salary per month
+    Trend {
+        /// First pay period, e.g. "2024-01"
+        #[arg(long)]
+        from: String,
+        /// Last pay period (default: the current month)
+        #[arg(long)]
+        to: Option<String>,
+        /// Also write the months to this CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Headcount, gross, deductions and net pay per department and month
+    Departments {
+        /// First pay period, e.g. "2024-01"
+        #[arg(long)]
+        from: String,
+        /// Last pay period (default: the current month)
+        #[arg(long)]
+        to: Option<String>,
+        /// Also write the totals to this CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Compare a department budget with the actual payroll cost and flag overspending
+    Budget {
+        /// CSV with department, period and amount columns
+        #[arg(long)]
+        budget: PathBuf,
+        /// Also write the variance report to this CSV file
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Export every payroll record, voided ones flagged, to a Parquet file for DuckDB, Spark and the like
+    #[cfg(feature = "parquet")]
+    Parquet {
+        output: PathBuf,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Print min/max/median/percentiles of gross and net pay
+    Stats {
+        /// Only this pay period
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        period: Option<String>,
+        /// First pay period of a range
+        #[arg(long)]
+        from: Option<String>,
+        /// Last pay period of a range
+        #[arg(long)]
+        to: Option<String>,
+        /// fulltime or contract
+        #[arg(long = "type")]
+        kind: Option<EmployeeKind>,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Check the tax table and every record against the payroll invariants (net within gross, totals
+    /// adding up, tax never falling as income rises)
+    SelfCheck {
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Recompute every record with the calculation context it was stored with and report amounts
+    /// that no longer match
+    Verify {
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Replace the records of pay periods older than retention.purge_records_after_years with
+    /// per-period summaries, removing their runs, timesheets, attendance, overtime, travel and expenses
+    Purge {
+        /// List what would be purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        data: Option<PathBuf>,
+    },
+    /// Watch a directory and process every hours CSV dropped into it
+    Watch {
+        dir: PathBuf,
+        #[command(flatten)]
+        options: WatchOptions,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct WatchOptions {
+    /// Where results and error reports are written (default: <dir>/output)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+    /// Pay period for files whose name has no YYYY-MM period
+    #[arg(long)]
+    pub period: Option<String>,
+    #[arg(long)]
+    pub data: Option<PathBuf>,
+    /// Seconds between directory scans
+    #[arg(long, default_value_t = 5)]
+    pub interval: u64,
+    /// Process the files already present and exit
+    #[arg(long)]
+    pub once: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+/// Failure classes of subcommands. The exit codes are documented in the README; keep them stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    General,
+    Usage,
+    Validation,
+    Storage,
+    DuplicatePeriod,
+    NotFound,
+    Unauthorized,
+    Conflict,
+}
+
+impl FailureKind {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FailureKind::General => 1,
+            FailureKind::Usage => 2,
+            FailureKind::Validation => 3,
+            FailureKind::Storage => 4,
+            FailureKind::DuplicatePeriod => 5,
+            FailureKind::NotFound => 6,
+            FailureKind::Unauthorized => 7,
+            FailureKind::Conflict => 8,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::General => "general",
+            FailureKind::Usage => "usage",
+            FailureKind::Validation => "validation",
+            FailureKind::Storage => "storage",
+            FailureKind::DuplicatePeriod => "duplicate_period",
+            FailureKind::NotFound => "not_found",
+            FailureKind::Unauthorized => "unauthorized",
+            FailureKind::Conflict => "conflict",
+        }
+    }
+}
+
+impl From<&ConfigError> for FailureKind {
+    fn from(err: &ConfigError) -> Self {
+        match err {
+            ConfigError::Io(_) => FailureKind::Storage,
+            ConfigError::Parse(_) => FailureKind::Validation,
+        }
+    }
+}
+
+impl From<&PayrollError> for FailureKind {
+    fn from(err: &PayrollError) -> Self {
+        match err {
+            PayrollError::PeriodClosed(_) | PayrollError::AlreadyProcessed(..) => FailureKind::DuplicatePeriod,
+            PayrollError::PeriodNotClosed(_)
+            | PayrollError::RecordVoided(_)
+            | PayrollError::RecordNotVoided(_)
+            | PayrollError::RunNotApproved(_)
+            | PayrollError::RunAlreadyApproved(_)
+            | PayrollError::RunAlreadyDisbursed(_)
+            | PayrollError::EmployeeHasHistory(..)
+            | PayrollError::AlreadyPseudonymized(_)
+            | PayrollError::ChangeRequestDecided(_)
+            | PayrollError::TimesheetApproved(..)
+            | PayrollError::TimesheetNotApproved(..)
+            | PayrollError::OvertimeRequestDecided(_)
+            | PayrollError::ReviewCycleExists(_)
+            | PayrollError::ReviewCycleClosed(_) => FailureKind::Conflict,
+            PayrollError::Disbursement(_) | PayrollError::EmployeeSource(_) => FailureKind::General,
+            PayrollError::RecordNotFound(_)
+            | PayrollError::RunNotFound(_)
+            | PayrollError::NoRecordsInPeriod(_)
+            | PayrollError::EmployeeNotFound(_)
+            | PayrollError::NoOnboardingChecklist(_)
+            | PayrollError::ChangeRequestNotFound(_)
+            | PayrollError::TimesheetNotFound(..)
+            | PayrollError::OvertimeRequestNotFound(_)
+            | PayrollError::TravelNotFound(_)
+            | PayrollError::ExpenseClaimNotFound(_)
+            | PayrollError::NotEnrolled(..)
+            | PayrollError::EquityGrantNotFound(_)
+            | PayrollError::NaturaNotFound(_)
+            | PayrollError::ReviewCycleNotFound(_) => FailureKind::NotFound,
+            PayrollError::InvalidCredentials
+            | PayrollError::AccountLocked(_)
+            | PayrollError::PinChangeRequired
+            | PayrollError::NotApprover(..)
+            | PayrollError::NotManager(..) => FailureKind::Unauthorized,
+            _ => FailureKind::Validation,
+        }
+    }
+}
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn new(kind: FailureKind, message: impl fmt::Display) -> Self {
+        Self {
+            kind,
+            message: message.to_string(),
+        }
+    }
+
+    /// Writes the error to stderr in the selected `--error-format` and returns its exit code.
+    pub fn report(&self) -> i32 {
+        if JSON_ERRORS.load(Ordering::Relaxed) {
+            let error = serde_json::json!({
+                "error": { "kind": self.kind.as_str(), "code": self.kind.exit_code(), "message": self.message }
+            });
+            eprintln!("{}", error);
+        } else {
+            eprintln!("{}", term::error(&self.message));
+        }
+        self.kind.exit_code()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn fail(kind: FailureKind, message: impl fmt::Display) -> i32 {
+    CommandError::new(kind, message).report()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+    Elvish,
+}
+
+impl CompletionShell {
+    fn name(&self) -> &'static str {
+        match self {
+            CompletionShell::Bash => "bash",
+            CompletionShell::Zsh => "zsh",
+            CompletionShell::Fish => "fish",
+            CompletionShell::Powershell => "powershell",
+            CompletionShell::Elvish => "elvish",
+        }
+    }
+}
+
+pub fn run() -> i32 {
+    CompleteEnv::with_factory(Cli::command).complete();
+
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() && json_errors_requested() => {
+            JSON_ERRORS.store(true, Ordering::Relaxed);
+            // Keep clap's message and drop the usage and help hints that follow the first blank line.
+            let rendered = err.render().to_string();
+            let message: Vec<&str> = rendered.split("\n\n").next().unwrap_or_default().lines().map(str::trim).collect();
+            return fail(FailureKind::Usage, message.join(" ").trim_start_matches("error: "));
+        }
+        Err(err) => err.exit(),
+    };
+    if cli.no_color {
+        term::disable_color();
+    }
+    let verbosity = match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, 0) => Verbosity::Normal,
+        (false, 1) => Verbosity::Verbose,
+        (false, _) => Verbosity::Trace,
+    };
+    term::set_verbosity(verbosity);
+    JSON_ERRORS.store(cli.error_format == ErrorFormat::Json, Ordering::Relaxed);
+    init_tracing(verbosity, !cli.no_color);
+
+    match cli.command {
+        None => {
+            CLI::new().run();
+            0
+        }
+        #[cfg(feature = "server")]
+        Some(Command::Serve {
+            addr,
+            data_file,
+            database,
+            cache,
+        }) => serve(&addr, data_file, database, cache),
+        Some(Command::SeedDemo { employees, months, file }) => seed_demo(employees, months, file),
+        #[cfg(feature = "snapshot")]
+        Some(Command::Convert { input, output, to }) => convert_data(&input, output.as_deref(), to),
+        #[cfg(feature = "postgres")]
+        Some(Command::DbImport { file, database }) => import_database(&file, &database),
+        Some(Command::VerifyPayslip { code, data }) => verify_payslip(&code, data),
+        Some(Command::Schema { name, output }) => print_schemas(name.as_deref(), output.as_deref()),
+        Some(Command::Employee(EmployeeCommand::List { filter, data })) => list_employees(&filter, data),
+        Some(Command::Employee(EmployeeCommand::Show {
+            employee_id,
+            period,
+            data,
+        })) => show_employee(&employee_id, period.as_deref(), data),
+        Some(Command::Employee(EmployeeCommand::Remove {
+            employee_id,
+            archive,
+            force,
+            data,
+        })) => remove_employee(&employee_id, archive, force, data),
+        Some(Command::Employee(EmployeeCommand::Terminate {
+            input,
+            data,
+            output,
+            errors,
+            payslips,
+        })) => terminate_employees(&input, data, output, errors, payslips),
+        Some(Command::Employee(EmployeeCommand::SetPin { employee_id, data })) => set_pin(&employee_id, data),
+        Some(Command::Employee(EmployeeCommand::IssuePin { employee_id, data })) => {
+            manage_pin(&employee_id, PinAction::Issue, data)
+        }
+        Some(Command::Employee(EmployeeCommand::UnlockPin { employee_id, data })) => {
+            manage_pin(&employee_id, PinAction::Unlock, data)
+        }
+        Some(Command::Employee(EmployeeCommand::RevokePin { employee_id, data })) => {
+            manage_pin(&employee_id, PinAction::Revoke, data)
+        }
+        Some(Command::Employee(EmployeeCommand::Pins { data })) => list_pins(data),
+        Some(Command::Employee(EmployeeCommand::Sync {
+            url,
+            deactivate_missing,
+            dry_run,
+            data,
+        })) => sync_employees(url, deactivate_missing, dry_run, data),
+        Some(Command::Employee(EmployeeCommand::Onboarding { employee_id, data })) => {
+            show_onboarding(employee_id.as_deref(), data)
+        }
+        Some(Command::Employee(EmployeeCommand::CompleteTask { employee_id, task, data })) => {
+            complete_task(&employee_id, task, data)
+        }
+        Some(Command::SelfService { data }) => self_service(data),
+        Some(Command::Employee(EmployeeCommand::SetManager { employee_id, manager_id, data })) => {
+            update_payroll(data, |payroll| {
+                payroll.set_manager(&employee_id, manager_id.as_deref())?;
+                match &manager_id {
+                    Some(manager_id) => term::status!("Employee {} now reports to {}.", employee_id, manager_id),
+                    None => term::status!("Employee {} no longer reports to anyone.", employee_id),
+                }
+                Ok(())
+            })
+        }
+        Some(Command::Employee(EmployeeCommand::SetResidency {
+            employee_id,
+            country,
+            rate,
+            resident: _,
+            data,
+        })) => set_residency(&employee_id, country, rate, data),
+        Some(Command::Employee(EmployeeCommand::SetTaxRegime { employee_id, regime, data })) => {
+            update_payroll(data, |payroll| {
+                payroll.set_tax_regime(&employee_id, regime.as_deref())?;
+                match &regime {
+                    Some(regime) => term::status!("Employee {} is now taxed under {}.", employee_id, regime),
+                    None => term::status!("Employee {} is taxed under the company's regime again.", employee_id),
+                }
+                Ok(())
+            })
+        }
+        Some(Command::Employee(EmployeeCommand::Reports { employee_id, data })) => show_reports(&employee_id, data),
+        Some(Command::Employee(EmployeeCommand::OrgChart { output, data })) => org_chart(output, data),
+        Some(Command::Employee(EmployeeCommand::StartProbation { employee_id, until, data })) => {
+            start_probation(&employee_id, until, data)
+        }
+        Some(Command::Employee(EmployeeCommand::Confirm { employee_id, salary, data })) => {
+            confirm_employee(&employee_id, salary, data)
+        }
+        Some(Command::Employee(EmployeeCommand::ProbationDue { within, data })) => probation_due(within, data),
+        Some(Command::Employee(EmployeeCommand::SalaryChange {
+            employee_id,
+            rate,
+            grade,
+            effective,
+            approver,
+            data,
+        })) => request_salary_change(&employee_id, rate, grade, effective, &approver, data),
+        Some(Command::Employee(EmployeeCommand::ApproveChange { request_id, approver, data })) => {
+            update_payroll(data, |payroll| {
+                let entry = payroll.approve_salary_change(request_id, &approver)?;
+                term::status!(
+                    "Change #{} approved: {} goes from {:.2} to {:.2} from {}.",
+                    request_id,
+                    entry.employee_id,
+                    entry.previous_rate,
+                    entry.rate,
+                    entry.effective
+                );
+                Ok(())
+            })
+        }
+        Some(Command::Employee(EmployeeCommand::RejectChange {
+            request_id,
+            approver,
+            reason,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.reject_salary_change(request_id, &approver, &reason)?;
+            term::status!("Change #{} rejected.", request_id);
+            Ok(())
+        }),
+        Some(Command::Employee(EmployeeCommand::SalaryChanges { all, data })) => list_salary_changes(all, data),
+        Some(Command::Employee(EmployeeCommand::SalaryHistory { employee_id, data })) => {
+            salary_history(&employee_id, data)
+        }
+        Some(Command::Employee(EmployeeCommand::History { employee_id, full, data })) => {
+            employee_history(&employee_id, full, data)
+        }
+        Some(Command::Employee(EmployeeCommand::ExportData { employee_id, output, data })) => {
+            export_subject_data(&employee_id, output, data)
+        }
+        Some(Command::Employee(EmployeeCommand::Pseudonymize { employee_id, dry_run, data })) => {
+            pseudonymize_employee(&employee_id, dry_run, data)
+        }
+        Some(Command::Payroll(PayrollCommand::ProcessBatch {
+            input,
+            period,
+            data,
+            output,
+            errors,
+        })) => process_batch(&input, &period, data, output, errors),
+        Some(Command::Payroll(PayrollCommand::Register { run_id, output, data })) => {
+            payroll_register(run_id, output, data)
+        }
+        Some(Command::Payroll(PayrollCommand::Journal {
+            run_id,
+            format,
+            output,
+            data,
+        })) => journal(run_id, format, output, data),
+        Some(Command::Payroll(PayrollCommand::BankFile { run_id, output, data })) => bank_file(run_id, output, data),
+        Some(Command::Payroll(PayrollCommand::EwalletFile {
+            run_id,
+            provider,
+            output,
+            data,
+        })) => ewallet_file(run_id, provider, output, data),
+        Some(Command::Payroll(PayrollCommand::Disbursement { run_id, data })) => show_disbursement(run_id, data),
+        Some(Command::Payroll(PayrollCommand::Calendar { from, to, output })) => payroll_calendar(from, to, output),
+        Some(Command::Payroll(PayrollCommand::Remittance { run_id, output, data })) => {
+            remittance_summary(run_id, output, data)
+        }
+        Some(Command::Payroll(PayrollCommand::Trend { from, to, output, data })) => {
+            cost_trend(&from, to.as_deref(), output, data)
+        }
+        Some(Command::Payroll(PayrollCommand::Departments { from, to, output, data })) => {
+            department_totals(&from, to.as_deref(), output, data)
+        }
+        Some(Command::Payroll(PayrollCommand::Budget { budget, output, data })) => {
+            compare_budget(&budget, output, data)
+        }
+        #[cfg(feature = "parquet")]
+        Some(Command::Payroll(PayrollCommand::Parquet { output, data })) => export_parquet(&output, data),
+        Some(Command::Payroll(PayrollCommand::Stats {
+            period,
+            from,
+            to,
+            kind,
+            data,
+        })) => payroll_statistics(period.as_deref(), from.as_deref(), to.as_deref(), kind, data),
+        Some(Command::Payroll(PayrollCommand::SelfCheck { data })) => self_check(data),
+        Some(Command::Payroll(PayrollCommand::Verify { data })) => verify_records(data),
+        Some(Command::Payroll(PayrollCommand::Purge { dry_run, data })) => purge_records(dry_run, data),
+        Some(Command::Payroll(PayrollCommand::Watch { dir, options })) => watch(&dir, options),
+        Some(Command::Review(ReviewCommand::Start { name, data })) => {
+            if name.trim().is_empty() {
+                return fail(FailureKind::Validation, "The review cycle needs a name");
+            }
+            update_payroll(data, |payroll| {
+                let cycle = payroll.start_review_cycle(&name)?;
+                term::status!("Review cycle '{}' started.", cycle.name);
+                Ok(())
+            })
+        }
+        Some(Command::Review(ReviewCommand::Score {
+            cycle,
+            employee_id,
+            score,
+            reviewer,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.record_review(&cycle, &employee_id, score, reviewer)?;
+            term::status!("Score {} recorded for {} in '{}'.", score, employee_id, cycle);
+            Ok(())
+        }),
+        Some(Command::Review(ReviewCommand::Close { cycle, data })) => update_payroll(data, |payroll| {
+            payroll.close_review_cycle(&cycle)?;
+            term::status!("Review cycle '{}' closed.", cycle);
+            Ok(())
+        }),
+        Some(Command::Review(ReviewCommand::List { data })) => list_review_cycles(data),
+        Some(Command::Review(ReviewCommand::Bonus { cycle, output, data })) => bonus_run(&cycle, output, data),
+        Some(Command::Timesheet(TimesheetCommand::Submit {
+            employee_id,
+            period,
+            hours,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.submit_timesheet(&employee_id, period, hours)?;
+            term::status!("Timesheet of {} for {} submitted: {} hours.", employee_id, period, hours);
+            Ok(())
+        }),
+        Some(Command::Timesheet(TimesheetCommand::Approve {
+            employee_id,
+            period,
+            approver,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let timesheet = payroll.approve_timesheet(&employee_id, &period, &approver)?;
+            term::status!("Timesheet of {} for {} approved: {} hours.", employee_id, period, timesheet.hours);
+            Ok(())
+        }),
+        Some(Command::Timesheet(TimesheetCommand::Reject {
+            employee_id,
+            period,
+            approver,
+            reason,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.reject_timesheet(&employee_id, &period, &approver, &reason)?;
+            term::status!("Timesheet of {} for {} rejected.", employee_id, period);
+            Ok(())
+        }),
+        Some(Command::Timesheet(TimesheetCommand::List { period, data })) => list_timesheets(period, data),
+        Some(Command::Overtime(OvertimeCommand::Request {
+            employee_id,
+            period,
+            hours,
+            reason,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let request = payroll.request_overtime(&employee_id, period, hours, &reason)?;
+            println!("{}", request.id);
+            term::status!("Overtime request #{} submitted: {} hours in {}.", request.id, hours, period);
+            Ok(())
+        }),
+        Some(Command::Overtime(OvertimeCommand::Approve { request_id, approver, data })) => {
+            update_payroll(data, |payroll| {
+                let request = payroll.approve_overtime(request_id, &approver)?;
+                term::status!(
+                    "Overtime request #{} approved: {} hours for {} in {}.",
+                    request_id,
+                    request.hours,
+                    request.employee_id,
+                    request.period
+                );
+                Ok(())
+            })
+        }
+        Some(Command::Overtime(OvertimeCommand::Reject {
+            request_id,
+            approver,
+            reason,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.reject_overtime(request_id, &approver, &reason)?;
+            term::status!("Overtime request #{} rejected.", request_id);
+            Ok(())
+        }),
+        Some(Command::Overtime(OvertimeCommand::List { all, data })) => list_overtime_requests(all, data),
+        Some(Command::Overtime(OvertimeCommand::Exceptions { period, data })) => overtime_exceptions(&period, data),
+        Some(Command::Attendance(AttendanceCommand::Record {
+            employee_id,
+            date,
+            late,
+            early,
+            data,
+        })) => {
+            let entry = match AttendanceEntry::new(&employee_id, date, late, early) {
+                Ok(entry) => entry,
+                Err(err) => return fail(FailureKind::Validation, PayrollError::InvalidAttendance(err)),
+            };
+            update_payroll(data, |payroll| {
+                payroll.record_attendance(entry)?;
+                term::status!("Attendance of {} on {} recorded.", employee_id, date);
+                Ok(())
+            })
+        }
+        Some(Command::Attendance(AttendanceCommand::Import { input, data })) => import_attendance(&input, data),
+        Some(Command::Attendance(AttendanceCommand::Summary { period, data })) => attendance_summary(&period, data),
+        Some(Command::Travel(TravelCommand::Record {
+            employee_id,
+            destination,
+            tier,
+            start,
+            days,
+            period,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let record = payroll.record_travel(&employee_id, &destination, tier, start, days, period)?;
+            println!("{}", record.id);
+            term::status!(
+                "Travel #{} recorded: {} days in {}, per diem {:.2} paid in {}.",
+                record.id,
+                record.days,
+                record.destination,
+                record.amount(),
+                record.period
+            );
+            Ok(())
+        }),
+        Some(Command::Travel(TravelCommand::Cancel { record_id, data })) => update_payroll(data, |payroll| {
+            payroll.cancel_travel(record_id)?;
+            term::status!("Travel #{} cancelled.", record_id);
+            Ok(())
+        }),
+        Some(Command::Travel(TravelCommand::List { period, data })) => list_travel(period, data),
+        Some(Command::Expense(ExpenseCommand::Submit {
+            employee_id,
+            category,
+            amount,
+            period,
+            description,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let claim = payroll.submit_expense(&employee_id, &category, period, amount, &description)?;
+            println!("{}", claim.id);
+            match &claim.note {
+                Some(note) => eprintln!(
+                    "{}",
+                    term::warning(format_args!("Expense claim #{} {}; {:.2} will be paid.", claim.id, note, claim.paid))
+                ),
+                None => term::status!("Expense claim #{} submitted: {:.2} paid in {}.", claim.id, claim.paid, period),
+            }
+            Ok(())
+        }),
+        Some(Command::Expense(ExpenseCommand::Cancel { claim_id, data })) => update_payroll(data, |payroll| {
+            payroll.cancel_expense(claim_id)?;
+            term::status!("Expense claim #{} withdrawn.", claim_id);
+            Ok(())
+        }),
+        Some(Command::Expense(ExpenseCommand::List { period, data })) => list_expense_claims(period, data),
+        Some(Command::Insurance(InsuranceCommand::Plans)) => insurance_plans(),
+        Some(Command::Insurance(InsuranceCommand::Enroll {
+            employee_id,
+            plan,
+            tier,
+            from,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let enrollment = payroll.enroll_insurance(&employee_id, &plan, &tier, from)?;
+            term::status!(
+                "{} enrolled in {} ({}) from {}.",
+                employee_id,
+                enrollment.plan,
+                enrollment.tier,
+                from
+            );
+            Ok(())
+        }),
+        Some(Command::Insurance(InsuranceCommand::End {
+            employee_id,
+            plan,
+            last,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.end_insurance(&employee_id, &plan, last)?;
+            term::status!("Cover of {} under {} ends after {}.", employee_id, plan, last);
+            Ok(())
+        }),
+        Some(Command::Insurance(InsuranceCommand::List { employee_id, data })) => {
+            list_insurance_enrollments(employee_id.as_deref(), data)
+        }
+        Some(Command::Equity(EquityCommand::Grant {
+            employee_id,
+            kind,
+            units,
+            strike,
+            fair_value,
+            granted,
+            cliff,
+            every,
+            over,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let schedule = VestingSchedule {
+                cliff_months: cliff,
+                every_months: every,
+                total_months: over,
+            };
+            let grant = payroll.grant_equity(&employee_id, kind, units, strike, fair_value, granted, schedule)?;
+            println!("{}", grant.id);
+            let events = grant.vesting_events();
+            match events.first() {
+                Some(first) => term::status!(
+                    "{} grant #{} recorded: {} units in {} vestings, the first on {}.",
+                    kind,
+                    grant.id,
+                    units,
+                    events.len(),
+                    first.date
+                ),
+                None => term::status!("{} grant #{} recorded.", kind, grant.id),
+            }
+            Ok(())
+        }),
+        Some(Command::Equity(EquityCommand::Revalue {
+            grant_id,
+            fair_value,
+            data,
+        })) => update_payroll(data, |payroll| {
+            payroll.revalue_equity(grant_id, fair_value)?;
+            term::status!("Grant #{} now valued at {:.2} per share.", grant_id, fair_value);
+            Ok(())
+        }),
+        Some(Command::Equity(EquityCommand::Forfeit { grant_id, on, data })) => update_payroll(data, |payroll| {
+            let grant = payroll.forfeit_equity(grant_id, on)?;
+            term::status!(
+                "Grant #{} forfeited on {}: {} of {} units vested.",
+                grant_id,
+                on,
+                grant.vested_units(on),
+                grant.units
+            );
+            Ok(())
+        }),
+        Some(Command::Equity(EquityCommand::List { employee_id, data })) => {
+            list_equity_grants(employee_id.as_deref(), data)
+        }
+        Some(Command::Equity(EquityCommand::Vesting { period, data })) => equity_vesting(&period, data),
+        Some(Command::Natura(NaturaCommand::Provide {
+            employee_id,
+            kind,
+            description,
+            value,
+            cost,
+            from,
+            data,
+        })) => update_payroll(data, |payroll| {
+            let valuation = match cost {
+                Some(cost) => NaturaValuation::Cost(cost),
+                None => NaturaValuation::Monthly(value.unwrap_or_default()),
+            };
+            let item = payroll.provide_natura(&employee_id, kind, &description, valuation, from)?;
+            println!("{}", item.id);
+            term::status!(
+                "Benefit #{} recorded: {} worth {:.2} a month from {}.",
+                item.id,
+                item.description,
+                item.monthly_value,
+                from
+            );
+            Ok(())
+        }),
+        Some(Command::Natura(NaturaCommand::End { item_id, last, data })) => update_payroll(data, |payroll| {
+            payroll.end_natura(item_id, last)?;
+            term::status!("Benefit #{} ends after {}.", item_id, last);
+            Ok(())
+        }),
+        Some(Command::Natura(NaturaCommand::List { employee_id, data })) => list_natura(employee_id.as_deref(), data),
+        Some(Command::Completions { shell }) => completions(shell),
+    }
+}
+
+/// `--error-format json` as typed, for argument errors reported before parsing succeeds.
+fn json_errors_requested() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().any(|arg| arg == "--error-format=json")
+        || args.windows(2).any(|pair| pair[0] == "--error-format" && pair[1] == "json")
+}
+
+fn init_tracing(verbosity: Verbosity, color: bool) {
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::prelude::*;
+
+    let level = match verbosity {
+        Verbosity::Quiet | Verbosity::Normal => return,
+        Verbosity::Verbose => LevelFilter::DEBUG,
+        Verbosity::Trace => LevelFilter::TRACE,
+    };
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(io::stderr)
+        .with_target(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(color && io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none());
+    let _ = tracing_subscriber::registry()
+        .with(layer)
+        .with(Targets::new().with_target("employee_management", level))
+        .try_init();
+}
+
+fn load_config() -> Result<Config, i32> {
+    Config::load_default().map_err(|err| fail(FailureKind::from(&err), err))
+}
+
+/// The data file at `path`, each save of which appends its record changes to the payroll ledger
+/// beside it.
+pub(crate) fn storage_at(path: impl AsRef<Path>) -> JsonFileStorage {
+    JsonFileStorage::new(path.as_ref()).with_ledger(ledger_beside(path.as_ref()))
+}
+
+/// The payroll ledger of the data file at `path`, signed with the key in PAYROLL_LEDGER_KEY when
+/// one is set.
+pub(crate) fn ledger_beside(path: &Path) -> PayrollLedger {
+    PayrollLedger::beside(path).with_key(std::env::var("PAYROLL_LEDGER_KEY").ok())
+}
+
+fn load_payroll(config: &Config, data: Option<PathBuf>) -> Result<Payroll, i32> {
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    match storage_at(&path).load() {
+        Ok(mut payroll) => {
+            payroll.configure(config);
+            Ok(payroll)
+        }
+        Err(err) => Err(fail(
+            FailureKind::Storage,
+            format_args!("Could not load {}: {}", path.display(), err),
+        )),
+    }
+}
+
+fn complete_employee_id(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let Ok(config) = Config::load_default() else {
+        return Vec::new();
+    };
+    let Ok(payroll) = storage_at(&config.storage_path).load() else {
+        return Vec::new();
+    };
+    payroll
+        .employees
+        .iter()
+        .map(|employee| employee.as_employee())
+        .filter(|employee| employee.employee_id().starts_with(current.as_ref()))
+        .map(|employee| {
+            CompletionCandidate::new(employee.employee_id()).help(Some(employee.profile().name.clone().into()))
+        })
+        .collect()
+}
+
+fn completions(shell: CompletionShell) -> i32 {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(shell.name()) else {
+        return fail(FailureKind::Usage, format_args!("Unsupported shell: {}", shell.name()));
+    };
+    let program = std::env::args().next().unwrap_or_else(|| BIN_NAME.to_string());
+    match completer.write_registration("COMPLETE", BIN_NAME, BIN_NAME, &program, &mut io::stdout()) {
+        Ok(()) => 0,
+        Err(err) => fail(FailureKind::General, format_args!("Could not write completions: {}", err)),
+    }
+}
+
+fn list_employees(filter: &EmployeeFilter, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let mut query = EmployeeQuery::new(&payroll);
+    if let Some(kind) = filter.kind {
+        query = query.employee_kind(kind);
+    }
+    if let Some(status) = filter.status {
+        query = query.status(status);
+    }
+    if let Some(department) = &filter.department {
+        query = query.department(department);
+    }
+    if let Some(manager_id) = &filter.manager {
+        query = query.manager(manager_id);
+    }
+    if let Some(amount) = filter.min_salary {
+        query = query.min_salary(amount);
+    }
+    if let Some(amount) = filter.max_salary {
+        query = query.max_salary(amount);
+    }
+
+    let employees = query.run();
+    if employees.is_empty() {
+        println!("No employees found.");
+        return 0;
+    }
+    PayrollPresentation::print_employee_list(&employees, &payroll.settings);
+    0
+}
+
+fn show_employee(employee_id: &str, period: Option<&str>, data: Option<PathBuf>) -> i32 {
+    let pay_period = match period.map(str::parse::<PayPeriod>).transpose() {
+        Ok(period) => period.unwrap_or_else(|| PayPeriod::from_date(chrono::Local::now().date_naive())),
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let Some(employee) = payroll.get_employee(employee_id) else {
+        return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+    };
+    match payroll.preview_payroll(employee_id, &pay_period.to_string()) {
+        Ok(preview) => {
+            PayrollPresentation::print_employee_detail(employee, &preview);
+            0
+        }
+        Err(err) => fail(FailureKind::from(&err), err),
+    }
+}
+
+fn payroll_register(run_id: u32, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let register = match payroll.register_for_run(run_id) {
+        Ok(register) => register,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    let Some(output) = output else {
+        PayrollPresentation::print_payroll_register(&register);
+        return 0;
+    };
+    match register.save(&output) {
+        Ok(()) => {
+            term::status!("{} rows exported to {}", register.rows.len(), output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, err),
+    }
+}
+
+fn journal(run_id: u32, format: JournalFormat, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let lines = match payroll.journal_for_run(run_id, &AccountCodes::default()) {
+        Ok(lines) => lines,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("journal_run_{}.csv", run_id)));
+    let result = File::create(&output).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        format.write(&lines, &mut writer)?;
+        writer.flush()
+    });
+    match result {
+        Ok(()) => {
+            term::status!("{} journal lines exported to {} ({})", lines.len(), output.display(), format);
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+/// Employees whose split is missing or does not add up are left out of the file and make the
+/// command exit with the validation status.
+fn bank_file(run_id: u32, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let batch = match payroll.bank_transfers_for_run(run_id) {
+        Ok(batch) => batch,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    PayrollPresentation::print_bank_transfers(&batch);
+    if let Some(output) = output {
+        if let Err(err) = File::create(&output).and_then(|file| batch.write_csv(BufWriter::new(file))) {
+            return fail(FailureKind::Storage, format_args!("Export failed: {}", err));
+        }
+        term::status!("Bank transfers exported to {}", output.display());
+    }
+    if !batch.problems.is_empty() {
+        return fail(
+            FailureKind::Validation,
+            format_args!("{} employees could not be paid", batch.problems.len()),
+        );
+    }
+    0
+}
+
+fn list_review_cycles(data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if payroll.review_cycles.is_empty() {
+        println!("No review cycles.");
+        return 0;
+    }
+    PayrollPresentation::print_review_cycles(&payroll.review_cycles);
+    0
+}
+
+fn list_timesheets(period: Option<PayPeriod>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let timesheets = match period {
+        Some(period) => payroll.timesheets.iter().filter(|timesheet| timesheet.period == period).collect(),
+        None => payroll.pending_timesheets(),
+    };
+    if timesheets.is_empty() {
+        println!("No timesheets.");
+        return 0;
+    }
+    PayrollPresentation::print_timesheets(&timesheets);
+    0
+}
+
+fn list_overtime_requests(all: bool, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let requests = match all {
+        true => payroll.overtime_requests.iter().collect(),
+        false => payroll.pending_overtime_requests(),
+    };
+    if requests.is_empty() {
+        println!("No overtime requests.");
+        return 0;
+    }
+    PayrollPresentation::print_overtime_requests(&requests);
+    0
+}
+
+fn overtime_exceptions(period: &str, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let records = payroll.overtime_exceptions(period);
+    if records.is_empty() {
+        println!("No overtime beyond policy in {}.", period);
+        return 0;
+    }
+    PayrollPresentation::print_overtime_exceptions(&records);
+    0
+}
+
+fn import_attendance(input: &Path, data: Option<PathBuf>) -> i32 {
+    let (entries, mut rejected) = match File::open(input).and_then(|file| read_attendance_csv(BufReader::new(file))) {
+        Ok(parsed) => parsed,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            return fail(FailureKind::Validation, format_args!("Could not read {}: {}", input.display(), err))
+        }
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not read {}: {}", input.display(), err)),
+    };
+    let mut imported = 0;
+    let code = update_payroll(data, |payroll| {
+        for AttendanceRow { line, entry } in entries {
+            let employee_id = entry.employee_id.clone();
+            match payroll.record_attendance(entry) {
+                Ok(()) => imported += 1,
+                Err(err) => rejected.push(RejectedRow {
+                    line,
+                    employee_id,
+                    reason: err.to_string(),
+                }),
+            }
+        }
+        Ok(())
+    });
+    if code != 0 {
+        return code;
+    }
+    rejected.sort_by_key(|row| row.line);
+    for row in &rejected {
+        eprintln!("{}", term::warning(format_args!("Line {} ({}): {}", row.line, row.employee_id, row.reason)));
+    }
+    term::status!("Imported {} attendance entries; {} rows rejected.", imported, rejected.len());
+    if imported == 0 && !rejected.is_empty() {
+        return fail(FailureKind::Validation, "No attendance entries could be imported");
+    }
+    0
+}
+
+fn attendance_summary(period: &PayPeriod, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if !payroll.attendance.iter().any(|entry| entry.period() == *period) {
+        println!("No attendance recorded for {}.", period);
+        return 0;
+    }
+    PayrollPresentation::print_attendance_summary(&payroll, period);
+    0
+}
+
+fn list_travel(period: Option<PayPeriod>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let records = payroll.travel_records(period.as_ref());
+    if records.is_empty() {
+        println!("No travel records.");
+        return 0;
+    }
+    PayrollPresentation::print_travel(&records);
+    0
+}
+
+fn list_expense_claims(period: Option<PayPeriod>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let claims = payroll.expense_claims_in(period.as_ref());
+    if claims.is_empty() {
+        println!("No expense claims.");
+        return 0;
+    }
+    PayrollPresentation::print_expense_claims(&claims);
+    0
+}
+
+fn insurance_plans() -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    if config.insurance.is_empty() {
+        println!("No insurance plans configured.");
+        return 0;
+    }
+    PayrollPresentation::print_insurance_plans(&config.insurance);
+    0
+}
+
+fn list_insurance_enrollments(employee_id: Option<&str>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let enrollments = payroll.insurance_enrollments_of(employee_id);
+    if enrollments.is_empty() {
+        println!("No insurance enrollments.");
+        return 0;
+    }
+    PayrollPresentation::print_insurance_enrollments(&enrollments);
+    0
+}
+
+fn list_equity_grants(employee_id: Option<&str>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let grants = payroll.equity_grants_of(employee_id);
+    if grants.is_empty() {
+        println!("No equity grants.");
+        return 0;
+    }
+    PayrollPresentation::print_equity_grants(&grants, chrono::Local::now().date_naive());
+    0
+}
+
+fn equity_vesting(period: &PayPeriod, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let events = payroll.vesting_in(period);
+    if events.is_empty() {
+        println!("Nothing vests in {}.", period);
+        return 0;
+    }
+    PayrollPresentation::print_vesting(&events);
+    0
+}
+
+fn list_natura(employee_id: Option<&str>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let items = payroll.natura_of(employee_id);
+    if items.is_empty() {
+        println!("No benefits in kind.");
+        return 0;
+    }
+    PayrollPresentation::print_natura(&items);
+    0
+}
+
+fn bonus_run(cycle: &str, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let payroll = match load_payroll(&config, data) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let run = match payroll.bonus_run(cycle, &config.bonus) {
+        Ok(run) => run,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    PayrollPresentation::print_bonus_run(&run);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| run.write_csv(BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("Bonuses exported to {}", output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn ewallet_file(run_id: u32, wallet: EWallet, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let batch = match payroll.bank_transfers_for_run(run_id) {
+        Ok(batch) => batch,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    PayrollPresentation::print_ewallet_transfers(&batch, wallet);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| batch.write_ewallet_csv(wallet, BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("{} payouts exported to {}", wallet.as_str(), output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn show_disbursement(run_id: u32, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let Some(run) = payroll.get_run(run_id) else {
+        return fail(FailureKind::NotFound, PayrollError::RunNotFound(run_id));
+    };
+    match &run.disbursement {
+        Some(disbursement) => PayrollPresentation::print_disbursement(run_id, disbursement),
+        None => println!("Payroll run #{} has not been submitted for payment.", run_id),
+    }
+    0
+}
+
+fn payroll_calendar(from: Option<PayPeriod>, to: Option<PayPeriod>, output: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let (default_from, default_to) = calendar::default_range(chrono::Local::now().date_naive());
+    let (from, to) = (from.unwrap_or(default_from), to.unwrap_or(default_to));
+    if to < from {
+        return fail(FailureKind::Usage, format_args!("--to {} is before --from {}", to, from));
+    }
+    let events = calendar::payroll_calendar(from, to, config.schedule.pay_day);
+    let output = output.unwrap_or_else(|| PathBuf::from("payroll_calendar.ics"));
+    let result = File::create(&output).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        calendar::write_ics(&events, chrono::Utc::now(), &mut writer)?;
+        writer.flush()
+    });
+    match result {
+        Ok(()) => {
+            term::status!("{} events from {} to {} written to {}", events.len(), from, to, output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn remittance_summary(run_id: u32, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let summary = match payroll.remittance_for_run(run_id, &BpjsRates::default()) {
+        Ok(summary) => summary,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    PayrollPresentation::print_remittance_summary(&summary);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| summary.write_csv(BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("Remittance summary exported to {}", output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn cost_trend(from: &str, to: Option<&str>, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let from = match from.parse::<PayPeriod>() {
+        Ok(period) => period,
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let to = match to.map(str::parse::<PayPeriod>).transpose() {
+        Ok(period) => period.unwrap_or_else(|| PayPeriod::from_date(chrono::Local::now().date_naive())),
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    if from > to {
+        return fail(FailureKind::Usage, "--from must not be after --to");
+    }
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+
+    let months = payroll.cost_trend(from, to, &BpjsRates::default());
+    PayrollPresentation::print_cost_trend(&months);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| write_cost_trend_csv(&months, BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("{} months exported to {}", months.len(), output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn department_totals(from: &str, to: Option<&str>, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let from = match from.parse::<PayPeriod>() {
+        Ok(period) => period,
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let to = match to.map(str::parse::<PayPeriod>).transpose() {
+        Ok(period) => period.unwrap_or_else(|| PayPeriod::from_date(chrono::Local::now().date_naive())),
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    if from > to {
+        return fail(FailureKind::Usage, "--from must not be after --to");
+    }
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+
+    let months = payroll.department_totals(from, to);
+    PayrollPresentation::print_department_totals(&months);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| write_department_totals_csv(&months, BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("{} rows exported to {}", months.len(), output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn compare_budget(budget: &Path, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let budget = match File::open(budget).and_then(|file| read_budget_csv(BufReader::new(file), &config.locale)) {
+        Ok(budget) => budget,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            return fail(FailureKind::Validation, format_args!("{}: {}", budget.display(), err));
+        }
+        Err(err) => return fail(FailureKind::Storage, format_args!("{}: {}", budget.display(), err)),
+    };
+    let payroll = match load_payroll(&config, data) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+
+    let rows = budget.compare(&payroll, &BpjsRates::default());
+    PayrollPresentation::print_budget_variance(&rows);
+    let Some(output) = output else {
+        return 0;
+    };
+    match File::create(&output).and_then(|file| write_variance_csv(&rows, BufWriter::new(file))) {
+        Ok(()) => {
+            term::status!("{} rows exported to {}", rows.len(), output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn export_parquet(output: &Path, data: Option<PathBuf>) -> i32 {
+    use crate::export::parquet::ParquetWriter;
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut writer = match File::create(output)
+        .map_err(crate::error::ExportError::from)
+        .and_then(|file| ParquetWriter::new(BufWriter::new(file)))
+    {
+        Ok(writer) => writer,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    };
+    // Records are streamed from the data file; an export error stops the writing but not the read.
+    let mut export_error = None;
+    let read = storage_at(&path).for_each_record(&mut |record| {
+        if export_error.is_none() {
+            export_error = writer.write_record(&record).err();
+        }
+        Ok(())
+    });
+    if let Err(err) = read {
+        return fail(FailureKind::Storage, format_args!("Could not load {}: {}", path.display(), err));
+    }
+    match export_error.map_or_else(|| writer.finish(), Err) {
+        Ok((_, rows)) => {
+            term::status!("{} records exported to {}", rows, output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn self_check(data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let violations = payroll.check_invariants();
+    PayrollPresentation::print_violations(&violations);
+    if violations.is_empty() {
+        0
+    } else {
+        fail(FailureKind::Validation, format_args!("{} invariant violations found", violations.len()))
+    }
+}
+
+fn verify_records(data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let verification = payroll.verify();
+    PayrollPresentation::print_verification(&verification);
+    if verification.mismatches.is_empty() {
+        0
+    } else {
+        fail(FailureKind::Validation, format_args!("{} mismatched amounts found", verification.mismatches.len()))
+    }
+}
+
+fn purge_records(dry_run: bool, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let years = config.retention.purge_records_after_years;
+    let today = chrono::Local::now().date_naive();
+    if dry_run {
+        PayrollPresentation::print_purge(&payroll.purge_preview(years, today, &BpjsRates::default()));
+        return 0;
+    }
+    let report = payroll.purge_records(years, today, &BpjsRates::default());
+    PayrollPresentation::print_purge(&report);
+    if report.is_empty() {
+        return 0;
+    }
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    term::status!("Purged {} payroll records; the periods are summarized and closed.", report.records());
+    0
+}
+
+fn print_schemas(name: Option<&str>, output: Option<&Path>) -> i32 {
+    #[cfg(not(feature = "schema"))]
+    {
+        let _ = (name, output);
+        fail(
+            FailureKind::Usage,
+            "JSON Schemas are not available in this build; rebuild with `--features schema`",
+        )
+    }
+    #[cfg(feature = "schema")]
+    {
+        use crate::schema::{schema, schemas, SCHEMA_NAMES};
+
+        let selected = match name {
+            Some(name) => match schema(name) {
+                Some(schema) => vec![(name, schema)],
+                None => {
+                    return fail(
+                        FailureKind::NotFound,
+                        format_args!("No schema named '{}' (expected one of: {})", name, SCHEMA_NAMES.join(", ")),
+                    )
+                }
+            },
+            None if output.is_some() => schemas(),
+            None => {
+                for name in SCHEMA_NAMES {
+                    println!("{}", name);
+                }
+                return 0;
+            }
+        };
+        let Some(output) = output else {
+            let json = serde_json::to_string_pretty(&selected[0].1).expect("schemas serialize");
+            println!("{}", json);
+            return 0;
+        };
+        if let Err(err) = std::fs::create_dir_all(output) {
+            return fail(FailureKind::Storage, format_args!("Could not create {}: {}", output.display(), err));
+        }
+        for (name, schema) in &selected {
+            let path = output.join(format!("{}.schema.json", name));
+            let json = serde_json::to_string_pretty(schema).expect("schemas serialize");
+            if let Err(err) = std::fs::write(&path, json + "\n") {
+                return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+            }
+        }
+        term::status!("{} schemas written to {}", selected.len(), output.display());
+        0
+    }
+}
+
+/// Exits with the validation status when the payslip does not hold, and not found when no record
+/// matches its code.
+fn verify_payslip(code: &str, data: Option<PathBuf>) -> i32 {
+    let code = match code.parse::<VerificationCode>() {
+        Ok(code) => code,
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let signing_key = std::env::var("PAYSLIP_SIGNING_KEY").ok().filter(|key| !key.is_empty());
+    let Some((record, verdict)) = payslip::verify_payslip(payroll.get_payroll_records(), &code, signing_key.as_deref())
+    else {
+        return fail(
+            FailureKind::NotFound,
+            "No payroll record matches this payslip; it was not issued from this payroll",
+        );
+    };
+    PayrollPresentation::print_payslip_verification(record, verdict);
+    match verdict.is_valid() {
+        true => 0,
+        false => fail(FailureKind::Validation, format_args!("Payslip not valid: {}", verdict)),
+    }
+}
+
+fn payroll_statistics(
+    period: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    kind: Option<EmployeeKind>,
+    data: Option<PathBuf>,
+) -> i32 {
+    let parse = |value: Option<&str>| value.map(str::parse::<PayPeriod>).transpose();
+    let (period, from, to) = match (parse(period), parse(from), parse(to)) {
+        (Ok(period), Ok(from), Ok(to)) => (period, from, to),
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => return fail(FailureKind::Usage, err),
+    };
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+
+    let mut query = payroll.query();
+    let mut scope = Vec::new();
+    if let Some(period) = period {
+        query = query.period(&period.to_string());
+        scope.push(period.to_string());
+    }
+    if from.is_some() || to.is_some() {
+        if let Some(from) = from {
+            query = query.from_period(from);
+        }
+        if let Some(to) = to {
+            query = query.to_period(to);
+        }
+        let bound = |period: Option<PayPeriod>| period.map_or("...".to_string(), |period| period.to_string());
+        scope.push(format!("{} - {}", bound(from), bound(to)));
+    }
+    if let Some(kind) = kind {
+        query = query.employee_kind(kind);
+        scope.push(kind.as_str().to_string());
+    }
+
+    let scope = if scope.is_empty() { "all records".to_string() } else { scope.join(", ") };
+    PayrollPresentation::print_statistics(&scope, &payroll.statistics(&query));
+    0
+}
+
+fn remove_employee(employee_id: &str, archive: bool, force: bool, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+
+    let result = if archive {
+        payroll.archive_employee(employee_id).map(|_| "archived")
+    } else {
+        payroll.remove_employee(employee_id, force).map(|_| "removed")
+    };
+    let action = match result {
+        Ok(action) => action,
+        Err(err @ PayrollError::EmployeeHasHistory(..)) => {
+            return fail(FailureKind::from(&err), format_args!("{} (--archive), or pass --force to remove it", err))
+        }
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    println!("Employee {} {}.", employee_id, action);
+    0
+}
+
+fn export_subject_data(employee_id: &str, output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let subject = match payroll.subject_data(employee_id, chrono::Utc::now()) {
+        Ok(subject) => subject,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}_data.json", employee_id)));
+    let result = File::create(&output).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &subject)?;
+        writer.flush()
+    });
+    match result {
+        Ok(()) => {
+            term::status!(
+                "Data held on {} ({} payroll records) exported to {}",
+                employee_id,
+                subject.payroll_records.len(),
+                output.display()
+            );
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn pseudonymize_employee(employee_id: &str, dry_run: bool, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let years = config.retention.pseudonymize_after_years;
+    let today = chrono::Local::now().date_naive();
+    let done = match payroll.pseudonymize_employee(employee_id, years, today) {
+        Ok(done) => done,
+        Err(err) => return fail(FailureKind::from(&err), err),
+    };
+    if dry_run {
+        term::status!(
+            "Employee {} can be pseudonymized: {} payroll records and {} other entries would be kept under a new ID.",
+            employee_id,
+            done.payroll_records,
+            done.other_entries
+        );
+        return 0;
+    }
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    term::status!(
+        "Employee {} pseudonymized as {}: {} payroll records and {} other entries kept.",
+        employee_id,
+        done.pseudonym,
+        done.payroll_records,
+        done.other_entries
+    );
+    let ledger = ledger_beside(&path);
+    if ledger.path().exists() {
+        eprintln!(
+            "{}",
+            term::warning(format_args!(
+                "{} keeps the old record ids of {} in hash-chained entries that are not rewritten.",
+                ledger.path().display(),
+                employee_id
+            ))
+        );
+    }
+    0
+}
+
+fn read_pin() -> io::Result<String> {
+    // Typed PINs are not echoed; piped ones are read as plain lines.
+    let read = |prompt: &str| {
+        if io::stdin().is_terminal() {
+            return rpassword::prompt_password(prompt).map(|pin| pin.trim().to_string());
+        }
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        Ok::<_, io::Error>(line.trim().to_string())
+    };
+    let pin = read("New PIN: ")?;
+    if io::stdin().is_terminal() && read("Repeat PIN: ")? != pin {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "the PINs do not match"));
+    }
+    Ok(pin)
+}
+
+fn set_pin(employee_id: &str, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if payroll.get_employee(employee_id).is_none() {
+        return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+    }
+
+    let pin = match read_pin() {
+        Ok(pin) => pin,
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    if let Err(err) = payroll.set_pin(employee_id, &pin) {
+        return fail(FailureKind::from(&err), err);
+    }
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    term::status!("PIN set for employee {}.", employee_id);
+    0
+}
+
+enum PinAction {
+    Issue,
+    Unlock,
+    Revoke,
+}
+
+fn manage_pin(employee_id: &str, action: PinAction, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if payroll.get_employee(employee_id).is_none() {
+        return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+    }
+
+    let message = match action {
+        PinAction::Issue => match payroll.issue_pin(employee_id) {
+            Ok(pin) => {
+                println!("{}", pin);
+                format!("Temporary PIN issued for employee {}; it must be changed at first sign-in.", employee_id)
+            }
+            Err(err) => return fail(FailureKind::from(&err), err),
+        },
+        PinAction::Unlock => match payroll.unlock_pin(employee_id) {
+            Ok(true) => format!("Self-service sign-in unlocked for employee {}.", employee_id),
+            Ok(false) => format!("Employee {} was not locked out; failed sign-ins cleared.", employee_id),
+            Err(err) => return fail(FailureKind::from(&err), err),
+        },
+        PinAction::Revoke => {
+            if !payroll.revoke_pin(employee_id) {
+                return fail(FailureKind::NotFound, format_args!("Employee {} has no PIN", employee_id));
+            }
+            format!("PIN revoked for employee {}.", employee_id)
+        }
+    };
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    term::status!("{}", message);
+    0
+}
+
+fn list_pins(data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    PayrollPresentation::print_pin_statuses(&payroll, chrono::Utc::now());
+    0
+}
+
+fn sync_employees(url: String, deactivate_missing: bool, dry_run: bool, data: Option<PathBuf>) -> i32 {
+    #[cfg(not(feature = "hris"))]
+    {
+        let _ = (url, deactivate_missing, dry_run, data);
+        fail(
+            FailureKind::Usage,
+            "HRIS sync is not available in this build; rebuild with `--features hris`",
+        )
+    }
+    #[cfg(feature = "hris")]
+    {
+        let config = match load_config() {
+            Ok(config) => config,
+            Err(code) => return code,
+        };
+        let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+        let mut payroll = match load_payroll(&config, Some(path.clone())) {
+            Ok(payroll) => payroll,
+            Err(code) => return code,
+        };
+        let source = crate::hris::RestEmployeeSource {
+            url,
+            token: std::env::var("HRIS_TOKEN").ok().filter(|token| !token.is_empty()),
+        };
+        let report = match payroll.sync_employees(&source, deactivate_missing) {
+            Ok(report) => report,
+            Err(err) => return fail(FailureKind::from(&err), err),
+        };
+        PayrollPresentation::print_sync_report(&report);
+        if dry_run || !report.has_changes() {
+            if dry_run {
+                term::status!("Dry run; nothing saved.");
+            }
+            return 0;
+        }
+        if let Err(err) = storage_at(&path).save(&payroll) {
+            return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+        }
+        term::status!(
+            "{} created, {} updated, {} deactivated.",
+            report.created.len(),
+            report.updated.len(),
+            report.deactivated.len()
+        );
+        0
+    }
+}
+
+fn show_reports(employee_id: &str, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if payroll.get_employee(employee_id).is_none() {
+        return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+    }
+    let chain: Vec<&str> = payroll
+        .reporting_chain(employee_id)
+        .iter()
+        .map(|manager| manager.as_employee().employee_id())
+        .collect();
+    match chain.is_empty() {
+        true => println!("{} does not report to anyone.", employee_id),
+        false => println!("Reporting chain: {} -> {}", employee_id, chain.join(" -> ")),
+    }
+    let reports = payroll.all_reports(employee_id);
+    if reports.is_empty() {
+        println!("Nobody reports to {}.", employee_id);
+        return 0;
+    }
+    let direct = payroll.direct_reports(employee_id).len();
+    println!("{} direct and {} indirect reports:", direct, reports.len() - direct);
+    PayrollPresentation::print_employee_list(&reports, &payroll.settings);
+    0
+}
+
+fn org_chart(output: Option<PathBuf>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let Some(output) = output else {
+        PayrollPresentation::print_org_chart(&payroll);
+        return 0;
+    };
+    let dot = output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dot"));
+    let result = File::create(&output).and_then(|file| match dot {
+        true => org_chart::write_org_chart_dot(&payroll, BufWriter::new(file)),
+        false => org_chart::write_org_chart_csv(&payroll, BufWriter::new(file)),
+    });
+    match result {
+        Ok(()) => {
+            term::status!("Organization chart exported to {}", output.display());
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Export failed: {}", err)),
+    }
+}
+
+fn start_probation(employee_id: &str, until: NaiveDate, data: Option<PathBuf>) -> i32 {
+    update_payroll(data, |payroll| {
+        payroll.start_probation(employee_id, until)?;
+        term::status!("Employee {} is on probation until {}.", employee_id, until.format("%Y-%m-%d"));
+        Ok(())
+    })
+}
+
+fn confirm_employee(employee_id: &str, salary: Option<f64>, data: Option<PathBuf>) -> i32 {
+    if salary.is_some_and(|salary| !salary.is_finite() || salary <= 0.0) {
+        return fail(FailureKind::Validation, "--salary must be a positive amount");
+    }
+    update_payroll(data, |payroll| {
+        let previous = payroll.get_employee(employee_id).map(EmployeeData::base_rate);
+        payroll.confirm_employee(employee_id, salary)?;
+        term::status!("Employee {} is now permanent.", employee_id);
+        if let (Some(previous), Some(salary)) = (previous, salary) {
+            term::status!("Base rate revised: {:.2} -> {:.2}", previous, salary);
+        }
+        Ok(())
+    })
+}
+
+fn request_salary_change(
+    employee_id: &str,
+    rate: f64,
+    grade: Option<String>,
+    effective: PayPeriod,
+    approver: &str,
+    data: Option<PathBuf>,
+) -> i32 {
+    update_payroll(data, |payroll| {
+        let request = payroll.request_salary_change(employee_id, rate, grade, effective, approver)?;
+        println!("{}", request.id);
+        term::status!("Change request #{} submitted; waiting for {} to approve.", request.id, request.approver);
+        Ok(())
+    })
+}
+
+fn list_salary_changes(all: bool, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let requests = match all {
+        true => payroll.salary_changes.iter().collect(),
+        false => payroll.pending_salary_changes(),
+    };
+    if requests.is_empty() {
+        println!("No salary change requests.");
+        return 0;
+    }
+    PayrollPresentation::print_salary_changes(&requests);
+    0
+}
+
+fn salary_history(employee_id: &str, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    if payroll.get_employee(employee_id).is_none() {
+        return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+    }
+    let history = payroll.salary_history_for(employee_id);
+    if history.is_empty() {
+        println!("No approved salary changes for {}.", employee_id);
+        return 0;
+    }
+    PayrollPresentation::print_salary_history(employee_id, &history);
+    0
+}
+
+/// Loads the data file, applies `update` and saves the result.
+fn update_payroll(
+    data: Option<PathBuf>,
+    update: impl FnOnce(&mut Payroll) -> Result<(), PayrollError>,
+) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let file = storage_at(&path);
+    let mut transaction = match file.transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not load {}: {}", path.display(), err)),
+    };
+    transaction.payroll_mut().configure(&config);
+    if let Err(err) = update(transaction.payroll_mut()) {
+        return fail(FailureKind::from(&err), err);
+    }
+    if let Err(err) = transaction.commit() {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    0
+}
+
+fn set_residency(employee_id: &str, country: Option<String>, rate: Option<f64>, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let residency = country.map(|country| {
+        let country = country.trim().to_uppercase();
+        NonResidency {
+            rate: rate.unwrap_or_else(|| config.tax.pph26_rate_for(&country)),
+            country,
+        }
+    });
+    update_payroll(data, |payroll| {
+        payroll.set_residency(employee_id, residency.clone())?;
+        match &residency {
+            Some(residency) => term::status!(
+                "Employee {} is resident in {}; PPh 26 is withheld at {}%.",
+                employee_id,
+                residency.country,
+                residency.rate * 100.0
+            ),
+            None => term::status!("Employee {} is resident in Indonesia; PPh 21 applies.", employee_id),
+        }
+        Ok(())
+    })
+}
+
+fn probation_due(within: i64, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let today = chrono::Local::now().date_naive();
+    let due = payroll.probation_reviews_due(today + chrono::Duration::days(within));
+    if due.is_empty() {
+        println!("No probation reviews due in the next {} days.", within);
+        return 0;
+    }
+    PayrollPresentation::print_probation_reviews(&due, today);
+    0
+}
+
+fn show_onboarding(employee_id: Option<&str>, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let checklists = match employee_id {
+        Some(employee_id) => match payroll.onboarding(employee_id) {
+            Some(checklist) => vec![checklist],
+            None if payroll.get_employee(employee_id).is_none() => {
+                return fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+            }
+            None => {
+                println!("Employee {} has no onboarding checklist.", employee_id);
+                return 0;
+            }
+        },
+        None => payroll.pending_onboarding(),
+    };
+    if checklists.is_empty() {
+        println!("No open onboarding tasks.");
+        return 0;
+    }
+    PayrollPresentation::print_onboarding(&checklists);
+    0
+}
+
+fn complete_task(employee_id: &str, task: OnboardingTask, data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    match payroll.complete_onboarding_task(employee_id, task) {
+        Ok(false) => {
+            term::status!("{} was already done for employee {}.", task, employee_id);
+            return 0;
+        }
+        Ok(true) => {}
+        Err(err) => return fail(FailureKind::from(&err), err),
+    }
+    if let Err(err) = storage_at(&path).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+    }
+    match payroll.is_onboarded(employee_id) {
+        true => term::status!("{} done; employee {} can now be included in payroll runs.", task, employee_id),
+        false => term::status!("{} done for employee {}.", task, employee_id),
+    }
+    0
+}
+
+/// Failed sign-ins and PIN changes are saved back to the data file when the session ends.
+fn self_service(data: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let mut payroll = match load_payroll(&config, Some(path.clone())) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let credentials = payroll.credentials.clone();
+    let result = crate::cli::run_self_service(&mut payroll);
+    if payroll.credentials != credentials {
+        if let Err(err) = storage_at(&path).save(&payroll) {
+            return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err));
+        }
+    }
+    match result {
+        Ok(()) => 0,
+        Err(err) => fail(FailureKind::from(&err), err),
+    }
+}
+
+fn employee_history(employee_id: &str, full: bool, data: Option<PathBuf>) -> i32 {
+    let payroll = match load_config().and_then(|config| load_payroll(&config, data)) {
+        Ok(payroll) => payroll,
+        Err(code) => return code,
+    };
+    let employee_id = match payroll.match_employee_id(employee_id) {
+        EmployeeIdMatch::Exact(employee) | EmployeeIdMatch::Completed(employee) => employee.as_employee().employee_id(),
+        EmployeeIdMatch::Ambiguous(matches) | EmployeeIdMatch::Unknown(matches) => {
+            let code = fail(FailureKind::NotFound, format_args!("No employee found with ID: {}", employee_id));
+            if !matches.is_empty() && !JSON_ERRORS.load(Ordering::Relaxed) {
+                let ids: Vec<&str> =
+                    matches.iter().take(8).map(|employee| employee.as_employee().employee_id()).collect();
+                eprintln!("Did you mean: {}{}", ids.join(", "), if matches.len() > 8 { ", ..." } else { "" });
+            }
+            return code;
+        }
+    };
+
+    let records = payroll.get_employee_payroll(employee_id);
+    if records.is_empty() {
+        println!("No payroll records found for employee ID: {}", employee_id);
+        return 0;
+    }
+    PayrollPresentation::print_employee_history(employee_id, &records);
+    if full {
+        println!();
+        for record in records {
+            PayrollPresentation::print_payroll_summary(record);
+            println!();
+        }
+    }
+    0
+}
+
+fn report_path(dir: &Path, input: &Path, suffix: &str) -> PathBuf {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    dir.join(format!("{}_{}.csv", stem, suffix))
+}
+
+struct BatchReport {
+    processed: usize,
+    unchanged: usize,
+    rejected: usize,
+    run_id: Option<u32>,
+}
+
+fn write_rejected(rejected: &[RejectedRow], path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    batch::write_rejected_csv(rejected, &mut writer)?;
+    io::Write::flush(&mut writer)
+}
+
+/// Processes one hours file against the data file and writes the results (and rejected rows, if
+/// any) next to each other. With `create_run`, the period's records are gathered into a draft run.
+fn run_batch(
+    config: &Config,
+    data: &Path,
+    input: &Path,
+    pay_period: &str,
+    output: &Path,
+    errors: &Path,
+    create_run: bool,
+) -> Result<BatchReport, CommandError> {
+    let storage = |message: String| CommandError::new(FailureKind::Storage, message);
+    let file = storage_at(data);
+    let mut transaction = file
+        .transaction()
+        .map_err(|err| storage(format!("Could not load {}: {}", data.display(), err)))?;
+    let payroll = transaction.payroll_mut();
+    payroll.configure(config);
+
+    let (entries, mut rejected) = File::open(input)
+        .and_then(|file| batch::read_hours_csv(BufReader::new(file), &config.locale))
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::InvalidData => CommandError::new(
+                FailureKind::Validation,
+                format_args!("Could not read {}: {}", input.display(), err),
+            ),
+            _ => storage(format!("Could not read {}: {}", input.display(), err)),
+        })?;
+    let outcome = batch::process_hours(payroll, entries, pay_period)
+        .map_err(|err| CommandError::new(FailureKind::from(&err), format_args!("Batch not processed: {}", err)))?;
+    rejected.extend(outcome.rejected);
+    rejected.sort_by_key(|row| row.line);
+    let run_id = if create_run && !outcome.records.is_empty() {
+        payroll.create_run(pay_period).ok().map(|run| run.run_id)
+    } else {
+        None
+    };
+
+    transaction
+        .commit()
+        .map_err(|err| storage(format!("Could not write {}: {}", data.display(), err)))?;
+
+    File::create(output)
+        .and_then(|file| {
+            let mut register = RegisterCsvWriter::new(BufWriter::new(file))?;
+            for record in outcome.records.iter().chain(&outcome.unchanged) {
+                register.write_record(record)?;
+            }
+            register.finish()
+        })
+        .map_err(|err| storage(format!("Could not write {}: {}", output.display(), err)))?;
+    if !rejected.is_empty() {
+        write_rejected(&rejected, errors)
+            .map_err(|err| storage(format!("Could not write {}: {}", errors.display(), err)))?;
+    }
+
+    Ok(BatchReport {
+        processed: outcome.records.len(),
+        unchanged: outcome.unchanged.len(),
+        rejected: rejected.len(),
+        run_id,
+    })
+}
+
+fn terminate_employees(
+    input: &Path,
+    data: Option<PathBuf>,
+    output: Option<PathBuf>,
+    errors: Option<PathBuf>,
+    payslips: Option<PathBuf>,
+) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let file = storage_at(&path);
+    let mut transaction = match file.transaction() {
+        Ok(transaction) => transaction,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not load {}: {}", path.display(), err)),
+    };
+    let payroll = transaction.payroll_mut();
+    payroll.configure(&config);
+    let dir = input.parent().unwrap_or(Path::new(""));
+    let output = output.unwrap_or_else(|| report_path(dir, input, "results"));
+    let errors = errors.unwrap_or_else(|| report_path(dir, input, "errors"));
+    let payslips = payslips.unwrap_or_else(|| {
+        dir.join(format!("{}_payslips", input.file_stem().unwrap_or_default().to_string_lossy()))
+    });
+
+    let (rows, mut rejected) = match File::open(input)
+        .and_then(|file| severance::read_terminations_csv(BufReader::new(file), &config.locale))
+    {
+        Ok(read) => read,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            return fail(FailureKind::Validation, format_args!("Could not read {}: {}", input.display(), err))
+        }
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not read {}: {}", input.display(), err)),
+    };
+    if rows.is_empty() && rejected.is_empty() {
+        println!("No terminations in {}.", input.display());
+        return 0;
+    }
+    rejected.extend(payroll.check_terminations(&rows));
+    if !rejected.is_empty() {
+        rejected.sort_by_key(|row| row.line);
+        if let Err(err) = write_rejected(&rejected, &errors) {
+            return fail(FailureKind::Storage, format_args!("Could not write {}: {}", errors.display(), err));
+        }
+        return fail(
+            FailureKind::Validation,
+            format_args!("{} rows rejected, no one terminated; see {}", rejected.len(), errors.display()),
+        );
+    }
+    let outcome = match payroll.terminate_employees(rows) {
+        Ok(outcome) => outcome,
+        Err(err) => return fail(FailureKind::from(&err), format_args!("Terminations not processed: {}", err)),
+    };
+
+    let payroll = match transaction.commit() {
+        Ok(payroll) => payroll,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err)),
+    };
+    let written = File::create(&output).and_then(|file| {
+        let mut register = RegisterCsvWriter::new(BufWriter::new(file))?;
+        for record in &outcome.records {
+            register.write_record(record)?;
+        }
+        register.finish()
+    });
+    if let Err(err) = written {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", output.display(), err));
+    }
+    let written = std::fs::create_dir_all(&payslips).and_then(|()| {
+        outcome.records.iter().try_for_each(|record| {
+            let html = render_html(record, &PayslipSeal::for_record(record, None));
+            std::fs::write(payslips.join(payslip_file_name(record, "html")), html)
+        })
+    });
+    if let Err(err) = written {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", payslips.display(), err));
+    }
+
+    let terminated: Vec<_> = payroll.terminations[payroll.terminations.len() - outcome.records.len()..]
+        .iter()
+        .collect();
+    PayrollPresentation::print_terminations(&terminated);
+    println!(
+        "Terminated {} employees; final records written to {} and payslips to {}",
+        outcome.records.len(),
+        output.display(),
+        payslips.display()
+    );
+    0
+}
+
+fn process_batch(
+    input: &Path,
+    period: &str,
+    data: Option<PathBuf>,
+    output: Option<PathBuf>,
+    errors: Option<PathBuf>,
+) -> i32 {
+    let pay_period = match period.parse::<PayPeriod>() {
+        Ok(period) => period.to_string(),
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let data = data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let dir = input.parent().unwrap_or(Path::new(""));
+    let output = output.unwrap_or_else(|| report_path(dir, input, "results"));
+    let errors = errors.unwrap_or_else(|| report_path(dir, input, "errors"));
+
+    let report = match run_batch(&config, &data, input, &pay_period, &output, &errors, false) {
+        Ok(report) => report,
+        Err(err) => return err.report(),
+    };
+    let unchanged = match report.unchanged {
+        0 => String::new(),
+        count => format!(" ({} already processed with the same input, unchanged)", count),
+    };
+    println!(
+        "Processed {} employees for {}{}; results written to {}",
+        report.processed,
+        pay_period,
+        unchanged,
+        output.display()
+    );
+    if report.rejected == 0 {
+        return 0;
+    }
+    fail(
+        FailureKind::Validation,
+        format_args!("{} rows rejected; see {}", report.rejected, errors.display()),
+    )
+}
+
+fn period_in_file_name(input: &Path) -> Option<PayPeriod> {
+    let name = input.file_stem()?.to_str()?;
+    (0..name.len()).find_map(|start| {
+        let candidate = name.get(start..start + 7)?;
+        let bytes = candidate.as_bytes();
+        let shaped = bytes[4] == b'-' && bytes.iter().enumerate().all(|(i, b)| i == 4 || b.is_ascii_digit());
+        if shaped {
+            candidate.parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+fn move_into(input: &Path, dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::rename(input, dir.join(input.file_name().unwrap_or_default()))
+}
+
+fn watch_file(config: &Config, data: &Path, input: &Path, period: Option<PayPeriod>, output: &Path) {
+    let name = input.file_name().unwrap_or_default().to_string_lossy();
+    let results = report_path(output, input, "results");
+    let errors = report_path(output, input, "errors");
+    let result = match period_in_file_name(input).or(period) {
+        Some(period) => run_batch(config, data, input, &period.to_string(), &results, &errors, true),
+        None => Err(CommandError::new(
+            FailureKind::Usage,
+            "no pay period in the file name (e.g. hours_2024-09.csv) and no --period given",
+        )),
+    };
+
+    let watched = input.parent().unwrap_or(Path::new(""));
+    let archive = match &result {
+        Ok(report) => {
+            let run = match report.run_id {
+                Some(run_id) => format!(", draft run #{}", run_id),
+                None => String::new(),
+            };
+            println!(
+                "{}: {} processed, {} unchanged, {} rejected{}",
+                name, report.processed, report.unchanged, report.rejected, run
+            );
+            watched.join("processed")
+        }
+        Err(err) => {
+            CommandError::new(err.kind, format_args!("{}: {}", name, err)).report();
+            let failure = [RejectedRow {
+                line: 0,
+                employee_id: String::new(),
+                reason: err.message.clone(),
+            }];
+            if let Err(err) = write_rejected(&failure, &errors) {
+                fail(FailureKind::Storage, format_args!("Could not write {}: {}", errors.display(), err));
+            }
+            watched.join("failed")
+        }
+    };
+    if let Err(err) = move_into(input, &archive) {
+        fail(
+            FailureKind::Storage,
+            format_args!("Could not move {} to {}: {}", name, archive.display(), err),
+        );
+    }
+}
+
+fn watch(dir: &Path, options: WatchOptions) -> i32 {
+    let period = match options.period.as_deref().map(str::parse::<PayPeriod>).transpose() {
+        Ok(period) => period,
+        Err(err) => return fail(FailureKind::Usage, err),
+    };
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let data = options.data.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    let output = options.output.unwrap_or_else(|| dir.join("output"));
+    if let Err(err) = std::fs::create_dir_all(&output) {
+        return fail(FailureKind::Storage, format_args!("Could not create {}: {}", output.display(), err));
+    }
+
+    term::status!("Watching {} for hours CSV files (results in {})", dir.display(), output.display());
+    let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
+    loop {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return fail(FailureKind::Storage, format_args!("Could not read {}: {}", dir.display(), err)),
+        };
+        let mut files: Vec<(PathBuf, u64)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Some((entry.path(), entry.metadata().ok().filter(|meta| meta.is_file())?.len())))
+            .filter(|(path, _)| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")))
+            .collect();
+        files.sort();
+
+        let mut pending = HashMap::new();
+        for (path, size) in files {
+            // A file is picked up once its size is unchanged since the previous scan, so exports that
+            // are still being written are left alone.
+            if options.once || sizes.get(&path) == Some(&size) {
+                watch_file(&config, &data, &path, period, &output);
+            } else {
+                pending.insert(path, size);
+            }
+        }
+        sizes = pending;
+
+        if options.once {
+            return 0;
+        }
+        std::thread::sleep(Duration::from_secs(options.interval.max(1)));
+    }
+}
+
+fn seed_demo(employees: usize, months: u32, file: Option<PathBuf>) -> i32 {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    let path = file.unwrap_or_else(|| PathBuf::from(&config.storage_path));
+    if path.exists() {
+        return fail(
+            FailureKind::Validation,
+            format_args!("{} already exists; remove it or pass another file name", path.display()),
+        );
+    }
+
+    let mut payroll = Payroll::new();
+    payroll.configure(&config);
+    demo::seed_demo(&mut payroll, &DemoOptions::new(employees, months));
+    match storage_at(&path).save(&payroll) {
+        Ok(()) => {
+            println!(
+                "Wrote {} demo employees and {} payroll records to {}",
+                payroll.employees.len(),
+                payroll.get_payroll_records().len(),
+                path.display()
+            );
+            0
+        }
+        Err(err) => fail(FailureKind::Storage, format_args!("Could not write {}: {}", path.display(), err)),
+    }
+}
+
+#[cfg(feature = "snapshot")]
+fn convert_data(input: &Path, output: Option<&Path>, format: crate::storage::DataFormat) -> i32 {
+    let output = output.unwrap_or(input);
+    if output != input && output.exists() {
+        return fail(
+            FailureKind::Validation,
+            format_args!("{} already exists; remove it or pass another file name", output.display()),
+        );
+    }
+    let payroll = match storage_at(input).load() {
+        Ok(payroll) => payroll,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not load {}: {}", input.display(), err)),
+    };
+    if output != input {
+        payroll.revision.store(0, Ordering::SeqCst);
+        // The converted file keeps its history: the ledger goes with it.
+        let (ledger, copy) = (PayrollLedger::beside(input), PayrollLedger::beside(output));
+        if ledger.path().exists() {
+            if let Err(err) = std::fs::copy(ledger.path(), copy.path()) {
+                return fail(FailureKind::Storage, format_args!("Could not write {}: {}", copy.path().display(), err));
+            }
+        }
+    }
+    let before = std::fs::metadata(input).map(|metadata| metadata.len()).unwrap_or(0);
+    if let Err(err) = storage_at(output).with_format(format).save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Could not write {}: {}", output.display(), err));
+    }
+    let after = std::fs::metadata(output).map(|metadata| metadata.len()).unwrap_or(0);
+    println!(
+        "Wrote {} employees and {} payroll records to {} as {} ({} bytes, {} before)",
+        payroll.employees.len(),
+        payroll.get_payroll_records().len(),
+        output.display(),
+        format,
+        after,
+        before
+    );
+    0
+}
+
+#[cfg(feature = "postgres")]
+fn import_database(file: &Path, url: &str) -> i32 {
+    let storage = match crate::postgres::PostgresStorage::connect(url) {
+        Ok(storage) => storage,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not connect to the database: {}", err)),
+    };
+    match storage.revision() {
+        Ok(0) => {}
+        Ok(revision) => {
+            return fail(
+                FailureKind::Usage,
+                format_args!("The database already holds payroll data (revision {}); nothing imported", revision),
+            )
+        }
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not read the database: {}", err)),
+    }
+    let payroll = match storage_at(file).load() {
+        Ok(payroll) => payroll,
+        Err(err) => return fail(FailureKind::Storage, format_args!("Could not load {}: {}", file.display(), err)),
+    };
+    payroll.revision.store(0, Ordering::SeqCst);
+    if let Err(err) = storage.save(&payroll) {
+        return fail(FailureKind::Storage, format_args!("Import failed: {}", err));
+    }
+    term::status!(
+        "Imported {} employees and {} payroll records from {}",
+        payroll.employees.len(),
+        payroll.get_payroll_records().len(),
+        file.display()
+    );
+    0
+}
+
+#[cfg(feature = "server")]
+fn serve(addr: &str, data_file: Option<PathBuf>, database: Option<String>, cache: Option<String>) -> i32 {
+    use crate::server::{self, ServerState};
+    use crate::shared::SharedPayroll;
+
+    let addr = match addr.parse() {
+        Ok(addr) => addr,
+        Err(err) => return fail(FailureKind::Usage, format_args!("Invalid listen address {}: {}", addr, err)),
+    };
+
+    let mut state = ServerState::new(SharedPayroll::default());
+    if let Some(path) = data_file {
+        let storage = storage_at(&path);
+        match storage.load() {
+            Ok(payroll) => state = ServerState::new(SharedPayroll::new(payroll)).with_storage(storage),
+            Err(err) => return fail(FailureKind::Storage, format_args!("Could not load {}: {}", path.display(), err)),
+        }
+    } else if let Some(url) = database {
+        #[cfg(feature = "postgres")]
+        match crate::postgres::PostgresStorage::connect(&url).and_then(|storage| Ok((storage.load()?, storage))) {
+            Ok((payroll, storage)) => state = ServerState::new(SharedPayroll::new(payroll)).with_storage(storage),
+            Err(err) => return fail(FailureKind::Storage, format_args!("Could not load the database: {}", err)),
+        }
+        #[cfg(not(feature = "postgres"))]
+        {
+            let _ = url;
+            return fail(
+                FailureKind::Usage,
+                "PostgreSQL storage is not available in this build; rebuild with `--features postgres`",
+            );
+        }
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(code) => return code,
+    };
+    state.payroll().write(|payroll| payroll.configure(&config));
+    let schedule = config.schedule.draft_run;
+    if !config.api.is_enabled() {
+        eprintln!(
+            "{}",
+            term::warning("No API keys or JWT secret configured; the API accepts every request (see [api])")
+        );
+    }
+    let mut state = state
+        .with_notifiers(config.notifications)
+        .with_pay_day(config.schedule.pay_day)
+        .with_auth(config.api);
+    let server = config.server;
+    if let Some(limiter) = crate::rate_limit::RateLimiter::new(server.rate_limit, server.rate_limit_burst) {
+        state = state.with_rate_limit(limiter);
+    }
+    if let Some(path) = server.access_log {
+        match crate::access_log::AccessLog::open(&path) {
+            Ok(log) => state = state.with_access_log(log),
+            Err(err) => return fail(FailureKind::Storage, format_args!("Could not open access log {}: {}", path, err)),
+        }
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(err) => return fail(FailureKind::General, format_args!("Could not start runtime: {}", err)),
+    };
+    #[cfg(feature = "redis")]
+    let state = match cache.map(|url| runtime.block_on(crate::cache::RedisCache::connect(&url))).transpose() {
+        Ok(Some(cache)) => state.with_cache(cache),
+        Ok(None) => state,
+        Err(err) => return fail(FailureKind::General, format_args!("Could not connect to the cache: {}", err)),
+    };
+    #[cfg(not(feature = "redis"))]
+    if cache.is_some() {
+        return fail(
+            FailureKind::Usage,
+            "Redis caching is not available in this build; rebuild with `--features redis`",
+        );
+    }
+    if let Some(schedule) = schedule {
+        term::status!("Draft runs scheduled at '{}'", schedule);
+        runtime.spawn(server::run_schedule(state.clone(), schedule));
+    }
+    term::status!("Payroll API listening on http://{}", addr);
+    match runtime.block_on(server::serve(addr, state)) {
+        Ok(()) => 0,
+        Err(err) => fail(FailureKind::General, format_args!("Server error: {}", err)),
+    }
+}
@@ -0,0 +1,239 @@
+use crate::error::PayrollError;
+use crate::payroll::EmployeeData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeRequestStatus {
+    Pending,
+    Approved,
+    Rejected(String),
+}
+
+impl fmt::Display for ChangeRequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeRequestStatus::Pending => write!(f, "pending"),
+            ChangeRequestStatus::Approved => write!(f, "approved"),
+            ChangeRequestStatus::Rejected(reason) => write!(f, "rejected: {}", reason),
+        }
+    }
+}
+
+/// A proposed promotion or increment. It changes nothing until the named approver approves it;
+/// it then becomes a `SalaryHistoryEntry`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SalaryChangeRequest {
+    pub id: u32,
+    pub employee_id: String,
+    /// New grade, if the change is a promotion.
+    pub grade: Option<String>,
+    /// New monthly base salary (hourly rate for contract employees).
+    pub rate: f64,
+    pub effective: PayPeriod,
+    pub approver: String,
+    pub requested: DateTime<Utc>,
+    pub status: ChangeRequestStatus,
+    pub decided: Option<DateTime<Utc>>,
+}
+
+impl SalaryChangeRequest {
+    pub fn is_pending(&self) -> bool {
+        self.status == ChangeRequestStatus::Pending
+    }
+
+    /// Whether `name` is the approver the request was addressed to.
+    pub fn is_approver(&self, name: &str) -> bool {
+        self.approver.trim().eq_ignore_ascii_case(name.trim())
+    }
+}
+
+/// A salary change in effect from `effective` on. The latest entry at or before a pay period
+/// decides the employee's rate (and grade) when that period is processed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SalaryHistoryEntry {
+    pub employee_id: String,
+    pub effective: PayPeriod,
+    pub previous_rate: f64,
+    pub rate: f64,
+    pub grade: Option<String>,
+    pub request_id: u32,
+    pub approved_by: String,
+    pub recorded: DateTime<Utc>,
+}
+
+impl SalaryHistoryEntry {
+    /// The change relative to the previous rate, in percent.
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.previous_rate != 0.0).then(|| (self.rate - self.previous_rate) / self.previous_rate * 100.0)
+    }
+}
+
+/// Every salary change requested, pending or decided.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SalaryChanges {
+    requests: Vec<SalaryChangeRequest>,
+}
+
+impl SalaryChanges {
+    pub fn get(&self, id: u32) -> Option<&SalaryChangeRequest> {
+        self.requests.iter().find(|request| request.id == id)
+    }
+
+    /// The request with `id`, as long as it still waits for `approver` to decide it.
+    pub fn undecided(&self, id: u32, approver: &str) -> Result<&SalaryChangeRequest, PayrollError> {
+        let request = self.get(id).ok_or(PayrollError::ChangeRequestNotFound(id))?;
+        if !request.is_pending() {
+            return Err(PayrollError::ChangeRequestDecided(id));
+        }
+        if !request.is_approver(approver) {
+            return Err(PayrollError::NotApprover(id, request.approver.clone()));
+        }
+        Ok(request)
+    }
+
+    /// Pending requests, oldest first.
+    pub fn pending(&self) -> Vec<&SalaryChangeRequest> {
+        self.requests.iter().filter(|request| request.is_pending()).collect()
+    }
+
+    pub(crate) fn request(
+        &mut self,
+        employee_id: &str,
+        rate: f64,
+        grade: Option<String>,
+        effective: PayPeriod,
+        approver: &str,
+    ) -> Result<&SalaryChangeRequest, PayrollError> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(PayrollError::InvalidSalaryChange("the new rate must be a positive amount".to_string()));
+        }
+        if approver.trim().is_empty() {
+            return Err(PayrollError::InvalidSalaryChange("an approver is required".to_string()));
+        }
+        let id = self.requests.iter().map(|request| request.id).max().unwrap_or(0) + 1;
+        self.requests.push(SalaryChangeRequest {
+            id,
+            employee_id: employee_id.to_string(),
+            grade: grade.filter(|grade| !grade.trim().is_empty()),
+            rate,
+            effective,
+            approver: approver.trim().to_string(),
+            requested: Utc::now(),
+            status: ChangeRequestStatus::Pending,
+            decided: None,
+        });
+        Ok(self.requests.last().expect("request was just pushed"))
+    }
+
+    /// Approves or rejects a pending request on behalf of its approver.
+    pub(crate) fn decide(
+        &mut self,
+        id: u32,
+        approver: &str,
+        status: ChangeRequestStatus,
+    ) -> Result<&SalaryChangeRequest, PayrollError> {
+        self.undecided(id, approver)?;
+        let request = self
+            .requests
+            .iter_mut()
+            .find(|request| request.id == id)
+            .expect("request was just found");
+        request.status = status;
+        request.decided = Some(Utc::now());
+        Ok(request)
+    }
+
+    /// Moves the employee's requests, and the ones addressed to them, to their pseudonym. Returns
+    /// how many were theirs.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        let mut renamed = 0;
+        for request in &mut self.requests {
+            renamed += renaming.id(&mut request.employee_id);
+            renaming.id(&mut request.approver);
+        }
+        renamed
+    }
+}
+
+impl Deref for SalaryChanges {
+    type Target = [SalaryChangeRequest];
+
+    fn deref(&self) -> &[SalaryChangeRequest] {
+        &self.requests
+    }
+}
+
+/// Every approved salary change, which decides the rate each pay period is processed at.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SalaryHistory {
+    entries: Vec<SalaryHistoryEntry>,
+}
+
+impl SalaryHistory {
+    /// An employee's changes, oldest effective period first.
+    pub fn of(&self, employee_id: &str) -> Vec<&SalaryHistoryEntry> {
+        let mut history: Vec<&SalaryHistoryEntry> =
+            self.entries.iter().filter(|entry| entry.employee_id == employee_id).collect();
+        history.sort_by_key(|entry| (entry.effective, entry.recorded));
+        history
+    }
+
+    /// The latest change in effect in `period`, if any.
+    pub fn in_effect(&self, employee_id: &str, period: &PayPeriod) -> Option<&SalaryHistoryEntry> {
+        self.of(employee_id).into_iter().rev().find(|entry| entry.effective <= *period)
+    }
+
+    /// Sets the rate and grade in effect in `pay_period` on `employee`. Periods before the first
+    /// change get the rate that change replaced.
+    pub fn apply_to(&self, mut employee: EmployeeData, pay_period: &str) -> EmployeeData {
+        let Ok(period) = pay_period.parse::<PayPeriod>() else {
+            return employee;
+        };
+        let history = self.of(employee.as_employee().employee_id());
+        match history.iter().rev().find(|entry| entry.effective <= period) {
+            Some(entry) => {
+                employee.set_base_rate(entry.rate);
+                if entry.grade.is_some() {
+                    employee.profile_mut().grade = entry.grade.clone();
+                }
+            }
+            None => {
+                if let Some(first) = history.first() {
+                    employee.set_base_rate(first.previous_rate);
+                }
+            }
+        }
+        employee
+    }
+
+    pub(crate) fn push(&mut self, entry: SalaryHistoryEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Moves the employee's changes, and the ones they approved, to their pseudonym. Returns how
+    /// many were theirs.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        let mut renamed = 0;
+        for entry in &mut self.entries {
+            renamed += renaming.id(&mut entry.employee_id);
+            renaming.id(&mut entry.approved_by);
+        }
+        renamed
+    }
+}
+
+impl Deref for SalaryHistory {
+    type Target = [SalaryHistoryEntry];
+
+    fn deref(&self) -> &[SalaryHistoryEntry] {
+        &self.entries
+    }
+}
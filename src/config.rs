@@ -0,0 +1,597 @@
+use crate::attendance::AttendancePolicy;
+use crate::auth::{ApiAuthConfig, JwtConfig};
+use crate::calc::{self, AllowancePeriod, CalculationSettings, TaxRounding, TaxTable};
+use crate::calendar;
+use crate::error::ConfigError;
+use crate::expense::ExpensePolicy;
+use crate::insurance::InsurancePlan;
+use crate::natura::NaturaRules;
+use crate::notify::NotifierConfig;
+use crate::overtime::{OvertimeExcess, OvertimePolicy};
+use crate::privacy;
+use crate::retention;
+use crate::review::BonusPolicy;
+use crate::rule_history::EffectiveRules;
+use crate::schedule::Schedule;
+use crate::tax::{self, TaxRegime};
+use crate::travel::PerDiemRates;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const CONFIG_FILE: &str = "employee.toml";
+pub const CONFIG_ENV: &str = "EMPLOYEE_CONFIG";
+pub const DEFAULT_STORAGE_PATH: &str = "payroll.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencyFormat {
+    pub symbol: String,
+    pub decimal_places: usize,
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+}
+
+impl Default for CurrencyFormat {
+    fn default() -> Self {
+        Self {
+            symbol: "Rp".to_string(),
+            decimal_places: 2,
+            thousands_separator: String::new(),
+            decimal_separator: ".".to_string(),
+        }
+    }
+}
+
+impl CurrencyFormat {
+    pub fn format(&self, amount: f64) -> String {
+        let fixed = format!("{:.*}", self.decimal_places, amount.abs());
+        let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+        let mut grouped = String::new();
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                grouped.push_str(&self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+        if !fraction.is_empty() {
+            grouped.push_str(&self.decimal_separator);
+            grouped.push_str(fraction);
+        }
+
+        let sign = if amount < 0.0 && fixed.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            "-"
+        } else {
+            ""
+        };
+        format!("{}{} {}", sign, self.symbol, grouped)
+    }
+}
+
+fn uses_decimal_comma(locale: &str) -> bool {
+    let language = locale.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    matches!(
+        language.as_str(),
+        "id" | "ms" | "de" | "fr" | "es" | "it" | "nl" | "pt" | "ru" | "tr" | "pl" | "vi" | "da" | "sv" | "nb" | "fi"
+    )
+}
+
+fn is_grouped(digits: &str, separator: char) -> bool {
+    let mut groups = digits.split(separator);
+    let first = groups.next().unwrap_or("");
+    (1..=3).contains(&first.len()) && groups.all(|group| group.len() == 3)
+}
+
+/// Parses a number typed the way `locale` writes it, e.g. `7.500.000` or `7500000,50` for `id-ID`.
+/// A plain `.` decimal point is always accepted, so `0.02` works in every locale.
+pub fn parse_number(input: &str, locale: &str) -> Result<f64, String> {
+    let invalid = || format!("Unrecognized number '{}'", input.trim());
+    let cleaned: String = input.chars().filter(|c| !c.is_whitespace() && *c != '_').collect();
+    let (sign, unsigned) = match cleaned.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", cleaned.as_str()),
+    };
+    let group_char = if uses_decimal_comma(locale) { '.' } else { ',' };
+
+    let decimal_char = match (unsigned.rfind('.'), unsigned.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+        (Some(_), None) => Some('.'),
+        (None, Some(_)) => Some(','),
+        (None, None) => None,
+    }
+    .filter(|&separator| {
+        let repeated = unsigned.matches(separator).count() > 1;
+        let both = unsigned.contains('.') && unsigned.contains(',');
+        both || !(repeated || (separator == group_char && is_grouped(unsigned, separator)))
+    });
+
+    let (whole, fraction) = match decimal_char {
+        Some(separator) => unsigned.rsplit_once(separator).ok_or_else(invalid)?,
+        None => (unsigned, ""),
+    };
+    let whole = match [',', '.'].into_iter().find(|separator| whole.contains(*separator)) {
+        Some(separator) if is_grouped(whole, separator) => whole.replace(separator, ""),
+        Some(_) => return Err(invalid()),
+        None => whole.to_string(),
+    };
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(&whole) || !is_digits(fraction) {
+        return Err(invalid());
+    }
+    format!("{}{}.{}", sign, whole, if fraction.is_empty() { "0" } else { fraction })
+        .parse()
+        .map_err(|_| invalid())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompanyConfig {
+    pub name: String,
+    pub npwp: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaxConfig {
+    /// The company's tax regime: a built-in table or one of `regimes`.
+    pub table: String,
+    pub rounding: TaxRounding,
+    /// PPh 26 rate for non-resident contractors from countries without a treaty rate.
+    pub pph26_rate: f64,
+    /// Reduced PPh 26 rates under tax treaties, by two-letter country code.
+    pub treaty_rates: BTreeMap<String, f64>,
+    pub regimes: Vec<TaxRegime>,
+}
+
+impl Default for TaxConfig {
+    fn default() -> Self {
+        Self {
+            table: calc::FULLTIME_TAX_TABLE_ID.to_string(),
+            rounding: TaxRounding::Exact,
+            pph26_rate: tax::PPH26_RATE,
+            treaty_rates: BTreeMap::new(),
+            regimes: Vec::new(),
+        }
+    }
+}
+
+impl TaxConfig {
+    /// The PPh 26 rate for a resident of `country`: its treaty rate, or the default rate.
+    pub fn pph26_rate_for(&self, country: &str) -> f64 {
+        self.treaty_rates
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(country))
+            .map_or(self.pph26_rate, |(_, rate)| *rate)
+    }
+
+    /// Every regime the company or an employee can be taxed under: the built-in tables, then `regimes`.
+    pub fn regime_ids(&self) -> Vec<&str> {
+        calc::FULLTIME_TAX_TABLES
+            .iter()
+            .map(|(id, _)| *id)
+            .chain(self.regimes.iter().map(|regime| regime.id.as_str()))
+            .collect()
+    }
+
+    /// The built-in table or configured regime `id` names.
+    pub fn regime(&self, id: &str) -> Option<TaxTable> {
+        tax::find_regime(&self.regimes, id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BpjsConfig {
+    pub kesehatan_rate: f64,
+    pub ketenagakerjaan_rate: f64,
+}
+
+impl Default for BpjsConfig {
+    fn default() -> Self {
+        Self {
+            kesehatan_rate: calc::BPJS_KESEHATAN_RATE,
+            ketenagakerjaan_rate: calc::BPJS_KETENAGAKERJAAN_RATE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OvertimeConfig {
+    pub standard_monthly_hours: f64,
+    pub multiplier: f64,
+    /// Most overtime hours paid to a full-time employee in a month.
+    pub monthly_cap: Option<f64>,
+    /// Only overtime covered by approved requests is within policy.
+    pub require_approval: bool,
+    /// Whether overtime beyond the cap or approval is excluded from pay or paid and flagged.
+    pub excess: OvertimeExcess,
+}
+
+impl Default for OvertimeConfig {
+    fn default() -> Self {
+        Self {
+            standard_monthly_hours: calc::STANDARD_MONTHLY_HOURS,
+            multiplier: calc::OVERTIME_MULTIPLIER,
+            monthly_cap: None,
+            require_approval: false,
+            excess: OvertimeExcess::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AllowanceConfig {
+    pub amount: f64,
+    #[serde(with = "allowance_period_name")]
+    pub period: AllowancePeriod,
+}
+
+impl Default for AllowanceConfig {
+    fn default() -> Self {
+        Self {
+            amount: 0.0,
+            period: AllowancePeriod::Monthly,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub draft_run: Option<Schedule>,
+    /// Day of the month salaries are paid, shown in the payroll calendar.
+    pub pay_day: u32,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            draft_run: None,
+            pay_day: calendar::DEFAULT_PAY_DAY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// JSON Lines file recording every API request: who made it, whose data it concerned and when.
+    pub access_log: Option<String>,
+    /// Requests each client may make per minute; 0 for no limit.
+    pub rate_limit: u32,
+    /// Requests a client may make at once before being held to the rate; 0 for a second's worth.
+    pub rate_limit_burst: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Years after their last day before a terminated employee may be pseudonymized.
+    pub pseudonymize_after_years: u32,
+    /// Years after a pay period ends before `payroll purge` replaces its records with a summary.
+    pub purge_records_after_years: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            pseudonymize_after_years: privacy::DEFAULT_RETENTION_YEARS,
+            purge_records_after_years: retention::DEFAULT_PURGE_YEARS,
+        }
+    }
+}
+
+mod allowance_period_name {
+    use crate::calc::AllowancePeriod;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(period: &AllowancePeriod, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(period.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AllowancePeriod, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub company: CompanyConfig,
+    pub storage_path: String,
+    pub locale: String,
+    pub currency: CurrencyFormat,
+    pub tax: TaxConfig,
+    pub bpjs: BpjsConfig,
+    /// Tax tables and BPJS rates in force before the current ones, for backdated periods.
+    pub rule_history: Vec<EffectiveRules>,
+    pub overtime: OvertimeConfig,
+    pub allowance: AllowanceConfig,
+    pub attendance: AttendancePolicy,
+    pub travel: PerDiemRates,
+    pub expenses: ExpensePolicy,
+    pub insurance: Vec<InsurancePlan>,
+    pub natura: NaturaRules,
+    pub bonus: BonusPolicy,
+    pub notifications: Vec<NotifierConfig>,
+    pub schedule: ScheduleConfig,
+    pub server: ServerConfig,
+    pub retention: RetentionConfig,
+    /// API keys and tokens the server accepts.
+    pub api: ApiAuthConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            company: CompanyConfig::default(),
+            storage_path: DEFAULT_STORAGE_PATH.to_string(),
+            locale: "id-ID".to_string(),
+            currency: CurrencyFormat::default(),
+            tax: TaxConfig::default(),
+            bpjs: BpjsConfig::default(),
+            rule_history: Vec::new(),
+            overtime: OvertimeConfig::default(),
+            allowance: AllowanceConfig::default(),
+            attendance: AttendancePolicy::default(),
+            travel: PerDiemRates::default(),
+            expenses: ExpensePolicy::default(),
+            insurance: Vec::new(),
+            natura: NaturaRules::default(),
+            bonus: BonusPolicy::default(),
+            notifications: Vec::new(),
+            schedule: ScheduleConfig::default(),
+            server: ServerConfig::default(),
+            retention: RetentionConfig::default(),
+            api: ApiAuthConfig::default(),
+        }
+    }
+}
+
+fn parse_env<T: FromStr>(name: &str, value: &str) -> Result<T, ConfigError>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|err| ConfigError::Parse(format!("{}: {}", name, err)))
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&contents).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn path() -> PathBuf {
+        std::env::var_os(CONFIG_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(CONFIG_FILE))
+    }
+
+    pub fn load_default() -> Result<Self, ConfigError> {
+        let mut config = Self::load(Self::path())?;
+        config.apply_overrides(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        self.validate()?;
+        let contents = toml::to_string(self).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn apply_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(value) = lookup("EMPLOYEE_STORAGE_PATH") {
+            self.storage_path = value;
+        }
+        if let Some(value) = lookup("EMPLOYEE_LOCALE") {
+            self.locale = value;
+        }
+        if let Some(value) = lookup("EMPLOYEE_CURRENCY_SYMBOL") {
+            self.currency.symbol = value;
+        }
+        if let Some(value) = lookup("EMPLOYEE_TAX_TABLE") {
+            self.tax.table = value;
+        }
+        if let Some(value) = lookup("EMPLOYEE_TAX_ROUNDING") {
+            self.tax.rounding = parse_env("EMPLOYEE_TAX_ROUNDING", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_PPH26_RATE") {
+            self.tax.pph26_rate = parse_env("EMPLOYEE_PPH26_RATE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_BPJS_KESEHATAN_RATE") {
+            self.bpjs.kesehatan_rate = parse_env("EMPLOYEE_BPJS_KESEHATAN_RATE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_BPJS_KETENAGAKERJAAN_RATE") {
+            self.bpjs.ketenagakerjaan_rate = parse_env("EMPLOYEE_BPJS_KETENAGAKERJAAN_RATE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_STANDARD_MONTHLY_HOURS") {
+            self.overtime.standard_monthly_hours = parse_env("EMPLOYEE_STANDARD_MONTHLY_HOURS", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_OVERTIME_MULTIPLIER") {
+            self.overtime.multiplier = parse_env("EMPLOYEE_OVERTIME_MULTIPLIER", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_OVERTIME_CAP") {
+            self.overtime.monthly_cap = match value.trim() {
+                "" => None,
+                value => Some(parse_env("EMPLOYEE_OVERTIME_CAP", value)?),
+            };
+        }
+        if let Some(value) = lookup("EMPLOYEE_OVERTIME_REQUIRE_APPROVAL") {
+            self.overtime.require_approval = parse_env("EMPLOYEE_OVERTIME_REQUIRE_APPROVAL", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_OVERTIME_EXCESS") {
+            self.overtime.excess = parse_env("EMPLOYEE_OVERTIME_EXCESS", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_DEFAULT_ALLOWANCE") {
+            self.allowance.amount = parse_env("EMPLOYEE_DEFAULT_ALLOWANCE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_DEFAULT_ALLOWANCE_PERIOD") {
+            self.allowance.period = parse_env("EMPLOYEE_DEFAULT_ALLOWANCE_PERIOD", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_ATTENDANCE_GRACE_MINUTES") {
+            self.attendance.grace_minutes = parse_env("EMPLOYEE_ATTENDANCE_GRACE_MINUTES", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_LATE_PENALTY") {
+            self.attendance.late_penalty = parse_env("EMPLOYEE_LATE_PENALTY", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_EARLY_LEAVE_PENALTY") {
+            self.attendance.early_leave_penalty = parse_env("EMPLOYEE_EARLY_LEAVE_PENALTY", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_MEAL_ALLOWANCE") {
+            self.attendance.meal_allowance = parse_env("EMPLOYEE_MEAL_ALLOWANCE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_TRANSPORT_ALLOWANCE") {
+            self.attendance.transport_allowance = parse_env("EMPLOYEE_TRANSPORT_ALLOWANCE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_MEAL_ALLOWANCE_TAXABLE") {
+            self.attendance.meal_allowance_taxable = parse_env("EMPLOYEE_MEAL_ALLOWANCE_TAXABLE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_TRANSPORT_ALLOWANCE_TAXABLE") {
+            self.attendance.transport_allowance_taxable = parse_env("EMPLOYEE_TRANSPORT_ALLOWANCE_TAXABLE", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_EXPENSE_OVER_CAP") {
+            self.expenses.over_cap = parse_env("EMPLOYEE_EXPENSE_OVER_CAP", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_BONUS_TARGET_MONTHS") {
+            self.bonus.target_months = parse_env("EMPLOYEE_BONUS_TARGET_MONTHS", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_DRAFT_RUN_SCHEDULE") {
+            self.schedule.draft_run = match value.trim() {
+                "" => None,
+                value => Some(parse_env("EMPLOYEE_DRAFT_RUN_SCHEDULE", value)?),
+            };
+        }
+        if let Some(value) = lookup("EMPLOYEE_PAY_DAY") {
+            self.schedule.pay_day = parse_env("EMPLOYEE_PAY_DAY", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_ACCESS_LOG") {
+            self.server.access_log = Some(value).filter(|path| !path.trim().is_empty());
+        }
+        if let Some(value) = lookup("EMPLOYEE_RATE_LIMIT") {
+            self.server.rate_limit = parse_env("EMPLOYEE_RATE_LIMIT", &value)?;
+        }
+        if let Some(value) = lookup("EMPLOYEE_API_JWT_SECRET") {
+            self.api.jwt.get_or_insert_with(JwtConfig::default).secret = value;
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| Err(ConfigError::Parse(message));
+        for (index, regime) in self.tax.regimes.iter().enumerate() {
+            regime.validate().map_err(ConfigError::Parse)?;
+            let mut taken = calc::FULLTIME_TAX_TABLES
+                .iter()
+                .map(|(id, _)| *id)
+                .chain(self.tax.regimes[..index].iter().map(|other| other.id.as_str()));
+            if taken.any(|id| id.eq_ignore_ascii_case(regime.id.trim())) {
+                return invalid(format!("tax regime {} is defined twice", regime.id));
+            }
+        }
+        if self.tax.regime(&self.tax.table).is_none() {
+            return invalid(format!("unknown tax table '{}'", self.tax.table));
+        }
+        for (index, rules) in self.rule_history.iter().enumerate() {
+            rules.validate().map_err(ConfigError::Parse)?;
+            if self.tax.regime(&rules.tax_table).is_none() {
+                return invalid(format!("unknown tax table '{}' in rule_history", rules.tax_table));
+            }
+            if self.rule_history[..index].iter().any(|other| other.until == rules.until) {
+                return invalid(format!("rule_history has two entries until {}", rules.until));
+            }
+        }
+        for (country, rate) in &self.tax.treaty_rates {
+            if !(0.0..=1.0).contains(rate) {
+                return invalid(format!("tax.treaty_rates.{} must be between 0 and 1", country));
+            }
+        }
+        for (name, rate) in [
+            ("tax.pph26_rate", self.tax.pph26_rate),
+            ("bpjs.kesehatan_rate", self.bpjs.kesehatan_rate),
+            ("bpjs.ketenagakerjaan_rate", self.bpjs.ketenagakerjaan_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return invalid(format!("{} must be between 0 and 1", name));
+            }
+        }
+        if self.overtime.standard_monthly_hours <= 0.0 {
+            return invalid("overtime.standard_monthly_hours must be positive".to_string());
+        }
+        if self.overtime.multiplier < 0.0 {
+            return invalid("overtime.multiplier must not be negative".to_string());
+        }
+        if self.overtime.monthly_cap.is_some_and(|cap| !cap.is_finite() || cap < 0.0) {
+            return invalid("overtime.monthly_cap must not be negative".to_string());
+        }
+        if self.allowance.amount < 0.0 {
+            return invalid("allowance.amount must not be negative".to_string());
+        }
+        self.attendance.validate().map_err(ConfigError::Parse)?;
+        self.travel.validate().map_err(ConfigError::Parse)?;
+        self.expenses.validate().map_err(ConfigError::Parse)?;
+        for (index, plan) in self.insurance.iter().enumerate() {
+            plan.validate().map_err(ConfigError::Parse)?;
+            if self.insurance[..index].iter().any(|other| other.code.eq_ignore_ascii_case(&plan.code)) {
+                return invalid(format!("insurance plan {} is defined twice", plan.code));
+            }
+        }
+        self.natura.validate().map_err(ConfigError::Parse)?;
+        self.bonus.validate().map_err(ConfigError::Parse)?;
+        if let Some(schedule) = &self.schedule.draft_run {
+            if schedule.next_after(chrono::Local::now().naive_local()).is_none() {
+                return invalid(format!("schedule.draft_run '{}' never fires", schedule));
+            }
+        }
+        if !(1..=31).contains(&self.schedule.pay_day) {
+            return invalid(format!("schedule.pay_day must be between 1 and 31, got {}", self.schedule.pay_day));
+        }
+        if self.retention.purge_records_after_years == 0 {
+            return invalid("retention.purge_records_after_years must be at least 1".to_string());
+        }
+        self.api.validate().map_err(ConfigError::Parse)?;
+        Ok(())
+    }
+
+    pub fn parse_number(&self, input: &str) -> Result<f64, String> {
+        parse_number(input, &self.locale)
+    }
+
+    pub fn overtime_policy(&self) -> OvertimePolicy {
+        OvertimePolicy {
+            monthly_cap: self.overtime.monthly_cap,
+            require_approval: self.overtime.require_approval,
+            excess: self.overtime.excess,
+        }
+    }
+
+    /// The calculation settings for the company.
+    pub fn settings(&self) -> CalculationSettings {
+        CalculationSettings {
+            standard_monthly_hours: self.overtime.standard_monthly_hours,
+            overtime_multiplier: self.overtime.multiplier,
+            bpjs_kesehatan_rate: self.bpjs.kesehatan_rate,
+            bpjs_ketenagakerjaan_rate: self.bpjs.ketenagakerjaan_rate,
+            tax_table: self.tax.regime(&self.tax.table).unwrap_or(TaxTable::FULLTIME),
+            tax_rounding: self.tax.rounding,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::calc::{self, CalculationSettings, TaxRounding, TaxTable};
+use crate::payroll::EmployeeData;
+use crate::tax::{PtkpStatus, TaxBracket, CONTRACT_TAX_RATE, CONTRACT_TAX_TABLE_ID, PPH26_TAX_TABLE_ID};
+use serde::{Deserialize, Serialize};
+
+/// Version of the calculation itself, kept on each record. Bump it whenever a change to the
+/// calculation code changes what the same employee and settings are paid, so `Payroll::verify` can
+/// tell such records from corrupted ones.
+pub const CALCULATION_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OvertimeRule {
+    pub standard_hours: f64,
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CalculationContext {
+    pub engine_version: String,
+    /// `CALCULATION_VERSION` when the record was calculated; 0 for records from before versioning.
+    #[serde(default)]
+    pub calculation_version: u32,
+    pub tax_table: String,
+    pub tax_brackets: Vec<TaxBracket>,
+    pub ptkp_status: Option<PtkpStatus>,
+    pub ptkp_allowance: f64,
+    pub bpjs_kesehatan_rate: f64,
+    pub bpjs_ketenagakerjaan_rate: f64,
+    pub overtime: Option<OvertimeRule>,
+    /// Missing on records calculated before tax was rounded, which recompute without rounding.
+    #[serde(default = "unrounded")]
+    pub tax_rounding: TaxRounding,
+}
+
+fn unrounded() -> TaxRounding {
+    TaxRounding::Exact
+}
+
+impl CalculationContext {
+    pub fn for_employee(employee: &EmployeeData) -> Self {
+        Self::with_settings(employee, &CalculationSettings::DEFAULT)
+    }
+
+    pub fn with_settings(employee: &EmployeeData, settings: &CalculationSettings) -> Self {
+        match employee {
+            EmployeeData::Fulltime(emp) => Self {
+                engine_version: env!("CARGO_PKG_VERSION").to_string(),
+                calculation_version: CALCULATION_VERSION,
+                tax_table: settings.tax_table.id().to_string(),
+                tax_brackets: settings.tax_table.brackets().to_vec(),
+                ptkp_status: emp.ptkp_status,
+                ptkp_allowance: emp.ptkp_status.map(|status| status.annual_allowance()).unwrap_or(0.0),
+                bpjs_kesehatan_rate: settings.bpjs_kesehatan_rate,
+                bpjs_ketenagakerjaan_rate: settings.bpjs_ketenagakerjaan_rate,
+                overtime: Some(OvertimeRule {
+                    standard_hours: settings.standard_monthly_hours,
+                    multiplier: settings.overtime_multiplier,
+                }),
+                tax_rounding: settings.tax_rounding,
+            },
+            EmployeeData::Contract(emp) => {
+                let (tax_table, rate) = match &emp.profile.non_resident {
+                    Some(residency) => (PPH26_TAX_TABLE_ID, residency.rate),
+                    None => (CONTRACT_TAX_TABLE_ID, CONTRACT_TAX_RATE),
+                };
+                Self {
+                    engine_version: env!("CARGO_PKG_VERSION").to_string(),
+                    calculation_version: CALCULATION_VERSION,
+                    tax_table: tax_table.to_string(),
+                    tax_brackets: vec![TaxBracket { up_to: None, rate }],
+                    ptkp_status: None,
+                    ptkp_allowance: 0.0,
+                    bpjs_kesehatan_rate: 0.0,
+                    bpjs_ketenagakerjaan_rate: 0.0,
+                    overtime: None,
+                    tax_rounding: settings.tax_rounding,
+                }
+            }
+        }
+    }
+
+    pub fn settings(&self) -> CalculationSettings {
+        let defaults = CalculationSettings::DEFAULT;
+        let overtime = self.overtime.unwrap_or(OvertimeRule {
+            standard_hours: defaults.standard_monthly_hours,
+            multiplier: defaults.overtime_multiplier,
+        });
+        // Flat-rate tables are not bracket tables full-time pay is taxed on, so they get the default.
+        let flat_rate = [CONTRACT_TAX_TABLE_ID, PPH26_TAX_TABLE_ID].contains(&self.tax_table.as_str());
+        let tax_table = if self.tax_brackets.is_empty() || flat_rate {
+            calc::fulltime_tax_table(&self.tax_table)
+        } else {
+            TaxTable::new(&self.tax_table, &self.tax_brackets)
+        };
+        CalculationSettings {
+            standard_monthly_hours: overtime.standard_hours,
+            overtime_multiplier: overtime.multiplier,
+            bpjs_kesehatan_rate: self.bpjs_kesehatan_rate,
+            bpjs_ketenagakerjaan_rate: self.bpjs_ketenagakerjaan_rate,
+            tax_table: tax_table.unwrap_or(defaults.tax_table),
+            tax_rounding: self.tax_rounding,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use crate::error::PayrollError;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{DateTime, Duration, Local, Utc};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+pub const MIN_PIN_LENGTH: usize = 4;
+pub const MAX_PIN_LENGTH: usize = 12;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 64;
+pub const TEMPORARY_PIN_LENGTH: usize = 6;
+/// Wrong PINs in a row before sign-in is locked for `LOCKOUT_MINUTES`.
+pub const MAX_FAILED_ATTEMPTS: u32 = 5;
+pub const LOCKOUT_MINUTES: i64 = 15;
+
+/// A self-service PIN or password, stored as an Argon2id hash in PHC string format, with the sign-in
+/// failures counted against it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmployeeCredential {
+    pub employee_id: String,
+    pub pin_hash: String,
+    pub updated: DateTime<Utc>,
+    /// Set for PINs issued by an administrator; the employee has to choose their own before use.
+    #[serde(default)]
+    pub must_change: bool,
+    #[serde(default)]
+    pub failed_attempts: u32,
+    #[serde(default)]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CredentialStatus {
+    Active,
+    MustChange,
+    Locked(DateTime<Utc>),
+}
+
+impl fmt::Display for CredentialStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialStatus::Active => write!(f, "active"),
+            CredentialStatus::MustChange => write!(f, "must change"),
+            CredentialStatus::Locked(until) => {
+                write!(f, "locked until {}", until.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
+            }
+        }
+    }
+}
+
+/// Accepts either a numeric PIN or a password mixing letters and digits.
+pub fn validate_pin(pin: &str) -> Result<(), String> {
+    if pin.chars().all(|c| c.is_ascii_digit()) {
+        if !(MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&pin.len()) {
+            return Err(format!("PIN must be {} to {} digits", MIN_PIN_LENGTH, MAX_PIN_LENGTH));
+        }
+        return Ok(());
+    }
+    let length = pin.chars().count();
+    if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length)
+        || !pin.chars().any(char::is_alphabetic)
+        || !pin.chars().any(|c| c.is_ascii_digit())
+    {
+        return Err(format!(
+            "PIN must be {} to {} digits, or a password of {} to {} characters with letters and digits",
+            MIN_PIN_LENGTH, MAX_PIN_LENGTH, MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+fn hash(pin: &str) -> String {
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &SaltString::generate(&mut OsRng))
+        .expect("Argon2 with the default parameters hashes any PIN")
+        .to_string()
+}
+
+/// A uniformly drawn PIN of `TEMPORARY_PIN_LENGTH` digits from the operating system's random source.
+fn temporary_pin() -> String {
+    let range = 10u32.pow(TEMPORARY_PIN_LENGTH as u32);
+    // Draws past the last whole multiple of `range` are drawn again, so no PIN is likelier than another.
+    let limit = u32::MAX - u32::MAX % range;
+    let pin = loop {
+        let draw = OsRng.next_u32();
+        if draw < limit {
+            break draw % range;
+        }
+    };
+    format!("{:0width$}", pin, width = TEMPORARY_PIN_LENGTH)
+}
+
+impl EmployeeCredential {
+    pub fn new(employee_id: &str, pin: &str) -> Result<Self, String> {
+        validate_pin(pin)?;
+        Ok(Self {
+            employee_id: employee_id.to_string(),
+            pin_hash: hash(pin),
+            updated: Utc::now(),
+            must_change: false,
+            failed_attempts: 0,
+            locked_until: None,
+        })
+    }
+
+    /// A credential with a random temporary PIN, returned alongside it so it can be handed to the
+    /// employee. The PIN must be changed at first sign-in.
+    pub fn issue(employee_id: &str) -> (Self, String) {
+        let pin = temporary_pin();
+        let mut credential = Self::new(employee_id, &pin).expect("temporary PINs are valid");
+        credential.must_change = true;
+        (credential, pin)
+    }
+
+    /// Checks `pin` against the hash. Argon2 is slow on purpose, so callers that hold a lock should
+    /// check a copy of the credential outside it and then
+    /// [`Payroll::record_sign_in`](crate::payroll::Payroll::record_sign_in).
+    pub fn verify(&self, pin: &str) -> bool {
+        PasswordHash::new(&self.pin_hash)
+            .is_ok_and(|hash| Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok())
+    }
+
+    /// When sign-in is locked at `now`, the time the lock ends.
+    pub fn locked_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.locked_until.filter(|until| *until > now)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> CredentialStatus {
+        match self.locked_at(now) {
+            Some(until) => CredentialStatus::Locked(until),
+            None if self.must_change => CredentialStatus::MustChange,
+            None => CredentialStatus::Active,
+        }
+    }
+
+    /// Counts a wrong PIN, locking sign-in for `LOCKOUT_MINUTES` once `MAX_FAILED_ATTEMPTS` is reached.
+    pub fn record_failure(&mut self, now: DateTime<Utc>) {
+        self.failed_attempts += 1;
+        if self.failed_attempts >= MAX_FAILED_ATTEMPTS {
+            self.failed_attempts = 0;
+            self.locked_until = Some(now + Duration::minutes(LOCKOUT_MINUTES));
+        }
+    }
+
+    pub fn unlock(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_until = None;
+    }
+}
+
+/// The self-service credentials, at most one per employee.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Credentials {
+    credentials: Vec<EmployeeCredential>,
+}
+
+impl Credentials {
+    pub fn get(&self, employee_id: &str) -> Option<&EmployeeCredential> {
+        self.credentials.iter().find(|credential| credential.employee_id == employee_id)
+    }
+
+    /// Stores a credential, replacing the employee's previous one.
+    pub(crate) fn set(&mut self, credential: EmployeeCredential) {
+        self.remove(&credential.employee_id);
+        self.credentials.push(credential);
+    }
+
+    /// Removes the employee's credential. Returns whether they had one.
+    pub(crate) fn remove(&mut self, employee_id: &str) -> bool {
+        let before = self.credentials.len();
+        self.credentials.retain(|credential| credential.employee_id != employee_id);
+        self.credentials.len() != before
+    }
+
+    /// Clears failed sign-ins and any lockout. Returns whether the employee was locked out.
+    pub(crate) fn unlock(&mut self, employee_id: &str) -> Result<bool, PayrollError> {
+        let credential = self
+            .credentials
+            .iter_mut()
+            .find(|credential| credential.employee_id == employee_id)
+            .ok_or_else(|| PayrollError::InvalidPin(format!("employee {} has no PIN", employee_id)))?;
+        let locked = credential.locked_at(Utc::now()).is_some();
+        credential.unlock();
+        Ok(locked)
+    }
+
+    /// Checks `pin` against the employee's credential. Unknown IDs and wrong PINs fail the same way;
+    /// wrong PINs are counted and lock sign-in for a while once there are too many in a row.
+    pub(crate) fn sign_in(&mut self, employee_id: &str, pin: &str, now: DateTime<Utc>) -> Result<(), PayrollError> {
+        let credential = self.get(employee_id).ok_or(PayrollError::InvalidCredentials)?;
+        if let Some(until) = credential.locked_at(now) {
+            return Err(PayrollError::AccountLocked(until));
+        }
+        let verified = credential.verify(pin);
+        let checked = credential.clone();
+        self.record_sign_in(&checked, verified, now)
+    }
+
+    /// Counts a PIN check made against `checked`, a copy of the employee's credential, as
+    /// [`sign_in`](Self::sign_in) does. A credential locked or given a new PIN since the copy was
+    /// taken fails without counting the attempt.
+    pub(crate) fn record_sign_in(
+        &mut self,
+        checked: &EmployeeCredential,
+        verified: bool,
+        now: DateTime<Utc>,
+    ) -> Result<(), PayrollError> {
+        let credential = self
+            .credentials
+            .iter_mut()
+            .find(|credential| credential.employee_id == checked.employee_id)
+            .filter(|credential| credential.pin_hash == checked.pin_hash)
+            .ok_or(PayrollError::InvalidCredentials)?;
+        if let Some(until) = credential.locked_at(now) {
+            return Err(PayrollError::AccountLocked(until));
+        }
+        if !verified {
+            credential.record_failure(now);
+            return Err(match credential.locked_at(now) {
+                Some(until) => PayrollError::AccountLocked(until),
+                None => PayrollError::InvalidCredentials,
+            });
+        }
+        credential.unlock();
+        Ok(())
+    }
+}
+
+impl Deref for Credentials {
+    type Target = [EmployeeCredential];
+
+    fn deref(&self) -> &[EmployeeCredential] {
+        &self.credentials
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(pin: &str) -> Credentials {
+        let mut credentials = Credentials::default();
+        credentials.set(EmployeeCredential::new("E1", pin).unwrap());
+        credentials
+    }
+
+    #[test]
+    fn pins_and_passwords_are_validated() {
+        assert!(validate_pin("1234").is_ok());
+        assert!(validate_pin("hunter2hunter").is_ok());
+        for pin in ["123", "1234567890123", "password", "abc1", "12ab"] {
+            assert!(validate_pin(pin).is_err(), "{} should be refused", pin);
+        }
+        assert!(EmployeeCredential::new("E1", "12").is_err());
+    }
+
+    #[test]
+    fn the_hash_is_salted_and_checks_only_the_pin() {
+        let first = EmployeeCredential::new("E1", "4321").unwrap();
+        let second = EmployeeCredential::new("E1", "4321").unwrap();
+        assert_ne!(first.pin_hash, second.pin_hash);
+        assert!(!first.pin_hash.contains("4321"));
+        assert!(first.verify("4321"));
+        assert!(!first.verify("4322"));
+    }
+
+    #[test]
+    fn an_issued_pin_has_six_digits_and_must_be_changed() {
+        let (credential, pin) = EmployeeCredential::issue("E1");
+        assert_eq!(pin.len(), TEMPORARY_PIN_LENGTH);
+        assert!(pin.chars().all(|c| c.is_ascii_digit()));
+        assert!(credential.verify(&pin));
+        assert_eq!(credential.status(Utc::now()), CredentialStatus::MustChange);
+    }
+
+    #[test]
+    fn unknown_employees_and_wrong_pins_fail_alike() {
+        let mut credentials = credentials("4321");
+        let now = Utc::now();
+        assert!(matches!(credentials.sign_in("E9", "4321", now), Err(PayrollError::InvalidCredentials)));
+        assert!(matches!(credentials.sign_in("E1", "0000", now), Err(PayrollError::InvalidCredentials)));
+        assert!(credentials.sign_in("E1", "4321", now).is_ok());
+        assert_eq!(credentials.get("E1").unwrap().failed_attempts, 0);
+    }
+
+    #[test]
+    fn too_many_wrong_pins_lock_sign_in_for_a_while() {
+        let mut credentials = credentials("4321");
+        let now = Utc::now();
+        for _ in 1..MAX_FAILED_ATTEMPTS {
+            assert!(matches!(credentials.sign_in("E1", "0000", now), Err(PayrollError::InvalidCredentials)));
+        }
+        let until = now + Duration::minutes(LOCKOUT_MINUTES);
+        assert!(matches!(credentials.sign_in("E1", "0000", now), Err(PayrollError::AccountLocked(t)) if t == until));
+        assert!(matches!(credentials.sign_in("E1", "4321", now), Err(PayrollError::AccountLocked(_))));
+        assert_eq!(credentials.get("E1").unwrap().status(now), CredentialStatus::Locked(until));
+
+        assert!(credentials.sign_in("E1", "4321", until + Duration::seconds(1)).is_ok());
+        assert_eq!(credentials.get("E1").unwrap().status(until), CredentialStatus::Active);
+    }
+
+    #[test]
+    fn unlocking_clears_the_lockout() {
+        let mut credentials = credentials("4321");
+        let now = Utc::now();
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            let _ = credentials.sign_in("E1", "0000", now);
+        }
+        assert!(credentials.unlock("E1").unwrap());
+        assert!(!credentials.unlock("E1").unwrap());
+        assert!(credentials.unlock("E9").is_err());
+        assert!(credentials.sign_in("E1", "4321", Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn a_check_against_a_replaced_pin_is_not_counted() {
+        let mut credentials = credentials("4321");
+        let checked = credentials.get("E1").unwrap().clone();
+        credentials.set(EmployeeCredential::new("E1", "5678").unwrap());
+
+        for verified in [false, true] {
+            let result = credentials.record_sign_in(&checked, verified, Utc::now());
+            assert!(matches!(result, Err(PayrollError::InvalidCredentials)));
+        }
+        assert_eq!(credentials.get("E1").unwrap().failed_attempts, 0);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::payslip::{payslip_file_name, render_html, PayslipSeal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum DeliveryStatus {
+    Sent(DateTime<Utc>),
+    Failed(String),
+    NoEmailAddress,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayslipDelivery {
+    pub employee_id: String,
+    pub email: Option<String>,
+    pub status: DeliveryStatus,
+}
+
+/// How payslips are sent: as HTML, or as PDFs opened with the employee's date of birth. The PDF
+/// owner password, which lifts the printing-only restriction, is either given or drawn at random
+/// for each payslip; it is never blank, as a blank one would open the payslip to anyone.
+#[derive(Clone)]
+pub struct PayslipOptions {
+    pdf: bool,
+    owner_password: Option<String>,
+    signing_key: Option<String>,
+}
+
+impl PayslipOptions {
+    pub fn html(signing_key: Option<String>) -> Self {
+        Self {
+            pdf: false,
+            owner_password: None,
+            signing_key,
+        }
+    }
+
+    /// PDF payslips with `owner_password`, or a random one for each payslip when `None`.
+    pub fn pdf(owner_password: Option<String>, signing_key: Option<String>) -> Result<Self, PayrollError> {
+        if owner_password.as_deref().is_some_and(|password| password.trim().is_empty()) {
+            return Err(PayrollError::BlankOwnerPassword);
+        }
+        Ok(Self {
+            pdf: true,
+            owner_password,
+            signing_key,
+        })
+    }
+
+    pub fn is_pdf(&self) -> bool {
+        self.pdf
+    }
+}
+
+impl fmt::Debug for PayslipOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "<redacted>");
+        f.debug_struct("PayslipOptions")
+            .field("pdf", &self.pdf)
+            .field("owner_password", &redacted(&self.owner_password))
+            .field("signing_key", &redacted(&self.signing_key))
+            .finish()
+    }
+}
+
+pub struct PayslipMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub attachment_name: String,
+    pub attachment_content_type: String,
+    pub attachment: Vec<u8>,
+}
+
+impl PayslipMessage {
+    pub fn for_record(record: &PayrollData, to: &str, options: &PayslipOptions) -> Result<Self, String> {
+        let seal = PayslipSeal::for_record(record, options.signing_key.as_deref());
+        let (extension, content_type, attachment) = if options.pdf {
+            ("pdf", "application/pdf", Self::render_pdf(record, &seal, options)?)
+        } else {
+            ("html", "text/html", render_html(record, &seal).into_bytes())
+        };
+
+        let mut body = format!(
+            "Dear {},\n\nPlease find attached your payslip for {}.\n",
+            record.employee.as_employee().profile().name,
+            record.pay_period
+        );
+        if options.pdf {
+            body.push_str("The attachment is protected with your date of birth (DDMMYYYY).\n");
+        }
+
+        Ok(Self {
+            to: to.to_string(),
+            subject: format!("Payslip {}", record.pay_period),
+            body,
+            attachment_name: payslip_file_name(record, extension),
+            attachment_content_type: content_type.to_string(),
+            attachment,
+        })
+    }
+
+    #[cfg(feature = "pdf")]
+    fn render_pdf(record: &PayrollData, seal: &PayslipSeal, options: &PayslipOptions) -> Result<Vec<u8>, String> {
+        let password = crate::payslip::payslip_password(record.employee.as_employee())
+            .ok_or_else(|| "no date of birth recorded for the payslip password".to_string())?;
+        crate::pdf::render_payslip_pdf(record, seal, Some(&password), options.owner_password.as_deref())
+    }
+
+    #[cfg(not(feature = "pdf"))]
+    fn render_pdf(_record: &PayrollData, _seal: &PayslipSeal, _options: &PayslipOptions) -> Result<Vec<u8>, String> {
+        Err("PDF payslips require the `pdf` feature".to_string())
+    }
+}
+
+pub trait PayslipSender {
+    fn send(&self, message: &PayslipMessage) -> Result<(), String>;
+}
+
+pub fn deliver(record: &PayrollData, sender: &dyn PayslipSender, options: &PayslipOptions) -> PayslipDelivery {
+    let employee = record.employee.as_employee();
+    let email = employee.profile().email.clone();
+    let status = match &email {
+        None => DeliveryStatus::NoEmailAddress,
+        Some(address) => match PayslipMessage::for_record(record, address, options)
+            .and_then(|message| sender.send(&message))
+        {
+            Ok(()) => DeliveryStatus::Sent(Utc::now()),
+            Err(err) => DeliveryStatus::Failed(err),
+        },
+    };
+    PayslipDelivery {
+        employee_id: employee.employee_id().to_string(),
+        email,
+        status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_owner_passwords_are_refused_and_secrets_never_printed() {
+        for blank in ["", " \t"] {
+            let options = PayslipOptions::pdf(Some(blank.to_string()), None);
+            assert_eq!(options.unwrap_err(), PayrollError::BlankOwnerPassword);
+        }
+        let options = PayslipOptions::pdf(Some("owner-secret".to_string()), Some("signing-key".to_string())).unwrap();
+        let printed = format!("{:?}", options);
+        assert!(!printed.contains("owner-secret") && !printed.contains("signing-key"), "{}", printed);
+        assert!(PayslipOptions::pdf(None, None).unwrap().is_pdf());
+        assert!(!PayslipOptions::html(None).is_pdf());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::disbursement::{SalarySplit, SplitAmount};
+use crate::employee::{AllowancePeriod, ContractEmployee, EmployeeProfile, EmployeeStatus, FulltimeEmployee};
+use crate::tax::PtkpStatus;
+use crate::onboarding::OnboardingTask;
+use crate::payroll::{EmployeeData, Payroll};
+use crate::period::PayPeriod;
+use chrono::{Duration, NaiveDate, Utc};
+
+const FIRST_NAMES: [&str; 20] = [
+    "Budi", "Siti", "Agus", "Dewi", "Andi", "Rina", "Eko", "Sri", "Joko", "Putri", "Hendra", "Wulan", "Rizky",
+    "Ayu", "Fajar", "Nur", "Bayu", "Indah", "Dimas", "Lestari",
+];
+const LAST_NAMES: [&str; 16] = [
+    "Santoso", "Wijaya", "Saputra", "Hidayat", "Kurniawan", "Pratama", "Susanto", "Nugroho", "Lestari", "Setiawan",
+    "Gunawan", "Halim", "Siregar", "Nasution", "Simanjuntak", "Wibowo",
+];
+const DEPARTMENTS: [&str; 6] = ["Finance", "Operations", "Engineering", "Sales", "Human Resources", "Marketing"];
+const PTKP_STATUSES: [PtkpStatus; 8] = [
+    PtkpStatus::TK0,
+    PtkpStatus::TK1,
+    PtkpStatus::TK2,
+    PtkpStatus::TK3,
+    PtkpStatus::K0,
+    PtkpStatus::K1,
+    PtkpStatus::K2,
+    PtkpStatus::K3,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemoOptions {
+    pub employees: usize,
+    pub months: u32,
+    pub last_period: PayPeriod,
+    pub seed: u64,
+}
+
+impl DemoOptions {
+    pub fn new(employees: usize, months: u32) -> Self {
+        Self {
+            employees,
+            months,
+            last_period: PayPeriod::from_date(Utc::now().date_naive()).previous(),
+            seed: 42,
+        }
+    }
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self::new(25, 6)
+    }
+}
+
+struct DemoRng(u64);
+
+impl DemoRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * (high - low)
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    fn digits(&mut self, count: usize) -> String {
+        (0..count).map(|_| char::from(b'0' + self.below(10) as u8)).collect()
+    }
+}
+
+fn round_to(amount: f64, step: f64) -> f64 {
+    (amount / step).round() * step
+}
+
+fn demo_profile(rng: &mut DemoRng, index: usize) -> EmployeeProfile {
+    let first = rng.pick(&FIRST_NAMES);
+    let last = rng.pick(&LAST_NAMES);
+    let npwp = rng.digits(15);
+    let birth_offset = rng.below(365 * 35) as i64;
+    EmployeeProfile {
+        name: format!("{} {}", first, last),
+        nik: Some(format!("3171{}", rng.digits(12))),
+        npwp: Some(format!(
+            "{}.{}.{}.{}-{}.{}",
+            &npwp[0..2],
+            &npwp[2..5],
+            &npwp[5..8],
+            &npwp[8..9],
+            &npwp[9..12],
+            &npwp[12..15]
+        )),
+        bpjs_tk_number: Some(rng.digits(11)),
+        email: Some(format!(
+            "{}.{}{}@example.co.id",
+            first.to_lowercase(),
+            last.to_lowercase(),
+            index + 1
+        )),
+        date_of_birth: NaiveDate::from_ymd_opt(1970, 1, 1).map(|date| date + Duration::days(birth_offset)),
+        department: Some(rng.pick(&DEPARTMENTS).to_string()),
+        grade: None,
+        manager_id: None,
+        status: EmployeeStatus::Active,
+        probation_end: None,
+        salary_split: vec![SalarySplit {
+            bank: "BCA".to_string(),
+            account_number: format!("{}", 7_000_000_000u64 + index as u64),
+            amount: SplitAmount::Remainder,
+        }],
+        non_resident: None,
+        tax_regime: None,
+    }
+}
+
+fn demo_employee(rng: &mut DemoRng, index: usize) -> EmployeeData {
+    let employee_id = format!("DEMO{:04}", index + 1);
+    let profile = demo_profile(rng, index);
+    if rng.below(4) == 0 {
+        let mut employee = ContractEmployee::new(
+            employee_id,
+            rng.range(80.0, 180.0).round(),
+            round_to(rng.range(0.0, 2_000_000.0), 250_000.0),
+            *rng.pick(&[AllowancePeriod::Monthly, AllowancePeriod::PerProject]),
+            round_to(rng.range(50_000.0, 200_000.0), 5_000.0),
+        );
+        employee.profile = profile;
+        EmployeeData::Contract(employee)
+    } else {
+        let mut employee = FulltimeEmployee::new(
+            employee_id,
+            173.0,
+            round_to(rng.range(250_000.0, 2_500_000.0), 250_000.0),
+            *rng.pick(&[AllowancePeriod::Monthly, AllowancePeriod::Monthly, AllowancePeriod::Yearly]),
+            round_to(rng.range(5_000_000.0, 35_000_000.0), 100_000.0),
+        );
+        employee.ptkp_status = Some(*rng.pick(&PTKP_STATUSES));
+        employee.profile = profile;
+        EmployeeData::Fulltime(employee)
+    }
+}
+
+fn with_hours(employee: &EmployeeData, hours: f64) -> EmployeeData {
+    let mut employee = employee.clone();
+    match &mut employee {
+        EmployeeData::Fulltime(emp) => emp.work_hour = hours,
+        EmployeeData::Contract(emp) => emp.work_hour = hours,
+    }
+    employee
+}
+
+pub fn seed_demo(payroll: &mut Payroll, options: &DemoOptions) {
+    let mut rng = DemoRng(options.seed);
+    let employees: Vec<EmployeeData> = (0..options.employees)
+        .map(|index| demo_employee(&mut rng, index))
+        .collect();
+    for employee in &employees {
+        payroll.add_employee(employee.clone());
+        // Demo employees have worked here for months, so their onboarding is long finished.
+        let _ = payroll.complete_onboarding_task(employee.as_employee().employee_id(), OnboardingTask::ContractSigned);
+    }
+
+    let mut period = options.last_period;
+    for _ in 1..options.months {
+        period = period.previous();
+    }
+    for month in 0..options.months {
+        let batch: Vec<EmployeeData> = employees
+            .iter()
+            .map(|employee| {
+                let hours = match employee {
+                    EmployeeData::Fulltime(_) => 173.0 + rng.below(25) as f64,
+                    EmployeeData::Contract(emp) => (emp.work_hour + rng.range(-20.0, 20.0)).max(0.0).round(),
+                };
+                with_hours(employee, hours)
+            })
+            .collect();
+
+        let pay_period = period.to_string();
+        let processed_date = (period.next().first_day() - Duration::days(3))
+            .and_hms_opt(17, 0, 0)
+            .map(|date| date.and_utc());
+        if let Ok(records) = payroll.process_batch(batch, &pay_period) {
+            let start = payroll.get_payroll_records().len() - records.len();
+            if let Some(processed_date) = processed_date {
+                for index in start..start + records.len() {
+                    let _ = payroll.update_record(index, |record| record.processed_date = processed_date);
+                }
+            }
+        }
+        if month + 1 < options.months {
+            payroll.close_period(&pay_period);
+        }
+        period = period.next();
+    }
+}
+
+pub fn demo_payroll(options: &DemoOptions) -> Payroll {
+    let mut payroll = Payroll::new();
+    seed_demo(&mut payroll, options);
+    payroll
+}
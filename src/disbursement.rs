@@ -0,0 +1,552 @@
+use crate::export::write_csv_row;
+use crate::payroll::PayrollData;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// How much of the net pay goes to one account.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SplitAmount {
+    Fixed(f64),
+    /// A percentage of what is left after the fixed amounts.
+    Percent(f64),
+    /// Whatever is left after the fixed amounts and percentages.
+    Remainder,
+}
+
+/// One bank account in an employee's salary split. E-wallets (`GOPAY`, `OVO`, `DANA`) take the
+/// wallet's phone number as the account number.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SalarySplit {
+    pub bank: String,
+    pub account_number: String,
+    pub amount: SplitAmount,
+}
+
+/// The most a verified (premium) e-wallet account may hold; larger payouts are usually rejected.
+pub const EWALLET_BALANCE_LIMIT: f64 = 20_000_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EWallet {
+    GoPay,
+    Ovo,
+    Dana,
+}
+
+impl EWallet {
+    pub const ALL: [EWallet; 3] = [EWallet::GoPay, EWallet::Ovo, EWallet::Dana];
+
+    /// The name used in salary splits and as the "bank" of a transfer.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EWallet::GoPay => "GOPAY",
+            EWallet::Ovo => "OVO",
+            EWallet::Dana => "DANA",
+        }
+    }
+
+    pub fn from_bank(bank: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|wallet| wallet.as_str().eq_ignore_ascii_case(bank))
+    }
+
+    /// Column headers of the provider's bulk-payment CSV template.
+    fn header(&self) -> [&'static str; 3] {
+        match self {
+            EWallet::GoPay => ["phone_number", "amount", "notes"],
+            EWallet::Ovo => ["Mobile Number", "Amount", "Remarks"],
+            EWallet::Dana => ["msisdn", "amount", "description"],
+        }
+    }
+
+    /// `phone` (stored as `628...`) in the form the provider's template expects.
+    fn format_phone(&self, phone: &str) -> String {
+        match self {
+            EWallet::GoPay => format!("0{}", phone.strip_prefix("62").unwrap_or(phone)),
+            EWallet::Ovo | EWallet::Dana => phone.to_string(),
+        }
+    }
+}
+
+impl FromStr for EWallet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_bank(s.trim()).ok_or_else(|| format!("Unknown e-wallet '{}' (expected gopay, ovo or dana)", s))
+    }
+}
+
+/// Normalizes an Indonesian mobile number (`0812...`, `62812...` or `+62 812-...`) to `62812...`.
+pub fn normalize_phone(input: &str) -> Result<String, String> {
+    let digits: String = input.chars().filter(|c| !matches!(c, ' ' | '-' | '+')).collect();
+    let national = digits
+        .strip_prefix("62")
+        .or_else(|| digits.strip_prefix('0'))
+        .unwrap_or(&digits);
+    let valid = national.starts_with('8') && national.chars().all(|c| c.is_ascii_digit());
+    if !valid || !(9..=12).contains(&national.len()) {
+        return Err(format!("'{}' is not an Indonesian mobile number", input.trim()));
+    }
+    Ok(format!("62{}", national))
+}
+
+/// Whole rupiah, optionally grouped in thousands with `.` or `,` (`1000000`, `1.000.000`).
+fn parse_rupiah(input: &str) -> Option<f64> {
+    let groups: Vec<&str> = input.split(['.', ',']).collect();
+    let grouped = groups[1..].iter().all(|group| group.len() == 3) && (1..=3).contains(&groups[0].len());
+    if groups.len() > 1 && !grouped {
+        return None;
+    }
+    let digits = groups.concat();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Parses `BANK:ACCOUNT`, `BANK:ACCOUNT=80%` or `BANK:ACCOUNT=1000000`; without an amount the
+/// account takes the remainder.
+impl FromStr for SalarySplit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (account, amount) = match s.split_once('=') {
+            Some((account, amount)) => (account, Some(amount.trim())),
+            None => (s, None),
+        };
+        let Some((bank, account_number)) = account.split_once(':') else {
+            return Err(format!("'{}' is not BANK:ACCOUNT[=AMOUNT|PERCENT%]", s.trim()));
+        };
+        let (bank, account_number) = (bank.trim(), account_number.trim());
+        if bank.is_empty() || account_number.is_empty() {
+            return Err(format!("'{}' needs both a bank and an account number", s.trim()));
+        }
+        let account_number = if EWallet::from_bank(bank).is_some() {
+            normalize_phone(account_number)?
+        } else if account_number.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            account_number.replace('-', "")
+        } else {
+            return Err(format!("account number '{}' must contain only digits", account_number));
+        };
+        let amount = match amount {
+            None | Some("") => SplitAmount::Remainder,
+            Some(amount) => match amount.strip_suffix('%') {
+                Some(percent) => match percent.trim().parse::<f64>() {
+                    Ok(percent) if percent > 0.0 && percent <= 100.0 => SplitAmount::Percent(percent),
+                    _ => return Err(format!("'{}' is not a percentage between 0 and 100", amount)),
+                },
+                None => match parse_rupiah(amount) {
+                    Some(fixed) if fixed > 0.0 => SplitAmount::Fixed(fixed),
+                    _ => return Err(format!("'{}' is not a positive whole rupiah amount", amount)),
+                },
+            },
+        };
+        Ok(Self {
+            bank: bank.to_uppercase(),
+            account_number,
+            amount,
+        })
+    }
+}
+
+impl fmt::Display for SalarySplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.bank, self.account_number)?;
+        match self.amount {
+            SplitAmount::Fixed(amount) => write!(f, "={:.0}", amount),
+            SplitAmount::Percent(percent) => write!(f, "={}%", percent),
+            SplitAmount::Remainder => Ok(()),
+        }
+    }
+}
+
+impl SalarySplit {
+    /// The account number with all but the last four digits hidden, for payslips.
+    pub fn masked_account(&self) -> String {
+        let visible = self.account_number.len().saturating_sub(4);
+        format!("****{}", &self.account_number[visible..])
+    }
+}
+
+/// Checks what can be checked without knowing the net pay: at most one remainder account and
+/// percentages adding up to no more than 100.
+pub fn validate_splits(splits: &[SalarySplit]) -> Result<(), String> {
+    let remainders = splits.iter().filter(|split| split.amount == SplitAmount::Remainder).count();
+    if remainders > 1 {
+        return Err("only one account can take the remainder".to_string());
+    }
+    let percent: f64 = splits
+        .iter()
+        .filter_map(|split| match split.amount {
+            SplitAmount::Percent(percent) => Some(percent),
+            _ => None,
+        })
+        .sum();
+    if percent > 100.0 + 1e-9 {
+        return Err(format!("percentages add up to {}%", percent));
+    }
+    Ok(())
+}
+
+/// Parses splits separated by spaces or semicolons (commas may group thousands in amounts).
+pub fn parse_salary_split(input: &str) -> Result<Vec<SalarySplit>, String> {
+    let splits = input
+        .split(|c: char| c == ';' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<SalarySplit>, String>>()?;
+    validate_splits(&splits)?;
+    Ok(splits)
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Divides `net` over the split: fixed amounts first, then percentages of what is left, then the
+/// remainder account. Fails unless the amounts add up to exactly `net`. Rounding differences go to
+/// the last percentage account when there is no remainder account.
+pub fn allocate(splits: &[SalarySplit], net: f64) -> Result<Vec<(&SalarySplit, f64)>, String> {
+    if splits.is_empty() {
+        return Err("no bank account".to_string());
+    }
+    validate_splits(splits)?;
+    let fixed: f64 = splits
+        .iter()
+        .filter_map(|split| match split.amount {
+            SplitAmount::Fixed(amount) => Some(amount),
+            _ => None,
+        })
+        .sum();
+    if fixed > net + 0.005 {
+        return Err(format!("fixed amounts of Rp {:.2} exceed net pay of Rp {:.2}", fixed, net));
+    }
+    let rest = net - fixed;
+    let mut allocations: Vec<(&SalarySplit, f64)> = splits
+        .iter()
+        .map(|split| {
+            let amount = match split.amount {
+                SplitAmount::Fixed(amount) => amount,
+                SplitAmount::Percent(percent) => round_cents(rest * percent / 100.0),
+                SplitAmount::Remainder => 0.0,
+            };
+            (split, amount)
+        })
+        .collect();
+
+    let left = round_cents(net - allocations.iter().map(|(_, amount)| amount).sum::<f64>());
+    let absorber = allocations
+        .iter()
+        .position(|(split, _)| split.amount == SplitAmount::Remainder)
+        .or_else(|| {
+            let last = allocations
+                .iter()
+                .rposition(|(split, _)| matches!(split.amount, SplitAmount::Percent(_)))?;
+            (left.abs() < 0.01 * allocations.len() as f64).then_some(last)
+        });
+    match absorber {
+        Some(index) => allocations[index].1 = round_cents(allocations[index].1 + left),
+        None if left.abs() >= 0.005 => {
+            return Err(format!(
+                "the split pays out Rp {:.2} but net pay is Rp {:.2}",
+                net - left,
+                net
+            ))
+        }
+        None => {}
+    }
+    Ok(allocations)
+}
+
+/// One line of a bank transfer file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankTransfer {
+    pub employee_id: String,
+    pub account_name: String,
+    pub bank: String,
+    pub account_number: String,
+    pub amount: f64,
+    pub reference: String,
+}
+
+impl BankTransfer {
+    pub fn ewallet(&self) -> Option<EWallet> {
+        EWallet::from_bank(&self.bank)
+    }
+}
+
+/// The transfers for a set of payroll records, and the employees that could not be paid because
+/// their split is missing or does not add up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BankTransferBatch {
+    pub reference: String,
+    pub transfers: Vec<BankTransfer>,
+    pub problems: Vec<(String, String)>,
+}
+
+impl BankTransferBatch {
+    /// Uses the split stored on each record, falling back to `current_split` (the employee's
+    /// current one) for records processed before a split was set.
+    pub fn from_records<'a, F>(reference: String, records: &[&PayrollData], current_split: F) -> Self
+    where
+        F: Fn(&str) -> Option<&'a [SalarySplit]>,
+    {
+        let mut batch = Self {
+            reference,
+            ..Self::default()
+        };
+        for record in records {
+            let employee = record.employee.as_employee();
+            let employee_id = employee.employee_id();
+            let splits = match employee.profile().salary_split.as_slice() {
+                [] => current_split(employee_id).unwrap_or_default(),
+                splits => splits,
+            };
+            match allocate(splits, record.net_salary) {
+                Ok(allocations) => {
+                    for (split, amount) in allocations {
+                        batch.transfers.push(BankTransfer {
+                            employee_id: employee_id.to_string(),
+                            account_name: employee.profile().name.clone(),
+                            bank: split.bank.clone(),
+                            account_number: split.account_number.clone(),
+                            amount,
+                            reference: batch.reference.clone(),
+                        });
+                    }
+                }
+                Err(err) => batch.problems.push((employee_id.to_string(), err)),
+            }
+        }
+        batch
+    }
+
+    pub fn total(&self) -> f64 {
+        self.transfers.iter().fold(0.0, |total, transfer| total + transfer.amount)
+    }
+
+    /// Transfer count and total per bank, in the order the banks first appear.
+    pub fn by_bank(&self) -> Vec<(&str, usize, f64)> {
+        let mut banks: Vec<(&str, usize, f64)> = Vec::new();
+        for transfer in &self.transfers {
+            match banks.iter_mut().find(|(bank, _, _)| *bank == transfer.bank) {
+                Some((_, count, amount)) => {
+                    *count += 1;
+                    *amount += transfer.amount;
+                }
+                None => banks.push((&transfer.bank, 1, transfer.amount)),
+            }
+        }
+        banks
+    }
+
+    /// Writes the bank transfer file; e-wallet payouts are left to `write_ewallet_csv`.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_csv_row(
+            &mut writer,
+            &["Bank", "Account Number", "Account Name", "Amount", "Employee ID", "Reference"].map(String::from),
+        )?;
+        for transfer in self.transfers.iter().filter(|transfer| transfer.ewallet().is_none()) {
+            write_csv_row(
+                &mut writer,
+                &[
+                    transfer.bank.clone(),
+                    transfer.account_number.clone(),
+                    transfer.account_name.clone(),
+                    format!("{:.2}", transfer.amount),
+                    transfer.employee_id.clone(),
+                    transfer.reference.clone(),
+                ],
+            )?;
+        }
+        writer.flush()
+    }
+
+    pub fn ewallet_transfers(&self, wallet: EWallet) -> impl Iterator<Item = &BankTransfer> {
+        self.transfers.iter().filter(move |transfer| transfer.ewallet() == Some(wallet))
+    }
+
+    /// Writes the payouts to `wallet` in the layout of its bulk-payment template: phone number,
+    /// whole-rupiah amount and a note with the reference and employee ID.
+    pub fn write_ewallet_csv<W: Write>(&self, wallet: EWallet, mut writer: W) -> io::Result<()> {
+        write_csv_row(&mut writer, &wallet.header().map(String::from))?;
+        for transfer in self.ewallet_transfers(wallet) {
+            write_csv_row(
+                &mut writer,
+                &[
+                    wallet.format_phone(&transfer.account_number),
+                    format!("{:.0}", transfer.amount.round()),
+                    format!("{} {}", transfer.reference, transfer.employee_id),
+                ],
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum BatchStatus {
+    Submitted,
+    Processing,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum TransferStatus {
+    Pending,
+    Paid(DateTime<Utc>),
+    Rejected(String),
+}
+
+/// What the provider reports for one transfer of a batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TransferConfirmation {
+    pub employee_id: String,
+    pub bank: String,
+    pub account_number: String,
+    pub amount: f64,
+    pub status: TransferStatus,
+}
+
+impl TransferConfirmation {
+    pub fn pending(transfer: &BankTransfer) -> Self {
+        Self {
+            employee_id: transfer.employee_id.clone(),
+            bank: transfer.bank.clone(),
+            account_number: transfer.account_number.clone(),
+            amount: transfer.amount,
+            status: TransferStatus::Pending,
+        }
+    }
+}
+
+/// A run's payout as submitted to a `DisbursementProvider`; kept on the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Disbursement {
+    pub provider: String,
+    pub batch_id: String,
+    pub submitted: DateTime<Utc>,
+    pub status: BatchStatus,
+    pub confirmations: Vec<TransferConfirmation>,
+}
+
+impl Disbursement {
+    pub fn count(&self, matches: impl Fn(&TransferStatus) -> bool) -> usize {
+        self.confirmations.iter().filter(|confirmation| matches(&confirmation.status)).count()
+    }
+
+    pub fn paid_total(&self) -> f64 {
+        self.confirmations
+            .iter()
+            .filter(|confirmation| matches!(confirmation.status, TransferStatus::Paid(_)))
+            .fold(0.0, |total, confirmation| total + confirmation.amount)
+    }
+}
+
+/// A bank or payment gateway API that pays out transfer batches. Implement it to connect a real
+/// bank; the crate only ships `MockDisbursementProvider`.
+pub trait DisbursementProvider {
+    fn name(&self) -> &str;
+    /// Submits the transfers and returns the provider's batch ID.
+    fn create_batch(&self, batch: &BankTransferBatch) -> Result<String, String>;
+    fn batch_status(&self, batch_id: &str) -> Result<BatchStatus, String>;
+    /// The result of each transfer in the batch, as far as the provider knows it.
+    fn fetch_confirmations(&self, batch_id: &str) -> Result<Vec<TransferConfirmation>, String>;
+}
+
+struct MockBatch {
+    batch_id: String,
+    transfers: Vec<BankTransfer>,
+    status_checks: u32,
+}
+
+/// Keeps batches in memory: a batch is processing at the first status check and completed from
+/// the second, and every transfer is paid except those to rejected account numbers.
+#[derive(Default)]
+pub struct MockDisbursementProvider {
+    batches: Mutex<Vec<MockBatch>>,
+    rejected_accounts: Vec<String>,
+}
+
+impl MockDisbursementProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes transfers to `account_number` fail, to exercise rejections.
+    pub fn reject_account(mut self, account_number: impl Into<String>) -> Self {
+        self.rejected_accounts.push(account_number.into());
+        self
+    }
+
+    fn with_batch<T>(&self, batch_id: &str, f: impl FnOnce(&mut MockBatch) -> T) -> Result<T, String> {
+        let mut batches = self.batches.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        batches
+            .iter_mut()
+            .find(|batch| batch.batch_id == batch_id)
+            .map(f)
+            .ok_or_else(|| format!("unknown batch {}", batch_id))
+    }
+}
+
+impl DisbursementProvider for MockDisbursementProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn create_batch(&self, batch: &BankTransferBatch) -> Result<String, String> {
+        if batch.transfers.is_empty() {
+            return Err("the batch has no transfers".to_string());
+        }
+        let mut batches = self.batches.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let batch_id = format!("MOCK-{:04}", batches.len() + 1);
+        batches.push(MockBatch {
+            batch_id: batch_id.clone(),
+            transfers: batch.transfers.clone(),
+            status_checks: 0,
+        });
+        Ok(batch_id)
+    }
+
+    fn batch_status(&self, batch_id: &str) -> Result<BatchStatus, String> {
+        self.with_batch(batch_id, |batch| {
+            batch.status_checks += 1;
+            match batch.status_checks {
+                1 => BatchStatus::Processing,
+                _ => BatchStatus::Completed,
+            }
+        })
+    }
+
+    fn fetch_confirmations(&self, batch_id: &str) -> Result<Vec<TransferConfirmation>, String> {
+        self.with_batch(batch_id, |batch| {
+            batch
+                .transfers
+                .iter()
+                .map(|transfer| {
+                    let mut confirmation = TransferConfirmation::pending(transfer);
+                    if batch.status_checks >= 2 {
+                        confirmation.status = if self.rejected_accounts.contains(&transfer.account_number) {
+                            TransferStatus::Rejected("account rejected by the mock bank".to_string())
+                        } else {
+                            TransferStatus::Paid(Utc::now())
+                        };
+                    }
+                    confirmation
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,57 @@
+use crate::delivery::{PayslipMessage, PayslipSender};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+pub struct SmtpPayslipSender {
+    transport: SmtpTransport,
+    from: String,
+}
+
+impl SmtpPayslipSender {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, String> {
+        let transport = SmtpTransport::starttls_relay(&settings.host)
+            .map_err(|err| err.to_string())?
+            .port(settings.port)
+            .credentials(Credentials::new(
+                settings.username.clone(),
+                settings.password.clone(),
+            ))
+            .build();
+        Ok(Self {
+            transport,
+            from: settings.from.clone(),
+        })
+    }
+}
+
+impl PayslipSender for SmtpPayslipSender {
+    fn send(&self, message: &PayslipMessage) -> Result<(), String> {
+        let content_type = ContentType::parse(&message.attachment_content_type)
+            .map_err(|err| format!("invalid attachment content type: {}", err))?;
+        let attachment = Attachment::new(message.attachment_name.clone())
+            .body(message.attachment.clone(), content_type);
+        let email = Message::builder()
+            .from(self.from.parse().map_err(|err| format!("invalid sender address: {}", err))?)
+            .to(message.to.parse().map_err(|err| format!("invalid recipient address: {}", err))?)
+            .subject(message.subject.clone())
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(message.body.clone()))
+                    .singlepart(attachment),
+            )
+            .map_err(|err| err.to_string())?;
+        self.transport.send(&email).map(|_| ()).map_err(|err| err.to_string())
+    }
+}
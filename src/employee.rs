@@ -1,42 +1,127 @@
-use crate::tax::Tax;
+use crate::calc::{self, CalculationSettings};
+use crate::disbursement::SalarySplit;
+use crate::tax::{NonResidency, PtkpStatus, Tax};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AllowancePeriod {
-    Monthly,
-    Yearly,
-    PerProject,
-}
+pub use crate::calc::{
+    AllowancePeriod, DeductionBreakdown, EarningBreakdown, BPJS_KESEHATAN_RATE, BPJS_KETENAGAKERJAAN_RATE, OVERTIME_MULTIPLIER,
+    STANDARD_MONTHLY_HOURS,
+};
+
+impl FromStr for AllowancePeriod {
+    type Err = String;
 
-impl AllowancePeriod {
-    pub fn from_str(s: &str) -> Option<AllowancePeriod> {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "monthly" => Some(AllowancePeriod::Monthly),
-            "yearly" => Some(AllowancePeriod::Yearly),
-            "per_project" => Some(AllowancePeriod::PerProject),
-            _ => None,
+            "monthly" => Ok(AllowancePeriod::Monthly),
+            "yearly" => Ok(AllowancePeriod::Yearly),
+            "per_project" => Ok(AllowancePeriod::PerProject),
+            _ => Err(format!("Unknown allowance period '{}'", s)),
+        }
+    }
+}
+
+/// How far ahead probation reviews are listed as due.
+pub const PROBATION_REVIEW_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EmployeeStatus {
+    #[default]
+    Active,
+    Probation,
+    Archived,
+    Terminated,
+}
+
+impl EmployeeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmployeeStatus::Active => "active",
+            EmployeeStatus::Probation => "probation",
+            EmployeeStatus::Archived => "archived",
+            EmployeeStatus::Terminated => "terminated",
+        }
+    }
+}
+
+impl FromStr for EmployeeStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "active" => Ok(EmployeeStatus::Active),
+            "probation" => Ok(EmployeeStatus::Probation),
+            "archived" => Ok(EmployeeStatus::Archived),
+            "terminated" => Ok(EmployeeStatus::Terminated),
+            _ => Err(format!("Unknown employee status '{}'", s.trim())),
         }
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct EmployeeProfile {
+    pub name: String,
+    pub nik: Option<String>,
+    pub npwp: Option<String>,
+    pub bpjs_tk_number: Option<String>,
+    pub email: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub department: Option<String>,
+    /// Pay grade, set by approved promotions.
+    pub grade: Option<String>,
+    /// The employee this one reports to.
+    pub manager_id: Option<String>,
+    pub status: EmployeeStatus,
+    /// Last day of the probation period; kept after the employee is confirmed.
+    pub probation_end: Option<NaiveDate>,
+    /// Bank accounts the net pay is paid into; see `disbursement::allocate`.
+    pub salary_split: Vec<SalarySplit>,
+    /// Set for contractors resident abroad, who are withheld PPh 26 instead of PPh 21.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_resident: Option<NonResidency>,
+    /// Tax regime charged instead of the company's; see `tax::find_regime`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_regime: Option<String>,
+}
+
 pub trait Employee {
     fn employee_id(&self) -> &str;
+    fn profile(&self) -> &EmployeeProfile;
     fn work_hour(&self) -> f64;
     fn tunjangan(&self) -> f64;
     fn periode_tunjangan(&self) -> &AllowancePeriod;
     fn calculate_gross(&self) -> f64;
+    fn calculate_tax(&self) -> f64;
+    fn deduction_breakdown(&self) -> DeductionBreakdown;
+    fn gross_with(&self, settings: &CalculationSettings) -> f64;
+    fn earning_breakdown_with(&self, settings: &CalculationSettings) -> EarningBreakdown;
+    fn deduction_breakdown_with(&self, settings: &CalculationSettings) -> DeductionBreakdown;
     fn calculate_deduction(&self) -> f64;
     fn calculate_net(&self) -> f64;
     fn employee_type(&self) -> &str;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FulltimeEmployee {
     pub employee_id: String,
     pub work_hour: f64,
     pub tunjangan: f64,
     pub periode_tunjangan: AllowancePeriod,
     pub base_salary: f64,
+    #[serde(default)]
+    pub ptkp_status: Option<PtkpStatus>,
+    #[serde(default)]
+    pub profile: EmployeeProfile,
 }
 
 impl FulltimeEmployee {
@@ -53,6 +138,8 @@ impl FulltimeEmployee {
             tunjangan,
             periode_tunjangan,
             base_salary,
+            ptkp_status: None,
+            profile: EmployeeProfile::default(),
         }
     }
 }
@@ -62,6 +149,10 @@ impl Employee for FulltimeEmployee {
         &self.employee_id
     }
 
+    fn profile(&self) -> &EmployeeProfile {
+        &self.profile
+    }
+
     fn work_hour(&self) -> f64 {
         self.work_hour
     }
@@ -75,31 +166,59 @@ impl Employee for FulltimeEmployee {
     }
 
     fn calculate_gross(&self) -> f64 {
-        let monthly_salary = self.base_salary;
-        let overtime_rate = self.base_salary / 173.0;
-        let overtime_hours = if self.work_hour > 173.0 { self.work_hour - 173.0 } else { 0.0 };
-        let overtime_pay = overtime_hours * overtime_rate * 1.5;
-
-        let monthly_tunjangan = match self.periode_tunjangan {
-            AllowancePeriod::Monthly => self.tunjangan,
-            AllowancePeriod::Yearly => self.tunjangan / 12.0,
-            AllowancePeriod::PerProject => 0.0,
-        };
+        self.gross_with(&CalculationSettings::DEFAULT)
+    }
+
+    fn calculate_tax(&self) -> f64 {
+        self.deduction_breakdown().tax
+    }
+
+    fn deduction_breakdown(&self) -> DeductionBreakdown {
+        self.deduction_breakdown_with(&CalculationSettings::DEFAULT)
+    }
 
-        monthly_salary + overtime_pay + monthly_tunjangan
+    fn gross_with(&self, settings: &CalculationSettings) -> f64 {
+        calc::fulltime_gross(
+            self.base_salary,
+            self.work_hour,
+            self.tunjangan,
+            self.periode_tunjangan,
+            settings,
+        )
+    }
+
+    fn earning_breakdown_with(&self, settings: &CalculationSettings) -> EarningBreakdown {
+        calc::fulltime_earnings(
+            self.base_salary,
+            self.work_hour,
+            self.tunjangan,
+            self.periode_tunjangan,
+            settings,
+        )
+    }
+
+    fn deduction_breakdown_with(&self, settings: &CalculationSettings) -> DeductionBreakdown {
+        let gross = self.gross_with(settings);
+        let breakdown = calc::fulltime_deductions(gross, self.ptkp_status, settings);
+        trace_event!(
+            trace,
+            employee_id = %self.employee_id,
+            gross,
+            taxable = calc::annual_taxable(gross, self.ptkp_status),
+            ptkp = ?self.ptkp_status,
+            tax_table = settings.tax_table.id(),
+            tax = breakdown.tax,
+            "PPh 21 calculated"
+        );
+        breakdown
     }
 
     fn calculate_deduction(&self) -> f64 {
-        let gross = self.calculate_gross();
-        let tax_calculator = crate::tax::FulltimeTax;
-        let tax = tax_calculator.calculate_tax(gross * 12.0) / 12.0;
-        let bpjs_kesehatan = gross * 0.01;
-        let bpjs_ketenagakerjaan = gross * 0.02;
-        tax + bpjs_kesehatan + bpjs_ketenagakerjaan
+        self.deduction_breakdown().total()
     }
 
     fn calculate_net(&self) -> f64 {
-        self.calculate_gross() - self.calculate_deduction()
+        calc::net(self.calculate_gross(), &self.deduction_breakdown())
     }
 
     fn employee_type(&self) -> &str {
@@ -108,12 +227,16 @@ impl Employee for FulltimeEmployee {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ContractEmployee {
     pub employee_id: String,
     pub work_hour: f64,
     pub tunjangan: f64,
     pub periode_tunjangan: AllowancePeriod,
     pub hourly_rate: f64,
+    #[serde(default)]
+    pub profile: EmployeeProfile,
 }
 
 impl ContractEmployee {
@@ -130,8 +253,18 @@ impl ContractEmployee {
             tunjangan,
             periode_tunjangan,
             hourly_rate,
+            profile: EmployeeProfile::default(),
         }
     }
+
+    /// Tax withheld on `income`: PPh 26 for non-residents, the flat contract rate otherwise.
+    pub fn tax_on(&self, income: f64, settings: &CalculationSettings) -> f64 {
+        let tax = match &self.profile.non_resident {
+            Some(residency) => residency.tax().calculate_tax(income),
+            None => calc::contract_tax(income),
+        };
+        settings.tax_rounding.tax(tax)
+    }
 }
 
 impl Employee for ContractEmployee {
@@ -139,6 +272,10 @@ impl Employee for ContractEmployee {
         &self.employee_id
     }
 
+    fn profile(&self) -> &EmployeeProfile {
+        &self.profile
+    }
+
     fn work_hour(&self) -> f64 {
         self.work_hour
     }
@@ -152,25 +289,41 @@ impl Employee for ContractEmployee {
     }
 
     fn calculate_gross(&self) -> f64 {
-        let base_pay = self.work_hour * self.hourly_rate;
+        self.gross_with(&CalculationSettings::DEFAULT)
+    }
 
-        let monthly_tunjangan = match self.periode_tunjangan {
-            AllowancePeriod::Monthly => self.tunjangan,
-            AllowancePeriod::Yearly => self.tunjangan / 12.0,
-            AllowancePeriod::PerProject => self.tunjangan,
-        };
+    fn calculate_tax(&self) -> f64 {
+        self.deduction_breakdown().tax
+    }
+
+    fn deduction_breakdown(&self) -> DeductionBreakdown {
+        self.deduction_breakdown_with(&CalculationSettings::DEFAULT)
+    }
 
-        base_pay + monthly_tunjangan
+    fn gross_with(&self, _settings: &CalculationSettings) -> f64 {
+        calc::contract_gross(self.hourly_rate, self.work_hour, self.tunjangan, self.periode_tunjangan)
+    }
+
+    fn earning_breakdown_with(&self, _settings: &CalculationSettings) -> EarningBreakdown {
+        calc::contract_earnings(self.hourly_rate, self.work_hour, self.tunjangan, self.periode_tunjangan)
+    }
+
+    fn deduction_breakdown_with(&self, settings: &CalculationSettings) -> DeductionBreakdown {
+        let gross = self.gross_with(settings);
+        let breakdown = DeductionBreakdown {
+            tax: self.tax_on(gross, settings),
+            ..calc::contract_deductions(gross)
+        };
+        trace_event!(trace, employee_id = %self.employee_id, gross, tax = breakdown.tax, "contract tax calculated");
+        breakdown
     }
 
     fn calculate_deduction(&self) -> f64 {
-        let gross = self.calculate_gross();
-        let tax_calculator = crate::tax::ContractTax;
-        tax_calculator.calculate_tax(gross)
+        self.deduction_breakdown().total()
     }
 
     fn calculate_net(&self) -> f64 {
-        self.calculate_gross() - self.calculate_deduction()
+        calc::net(self.calculate_gross(), &self.deduction_breakdown())
     }
 
     fn employee_type(&self) -> &str {
@@ -0,0 +1,168 @@
+use crate::error::StorageError;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+pub const MAGIC: &[u8] = b"EMPENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone)]
+pub enum EncryptionKey {
+    Passphrase(String),
+    KeyFile(PathBuf),
+}
+
+impl EncryptionKey {
+    fn derive(&self, salt: &[u8]) -> Result<[u8; 32], StorageError> {
+        let mut key = [0u8; 32];
+        match self {
+            EncryptionKey::Passphrase(passphrase) => {
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|err| StorageError::Encryption(err.to_string()))?;
+            }
+            EncryptionKey::KeyFile(path) => {
+                let contents = std::fs::read(path)?;
+                let mut hasher = Sha256::new();
+                hasher.update(salt);
+                hasher.update(&contents);
+                key.copy_from_slice(&hasher.finalize());
+            }
+        }
+        Ok(key)
+    }
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn encrypt(plaintext: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, StorageError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let derived = key.derive(&salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&derived));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|err| StorageError::Encryption(err.to_string()))?;
+
+    let mut output = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+pub fn decrypt(bytes: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, StorageError> {
+    let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if !is_encrypted(bytes) || bytes.len() < header_len {
+        return Err(StorageError::Encryption("not an encrypted data file".to_string()));
+    }
+    let salt = &bytes[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = Nonce::from_slice(&bytes[MAGIC.len() + SALT_LEN..header_len]);
+    let derived = key.derive(salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&derived));
+    cipher
+        .decrypt(nonce, &bytes[header_len..])
+        .map_err(|_| StorageError::Encryption("wrong key or corrupted data file".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::{EmployeeData, Payroll};
+    use crate::storage::{JsonFileStorage, Storage};
+
+    /// A file under the temp directory, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("employee-encryption-{}-{}", std::process::id(), name));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+            let _ = std::fs::remove_file(self.0.with_extension("lock"));
+        }
+    }
+
+    fn passphrase(text: &str) -> EncryptionKey {
+        EncryptionKey::Passphrase(text.to_string())
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    #[test]
+    fn a_passphrase_round_trips_and_hides_the_plaintext() {
+        let plaintext = br#"{"employee_id":"E1","base_salary":10000000}"#;
+        let first = encrypt(plaintext, &passphrase("correct horse")).unwrap();
+        let second = encrypt(plaintext, &passphrase("correct horse")).unwrap();
+
+        assert!(is_encrypted(&first));
+        assert!(!contains(&first, b"employee_id"));
+        assert_ne!(first, second, "each encryption draws a fresh salt and nonce");
+        assert_eq!(decrypt(&first, &passphrase("correct horse")).unwrap(), plaintext);
+        assert_eq!(decrypt(&second, &passphrase("correct horse")).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn a_wrong_key_or_altered_byte_is_refused() {
+        let encrypted = encrypt(b"payroll", &passphrase("right")).unwrap();
+        assert!(matches!(decrypt(&encrypted, &passphrase("wrong")), Err(StorageError::Encryption(_))));
+
+        let mut altered = encrypted.clone();
+        *altered.last_mut().unwrap() ^= 1;
+        assert!(matches!(decrypt(&altered, &passphrase("right")), Err(StorageError::Encryption(_))));
+        assert!(matches!(decrypt(b"{}", &passphrase("right")), Err(StorageError::Encryption(_))));
+    }
+
+    #[test]
+    fn a_key_file_round_trips() {
+        let key_file = TempFile::new("key");
+        std::fs::write(&key_file.0, [7u8; 32]).unwrap();
+        let key = EncryptionKey::KeyFile(key_file.0.clone());
+
+        let encrypted = encrypt(b"payroll", &key).unwrap();
+        assert_eq!(decrypt(&encrypted, &key).unwrap(), b"payroll");
+        std::fs::write(&key_file.0, [8u8; 32]).unwrap();
+        assert!(decrypt(&encrypted, &key).is_err());
+    }
+
+    #[test]
+    fn an_encrypted_data_file_loads_only_with_its_key() {
+        let data = TempFile::new("data.json");
+        let storage = JsonFileStorage::new(&data.0).with_encryption(passphrase("secret"));
+        let mut payroll = Payroll::new();
+        payroll.add_employee(EmployeeData::Fulltime(FulltimeEmployee::new(
+            "E1".to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            10_000_000.0,
+        )));
+        storage.save(&payroll).unwrap();
+
+        let bytes = std::fs::read(&data.0).unwrap();
+        assert!(is_encrypted(&bytes));
+        assert!(!contains(&bytes, b"base_salary"));
+        assert!(storage.load().unwrap().get_employee("E1").is_some());
+        assert!(matches!(JsonFileStorage::new(&data.0).load(), Err(StorageError::EncryptedFile)));
+        let wrong = JsonFileStorage::new(&data.0).with_encryption(passphrase("guess"));
+        assert!(matches!(wrong.load(), Err(StorageError::Encryption(_))));
+    }
+}
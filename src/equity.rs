@@ -0,0 +1,299 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// The deduction line offsetting vested equity, which is paid in shares rather than cash.
+pub const EQUITY_SETTLED: &str = "Equity Vesting Settled in Shares";
+/// The deduction line withholding PPh 21 on vested equity.
+pub const EQUITY_TAX: &str = "PPh 21 on Equity Vesting";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquityKind {
+    /// Restricted stock units: each vested unit is a share.
+    Rsu,
+    /// Stock options: each vested unit is worth its fair value less the strike price.
+    Option,
+}
+
+impl EquityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EquityKind::Rsu => "rsu",
+            EquityKind::Option => "option",
+        }
+    }
+}
+
+impl fmt::Display for EquityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquityKind::Rsu => write!(f, "RSU"),
+            EquityKind::Option => write!(f, "Option"),
+        }
+    }
+}
+
+impl FromStr for EquityKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rsu" => Ok(EquityKind::Rsu),
+            "option" | "options" => Ok(EquityKind::Option),
+            _ => Err(format!("Unknown equity kind '{}' (expected rsu or option)", s)),
+        }
+    }
+}
+
+/// When a grant vests: nothing before `cliff_months`, then every `every_months` until it is fully
+/// vested after `total_months`. Each vesting brings the grant to the share of units its age earns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VestingSchedule {
+    pub cliff_months: u32,
+    pub every_months: u32,
+    pub total_months: u32,
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.every_months == 0 || self.total_months == 0 {
+            return Err("vesting needs a frequency and a total of at least one month".to_string());
+        }
+        if self.cliff_months > self.total_months {
+            return Err("the cliff cannot be longer than the vesting period".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Units vesting on one date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VestingEvent {
+    pub date: NaiveDate,
+    pub units: u32,
+}
+
+impl VestingEvent {
+    pub fn period(&self) -> PayPeriod {
+        PayPeriod::from_date(self.date)
+    }
+}
+
+/// Stock options or RSUs granted to an employee. Vested units count as taxable benefit-in-kind
+/// income in the pay period they vest in, valued at the grant's current fair value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityGrant {
+    pub id: u32,
+    pub employee_id: String,
+    pub kind: EquityKind,
+    pub units: u32,
+    /// Price an option holder pays per share; 0 for RSUs.
+    pub strike_price: f64,
+    /// Value of one share, used for vesting income.
+    pub fair_value: f64,
+    pub granted: NaiveDate,
+    pub schedule: VestingSchedule,
+    /// Units not vested by this date are forfeited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forfeited: Option<NaiveDate>,
+    pub recorded: DateTime<Utc>,
+}
+
+impl EquityGrant {
+    /// Every vesting of the grant, up to its forfeiture.
+    pub fn vesting_events(&self) -> Vec<VestingEvent> {
+        let schedule = self.schedule;
+        let vested_after = |months: u32| (self.units as u64 * months as u64 / schedule.total_months as u64) as u32;
+        let mut events = Vec::new();
+        let mut vested = 0;
+        let mut months = schedule.every_months;
+        loop {
+            let at = months.min(schedule.total_months);
+            if at >= schedule.cliff_months {
+                let Some(date) = self.granted.checked_add_months(Months::new(at)) else {
+                    break;
+                };
+                if self.forfeited.is_some_and(|forfeited| date > forfeited) {
+                    break;
+                }
+                let units = vested_after(at) - vested;
+                if units > 0 {
+                    events.push(VestingEvent { date, units });
+                    vested += units;
+                }
+            }
+            if at == schedule.total_months {
+                break;
+            }
+            months += schedule.every_months;
+        }
+        events
+    }
+
+    /// Benefit-in-kind income from `units` vested units.
+    pub fn value_of(&self, units: u32) -> f64 {
+        units as f64 * (self.fair_value - self.strike_price).max(0.0)
+    }
+
+    pub fn vested_units(&self, on: NaiveDate) -> u32 {
+        self.vesting_events()
+            .iter()
+            .filter(|event| event.date <= on)
+            .map(|event| event.units)
+            .sum()
+    }
+}
+
+/// Every equity grant made.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EquityGrants {
+    grants: Vec<EquityGrant>,
+}
+
+impl EquityGrants {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn grant(
+        &mut self,
+        employee_id: &str,
+        kind: EquityKind,
+        units: u32,
+        strike_price: f64,
+        fair_value: f64,
+        granted: NaiveDate,
+        schedule: VestingSchedule,
+    ) -> Result<&EquityGrant, PayrollError> {
+        if units == 0 {
+            return Err(PayrollError::InvalidEquityGrant("units must be positive".to_string()));
+        }
+        for (name, price) in [("strike price", strike_price), ("fair value", fair_value)] {
+            if !price.is_finite() || price < 0.0 {
+                return Err(PayrollError::InvalidEquityGrant(format!("{} must not be negative", name)));
+            }
+        }
+        if kind == EquityKind::Rsu && strike_price > 0.0 {
+            return Err(PayrollError::InvalidEquityGrant("RSUs have no strike price".to_string()));
+        }
+        schedule.validate().map_err(PayrollError::InvalidEquityGrant)?;
+        let id = self.grants.iter().map(|grant| grant.id).max().unwrap_or(0) + 1;
+        self.grants.push(EquityGrant {
+            id,
+            employee_id: employee_id.to_string(),
+            kind,
+            units,
+            strike_price,
+            fair_value,
+            granted,
+            schedule,
+            forfeited: None,
+            recorded: Utc::now(),
+        });
+        Ok(self.grants.last().expect("grant was just pushed"))
+    }
+
+    fn get_mut(&mut self, id: u32) -> Result<&mut EquityGrant, PayrollError> {
+        self.grants
+            .iter_mut()
+            .find(|grant| grant.id == id)
+            .ok_or(PayrollError::EquityGrantNotFound(id))
+    }
+
+    /// Sets the share value that vestings processed from now on are taxed at.
+    pub(crate) fn revalue(&mut self, id: u32, fair_value: f64) -> Result<&EquityGrant, PayrollError> {
+        if !fair_value.is_finite() || fair_value < 0.0 {
+            return Err(PayrollError::InvalidEquityGrant("fair value must not be negative".to_string()));
+        }
+        let grant = self.get_mut(id)?;
+        grant.fair_value = fair_value;
+        Ok(grant)
+    }
+
+    /// Forfeits the units of a grant that have not vested by `on`.
+    pub(crate) fn forfeit(&mut self, id: u32, on: NaiveDate) -> Result<&EquityGrant, PayrollError> {
+        let grant = self.get_mut(id)?;
+        grant.forfeited = Some(on);
+        Ok(grant)
+    }
+
+    /// Grants of one employee, or of everyone, by employee and grant date.
+    pub fn of(&self, employee_id: Option<&str>) -> Vec<&EquityGrant> {
+        let mut grants: Vec<&EquityGrant> = self
+            .grants
+            .iter()
+            .filter(|grant| employee_id.is_none_or(|id| grant.employee_id == id))
+            .collect();
+        grants.sort_by(|a, b| (&a.employee_id, a.granted, a.id).cmp(&(&b.employee_id, b.granted, b.id)));
+        grants
+    }
+
+    /// Every vesting falling in `period`, with its grant.
+    pub fn vesting_in(&self, period: &PayPeriod) -> Vec<(&EquityGrant, VestingEvent)> {
+        let mut events: Vec<(&EquityGrant, VestingEvent)> = self
+            .grants
+            .iter()
+            .flat_map(|grant| grant.vesting_events().into_iter().map(move |event| (grant, event)))
+            .filter(|(_, event)| event.period() == *period)
+            .collect();
+        events.sort_by(|(a, x), (b, y)| (&a.employee_id, x.date).cmp(&(&b.employee_id, y.date)));
+        events
+    }
+
+    /// Adds the equity vesting in the record's period as benefit-in-kind income: taxed like pay, but
+    /// settled in shares, so only the PPh 21 on it comes out of net pay.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee_id = record.employee.as_employee().employee_id().to_string();
+        let mut income = 0.0;
+        for (grant, event) in self.vesting_in(&period) {
+            let value = grant.value_of(event.units);
+            if grant.employee_id != employee_id || value <= 0.0 {
+                continue;
+            }
+            record.extra_earnings.push(PayrollComponent {
+                name: format!("{} Vesting (grant #{}, {} units)", grant.kind, grant.id, event.units),
+                amount: value,
+            });
+            income += value;
+        }
+        if income <= 0.0 {
+            return;
+        }
+        let tax = record.add_taxable_income(income);
+        record.gross_salary += income;
+        record.deductions += income + tax;
+        record.extra_deductions.push(PayrollComponent {
+            name: EQUITY_SETTLED.to_string(),
+            amount: income,
+        });
+        if tax > 0.0 {
+            record.extra_deductions.push(PayrollComponent {
+                name: EQUITY_TAX.to_string(),
+                amount: tax,
+            });
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+
+    /// Moves the employee's grants to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.grants.iter_mut().map(|grant| renaming.id(&mut grant.employee_id)).sum()
+    }
+}
+
+impl Deref for EquityGrants {
+    type Target = [EquityGrant];
+
+    fn deref(&self) -> &[EquityGrant] {
+        &self.grants
+    }
+}
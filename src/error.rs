@@ -0,0 +1,366 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayrollError {
+    PeriodClosed(String),
+    PeriodNotClosed(String),
+    PermissionDenied(String),
+    RecordNotFound(usize),
+    RecordVoided(usize),
+    RecordNotVoided(usize),
+    AlreadyProcessed(String, String),
+    NoRecordsInPeriod(String),
+    RunNotFound(u32),
+    RunNotApproved(u32),
+    RunAlreadyApproved(u32),
+    RunAlreadyDisbursed(u32),
+    Disbursement(String),
+    EmployeeSource(String),
+    EmployeeNotFound(String),
+    EmployeeHasHistory(String, usize),
+    NotOnProbation(String),
+    NotTerminated(String),
+    RetentionNotElapsed(String, chrono::NaiveDate),
+    AlreadyPseudonymized(String),
+    InvalidSalaryChange(String),
+    ChangeRequestNotFound(u32),
+    ChangeRequestDecided(u32),
+    NotApprover(u32, String),
+    ReportingCycle(String, String),
+    InvalidTimesheet(String),
+    TimesheetNotFound(String, String),
+    TimesheetApproved(String, String),
+    TimesheetNotApproved(String, String),
+    NotManager(String, String),
+    InvalidOvertimeRequest(String),
+    InvalidAttendance(String),
+    InvalidTravel(String),
+    TravelNotFound(u32),
+    InvalidExpenseClaim(String),
+    ExpenseCapExceeded(String),
+    ExpenseClaimNotFound(u32),
+    InvalidInsurance(String),
+    InvalidEquityGrant(String),
+    EquityGrantNotFound(u32),
+    InvalidNatura(String),
+    NaturaNotFound(u32),
+    InvalidResidency(String),
+    InvalidTaxRegime(String),
+    NotEnrolled(String, String),
+    OvertimeRequestNotFound(u32),
+    OvertimeRequestDecided(u32),
+    ReviewCycleNotFound(String),
+    ReviewCycleExists(String),
+    ReviewCycleClosed(String),
+    InvalidScore(String),
+    NoOnboardingChecklist(String),
+    OnboardingIncomplete(Vec<String>),
+    InvalidPin(String),
+    InvalidCredentials,
+    AccountLocked(chrono::DateTime<chrono::Utc>),
+    PinChangeRequired,
+    BlankOwnerPassword,
+}
+
+impl fmt::Display for PayrollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayrollError::PeriodClosed(period) => {
+                write!(f, "pay period '{}' is closed", period)
+            }
+            PayrollError::PeriodNotClosed(period) => {
+                write!(f, "pay period '{}' is not closed", period)
+            }
+            PayrollError::PermissionDenied(action) => {
+                write!(f, "permission denied: {} requires the admin scope", action)
+            }
+            PayrollError::RecordNotFound(index) => {
+                write!(f, "payroll record #{} not found", index)
+            }
+            PayrollError::RecordVoided(index) => {
+                write!(f, "payroll record #{} has been voided", index)
+            }
+            PayrollError::RecordNotVoided(index) => {
+                write!(f, "payroll record #{} is not voided", index)
+            }
+            PayrollError::AlreadyProcessed(employee_id, period) => write!(
+                f,
+                "{} is already processed for {}; void that record first",
+                employee_id, period
+            ),
+            PayrollError::NoRecordsInPeriod(period) => {
+                write!(f, "no payroll records found for pay period '{}'", period)
+            }
+            PayrollError::RunNotFound(run_id) => write!(f, "payroll run #{} not found", run_id),
+            PayrollError::RunNotApproved(run_id) => {
+                write!(f, "payroll run #{} has not been approved", run_id)
+            }
+            PayrollError::RunAlreadyApproved(run_id) => {
+                write!(f, "payroll run #{} is already approved", run_id)
+            }
+            PayrollError::RunAlreadyDisbursed(run_id) => {
+                write!(f, "payroll run #{} has already been submitted for payment", run_id)
+            }
+            PayrollError::Disbursement(reason) => write!(f, "disbursement failed: {}", reason),
+            PayrollError::EmployeeSource(reason) => write!(f, "employee sync failed: {}", reason),
+            PayrollError::EmployeeNotFound(employee_id) => write!(f, "employee {} not found", employee_id),
+            PayrollError::EmployeeHasHistory(employee_id, records) => write!(
+                f,
+                "employee {} has {} payroll records; archive the employee instead",
+                employee_id, records
+            ),
+            PayrollError::NotOnProbation(employee_id) => write!(f, "employee {} is not on probation", employee_id),
+            PayrollError::NotTerminated(employee_id) => write!(f, "employee {} is not terminated", employee_id),
+            PayrollError::RetentionNotElapsed(employee_id, until) => write!(
+                f,
+                "employee {}'s data has to be kept until {}",
+                employee_id,
+                until.format("%Y-%m-%d")
+            ),
+            PayrollError::AlreadyPseudonymized(employee_id) => {
+                write!(f, "employee {} has already been pseudonymized", employee_id)
+            }
+            PayrollError::InvalidSalaryChange(reason) => write!(f, "invalid salary change: {}", reason),
+            PayrollError::ChangeRequestNotFound(id) => write!(f, "salary change request #{} not found", id),
+            PayrollError::ChangeRequestDecided(id) => {
+                write!(f, "salary change request #{} has already been decided", id)
+            }
+            PayrollError::NotApprover(id, approver) => {
+                write!(f, "salary change request #{} can only be decided by {}", id, approver)
+            }
+            PayrollError::ReportingCycle(employee_id, manager_id) => write!(
+                f,
+                "{} cannot report to {}: {} is already in their reporting line",
+                employee_id, manager_id, employee_id
+            ),
+            PayrollError::InvalidTimesheet(reason) => write!(f, "invalid timesheet: {}", reason),
+            PayrollError::TimesheetNotFound(employee_id, period) => {
+                write!(f, "no timesheet from {} for {}", employee_id, period)
+            }
+            PayrollError::TimesheetApproved(employee_id, period) => {
+                write!(f, "the {} timesheet of {} is already approved", period, employee_id)
+            }
+            PayrollError::TimesheetNotApproved(employee_id, period) => write!(
+                f,
+                "the {} timesheet of {} has not been approved; its hours cannot be paid yet",
+                period, employee_id
+            ),
+            PayrollError::NotManager(approver_id, employee_id) => {
+                write!(f, "{} is not in the reporting chain of {}", approver_id, employee_id)
+            }
+            PayrollError::InvalidOvertimeRequest(reason) => write!(f, "invalid overtime request: {}", reason),
+            PayrollError::InvalidAttendance(reason) => write!(f, "invalid attendance: {}", reason),
+            PayrollError::InvalidTravel(reason) => write!(f, "invalid travel record: {}", reason),
+            PayrollError::TravelNotFound(id) => write!(f, "travel record #{} not found", id),
+            PayrollError::InvalidExpenseClaim(reason) => write!(f, "invalid expense claim: {}", reason),
+            PayrollError::ExpenseCapExceeded(reason) => write!(f, "expense claim rejected: {}", reason),
+            PayrollError::ExpenseClaimNotFound(id) => write!(f, "expense claim #{} not found", id),
+            PayrollError::InvalidInsurance(reason) => write!(f, "invalid insurance enrollment: {}", reason),
+            PayrollError::InvalidEquityGrant(reason) => write!(f, "invalid equity grant: {}", reason),
+            PayrollError::EquityGrantNotFound(id) => write!(f, "equity grant #{} not found", id),
+            PayrollError::InvalidNatura(reason) => write!(f, "invalid benefit in kind: {}", reason),
+            PayrollError::NaturaNotFound(id) => write!(f, "benefit in kind #{} not found", id),
+            PayrollError::InvalidResidency(reason) => write!(f, "invalid tax residency: {}", reason),
+            PayrollError::InvalidTaxRegime(reason) => write!(f, "invalid tax regime: {}", reason),
+            PayrollError::NotEnrolled(employee_id, plan) => {
+                write!(f, "{} is not enrolled in insurance plan {}", employee_id, plan)
+            }
+            PayrollError::OvertimeRequestNotFound(id) => write!(f, "overtime request #{} not found", id),
+            PayrollError::OvertimeRequestDecided(id) => {
+                write!(f, "overtime request #{} has already been decided", id)
+            }
+            PayrollError::ReviewCycleNotFound(name) => write!(f, "review cycle '{}' not found", name),
+            PayrollError::ReviewCycleExists(name) => write!(f, "review cycle '{}' already exists", name),
+            PayrollError::ReviewCycleClosed(name) => write!(f, "review cycle '{}' is closed", name),
+            PayrollError::InvalidScore(reason) => write!(f, "invalid review score: {}", reason),
+            PayrollError::NoOnboardingChecklist(employee_id) => {
+                write!(f, "employee {} has no onboarding checklist", employee_id)
+            }
+            PayrollError::OnboardingIncomplete(employee_ids) => write!(
+                f,
+                "onboarding is not finished for {}; their records are held from payroll runs",
+                employee_ids.join(", ")
+            ),
+            PayrollError::InvalidPin(reason) => write!(f, "invalid PIN: {}", reason),
+            PayrollError::InvalidCredentials => write!(f, "unknown employee ID or wrong PIN"),
+            PayrollError::AccountLocked(until) => write!(
+                f,
+                "sign-in is locked after too many wrong PINs; try again after {}",
+                until.with_timezone(&chrono::Local).format("%H:%M")
+            ),
+            PayrollError::PinChangeRequired => write!(f, "the PIN was issued by an administrator and must be changed"),
+            PayrollError::BlankOwnerPassword => {
+                write!(f, "the PDF owner password must not be blank; leave it out to have one generated")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PayrollError {}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Storage(StorageError),
+    #[cfg(feature = "xlsx")]
+    Xlsx(rust_xlsxwriter::XlsxError),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet::errors::ParquetError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(err) => write!(f, "export failed: {}", err),
+            ExportError::Storage(err) => write!(f, "export failed: {}", err),
+            #[cfg(feature = "xlsx")]
+            ExportError::Xlsx(err) => write!(f, "xlsx export failed: {}", err),
+            #[cfg(feature = "parquet")]
+            ExportError::Parquet(err) => write!(f, "parquet export failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        ExportError::Io(err)
+    }
+}
+
+#[cfg(feature = "xlsx")]
+impl From<rust_xlsxwriter::XlsxError> for ExportError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        ExportError::Xlsx(err)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for ExportError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        ExportError::Parquet(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(std::io::Error),
+    Serialization(serde_json::Error),
+    Encryption(String),
+    EncryptedFile,
+    Snapshot(String),
+    SnapshotFile,
+    UnsupportedSchemaVersion(u32),
+    #[cfg(feature = "postgres")]
+    Database(sqlx::Error),
+    /// The data file was saved by someone else since it was loaded: its revision on disk, and the
+    /// one it was loaded at.
+    Conflict(u64, u64),
+    /// The payroll ledger is signed and no key was given to sign the next entries with.
+    LedgerKeyRequired,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "storage I/O failed: {}", err),
+            StorageError::Serialization(err) => write!(f, "invalid data file: {}", err),
+            StorageError::Encryption(err) => write!(f, "encryption failed: {}", err),
+            StorageError::EncryptedFile => {
+                write!(f, "data file is encrypted and no key was provided")
+            }
+            StorageError::Snapshot(err) => write!(f, "snapshot failed: {}", err),
+            StorageError::SnapshotFile => {
+                write!(f, "data file is a binary snapshot and this build was made without the snapshot feature")
+            }
+            StorageError::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "data was written with schema version {}, this build supports up to {}",
+                version,
+                crate::migration::CURRENT_SCHEMA_VERSION
+            ),
+            StorageError::Conflict(stored, loaded) => write!(
+                f,
+                "data modified by another process (revision {} on disk, {} when loaded); load it again and retry",
+                stored, loaded
+            ),
+            StorageError::LedgerKeyRequired => {
+                write!(f, "the payroll ledger is signed and no key was provided (set PAYROLL_LEDGER_KEY)")
+            }
+            #[cfg(feature = "postgres")]
+            StorageError::Database(err) => write!(f, "database error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(err: serde_json::Error) -> Self {
+        StorageError::Serialization(err)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<sqlx::Error> for StorageError {
+    fn from(err: sqlx::Error) -> Self {
+        StorageError::Database(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read config file: {}", err),
+            ConfigError::Parse(err) => write!(f, "invalid config file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+/// Why an API request was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    MissingCredentials,
+    UnknownApiKey,
+    InvalidToken(String),
+    TokenExpired,
+    /// The client is known but lacks this scope.
+    InsufficientScope(crate::auth::Scope),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => {
+                write!(f, "authentication required: send an API key or token as `Authorization: Bearer ...`")
+            }
+            AuthError::UnknownApiKey => write!(f, "unknown API key"),
+            AuthError::InvalidToken(reason) => write!(f, "invalid token: {}", reason),
+            AuthError::TokenExpired => write!(f, "token expired"),
+            AuthError::InsufficientScope(scope) => write!(f, "this request needs the {} scope", scope),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
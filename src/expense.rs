@@ -0,0 +1,274 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// What happens to a claim that would take a category over its monthly cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverCap {
+    /// Pay what is left under the cap.
+    #[default]
+    Truncate,
+    /// Refuse the claim.
+    Reject,
+}
+
+impl OverCap {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverCap::Truncate => "truncate",
+            OverCap::Reject => "reject",
+        }
+    }
+}
+
+impl fmt::Display for OverCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OverCap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "truncate" => Ok(OverCap::Truncate),
+            "reject" => Ok(OverCap::Reject),
+            _ => Err(format!("Unknown over-cap policy '{}' (expected truncate or reject)", s)),
+        }
+    }
+}
+
+/// The most reimbursed per month in one category. Without a grade it applies to every grade that
+/// has no cap of its own for the category.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpenseCap {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    pub category: String,
+    pub monthly: f64,
+}
+
+/// Monthly reimbursement caps by grade and category (`[expenses]` in the configuration).
+/// Categories without a cap are reimbursed in full.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpensePolicy {
+    pub over_cap: OverCap,
+    pub caps: Vec<ExpenseCap>,
+}
+
+impl ExpensePolicy {
+    /// The monthly cap on `category` for an employee of `grade`, if any.
+    pub fn cap(&self, grade: Option<&str>, category: &str) -> Option<&ExpenseCap> {
+        let in_category = |cap: &&ExpenseCap| cap.category.eq_ignore_ascii_case(category);
+        self.caps
+            .iter()
+            .filter(in_category)
+            .find(|cap| grade.is_some_and(|grade| cap.grade.as_deref() == Some(grade)))
+            .or_else(|| self.caps.iter().filter(in_category).find(|cap| cap.grade.is_none()))
+    }
+
+    /// How much of a claim of `amount` is paid when `claimed` has already been reimbursed in the
+    /// category this month, with the reason it was cut; or why it is refused.
+    pub fn check(
+        &self,
+        grade: Option<&str>,
+        category: &str,
+        claimed: f64,
+        amount: f64,
+    ) -> Result<(f64, Option<String>), String> {
+        let Some(cap) = self.cap(grade, category) else {
+            return Ok((amount, None));
+        };
+        let left = (cap.monthly - claimed).max(0.0);
+        if amount <= left {
+            return Ok((amount, None));
+        }
+        let limit = match &cap.grade {
+            Some(grade) => format!("the grade {} cap of {:.2} a month", grade, cap.monthly),
+            None => format!("the cap of {:.2} a month", cap.monthly),
+        };
+        let reason = format!(
+            "{} claims would reach {:.2}, above {} ({:.2} left)",
+            category,
+            claimed + amount,
+            limit,
+            left
+        );
+        match self.over_cap {
+            OverCap::Truncate if left > 0.0 => Ok((left, Some(format!("truncated: {}", reason)))),
+            _ => Err(reason),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for cap in &self.caps {
+            if cap.category.trim().is_empty() {
+                return Err("expenses.caps entries need a category".to_string());
+            }
+            if !cap.monthly.is_finite() || cap.monthly < 0.0 {
+                return Err(format!("expenses cap for {} must not be negative", cap.category));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A reimbursement claim, paid as a non-taxable earning in `period`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpenseClaim {
+    pub id: u32,
+    pub employee_id: String,
+    pub category: String,
+    pub period: PayPeriod,
+    /// The amount claimed.
+    pub amount: f64,
+    /// The amount reimbursed, less than claimed when the claim was truncated at the cap.
+    pub paid: f64,
+    pub description: String,
+    pub submitted: DateTime<Utc>,
+    /// Why less than the amount claimed is paid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ExpenseClaim {
+    pub fn is_truncated(&self) -> bool {
+        self.paid < self.amount
+    }
+}
+
+/// Every reimbursement claim submitted, with the caps new claims are checked against.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExpenseClaims {
+    claims: Vec<ExpenseClaim>,
+    #[serde(skip)]
+    policy: ExpensePolicy,
+}
+
+impl ExpenseClaims {
+    pub fn policy(&self) -> &ExpensePolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: ExpensePolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get(&self, id: u32) -> Option<&ExpenseClaim> {
+        self.claims.iter().find(|claim| claim.id == id)
+    }
+
+    /// Stores a claim by an employee of `grade`. One that would take the category over its monthly
+    /// cap is truncated or rejected, as the policy says.
+    pub(crate) fn submit(
+        &mut self,
+        employee_id: &str,
+        grade: Option<&str>,
+        category: &str,
+        period: PayPeriod,
+        amount: f64,
+        description: &str,
+    ) -> Result<&ExpenseClaim, PayrollError> {
+        let category = category.trim().to_lowercase();
+        if category.is_empty() {
+            return Err(PayrollError::InvalidExpenseClaim("category is required".to_string()));
+        }
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(PayrollError::InvalidExpenseClaim("amount must be positive".to_string()));
+        }
+        let claimed = self
+            .claims
+            .iter()
+            .filter(|claim| claim.employee_id == employee_id && claim.period == period && claim.category == category)
+            .map(|claim| claim.paid)
+            .sum();
+        let (paid, note) = self
+            .policy
+            .check(grade, &category, claimed, amount)
+            .map_err(PayrollError::ExpenseCapExceeded)?;
+        let id = self.claims.iter().map(|claim| claim.id).max().unwrap_or(0) + 1;
+        self.claims.push(ExpenseClaim {
+            id,
+            employee_id: employee_id.to_string(),
+            category,
+            period,
+            amount,
+            paid,
+            description: description.trim().to_string(),
+            submitted: Utc::now(),
+            note,
+        });
+        Ok(self.claims.last().expect("claim was just pushed"))
+    }
+
+    pub(crate) fn cancel(&mut self, id: u32) -> Result<ExpenseClaim, PayrollError> {
+        let index = self
+            .claims
+            .iter()
+            .position(|claim| claim.id == id)
+            .ok_or(PayrollError::ExpenseClaimNotFound(id))?;
+        Ok(self.claims.remove(index))
+    }
+
+    /// Claims paid in `period`, or all claims, in the order they were submitted.
+    pub fn in_period(&self, period: Option<&PayPeriod>) -> Vec<&ExpenseClaim> {
+        self.claims
+            .iter()
+            .filter(|claim| period.is_none_or(|period| claim.period == *period))
+            .collect()
+    }
+
+    /// Adds the record's reimbursements to its earnings, one line per category. They are not taxed.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee_id = record.employee.as_employee().employee_id().to_string();
+        let mut lines: Vec<PayrollComponent> = Vec::new();
+        for claim in self
+            .claims
+            .iter()
+            .filter(|claim| claim.employee_id == employee_id && claim.period == period)
+        {
+            let name = format!("Reimbursement ({})", claim.category);
+            match lines.iter_mut().find(|line| line.name == name) {
+                Some(line) => line.amount += claim.paid,
+                None => lines.push(PayrollComponent { name, amount: claim.paid }),
+            }
+        }
+        for line in lines {
+            record.gross_salary += line.amount;
+            record.net_salary += line.amount;
+            record.extra_earnings.push(line);
+        }
+    }
+
+    /// Drops the claims paid in periods before `cutoff`.
+    pub(crate) fn purge_before(&mut self, cutoff: PayPeriod) {
+        self.claims.retain(|claim| claim.period >= cutoff);
+    }
+
+    /// Moves the employee's claims to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.claims.iter_mut().map(|claim| renaming.id(&mut claim.employee_id)).sum()
+    }
+}
+
+impl Deref for ExpenseClaims {
+    type Target = [ExpenseClaim];
+
+    fn deref(&self) -> &[ExpenseClaim] {
+        &self.claims
+    }
+}
@@ -0,0 +1,104 @@
+pub mod audit_bundle;
+pub mod ebupot;
+pub mod org_chart;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod register;
+pub mod sipp;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::read_model::DepartmentMonth;
+use crate::report::MonthlyCost;
+use std::io::{self, Write};
+
+pub struct PeriodTotals {
+    pub pay_period: String,
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+pub fn period_totals(records: &[PayrollData]) -> Vec<PeriodTotals> {
+    let mut totals: Vec<PeriodTotals> = Vec::new();
+    for record in records.iter().filter(|record| !record.voided) {
+        let entry = match totals
+            .iter_mut()
+            .position(|t| PayPeriod::same_period(&t.pay_period, &record.pay_period))
+        {
+            Some(index) => &mut totals[index],
+            None => {
+                totals.push(PeriodTotals {
+                    pay_period: record.pay_period.clone(),
+                    employees: 0,
+                    gross: 0.0,
+                    deductions: 0.0,
+                    net: 0.0,
+                });
+                totals.last_mut().unwrap()
+            }
+        };
+        entry.employees += 1;
+        entry.gross += record.gross_salary;
+        entry.deductions += record.deductions;
+        entry.net += record.net_salary;
+    }
+    totals
+}
+
+pub fn write_cost_trend_csv<W: Write>(months: &[MonthlyCost], mut writer: W) -> io::Result<()> {
+    write_csv_row(
+        &mut writer,
+        &["Period", "Headcount", "Total Gross", "Employer Cost", "Average Gross"].map(String::from),
+    )?;
+    for month in months {
+        write_csv_row(
+            &mut writer,
+            &[
+                format!("{}-{:02}", month.period.year, month.period.month),
+                month.headcount.to_string(),
+                format!("{:.2}", month.gross),
+                format!("{:.2}", month.employer_cost),
+                format!("{:.2}", month.average_gross()),
+            ],
+        )?;
+    }
+    writer.flush()
+}
+
+pub fn write_department_totals_csv<W: Write>(months: &[DepartmentMonth], mut writer: W) -> io::Result<()> {
+    write_csv_row(
+        &mut writer,
+        &["Period", "Department", "Employees", "Total Gross", "Total Deductions", "Total Net"].map(String::from),
+    )?;
+    for month in months {
+        write_csv_row(
+            &mut writer,
+            &[
+                month.pay_period.clone(),
+                month.department.clone().unwrap_or_default(),
+                month.employees.to_string(),
+                format!("{:.2}", month.gross),
+                format!("{:.2}", month.deductions),
+                format!("{:.2}", month.net),
+            ],
+        )?;
+    }
+    writer.flush()
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_csv_row<W: Write>(writer: &mut W, fields: &[String]) -> io::Result<()> {
+    let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    writeln!(writer, "{}", row.join(","))
+}
@@ -0,0 +1,203 @@
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::error::ExportError;
+use crate::export::register::write_register_csv;
+use crate::export::write_csv_row;
+use crate::ledger::PayrollLedger;
+use crate::migration::{legacy_schema_version, CURRENT_SCHEMA_VERSION};
+use crate::payroll::{Payroll, PayrollData};
+use crate::payslip::to_hex;
+use crate::period::PayPeriod;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BundleFile {
+    pub name: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuditBundleManifest {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub from: PayPeriod,
+    pub to: PayPeriod,
+    pub generated_at: DateTime<Utc>,
+    pub records: usize,
+    pub files: Vec<BundleFile>,
+}
+
+pub fn records_in_range(payroll: &Payroll, from: PayPeriod, to: PayPeriod) -> Vec<&PayrollData> {
+    payroll
+        .get_payroll_records()
+        .iter()
+        .filter(|record| match record.pay_period.parse::<PayPeriod>() {
+            Ok(period) => period >= from && period <= to,
+            Err(_) => false,
+        })
+        .collect()
+}
+
+pub fn write_audit_bundle(
+    payroll: &Payroll,
+    ledger: Option<&PayrollLedger>,
+    rates: &BpjsRates,
+    from: PayPeriod,
+    to: PayPeriod,
+    dir: &Path,
+) -> Result<AuditBundleManifest, ExportError> {
+    fs::create_dir_all(dir)?;
+    let records = records_in_range(payroll, from, to);
+    let mut files = Vec::new();
+
+    files.push(write_file(dir, "payroll_register.csv", &payroll_register(&records)?)?);
+    files.push(write_file(dir, "tax_summary.csv", &tax_summary(&records)?)?);
+    files.push(write_file(dir, "bpjs_summary.csv", &bpjs_summary(&records, rates)?)?);
+    if let Some(ledger) = ledger {
+        files.push(write_file(dir, "audit_log.jsonl", &audit_log(ledger, from, to)?)?);
+    }
+
+    let manifest = AuditBundleManifest {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        from,
+        to,
+        generated_at: Utc::now(),
+        records: records.len(),
+        files,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(std::io::Error::from)?;
+    write_file(dir, "manifest.json", &manifest_json)?;
+    Ok(manifest)
+}
+
+fn write_file(dir: &Path, name: &str, contents: &[u8]) -> Result<BundleFile, ExportError> {
+    let path = dir.join(name);
+    if path.exists() {
+        let mut permissions = fs::metadata(&path)?.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&path, permissions)?;
+    }
+    fs::write(&path, contents)?;
+    let mut permissions = fs::metadata(&path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&path, permissions)?;
+    Ok(BundleFile {
+        name: name.to_string(),
+        sha256: to_hex(&Sha256::digest(contents)),
+    })
+}
+
+fn payroll_register(records: &[&PayrollData]) -> Result<Vec<u8>, ExportError> {
+    let mut out = Vec::new();
+    write_register_csv(records.iter().copied(), &mut out)?;
+    Ok(out)
+}
+
+fn grouped_by_period<'a>(records: &[&'a PayrollData]) -> Vec<(PayPeriod, Vec<&'a PayrollData>)> {
+    let mut groups: Vec<(PayPeriod, Vec<&PayrollData>)> = Vec::new();
+    for record in records.iter().filter(|record| !record.voided) {
+        let period = match record.pay_period.parse::<PayPeriod>() {
+            Ok(period) => period,
+            Err(_) => continue,
+        };
+        match groups.iter_mut().find(|(p, _)| *p == period) {
+            Some((_, group)) => group.push(record),
+            None => groups.push((period, vec![record])),
+        }
+    }
+    groups.sort_by_key(|(period, _)| *period);
+    groups
+}
+
+fn tax_summary(records: &[&PayrollData]) -> Result<Vec<u8>, ExportError> {
+    let mut out = Vec::new();
+    write_csv_row(&mut out, &["Pay Period", "Employees", "Gross", "PPh 21"].map(String::from))?;
+    for (period, group) in grouped_by_period(records) {
+        let gross: f64 = group.iter().map(|r| r.gross_salary).sum();
+        let tax: f64 = group
+            .iter()
+            .map(|r| r.breakdown().tax)
+            .sum();
+        write_csv_row(
+            &mut out,
+            &[
+                period.to_string(),
+                group.len().to_string(),
+                format!("{:.2}", gross),
+                format!("{:.2}", tax),
+            ],
+        )?;
+    }
+    Ok(out)
+}
+
+fn bpjs_summary(records: &[&PayrollData], rates: &BpjsRates) -> Result<Vec<u8>, ExportError> {
+    let mut out = Vec::new();
+    write_csv_row(
+        &mut out,
+        &[
+            "Pay Period",
+            "BPJS Kesehatan",
+            "JHT TK",
+            "JHT PK",
+            "JP TK",
+            "JP PK",
+            "JKK",
+            "JKM",
+        ]
+        .map(String::from),
+    )?;
+    for (period, group) in grouped_by_period(records) {
+        let kesehatan: f64 = group
+            .iter()
+            .map(|r| r.breakdown().bpjs_kesehatan)
+            .sum();
+        let mut total = BpjsContribution::default();
+        for contribution in group.iter().filter_map(|r| BpjsContribution::for_record(r, rates)) {
+            total.jht_employee += contribution.jht_employee;
+            total.jht_employer += contribution.jht_employer;
+            total.jp_employee += contribution.jp_employee;
+            total.jp_employer += contribution.jp_employer;
+            total.jkk += contribution.jkk;
+            total.jkm += contribution.jkm;
+        }
+        write_csv_row(
+            &mut out,
+            &[
+                period.to_string(),
+                format!("{:.2}", kesehatan),
+                format!("{:.2}", total.jht_employee),
+                format!("{:.2}", total.jht_employer),
+                format!("{:.2}", total.jp_employee),
+                format!("{:.2}", total.jp_employer),
+                format!("{:.2}", total.jkk),
+                format!("{:.2}", total.jkm),
+            ],
+        )?;
+    }
+    Ok(out)
+}
+
+fn audit_log(ledger: &PayrollLedger, from: PayPeriod, to: PayPeriod) -> Result<Vec<u8>, ExportError> {
+    let entries = ledger.entries().map_err(ExportError::Storage)?;
+    let mut out = Vec::new();
+    for entry in entries {
+        let in_range = match entry.event.record().pay_period.parse::<PayPeriod>() {
+            Ok(period) => period >= from && period <= to,
+            Err(_) => false,
+        };
+        if in_range {
+            out.extend(serde_json::to_vec(&entry).map_err(std::io::Error::from)?);
+            out.push(b'\n');
+        }
+    }
+    Ok(out)
+}
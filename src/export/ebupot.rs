@@ -0,0 +1,121 @@
+use crate::export::write_csv_row;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::period::PayPeriod;
+use std::borrow::Borrow;
+use std::io::{self, Write};
+
+pub const KODE_OBJEK_PEGAWAI_TETAP: &str = "21-100-01";
+pub const KODE_OBJEK_PEGAWAI_TIDAK_TETAP: &str = "21-100-03";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EbupotRow {
+    pub masa_pajak: u32,
+    pub tahun_pajak: i32,
+    pub npwp: String,
+    pub nik: String,
+    pub nama: String,
+    pub kode_objek_pajak: &'static str,
+    pub status_ptkp: String,
+    pub penghasilan_bruto: f64,
+    pub pph_dipotong: f64,
+    pub tanggal_pemotongan: String,
+}
+
+impl EbupotRow {
+    pub fn from_record(record: &PayrollData) -> Option<Self> {
+        let period = record.pay_period.parse::<PayPeriod>().ok()?;
+        let employee = record.employee.as_employee();
+        let profile = employee.profile();
+        let (kode_objek_pajak, status_ptkp) = match &record.employee {
+            EmployeeData::Fulltime(emp) => (
+                KODE_OBJEK_PEGAWAI_TETAP,
+                emp.ptkp_status.map(|status| status.code().to_string()).unwrap_or_default(),
+            ),
+            EmployeeData::Contract(_) => (KODE_OBJEK_PEGAWAI_TIDAK_TETAP, String::new()),
+        };
+
+        Some(Self {
+            masa_pajak: period.month,
+            tahun_pajak: period.year,
+            npwp: profile.npwp.clone().unwrap_or_default(),
+            nik: profile.nik.clone().unwrap_or_default(),
+            nama: profile.name.clone(),
+            kode_objek_pajak,
+            status_ptkp,
+            penghasilan_bruto: record.gross_salary,
+            pph_dipotong: record.breakdown().tax,
+            tanggal_pemotongan: record.processed_date.format("%d/%m/%Y").to_string(),
+        })
+    }
+}
+
+pub fn ebupot_rows(records: &[&PayrollData]) -> Vec<EbupotRow> {
+    records
+        .iter()
+        .filter(|record| !record.voided)
+        .filter_map(|record| EbupotRow::from_record(record))
+        .collect()
+}
+
+pub fn missing_tax_ids(records: &[&PayrollData]) -> Vec<String> {
+    let mut missing: Vec<String> = records
+        .iter()
+        .filter(|record| !record.voided)
+        .map(|record| record.employee.as_employee())
+        .filter(|employee| employee.profile().npwp.is_none() && employee.profile().nik.is_none())
+        .map(|employee| employee.employee_id().to_string())
+        .collect();
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+pub fn write_ebupot_csv<I, W>(records: I, writer: &mut W) -> io::Result<usize>
+where
+    I: IntoIterator,
+    I::Item: Borrow<PayrollData>,
+    W: Write,
+{
+    write_csv_row(
+        writer,
+        &[
+            "Masa Pajak",
+            "Tahun Pajak",
+            "Pembetulan",
+            "NPWP",
+            "NIK",
+            "Nama",
+            "Kode Objek Pajak",
+            "Status PTKP",
+            "Penghasilan Bruto",
+            "PPh Dipotong",
+            "Tanggal Pemotongan",
+        ]
+        .map(String::from),
+    )?;
+    let rows = records
+        .into_iter()
+        .filter(|record| !record.borrow().voided)
+        .filter_map(|record| EbupotRow::from_record(record.borrow()));
+    let mut count = 0;
+    for row in rows {
+        write_csv_row(
+            writer,
+            &[
+                row.masa_pajak.to_string(),
+                row.tahun_pajak.to_string(),
+                "0".to_string(),
+                row.npwp.clone(),
+                row.nik.clone(),
+                row.nama.clone(),
+                row.kode_objek_pajak.to_string(),
+                row.status_ptkp.clone(),
+                format!("{:.0}", row.penghasilan_bruto),
+                format!("{:.0}", row.pph_dipotong),
+                row.tanggal_pemotongan.clone(),
+            ],
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,61 @@
+use crate::export::write_csv_row;
+use crate::payroll::Payroll;
+use std::io::{self, Write};
+
+/// One row per employee with their manager and how many levels sit above them.
+pub fn write_org_chart_csv<W: Write>(payroll: &Payroll, mut writer: W) -> io::Result<()> {
+    write_csv_row(
+        &mut writer,
+        &["Employee ID", "Name", "Manager ID", "Department", "Grade", "Level", "Direct Reports"].map(String::from),
+    )?;
+    for employee in &payroll.employees {
+        let emp = employee.as_employee();
+        let profile = emp.profile();
+        write_csv_row(
+            &mut writer,
+            &[
+                emp.employee_id().to_string(),
+                profile.name.clone(),
+                profile.manager_id.clone().unwrap_or_default(),
+                profile.department.clone().unwrap_or_default(),
+                profile.grade.clone().unwrap_or_default(),
+                payroll.reporting_chain(emp.employee_id()).len().to_string(),
+                payroll.direct_reports(emp.employee_id()).len().to_string(),
+            ],
+        )?;
+    }
+    writer.flush()
+}
+
+fn dot_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The reporting lines as a Graphviz digraph, managers above their reports.
+pub fn write_org_chart_dot<W: Write>(payroll: &Payroll, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "digraph org {{")?;
+    writeln!(writer, "  rankdir=TB;")?;
+    writeln!(writer, "  node [shape=box];")?;
+    for employee in &payroll.employees {
+        let emp = employee.as_employee();
+        let profile = emp.profile();
+        let mut label = format!("{}\\n{}", dot_label(&profile.name), dot_label(emp.employee_id()));
+        if let Some(department) = &profile.department {
+            label.push_str(&format!("\\n{}", dot_label(department)));
+        }
+        writeln!(writer, "  \"{}\" [label=\"{}\"];", dot_label(emp.employee_id()), label)?;
+    }
+    for employee in &payroll.employees {
+        let emp = employee.as_employee();
+        if let Some(manager) = payroll.manager_of(emp.employee_id()) {
+            writeln!(
+                writer,
+                "  \"{}\" -> \"{}\";",
+                dot_label(manager.as_employee().employee_id()),
+                dot_label(emp.employee_id())
+            )?;
+        }
+    }
+    writeln!(writer, "}}")?;
+    writer.flush()
+}
@@ -0,0 +1,191 @@
+use crate::error::ExportError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::Compression;
+use ::parquet::file::properties::WriterProperties;
+use arrow_array::builder::{BooleanBuilder, Float64Builder, StringBuilder, TimestampMicrosecondBuilder, UInt32Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Records buffered before they are written out as one row group.
+const ROW_GROUP_SIZE: usize = 8192;
+
+/// The columns of a payroll Parquet file: one row per record, voided ones included, with the pay
+/// period as "YYYY-MM". Names and other personal details are left out; the employee id joins the
+/// rows to the HR data where needed.
+pub fn payroll_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("record_id", DataType::Utf8, true),
+        Field::new("employee_id", DataType::Utf8, false),
+        Field::new("employee_type", DataType::Utf8, false),
+        Field::new("department", DataType::Utf8, true),
+        Field::new("grade", DataType::Utf8, true),
+        Field::new("pay_period", DataType::Utf8, false),
+        Field::new("processed_at", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), false),
+        Field::new("work_hours", DataType::Float64, false),
+        Field::new("allowance", DataType::Float64, false),
+        Field::new("gross", DataType::Float64, false),
+        Field::new("pph21", DataType::Float64, false),
+        Field::new("bpjs_kesehatan", DataType::Float64, false),
+        Field::new("bpjs_ketenagakerjaan", DataType::Float64, false),
+        Field::new("extra_earnings", DataType::Float64, false),
+        Field::new("extra_deductions", DataType::Float64, false),
+        Field::new("deductions", DataType::Float64, false),
+        Field::new("net", DataType::Float64, false),
+        Field::new("voided", DataType::Boolean, false),
+        Field::new("calculation_version", DataType::UInt32, true),
+    ])
+}
+
+#[derive(Default)]
+struct Columns {
+    record_id: StringBuilder,
+    employee_id: StringBuilder,
+    employee_type: StringBuilder,
+    department: StringBuilder,
+    grade: StringBuilder,
+    pay_period: StringBuilder,
+    processed_at: TimestampMicrosecondBuilder,
+    work_hours: Float64Builder,
+    allowance: Float64Builder,
+    gross: Float64Builder,
+    pph21: Float64Builder,
+    bpjs_kesehatan: Float64Builder,
+    bpjs_ketenagakerjaan: Float64Builder,
+    extra_earnings: Float64Builder,
+    extra_deductions: Float64Builder,
+    deductions: Float64Builder,
+    net: Float64Builder,
+    voided: BooleanBuilder,
+    calculation_version: UInt32Builder,
+}
+
+impl Columns {
+    fn append(&mut self, record: &PayrollData) {
+        let employee = record.employee.as_employee();
+        let profile = employee.profile();
+        let breakdown = record.breakdown();
+        self.record_id.append_option(Some(record.record_id.as_str()).filter(|id| !id.is_empty()));
+        self.employee_id.append_value(employee.employee_id());
+        self.employee_type.append_value(employee.employee_type());
+        self.department.append_option(profile.department.as_deref());
+        self.grade.append_option(profile.grade.as_deref());
+        match record.pay_period.parse::<PayPeriod>() {
+            Ok(period) => self.pay_period.append_value(format!("{}-{:02}", period.year, period.month)),
+            Err(_) => self.pay_period.append_value(&record.pay_period),
+        }
+        self.processed_at.append_value(record.processed_date.timestamp_micros());
+        self.work_hours.append_value(employee.work_hour());
+        self.allowance.append_value(employee.tunjangan());
+        self.gross.append_value(record.gross_salary);
+        self.pph21.append_value(breakdown.tax);
+        self.bpjs_kesehatan.append_value(breakdown.bpjs_kesehatan);
+        self.bpjs_ketenagakerjaan.append_value(breakdown.bpjs_ketenagakerjaan);
+        self.extra_earnings.append_value(record.extra_earnings.iter().fold(0.0, |sum, c| sum + c.amount));
+        self.extra_deductions.append_value(record.extra_deductions.iter().fold(0.0, |sum, c| sum + c.amount));
+        self.deductions.append_value(record.deductions);
+        self.net.append_value(record.net_salary);
+        self.voided.append_value(record.voided);
+        self.calculation_version.append_option(record.context.as_ref().map(|context| context.calculation_version));
+    }
+
+    /// Takes the buffered rows as a batch, leaving the builders empty.
+    fn finish(&mut self, schema: SchemaRef) -> Result<RecordBatch, ExportError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.record_id.finish()),
+            Arc::new(self.employee_id.finish()),
+            Arc::new(self.employee_type.finish()),
+            Arc::new(self.department.finish()),
+            Arc::new(self.grade.finish()),
+            Arc::new(self.pay_period.finish()),
+            Arc::new(self.processed_at.finish().with_timezone("UTC")),
+            Arc::new(self.work_hours.finish()),
+            Arc::new(self.allowance.finish()),
+            Arc::new(self.gross.finish()),
+            Arc::new(self.pph21.finish()),
+            Arc::new(self.bpjs_kesehatan.finish()),
+            Arc::new(self.bpjs_ketenagakerjaan.finish()),
+            Arc::new(self.extra_earnings.finish()),
+            Arc::new(self.extra_deductions.finish()),
+            Arc::new(self.deductions.finish()),
+            Arc::new(self.net.finish()),
+            Arc::new(self.voided.finish()),
+            Arc::new(self.calculation_version.finish()),
+        ];
+        Ok(RecordBatch::try_new(schema, columns).map_err(::parquet::errors::ParquetError::from)?)
+    }
+}
+
+/// Writes payroll records to a Parquet file one at a time, so a whole history can be exported
+/// straight from [`Storage::for_each_record`](crate::storage::Storage::for_each_record).
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+    columns: Columns,
+    buffered: usize,
+    rows: usize,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(writer: W) -> Result<Self, ExportError> {
+        let schema = Arc::new(payroll_schema());
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(ROW_GROUP_SIZE)
+            .build();
+        Ok(Self {
+            writer: ArrowWriter::try_new(writer, schema.clone(), Some(properties))?,
+            schema,
+            columns: Columns::default(),
+            buffered: 0,
+            rows: 0,
+        })
+    }
+
+    pub fn write_record(&mut self, record: &PayrollData) -> Result<(), ExportError> {
+        self.columns.append(record);
+        self.buffered += 1;
+        self.rows += 1;
+        if self.buffered == ROW_GROUP_SIZE {
+            self.flush_rows()?;
+        }
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Writes the remaining rows and the file footer.
+    pub fn finish(mut self) -> Result<(W, usize), ExportError> {
+        self.flush_rows()?;
+        let mut writer = self.writer.into_inner()?;
+        writer.flush()?;
+        Ok((writer, self.rows))
+    }
+
+    fn flush_rows(&mut self) -> Result<(), ExportError> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+        let batch = self.columns.finish(self.schema.clone())?;
+        self.writer.write(&batch)?;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+pub fn write_payroll_parquet<'a, I, W>(records: I, writer: W) -> Result<usize, ExportError>
+where
+    I: IntoIterator<Item = &'a PayrollData>,
+    W: Write + Send,
+{
+    let mut parquet = ParquetWriter::new(writer)?;
+    for record in records {
+        parquet.write_record(record)?;
+    }
+    parquet.finish().map(|(_, rows)| rows)
+}
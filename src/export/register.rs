@@ -0,0 +1,229 @@
+use crate::error::ExportError;
+use crate::export::write_csv_row;
+use crate::payroll::PayrollData;
+use crate::rules::PayrollComponent;
+use std::borrow::Borrow;
+use std::io::{self, Write};
+use std::path::Path;
+
+const HEADER: [&str; 13] = [
+    "Employee ID",
+    "Name",
+    "Employee Type",
+    "Pay Period",
+    "Processed Date",
+    "Work Hours",
+    "Gross",
+    "PPh 21",
+    "BPJS Kesehatan",
+    "BPJS Ketenagakerjaan",
+    "Deductions",
+    "Net",
+    "Status",
+];
+
+pub struct RegisterCsvWriter<W: Write> {
+    writer: W,
+    rows: usize,
+}
+
+impl<W: Write> RegisterCsvWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        write_csv_row(&mut writer, &HEADER.map(String::from))?;
+        Ok(Self { writer, rows: 0 })
+    }
+
+    pub fn write_record(&mut self, record: &PayrollData) -> io::Result<()> {
+        let employee = record.employee.as_employee();
+        let breakdown = record.breakdown();
+        write_csv_row(
+            &mut self.writer,
+            &[
+                employee.employee_id().to_string(),
+                employee.profile().name.clone(),
+                employee.employee_type().to_string(),
+                record.pay_period.clone(),
+                record.processed_date.format("%Y-%m-%d %H:%M:%S").to_string(),
+                employee.work_hour().to_string(),
+                format!("{:.2}", record.gross_salary),
+                format!("{:.2}", breakdown.tax),
+                format!("{:.2}", breakdown.bpjs_kesehatan),
+                format!("{:.2}", breakdown.bpjs_ketenagakerjaan),
+                format!("{:.2}", record.deductions),
+                format!("{:.2}", record.net_salary),
+                if record.voided { "VOID" } else { "ACTIVE" }.to_string(),
+            ],
+        )?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn finish(mut self) -> io::Result<(W, usize)> {
+        self.writer.flush()?;
+        Ok((self.writer, self.rows))
+    }
+}
+
+pub fn write_register_csv<I, W>(records: I, writer: W) -> io::Result<usize>
+where
+    I: IntoIterator,
+    I::Item: Borrow<PayrollData>,
+    W: Write,
+{
+    let mut register = RegisterCsvWriter::new(writer)?;
+    for record in records {
+        register.write_record(record.borrow())?;
+    }
+    register.finish().map(|(_, rows)| rows)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterRow {
+    pub employee_id: String,
+    pub name: String,
+    pub work_hours: f64,
+    pub earnings: Vec<f64>,
+    pub gross: f64,
+    pub deductions: Vec<f64>,
+    pub total_deductions: f64,
+    pub net: f64,
+}
+
+/// A wide payroll register: one row per employee with a column for every earning and deduction
+/// component that appears in the records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayrollRegister {
+    pub title: String,
+    pub earning_columns: Vec<String>,
+    pub deduction_columns: Vec<String>,
+    pub rows: Vec<RegisterRow>,
+}
+
+fn column_amounts(columns: &mut Vec<String>, items: Vec<PayrollComponent>) -> Vec<(usize, f64)> {
+    items
+        .into_iter()
+        .map(|item| {
+            let index = match columns.iter().position(|name| *name == item.name) {
+                Some(index) => index,
+                None => {
+                    columns.push(item.name);
+                    columns.len() - 1
+                }
+            };
+            (index, item.amount)
+        })
+        .collect()
+}
+
+fn spread(amounts: &[(usize, f64)], width: usize) -> Vec<f64> {
+    let mut row = vec![0.0; width];
+    for (index, amount) in amounts {
+        row[*index] += amount;
+    }
+    row
+}
+
+impl PayrollRegister {
+    /// Builds the register from `records`, skipping voided ones. Columns keep the order in which
+    /// components first appear; components with the same name are added together.
+    pub fn from_records<'a>(title: impl Into<String>, records: impl IntoIterator<Item = &'a PayrollData>) -> Self {
+        let mut earning_columns = Vec::new();
+        let mut deduction_columns = Vec::new();
+        let mut pending = Vec::new();
+        for record in records.into_iter().filter(|record| !record.voided) {
+            let earnings = column_amounts(&mut earning_columns, record.earning_items());
+            let deductions = column_amounts(&mut deduction_columns, record.deduction_items());
+            pending.push((record, earnings, deductions));
+        }
+
+        let rows = pending
+            .into_iter()
+            .map(|(record, earnings, deductions)| {
+                let employee = record.employee.as_employee();
+                RegisterRow {
+                    employee_id: employee.employee_id().to_string(),
+                    name: employee.profile().name.clone(),
+                    work_hours: employee.work_hour(),
+                    earnings: spread(&earnings, earning_columns.len()),
+                    gross: record.gross_salary,
+                    deductions: spread(&deductions, deduction_columns.len()),
+                    total_deductions: record.deductions,
+                    net: record.net_salary,
+                }
+            })
+            .collect();
+
+        Self {
+            title: title.into(),
+            earning_columns,
+            deduction_columns,
+            rows,
+        }
+    }
+
+    pub fn totals(&self) -> RegisterRow {
+        let sum = |width: usize, column: fn(&RegisterRow) -> &Vec<f64>| {
+            (0..width)
+                .map(|index| self.rows.iter().map(|row| column(row)[index]).sum())
+                .collect()
+        };
+        RegisterRow {
+            employee_id: "Total".to_string(),
+            name: format!("{} employees", self.rows.len()),
+            work_hours: self.rows.iter().map(|row| row.work_hours).sum(),
+            earnings: sum(self.earning_columns.len(), |row| &row.earnings),
+            gross: self.rows.iter().map(|row| row.gross).sum(),
+            deductions: sum(self.deduction_columns.len(), |row| &row.deductions),
+            total_deductions: self.rows.iter().map(|row| row.total_deductions).sum(),
+            net: self.rows.iter().map(|row| row.net).sum(),
+        }
+    }
+
+    /// Column titles in row order: ID, name, hours, earnings, gross, deductions, total deductions, net.
+    pub fn header(&self) -> Vec<String> {
+        ["Employee ID", "Name", "Work Hours"]
+            .into_iter()
+            .map(String::from)
+            .chain(self.earning_columns.iter().cloned())
+            .chain(std::iter::once("Gross".to_string()))
+            .chain(self.deduction_columns.iter().cloned())
+            .chain(["Total Deductions", "Net"].map(String::from))
+            .collect()
+    }
+
+    /// Writes the register with a totals row at the bottom; returns the number of employee rows.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<usize> {
+        write_csv_row(&mut writer, &self.header())?;
+        for row in self.rows.iter().chain(std::iter::once(&self.totals())) {
+            let mut fields = vec![row.employee_id.clone(), row.name.clone(), row.work_hours.to_string()];
+            fields.extend(row.earnings.iter().map(|amount| format!("{:.2}", amount)));
+            fields.push(format!("{:.2}", row.gross));
+            fields.extend(row.deductions.iter().map(|amount| format!("{:.2}", amount)));
+            fields.push(format!("{:.2}", row.total_deductions));
+            fields.push(format!("{:.2}", row.net));
+            write_csv_row(&mut writer, &fields)?;
+        }
+        writer.flush()?;
+        Ok(self.rows.len())
+    }
+
+    /// Saves the register as XLSX when `path` ends in `.xlsx`, and as CSV otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ExportError> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx")) {
+            #[cfg(feature = "xlsx")]
+            return crate::export::xlsx::write_register_workbook(self, path);
+            #[cfg(not(feature = "xlsx"))]
+            return Err(ExportError::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Excel export is not available in this build; rebuild with `--features xlsx`",
+            )));
+        }
+        self.write_csv(io::BufWriter::new(std::fs::File::create(path)?))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::export::write_csv_row;
+use crate::payroll::PayrollData;
+use std::borrow::Borrow;
+use std::io::{self, Write};
+
+pub fn write_sipp_csv<I, W>(records: I, rates: &BpjsRates, writer: &mut W) -> io::Result<usize>
+where
+    I: IntoIterator,
+    I::Item: Borrow<PayrollData>,
+    W: Write,
+{
+    write_csv_row(
+        writer,
+        &[
+            "No KPJ",
+            "NIK",
+            "Nama",
+            "Upah",
+            "JHT TK",
+            "JHT PK",
+            "JP TK",
+            "JP PK",
+            "JKK",
+            "JKM",
+            "Total Iuran",
+        ]
+        .map(String::from),
+    )?;
+
+    let mut rows = 0;
+    for record in records {
+        let record = record.borrow();
+        let contribution = match BpjsContribution::for_record(record, rates) {
+            Some(contribution) => contribution,
+            None => continue,
+        };
+        let profile = record.employee.as_employee().profile();
+        write_csv_row(
+            writer,
+            &[
+                profile.bpjs_tk_number.clone().unwrap_or_default(),
+                profile.nik.clone().unwrap_or_default(),
+                profile.name.clone(),
+                format!("{:.0}", contribution.wage_base),
+                format!("{:.0}", contribution.jht_employee),
+                format!("{:.0}", contribution.jht_employer),
+                format!("{:.0}", contribution.jp_employee),
+                format!("{:.0}", contribution.jp_employer),
+                format!("{:.0}", contribution.jkk),
+                format!("{:.0}", contribution.jkm),
+                format!("{:.0}", contribution.total()),
+            ],
+        )?;
+        rows += 1;
+    }
+    Ok(rows)
+}
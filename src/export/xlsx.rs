@@ -0,0 +1,144 @@
+use crate::error::ExportError;
+use crate::export::period_totals;
+use crate::export::register::PayrollRegister;
+use crate::payroll::PayrollData;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use std::path::Path;
+
+pub fn write_payroll_workbook(records: &[PayrollData], path: impl AsRef<Path>) -> Result<(), ExportError> {
+    let mut workbook = build_workbook(records)?;
+    workbook.save(path.as_ref())?;
+    Ok(())
+}
+
+pub fn payroll_workbook_bytes(records: &[PayrollData]) -> Result<Vec<u8>, ExportError> {
+    let mut workbook = build_workbook(records)?;
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn build_workbook(records: &[PayrollData]) -> Result<Workbook, ExportError> {
+    let header = Format::new().set_bold().set_background_color("#D9E1F2");
+    let money = Format::new().set_num_format("#,##0.00");
+    let total = Format::new().set_bold().set_num_format("#,##0.00");
+    let active: Vec<&PayrollData> = records.iter().filter(|record| !record.voided).collect();
+
+    let mut workbook = Workbook::new();
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("Summary")?;
+    write_header(summary, &header, &["Pay Period", "Employees", "Gross", "Deductions", "Net"])?;
+    let totals = period_totals(records);
+    for (i, period) in totals.iter().enumerate() {
+        let row = i as u32 + 1;
+        summary.write_string(row, 0, &period.pay_period)?;
+        summary.write_number(row, 1, period.employees as f64)?;
+        summary.write_number_with_format(row, 2, period.gross, &money)?;
+        summary.write_number_with_format(row, 3, period.deductions, &money)?;
+        summary.write_number_with_format(row, 4, period.net, &money)?;
+    }
+    let total_row = totals.len() as u32 + 1;
+    summary.write_string_with_format(total_row, 0, "Total", &header)?;
+    summary.write_number_with_format(total_row, 1, active.len() as f64, &total)?;
+    summary.write_number_with_format(total_row, 2, active.iter().map(|r| r.gross_salary).sum::<f64>(), &total)?;
+    summary.write_number_with_format(total_row, 3, active.iter().map(|r| r.deductions).sum::<f64>(), &total)?;
+    summary.write_number_with_format(total_row, 4, active.iter().map(|r| r.net_salary).sum::<f64>(), &total)?;
+    summary.autofit();
+
+    let detail = workbook.add_worksheet();
+    detail.set_name("Employee Detail")?;
+    write_header(
+        detail,
+        &header,
+        &[
+            "Employee ID",
+            "Employee Type",
+            "Pay Period",
+            "Processed Date",
+            "Work Hours",
+            "Allowance",
+            "Gross",
+            "Deductions",
+            "Net",
+        ],
+    )?;
+    for (i, record) in active.iter().enumerate() {
+        let row = i as u32 + 1;
+        let employee = record.employee.as_employee();
+        detail.write_string(row, 0, employee.employee_id())?;
+        detail.write_string(row, 1, employee.employee_type())?;
+        detail.write_string(row, 2, &record.pay_period)?;
+        detail.write_string(row, 3, record.processed_date.format("%Y-%m-%d %H:%M:%S").to_string())?;
+        detail.write_number(row, 4, employee.work_hour())?;
+        detail.write_number_with_format(row, 5, employee.tunjangan(), &money)?;
+        detail.write_number_with_format(row, 6, record.gross_salary, &money)?;
+        detail.write_number_with_format(row, 7, record.deductions, &money)?;
+        detail.write_number_with_format(row, 8, record.net_salary, &money)?;
+    }
+    detail.set_freeze_panes(1, 0)?;
+    detail.autofit();
+
+    let deductions = workbook.add_worksheet();
+    deductions.set_name("Deductions")?;
+    write_header(
+        deductions,
+        &header,
+        &["Employee ID", "Pay Period", "PPh 21", "BPJS Kesehatan", "BPJS Ketenagakerjaan", "Total"],
+    )?;
+    for (i, record) in active.iter().enumerate() {
+        let row = i as u32 + 1;
+        let employee = record.employee.as_employee();
+        let breakdown = record.breakdown();
+        deductions.write_string(row, 0, employee.employee_id())?;
+        deductions.write_string(row, 1, &record.pay_period)?;
+        deductions.write_number_with_format(row, 2, breakdown.tax, &money)?;
+        deductions.write_number_with_format(row, 3, breakdown.bpjs_kesehatan, &money)?;
+        deductions.write_number_with_format(row, 4, breakdown.bpjs_ketenagakerjaan, &money)?;
+        deductions.write_number_with_format(row, 5, breakdown.total(), &money)?;
+    }
+    deductions.set_freeze_panes(1, 0)?;
+    deductions.autofit();
+
+    Ok(workbook)
+}
+
+pub fn write_register_workbook(register: &PayrollRegister, path: impl AsRef<Path>) -> Result<(), ExportError> {
+    let header = Format::new().set_bold().set_background_color("#D9E1F2");
+    let money = Format::new().set_num_format("#,##0.00");
+    let total = Format::new().set_bold().set_num_format("#,##0.00");
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Register")?;
+    let columns = register.header();
+    write_header(sheet, &header, &columns.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+    let totals = register.totals();
+    for (i, row) in register.rows.iter().chain(std::iter::once(&totals)).enumerate() {
+        let line = i as u32 + 1;
+        let format = if i == register.rows.len() { &total } else { &money };
+        let amounts = row
+            .earnings
+            .iter()
+            .chain(std::iter::once(&row.gross))
+            .chain(&row.deductions)
+            .chain([&row.total_deductions, &row.net]);
+        sheet.write_string(line, 0, &row.employee_id)?;
+        sheet.write_string(line, 1, &row.name)?;
+        sheet.write_number(line, 2, row.work_hours)?;
+        for (col, amount) in amounts.enumerate() {
+            sheet.write_number_with_format(line, col as u16 + 3, *amount, format)?;
+        }
+    }
+    sheet.set_freeze_panes(1, 2)?;
+    sheet.autofit();
+
+    workbook.save(path.as_ref())?;
+    Ok(())
+}
+
+fn write_header(sheet: &mut Worksheet, format: &Format, columns: &[&str]) -> Result<(), ExportError> {
+    for (col, title) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, format)?;
+    }
+    Ok(())
+}
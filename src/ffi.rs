@@ -0,0 +1,186 @@
+use crate::calc::{self, AllowancePeriod, CalculationSettings, PtkpStatus};
+use crate::payroll::{EmployeeData, PayrollData};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+pub const EMP_OK: c_int = 0;
+pub const EMP_NULL_POINTER: c_int = 1;
+pub const EMP_INVALID_ARGUMENT: c_int = 2;
+
+pub const EMP_FULLTIME: c_int = 0;
+pub const EMP_CONTRACT: c_int = 1;
+
+pub const EMP_ALLOWANCE_MONTHLY: c_int = 0;
+pub const EMP_ALLOWANCE_YEARLY: c_int = 1;
+pub const EMP_ALLOWANCE_PER_PROJECT: c_int = 2;
+
+pub const EMP_PTKP_NONE: c_int = -1;
+pub const EMP_PTKP_TK0: c_int = 0;
+pub const EMP_PTKP_TK1: c_int = 1;
+pub const EMP_PTKP_TK2: c_int = 2;
+pub const EMP_PTKP_TK3: c_int = 3;
+pub const EMP_PTKP_K0: c_int = 4;
+pub const EMP_PTKP_K1: c_int = 5;
+pub const EMP_PTKP_K2: c_int = 6;
+pub const EMP_PTKP_K3: c_int = 7;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct EmpEmployee {
+    pub kind: c_int,
+    pub work_hours: f64,
+    pub allowance: f64,
+    pub allowance_period: c_int,
+    pub base_salary: f64,
+    pub hourly_rate: f64,
+    pub ptkp_status: c_int,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn allowance_period(value: c_int) -> Result<AllowancePeriod, String> {
+    match value {
+        EMP_ALLOWANCE_MONTHLY => Ok(AllowancePeriod::Monthly),
+        EMP_ALLOWANCE_YEARLY => Ok(AllowancePeriod::Yearly),
+        EMP_ALLOWANCE_PER_PROJECT => Ok(AllowancePeriod::PerProject),
+        _ => Err(format!("unknown allowance period {}", value)),
+    }
+}
+
+fn ptkp_status(value: c_int) -> Result<Option<PtkpStatus>, String> {
+    match value {
+        EMP_PTKP_NONE => Ok(None),
+        EMP_PTKP_TK0 => Ok(Some(PtkpStatus::TK0)),
+        EMP_PTKP_TK1 => Ok(Some(PtkpStatus::TK1)),
+        EMP_PTKP_TK2 => Ok(Some(PtkpStatus::TK2)),
+        EMP_PTKP_TK3 => Ok(Some(PtkpStatus::TK3)),
+        EMP_PTKP_K0 => Ok(Some(PtkpStatus::K0)),
+        EMP_PTKP_K1 => Ok(Some(PtkpStatus::K1)),
+        EMP_PTKP_K2 => Ok(Some(PtkpStatus::K2)),
+        EMP_PTKP_K3 => Ok(Some(PtkpStatus::K3)),
+        _ => Err(format!("unknown PTKP status {}", value)),
+    }
+}
+
+impl EmpEmployee {
+    fn gross(&self) -> Result<f64, String> {
+        let period = allowance_period(self.allowance_period)?;
+        match self.kind {
+            EMP_FULLTIME => Ok(calc::fulltime_gross(
+                self.base_salary,
+                self.work_hours,
+                self.allowance,
+                period,
+                &CalculationSettings::DEFAULT,
+            )),
+            EMP_CONTRACT => Ok(calc::contract_gross(self.hourly_rate, self.work_hours, self.allowance, period)),
+            _ => Err(format!("unknown employee kind {}", self.kind)),
+        }
+    }
+
+    fn tax(&self) -> Result<f64, String> {
+        let gross = self.gross()?;
+        match self.kind {
+            EMP_FULLTIME => Ok(calc::fulltime_tax(
+                gross,
+                ptkp_status(self.ptkp_status)?,
+                &CalculationSettings::DEFAULT,
+            )),
+            _ => Ok(calc::contract_tax(gross)),
+        }
+    }
+}
+
+unsafe fn write_result(result: Result<f64, String>, out: *mut f64) -> c_int {
+    match result {
+        Ok(value) => {
+            *out = value;
+            EMP_OK
+        }
+        Err(err) => {
+            set_last_error(err);
+            EMP_INVALID_ARGUMENT
+        }
+    }
+}
+
+/// # Safety
+///
+/// `employee` must point to a valid `EmpEmployee` and `gross` to writable memory for one `double`.
+#[no_mangle]
+pub unsafe extern "C" fn emp_calculate_gross(employee: *const EmpEmployee, gross: *mut f64) -> c_int {
+    if employee.is_null() || gross.is_null() {
+        set_last_error("employee and gross must not be null");
+        return EMP_NULL_POINTER;
+    }
+    write_result((*employee).gross(), gross)
+}
+
+/// # Safety
+///
+/// `employee` must point to a valid `EmpEmployee` and `tax` to writable memory for one `double`.
+#[no_mangle]
+pub unsafe extern "C" fn emp_calculate_tax(employee: *const EmpEmployee, tax: *mut f64) -> c_int {
+    if employee.is_null() || tax.is_null() {
+        set_last_error("employee and tax must not be null");
+        return EMP_NULL_POINTER;
+    }
+    write_result((*employee).tax(), tax)
+}
+
+unsafe fn read_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err(format!("{} must not be null", name));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// # Safety
+///
+/// `employee_json` and `pay_period` must be valid NUL-terminated strings. The returned string
+/// must be released with `emp_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn emp_process_record(employee_json: *const c_char, pay_period: *const c_char) -> *mut c_char {
+    let result = (|| {
+        let employee: EmployeeData = serde_json::from_str(read_str(employee_json, "employee_json")?)
+            .map_err(|err| format!("invalid employee: {}", err))?;
+        let pay_period = read_str(pay_period, "pay_period")?;
+        let record = PayrollData::new(employee, pay_period.to_string());
+        let json = serde_json::to_string(&record).map_err(|err| err.to_string())?;
+        CString::new(json).map_err(|err| err.to_string())
+    })();
+    match result {
+        Ok(json) => json.into_raw(),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `value` must be null or a string returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn emp_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Returns the message of the last failed call on this thread, or null. The pointer stays valid
+/// until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn emp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
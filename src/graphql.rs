@@ -0,0 +1,332 @@
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::period::PayPeriod;
+use crate::query::{EmployeeKind, PayrollQuery};
+use crate::report::PayrollTotals;
+use crate::shared::SharedPayroll;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject};
+use axum::extract::State;
+use axum::Json;
+
+pub type PayrollSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn schema(payroll: SharedPayroll) -> PayrollSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(payroll)
+        .finish()
+}
+
+pub async fn graphql_handler(
+    State(schema): State<PayrollSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct LineItem {
+    pub name: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct Totals {
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+impl From<PayrollTotals> for Totals {
+    fn from(totals: PayrollTotals) -> Self {
+        Self {
+            employees: totals.employees,
+            gross: totals.gross,
+            deductions: totals.deductions,
+            net: totals.net,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct RecordFilter {
+    employee_id: Option<String>,
+    pay_period: Option<String>,
+    from_period: Option<String>,
+    to_period: Option<String>,
+    employee_type: Option<String>,
+    include_voided: bool,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl RecordFilter {
+    fn run(&self, payroll: &SharedPayroll) -> Result<Vec<PayrollRecordNode>> {
+        let from = self.from_period.as_deref().map(parse_period).transpose()?;
+        let to = self.to_period.as_deref().map(parse_period).transpose()?;
+        let kind = self
+            .employee_type
+            .as_deref()
+            .map(|kind| kind.parse::<EmployeeKind>())
+            .transpose()?;
+
+        Ok(payroll.read(|payroll| {
+            let mut query = PayrollQuery::new(payroll).include_voided(self.include_voided);
+            if let Some(employee_id) = &self.employee_id {
+                query = query.employee(employee_id);
+            }
+            if let Some(pay_period) = &self.pay_period {
+                query = query.period(pay_period);
+            }
+            if let Some(from) = from {
+                query = query.from_period(from);
+            }
+            if let Some(to) = to {
+                query = query.to_period(to);
+            }
+            if let Some(kind) = kind {
+                query = query.employee_kind(kind);
+            }
+            payroll
+                .get_payroll_records()
+                .iter()
+                .enumerate()
+                .filter(|(_, record)| query.matches(record))
+                .skip(self.offset)
+                .take(self.limit.unwrap_or(usize::MAX))
+                .map(|(index, record)| PayrollRecordNode {
+                    index,
+                    record: record.clone(),
+                })
+                .collect()
+        }))
+    }
+}
+
+fn parse_period(period: &str) -> Result<PayPeriod> {
+    period.parse::<PayPeriod>().map_err(|err| err.to_string().into())
+}
+
+pub struct EmployeeNode(EmployeeData);
+
+#[Object(name = "Employee")]
+impl EmployeeNode {
+    async fn employee_id(&self) -> &str {
+        self.0.as_employee().employee_id()
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.as_employee().profile().name
+    }
+
+    async fn email(&self) -> Option<&str> {
+        self.0.as_employee().profile().email.as_deref()
+    }
+
+    async fn employee_type(&self) -> &str {
+        self.0.as_employee().employee_type()
+    }
+
+    async fn work_hours(&self) -> f64 {
+        self.0.as_employee().work_hour()
+    }
+
+    async fn allowance(&self) -> f64 {
+        self.0.as_employee().tunjangan()
+    }
+
+    async fn base_salary(&self) -> Option<f64> {
+        match &self.0 {
+            EmployeeData::Fulltime(emp) => Some(emp.base_salary),
+            EmployeeData::Contract(_) => None,
+        }
+    }
+
+    async fn hourly_rate(&self) -> Option<f64> {
+        match &self.0 {
+            EmployeeData::Fulltime(_) => None,
+            EmployeeData::Contract(emp) => Some(emp.hourly_rate),
+        }
+    }
+
+    async fn gross_salary(&self) -> f64 {
+        self.0.as_employee().calculate_gross()
+    }
+
+    async fn net_salary(&self) -> f64 {
+        self.0.as_employee().calculate_net()
+    }
+
+    async fn payroll_history(
+        &self,
+        ctx: &Context<'_>,
+        pay_period: Option<String>,
+        from_period: Option<String>,
+        to_period: Option<String>,
+        #[graphql(default)] include_voided: bool,
+    ) -> Result<Vec<PayrollRecordNode>> {
+        let filter = RecordFilter {
+            employee_id: Some(self.0.as_employee().employee_id().to_string()),
+            pay_period,
+            from_period,
+            to_period,
+            include_voided,
+            ..RecordFilter::default()
+        };
+        filter.run(ctx.data::<SharedPayroll>()?)
+    }
+}
+
+pub struct PayrollRecordNode {
+    index: usize,
+    record: PayrollData,
+}
+
+#[Object(name = "PayrollRecord")]
+impl PayrollRecordNode {
+    async fn index(&self) -> usize {
+        self.index
+    }
+
+    async fn pay_period(&self) -> &str {
+        &self.record.pay_period
+    }
+
+    async fn processed_date(&self) -> String {
+        self.record.processed_date.to_rfc3339()
+    }
+
+    async fn gross_salary(&self) -> f64 {
+        self.record.gross_salary
+    }
+
+    async fn deductions(&self) -> f64 {
+        self.record.deductions
+    }
+
+    async fn net_salary(&self) -> f64 {
+        self.record.net_salary
+    }
+
+    async fn voided(&self) -> bool {
+        self.record.voided
+    }
+
+    async fn tax_table(&self) -> Option<&str> {
+        self.record.context.as_ref().map(|context| context.tax_table.as_str())
+    }
+
+    async fn employee(&self) -> EmployeeNode {
+        EmployeeNode(self.record.employee.clone())
+    }
+
+    async fn earning_items(&self) -> Vec<LineItem> {
+        self.record
+            .extra_earnings
+            .iter()
+            .map(|component| LineItem {
+                name: component.name.clone(),
+                amount: component.amount,
+            })
+            .collect()
+    }
+
+    async fn deduction_items(&self) -> Vec<LineItem> {
+        self.record
+            .deduction_items()
+            .into_iter()
+            .map(|component| LineItem {
+                name: component.name,
+                amount: component.amount,
+            })
+            .collect()
+    }
+}
+
+pub struct PeriodReportNode {
+    pay_period: String,
+    records: Vec<PayrollRecordNode>,
+}
+
+#[Object(name = "PeriodReport")]
+impl PeriodReportNode {
+    async fn pay_period(&self) -> &str {
+        &self.pay_period
+    }
+
+    async fn totals(&self) -> Totals {
+        let mut totals = PayrollTotals::default();
+        for node in &self.records {
+            totals.add(&node.record);
+        }
+        totals.into()
+    }
+
+    async fn records(&self) -> &[PayrollRecordNode] {
+        &self.records
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn employees(&self, ctx: &Context<'_>, employee_type: Option<String>) -> Result<Vec<EmployeeNode>> {
+        let kind = employee_type
+            .as_deref()
+            .map(|kind| kind.parse::<EmployeeKind>())
+            .transpose()?;
+        Ok(ctx.data::<SharedPayroll>()?.read(|payroll| {
+            payroll
+                .employees
+                .iter()
+                .filter(|employee| kind.is_none_or(|kind| EmployeeKind::of(employee) == kind))
+                .cloned()
+                .map(EmployeeNode)
+                .collect()
+        }))
+    }
+
+    async fn employee(&self, ctx: &Context<'_>, employee_id: String) -> Result<Option<EmployeeNode>> {
+        Ok(ctx
+            .data::<SharedPayroll>()?
+            .get_employee(&employee_id)
+            .map(EmployeeNode))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn payroll_records(
+        &self,
+        ctx: &Context<'_>,
+        employee_id: Option<String>,
+        pay_period: Option<String>,
+        from_period: Option<String>,
+        to_period: Option<String>,
+        employee_type: Option<String>,
+        #[graphql(default)] include_voided: bool,
+        #[graphql(default)] offset: usize,
+        limit: Option<usize>,
+    ) -> Result<Vec<PayrollRecordNode>> {
+        let filter = RecordFilter {
+            employee_id,
+            pay_period,
+            from_period,
+            to_period,
+            employee_type,
+            include_voided,
+            offset,
+            limit,
+        };
+        filter.run(ctx.data::<SharedPayroll>()?)
+    }
+
+    async fn period_report(&self, ctx: &Context<'_>, pay_period: String) -> Result<PeriodReportNode> {
+        let filter = RecordFilter {
+            pay_period: Some(pay_period.clone()),
+            ..RecordFilter::default()
+        };
+        Ok(PeriodReportNode {
+            records: filter.run(ctx.data::<SharedPayroll>()?)?,
+            pay_period,
+        })
+    }
+}
@@ -0,0 +1,318 @@
+#![allow(clippy::result_large_err)]
+
+use crate::auth::{Principal, Scope};
+use crate::employee::{AllowancePeriod, ContractEmployee, EmployeeProfile, FulltimeEmployee};
+use crate::error::PayrollError;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::period::PayPeriod;
+use crate::query::{EmployeeKind, PayrollQuery};
+use crate::server::{ApiError, ServerState};
+use crate::webhook::WebhookEventKind;
+use axum::http::StatusCode;
+use std::pin::Pin;
+use std::time::Instant;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("employee.payroll.v1");
+}
+
+use proto::payroll_service_server::{PayrollService, PayrollServiceServer};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+pub fn routes(state: ServerState) -> axum::Router {
+    tonic::service::Routes::new(PayrollServiceServer::new(GrpcPayroll::new(state))).into_axum_router()
+}
+
+#[derive(Clone)]
+pub struct GrpcPayroll {
+    state: ServerState,
+}
+
+impl GrpcPayroll {
+    pub fn new(state: ServerState) -> Self {
+        Self { state }
+    }
+}
+
+fn employee_kind(value: i32) -> Result<Option<EmployeeKind>, Status> {
+    match proto::EmployeeType::try_from(value) {
+        Ok(proto::EmployeeType::Unspecified) => Ok(None),
+        Ok(proto::EmployeeType::Fulltime) => Ok(Some(EmployeeKind::Fulltime)),
+        Ok(proto::EmployeeType::Contract) => Ok(Some(EmployeeKind::Contract)),
+        Err(_) => Err(Status::invalid_argument(format!("unknown employee type {}", value))),
+    }
+}
+
+fn parse_period(period: Option<&str>) -> Result<Option<PayPeriod>, Status> {
+    period
+        .map(|period| period.parse::<PayPeriod>().map_err(Status::invalid_argument))
+        .transpose()
+}
+
+impl From<&EmployeeData> for proto::Employee {
+    fn from(employee: &EmployeeData) -> Self {
+        let emp_ref = employee.as_employee();
+        let allowance_period = match emp_ref.periode_tunjangan() {
+            AllowancePeriod::Monthly => proto::AllowancePeriod::Monthly,
+            AllowancePeriod::Yearly => proto::AllowancePeriod::Yearly,
+            AllowancePeriod::PerProject => proto::AllowancePeriod::PerProject,
+        };
+        let mut message = proto::Employee {
+            employee_id: emp_ref.employee_id().to_string(),
+            name: emp_ref.profile().name.clone(),
+            email: emp_ref.profile().email.clone(),
+            work_hours: emp_ref.work_hour(),
+            allowance: emp_ref.tunjangan(),
+            allowance_period: allowance_period as i32,
+            ..Default::default()
+        };
+        match employee {
+            EmployeeData::Fulltime(emp) => {
+                message.employee_type = proto::EmployeeType::Fulltime as i32;
+                message.base_salary = Some(emp.base_salary);
+                message.ptkp_status = emp.ptkp_status.map(|status| status.code().to_string());
+            }
+            EmployeeData::Contract(emp) => {
+                message.employee_type = proto::EmployeeType::Contract as i32;
+                message.hourly_rate = Some(emp.hourly_rate);
+            }
+        }
+        message
+    }
+}
+
+impl TryFrom<proto::Employee> for EmployeeData {
+    type Error = Status;
+
+    fn try_from(message: proto::Employee) -> Result<Self, Self::Error> {
+        if message.employee_id.trim().is_empty() {
+            return Err(Status::invalid_argument("employee_id is required"));
+        }
+        let periode_tunjangan = match proto::AllowancePeriod::try_from(message.allowance_period) {
+            Ok(proto::AllowancePeriod::Monthly) => AllowancePeriod::Monthly,
+            Ok(proto::AllowancePeriod::Yearly) => AllowancePeriod::Yearly,
+            Ok(proto::AllowancePeriod::PerProject) => AllowancePeriod::PerProject,
+            _ => return Err(Status::invalid_argument("allowance_period is required")),
+        };
+        let profile = EmployeeProfile {
+            name: message.name,
+            email: message.email,
+            ..EmployeeProfile::default()
+        };
+
+        match employee_kind(message.employee_type)? {
+            Some(EmployeeKind::Fulltime) => {
+                let base_salary = message
+                    .base_salary
+                    .ok_or_else(|| Status::invalid_argument("base_salary is required for fulltime employees"))?;
+                let mut employee = FulltimeEmployee::new(
+                    message.employee_id,
+                    message.work_hours,
+                    message.allowance,
+                    periode_tunjangan,
+                    base_salary,
+                );
+                employee.ptkp_status = message
+                    .ptkp_status
+                    .map(|status| status.parse().map_err(Status::invalid_argument))
+                    .transpose()?;
+                employee.profile = profile;
+                Ok(EmployeeData::Fulltime(employee))
+            }
+            Some(EmployeeKind::Contract) => {
+                let hourly_rate = message
+                    .hourly_rate
+                    .ok_or_else(|| Status::invalid_argument("hourly_rate is required for contract employees"))?;
+                let mut employee = ContractEmployee::new(
+                    message.employee_id,
+                    message.work_hours,
+                    message.allowance,
+                    periode_tunjangan,
+                    hourly_rate,
+                );
+                employee.profile = profile;
+                Ok(EmployeeData::Contract(employee))
+            }
+            None => Err(Status::invalid_argument("employee_type is required")),
+        }
+    }
+}
+
+fn record_message(index: usize, record: &PayrollData) -> proto::PayrollRecord {
+    let line_item = |component: crate::rules::PayrollComponent| proto::LineItem {
+        name: component.name,
+        amount: component.amount,
+    };
+    proto::PayrollRecord {
+        index: index as u64,
+        employee: Some((&record.employee).into()),
+        pay_period: record.pay_period.clone(),
+        processed_date: record.processed_date.to_rfc3339(),
+        gross_salary: record.gross_salary,
+        deductions: record.deductions,
+        net_salary: record.net_salary,
+        voided: record.voided,
+        earning_items: record.extra_earnings.iter().cloned().map(line_item).collect(),
+        deduction_items: record.deduction_items().into_iter().map(line_item).collect(),
+        record_id: record.record_id.clone(),
+    }
+}
+
+fn payroll_status(err: PayrollError) -> Status {
+    match err {
+        PayrollError::RecordNotFound(_) | PayrollError::RunNotFound(_) | PayrollError::EmployeeNotFound(_) => {
+            Status::not_found(err.to_string())
+        }
+        PayrollError::PermissionDenied(_) => Status::permission_denied(err.to_string()),
+        _ => Status::failed_precondition(err.to_string()),
+    }
+}
+
+fn api_status(err: ApiError) -> Status {
+    match err.status() {
+        StatusCode::NOT_FOUND => Status::not_found(err.message()),
+        StatusCode::BAD_REQUEST => Status::invalid_argument(err.message()),
+        StatusCode::FORBIDDEN => Status::permission_denied(err.message()),
+        StatusCode::CONFLICT => Status::failed_precondition(err.message()),
+        _ => Status::internal(err.message()),
+    }
+}
+
+/// Checks the scope of the client the server's auth layer let through, which it records on every
+/// request it passes on.
+fn require_scope<T>(request: &Request<T>, scope: Scope) -> Result<(), Status> {
+    let principal = request
+        .extensions()
+        .get::<Principal>()
+        .ok_or_else(|| Status::unauthenticated("not authenticated"))?;
+    principal.require(scope).map_err(|err| Status::permission_denied(err.to_string()))
+}
+
+fn stream_items<T: Send + 'static>(items: Vec<T>) -> ResponseStream<T> {
+    Box::pin(tokio_stream::iter(items.into_iter().map(Ok)))
+}
+
+#[tonic::async_trait]
+impl PayrollService for GrpcPayroll {
+    type ListEmployeesStream = ResponseStream<proto::Employee>;
+    type ListPayrollRecordsStream = ResponseStream<proto::PayrollRecord>;
+
+    async fn list_employees(
+        &self,
+        request: Request<proto::ListEmployeesRequest>,
+    ) -> Result<Response<Self::ListEmployeesStream>, Status> {
+        let kind = employee_kind(request.into_inner().employee_type)?;
+        let employees: Vec<proto::Employee> = self.state.payroll().read(|payroll| {
+            payroll
+                .employees
+                .iter()
+                .filter(|employee| kind.is_none_or(|kind| EmployeeKind::of(employee) == kind))
+                .map(proto::Employee::from)
+                .collect()
+        });
+        Ok(Response::new(stream_items(employees)))
+    }
+
+    async fn get_employee(
+        &self,
+        request: Request<proto::GetEmployeeRequest>,
+    ) -> Result<Response<proto::Employee>, Status> {
+        let employee_id = request.into_inner().employee_id;
+        self.state
+            .payroll()
+            .get_employee(&employee_id)
+            .map(|employee| Response::new((&employee).into()))
+            .ok_or_else(|| Status::not_found(format!("employee {} not found", employee_id)))
+    }
+
+    async fn add_employee(&self, request: Request<proto::Employee>) -> Result<Response<proto::Employee>, Status> {
+        require_scope(&request, Scope::Admin)?;
+        let employee = EmployeeData::try_from(request.into_inner())?;
+        let employee_id = employee.as_employee().employee_id().to_string();
+        let added = self.state.payroll().write(|payroll| {
+            let added = payroll.get_employee(&employee_id).is_none();
+            if added {
+                payroll.add_employee(employee.clone());
+            }
+            added
+        });
+        if !added {
+            return Err(Status::already_exists(format!("employee {} already exists", employee_id)));
+        }
+        self.state.persist().await.map_err(api_status)?;
+        self.state.notify(
+            WebhookEventKind::EmployeeAdded,
+            serde_json::to_value(&employee).unwrap_or_default(),
+        );
+        Ok(Response::new((&employee).into()))
+    }
+
+    async fn process_payroll(
+        &self,
+        request: Request<proto::ProcessPayrollRequest>,
+    ) -> Result<Response<proto::PayrollRecord>, Status> {
+        require_scope(&request, Scope::PayrollProcess)?;
+        let request = request.into_inner();
+        let employee = self
+            .state
+            .payroll()
+            .get_employee(&request.employee_id)
+            .ok_or_else(|| Status::not_found(format!("employee {} not found", request.employee_id)))?;
+        let started = Instant::now();
+        let processed = self.state.payroll().write(|payroll| {
+            payroll
+                .process_payroll(employee, request.pay_period)
+                .map(|record| (payroll.get_payroll_records().len() - 1, record))
+        });
+        let (index, record) = match processed {
+            Ok(processed) => processed,
+            Err(err) => {
+                self.state.metrics().processing_failed();
+                return Err(payroll_status(err));
+            }
+        };
+        self.state.persist().await.map_err(api_status)?;
+        self.state.metrics().record_processed(started.elapsed());
+        Ok(Response::new(record_message(index, &record)))
+    }
+
+    async fn list_payroll_records(
+        &self,
+        request: Request<proto::ListPayrollRecordsRequest>,
+    ) -> Result<Response<Self::ListPayrollRecordsStream>, Status> {
+        let request = request.into_inner();
+        let kind = employee_kind(request.employee_type)?;
+        let from = parse_period(request.from_period.as_deref())?;
+        let to = parse_period(request.to_period.as_deref())?;
+
+        let records: Vec<proto::PayrollRecord> = self.state.payroll().read(|payroll| {
+            let mut query = PayrollQuery::new(payroll).include_voided(request.include_voided);
+            if let Some(employee_id) = &request.employee_id {
+                query = query.employee(employee_id);
+            }
+            if let Some(pay_period) = &request.pay_period {
+                query = query.period(pay_period);
+            }
+            if let Some(from) = from {
+                query = query.from_period(from);
+            }
+            if let Some(to) = to {
+                query = query.to_period(to);
+            }
+            if let Some(kind) = kind {
+                query = query.employee_kind(kind);
+            }
+            payroll
+                .get_payroll_records()
+                .iter()
+                .enumerate()
+                .filter(|(_, record)| query.matches(record))
+                .map(|(index, record)| record_message(index, record))
+                .collect()
+        });
+        Ok(Response::new(stream_items(records)))
+    }
+}
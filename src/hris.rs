@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// An employee as an HR system or directory knows them: master data only, without pay details
+/// unless the system happens to hold them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceEmployee {
+    #[serde(alias = "id")]
+    pub employee_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub department: Option<String>,
+    /// Whether the employee is still employed; inactive ones are archived.
+    #[serde(default = "active")]
+    pub active: bool,
+    /// Monthly base salary, for creating a full-time employee.
+    #[serde(default)]
+    pub base_salary: Option<f64>,
+    /// Hourly rate, for creating a contract employee.
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,
+}
+
+fn active() -> bool {
+    true
+}
+
+impl SourceEmployee {
+    pub fn new(employee_id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            employee_id: employee_id.into(),
+            name: name.into(),
+            department: None,
+            active: true,
+            base_salary: None,
+            hourly_rate: None,
+        }
+    }
+}
+
+/// Where employee master data comes from, e.g. an HRIS or an LDAP directory. Implement it to
+/// connect another system; the crate ships `RestEmployeeSource` (`hris` feature) and
+/// `MockEmployeeSource`.
+pub trait EmployeeSource {
+    fn name(&self) -> &str;
+    /// Every employee the source knows, including those who have left when it still lists them.
+    fn fetch_employees(&self) -> Result<Vec<SourceEmployee>, String>;
+}
+
+/// What syncing from an [`EmployeeSource`] changed, by employee ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    pub source: String,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deactivated: Vec<String>,
+    pub unchanged: usize,
+    /// Employees the source lists but that could not be synced, with the reason.
+    pub skipped: Vec<(String, String)>,
+    /// Active employees the source does not list; deactivated only when asked to.
+    pub missing: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn has_changes(&self) -> bool {
+        !self.created.is_empty() || !self.updated.is_empty() || !self.deactivated.is_empty()
+    }
+}
+
+/// Serves a fixed list of employees, and can be changed in between syncs.
+#[derive(Default)]
+pub struct MockEmployeeSource {
+    employees: Mutex<Vec<SourceEmployee>>,
+}
+
+impl MockEmployeeSource {
+    pub fn new(employees: Vec<SourceEmployee>) -> Self {
+        Self {
+            employees: Mutex::new(employees),
+        }
+    }
+
+    /// Adds `employee`, or replaces the one with the same ID.
+    pub fn upsert(&self, employee: SourceEmployee) {
+        let mut employees = self.employees.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match employees.iter_mut().find(|existing| existing.employee_id == employee.employee_id) {
+            Some(existing) => *existing = employee,
+            None => employees.push(employee),
+        }
+    }
+}
+
+impl EmployeeSource for MockEmployeeSource {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn fetch_employees(&self) -> Result<Vec<SourceEmployee>, String> {
+        Ok(self.employees.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone())
+    }
+}
+
+/// Reads employees from an HTTP endpoint returning a JSON array of [`SourceEmployee`], or an
+/// object with the array under `employees`, as HRIS exports and directory gateways usually can.
+#[cfg(feature = "hris")]
+#[derive(Debug, Clone)]
+pub struct RestEmployeeSource {
+    pub url: String,
+    /// Sent as a bearer token when set.
+    pub token: Option<String>,
+}
+
+#[cfg(feature = "hris")]
+impl EmployeeSource for RestEmployeeSource {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    fn fetch_employees(&self) -> Result<Vec<SourceEmployee>, String> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Listing {
+            Employees(Vec<SourceEmployee>),
+            Wrapped { employees: Vec<SourceEmployee> },
+        }
+
+        let mut request = ureq::get(&self.url).timeout(std::time::Duration::from_secs(30));
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        let body = request
+            .call()
+            .map_err(|err| err.to_string())?
+            .into_string()
+            .map_err(|err| err.to_string())?;
+        match serde_json::from_str(&body).map_err(|err| format!("unexpected response: {}", err))? {
+            Listing::Employees(employees) | Listing::Wrapped { employees } => Ok(employees),
+        }
+    }
+}
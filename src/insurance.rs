@@ -0,0 +1,226 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// Premiums are remitted to insurers by this day of the month after the pay period.
+pub const INSURANCE_DUE_DAY: u32 = 10;
+
+/// One coverage level of a plan (e.g. employee only, with spouse, family) and its monthly premium,
+/// split between the employee and the company.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanTier {
+    pub name: String,
+    pub employee_premium: f64,
+    pub employer_premium: f64,
+}
+
+/// A private insurance plan (`[[insurance]]` in the configuration).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsurancePlan {
+    pub code: String,
+    pub name: String,
+    pub insurer: String,
+    pub tiers: Vec<PlanTier>,
+}
+
+impl InsurancePlan {
+    pub fn tier(&self, name: &str) -> Option<&PlanTier> {
+        self.tiers.iter().find(|tier| tier.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.code.trim().is_empty() || self.insurer.trim().is_empty() {
+            return Err("insurance plans need a code and an insurer".to_string());
+        }
+        if self.tiers.is_empty() {
+            return Err(format!("insurance plan {} needs at least one tier", self.code));
+        }
+        for tier in &self.tiers {
+            for premium in [tier.employee_premium, tier.employer_premium] {
+                if !premium.is_finite() || premium < 0.0 {
+                    return Err(format!("premiums of insurance plan {} must not be negative", self.code));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An employee's cover under a plan tier from `start` until `end` (inclusive), or until ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InsuranceEnrollment {
+    pub employee_id: String,
+    pub plan: String,
+    pub tier: String,
+    pub start: PayPeriod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<PayPeriod>,
+}
+
+impl InsuranceEnrollment {
+    pub fn covers(&self, period: PayPeriod) -> bool {
+        self.start <= period && self.end.is_none_or(|end| period <= end)
+    }
+}
+
+/// The premium a payroll record paid under one plan, kept with the record for remittance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct InsurancePremium {
+    pub plan: String,
+    pub insurer: String,
+    pub tier: String,
+    pub employee_share: f64,
+    pub employer_share: f64,
+}
+
+impl InsurancePremium {
+    /// The deduction line the employee's share is taken from pay under.
+    pub fn deduction_name(plan: &str) -> String {
+        format!("Insurance ({})", plan)
+    }
+}
+
+/// Every enrollment in a private insurance plan, with the plans configured.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InsuranceEnrollments {
+    enrollments: Vec<InsuranceEnrollment>,
+    #[serde(skip)]
+    plans: Vec<InsurancePlan>,
+}
+
+impl InsuranceEnrollments {
+    pub fn plans(&self) -> &[InsurancePlan] {
+        &self.plans
+    }
+
+    pub fn set_plans(&mut self, plans: Vec<InsurancePlan>) {
+        self.plans = plans;
+    }
+
+    pub fn plan(&self, code: &str) -> Option<&InsurancePlan> {
+        self.plans.iter().find(|plan| plan.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Enrolls an employee in a plan tier from `start` on. An earlier enrollment in the same plan
+    /// ends the period before.
+    pub(crate) fn enroll(
+        &mut self,
+        employee_id: &str,
+        plan: &str,
+        tier: &str,
+        start: PayPeriod,
+    ) -> Result<&InsuranceEnrollment, PayrollError> {
+        let plan = self
+            .plan(plan)
+            .ok_or_else(|| PayrollError::InvalidInsurance(format!("unknown plan '{}'", plan)))?;
+        let tier = plan.tier(tier).ok_or_else(|| {
+            let tiers: Vec<&str> = plan.tiers.iter().map(|tier| tier.name.as_str()).collect();
+            PayrollError::InvalidInsurance(format!(
+                "plan {} has no tier '{}' (tiers: {})",
+                plan.code,
+                tier,
+                tiers.join(", ")
+            ))
+        })?;
+        let (code, tier) = (plan.code.clone(), tier.name.clone());
+        self.enrollments.retain(|enrollment| {
+            enrollment.employee_id != employee_id || enrollment.plan != code || enrollment.start < start
+        });
+        for enrollment in self.enrollments.iter_mut() {
+            if enrollment.employee_id == employee_id && enrollment.plan == code && enrollment.covers(start) {
+                enrollment.end = Some(start.previous());
+            }
+        }
+        self.enrollments.push(InsuranceEnrollment {
+            employee_id: employee_id.to_string(),
+            plan: code,
+            tier,
+            start,
+            end: None,
+        });
+        Ok(self.enrollments.last().expect("enrollment was just pushed"))
+    }
+
+    /// Ends an employee's cover under a plan after `last`.
+    pub(crate) fn end(&mut self, employee_id: &str, plan: &str, last: PayPeriod) -> Result<(), PayrollError> {
+        let enrollment = self
+            .enrollments
+            .iter_mut()
+            .find(|enrollment| {
+                enrollment.employee_id == employee_id
+                    && enrollment.plan.eq_ignore_ascii_case(plan)
+                    && enrollment.end.is_none_or(|end| end > last)
+                    && enrollment.start <= last
+            })
+            .ok_or_else(|| PayrollError::NotEnrolled(employee_id.to_string(), plan.to_string()))?;
+        enrollment.end = Some(last);
+        Ok(())
+    }
+
+    /// Enrollments of one employee, or of everyone, by employee and start.
+    pub fn of(&self, employee_id: Option<&str>) -> Vec<&InsuranceEnrollment> {
+        let mut enrollments: Vec<&InsuranceEnrollment> = self
+            .enrollments
+            .iter()
+            .filter(|enrollment| employee_id.is_none_or(|id| enrollment.employee_id == id))
+            .collect();
+        enrollments.sort_by(|a, b| (&a.employee_id, a.start, &a.plan).cmp(&(&b.employee_id, b.start, &b.plan)));
+        enrollments
+    }
+
+    /// Deducts the employee's share of the premium of every plan covering the record's period, and
+    /// keeps both shares on the record for remittance to the insurer.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee_id = record.employee.as_employee().employee_id().to_string();
+        for enrollment in self
+            .enrollments
+            .iter()
+            .filter(|enrollment| enrollment.employee_id == employee_id && enrollment.covers(period))
+        {
+            let Some(plan) = self.plan(&enrollment.plan) else {
+                continue;
+            };
+            let Some(tier) = plan.tier(&enrollment.tier) else {
+                continue;
+            };
+            if tier.employee_premium > 0.0 {
+                record.deductions += tier.employee_premium;
+                record.extra_deductions.push(PayrollComponent {
+                    name: InsurancePremium::deduction_name(&plan.name),
+                    amount: tier.employee_premium,
+                });
+            }
+            record.insurance.push(InsurancePremium {
+                plan: plan.name.clone(),
+                insurer: plan.insurer.clone(),
+                tier: tier.name.clone(),
+                employee_share: tier.employee_premium,
+                employer_share: tier.employer_premium,
+            });
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+
+    /// Moves the employee's enrollments to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.enrollments.iter_mut().map(|enrollment| renaming.id(&mut enrollment.employee_id)).sum()
+    }
+}
+
+impl Deref for InsuranceEnrollments {
+    type Target = [InsuranceEnrollment];
+
+    fn deref(&self) -> &[InsuranceEnrollment] {
+        &self.enrollments
+    }
+}
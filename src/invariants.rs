@@ -0,0 +1,243 @@
+use crate::calc::{self, CalculationSettings, PtkpStatus, TaxBracket};
+use crate::payroll::PayrollData;
+use std::fmt;
+
+/// Amounts closer than this are treated as equal, to allow for floating-point error.
+const TOLERANCE: f64 = 0.01;
+
+const PTKP_STATUSES: [Option<PtkpStatus>; 9] = [
+    None,
+    Some(PtkpStatus::TK0),
+    Some(PtkpStatus::TK1),
+    Some(PtkpStatus::TK2),
+    Some(PtkpStatus::TK3),
+    Some(PtkpStatus::K0),
+    Some(PtkpStatus::K1),
+    Some(PtkpStatus::K2),
+    Some(PtkpStatus::K3),
+];
+
+/// An invariant that does not hold: what it was checked on, which one, and how it fails.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub subject: String,
+    pub invariant: &'static str,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.subject, self.detail, self.invariant)
+    }
+}
+
+/// Checks a bracket table: at least one bracket, rates between 0 and 1 that never go down, limits
+/// that go up, and only the last bracket left open so every income falls in one.
+pub fn check_brackets(subject: &str, brackets: &[TaxBracket]) -> Vec<Violation> {
+    let violation = |invariant, detail: String| Violation {
+        subject: subject.to_string(),
+        invariant,
+        detail,
+    };
+    let mut violations = Vec::new();
+    if brackets.is_empty() {
+        violations.push(violation("non-empty", "has no brackets".to_string()));
+    }
+    for (index, bracket) in brackets.iter().enumerate() {
+        if !(0.0..=1.0).contains(&bracket.rate) {
+            violations.push(violation("rate-range", format!("bracket {} has rate {}", index + 1, bracket.rate)));
+        }
+    }
+    for (index, pair) in brackets.windows(2).enumerate() {
+        match (pair[0].up_to, pair[1].up_to) {
+            (Some(lower), Some(upper)) if lower >= upper => violations.push(violation(
+                "contiguous",
+                format!("bracket {} ends at {} but bracket {} at {}", index + 1, lower, index + 2, upper),
+            )),
+            (None, _) => violations.push(violation(
+                "contiguous",
+                format!("bracket {} is open-ended but is not the last", index + 1),
+            )),
+            _ => {}
+        }
+        if pair[1].rate < pair[0].rate {
+            violations.push(violation(
+                "monotonic",
+                format!("bracket {} has a lower rate than bracket {}", index + 2, index + 1),
+            ));
+        }
+    }
+    if brackets.last().is_some_and(|bracket| bracket.up_to.is_some()) {
+        violations.push(violation("contiguous", "the last bracket is not open-ended".to_string()));
+    }
+    violations
+}
+
+/// Monthly gross pay one rupiah below, at and above every bracket limit for an employee with
+/// `ptkp_status`, plus zero: the incomes where a bracket table is most likely to go wrong.
+pub fn boundary_incomes(brackets: &[TaxBracket], ptkp_status: Option<PtkpStatus>) -> Vec<f64> {
+    let allowance = ptkp_status.map_or(0.0, |status| status.annual_allowance());
+    let mut incomes = vec![0.0];
+    for limit in brackets.iter().filter_map(|bracket| bracket.up_to) {
+        let monthly = (limit + allowance) / 12.0;
+        incomes.extend([monthly - 1.0, monthly, monthly + 1.0].into_iter().filter(|income| *income >= 0.0));
+    }
+    incomes.sort_by(f64::total_cmp);
+    incomes
+}
+
+/// Checks full-time tax over `incomes` (monthly gross): never negative, never more than the income,
+/// and never lower on a higher income.
+pub fn check_tax_curve(
+    subject: &str,
+    settings: &CalculationSettings,
+    ptkp_status: Option<PtkpStatus>,
+    incomes: &[f64],
+) -> Vec<Violation> {
+    let violation = |invariant, detail: String| Violation {
+        subject: subject.to_string(),
+        invariant,
+        detail,
+    };
+    let mut incomes = incomes.to_vec();
+    incomes.sort_by(f64::total_cmp);
+    let mut violations = Vec::new();
+    let mut previous: Option<(f64, f64)> = None;
+    for income in incomes {
+        let tax = calc::fulltime_tax(income, ptkp_status, settings);
+        if tax < 0.0 || tax > income + TOLERANCE {
+            violations.push(violation("tax-range", format!("tax on {:.2} is {:.2}", income, tax)));
+        }
+        if let Some((previous_income, previous_tax)) = previous {
+            if tax + TOLERANCE < previous_tax {
+                violations.push(violation(
+                    "monotonic",
+                    format!(
+                        "tax falls from {:.2} on {:.2} to {:.2} on {:.2}",
+                        previous_tax, previous_income, tax, income
+                    ),
+                ));
+            }
+        }
+        previous = Some((income, tax));
+    }
+    violations
+}
+
+/// Checks the settings' bracket table, then its tax curve at every bracket boundary for each
+/// PTKP status.
+pub fn check_settings(settings: &CalculationSettings) -> Vec<Violation> {
+    let subject = format!("tax table {}", settings.tax_table.id());
+    let mut violations = check_brackets(&subject, settings.tax_table.brackets());
+    for ptkp_status in PTKP_STATUSES {
+        let incomes = boundary_incomes(settings.tax_table.brackets(), ptkp_status);
+        let subject = format!("{} ({})", subject, ptkp_status.map_or("no PTKP", |status| status.code()));
+        violations.extend(check_tax_curve(&subject, settings, ptkp_status, &incomes));
+    }
+    violations
+}
+
+/// Checks a payroll record's totals: net is gross less deductions and never more than gross, and
+/// gross and deductions add up from the record's lines.
+pub fn check_record(record: &PayrollData) -> Vec<Violation> {
+    let subject = format!("{} {}", record.employee.as_employee().employee_id(), record.pay_period);
+    let violation = |invariant, detail: String| Violation {
+        subject: subject.clone(),
+        invariant,
+        detail,
+    };
+    let mut violations = Vec::new();
+    if (record.gross_salary - record.deductions - record.net_salary).abs() > TOLERANCE {
+        violations.push(violation(
+            "net",
+            format!(
+                "net {:.2} is not gross {:.2} less deductions {:.2}",
+                record.net_salary, record.gross_salary, record.deductions
+            ),
+        ));
+    }
+    if record.net_salary > record.gross_salary + TOLERANCE {
+        violations.push(violation(
+            "net-within-gross",
+            format!("net {:.2} exceeds gross {:.2}", record.net_salary, record.gross_salary),
+        ));
+    }
+    if record.deductions < -TOLERANCE {
+        violations.push(violation("deductions", format!("deductions are {:.2}", record.deductions)));
+    }
+    let earned: f64 = record.earning_items().iter().map(|item| item.amount).sum();
+    if (earned - record.gross_salary).abs() > TOLERANCE {
+        violations.push(violation(
+            "gross-lines",
+            format!("earning lines add up to {:.2}, gross is {:.2}", earned, record.gross_salary),
+        ));
+    }
+    let deducted: f64 = record.deduction_items().iter().map(|item| item.amount).sum();
+    if (deducted - record.deductions).abs() > TOLERANCE {
+        violations.push(violation(
+            "deduction-lines",
+            format!("deduction lines add up to {:.2}, deductions are {:.2}", deducted, record.deductions),
+        ));
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::{AllowancePeriod, TaxTable, FULLTIME_TAX_BRACKETS};
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::EmployeeData;
+
+    fn invariants(violations: &[Violation]) -> Vec<&'static str> {
+        violations.iter().map(|violation| violation.invariant).collect()
+    }
+
+    #[test]
+    fn the_built_in_table_holds_every_invariant() {
+        assert_eq!(check_settings(&CalculationSettings::DEFAULT), Vec::new());
+    }
+
+    #[test]
+    fn broken_bracket_tables_are_reported() {
+        assert_eq!(invariants(&check_brackets("empty", &[])), ["non-empty"]);
+        let brackets = [
+            TaxBracket { up_to: Some(100.0), rate: 0.2 },
+            TaxBracket { up_to: Some(50.0), rate: 0.1 },
+            TaxBracket { up_to: Some(200.0), rate: 1.5 },
+        ];
+        assert_eq!(
+            invariants(&check_brackets("broken", &brackets)),
+            ["rate-range", "contiguous", "monotonic", "contiguous"]
+        );
+    }
+
+    #[test]
+    fn boundary_incomes_straddle_each_limit_after_the_allowance() {
+        let incomes = boundary_incomes(&FULLTIME_TAX_BRACKETS[..1], Some(PtkpStatus::TK0));
+        assert_eq!(incomes, [0.0, 8_999_999.0, 9_000_000.0, 9_000_001.0]);
+    }
+
+    #[test]
+    fn a_tax_curve_that_falls_is_not_monotonic() {
+        let falling = [TaxBracket { up_to: Some(100_000_000.0), rate: 0.3 }, TaxBracket { up_to: None, rate: 0.1 }];
+        let settings = CalculationSettings {
+            tax_table: TaxTable::new("falling", &falling).unwrap(),
+            ..CalculationSettings::DEFAULT
+        };
+        let violations = check_settings(&settings);
+        assert!(invariants(&violations).contains(&"monotonic"));
+        assert!(violations.iter().any(|violation| violation.subject == "tax table falling (TK/0)"));
+    }
+
+    #[test]
+    fn processed_records_add_up() {
+        let mut employee = FulltimeEmployee::new("E1".to_string(), 180.0, 1_200_000.0, AllowancePeriod::Yearly, 8_000_000.0);
+        employee.ptkp_status = Some(PtkpStatus::K1);
+        let mut record = PayrollData::new(EmployeeData::Fulltime(employee), "2026-01".to_string());
+        assert_eq!(check_record(&record), Vec::new());
+
+        record.net_salary = record.gross_salary + 1.0;
+        assert_eq!(invariants(&check_record(&record)), ["net", "net-within-gross"]);
+    }
+}
@@ -0,0 +1,735 @@
+use crate::error::StorageError;
+use crate::migration::{check_schema_version, legacy_schema_version, CURRENT_SCHEMA_VERSION};
+use crate::payroll::{Payroll, PayrollData};
+use crate::payslip::{record_hash, to_hex};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// A payroll record as the ledger knows it: its id, its period and a hash of its contents. The
+/// record itself stays in the data file, so nothing the ledger holds identifies the employee once
+/// they are pseudonymized, while the hash still ties the ledger to the record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LedgerRecord {
+    pub record_id: String,
+    /// `YYYY-MM`.
+    pub pay_period: String,
+    /// The record's [`record_hash`] after the change.
+    pub hash: String,
+    #[serde(default)]
+    pub voided: bool,
+}
+
+impl LedgerRecord {
+    pub fn of(record: &PayrollData) -> Self {
+        Self {
+            record_id: record.record_id.clone(),
+            pay_period: record.pay_period.clone(),
+            hash: record_hash(record),
+            voided: record.voided,
+        }
+    }
+}
+
+/// A change to a stored payroll record. Events are not written by hand: each save of a data file
+/// with a ledger appends the changes since the previous save (see [`PayrollLedger::changes`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum LedgerEvent {
+    RecordProcessed(LedgerRecord),
+    RecordVoided(LedgerRecord),
+    RecordRestored(LedgerRecord),
+    /// The record was changed in place, e.g. edited, recalculated or given a later adjustment.
+    RecordEdited(LedgerRecord),
+    /// The employee was pseudonymized, which moves their records to new ids.
+    RecordPseudonymized {
+        previous_record_id: String,
+        record: LedgerRecord,
+    },
+    /// The record was dropped, e.g. by purging its period or undoing its processing.
+    RecordRemoved(LedgerRecord),
+}
+
+impl LedgerEvent {
+    /// The record the event changed.
+    pub fn record(&self) -> &LedgerRecord {
+        match self {
+            LedgerEvent::RecordProcessed(record)
+            | LedgerEvent::RecordVoided(record)
+            | LedgerEvent::RecordRestored(record)
+            | LedgerEvent::RecordEdited(record)
+            | LedgerEvent::RecordPseudonymized { record, .. }
+            | LedgerEvent::RecordRemoved(record) => record,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LedgerEntry {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub event: LedgerEvent,
+    pub previous_hash: String,
+    /// Whether `hash` is an HMAC under the ledger key rather than a plain SHA-256.
+    #[serde(default)]
+    pub signed: bool,
+    pub hash: String,
+}
+
+impl LedgerEntry {
+    fn compute_hash(
+        sequence: u64,
+        timestamp: &DateTime<Utc>,
+        event: &LedgerEvent,
+        previous_hash: &str,
+        key: Option<&str>,
+    ) -> Result<String, StorageError> {
+        let mut bytes = sequence.to_be_bytes().to_vec();
+        bytes.extend_from_slice(timestamp.to_rfc3339().as_bytes());
+        bytes.extend(serde_json::to_vec(event)?);
+        bytes.extend_from_slice(previous_hash.as_bytes());
+        Ok(match key {
+            Some(key) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key length");
+                mac.update(&bytes);
+                to_hex(&mac.finalize().into_bytes())
+            }
+            None => to_hex(&Sha256::digest(&bytes)),
+        })
+    }
+
+    fn head(&self) -> LedgerHead {
+        LedgerHead {
+            sequence: self.sequence,
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+/// The last ledger entry written with a save, kept in the data file. A ledger cut back to an
+/// earlier entry, or one the data file was not saved with, no longer ends at it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerHead {
+    pub sequence: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedgerVerification {
+    Valid { entries: usize },
+    Tampered { sequence: u64, reason: String },
+    /// The entries are signed and the ledger was opened without the key to check them.
+    KeyRequired { sequence: u64 },
+    /// The chain is intact but does not describe the records in the data file.
+    DataMismatch { differences: Vec<String> },
+}
+
+/// Where a save left the ledger, to cut it back if writing the data file fails.
+pub(crate) struct Appended {
+    length: u64,
+    head: Option<LedgerHead>,
+}
+
+#[derive(Clone)]
+pub struct PayrollLedger {
+    path: PathBuf,
+    key: Option<String>,
+}
+
+impl fmt::Debug for PayrollLedger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayrollLedger")
+            .field("path", &self.path)
+            .field("signed", &self.key.is_some())
+            .finish()
+    }
+}
+
+impl PayrollLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            key: None,
+        }
+    }
+
+    /// The ledger kept beside a data file: `payroll.json` appends to `payroll.ledger.jsonl` in the
+    /// same directory, so each data file has its own history wherever the program is run from.
+    pub fn beside(data: &Path) -> Self {
+        Self::new(data.with_extension("ledger.jsonl"))
+    }
+
+    /// Signs new entries with an HMAC under `key`, and checks signed entries with it. Without the
+    /// key anyone can rewrite the ledger and recompute its hashes; with it they cannot.
+    pub fn with_key(mut self, key: Option<String>) -> Self {
+        self.key = key.filter(|key| !key.is_empty());
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The events that bring the ledger up to the records of `payroll`: records it has not seen are
+    /// processed, ones whose contents changed are voided, restored or edited, ones moved to a new id
+    /// are pseudonymized, and ones no longer there are removed. Records without an id are left out.
+    pub fn changes(&self, payroll: &Payroll) -> Result<Vec<LedgerEvent>, StorageError> {
+        let mut known = replay(&self.entries()?, None);
+        let records = payroll.get_payroll_records();
+        let current: HashSet<&str> = records.iter().map(|record| record.record_id.as_str()).collect();
+
+        let mut events = Vec::new();
+        for record in records.iter().filter(|record| !record.record_id.is_empty()) {
+            let now = LedgerRecord::of(record);
+            let event = match known.remove(&record.record_id) {
+                Some(before) if before.hash == now.hash => continue,
+                Some(before) if now.voided && !before.voided => LedgerEvent::RecordVoided(now),
+                Some(before) if !now.voided && before.voided => LedgerEvent::RecordRestored(now),
+                Some(_) => LedgerEvent::RecordEdited(now),
+                None => {
+                    // Record ids are never given out twice, so following the moves back ends.
+                    let mut previous = payroll.renamed_from(&record.record_id);
+                    while let Some(id) = previous.filter(|id| current.contains(id) || !known.contains_key(*id)) {
+                        previous = payroll.renamed_from(id);
+                    }
+                    match previous.and_then(|id| known.remove_entry(id)) {
+                        Some((previous_record_id, _)) => LedgerEvent::RecordPseudonymized {
+                            previous_record_id,
+                            record: now,
+                        },
+                        None => LedgerEvent::RecordProcessed(now),
+                    }
+                }
+            };
+            events.push(event);
+        }
+        events.extend(known.into_values().map(LedgerEvent::RecordRemoved));
+        Ok(events)
+    }
+
+    /// Appends the changes `payroll` holds since the ledger was last written, and notes the new last
+    /// entry in `payroll`. Called by [`Storage::save_with`](crate::storage::Storage::save_with) once
+    /// no one else can save.
+    pub(crate) fn record(&self, payroll: &Payroll) -> Result<Appended, StorageError> {
+        let appended = Appended {
+            length: self.len()?,
+            head: payroll.ledger_head(),
+        };
+        let events = self.changes(payroll)?;
+        if let Some(last) = self.append_all(events)?.last() {
+            payroll.set_ledger_head(Some(last.head()));
+        } else if appended.head.is_none() {
+            payroll.set_ledger_head(self.entries()?.last().map(LedgerEntry::head));
+        }
+        Ok(appended)
+    }
+
+    /// Undoes [`record`](Self::record) after the data file could not be written.
+    pub(crate) fn roll_back(&self, payroll: &Payroll, appended: Appended) -> Result<(), StorageError> {
+        payroll.set_ledger_head(appended.head);
+        self.truncate(appended.length)
+    }
+
+    /// Appends several events in one write. If the write fails, the ledger is cut back to where it
+    /// was, so it holds either all of the events or none.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(path = %self.path.display(), events = events.len()), err(Display)))]
+    pub(crate) fn append_all(&self, events: Vec<LedgerEvent>) -> Result<Vec<LedgerEntry>, StorageError> {
+        if events.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (mut sequence, mut previous_hash) = match self.entries()?.last() {
+            Some(last) if last.signed && self.key.is_none() => return Err(StorageError::LedgerKeyRequired),
+            Some(last) => (last.sequence, last.hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        let mut entries = Vec::with_capacity(events.len());
+        let mut lines = String::new();
+        for event in events {
+            sequence += 1;
+            let timestamp = Utc::now();
+            let hash = LedgerEntry::compute_hash(sequence, &timestamp, &event, &previous_hash, self.key.as_deref())?;
+            let entry = LedgerEntry {
+                schema_version: CURRENT_SCHEMA_VERSION,
+                sequence,
+                timestamp,
+                event,
+                previous_hash,
+                signed: self.key.is_some(),
+                hash: hash.clone(),
+            };
+            lines.push_str(&serde_json::to_string(&entry)?);
+            lines.push('\n');
+            entries.push(entry);
+            previous_hash = hash;
+        }
+
+        let length = self.len()?;
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()).and_then(|()| file.sync_all()));
+        if let Err(err) = written {
+            self.truncate(length)?;
+            return Err(err.into());
+        }
+        Ok(entries)
+    }
+
+    /// The size of the ledger file in bytes, 0 before the first entry.
+    pub(crate) fn len(&self) -> Result<u64, StorageError> {
+        match fs::metadata(&self.path) {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Cuts the ledger back to `length` bytes, dropping the entries appended after it.
+    pub(crate) fn truncate(&self, length: u64) -> Result<(), StorageError> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let file = OpenOptions::new().write(true).open(&self.path)?;
+        file.set_len(length)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Result<Vec<LedgerEntry>, StorageError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let reader = BufReader::new(fs::File::open(&self.path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: LedgerEntry = serde_json::from_str(&line)?;
+            check_schema_version(entry.schema_version)?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Checks that the entries chain from the genesis hash and that none was changed. Entries written
+    /// before a key was set may stay unsigned, but none may follow a signed one.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), fields(path = %self.path.display()), err(Display)))]
+    pub fn verify(&self) -> Result<LedgerVerification, StorageError> {
+        self.verify_entries().map(|checked| match checked {
+            Ok(entries) => LedgerVerification::Valid {
+                entries: entries.len(),
+            },
+            Err(verification) => verification,
+        })
+    }
+
+    /// Checks the chain as [`verify`](Self::verify) does, then that it ends at the head saved in
+    /// `payroll` and that replaying it gives exactly the records `payroll` holds.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(path = %self.path.display()), err(Display)))]
+    pub fn verify_against(&self, payroll: &Payroll) -> Result<LedgerVerification, StorageError> {
+        let entries = match self.verify_entries()? {
+            Ok(entries) => entries,
+            Err(verification) => return Ok(verification),
+        };
+
+        let mut differences = Vec::new();
+        let last = entries.last().map(LedgerEntry::head);
+        match (payroll.ledger_head(), last) {
+            (Some(head), Some(last)) if head == last => {}
+            (Some(head), _) if !entries.iter().any(|entry| entry.head() == head) => differences.push(format!(
+                "the data file was saved with entry #{}, which is not in the ledger",
+                head.sequence
+            )),
+            (Some(head), _) => differences.push(format!(
+                "the ledger continues past entry #{}, the last one the data file was saved with",
+                head.sequence
+            )),
+            (None, Some(_)) => differences.push("the data file was not saved with this ledger".to_string()),
+            (None, None) => {}
+        }
+
+        let mut known = replay(&entries, payroll.ledger_head().map(|head| head.sequence));
+        for record in payroll.get_payroll_records().iter().filter(|record| !record.record_id.is_empty()) {
+            match known.remove(&record.record_id) {
+                Some(logged) if logged.hash == record_hash(record) => {}
+                Some(_) => differences.push(format!("record {} differs from the ledger", record.record_id)),
+                None => differences.push(format!("record {} is not in the ledger", record.record_id)),
+            }
+        }
+        for record_id in known.into_keys() {
+            differences.push(format!("record {} is in the ledger but not in the data file", record_id));
+        }
+
+        Ok(match differences.is_empty() {
+            true => LedgerVerification::Valid {
+                entries: entries.len(),
+            },
+            false => LedgerVerification::DataMismatch { differences },
+        })
+    }
+
+    /// The entries when their chain is intact, or what is wrong with it.
+    fn verify_entries(&self) -> Result<Result<Vec<LedgerEntry>, LedgerVerification>, StorageError> {
+        let entries = match self.entries() {
+            Ok(entries) => entries,
+            Err(StorageError::Serialization(err)) => {
+                return Ok(Err(LedgerVerification::Tampered {
+                    sequence: 0,
+                    reason: format!("unreadable entry: {}", err),
+                }))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut previous_hash = GENESIS_HASH.to_string();
+        let mut signed = false;
+        for (position, entry) in entries.iter().enumerate() {
+            let expected_sequence = position as u64 + 1;
+            let tampered = |reason: String| {
+                Ok(Err(LedgerVerification::Tampered {
+                    sequence: entry.sequence,
+                    reason,
+                }))
+            };
+            if entry.sequence != expected_sequence {
+                return tampered(format!("expected sequence {}", expected_sequence));
+            }
+            if entry.previous_hash != previous_hash {
+                return tampered("previous hash does not match the preceding entry".to_string());
+            }
+            if signed && !entry.signed {
+                return tampered("entry is not signed, unlike the ones before it".to_string());
+            }
+            signed = entry.signed;
+            let key = match (entry.signed, self.key.as_deref()) {
+                (true, None) => {
+                    return Ok(Err(LedgerVerification::KeyRequired {
+                        sequence: entry.sequence,
+                    }))
+                }
+                (true, key) => key,
+                (false, _) => None,
+            };
+            let hash =
+                LedgerEntry::compute_hash(entry.sequence, &entry.timestamp, &entry.event, &entry.previous_hash, key)?;
+            if entry.hash != hash {
+                return tampered("entry contents do not match its hash".to_string());
+            }
+            previous_hash = entry.hash.clone();
+        }
+        Ok(Ok(entries))
+    }
+}
+
+/// The records as the entries up to sequence `until` (all of them for `None`) leave them, by id.
+fn replay(entries: &[LedgerEntry], until: Option<u64>) -> BTreeMap<String, LedgerRecord> {
+    let mut records = BTreeMap::new();
+    for entry in entries.iter().take_while(|entry| until.is_none_or(|until| entry.sequence <= until)) {
+        match &entry.event {
+            LedgerEvent::RecordRemoved(record) => {
+                records.remove(&record.record_id);
+            }
+            LedgerEvent::RecordPseudonymized {
+                previous_record_id,
+                record,
+            } => {
+                records.remove(previous_record_id);
+                records.insert(record.record_id.clone(), record.clone());
+            }
+            event => {
+                records.insert(event.record().record_id.clone(), event.record().clone());
+            }
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpjs::BpjsRates;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::EmployeeData;
+    use crate::storage::{JsonFileStorage, Storage};
+    use chrono::NaiveDate;
+
+    /// A ledger in a file of its own under the temp directory, removed when dropped.
+    struct TempLedger(PayrollLedger);
+
+    impl TempLedger {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("employee-ledger-{}-{}.jsonl", std::process::id(), name));
+            let _ = fs::remove_file(&path);
+            Self(PayrollLedger::new(path))
+        }
+
+        fn rewrite(&self, edit: impl FnOnce(Vec<String>) -> Vec<String>) {
+            let text = fs::read_to_string(self.0.path()).unwrap();
+            let lines = edit(text.lines().map(str::to_string).collect());
+            fs::write(self.0.path(), lines.join("\n") + "\n").unwrap();
+        }
+    }
+
+    impl Drop for TempLedger {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(self.0.path());
+        }
+    }
+
+    fn voided(record_index: usize) -> LedgerEvent {
+        LedgerEvent::RecordVoided(LedgerRecord {
+            record_id: format!("E1-{}", record_index),
+            pay_period: "2026-01".to_string(),
+            hash: GENESIS_HASH.to_string(),
+            voided: true,
+        })
+    }
+
+    #[test]
+    fn entries_chain_from_the_genesis_hash() {
+        let ledger = TempLedger::new("chain");
+        assert_eq!(ledger.0.verify().unwrap(), LedgerVerification::Valid { entries: 0 });
+
+        let first = ledger.0.append_all(vec![voided(0)]).unwrap().remove(0);
+        let rest = ledger.0.append_all(vec![voided(1), voided(2)]).unwrap();
+        assert_eq!(first.sequence, 1);
+        assert_eq!(first.previous_hash, GENESIS_HASH);
+        assert_eq!(rest[0].previous_hash, first.hash);
+        assert_eq!(rest[1].sequence, 3);
+        assert_eq!(ledger.0.entries().unwrap().len(), 3);
+        assert_eq!(ledger.0.verify().unwrap(), LedgerVerification::Valid { entries: 3 });
+    }
+
+    #[test]
+    fn an_edited_entry_fails_its_hash() {
+        let ledger = TempLedger::new("edited");
+        ledger.0.append_all(vec![voided(0), voided(1)]).unwrap();
+        ledger.rewrite(|mut lines| {
+            lines[1] = lines[1].replace("\"2026-01\"", "\"2026-02\"");
+            lines
+        });
+        assert_eq!(
+            ledger.0.verify().unwrap(),
+            LedgerVerification::Tampered {
+                sequence: 2,
+                reason: "entry contents do not match its hash".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn a_removed_entry_breaks_the_sequence() {
+        let ledger = TempLedger::new("removed");
+        ledger.0.append_all(vec![voided(0), voided(1), voided(2)]).unwrap();
+        ledger.rewrite(|mut lines| {
+            lines.remove(1);
+            lines
+        });
+        assert_eq!(
+            ledger.0.verify().unwrap(),
+            LedgerVerification::Tampered {
+                sequence: 3,
+                reason: "expected sequence 2".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn an_unreadable_entry_is_reported_as_tampering() {
+        let ledger = TempLedger::new("unreadable");
+        ledger.0.append_all(vec![voided(0)]).unwrap();
+        ledger.rewrite(|mut lines| {
+            lines.push("{not json".to_string());
+            lines
+        });
+        assert!(matches!(ledger.0.verify().unwrap(), LedgerVerification::Tampered { sequence: 0, .. }));
+    }
+
+    #[test]
+    fn the_ledger_sits_beside_its_data_file() {
+        let ledger = PayrollLedger::beside(Path::new("/srv/payroll/acme.json"));
+        assert_eq!(ledger.path(), Path::new("/srv/payroll/acme.ledger.jsonl"));
+    }
+
+    /// A data file that keeps a ledger, both under the temp directory and removed when dropped.
+    struct TempData(JsonFileStorage);
+
+    impl TempData {
+        fn new(name: &str, key: Option<&str>) -> Self {
+            let path = std::env::temp_dir().join(format!("employee-ledger-{}-{}.json", std::process::id(), name));
+            let ledger = PayrollLedger::beside(&path).with_key(key.map(String::from));
+            let data = Self(JsonFileStorage::new(&path).with_ledger(ledger));
+            data.remove_files();
+            data
+        }
+
+        fn ledger(&self) -> &PayrollLedger {
+            self.0.ledger().unwrap()
+        }
+
+        /// The events of the entries after the first `skip`.
+        fn events(&self, skip: usize) -> Vec<LedgerEvent> {
+            self.ledger().entries().unwrap().into_iter().skip(skip).map(|entry| entry.event).collect()
+        }
+
+        fn remove_files(&self) {
+            let path = self.0.path();
+            for file in [path.to_path_buf(), path.with_extension("lock"), self.ledger().path().to_path_buf()] {
+                let _ = fs::remove_file(file);
+            }
+        }
+    }
+
+    impl Drop for TempData {
+        fn drop(&mut self) {
+            self.remove_files();
+        }
+    }
+
+    fn employee(employee_id: &str, base_salary: f64) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            employee_id.to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            base_salary,
+        ))
+    }
+
+    fn processed(periods: &[&str]) -> Payroll {
+        let mut payroll = Payroll::new();
+        for (employee_id, base_salary) in [("E1", 9_000_000.0), ("E2", 12_000_000.0)] {
+            payroll.add_employee(employee(employee_id, base_salary));
+            for period in periods {
+                payroll.process_payroll(employee(employee_id, base_salary), period.to_string()).unwrap();
+            }
+        }
+        payroll
+    }
+
+    #[test]
+    fn each_save_logs_the_record_changes_it_holds() {
+        let data = TempData::new("changes", None);
+        let mut payroll = processed(&["2026-01"]);
+        data.0.save(&payroll).unwrap();
+        assert!(matches!(&data.events(0)[..], [LedgerEvent::RecordProcessed(_), LedgerEvent::RecordProcessed(_)]));
+
+        data.0.save(&payroll).unwrap();
+        assert!(data.events(2).is_empty());
+
+        let first = payroll.find_record_index("E1", "2026-01").unwrap();
+        let second = payroll.find_record_index("E2", "2026-01").unwrap();
+        payroll.void_record(first).unwrap();
+        payroll.edit_record(second, employee("E2", 13_000_000.0)).unwrap();
+        data.0.save(&payroll).unwrap();
+        let events = data.events(2);
+        assert!(matches!(&events[..], [LedgerEvent::RecordVoided(_), LedgerEvent::RecordEdited(_)]));
+        assert_eq!(events[1].record(), &LedgerRecord::of(&payroll.get_payroll_records()[second]));
+
+        payroll.restore_record(first).unwrap();
+        data.0.save(&payroll).unwrap();
+        assert!(matches!(&data.events(4)[..], [LedgerEvent::RecordRestored(_)]));
+        assert_eq!(
+            data.ledger().verify_against(&data.0.load().unwrap()).unwrap(),
+            LedgerVerification::Valid { entries: 5 }
+        );
+    }
+
+    #[test]
+    fn moved_and_purged_records_are_logged() {
+        let data = TempData::new("moved", None);
+        let mut payroll = processed(&["2010-01", "2026-01"]);
+        data.0.save(&payroll).unwrap();
+
+        let index = payroll.find_record_index("E1", "2026-01").unwrap();
+        let previous = payroll.get_payroll_records()[index].record_id.clone();
+        payroll.update_record(index, |record| record.record_id = "anon-1-2026-01-9".to_string()).unwrap();
+        payroll.purge_records(10, NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(), &BpjsRates::default());
+        data.0.save(&payroll).unwrap();
+
+        let events = data.events(4);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            LedgerEvent::RecordPseudonymized { previous_record_id, record }
+                if *previous_record_id == previous && record.record_id == "anon-1-2026-01-9"
+        ));
+        assert!(events[1..]
+            .iter()
+            .all(|event| matches!(event, LedgerEvent::RecordRemoved(record) if record.pay_period == "2010-01")));
+        assert_eq!(data.ledger().verify_against(&payroll).unwrap(), LedgerVerification::Valid { entries: 7 });
+    }
+
+    #[test]
+    fn data_changed_behind_the_ledger_does_not_match_it() {
+        let data = TempData::new("behind", None);
+        let mut payroll = processed(&["2026-01"]);
+        data.0.save(&payroll).unwrap();
+
+        let index = payroll.find_record_index("E1", "2026-01").unwrap();
+        payroll.edit_record(index, employee("E1", 19_000_000.0)).unwrap();
+        JsonFileStorage::new(data.0.path()).save(&payroll).unwrap();
+        let record_id = &payroll.get_payroll_records()[index].record_id;
+        assert_eq!(
+            data.ledger().verify_against(&data.0.load().unwrap()).unwrap(),
+            LedgerVerification::DataMismatch {
+                differences: vec![format!("record {} differs from the ledger", record_id)],
+            }
+        );
+    }
+
+    #[test]
+    fn a_ledger_cut_back_no_longer_ends_at_the_saved_head() {
+        let data = TempData::new("cut", None);
+        let mut payroll = processed(&["2026-01"]);
+        data.0.save(&payroll).unwrap();
+        payroll.void_record(0).unwrap();
+        data.0.save(&payroll).unwrap();
+
+        let text = fs::read_to_string(data.ledger().path()).unwrap();
+        let kept: Vec<&str> = text.lines().take(2).collect();
+        fs::write(data.ledger().path(), kept.join("\n") + "\n").unwrap();
+        assert_eq!(data.ledger().verify().unwrap(), LedgerVerification::Valid { entries: 2 });
+        assert!(matches!(
+            data.ledger().verify_against(&payroll).unwrap(),
+            LedgerVerification::DataMismatch { differences } if differences[0].contains("entry #3")
+        ));
+    }
+
+    #[test]
+    fn signed_entries_need_the_key() {
+        let data = TempData::new("signed", Some("ledger secret"));
+        let payroll = processed(&["2026-01"]);
+        data.0.save(&payroll).unwrap();
+        assert!(data.ledger().entries().unwrap().iter().all(|entry| entry.signed));
+        assert_eq!(data.ledger().verify_against(&payroll).unwrap(), LedgerVerification::Valid { entries: 2 });
+
+        let unkeyed = PayrollLedger::beside(data.0.path());
+        assert_eq!(unkeyed.verify().unwrap(), LedgerVerification::KeyRequired { sequence: 1 });
+        let wrong = PayrollLedger::beside(data.0.path()).with_key(Some("guess".to_string()));
+        assert!(matches!(wrong.verify().unwrap(), LedgerVerification::Tampered { sequence: 1, .. }));
+        assert!(matches!(
+            unkeyed.append_all(vec![voided(0)]),
+            Err(StorageError::LedgerKeyRequired)
+        ));
+    }
+}
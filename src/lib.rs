@@ -1,9 +1,259 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "core")))]
+compile_error!("enable the default `std` feature, or `core` for the calculation rules only");
+
+#[cfg(feature = "std")]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+#[cfg(feature = "std")]
+pub mod access_log;
+#[cfg(feature = "std")]
+pub mod accounting;
+#[cfg(feature = "async")]
+pub mod async_api;
+#[cfg(feature = "std")]
+pub mod attendance;
+#[cfg(feature = "std")]
+pub mod auth;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bpjs;
+#[cfg(feature = "std")]
+pub mod budget;
+#[cfg(feature = "server")]
+pub mod cache;
+pub mod calc;
+#[cfg(feature = "std")]
+pub mod calendar;
+#[cfg(feature = "cli")]
+pub mod commands;
+#[cfg(feature = "std")]
+pub mod compensation;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod credentials;
+#[cfg(feature = "std")]
+pub mod delivery;
+#[cfg(feature = "std")]
+pub mod disbursement;
+#[cfg(feature = "std")]
+pub mod demo;
+#[cfg(feature = "email")]
+pub mod email;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+#[cfg(feature = "std")]
+pub mod equity;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod expense;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "std")]
+pub mod hris;
+#[cfg(feature = "std")]
+pub mod insurance;
+#[cfg(feature = "std")]
+pub mod invariants;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
 pub mod tax;
+#[cfg(feature = "std")]
 pub mod employee;
+#[cfg(feature = "std")]
+pub mod ledger;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod migration;
+#[cfg(feature = "std")]
+pub mod natura;
+#[cfg(feature = "std")]
+pub mod notify;
+#[cfg(feature = "std")]
+pub mod onboarding;
+#[cfg(feature = "std")]
+pub mod overtime;
+#[cfg(feature = "std")]
 pub mod payroll;
+#[cfg(feature = "std")]
+pub mod payslip;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "std")]
+pub mod period;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "std")]
+pub mod presentation;
+#[cfg(feature = "std")]
+pub mod privacy;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(feature = "std")]
+pub mod rate_limit;
+#[cfg(feature = "std")]
+pub mod read_model;
+#[cfg(feature = "std")]
+pub mod records;
+#[cfg(feature = "std")]
+pub mod remittance;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod retention;
+#[cfg(feature = "std")]
+pub mod review;
+#[cfg(feature = "std")]
+pub mod rule_history;
+#[cfg(feature = "std")]
+pub mod rules;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "std")]
+pub mod self_service;
+#[cfg(feature = "std")]
+pub mod severance;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod shared;
+#[cfg(feature = "std")]
+pub mod simulation;
+#[cfg(feature = "std")]
+pub mod statistics;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod storage;
+#[cfg(feature = "std")]
+pub mod transaction;
+#[cfg(feature = "cli")]
+pub mod term;
+#[cfg(feature = "std")]
+pub mod timesheet;
+#[cfg(feature = "std")]
+pub mod travel;
+#[cfg(feature = "std")]
+pub mod webhook;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "cli")]
 pub mod cli;
 
+#[cfg(feature = "std")]
+pub use accounting::*;
+#[cfg(feature = "std")]
+pub use attendance::*;
+#[cfg(feature = "std")]
+pub use batch::*;
+#[cfg(feature = "std")]
+pub use bpjs::*;
+#[cfg(feature = "std")]
+pub use budget::*;
+#[cfg(feature = "std")]
+pub use compensation::*;
+#[cfg(feature = "std")]
+pub use config::*;
+#[cfg(feature = "std")]
+pub use context::*;
+#[cfg(feature = "std")]
+pub use credentials::*;
+#[cfg(feature = "std")]
+pub use delivery::*;
+#[cfg(feature = "std")]
+pub use disbursement::*;
+#[cfg(feature = "std")]
+pub use demo::*;
+#[cfg(feature = "std")]
+pub use equity::*;
+#[cfg(feature = "std")]
+pub use error::*;
+#[cfg(feature = "std")]
+pub use expense::*;
+#[cfg(feature = "std")]
 pub use tax::*;
+#[cfg(feature = "std")]
 pub use employee::*;
+#[cfg(feature = "std")]
+pub use ledger::*;
+#[cfg(feature = "std")]
+pub use insurance::*;
+#[cfg(feature = "std")]
+pub use invariants::*;
+#[cfg(feature = "std")]
+pub use verify::*;
+#[cfg(feature = "std")]
+pub use natura::*;
+#[cfg(feature = "std")]
+pub use notify::*;
+#[cfg(feature = "std")]
+pub use onboarding::*;
+#[cfg(feature = "std")]
+pub use overtime::*;
+#[cfg(feature = "std")]
 pub use payroll::*;
+#[cfg(feature = "std")]
+pub use period::*;
+#[cfg(feature = "std")]
+pub use presentation::*;
+#[cfg(feature = "std")]
+pub use query::*;
+#[cfg(feature = "std")]
+pub use remittance::*;
+#[cfg(feature = "std")]
+pub use report::*;
+#[cfg(feature = "std")]
+pub use review::*;
+#[cfg(feature = "std")]
+pub use rule_history::*;
+#[cfg(feature = "std")]
+pub use rules::*;
+#[cfg(feature = "std")]
+pub use schedule::*;
+#[cfg(feature = "std")]
+pub use search::*;
+#[cfg(feature = "std")]
+pub use self_service::*;
+#[cfg(feature = "std")]
+pub use severance::*;
+#[cfg(feature = "std")]
+pub use shared::*;
+#[cfg(feature = "std")]
+pub use simulation::*;
+#[cfg(feature = "std")]
+pub use statistics::*;
+#[cfg(feature = "std")]
+pub use storage::*;
+#[cfg(feature = "std")]
+pub use transaction::*;
+#[cfg(feature = "std")]
+pub use timesheet::*;
+#[cfg(feature = "std")]
+pub use travel::*;
+#[cfg(feature = "std")]
+pub use webhook::*;
+#[cfg(feature = "cli")]
 pub use cli::*;
\ No newline at end of file
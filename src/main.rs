@@ -1,6 +1,3 @@
-use employee_management::cli::CLI;
-
 fn main() {
-    let mut cli = CLI::new();
-    cli.run();
-}
\ No newline at end of file
+    std::process::exit(employee_management::commands::run());
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const LATENCY_BUCKETS: [f64; 9] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    counts: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    records_processed: AtomicU64,
+    processing_errors: AtomicU64,
+    runs_approved: AtomicU64,
+    storage_errors: AtomicU64,
+    webhook_failures: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    cache_errors: AtomicU64,
+    rate_limited: AtomicU64,
+    access_log_errors: AtomicU64,
+    processing_latency: Mutex<Histogram>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_processed(&self, elapsed: Duration) {
+        self.records_processed.fetch_add(1, Ordering::Relaxed);
+        let seconds = elapsed.as_secs_f64();
+        let mut histogram = self
+            .processing_latency
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (bucket, count) in LATENCY_BUCKETS.iter().zip(histogram.counts.iter_mut()) {
+            if seconds <= *bucket {
+                *count += 1;
+            }
+        }
+        histogram.count += 1;
+        histogram.sum += seconds;
+    }
+
+    pub fn processing_failed(&self) {
+        self.processing_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn run_approved(&self) {
+        self.runs_approved.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn storage_failed(&self) {
+        self.storage_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn webhook_failed(&self) {
+        self.webhook_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_missed(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_failed(&self) {
+        self.cache_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn request_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn access_log_failed(&self) {
+        self.access_log_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn render(&self, employees: usize, records: usize) -> String {
+        let mut out = String::new();
+        counter(&mut out, "payroll_records_processed_total", "Payroll records processed.", &self.records_processed);
+        counter(&mut out, "payroll_processing_errors_total", "Payroll processing requests rejected.", &self.processing_errors);
+        counter(&mut out, "payroll_runs_approved_total", "Payroll runs approved.", &self.runs_approved);
+        counter(&mut out, "payroll_storage_errors_total", "Failed storage operations.", &self.storage_errors);
+        counter(&mut out, "payroll_webhook_failures_total", "Webhook deliveries that failed after all retries.", &self.webhook_failures);
+        counter(&mut out, "payroll_cache_hits_total", "Query results served from the cache.", &self.cache_hits);
+        counter(&mut out, "payroll_cache_misses_total", "Query results computed and cached.", &self.cache_misses);
+        counter(&mut out, "payroll_cache_errors_total", "Failed cache operations.", &self.cache_errors);
+        counter(&mut out, "payroll_rate_limited_total", "API requests refused by the rate limit.", &self.rate_limited);
+        counter(&mut out, "payroll_access_log_errors_total", "Requests not written to the access log.", &self.access_log_errors);
+        gauge(&mut out, "payroll_employees", "Registered employees.", employees);
+        gauge(&mut out, "payroll_records", "Stored payroll records.", records);
+
+        let histogram = self
+            .processing_latency
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let name = "payroll_processing_seconds";
+        let _ = writeln!(out, "# HELP {} Time spent calculating and storing a payroll record.", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bucket, count) in LATENCY_BUCKETS.iter().zip(histogram.counts.iter()) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bucket, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+        let _ = writeln!(out, "{}_sum {}", name, histogram.sum);
+        let _ = writeln!(out, "{}_count {}", name, histogram.count);
+        out
+    }
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: &AtomicU64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: usize) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", name, value);
+}
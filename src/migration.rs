@@ -0,0 +1,161 @@
+use crate::error::StorageError;
+use crate::payroll::PayrollData;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const CURRENT_SCHEMA_VERSION: u32 = 3;
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+type Migration = fn(&mut Value);
+
+// MIGRATIONS[n] upgrades a document from version n + 1 to version n + 2.
+const MIGRATIONS: [Migration; 2] = [v1_to_v2, v2_to_v3];
+
+pub fn legacy_schema_version() -> u32 {
+    LEGACY_SCHEMA_VERSION
+}
+
+pub fn schema_version(document: &Value) -> u32 {
+    document
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|version| version as u32)
+        .unwrap_or(LEGACY_SCHEMA_VERSION)
+}
+
+pub fn check_schema_version(version: u32) -> Result<(), StorageError> {
+    if version == 0 || version > CURRENT_SCHEMA_VERSION {
+        Err(StorageError::UnsupportedSchemaVersion(version))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn migrate(mut document: Value) -> Result<Value, StorageError> {
+    let version = schema_version(&document);
+    check_schema_version(version)?;
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(&mut document);
+    }
+    Ok(document)
+}
+
+// Version 1 files predate the schema_version field; their layout is otherwise unchanged.
+fn v1_to_v2(document: &mut Value) {
+    if let Some(object) = document.as_object_mut() {
+        object.insert("schema_version".to_string(), Value::from(2));
+    }
+}
+
+// Version 3 gives payroll records ids, numbering each employee's records for a period in the order
+// they were stored.
+fn v2_to_v3(document: &mut Value) {
+    let Some(object) = document.as_object_mut() else {
+        return;
+    };
+    if let Some(records) = object.get_mut("payroll_records").and_then(Value::as_array_mut) {
+        let mut runs: HashMap<String, usize> = HashMap::new();
+        for record in records.iter_mut().filter_map(Value::as_object_mut) {
+            let employee_id = record
+                .get("employee")
+                .and_then(Value::as_object)
+                .and_then(|employee| employee.values().next())
+                .and_then(|employee| employee.get("employee_id"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let pay_period = record.get("pay_period").and_then(Value::as_str).unwrap_or_default().to_string();
+            // Keyed by the id of the first run, which already reads the period however it is written.
+            let run = runs.entry(PayrollData::derive_id(&employee_id, &pay_period, 0)).or_insert(0);
+            if !record.contains_key("record_id") {
+                record.insert(
+                    "record_id".to_string(),
+                    Value::from(PayrollData::derive_id(&employee_id, &pay_period, *run)),
+                );
+            }
+            *run += 1;
+        }
+    }
+    object.insert("schema_version".to_string(), Value::from(3));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::{EmployeeData, Payroll};
+
+    fn employee(employee_id: &str) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            employee_id.to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            10_000_000.0,
+        ))
+    }
+
+    /// A document as version 1 wrote it: no schema version and no record ids. E1 was processed
+    /// twice for the period, as version 1 allowed.
+    fn legacy_document() -> Value {
+        let mut payroll = Payroll::new();
+        for employee_id in ["E1", "E2"] {
+            payroll.add_employee(employee(employee_id));
+            payroll.process_payroll(employee(employee_id), "2024-09".to_string()).unwrap();
+        }
+        let mut document = serde_json::to_value(&payroll).unwrap();
+        let object = document.as_object_mut().unwrap();
+        object.remove("schema_version");
+        let records = object.get_mut("payroll_records").unwrap().as_array_mut().unwrap();
+        records.insert(1, records[0].clone());
+        for record in records.iter_mut() {
+            record.as_object_mut().unwrap().remove("record_id");
+        }
+        document
+    }
+
+    #[test]
+    fn a_document_without_a_version_is_version_one() {
+        assert_eq!(schema_version(&serde_json::json!({ "employees": [] })), LEGACY_SCHEMA_VERSION);
+        assert_eq!(schema_version(&serde_json::json!({ "schema_version": 2 })), 2);
+    }
+
+    #[test]
+    fn versions_outside_the_known_range_are_refused() {
+        for version in [0, CURRENT_SCHEMA_VERSION + 1] {
+            let document = serde_json::json!({ "schema_version": version });
+            assert!(matches!(migrate(document), Err(StorageError::UnsupportedSchemaVersion(v)) if v == version));
+        }
+        assert!(check_schema_version(CURRENT_SCHEMA_VERSION).is_ok());
+    }
+
+    #[test]
+    fn a_legacy_document_is_numbered_per_employee_and_period() {
+        let migrated = migrate(legacy_document()).unwrap();
+        assert_eq!(schema_version(&migrated), CURRENT_SCHEMA_VERSION);
+
+        let payroll: Payroll = serde_json::from_value(migrated).unwrap();
+        let ids: Vec<&str> = payroll.get_payroll_records().iter().map(|record| record.record_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                PayrollData::derive_id("E1", "2024-09", 0),
+                PayrollData::derive_id("E1", "2024-09", 1),
+                PayrollData::derive_id("E2", "2024-09", 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn migrating_a_current_document_changes_nothing() {
+        let current = migrate(legacy_document()).unwrap();
+        assert_eq!(migrate(current.clone()).unwrap(), current);
+
+        let mut partial = legacy_document();
+        partial["payroll_records"][0]["record_id"] = Value::from("kept");
+        let migrated = migrate(partial).unwrap();
+        assert_eq!(migrated["payroll_records"][0]["record_id"], "kept");
+        assert_eq!(migrated["payroll_records"][1]["record_id"], PayrollData::derive_id("E1", "2024-09", 1));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// The deduction line offsetting benefits in kind, which the employee receives rather than is paid.
+pub const NATURA_PROVIDED: &str = "Benefit in Kind Provided";
+/// The deduction line withholding PPh 21 on the taxable part of benefits in kind.
+pub const NATURA_TAX: &str = "PPh 21 on Benefit in Kind";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NaturaKind {
+    /// A company car or motorcycle for the employee's own use.
+    Vehicle,
+    /// A house or apartment provided to one employee.
+    Housing,
+    Other,
+}
+
+impl NaturaKind {
+    pub const ALL: [NaturaKind; 3] = [NaturaKind::Vehicle, NaturaKind::Housing, NaturaKind::Other];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NaturaKind::Vehicle => "vehicle",
+            NaturaKind::Housing => "housing",
+            NaturaKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for NaturaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NaturaKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "vehicle" | "car" => Ok(NaturaKind::Vehicle),
+            "housing" | "house" => Ok(NaturaKind::Housing),
+            "other" => Ok(NaturaKind::Other),
+            _ => Err(format!("Unknown benefit in kind '{}' (expected vehicle, housing or other)", s)),
+        }
+    }
+}
+
+/// How one kind of benefit is valued and how much of it is exempt. An item recorded at its cost to
+/// the company is worth `annual_rate` of that cost a year; up to `non_taxable` a month of an
+/// employee's items of the kind is not income.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NaturaRule {
+    pub annual_rate: f64,
+    pub non_taxable: f64,
+}
+
+impl NaturaRule {
+    pub fn monthly_value(&self, cost: f64) -> f64 {
+        cost * self.annual_rate / 12.0
+    }
+
+    /// The part of a month's `value` of this kind that is taxable.
+    pub fn taxable(&self, value: f64) -> f64 {
+        (value - self.non_taxable).max(0.0)
+    }
+}
+
+/// Valuation and exemption rules by kind of benefit (`[natura]` in the configuration). The defaults
+/// value items by the tax depreciation rate of their asset group and exempt housing up to
+/// Rp2,000,000 a month (PMK 66/2023).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NaturaRules {
+    pub vehicle: NaturaRule,
+    pub housing: NaturaRule,
+    pub other: NaturaRule,
+}
+
+impl Default for NaturaRules {
+    fn default() -> Self {
+        Self {
+            vehicle: NaturaRule {
+                annual_rate: 0.125,
+                non_taxable: 0.0,
+            },
+            housing: NaturaRule {
+                annual_rate: 0.05,
+                non_taxable: 2_000_000.0,
+            },
+            other: NaturaRule {
+                annual_rate: 0.25,
+                non_taxable: 0.0,
+            },
+        }
+    }
+}
+
+impl NaturaRules {
+    pub fn rule(&self, kind: NaturaKind) -> NaturaRule {
+        match kind {
+            NaturaKind::Vehicle => self.vehicle,
+            NaturaKind::Housing => self.housing,
+            NaturaKind::Other => self.other,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for kind in NaturaKind::ALL {
+            let rule = self.rule(kind);
+            if !rule.annual_rate.is_finite() || rule.annual_rate < 0.0 {
+                return Err(format!("natura.{}.annual_rate must not be negative", kind));
+            }
+            if !rule.non_taxable.is_finite() || rule.non_taxable < 0.0 {
+                return Err(format!("natura.{}.non_taxable must not be negative", kind));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a benefit is valued when it is recorded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NaturaValuation {
+    /// What the benefit is worth a month, e.g. the rent or lease the company pays.
+    Monthly(f64),
+    /// What the company paid for the item, valued with the kind's annual rate.
+    Cost(f64),
+}
+
+/// A benefit in kind provided to an employee from `start` until `end` (inclusive), or until ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NaturaItem {
+    pub id: u32,
+    pub employee_id: String,
+    pub kind: NaturaKind,
+    pub description: String,
+    pub monthly_value: f64,
+    /// The cost the monthly value was derived from, when recorded by cost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    pub start: PayPeriod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<PayPeriod>,
+    pub recorded: DateTime<Utc>,
+}
+
+impl NaturaItem {
+    pub fn covers(&self, period: PayPeriod) -> bool {
+        self.start <= period && self.end.is_none_or(|end| period <= end)
+    }
+}
+
+/// Every benefit in kind provided, with the rules that value and exempt them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NaturaItems {
+    items: Vec<NaturaItem>,
+    #[serde(skip)]
+    rules: NaturaRules,
+}
+
+impl NaturaItems {
+    pub fn rules(&self) -> &NaturaRules {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: NaturaRules) {
+        self.rules = rules;
+    }
+
+    /// Stores a benefit provided to an employee from `start` on.
+    pub(crate) fn provide(
+        &mut self,
+        employee_id: &str,
+        kind: NaturaKind,
+        description: &str,
+        valuation: NaturaValuation,
+        start: PayPeriod,
+    ) -> Result<&NaturaItem, PayrollError> {
+        if description.trim().is_empty() {
+            return Err(PayrollError::InvalidNatura("description is required".to_string()));
+        }
+        let (monthly_value, cost) = match valuation {
+            NaturaValuation::Monthly(value) => (value, None),
+            NaturaValuation::Cost(cost) => (self.rules.rule(kind).monthly_value(cost), Some(cost)),
+        };
+        if !monthly_value.is_finite() || monthly_value <= 0.0 || cost.is_some_and(|cost| !cost.is_finite()) {
+            return Err(PayrollError::InvalidNatura("the value must be positive".to_string()));
+        }
+        let id = self.items.iter().map(|item| item.id).max().unwrap_or(0) + 1;
+        self.items.push(NaturaItem {
+            id,
+            employee_id: employee_id.to_string(),
+            kind,
+            description: description.trim().to_string(),
+            monthly_value,
+            cost,
+            start,
+            end: None,
+            recorded: Utc::now(),
+        });
+        Ok(self.items.last().expect("benefit was just pushed"))
+    }
+
+    /// Stops a benefit after `last`.
+    pub(crate) fn end(&mut self, id: u32, last: PayPeriod) -> Result<&NaturaItem, PayrollError> {
+        let item = self
+            .items
+            .iter_mut()
+            .find(|item| item.id == id)
+            .ok_or(PayrollError::NaturaNotFound(id))?;
+        if last < item.start || item.end.is_some_and(|end| end <= last) {
+            return Err(PayrollError::InvalidNatura(format!("benefit #{} is not provided after {}", id, last)));
+        }
+        item.end = Some(last);
+        Ok(item)
+    }
+
+    /// Benefits of one employee, or of everyone, by employee and start.
+    pub fn of(&self, employee_id: Option<&str>) -> Vec<&NaturaItem> {
+        let mut items: Vec<&NaturaItem> = self
+            .items
+            .iter()
+            .filter(|item| employee_id.is_none_or(|id| item.employee_id == id))
+            .collect();
+        items.sort_by(|a, b| (&a.employee_id, a.start, a.id).cmp(&(&b.employee_id, b.start, b.id)));
+        items
+    }
+
+    /// Adds the benefits covering the record's period as income. They are received rather than
+    /// paid, so only the PPh 21 on the part above each kind's exemption comes out of net pay.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee_id = record.employee.as_employee().employee_id().to_string();
+        let items: Vec<&NaturaItem> = self
+            .items
+            .iter()
+            .filter(|item| item.employee_id == employee_id && item.covers(period))
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+        let value_of = |kind: NaturaKind| -> f64 {
+            items.iter().filter(|item| item.kind == kind).map(|item| item.monthly_value).sum()
+        };
+        let income: f64 = items.iter().map(|item| item.monthly_value).sum();
+        let taxable = NaturaKind::ALL
+            .into_iter()
+            .map(|kind| self.rules.rule(kind).taxable(value_of(kind)))
+            .sum();
+        for item in &items {
+            record.extra_earnings.push(PayrollComponent {
+                name: format!("Benefit in Kind ({})", item.description),
+                amount: item.monthly_value,
+            });
+        }
+        let tax = record.add_taxable_income(taxable);
+        record.gross_salary += income;
+        record.deductions += income + tax;
+        record.extra_deductions.push(PayrollComponent {
+            name: NATURA_PROVIDED.to_string(),
+            amount: income,
+        });
+        if tax > 0.0 {
+            record.extra_deductions.push(PayrollComponent {
+                name: NATURA_TAX.to_string(),
+                amount: tax,
+            });
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+
+    /// Moves the employee's benefits to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.items.iter_mut().map(|item| renaming.id(&mut item.employee_id)).sum()
+    }
+}
+
+impl Deref for NaturaItems {
+    type Target = [NaturaItem];
+
+    fn deref(&self) -> &[NaturaItem] {
+        &self.items
+    }
+}
@@ -0,0 +1,164 @@
+use crate::error::PayrollError;
+use crate::payroll::{Payroll, RunStatus};
+use crate::report::PayrollReport;
+use serde::{Deserialize, Serialize};
+
+const NET_CHANGE_THRESHOLD: f64 = 0.25;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub run_id: u32,
+    pub pay_period: String,
+    pub status: RunStatus,
+    pub headcount: usize,
+    pub total_gross: f64,
+    pub total_net: f64,
+    pub anomalies: Vec<String>,
+}
+
+impl RunSummary {
+    pub fn for_run(payroll: &Payroll, run_id: u32) -> Result<Self, PayrollError> {
+        let run = payroll.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        let records = payroll.run_records(run);
+
+        let mut anomalies: Vec<String> = records
+            .iter()
+            .filter(|record| record.net_salary <= 0.0)
+            .map(|record| {
+                format!(
+                    "{}: net pay is Rp {:.2}",
+                    record.employee.as_employee().employee_id(),
+                    record.net_salary
+                )
+            })
+            .collect();
+        if let Some(comparison) = PayrollReport::compare_with_previous(payroll.get_payroll_records(), &run.pay_period) {
+            for diff in &comparison.employees {
+                let (previous, current) = match (&diff.previous, &diff.current) {
+                    (Some(previous), Some(current)) => (previous, current),
+                    _ => continue,
+                };
+                if previous.net_salary > 0.0
+                    && (diff.net_change() / previous.net_salary).abs() > NET_CHANGE_THRESHOLD
+                {
+                    anomalies.push(format!(
+                        "{}: net pay changed {:+.0}% (Rp {:.2} -> Rp {:.2})",
+                        diff.employee_id,
+                        diff.net_change() / previous.net_salary * 100.0,
+                        previous.net_salary,
+                        current.net_salary
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            run_id,
+            pay_period: run.pay_period.clone(),
+            status: run.status,
+            headcount: records.len(),
+            total_gross: records.iter().map(|record| record.gross_salary).sum(),
+            total_net: records.iter().map(|record| record.net_salary).sum(),
+            anomalies,
+        })
+    }
+
+    pub fn message(&self) -> String {
+        let state = match self.status {
+            RunStatus::Draft => "is ready for approval",
+            RunStatus::Approved => "completed",
+        };
+        let mut message = format!(
+            "Payroll run #{} for {} {}\nHeadcount: {}\nTotal gross: Rp {:.2}\nTotal net: Rp {:.2}",
+            self.run_id, self.pay_period, state, self.headcount, self.total_gross, self.total_net
+        );
+        if self.anomalies.is_empty() {
+            message.push_str("\nNo anomalies detected");
+        } else {
+            message.push_str(&format!("\nAnomalies ({}):", self.anomalies.len()));
+            for anomaly in &self.anomalies {
+                message.push_str(&format!("\n- {}", anomaly));
+            }
+        }
+        message
+    }
+}
+
+pub trait Notifier: Send + Sync {
+    fn notify(&self, summary: &RunSummary) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NotifierConfig {
+    Slack { webhook_url: String },
+    Telegram { bot_token: String, chat_id: String },
+}
+
+impl NotifierConfig {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotifierConfig::Slack { .. } => "Slack",
+            NotifierConfig::Telegram { .. } => "Telegram",
+        }
+    }
+
+    #[cfg(feature = "notifications")]
+    pub fn build(&self) -> Box<dyn Notifier> {
+        match self {
+            NotifierConfig::Slack { webhook_url } => Box::new(SlackNotifier {
+                webhook_url: webhook_url.clone(),
+            }),
+            NotifierConfig::Telegram { bot_token, chat_id } => Box::new(TelegramNotifier {
+                bot_token: bot_token.clone(),
+                chat_id: chat_id.clone(),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "notifications")]
+#[derive(Debug, Clone)]
+pub struct SlackNotifier {
+    pub webhook_url: String,
+}
+
+#[cfg(feature = "notifications")]
+impl Notifier for SlackNotifier {
+    fn notify(&self, summary: &RunSummary) -> Result<(), String> {
+        let body = serde_json::json!({ "text": summary.message() });
+        ureq::post(&self.webhook_url)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "notifications")]
+#[derive(Debug, Clone)]
+pub struct TelegramNotifier {
+    pub bot_token: String,
+    pub chat_id: String,
+}
+
+#[cfg(feature = "notifications")]
+impl Notifier for TelegramNotifier {
+    fn notify(&self, summary: &RunSummary) -> Result<(), String> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let body = serde_json::json!({ "chat_id": self.chat_id, "text": summary.message() });
+        ureq::post(&url)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(feature = "notifications")]
+pub fn notify_all(configs: &[NotifierConfig], summary: &RunSummary) -> Vec<(&'static str, Result<(), String>)> {
+    configs
+        .iter()
+        .map(|config| (config.name(), config.build().notify(summary)))
+        .collect()
+}
@@ -0,0 +1,196 @@
+use crate::employee::EmployeeProfile;
+use crate::error::PayrollError;
+use crate::privacy::Renaming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingTask {
+    BpjsRegistration,
+    BankAccount,
+    NpwpCollection,
+    ContractSigned,
+}
+
+impl OnboardingTask {
+    pub const ALL: [OnboardingTask; 4] = [
+        OnboardingTask::BpjsRegistration,
+        OnboardingTask::BankAccount,
+        OnboardingTask::NpwpCollection,
+        OnboardingTask::ContractSigned,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnboardingTask::BpjsRegistration => "bpjs",
+            OnboardingTask::BankAccount => "bank",
+            OnboardingTask::NpwpCollection => "npwp",
+            OnboardingTask::ContractSigned => "contract",
+        }
+    }
+
+    /// Whether the task blocks payroll runs. A missing NPWP only raises the withholding rate, so
+    /// collecting it is not mandatory.
+    pub fn mandatory(&self) -> bool {
+        !matches!(self, OnboardingTask::NpwpCollection)
+    }
+
+    /// Whether the profile already holds what the task collects.
+    fn satisfied_by(&self, profile: &EmployeeProfile) -> bool {
+        match self {
+            OnboardingTask::BpjsRegistration => profile.bpjs_tk_number.is_some(),
+            OnboardingTask::BankAccount => !profile.salary_split.is_empty(),
+            OnboardingTask::NpwpCollection => profile.npwp.is_some(),
+            OnboardingTask::ContractSigned => false,
+        }
+    }
+}
+
+impl fmt::Display for OnboardingTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OnboardingTask::BpjsRegistration => "BPJS registration",
+            OnboardingTask::BankAccount => "Bank account",
+            OnboardingTask::NpwpCollection => "NPWP collection",
+            OnboardingTask::ContractSigned => "Contract signed",
+        })
+    }
+}
+
+impl FromStr for OnboardingTask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|task| task.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("Unknown onboarding task '{}' (expected bpjs, bank, npwp or contract)", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub task: OnboardingTask,
+    pub completed: Option<DateTime<Utc>>,
+}
+
+/// The onboarding tasks of a new employee. Until every mandatory task is done the employee's
+/// records are held back from payroll runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnboardingChecklist {
+    pub employee_id: String,
+    pub created: DateTime<Utc>,
+    pub items: Vec<ChecklistItem>,
+}
+
+impl OnboardingChecklist {
+    pub fn new(employee_id: &str, profile: &EmployeeProfile) -> Self {
+        let mut checklist = Self {
+            employee_id: employee_id.to_string(),
+            created: Utc::now(),
+            items: OnboardingTask::ALL
+                .into_iter()
+                .map(|task| ChecklistItem { task, completed: None })
+                .collect(),
+        };
+        checklist.sync(profile);
+        checklist
+    }
+
+    /// Ticks the tasks the profile now satisfies, e.g. once a BPJS number or bank account is entered.
+    pub fn sync(&mut self, profile: &EmployeeProfile) {
+        let now = Utc::now();
+        for item in self.items.iter_mut().filter(|item| item.completed.is_none()) {
+            if item.task.satisfied_by(profile) {
+                item.completed = Some(now);
+            }
+        }
+    }
+
+    /// Marks `task` done. Returns false when it already was.
+    pub fn complete(&mut self, task: OnboardingTask) -> bool {
+        match self.items.iter_mut().find(|item| item.task == task) {
+            Some(item) if item.completed.is_some() => false,
+            Some(item) => {
+                item.completed = Some(Utc::now());
+                true
+            }
+            None => {
+                self.items.push(ChecklistItem {
+                    task,
+                    completed: Some(Utc::now()),
+                });
+                true
+            }
+        }
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = OnboardingTask> + '_ {
+        self.items.iter().filter(|item| item.completed.is_none()).map(|item| item.task)
+    }
+
+    pub fn pending_mandatory(&self) -> impl Iterator<Item = OnboardingTask> + '_ {
+        self.pending().filter(OnboardingTask::mandatory)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending_mandatory().next().is_none()
+    }
+}
+
+/// The onboarding checklist of every employee registered since onboarding was tracked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OnboardingChecklists {
+    checklists: Vec<OnboardingChecklist>,
+}
+
+impl OnboardingChecklists {
+    pub fn get(&self, employee_id: &str) -> Option<&OnboardingChecklist> {
+        self.checklists.iter().find(|checklist| checklist.employee_id == employee_id)
+    }
+
+    /// Ticks what an updated profile now provides on the employee's checklist, or starts one for an
+    /// employee who is not `registered` yet.
+    pub(crate) fn sync(&mut self, employee_id: &str, profile: &EmployeeProfile, registered: bool) {
+        match self.checklists.iter_mut().find(|checklist| checklist.employee_id == employee_id) {
+            Some(checklist) => checklist.sync(profile),
+            None if !registered => self.checklists.push(OnboardingChecklist::new(employee_id, profile)),
+            None => {}
+        }
+    }
+
+    /// Marks a task done. Returns false when it already was.
+    pub(crate) fn complete(&mut self, employee_id: &str, task: OnboardingTask) -> Result<bool, PayrollError> {
+        self.checklists
+            .iter_mut()
+            .find(|checklist| checklist.employee_id == employee_id)
+            .map(|checklist| checklist.complete(task))
+            .ok_or_else(|| PayrollError::NoOnboardingChecklist(employee_id.to_string()))
+    }
+
+    /// Checklists with tasks still open, oldest first.
+    pub fn pending(&self) -> Vec<&OnboardingChecklist> {
+        let mut pending: Vec<&OnboardingChecklist> =
+            self.checklists.iter().filter(|checklist| checklist.pending().next().is_some()).collect();
+        pending.sort_by_key(|checklist| checklist.created);
+        pending
+    }
+
+    /// Moves the employee's checklist to their pseudonym. Returns 1 when they had one.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.checklists.iter_mut().map(|checklist| renaming.id(&mut checklist.employee_id)).sum()
+    }
+}
+
+impl Deref for OnboardingChecklists {
+    type Target = [OnboardingChecklist];
+
+    fn deref(&self) -> &[OnboardingChecklist] {
+        &self.checklists
+    }
+}
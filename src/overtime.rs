@@ -0,0 +1,271 @@
+use crate::compensation::ChangeRequestStatus;
+use crate::error::PayrollError;
+use crate::payroll::EmployeeData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// What happens to overtime beyond the monthly cap or the approved hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OvertimeExcess {
+    /// Leave the excess hours out of pay.
+    Exclude,
+    /// Pay them, but flag the record for review.
+    #[default]
+    Flag,
+}
+
+impl OvertimeExcess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OvertimeExcess::Exclude => "exclude",
+            OvertimeExcess::Flag => "flag",
+        }
+    }
+}
+
+impl fmt::Display for OvertimeExcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OvertimeExcess {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "exclude" => Ok(OvertimeExcess::Exclude),
+            "flag" => Ok(OvertimeExcess::Flag),
+            _ => Err(format!("Unknown overtime excess policy '{}' (expected exclude or flag)", s)),
+        }
+    }
+}
+
+/// Limits on the overtime paid to full-time employees (`[overtime]` in the configuration).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OvertimePolicy {
+    /// Most overtime hours paid in a month, if limited.
+    pub monthly_cap: Option<f64>,
+    /// Only overtime covered by approved requests is within policy.
+    pub require_approval: bool,
+    pub excess: OvertimeExcess,
+}
+
+impl OvertimePolicy {
+    pub fn is_unrestricted(&self) -> bool {
+        self.monthly_cap.is_none() && !self.require_approval
+    }
+
+    /// Splits `worked` overtime hours into the hours within policy and the excess.
+    pub fn check(&self, worked: f64, approved: f64) -> OvertimeCheck {
+        let mut allowed = worked;
+        if let Some(cap) = self.monthly_cap {
+            allowed = allowed.min(cap);
+        }
+        if self.require_approval {
+            allowed = allowed.min(approved);
+        }
+        let excess_hours = worked - allowed;
+        let excluded = excess_hours > 0.0 && self.excess == OvertimeExcess::Exclude;
+        OvertimeCheck {
+            worked_hours: worked,
+            approved_hours: self.require_approval.then_some(approved),
+            cap: self.monthly_cap,
+            paid_hours: if excluded { allowed } else { worked },
+            excess_hours,
+            excluded,
+        }
+    }
+}
+
+/// How a record's overtime measured up to the policy when it was processed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OvertimeCheck {
+    pub worked_hours: f64,
+    /// Hours covered by approved requests, when approval is required.
+    pub approved_hours: Option<f64>,
+    pub cap: Option<f64>,
+    pub paid_hours: f64,
+    /// Hours beyond the cap or the approved hours.
+    pub excess_hours: f64,
+    /// Whether the excess was left out of pay rather than paid and flagged.
+    pub excluded: bool,
+}
+
+impl OvertimeCheck {
+    pub fn is_flagged(&self) -> bool {
+        self.excess_hours > 0.0 && !self.excluded
+    }
+}
+
+/// A request to work overtime in a pay period, decided by a manager above the employee in the
+/// reporting chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OvertimeRequest {
+    pub id: u32,
+    pub employee_id: String,
+    pub period: PayPeriod,
+    pub hours: f64,
+    pub reason: String,
+    pub requested: DateTime<Utc>,
+    pub status: ChangeRequestStatus,
+    pub decided_by: Option<String>,
+    pub decided: Option<DateTime<Utc>>,
+}
+
+impl OvertimeRequest {
+    pub fn is_pending(&self) -> bool {
+        self.status == ChangeRequestStatus::Pending
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.status == ChangeRequestStatus::Approved
+    }
+}
+
+/// Every overtime request, with the policy overtime is checked against when a period is processed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OvertimeRequests {
+    requests: Vec<OvertimeRequest>,
+    #[serde(skip)]
+    policy: OvertimePolicy,
+}
+
+impl OvertimeRequests {
+    pub fn policy(&self) -> &OvertimePolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: OvertimePolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get(&self, id: u32) -> Option<&OvertimeRequest> {
+        self.requests.iter().find(|request| request.id == id)
+    }
+
+    /// The request with `id`, as long as it still waits for a decision.
+    pub fn undecided(&self, id: u32) -> Result<&OvertimeRequest, PayrollError> {
+        let request = self.get(id).ok_or(PayrollError::OvertimeRequestNotFound(id))?;
+        match request.is_pending() {
+            true => Ok(request),
+            false => Err(PayrollError::OvertimeRequestDecided(id)),
+        }
+    }
+
+    /// Requests waiting for a decision, oldest first.
+    pub fn pending(&self) -> Vec<&OvertimeRequest> {
+        self.requests.iter().filter(|request| request.is_pending()).collect()
+    }
+
+    /// Overtime hours approved for an employee in a pay period.
+    pub fn approved_hours(&self, employee_id: &str, period: &PayPeriod) -> f64 {
+        self.requests
+            .iter()
+            .filter(|request| request.employee_id == employee_id && request.period == *period && request.is_approved())
+            .fold(0.0, |sum, request| sum + request.hours)
+    }
+
+    pub(crate) fn request(
+        &mut self,
+        employee_id: &str,
+        period: PayPeriod,
+        hours: f64,
+        reason: &str,
+    ) -> Result<&OvertimeRequest, PayrollError> {
+        if !hours.is_finite() || hours <= 0.0 {
+            return Err(PayrollError::InvalidOvertimeRequest("hours must be positive".to_string()));
+        }
+        let id = self.requests.iter().map(|request| request.id).max().unwrap_or(0) + 1;
+        self.requests.push(OvertimeRequest {
+            id,
+            employee_id: employee_id.to_string(),
+            period,
+            hours,
+            reason: reason.trim().to_string(),
+            requested: Utc::now(),
+            status: ChangeRequestStatus::Pending,
+            decided_by: None,
+            decided: None,
+        });
+        Ok(self.requests.last().expect("request was just pushed"))
+    }
+
+    /// Approves or rejects a pending request on behalf of `approver_id`.
+    pub(crate) fn decide(
+        &mut self,
+        id: u32,
+        approver_id: &str,
+        status: ChangeRequestStatus,
+    ) -> Result<&OvertimeRequest, PayrollError> {
+        self.undecided(id)?;
+        let request = self
+            .requests
+            .iter_mut()
+            .find(|request| request.id == id)
+            .expect("request was just found");
+        request.status = status;
+        request.decided_by = Some(approver_id.to_string());
+        request.decided = Some(Utc::now());
+        Ok(request)
+    }
+
+    /// Checks a full-time employee's overtime beyond `standard_hours` against the policy, taking
+    /// excluded hours off the hours paid.
+    pub fn apply(
+        &self,
+        mut employee: EmployeeData,
+        pay_period: &str,
+        standard_hours: f64,
+    ) -> (EmployeeData, Option<OvertimeCheck>) {
+        if self.policy.is_unrestricted() {
+            return (employee, None);
+        }
+        let EmployeeData::Fulltime(emp) = &mut employee else {
+            return (employee, None);
+        };
+        if emp.work_hour <= standard_hours {
+            return (employee, None);
+        }
+        let approved = pay_period
+            .parse::<PayPeriod>()
+            .map(|period| self.approved_hours(&emp.employee_id, &period))
+            .unwrap_or(0.0);
+        let check = self.policy.check(emp.work_hour - standard_hours, approved);
+        emp.work_hour = standard_hours + check.paid_hours;
+        (employee, Some(check))
+    }
+
+    /// Drops the requests for periods before `cutoff`.
+    pub(crate) fn purge_before(&mut self, cutoff: PayPeriod) {
+        self.requests.retain(|request| request.period >= cutoff);
+    }
+
+    /// Moves the employee's requests, and the ones they decided, to their pseudonym. Returns how many
+    /// were theirs.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        let mut renamed = 0;
+        for request in &mut self.requests {
+            renamed += renaming.id(&mut request.employee_id);
+            renaming.option(&mut request.decided_by);
+        }
+        renamed
+    }
+}
+
+impl Deref for OvertimeRequests {
+    type Target = [OvertimeRequest];
+
+    fn deref(&self) -> &[OvertimeRequest] {
+        &self.requests
+    }
+}
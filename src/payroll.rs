@@ -1,8 +1,63 @@
-use crate::employee::{Employee, FulltimeEmployee, ContractEmployee};
-use chrono::{DateTime, Utc};
+use crate::accounting::{journal_entries, AccountCodes, JournalLine};
+use crate::attendance::{AttendanceEntry, AttendanceLog, AttendanceSummary};
+use crate::auth::{Principal, Scope};
+use crate::batch::RejectedRow;
+use crate::bpjs::BpjsRates;
+use crate::calc::{self, CalculationSettings};
+use crate::compensation::{ChangeRequestStatus, SalaryChangeRequest, SalaryChanges, SalaryHistory, SalaryHistoryEntry};
+use crate::config::Config;
+use crate::context::CalculationContext;
+use crate::credentials::{Credentials, EmployeeCredential};
+use crate::delivery::{deliver, DeliveryStatus, PayslipDelivery, PayslipOptions, PayslipSender};
+use crate::disbursement::{BankTransferBatch, BatchStatus, Disbursement, DisbursementProvider, TransferConfirmation};
+use crate::employee::{
+    ContractEmployee, DeductionBreakdown, EarningBreakdown, Employee, EmployeeProfile, EmployeeStatus,
+    FulltimeEmployee,
+};
+use crate::equity::{EquityGrant, EquityGrants, EquityKind, VestingEvent, VestingSchedule};
+use crate::error::PayrollError;
+use crate::expense::{ExpenseClaim, ExpenseClaims};
+use crate::insurance::{InsuranceEnrollment, InsuranceEnrollments, InsurancePlan, InsurancePremium};
+use crate::export::register::PayrollRegister;
+use crate::hris::{EmployeeSource, SourceEmployee, SyncReport};
+use crate::invariants::{self, Violation};
+use crate::ledger::LedgerHead;
+use crate::migration::CURRENT_SCHEMA_VERSION;
+use crate::natura::{NaturaItem, NaturaItems, NaturaKind, NaturaValuation};
+use crate::onboarding::{OnboardingChecklist, OnboardingChecklists, OnboardingTask};
+use crate::overtime::{OvertimeCheck, OvertimeRequest, OvertimeRequests};
+use crate::payslip::to_hex;
+use crate::period::PayPeriod;
+use crate::privacy::{
+    self, CredentialSummary, CycleReview, Pseudonymization, Renaming, RunDelivery, RunTransfer, SubjectData,
+};
+use crate::query::PayrollQuery;
+use crate::read_model::DepartmentMonth;
+use crate::records::{PayrollRecords, RecordKey};
+use crate::remittance::RemittanceSummary;
+use crate::report::{MonthlyCost, PayrollReport, PeriodComparison, PeriodReport};
+use crate::retention::{self, PeriodSummaries, PeriodSummary, PurgeReport};
+use crate::review::{BonusLine, BonusPolicy, BonusRun, ReviewCycle, ReviewCycles};
+use crate::rule_history::{self, EffectiveRules};
+use crate::rules::{DeductionRule, EarningRule, PayrollComponent, RuleSet};
+use crate::self_service::{SelfService, YearToDate};
+use crate::severance::{Severance, TerminationOutcome, TerminationRecord, TerminationRow, Terminations};
+use crate::search::{match_employee_id, search, EmployeeIdMatch, SearchResults};
+use crate::statistics::PayrollStatistics;
+use crate::tax::{self, NonResidency, TaxRegime};
+use crate::timesheet::{Timesheet, TimesheetStatus, Timesheets};
+use crate::travel::{DestinationTier, TravelLog, TravelRecord};
+use crate::verify::{self, Verification};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::AtomicU64;
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum EmployeeData {
     Fulltime(FulltimeEmployee),
     Contract(ContractEmployee),
@@ -15,99 +70,2474 @@ impl EmployeeData {
             EmployeeData::Contract(emp) => emp,
         }
     }
+
+    pub fn profile_mut(&mut self) -> &mut EmployeeProfile {
+        match self {
+            EmployeeData::Fulltime(emp) => &mut emp.profile,
+            EmployeeData::Contract(emp) => &mut emp.profile,
+        }
+    }
+
+    /// The monthly base salary of a full-time employee, or the hourly rate of a contract employee.
+    pub fn base_rate(&self) -> f64 {
+        match self {
+            EmployeeData::Fulltime(emp) => emp.base_salary,
+            EmployeeData::Contract(emp) => emp.hourly_rate,
+        }
+    }
+
+    pub fn set_base_rate(&mut self, rate: f64) {
+        match self {
+            EmployeeData::Fulltime(emp) => emp.base_salary = rate,
+            EmployeeData::Contract(emp) => emp.hourly_rate = rate,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PayrollData {
+    /// Stable id of the record within a payroll (see [`PayrollData::derive_id`]); empty for records
+    /// calculated outside one.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub record_id: String,
     pub employee: EmployeeData,
     pub pay_period: String,
     pub processed_date: DateTime<Utc>,
     pub gross_salary: f64,
     pub deductions: f64,
     pub net_salary: f64,
+    #[serde(default)]
+    pub voided: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<CalculationContext>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_earnings: Vec<PayrollComponent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_deductions: Vec<PayrollComponent>,
+    /// Overtime checked against the overtime policy, when one restricted it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtime: Option<OvertimeCheck>,
+    /// Private insurance premiums, with the company's share of each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub insurance: Vec<InsurancePremium>,
+    /// Income PPh 21 was withheld on so far on top of the calculated pay, while extras are added.
+    #[serde(skip)]
+    taxable_extras: f64,
 }
 
 impl PayrollData {
     pub fn new(employee: EmployeeData, pay_period: String) -> Self {
+        Self::with_settings(employee, pay_period, &CalculationSettings::DEFAULT)
+    }
+
+    /// Calculates a record for `pay_period` with `settings` as given; [`Payroll::settings_for`] picks
+    /// the ones in force for the period and employee.
+    pub fn with_settings(employee: EmployeeData, pay_period: String, settings: &CalculationSettings) -> Self {
         let emp_ref = employee.as_employee();
-        let gross_salary = emp_ref.calculate_gross();
-        let deductions = emp_ref.calculate_deduction();
-        let net_salary = emp_ref.calculate_net();
+        let gross_salary = emp_ref.gross_with(settings);
+        let deductions = emp_ref.deduction_breakdown_with(settings).total();
+        let net_salary = gross_salary - deductions;
+        let context = CalculationContext::with_settings(&employee, settings);
+        trace_event!(
+            debug,
+            employee_id = emp_ref.employee_id(),
+            pay_period = %pay_period,
+            gross = gross_salary,
+            deductions,
+            net = net_salary,
+            "payroll calculated"
+        );
 
         Self {
+            record_id: String::new(),
             employee,
             pay_period,
             processed_date: Utc::now(),
             gross_salary,
             deductions,
             net_salary,
+            voided: false,
+            context: Some(context),
+            extra_earnings: Vec::new(),
+            extra_deductions: Vec::new(),
+            overtime: None,
+            insurance: Vec::new(),
+            taxable_extras: 0.0,
+        }
+    }
+
+    /// The id records got before ids were numbered: a hash of the employee, the period and the
+    /// `run`th record for them (counting from 0, voided ones included). Only migrating old data files
+    /// still gives it out; see [`sequence_id`](Self::sequence_id).
+    pub fn derive_id(employee_id: &str, pay_period: &str, run: usize) -> String {
+        to_hex(&Sha256::digest(format!("{}|{}|{}", employee_id, Self::period_code(pay_period), run))[..8])
+    }
+
+    /// The id of the `sequence`th record a payroll gave an id to, for an employee and period. The
+    /// sequence only ever grows, so no two records get the same id, even once one of them is purged.
+    pub fn sequence_id(employee_id: &str, pay_period: &str, sequence: u64) -> String {
+        to_hex(&Sha256::digest(format!("{}|{}|#{}", employee_id, Self::period_code(pay_period), sequence))[..8])
+    }
+
+    fn period_code(pay_period: &str) -> String {
+        match pay_period.parse::<PayPeriod>() {
+            Ok(period) => format!("{}-{:02}", period.year, period.month),
+            Err(_) => pay_period.trim().to_string(),
+        }
+    }
+
+    pub fn with_rules(employee: EmployeeData, pay_period: String, rules: &RuleSet) -> Self {
+        Self::calculate(employee, pay_period, rules, &CalculationSettings::DEFAULT)
+    }
+
+    pub fn calculate(
+        employee: EmployeeData,
+        pay_period: String,
+        rules: &RuleSet,
+        settings: &CalculationSettings,
+    ) -> Self {
+        let mut payroll_data = Self::with_settings(employee, pay_period, settings);
+        if rules.is_empty() {
+            return payroll_data;
+        }
+
+        let emp_ref = payroll_data.employee.as_employee();
+        let extra_earnings = rules.earnings_for(emp_ref, &payroll_data.pay_period);
+        let extra_deductions = rules.deductions_for(emp_ref, &payroll_data.pay_period);
+        payroll_data.gross_salary += extra_earnings.iter().map(|c| c.amount).sum::<f64>();
+        payroll_data.deductions += extra_deductions.iter().map(|c| c.amount).sum::<f64>();
+        payroll_data.net_salary = payroll_data.gross_salary - payroll_data.deductions;
+        payroll_data.extra_earnings = extra_earnings;
+        payroll_data.extra_deductions = extra_deductions;
+        payroll_data
+    }
+
+    pub fn settings(&self) -> CalculationSettings {
+        self.context
+            .as_ref()
+            .map(CalculationContext::settings)
+            .unwrap_or_default()
+    }
+
+    /// PPh 21 on `income` received on top of this record's pay and the taxable income already added
+    /// to it, which `income` joins: several extras in one period are taxed at the marginal rate of
+    /// their total, not each as if it were the only one.
+    pub fn add_taxable_income(&mut self, income: f64) -> f64 {
+        if income <= 0.0 {
+            return 0.0;
+        }
+        let settings = self.settings();
+        let tax = match &self.employee {
+            EmployeeData::Fulltime(emp) => {
+                let taxed = emp.gross_with(&settings) + self.taxable_extras;
+                calc::fulltime_tax(taxed + income, emp.ptkp_status, &settings)
+                    - calc::fulltime_tax(taxed, emp.ptkp_status, &settings)
+            }
+            EmployeeData::Contract(emp) => emp.tax_on(income, &settings),
+        };
+        self.taxable_extras += income;
+        tax
+    }
+
+    /// The income tax withheld on the record: PPh 26 for non-residents, PPh 21 otherwise.
+    pub fn tax_name(&self) -> &'static str {
+        match self.employee.as_employee().profile().non_resident {
+            Some(_) => "PPh 26",
+            None => "PPh 21",
         }
     }
+
+    pub fn breakdown(&self) -> DeductionBreakdown {
+        self.employee.as_employee().deduction_breakdown_with(&self.settings())
+    }
+
+    pub fn earnings(&self) -> EarningBreakdown {
+        self.employee.as_employee().earning_breakdown_with(&self.settings())
+    }
+
+    pub fn earning_items(&self) -> Vec<PayrollComponent> {
+        let earnings = self.earnings();
+        let base = [("Base Pay", earnings.base), ("Overtime", earnings.overtime), ("Allowance", earnings.allowance)];
+        base.into_iter()
+            .map(|(name, amount)| PayrollComponent {
+                name: name.to_string(),
+                amount,
+            })
+            .chain(self.extra_earnings.iter().cloned())
+            .collect()
+    }
+
+    pub fn deduction_items(&self) -> Vec<PayrollComponent> {
+        let breakdown = self.breakdown();
+        let statutory = [
+            (self.tax_name(), breakdown.tax),
+            ("BPJS Kesehatan", breakdown.bpjs_kesehatan),
+            ("BPJS Ketenagakerjaan", breakdown.bpjs_ketenagakerjaan),
+        ];
+        statutory
+            .into_iter()
+            .map(|(name, amount)| PayrollComponent {
+                name: name.to_string(),
+                amount,
+            })
+            .chain(self.extra_deductions.iter().cloned())
+            .collect()
+    }
+
+    pub fn compute_batch(employees: Vec<EmployeeData>, pay_period: &str) -> Vec<PayrollData> {
+        Self::compute_batch_with_rules(employees, pay_period, &RuleSet::default())
+    }
+
+    pub fn compute_batch_with_rules(
+        employees: Vec<EmployeeData>,
+        pay_period: &str,
+        rules: &RuleSet,
+    ) -> Vec<PayrollData> {
+        Self::compute_batch_with(employees, pay_period, rules, &CalculationSettings::DEFAULT)
+    }
+
+    pub fn compute_batch_with(
+        employees: Vec<EmployeeData>,
+        pay_period: &str,
+        rules: &RuleSet,
+        settings: &CalculationSettings,
+    ) -> Vec<PayrollData> {
+        let employees = employees.into_iter().map(|employee| (employee, *settings)).collect();
+        Self::compute_batch_each(employees, pay_period, rules)
+    }
+
+    /// Calculates each employee with the settings paired with them.
+    pub fn compute_batch_each(
+        employees: Vec<(EmployeeData, CalculationSettings)>,
+        pay_period: &str,
+        rules: &RuleSet,
+    ) -> Vec<PayrollData> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            employees
+                .into_par_iter()
+                .map(|(employee, settings)| PayrollData::calculate(employee, pay_period.to_string(), rules, &settings))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            employees
+                .into_iter()
+                .map(|(employee, settings)| PayrollData::calculate(employee, pay_period.to_string(), rules, &settings))
+                .collect()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum RunStatus {
+    Draft,
+    Approved,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayrollRun {
+    pub run_id: u32,
+    pub pay_period: String,
+    pub record_indices: Vec<usize>,
+    pub status: RunStatus,
+    pub created_date: DateTime<Utc>,
+    pub approved_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deliveries: Vec<PayslipDelivery>,
+    #[serde(default)]
+    pub disbursement: Option<Disbursement>,
+    /// Employees whose records were left out because their onboarding is not finished.
+    #[serde(default)]
+    pub held: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Payroll {
-    pub payroll_records: Vec<PayrollData>,
+    pub schema_version: u32,
+    /// How many times the data file has been saved. A save only goes through if the file is still at
+    /// the revision this payroll was loaded at, and then advances it (through `&self`, hence atomic).
+    pub revision: AtomicU64,
+    pub employees: Vec<EmployeeData>,
+    /// Records are appended by processing payroll and only changed through the methods here.
+    payroll_records: PayrollRecords,
+    /// Closed only through `close_period`, reopened only by an administrator (`reopen_period`).
+    closed_periods: Vec<String>,
+    pub runs: Vec<PayrollRun>,
+    pub credentials: Credentials,
+    pub onboarding: OnboardingChecklists,
+    pub salary_changes: SalaryChanges,
+    pub salary_history: SalaryHistory,
+    pub review_cycles: ReviewCycles,
+    pub timesheets: Timesheets,
+    pub overtime_requests: OvertimeRequests,
+    pub attendance: AttendanceLog,
+    pub travel: TravelLog,
+    pub expense_claims: ExpenseClaims,
+    pub insurance_enrollments: InsuranceEnrollments,
+    pub equity_grants: EquityGrants,
+    pub natura: NaturaItems,
+    pub terminations: Terminations,
+    /// Totals of the pay periods whose detailed records were purged.
+    pub period_summaries: PeriodSummaries,
+    /// How many record ids were given out; each new one is derived from the next number.
+    record_sequence: u64,
+    /// The last payroll ledger entry the data file was saved with, when it has a ledger.
+    ledger_head: Mutex<Option<LedgerHead>>,
+    #[serde(skip)]
+    pub rules: RuleSet,
+    #[serde(skip)]
+    pub settings: CalculationSettings,
+    /// Rules in force before `settings`, for backdated periods.
+    #[serde(skip)]
+    pub rule_history: Vec<EffectiveRules>,
+    /// Regimes besides the built-in tables that employees and the rule history can refer to.
+    #[serde(skip)]
+    pub tax_regimes: Vec<TaxRegime>,
+}
+
+impl Default for Payroll {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Payroll {
     pub fn new() -> Self {
         Self {
-            payroll_records: Vec::new(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            revision: AtomicU64::new(0),
+            employees: Vec::new(),
+            payroll_records: PayrollRecords::default(),
+            closed_periods: Vec::new(),
+            runs: Vec::new(),
+            credentials: Credentials::default(),
+            onboarding: OnboardingChecklists::default(),
+            salary_changes: SalaryChanges::default(),
+            salary_history: SalaryHistory::default(),
+            review_cycles: ReviewCycles::default(),
+            timesheets: Timesheets::default(),
+            overtime_requests: OvertimeRequests::default(),
+            attendance: AttendanceLog::default(),
+            travel: TravelLog::default(),
+            expense_claims: ExpenseClaims::default(),
+            insurance_enrollments: InsuranceEnrollments::default(),
+            equity_grants: EquityGrants::default(),
+            natura: NaturaItems::default(),
+            terminations: Terminations::default(),
+            period_summaries: PeriodSummaries::default(),
+            record_sequence: 0,
+            ledger_head: Mutex::new(None),
+            rules: RuleSet::new(),
+            settings: CalculationSettings::DEFAULT,
+            rule_history: Vec::new(),
+            tax_regimes: Vec::new(),
         }
     }
 
-    pub fn process_payroll(&mut self, employee: EmployeeData, pay_period: String) -> PayrollData {
-        let payroll_data = PayrollData::new(employee, pay_period);
-        self.payroll_records.push(payroll_data.clone());
-        payroll_data
+    /// Applies the configured calculation settings, rules in force before them, tax regimes and the
+    /// policies each feature checks its entries against.
+    pub fn configure(&mut self, config: &Config) {
+        self.settings = config.settings();
+        self.rule_history = config.rule_history.clone();
+        self.tax_regimes = config.tax.regimes.clone();
+        self.overtime_requests.set_policy(config.overtime_policy());
+        self.attendance.set_policy(config.attendance);
+        self.travel.set_rates(config.travel);
+        self.expense_claims.set_policy(config.expenses.clone());
+        self.insurance_enrollments.set_plans(config.insurance.clone());
+        self.natura.set_rules(config.natura);
     }
 
-    pub fn get_payroll_records(&self) -> &Vec<PayrollData> {
-        &self.payroll_records
+    pub fn register_earning_rule(&mut self, rule: Box<dyn EarningRule>) {
+        self.rules.add_earning(rule);
     }
 
-    pub fn get_employee_payroll(&self, employee_id: &str) -> Vec<&PayrollData> {
-        self.payroll_records
+    pub fn register_deduction_rule(&mut self, rule: Box<dyn DeductionRule>) {
+        self.rules.add_deduction(rule);
+    }
+
+    /// Adds or replaces an employee. New employees get an onboarding checklist; for known ones the
+    /// checklist picks up anything the updated profile now provides.
+    pub fn add_employee(&mut self, employee: EmployeeData) {
+        let employee_id = employee.as_employee().employee_id().to_string();
+        let profile = employee.as_employee().profile();
+        let registered = self.get_employee(&employee_id).is_some();
+        self.onboarding.sync(&employee_id, profile, registered);
+        match self
+            .employees
+            .iter_mut()
+            .find(|existing| existing.as_employee().employee_id() == employee_id)
+        {
+            Some(existing) => *existing = employee,
+            None => self.employees.push(employee),
+        }
+    }
+
+    pub fn onboarding(&self, employee_id: &str) -> Option<&OnboardingChecklist> {
+        self.onboarding.get(employee_id)
+    }
+
+    /// Marks an onboarding task done. Returns false when it already was.
+    pub fn complete_onboarding_task(&mut self, employee_id: &str, task: OnboardingTask) -> Result<bool, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.onboarding.complete(employee_id, task)
+    }
+
+    /// Employees without a checklist were registered before onboarding was tracked and are not held.
+    pub fn is_onboarded(&self, employee_id: &str) -> bool {
+        self.onboarding(employee_id).is_none_or(OnboardingChecklist::is_complete)
+    }
+
+    /// Checklists of registered employees with tasks still open, oldest first.
+    pub fn pending_onboarding(&self) -> Vec<&OnboardingChecklist> {
+        let mut pending = self.onboarding.pending();
+        pending.retain(|checklist| self.get_employee(&checklist.employee_id).is_some());
+        pending
+    }
+
+    pub fn get_employee(&self, employee_id: &str) -> Option<&EmployeeData> {
+        self.employees
+            .iter()
+            .find(|employee| employee.as_employee().employee_id() == employee_id)
+    }
+
+    /// Removes an employee from the registry. Employees with payroll history are only removed with
+    /// `force`; archiving keeps them registered so their records never point at an unknown ID.
+    pub fn remove_employee(&mut self, employee_id: &str, force: bool) -> Result<EmployeeData, PayrollError> {
+        let index = self
+            .employees
+            .iter()
+            .position(|employee| employee.as_employee().employee_id() == employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))?;
+        let records = self
+            .payroll_records
             .iter()
             .filter(|record| record.employee.as_employee().employee_id() == employee_id)
+            .count();
+        if records > 0 && !force {
+            return Err(PayrollError::EmployeeHasHistory(employee_id.to_string(), records));
+        }
+        self.credentials.remove(employee_id);
+        Ok(self.employees.remove(index))
+    }
+
+    /// Sets or replaces the self-service PIN of a registered employee.
+    pub fn set_pin(&mut self, employee_id: &str, pin: &str) -> Result<(), PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        let credential = EmployeeCredential::new(employee_id, pin).map_err(PayrollError::InvalidPin)?;
+        self.credentials.set(credential);
+        Ok(())
+    }
+
+    /// Issues a temporary PIN, replacing any existing one and clearing a lockout. The employee has
+    /// to change it at first sign-in.
+    pub fn issue_pin(&mut self, employee_id: &str) -> Result<String, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        let (credential, pin) = EmployeeCredential::issue(employee_id);
+        self.credentials.set(credential);
+        Ok(pin)
+    }
+
+    /// Clears failed sign-ins and any lockout. Returns whether the employee was locked out.
+    pub fn unlock_pin(&mut self, employee_id: &str) -> Result<bool, PayrollError> {
+        self.credentials.unlock(employee_id)
+    }
+
+    /// Removes the employee's PIN, ending their self-service access. Returns whether they had one.
+    pub fn revoke_pin(&mut self, employee_id: &str) -> bool {
+        self.credentials.remove(employee_id)
+    }
+
+    pub fn has_pin(&self, employee_id: &str) -> bool {
+        self.credential(employee_id).is_some()
+    }
+
+    pub fn credential(&self, employee_id: &str) -> Option<&EmployeeCredential> {
+        self.credentials.get(employee_id)
+    }
+
+    /// Signs an employee in for self-service. Unknown IDs and wrong PINs fail the same way; wrong
+    /// PINs are counted and lock sign-in for a while once there are too many in a row.
+    pub fn sign_in(&mut self, employee_id: &str, pin: &str) -> Result<SelfService<'_>, PayrollError> {
+        let employee_id = employee_id.trim();
+        self.credentials.sign_in(employee_id, pin, Utc::now())?;
+        Ok(SelfService::new(self, employee_id))
+    }
+
+    /// Counts a PIN check made against `checked`, a copy of the employee's credential, outside the
+    /// payroll, e.g. by the server without holding its lock while Argon2 runs. Fails as `sign_in`
+    /// does, and also when the credential was locked or given a new PIN since the copy.
+    pub fn record_sign_in(&mut self, checked: &EmployeeCredential, verified: bool) -> Result<(), PayrollError> {
+        self.credentials.record_sign_in(checked, verified, Utc::now())
+    }
+
+    /// Replaces the employee's PIN after checking the current one the same way `sign_in` does.
+    pub fn change_pin(&mut self, employee_id: &str, current: &str, new_pin: &str) -> Result<(), PayrollError> {
+        let employee_id = self.sign_in(employee_id, current)?.employee_id().to_string();
+        if new_pin == current {
+            return Err(PayrollError::InvalidPin("the new PIN must differ from the current one".to_string()));
+        }
+        self.set_pin(&employee_id, new_pin)
+    }
+
+    /// The settings `employee` is paid with for `pay_period`: the company's, or the rules that were in
+    /// force then when the rule history covers the period, with the employee's own tax regime.
+    pub fn settings_for(&self, employee: &EmployeeData, pay_period: &str) -> CalculationSettings {
+        let settings = match pay_period.parse::<PayPeriod>() {
+            Ok(period) => {
+                rule_history::settings_in_force(&self.rule_history, period, &self.settings, &self.tax_regimes)
+            }
+            Err(_) => self.settings,
+        };
+        tax::settings_for(employee.as_employee().profile(), &settings, &self.tax_regimes)
+    }
+
+    /// Calculates what `employee_id` would be paid for `pay_period` with the current rules and
+    /// settings, without storing a record.
+    pub fn preview_payroll(&self, employee_id: &str, pay_period: &str) -> Result<PayrollData, PayrollError> {
+        let employee = self
+            .get_employee(employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))?;
+        Ok(self.calculate_record(employee.clone(), pay_period))
+    }
+
+    /// The record processing `employee` for `pay_period` stores, without its id: calculated with the
+    /// salary, timesheet hours and settings in effect then and the overtime policy, plus the
+    /// attendance, travel, expense, insurance, equity and benefit-in-kind lines.
+    fn calculate_record(&self, employee: EmployeeData, pay_period: &str) -> PayrollData {
+        let employee = self.with_salary_in_effect(employee, pay_period);
+        let employee = self.with_timesheet_hours(employee, pay_period);
+        let (employee, overtime) = self.with_overtime_policy(employee, pay_period);
+        let settings = self.settings_for(&employee, pay_period);
+        let mut payroll_data = PayrollData::calculate(employee, pay_period.to_string(), &self.rules, &settings);
+        payroll_data.overtime = overtime;
+        self.apply_extras(&mut payroll_data);
+        payroll_data
+    }
+
+    fn apply_extras(&self, record: &mut PayrollData) {
+        self.attendance.apply(record);
+        self.travel.apply(record);
+        self.expense_claims.apply(record);
+        self.insurance_enrollments.apply(record);
+        self.equity_grants.apply(record);
+        self.natura.apply(record);
+    }
+
+    fn employee_mut(&mut self, employee_id: &str) -> Result<&mut EmployeeData, PayrollError> {
+        self.employees
+            .iter_mut()
+            .find(|employee| employee.as_employee().employee_id() == employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))
+    }
+
+    pub fn archive_employee(&mut self, employee_id: &str) -> Result<&EmployeeData, PayrollError> {
+        let employee = self.employee_mut(employee_id)?;
+        employee.profile_mut().status = EmployeeStatus::Archived;
+        Ok(employee)
+    }
+
+    /// Everything held on `employee_id`, for a data-subject access request. PIN hashes are left
+    /// out; the rest is exported as stored.
+    pub fn subject_data(&self, employee_id: &str, now: DateTime<Utc>) -> Result<SubjectData, PayrollError> {
+        let employee = self
+            .get_employee(employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))?;
+        let runs = || self.runs.iter();
+        Ok(SubjectData {
+            format: privacy::SUBJECT_DATA_FORMAT.to_string(),
+            exported: now,
+            employee: employee.clone(),
+            self_service: self.credential(employee_id).map(CredentialSummary::from),
+            onboarding: self.onboarding(employee_id).cloned(),
+            payroll_records: self
+                .payroll_records
+                .iter()
+                .filter(|record| record.employee.as_employee().employee_id() == employee_id)
+                .cloned()
+                .collect(),
+            payslip_deliveries: runs()
+                .flat_map(|run| run.deliveries.iter().map(move |delivery| (run, delivery)))
+                .filter(|(_, delivery)| delivery.employee_id == employee_id)
+                .map(|(run, delivery)| RunDelivery {
+                    run_id: run.run_id,
+                    pay_period: run.pay_period.clone(),
+                    delivery: delivery.clone(),
+                })
+                .collect(),
+            transfers: runs()
+                .filter_map(|run| run.disbursement.as_ref().map(|disbursement| (run, disbursement)))
+                .flat_map(|(run, disbursement)| {
+                    disbursement.confirmations.iter().map(move |transfer| (run, disbursement, transfer))
+                })
+                .filter(|(_, _, transfer)| transfer.employee_id == employee_id)
+                .map(|(run, disbursement, transfer)| RunTransfer {
+                    run_id: run.run_id,
+                    pay_period: run.pay_period.clone(),
+                    provider: disbursement.provider.clone(),
+                    transfer: transfer.clone(),
+                })
+                .collect(),
+            salary_changes: self.salary_changes.iter().filter(|c| c.employee_id == employee_id).cloned().collect(),
+            salary_history: self.salary_history.iter().filter(|e| e.employee_id == employee_id).cloned().collect(),
+            reviews: self
+                .review_cycles
+                .iter()
+                .flat_map(|cycle| cycle.reviews.iter().map(move |review| (cycle, review)))
+                .filter(|(_, review)| review.employee_id == employee_id)
+                .map(|(cycle, review)| CycleReview {
+                    cycle: cycle.name.clone(),
+                    review: review.clone(),
+                })
+                .collect(),
+            timesheets: self.timesheets.iter().filter(|t| t.employee_id == employee_id).cloned().collect(),
+            overtime_requests: self
+                .overtime_requests
+                .iter()
+                .filter(|r| r.employee_id == employee_id)
+                .cloned()
+                .collect(),
+            attendance: self.attendance.iter().filter(|a| a.employee_id == employee_id).cloned().collect(),
+            travel: self.travel.iter().filter(|t| t.employee_id == employee_id).cloned().collect(),
+            expense_claims: self.expense_claims.iter().filter(|c| c.employee_id == employee_id).cloned().collect(),
+            insurance_enrollments: self
+                .insurance_enrollments
+                .iter()
+                .filter(|e| e.employee_id == employee_id)
+                .cloned()
+                .collect(),
+            equity_grants: self.equity_grants.iter().filter(|g| g.employee_id == employee_id).cloned().collect(),
+            natura: self.natura.iter().filter(|n| n.employee_id == employee_id).cloned().collect(),
+            terminations: self.terminations.iter().filter(|t| t.employee_id == employee_id).cloned().collect(),
+        })
+    }
+
+    /// The employee's last day: the one their termination took effect on, or the end of the last
+    /// period they were paid for when they were marked terminated some other way.
+    pub fn employment_ended(&self, employee_id: &str) -> Option<NaiveDate> {
+        self.terminations.last_day(employee_id).or_else(|| {
+            self.payroll_records
+                .iter()
+                .filter(|record| record.employee.as_employee().employee_id() == employee_id)
+                .filter_map(|record| record.pay_period.parse::<PayPeriod>().ok())
+                .max()
+                .and_then(|period| period.next().first_day().pred_opt())
+        })
+    }
+
+    /// Pseudonymizes a terminated employee once `retention_years` have passed since their last day.
+    /// Their records and every other entry move to a fresh pseudonym with their identifying details
+    /// dropped (see [`privacy::scrub_employee`]), so amounts and periods still count in reports;
+    /// their PIN is removed and their bank accounts and e-mail are cleared from runs.
+    pub fn pseudonymize_employee(
+        &mut self,
+        employee_id: &str,
+        retention_years: u32,
+        today: NaiveDate,
+    ) -> Result<Pseudonymization, PayrollError> {
+        let employee = self
+            .get_employee(employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))?;
+        if privacy::is_pseudonym(employee_id) {
+            return Err(PayrollError::AlreadyPseudonymized(employee_id.to_string()));
+        }
+        if employee.as_employee().profile().status != EmployeeStatus::Terminated {
+            return Err(PayrollError::NotTerminated(employee_id.to_string()));
+        }
+        if let Some(last_day) = self.employment_ended(employee_id) {
+            let until = privacy::retention_ends(last_day, retention_years);
+            if today < until {
+                return Err(PayrollError::RetentionNotElapsed(employee_id.to_string(), until));
+            }
+        }
+
+        let pseudonym = privacy::new_pseudonym();
+        let renaming = Renaming {
+            employee_id,
+            pseudonym: &pseudonym,
+        };
+        self.credentials.remove(employee_id);
+        for employee in &mut self.employees {
+            if employee.as_employee().employee_id() == employee_id {
+                privacy::scrub_employee(employee, &pseudonym);
+            } else {
+                renaming.option(&mut employee.profile_mut().manager_id);
+            }
+        }
+
+        let indices: Vec<usize> = (0..self.payroll_records.len())
+            .filter(|&index| self.payroll_records[index].employee.as_employee().employee_id() == employee_id)
+            .collect();
+        for &index in &indices {
+            let record_id = match self.payroll_records[index].record_id.is_empty() {
+                true => String::new(),
+                false => {
+                    let pay_period = self.payroll_records[index].pay_period.clone();
+                    self.next_record_id(&pseudonym, &pay_period)
+                }
+            };
+            self.payroll_records.update(index, |record| {
+                privacy::scrub_employee(&mut record.employee, &pseudonym);
+                record.record_id = record_id;
+            });
+        }
+        self.payroll_records.update_all(|record| {
+            renaming.option(&mut record.employee.profile_mut().manager_id);
+        });
+
+        let mut other_entries = 0;
+        for run in &mut self.runs {
+            for delivery in run.deliveries.iter_mut() {
+                if renaming.id(&mut delivery.employee_id) == 1 {
+                    delivery.email = None;
+                    other_entries += 1;
+                }
+            }
+            for transfer in run.disbursement.iter_mut().flat_map(|d| d.confirmations.iter_mut()) {
+                if renaming.id(&mut transfer.employee_id) == 1 {
+                    transfer.account_number.clear();
+                    other_entries += 1;
+                }
+            }
+            other_entries += run.held.iter_mut().map(|id| renaming.id(id)).sum::<usize>();
+        }
+        other_entries += self.onboarding.pseudonymize(&renaming);
+        other_entries += self.salary_changes.pseudonymize(&renaming);
+        other_entries += self.salary_history.pseudonymize(&renaming);
+        other_entries += self.review_cycles.pseudonymize(&renaming);
+        other_entries += self.timesheets.pseudonymize(&renaming);
+        other_entries += self.overtime_requests.pseudonymize(&renaming);
+        other_entries += self.attendance.pseudonymize(&renaming);
+        other_entries += self.travel.pseudonymize(&renaming);
+        other_entries += self.expense_claims.pseudonymize(&renaming);
+        other_entries += self.insurance_enrollments.pseudonymize(&renaming);
+        other_entries += self.equity_grants.pseudonymize(&renaming);
+        other_entries += self.natura.pseudonymize(&renaming);
+        other_entries += self.terminations.pseudonymize(&renaming);
+        Ok(Pseudonymization {
+            employee_id: employee_id.to_string(),
+            pseudonym,
+            payroll_records: indices.len(),
+            other_entries,
+        })
+    }
+
+    /// Brings employee master data (name, department and status) in line with `source`. Employees
+    /// it lists as inactive are archived and archived ones it lists as active are reactivated;
+    /// terminated employees keep their status. Unregistered employees are created when the source
+    /// gives a base salary or hourly rate. Active employees the source does not list are archived
+    /// only with `deactivate_missing`, and never when it lists no one at all.
+    pub fn sync_employees(
+        &mut self,
+        source: &dyn EmployeeSource,
+        deactivate_missing: bool,
+    ) -> Result<SyncReport, PayrollError> {
+        let listed = source.fetch_employees().map_err(PayrollError::EmployeeSource)?;
+        if listed.is_empty() && deactivate_missing {
+            return Err(PayrollError::EmployeeSource(format!(
+                "{} listed no employees; nothing deactivated",
+                source.name()
+            )));
+        }
+        let mut report = SyncReport {
+            source: source.name().to_string(),
+            ..SyncReport::default()
+        };
+        let mut seen: Vec<String> = Vec::new();
+        for entry in listed {
+            let employee_id = entry.employee_id.trim().to_string();
+            if employee_id.is_empty() {
+                report.skipped.push((entry.name, "no employee ID".to_string()));
+                continue;
+            }
+            if seen.contains(&employee_id) {
+                report.skipped.push((employee_id, "listed more than once".to_string()));
+                continue;
+            }
+            seen.push(employee_id.clone());
+            let Ok(employee) = self.employee_mut(&employee_id) else {
+                if entry.active {
+                    match Self::employee_from_source(&employee_id, entry) {
+                        Ok(employee) => {
+                            self.add_employee(employee);
+                            report.created.push(employee_id);
+                        }
+                        Err(reason) => report.skipped.push((employee_id, reason)),
+                    }
+                }
+                continue;
+            };
+            let profile = employee.profile_mut();
+            let mut changed = false;
+            if profile.name != entry.name {
+                profile.name = entry.name;
+                changed = true;
+            }
+            if entry.department.is_some() && profile.department != entry.department {
+                profile.department = entry.department;
+                changed = true;
+            }
+            match (entry.active, profile.status) {
+                (false, EmployeeStatus::Active | EmployeeStatus::Probation) => {
+                    profile.status = EmployeeStatus::Archived;
+                    report.deactivated.push(employee_id);
+                    continue;
+                }
+                (true, EmployeeStatus::Archived) => {
+                    profile.status = EmployeeStatus::Active;
+                    changed = true;
+                }
+                _ => {}
+            }
+            match changed {
+                true => report.updated.push(employee_id),
+                false => report.unchanged += 1,
+            }
+        }
+
+        for employee in &mut self.employees {
+            let employee_id = employee.as_employee().employee_id().to_string();
+            let profile = employee.profile_mut();
+            if seen.contains(&employee_id)
+                || !matches!(profile.status, EmployeeStatus::Active | EmployeeStatus::Probation)
+            {
+                continue;
+            }
+            if deactivate_missing {
+                profile.status = EmployeeStatus::Archived;
+                report.deactivated.push(employee_id);
+            } else {
+                report.missing.push(employee_id);
+            }
+        }
+        Ok(report)
+    }
+
+    fn employee_from_source(employee_id: &str, entry: SourceEmployee) -> Result<EmployeeData, String> {
+        let mut employee = match (entry.base_salary, entry.hourly_rate) {
+            (Some(_), Some(_)) => return Err("both a base salary and an hourly rate given".to_string()),
+            (None, None) => return Err("not registered and no base salary or hourly rate to create it".to_string()),
+            (Some(pay), None) | (None, Some(pay)) if !pay.is_finite() || pay < 0.0 => {
+                return Err(format!("invalid pay {}", pay))
+            }
+            (Some(base_salary), None) => EmployeeData::Fulltime(FulltimeEmployee::new(
+                employee_id.to_string(),
+                calc::STANDARD_MONTHLY_HOURS,
+                0.0,
+                calc::AllowancePeriod::Monthly,
+                base_salary,
+            )),
+            (None, Some(hourly_rate)) => EmployeeData::Contract(ContractEmployee::new(
+                employee_id.to_string(),
+                calc::STANDARD_MONTHLY_HOURS,
+                0.0,
+                calc::AllowancePeriod::Monthly,
+                hourly_rate,
+            )),
+        };
+        let profile = employee.profile_mut();
+        profile.name = entry.name;
+        profile.department = entry.department;
+        Ok(employee)
+    }
+
+    /// Checks that `manager_id` is registered and that `employee_id` would not end up managing
+    /// themselves through the reporting line.
+    pub fn check_manager(&self, employee_id: &str, manager_id: &str) -> Result<(), PayrollError> {
+        if self.get_employee(manager_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(manager_id.to_string()));
+        }
+        let in_line = manager_id == employee_id
+            || self
+                .reporting_chain(manager_id)
+                .iter()
+                .any(|manager| manager.as_employee().employee_id() == employee_id);
+        if in_line {
+            return Err(PayrollError::ReportingCycle(employee_id.to_string(), manager_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Sets or clears who an employee reports to.
+    pub fn set_manager(&mut self, employee_id: &str, manager_id: Option<&str>) -> Result<&EmployeeData, PayrollError> {
+        if let Some(manager_id) = manager_id {
+            self.check_manager(employee_id, manager_id)?;
+        }
+        let employee = self.employee_mut(employee_id)?;
+        employee.profile_mut().manager_id = manager_id.map(str::to_string);
+        Ok(employee)
+    }
+
+    /// Marks a contract employee as resident abroad, withheld PPh 26 from their next payroll on, or
+    /// as resident again when `residency` is `None`.
+    pub fn set_residency(
+        &mut self,
+        employee_id: &str,
+        residency: Option<NonResidency>,
+    ) -> Result<&EmployeeData, PayrollError> {
+        if let Some(residency) = &residency {
+            residency.validate().map_err(PayrollError::InvalidResidency)?;
+        }
+        let employee = self.employee_mut(employee_id)?;
+        if residency.is_some() && matches!(employee, EmployeeData::Fulltime(_)) {
+            return Err(PayrollError::InvalidResidency(format!(
+                "{} is a full-time employee; PPh 26 is only withheld from contractors",
+                employee_id
+            )));
+        }
+        employee.profile_mut().non_resident = residency;
+        Ok(employee)
+    }
+
+    /// Charges a full-time employee's tax under a registered regime instead of the company's, or under
+    /// the company's again when `regime` is `None`.
+    pub fn set_tax_regime(&mut self, employee_id: &str, regime: Option<&str>) -> Result<&EmployeeData, PayrollError> {
+        let regime = match regime {
+            Some(id) => match tax::find_regime(&self.tax_regimes, id) {
+                Some(table) => Some(table.id().to_string()),
+                None => return Err(PayrollError::InvalidTaxRegime(format!("'{}' is not configured", id))),
+            },
+            None => None,
+        };
+        let employee = self.employee_mut(employee_id)?;
+        if regime.is_some() && matches!(employee, EmployeeData::Contract(_)) {
+            return Err(PayrollError::InvalidTaxRegime(format!(
+                "{} is a contract employee, taxed at a flat rate",
+                employee_id
+            )));
+        }
+        employee.profile_mut().tax_regime = regime;
+        Ok(employee)
+    }
+
+    pub fn manager_of(&self, employee_id: &str) -> Option<&EmployeeData> {
+        let manager_id = self.get_employee(employee_id)?.as_employee().profile().manager_id.as_deref()?;
+        self.get_employee(manager_id)
+    }
+
+    pub fn direct_reports(&self, employee_id: &str) -> Vec<&EmployeeData> {
+        self.employees
+            .iter()
+            .filter(|employee| employee.as_employee().profile().manager_id.as_deref() == Some(employee_id))
             .collect()
     }
+
+    /// Everyone below an employee, direct reports first, then their reports, and so on.
+    pub fn all_reports(&self, employee_id: &str) -> Vec<&EmployeeData> {
+        let mut reports = self.direct_reports(employee_id);
+        let mut next = 0;
+        while next < reports.len() {
+            let manager_id = reports[next].as_employee().employee_id();
+            for report in self.direct_reports(manager_id) {
+                let id = report.as_employee().employee_id();
+                if id != employee_id && !reports.iter().any(|seen| seen.as_employee().employee_id() == id) {
+                    reports.push(report);
+                }
+            }
+            next += 1;
+        }
+        reports
+    }
+
+    /// An employee's manager, their manager, and so on up to the top. Approvals are routed along
+    /// this chain.
+    pub fn reporting_chain(&self, employee_id: &str) -> Vec<&EmployeeData> {
+        let mut chain: Vec<&EmployeeData> = Vec::new();
+        let mut current = employee_id;
+        while let Some(manager) = self.manager_of(current) {
+            let manager_id = manager.as_employee().employee_id();
+            if manager_id == employee_id || chain.iter().any(|seen| seen.as_employee().employee_id() == manager_id) {
+                break;
+            }
+            chain.push(manager);
+            current = manager_id;
+        }
+        chain
+    }
+
+    /// Whether `approver_id` is above `employee_id` in the reporting chain, and so may approve
+    /// their timesheets, leave and pay.
+    pub fn can_approve_for(&self, approver_id: &str, employee_id: &str) -> bool {
+        self.reporting_chain(employee_id)
+            .iter()
+            .any(|manager| manager.as_employee().employee_id() == approver_id)
+    }
+
+    /// Employees at the top of the org chart: those without a registered manager.
+    pub fn org_roots(&self) -> Vec<&EmployeeData> {
+        self.employees
+            .iter()
+            .filter(|employee| {
+                let manager_id = employee.as_employee().profile().manager_id.as_deref();
+                manager_id.is_none_or(|manager_id| self.get_employee(manager_id).is_none())
+            })
+            .collect()
+    }
+
+    /// Submits a contract employee's hours for a pay period, replacing an earlier submission that
+    /// has not been approved.
+    pub fn submit_timesheet(
+        &mut self,
+        employee_id: &str,
+        period: PayPeriod,
+        hours: f64,
+    ) -> Result<&Timesheet, PayrollError> {
+        match self.get_employee(employee_id) {
+            None => return Err(PayrollError::EmployeeNotFound(employee_id.to_string())),
+            Some(EmployeeData::Fulltime(_)) => {
+                return Err(PayrollError::InvalidTimesheet(format!("{} is not a contract employee", employee_id)))
+            }
+            Some(EmployeeData::Contract(_)) => {}
+        }
+        self.ensure_period_open(&period.to_string())?;
+        let timesheet = Timesheet::new(employee_id, period, hours).map_err(PayrollError::InvalidTimesheet)?;
+        self.timesheets.submit(timesheet)
+    }
+
+    pub fn timesheet(&self, employee_id: &str, period: &PayPeriod) -> Option<&Timesheet> {
+        self.timesheets.get(employee_id, period)
+    }
+
+    /// Approves or rejects a submitted timesheet. Only managers above the employee in the reporting
+    /// chain may.
+    fn decide_timesheet(
+        &mut self,
+        employee_id: &str,
+        period: &PayPeriod,
+        approver_id: &str,
+        status: TimesheetStatus,
+    ) -> Result<&Timesheet, PayrollError> {
+        if !self.can_approve_for(approver_id, employee_id) {
+            return Err(PayrollError::NotManager(approver_id.to_string(), employee_id.to_string()));
+        }
+        self.ensure_period_open(&period.to_string())?;
+        self.timesheets.decide(employee_id, period, approver_id, status)
+    }
+
+    pub fn approve_timesheet(
+        &mut self,
+        employee_id: &str,
+        period: &PayPeriod,
+        approver_id: &str,
+    ) -> Result<&Timesheet, PayrollError> {
+        self.decide_timesheet(employee_id, period, approver_id, TimesheetStatus::Approved)
+    }
+
+    pub fn reject_timesheet(
+        &mut self,
+        employee_id: &str,
+        period: &PayPeriod,
+        approver_id: &str,
+        reason: &str,
+    ) -> Result<(), PayrollError> {
+        let status = TimesheetStatus::Rejected(reason.trim().to_string());
+        self.decide_timesheet(employee_id, period, approver_id, status).map(|_| ())
+    }
+
+    /// Timesheets waiting for approval, oldest submission first.
+    pub fn pending_timesheets(&self) -> Vec<&Timesheet> {
+        self.timesheets.pending()
+    }
+
+    /// Replaces a contract employee's entered hours with those of their approved timesheet.
+    fn with_timesheet_hours(&self, mut employee: EmployeeData, pay_period: &str) -> EmployeeData {
+        let Ok(period) = pay_period.parse::<PayPeriod>() else {
+            return employee;
+        };
+        if let EmployeeData::Contract(emp) = &mut employee {
+            if let Some(hours) = self.timesheets.approved_hours(&emp.employee_id, &period) {
+                emp.work_hour = hours;
+            }
+        }
+        employee
+    }
+
+    /// Asks to work `hours` of overtime in a pay period. Only full-time employees are paid overtime.
+    pub fn request_overtime(
+        &mut self,
+        employee_id: &str,
+        period: PayPeriod,
+        hours: f64,
+        reason: &str,
+    ) -> Result<&OvertimeRequest, PayrollError> {
+        match self.get_employee(employee_id) {
+            None => return Err(PayrollError::EmployeeNotFound(employee_id.to_string())),
+            Some(EmployeeData::Contract(_)) => {
+                return Err(PayrollError::InvalidOvertimeRequest(format!(
+                    "{} is a contract employee and is paid by the hour",
+                    employee_id
+                )))
+            }
+            Some(EmployeeData::Fulltime(_)) => {}
+        }
+        self.ensure_period_open(&period.to_string())?;
+        self.overtime_requests.request(employee_id, period, hours, reason)
+    }
+
+    pub fn overtime_request(&self, id: u32) -> Option<&OvertimeRequest> {
+        self.overtime_requests.get(id)
+    }
+
+    /// Approves or rejects an overtime request. Only managers above the employee in the reporting
+    /// chain may.
+    fn decide_overtime(
+        &mut self,
+        id: u32,
+        approver_id: &str,
+        status: ChangeRequestStatus,
+    ) -> Result<&OvertimeRequest, PayrollError> {
+        let request = self.overtime_requests.undecided(id)?;
+        if !self.can_approve_for(approver_id, &request.employee_id) {
+            return Err(PayrollError::NotManager(approver_id.to_string(), request.employee_id.clone()));
+        }
+        self.ensure_period_open(&request.period.to_string())?;
+        self.overtime_requests.decide(id, approver_id, status)
+    }
+
+    pub fn approve_overtime(&mut self, id: u32, approver_id: &str) -> Result<&OvertimeRequest, PayrollError> {
+        self.decide_overtime(id, approver_id, ChangeRequestStatus::Approved)
+    }
+
+    pub fn reject_overtime(&mut self, id: u32, approver_id: &str, reason: &str) -> Result<(), PayrollError> {
+        let status = ChangeRequestStatus::Rejected(reason.trim().to_string());
+        self.decide_overtime(id, approver_id, status).map(|_| ())
+    }
+
+    /// Overtime requests waiting for a decision, oldest first.
+    pub fn pending_overtime_requests(&self) -> Vec<&OvertimeRequest> {
+        self.overtime_requests.pending()
+    }
+
+    /// Overtime hours approved for an employee in a pay period.
+    pub fn approved_overtime(&self, employee_id: &str, period: &PayPeriod) -> f64 {
+        self.overtime_requests.approved_hours(employee_id, period)
+    }
+
+    /// Records of a period whose overtime went beyond the cap or the approved hours, whether it was
+    /// excluded from pay or flagged.
+    pub fn overtime_exceptions(&self, pay_period: &str) -> Vec<&PayrollData> {
+        self.get_period_records(pay_period)
+            .into_iter()
+            .filter(|record| record.overtime.is_some_and(|check| check.excess_hours > 0.0))
+            .collect()
+    }
+
+    /// Checks a full-time employee's overtime against the overtime policy, taking excluded hours
+    /// off the hours paid.
+    fn with_overtime_policy(&self, employee: EmployeeData, pay_period: &str) -> (EmployeeData, Option<OvertimeCheck>) {
+        self.overtime_requests.apply(employee, pay_period, self.settings.standard_monthly_hours)
+    }
+
+    /// Records a day of attendance, replacing an earlier entry for the same employee and date.
+    pub fn record_attendance(&mut self, entry: AttendanceEntry) -> Result<(), PayrollError> {
+        if self.get_employee(&entry.employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(entry.employee_id));
+        }
+        self.ensure_period_open(&entry.period().to_string())?;
+        self.attendance.record(entry);
+        Ok(())
+    }
+
+    /// An employee's attendance in a pay period, by date.
+    pub fn attendance_for(&self, employee_id: &str, period: &PayPeriod) -> Vec<&AttendanceEntry> {
+        self.attendance.for_employee(employee_id, period)
+    }
+
+    pub fn attendance_summary(&self, employee_id: &str, period: &PayPeriod) -> AttendanceSummary {
+        self.attendance.summary(employee_id, period)
+    }
+
+    /// Records a business trip starting on `start`, paid at the current per-diem rate of its tier in
+    /// `period` (the month it starts in unless given).
+    pub fn record_travel(
+        &mut self,
+        employee_id: &str,
+        destination: &str,
+        tier: DestinationTier,
+        start: NaiveDate,
+        days: u32,
+        period: Option<PayPeriod>,
+    ) -> Result<&TravelRecord, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        let period = period.unwrap_or_else(|| PayPeriod::from_date(start));
+        self.ensure_period_open(&period.to_string())?;
+        self.travel.record(employee_id, destination, tier, start, days, period)
+    }
+
+    /// Removes a travel record whose pay period is still open.
+    pub fn cancel_travel(&mut self, id: u32) -> Result<TravelRecord, PayrollError> {
+        let record = self.travel.get(id).ok_or(PayrollError::TravelNotFound(id))?;
+        self.ensure_period_open(&record.period.to_string())?;
+        self.travel.cancel(id)
+    }
+
+    /// Travel paid in `period`, or all travel, by start date.
+    pub fn travel_records(&self, period: Option<&PayPeriod>) -> Vec<&TravelRecord> {
+        self.travel.in_period(period)
+    }
+
+    /// Submits a reimbursement claim to be paid in `period`. A claim that would take the category over
+    /// the monthly cap of the employee's grade is truncated or rejected, as the expense policy says.
+    pub fn submit_expense(
+        &mut self,
+        employee_id: &str,
+        category: &str,
+        period: PayPeriod,
+        amount: f64,
+        description: &str,
+    ) -> Result<&ExpenseClaim, PayrollError> {
+        let employee = self
+            .get_employee(employee_id)
+            .ok_or_else(|| PayrollError::EmployeeNotFound(employee_id.to_string()))?;
+        self.ensure_period_open(&period.to_string())?;
+        let employee = self.with_salary_in_effect(employee.clone(), &period.to_string());
+        let grade = employee.as_employee().profile().grade.as_deref();
+        self.expense_claims.submit(employee_id, grade, category, period, amount, description)
+    }
+
+    /// Withdraws a claim whose pay period is still open.
+    pub fn cancel_expense(&mut self, id: u32) -> Result<ExpenseClaim, PayrollError> {
+        let claim = self.expense_claims.get(id).ok_or(PayrollError::ExpenseClaimNotFound(id))?;
+        self.ensure_period_open(&claim.period.to_string())?;
+        self.expense_claims.cancel(id)
+    }
+
+    /// Claims paid in `period`, or all claims, in the order they were submitted.
+    pub fn expense_claims_in(&self, period: Option<&PayPeriod>) -> Vec<&ExpenseClaim> {
+        self.expense_claims.in_period(period)
+    }
+
+    pub fn insurance_plan(&self, code: &str) -> Option<&InsurancePlan> {
+        self.insurance_enrollments.plan(code)
+    }
+
+    /// Enrolls an employee in a plan tier from `start` on. An earlier enrollment in the same plan
+    /// ends the period before, so changing tier is enrolling again.
+    pub fn enroll_insurance(
+        &mut self,
+        employee_id: &str,
+        plan: &str,
+        tier: &str,
+        start: PayPeriod,
+    ) -> Result<&InsuranceEnrollment, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.ensure_period_open(&start.to_string())?;
+        self.insurance_enrollments.enroll(employee_id, plan, tier, start)
+    }
+
+    /// Ends an employee's cover under a plan after `last`.
+    pub fn end_insurance(&mut self, employee_id: &str, plan: &str, last: PayPeriod) -> Result<(), PayrollError> {
+        self.ensure_period_open(&last.next().to_string())?;
+        self.insurance_enrollments.end(employee_id, plan, last)
+    }
+
+    /// Enrollments of one employee, or of everyone, by employee and start.
+    pub fn insurance_enrollments_of(&self, employee_id: Option<&str>) -> Vec<&InsuranceEnrollment> {
+        self.insurance_enrollments.of(employee_id)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn grant_equity(
+        &mut self,
+        employee_id: &str,
+        kind: EquityKind,
+        units: u32,
+        strike_price: f64,
+        fair_value: f64,
+        granted: NaiveDate,
+        schedule: VestingSchedule,
+    ) -> Result<&EquityGrant, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.equity_grants.grant(employee_id, kind, units, strike_price, fair_value, granted, schedule)
+    }
+
+    /// Sets the share value that vestings processed from now on are taxed at.
+    pub fn revalue_equity(&mut self, id: u32, fair_value: f64) -> Result<&EquityGrant, PayrollError> {
+        self.equity_grants.revalue(id, fair_value)
+    }
+
+    /// Forfeits the units of a grant that have not vested by `on`, e.g. when the employee leaves.
+    pub fn forfeit_equity(&mut self, id: u32, on: NaiveDate) -> Result<&EquityGrant, PayrollError> {
+        self.equity_grants.forfeit(id, on)
+    }
+
+    /// Grants of one employee, or of everyone, by employee and grant date.
+    pub fn equity_grants_of(&self, employee_id: Option<&str>) -> Vec<&EquityGrant> {
+        self.equity_grants.of(employee_id)
+    }
+
+    /// Every vesting falling in `period`, with its grant.
+    pub fn vesting_in(&self, period: &PayPeriod) -> Vec<(&EquityGrant, VestingEvent)> {
+        self.equity_grants.vesting_in(period)
+    }
+
+    /// Records a benefit in kind provided to an employee from `start` on.
+    pub fn provide_natura(
+        &mut self,
+        employee_id: &str,
+        kind: NaturaKind,
+        description: &str,
+        valuation: NaturaValuation,
+        start: PayPeriod,
+    ) -> Result<&NaturaItem, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.ensure_period_open(&start.to_string())?;
+        self.natura.provide(employee_id, kind, description, valuation, start)
+    }
+
+    /// Stops a benefit in kind after `last`.
+    pub fn end_natura(&mut self, id: u32, last: PayPeriod) -> Result<&NaturaItem, PayrollError> {
+        self.ensure_period_open(&last.next().to_string())?;
+        self.natura.end(id, last)
+    }
+
+    /// Benefits in kind of one employee, or of everyone, by employee and start.
+    pub fn natura_of(&self, employee_id: Option<&str>) -> Vec<&NaturaItem> {
+        self.natura.of(employee_id)
+    }
+
+    /// Puts an employee on probation until `end` (inclusive).
+    pub fn start_probation(&mut self, employee_id: &str, end: NaiveDate) -> Result<&EmployeeData, PayrollError> {
+        let employee = self.employee_mut(employee_id)?;
+        let profile = employee.profile_mut();
+        profile.status = EmployeeStatus::Probation;
+        profile.probation_end = Some(end);
+        Ok(employee)
+    }
+
+    /// Converts an employee on probation to permanent. With `new_rate`, their base salary (hourly
+    /// rate for contract employees) is revised at the same time.
+    pub fn confirm_employee(
+        &mut self,
+        employee_id: &str,
+        new_rate: Option<f64>,
+    ) -> Result<&EmployeeData, PayrollError> {
+        let employee = self.employee_mut(employee_id)?;
+        if employee.as_employee().profile().status != EmployeeStatus::Probation {
+            return Err(PayrollError::NotOnProbation(employee_id.to_string()));
+        }
+        employee.profile_mut().status = EmployeeStatus::Active;
+        if let Some(rate) = new_rate {
+            employee.set_base_rate(rate);
+        }
+        Ok(employee)
+    }
+
+    /// Employees on probation whose period ends on or before `until`, soonest first.
+    pub fn probation_reviews_due(&self, until: NaiveDate) -> Vec<&EmployeeData> {
+        let mut due: Vec<&EmployeeData> = self
+            .employees
+            .iter()
+            .filter(|employee| {
+                let profile = employee.as_employee().profile();
+                profile.status == EmployeeStatus::Probation && profile.probation_end.is_none_or(|end| end <= until)
+            })
+            .collect();
+        due.sort_by_key(|employee| employee.as_employee().profile().probation_end);
+        due
+    }
+
+    /// Proposes a new rate (and grade) for an employee from `effective` on, to be decided by `approver`.
+    pub fn request_salary_change(
+        &mut self,
+        employee_id: &str,
+        rate: f64,
+        grade: Option<String>,
+        effective: PayPeriod,
+        approver: &str,
+    ) -> Result<&SalaryChangeRequest, PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.salary_changes.request(employee_id, rate, grade, effective, approver)
+    }
+
+    pub fn salary_change(&self, id: u32) -> Option<&SalaryChangeRequest> {
+        self.salary_changes.get(id)
+    }
+
+    /// Approves a pending change and writes it into the salary history. Changes already in effect
+    /// this month are applied to the employee right away; later ones when their period is processed.
+    pub fn approve_salary_change(&mut self, id: u32, approver: &str) -> Result<&SalaryHistoryEntry, PayrollError> {
+        let request = self.salary_changes.decide(id, approver, ChangeRequestStatus::Approved)?.clone();
+        let previous_rate = match self.salary_in_effect(&request.employee_id, &request.effective) {
+            Some(entry) => entry.rate,
+            None => self
+                .get_employee(&request.employee_id)
+                .map(EmployeeData::base_rate)
+                .ok_or_else(|| PayrollError::EmployeeNotFound(request.employee_id.clone()))?,
+        };
+        self.salary_history.push(SalaryHistoryEntry {
+            employee_id: request.employee_id.clone(),
+            effective: request.effective,
+            previous_rate,
+            rate: request.rate,
+            grade: request.grade.clone(),
+            request_id: request.id,
+            approved_by: request.approver.clone(),
+            recorded: Utc::now(),
+        });
+        if request.effective <= PayPeriod::from_date(Utc::now().date_naive()) {
+            self.sync_salary(&request.employee_id, &request.effective);
+        }
+        Ok(self.salary_history.last().expect("entry was just pushed"))
+    }
+
+    pub fn reject_salary_change(&mut self, id: u32, approver: &str, reason: &str) -> Result<(), PayrollError> {
+        let status = ChangeRequestStatus::Rejected(reason.trim().to_string());
+        self.salary_changes.decide(id, approver, status).map(|_| ())
+    }
+
+    /// Pending change requests, oldest first.
+    pub fn pending_salary_changes(&self) -> Vec<&SalaryChangeRequest> {
+        self.salary_changes.pending()
+    }
+
+    /// An employee's salary history, oldest effective period first.
+    pub fn salary_history_for(&self, employee_id: &str) -> Vec<&SalaryHistoryEntry> {
+        self.salary_history.of(employee_id)
+    }
+
+    /// The latest approved change in effect in `period`, if any.
+    pub fn salary_in_effect(&self, employee_id: &str, period: &PayPeriod) -> Option<&SalaryHistoryEntry> {
+        self.salary_history.in_effect(employee_id, period)
+    }
+
+    /// Sets the rate and grade in effect in `period` on `employee`. Periods before the first
+    /// approved change get the rate that change replaced.
+    fn with_salary_in_effect(&self, employee: EmployeeData, pay_period: &str) -> EmployeeData {
+        self.salary_history.apply_to(employee, pay_period)
+    }
+
+    /// Brings the registered employee up to the salary in effect in `period`, unless a later
+    /// period has already been applied.
+    fn sync_salary(&mut self, employee_id: &str, period: &PayPeriod) {
+        let latest = self.salary_history_for(employee_id).last().map(|entry| entry.effective);
+        if latest.is_some_and(|latest| latest > *period) {
+            return;
+        }
+        let Some(index) = self
+            .employees
+            .iter()
+            .position(|employee| employee.as_employee().employee_id() == employee_id)
+        else {
+            return;
+        };
+        let employee = self.employees[index].clone();
+        self.employees[index] = self.with_salary_in_effect(employee, &period.to_string());
+    }
+
+    pub fn start_review_cycle(&mut self, name: &str) -> Result<&ReviewCycle, PayrollError> {
+        self.review_cycles.start(name)
+    }
+
+    pub fn review_cycle(&self, name: &str) -> Option<&ReviewCycle> {
+        self.review_cycles.get(name)
+    }
+
+    /// Records (or replaces) an employee's score in an open review cycle.
+    pub fn record_review(
+        &mut self,
+        cycle: &str,
+        employee_id: &str,
+        score: f64,
+        reviewer: Option<String>,
+    ) -> Result<(), PayrollError> {
+        if self.get_employee(employee_id).is_none() {
+            return Err(PayrollError::EmployeeNotFound(employee_id.to_string()));
+        }
+        self.review_cycles.record(cycle, employee_id, score, reviewer)
+    }
+
+    /// Freezes a cycle's scores.
+    pub fn close_review_cycle(&mut self, name: &str) -> Result<(), PayrollError> {
+        self.review_cycles.close(name)
+    }
+
+    /// Bonuses for the active employees scored in `cycle`. Full-time employees' monthly base is
+    /// their base salary; contract employees' is their hourly rate over the standard monthly hours.
+    pub fn bonus_run(&self, cycle: &str, policy: &BonusPolicy) -> Result<BonusRun, PayrollError> {
+        let cycle = self
+            .review_cycle(cycle)
+            .ok_or_else(|| PayrollError::ReviewCycleNotFound(cycle.trim().to_string()))?;
+        let mut run = BonusRun {
+            cycle: cycle.name.clone(),
+            target_months: policy.target_months,
+            lines: Vec::new(),
+            unreviewed: Vec::new(),
+        };
+        for employee in &self.employees {
+            let emp = employee.as_employee();
+            if !matches!(emp.profile().status, EmployeeStatus::Active | EmployeeStatus::Probation) {
+                continue;
+            }
+            let Some(review) = cycle.review(emp.employee_id()) else {
+                run.unreviewed.push(emp.employee_id().to_string());
+                continue;
+            };
+            let monthly_base = match employee {
+                EmployeeData::Fulltime(fulltime) => fulltime.base_salary,
+                EmployeeData::Contract(contract) => contract.hourly_rate * self.settings.standard_monthly_hours,
+            };
+            let multiplier = policy.multiplier(review.score);
+            run.lines.push(BonusLine {
+                employee_id: emp.employee_id().to_string(),
+                name: emp.profile().name.clone(),
+                score: review.score,
+                multiplier,
+                monthly_base,
+                bonus: monthly_base * policy.target_months * multiplier,
+            });
+        }
+        Ok(run)
+    }
+
+    /// Calculates and stores the employee's record for `pay_period`. An employee has at most one
+    /// active record per period: while one is not voided, this fails with `AlreadyProcessed`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(employee_id = %employee.as_employee().employee_id(), pay_period = %pay_period), err(Display)))]
+    pub fn process_payroll(
+        &mut self,
+        employee: EmployeeData,
+        pay_period: String,
+    ) -> Result<PayrollData, PayrollError> {
+        self.ensure_period_open(&pay_period)?;
+        let employee_id = employee.as_employee().employee_id();
+        if self.find_record_index(employee_id, &pay_period).is_some() {
+            return Err(PayrollError::AlreadyProcessed(employee_id.to_string(), pay_period.trim().to_string()));
+        }
+        self.timesheets.ensure_approved(employee_id, &pay_period)?;
+        let mut payroll_data = self.calculate_record(employee, &pay_period);
+        if let Ok(period) = pay_period.parse::<PayPeriod>() {
+            self.sync_salary(payroll_data.employee.as_employee().employee_id(), &period);
+        }
+        self.assign_record_id(&mut payroll_data);
+        self.payroll_records.push(payroll_data.clone());
+        trace_event!(info, record_index = self.payroll_records.len() - 1, net = payroll_data.net_salary, "payroll record stored");
+        Ok(payroll_data)
+    }
+
+    /// Processes several employees for one period. Employees already processed for it (or listed
+    /// twice) with the same hours and allowance are skipped, so running the same batch again stores
+    /// nothing new; only the records stored are returned. An employee processed or listed with other
+    /// hours or another allowance fails the batch, as `batch::process_hours` rejects such rows.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(employees = employees.len(), pay_period = %pay_period), err(Display)))]
+    pub fn process_batch(
+        &mut self,
+        employees: Vec<EmployeeData>,
+        pay_period: &str,
+    ) -> Result<Vec<PayrollData>, PayrollError> {
+        self.ensure_period_open(pay_period)?;
+        let same_input = |a: &EmployeeData, b: &EmployeeData| {
+            let (a, b) = (a.as_employee(), b.as_employee());
+            a.work_hour() == b.work_hour() && a.tunjangan() == b.tunjangan()
+        };
+        let mut pending: Vec<EmployeeData> = Vec::new();
+        for employee in employees {
+            let employee_id = employee.as_employee().employee_id();
+            let earlier = match pending.iter().find(|other| other.as_employee().employee_id() == employee_id) {
+                Some(listed) => Some(listed),
+                None => self
+                    .find_record_index(employee_id, pay_period)
+                    .map(|index| &self.payroll_records[index].employee),
+            };
+            match earlier {
+                None => pending.push(employee),
+                Some(earlier) if same_input(earlier, &employee) => {}
+                Some(_) => {
+                    return Err(PayrollError::AlreadyProcessed(
+                        employee_id.to_string(),
+                        pay_period.trim().to_string(),
+                    ))
+                }
+            }
+        }
+        let employees = pending;
+        for employee in &employees {
+            self.timesheets.ensure_approved(employee.as_employee().employee_id(), pay_period)?;
+        }
+        let (employees, overtime): (Vec<EmployeeData>, Vec<Option<OvertimeCheck>>) = employees
+            .into_iter()
+            .map(|employee| {
+                let employee = self.with_timesheet_hours(self.with_salary_in_effect(employee, pay_period), pay_period);
+                self.with_overtime_policy(employee, pay_period)
+            })
+            .unzip();
+        if let Ok(period) = pay_period.parse::<PayPeriod>() {
+            for employee in &employees {
+                self.sync_salary(employee.as_employee().employee_id(), &period);
+            }
+        }
+        let employees = employees
+            .into_iter()
+            .map(|employee| {
+                let settings = self.settings_for(&employee, pay_period);
+                (employee, settings)
+            })
+            .collect();
+        let mut records = PayrollData::compute_batch_each(employees, pay_period, &self.rules);
+        for (record, overtime) in records.iter_mut().zip(overtime) {
+            record.overtime = overtime;
+            self.apply_extras(record);
+        }
+        for record in &mut records {
+            self.assign_record_id(record);
+            self.payroll_records.push(record.clone());
+        }
+        Ok(records)
+    }
+
+    /// The rows [`terminate_employees`](Self::terminate_employees) would reject, with the reason.
+    pub fn check_terminations(&self, rows: &[TerminationRow]) -> Vec<RejectedRow> {
+        let mut rejected = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            let termination = &row.termination;
+            let period = termination.period().to_string();
+            let reason = if let Some(first) = rows[..index]
+                .iter()
+                .find(|other| other.termination.employee_id == termination.employee_id)
+            {
+                Some(format!("duplicate row (first on line {})", first.line))
+            } else {
+                match self.get_employee(&termination.employee_id) {
+                    None => Some("employee not registered".to_string()),
+                    Some(employee) if employee.as_employee().profile().status == EmployeeStatus::Terminated => {
+                        Some("already terminated".to_string())
+                    }
+                    Some(_) if self.find_record_index(&termination.employee_id, &period).is_some() => {
+                        Some(format!("already processed for {}; void that record first", period))
+                    }
+                    Some(_) => self
+                        .ensure_period_open(&period)
+                        .and_then(|()| self.timesheets.ensure_approved(&termination.employee_id, &period))
+                        .err()
+                        .map(|err| err.to_string()),
+                }
+            };
+            if let Some(reason) = reason {
+                rejected.push(RejectedRow {
+                    line: row.line,
+                    employee_id: termination.employee_id.clone(),
+                    reason,
+                });
+            }
+        }
+        rejected
+    }
+
+    /// Terminates several employees at once, e.g. in a layoff. Each gets a final payroll record for
+    /// the period of their last day carrying their severance and its final tax, and is marked
+    /// terminated. Every row is checked first; if any is rejected, nothing is changed, and a row that
+    /// fails to process undoes the rows before it, salary updates and record numbers included.
+    pub fn terminate_employees(&mut self, rows: Vec<TerminationRow>) -> Result<TerminationOutcome, PayrollError> {
+        let rejected = self.check_terminations(&rows);
+        if !rejected.is_empty() {
+            return Ok(TerminationOutcome {
+                records: Vec::new(),
+                rejected,
+            });
+        }
+
+        // Processing brings registered salaries up to date and numbers the records as it goes; all of
+        // it is undone when a row fails.
+        let employees = self.employees.clone();
+        let stored = self.payroll_records.len();
+        let sequence = self.record_sequence;
+        let mut records = Vec::new();
+        for row in &rows {
+            let termination = &row.termination;
+            let mut employee = self
+                .get_employee(&termination.employee_id)
+                .cloned()
+                .expect("terminated employees were checked");
+            if let Some(hours) = termination.work_hours {
+                match &mut employee {
+                    EmployeeData::Fulltime(emp) => emp.work_hour = hours,
+                    EmployeeData::Contract(emp) => emp.work_hour = hours,
+                }
+            }
+            let settings = self.settings_for(&employee, &termination.period().to_string());
+            let severance = Severance::calculate(&employee, termination, &settings);
+            if let Err(err) = self.process_payroll(employee, termination.period().to_string()) {
+                self.employees = employees;
+                self.payroll_records.truncate(stored);
+                self.record_sequence = sequence;
+                return Err(err);
+            }
+            let index = self.payroll_records.len() - 1;
+            let record = self.payroll_records.update(index, |record| {
+                severance.apply(record);
+                record.clone()
+            });
+            records.push((record.expect("record was just stored"), severance));
+        }
+        for ((record, severance), row) in records.iter().zip(&rows) {
+            let termination = &row.termination;
+            self.employee_mut(&termination.employee_id)?.profile_mut().status = EmployeeStatus::Terminated;
+            self.terminations.push(TerminationRecord {
+                employee_id: termination.employee_id.clone(),
+                hired: termination.hired,
+                last_day: termination.last_day,
+                reason: termination.reason,
+                years_of_service: severance.years_of_service,
+                severance: severance.total(),
+                tax: severance.tax,
+                processed: record.processed_date,
+            });
+        }
+        Ok(TerminationOutcome {
+            records: records.into_iter().map(|(record, _)| record).collect(),
+            rejected,
+        })
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err(Display)))]
+    pub fn void_record(&mut self, index: usize) -> Result<(), PayrollError> {
+        let record = self
+            .payroll_records
+            .get(index)
+            .ok_or(PayrollError::RecordNotFound(index))?;
+        self.ensure_period_open(&record.pay_period)?;
+        if record.voided {
+            return Err(PayrollError::RecordVoided(index));
+        }
+        self.payroll_records.update(index, |record| record.voided = true);
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err(Display)))]
+    pub fn restore_record(&mut self, index: usize) -> Result<(), PayrollError> {
+        let record = self
+            .payroll_records
+            .get(index)
+            .ok_or(PayrollError::RecordNotFound(index))?;
+        self.ensure_period_open(&record.pay_period)?;
+        if !record.voided {
+            return Err(PayrollError::RecordNotVoided(index));
+        }
+        self.payroll_records.update(index, |record| record.voided = false);
+        Ok(())
+    }
+
+    /// Recalculates a stored record from edited employee details the way processing calculates one,
+    /// keeping its id so payslips and their verification codes still find it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(index, employee_id = %employee.as_employee().employee_id()), err(Display)))]
+    pub fn edit_record(
+        &mut self,
+        index: usize,
+        employee: EmployeeData,
+    ) -> Result<&PayrollData, PayrollError> {
+        let record = self
+            .payroll_records
+            .get(index)
+            .ok_or(PayrollError::RecordNotFound(index))?;
+        self.ensure_period_open(&record.pay_period)?;
+        if record.voided {
+            return Err(PayrollError::RecordVoided(index));
+        }
+        let pay_period = record.pay_period.clone();
+        let record_id = record.record_id.clone();
+        self.timesheets.ensure_approved(employee.as_employee().employee_id(), &pay_period)?;
+        let mut edited = self.calculate_record(employee, &pay_period);
+        if let Ok(period) = pay_period.parse::<PayPeriod>() {
+            self.sync_salary(edited.employee.as_employee().employee_id(), &period);
+        }
+        edited.record_id = record_id;
+        self.payroll_records.update(index, |record| *record = edited);
+        Ok(&self.payroll_records[index])
+    }
+
+    /// Gives a new record the id of the next number in the record sequence.
+    fn assign_record_id(&mut self, record: &mut PayrollData) {
+        record.record_id = self.next_record_id(record.employee.as_employee().employee_id(), &record.pay_period);
+    }
+
+    fn next_record_id(&mut self, employee_id: &str, pay_period: &str) -> String {
+        let id = PayrollData::sequence_id(employee_id, pay_period, self.record_sequence);
+        self.record_sequence += 1;
+        id
+    }
+
+    pub fn find_record_by_id(&self, record_id: &str) -> Option<&PayrollData> {
+        self.payroll_records.iter().find(|record| record.record_id == record_id)
+    }
+
+    pub fn find_record_index(&self, employee_id: &str, pay_period: &str) -> Option<usize> {
+        self.payroll_records.iter().position(|record| {
+            !record.voided
+                && record.employee.as_employee().employee_id() == employee_id
+                && Self::same_period(&record.pay_period, pay_period)
+        })
+    }
+
+    pub fn compare_periods(&self, previous_period: &str, current_period: &str) -> PeriodComparison {
+        PayrollReport::compare_periods(&self.payroll_records, previous_period, current_period)
+    }
+
+    /// The cost trend of the records, with purged periods taken from their summaries.
+    pub fn cost_trend(&self, from: PayPeriod, to: PayPeriod, rates: &BpjsRates) -> Vec<MonthlyCost> {
+        let mut months = PayrollReport::cost_trend(&self.payroll_records, from, to, rates);
+        for month in &mut months {
+            if let Some(summary) = self.period_summary(month.period) {
+                *month = summary.monthly_cost(month.period);
+            }
+        }
+        months
+    }
+
+    /// Totals of the period's active records, or its summary once they have been purged.
+    pub fn period_report(&self, period: PayPeriod) -> PeriodReport {
+        match self.period_summary(period) {
+            Some(summary) => summary.report(),
+            None => self.payroll_records.read_models().period_report(period),
+        }
+    }
+
+    /// The employee's totals over the year's active records.
+    pub fn year_to_date(&self, employee_id: &str, year: i32) -> YearToDate {
+        self.payroll_records.read_models().year_to_date(employee_id, year)
+    }
+
+    /// Department totals of the pay periods `from` to `to`, by period and then department, purged
+    /// periods included.
+    pub fn department_totals(&self, from: PayPeriod, to: PayPeriod) -> Vec<DepartmentMonth> {
+        let mut months: BTreeMap<(String, Option<String>), DepartmentMonth> = self
+            .payroll_records
+            .read_models()
+            .departments(from, to)
+            .map(|month| ((month.pay_period.clone(), month.department.clone()), month.clone()))
+            .collect();
+        for summary in self.period_summaries.iter() {
+            if !summary.period().is_some_and(|period| (from..=to).contains(&period)) {
+                continue;
+            }
+            for totals in &summary.departments {
+                months
+                    .entry((summary.pay_period.clone(), totals.department.clone()))
+                    .or_insert_with(|| DepartmentMonth::new(&summary.pay_period, totals.department.clone()))
+                    .add(totals);
+            }
+        }
+        months.into_values().collect()
+    }
+
+    pub fn period_summary(&self, period: PayPeriod) -> Option<&PeriodSummary> {
+        self.period_summaries.get(period)
+    }
+
+    /// What [`purge_records`](Self::purge_records) would remove on `today`, without changing anything.
+    pub fn purge_preview(&self, years: u32, today: NaiveDate, rates: &BpjsRates) -> PurgeReport {
+        let cutoff = retention::purge_cutoff(today, years);
+        let before = |period: PayPeriod| period < cutoff;
+        let parsed = |period: &str| period.parse::<PayPeriod>().ok();
+        let mut periods: Vec<PayPeriod> = self
+            .payroll_records
+            .iter()
+            .filter_map(|record| parsed(&record.pay_period))
+            .filter(|&period| before(period))
+            .collect();
+        periods.sort();
+        periods.dedup();
+        let now = Utc::now();
+        let other_entries = self.timesheets.iter().filter(|t| before(t.period)).count()
+            + self.attendance.iter().filter(|a| before(PayPeriod::from_date(a.date))).count()
+            + self.overtime_requests.iter().filter(|r| before(r.period)).count()
+            + self.travel.iter().filter(|t| before(t.period)).count()
+            + self.expense_claims.iter().filter(|c| before(c.period)).count();
+        PurgeReport {
+            cutoff,
+            summaries: periods
+                .into_iter()
+                .map(|period| PeriodSummary::summarize(&self.payroll_records, period, rates, now))
+                .collect(),
+            runs: self
+                .runs
+                .iter()
+                .filter(|run| parsed(&run.pay_period).is_some_and(before))
+                .count(),
+            other_entries,
+        }
+    }
+
+    /// Removes the detailed records of pay periods that ended more than `years` before `today`,
+    /// keeping a [`PeriodSummary`] of each, together with their runs, timesheets, attendance,
+    /// overtime requests, travel and expense claims. The periods are closed so nothing is processed
+    /// into them again. Employees, salary history and terminations are kept.
+    pub fn purge_records(&mut self, years: u32, today: NaiveDate, rates: &BpjsRates) -> PurgeReport {
+        let report = self.purge_preview(years, today, rates);
+        let before = |period: &str| period.parse::<PayPeriod>().is_ok_and(|period| period < report.cutoff);
+        let kept: Vec<Option<usize>> = self
+            .payroll_records
+            .iter()
+            .scan(0, |next, record| {
+                Some(match before(&record.pay_period) {
+                    true => None,
+                    false => {
+                        *next += 1;
+                        Some(*next - 1)
+                    }
+                })
+            })
+            .collect();
+        self.payroll_records.retain(|record| !before(&record.pay_period));
+        self.runs.retain(|run| !before(&run.pay_period));
+        for run in &mut self.runs {
+            run.record_indices = run
+                .record_indices
+                .iter()
+                .filter_map(|&index| kept.get(index).copied().flatten())
+                .collect();
+        }
+        let cutoff = report.cutoff;
+        self.timesheets.purge_before(cutoff);
+        self.attendance.purge_before(cutoff);
+        self.overtime_requests.purge_before(cutoff);
+        self.travel.purge_before(cutoff);
+        self.expense_claims.purge_before(cutoff);
+        for summary in &report.summaries {
+            self.period_summaries.add(summary);
+            if let Some(period) = summary.period() {
+                self.close_period(&period.to_string());
+            }
+        }
+        report
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    /// Collects the period's records into a draft run, holding back those of employees whose
+    /// mandatory onboarding tasks are still open.
+    pub fn create_run(&mut self, pay_period: &str) -> Result<&PayrollRun, PayrollError> {
+        let mut record_indices = Vec::new();
+        let mut held: Vec<String> = Vec::new();
+        for (index, record) in self.payroll_records.iter().enumerate() {
+            if record.voided || !Self::same_period(&record.pay_period, pay_period) {
+                continue;
+            }
+            let employee_id = record.employee.as_employee().employee_id();
+            if self.is_onboarded(employee_id) {
+                record_indices.push(index);
+            } else if !held.iter().any(|held| held == employee_id) {
+                held.push(employee_id.to_string());
+            }
+        }
+        if record_indices.is_empty() {
+            return Err(match held.is_empty() {
+                true => PayrollError::NoRecordsInPeriod(pay_period.trim().to_string()),
+                false => PayrollError::OnboardingIncomplete(held),
+            });
+        }
+
+        let run = PayrollRun {
+            run_id: self.runs.iter().map(|run| run.run_id).max().unwrap_or(0) + 1,
+            pay_period: pay_period.trim().to_string(),
+            record_indices,
+            status: RunStatus::Draft,
+            created_date: Utc::now(),
+            approved_date: None,
+            deliveries: Vec::new(),
+            disbursement: None,
+            held,
+        };
+        self.runs.push(run);
+        Ok(self.runs.last().unwrap())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    pub fn approve_run(&mut self, run_id: u32) -> Result<&PayrollRun, PayrollError> {
+        let run = self
+            .runs
+            .iter_mut()
+            .find(|run| run.run_id == run_id)
+            .ok_or(PayrollError::RunNotFound(run_id))?;
+        if run.status == RunStatus::Approved {
+            return Err(PayrollError::RunAlreadyApproved(run_id));
+        }
+        run.status = RunStatus::Approved;
+        run.approved_date = Some(Utc::now());
+        Ok(run)
+    }
+
+    pub fn get_run(&self, run_id: u32) -> Option<&PayrollRun> {
+        self.runs.iter().find(|run| run.run_id == run_id)
+    }
+
+    pub fn run_records(&self, run: &PayrollRun) -> Vec<&PayrollData> {
+        run.record_indices
+            .iter()
+            .filter_map(|index| self.payroll_records.get(*index))
+            .filter(|record| !record.voided)
+            .collect()
+    }
+
+    pub fn register_for_run(&self, run_id: u32) -> Result<PayrollRegister, PayrollError> {
+        let run = self.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        let title = format!("Run #{} - {} ({:?})", run.run_id, run.pay_period, run.status);
+        Ok(PayrollRegister::from_records(title, self.run_records(run)))
+    }
+
+    /// The bank transfers paying out an approved run, following each employee's salary split.
+    pub fn bank_transfers_for_run(&self, run_id: u32) -> Result<BankTransferBatch, PayrollError> {
+        let run = self.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        if run.status != RunStatus::Approved {
+            return Err(PayrollError::RunNotApproved(run_id));
+        }
+        let reference = format!("SALARY {} RUN {}", run.pay_period, run.run_id);
+        Ok(BankTransferBatch::from_records(reference, &self.run_records(run), |employee_id| {
+            self.get_employee(employee_id)
+                .map(|employee| employee.as_employee().profile().salary_split.as_slice())
+        }))
+    }
+
+    /// Submits the run's transfers to `provider`. Every employee in the run needs a valid salary
+    /// split, and a run can only be submitted again after its previous batch failed.
+    pub fn disburse_run(
+        &mut self,
+        run_id: u32,
+        provider: &dyn DisbursementProvider,
+    ) -> Result<&Disbursement, PayrollError> {
+        let batch = self.bank_transfers_for_run(run_id)?;
+        let run = self.runs.iter_mut().find(|run| run.run_id == run_id).unwrap();
+        if run
+            .disbursement
+            .as_ref()
+            .is_some_and(|disbursement| disbursement.status != BatchStatus::Failed)
+        {
+            return Err(PayrollError::RunAlreadyDisbursed(run_id));
+        }
+        if let Some((employee_id, problem)) = batch.problems.first() {
+            return Err(PayrollError::Disbursement(format!(
+                "{} employees cannot be paid ({}: {})",
+                batch.problems.len(),
+                employee_id,
+                problem
+            )));
+        }
+        let batch_id = provider.create_batch(&batch).map_err(PayrollError::Disbursement)?;
+        Ok(run.disbursement.insert(Disbursement {
+            provider: provider.name().to_string(),
+            batch_id,
+            submitted: Utc::now(),
+            status: BatchStatus::Submitted,
+            confirmations: batch.transfers.iter().map(TransferConfirmation::pending).collect(),
+        }))
+    }
+
+    /// Asks the provider the run was submitted to for the batch status and transfer results.
+    pub fn refresh_disbursement(
+        &mut self,
+        run_id: u32,
+        provider: &dyn DisbursementProvider,
+    ) -> Result<&Disbursement, PayrollError> {
+        let run = self
+            .runs
+            .iter_mut()
+            .find(|run| run.run_id == run_id)
+            .ok_or(PayrollError::RunNotFound(run_id))?;
+        let disbursement = run
+            .disbursement
+            .as_mut()
+            .ok_or_else(|| PayrollError::Disbursement(format!("run #{} has not been submitted", run_id)))?;
+        if disbursement.provider != provider.name() {
+            return Err(PayrollError::Disbursement(format!(
+                "run #{} was submitted to {}, not {}",
+                run_id,
+                disbursement.provider,
+                provider.name()
+            )));
+        }
+        disbursement.status = provider
+            .batch_status(&disbursement.batch_id)
+            .map_err(PayrollError::Disbursement)?;
+        let confirmations = provider
+            .fetch_confirmations(&disbursement.batch_id)
+            .map_err(PayrollError::Disbursement)?;
+        for confirmation in confirmations {
+            let same = |existing: &&mut TransferConfirmation| {
+                existing.employee_id == confirmation.employee_id
+                    && existing.account_number == confirmation.account_number
+            };
+            match disbursement.confirmations.iter_mut().find(same) {
+                Some(existing) => *existing = confirmation,
+                None => disbursement.confirmations.push(confirmation),
+            }
+        }
+        Ok(disbursement)
+    }
+
+    pub fn remittance_for_run(&self, run_id: u32, rates: &BpjsRates) -> Result<RemittanceSummary, PayrollError> {
+        let run = self.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        let pay_period = run
+            .pay_period
+            .parse::<PayPeriod>()
+            .unwrap_or_else(|_| PayPeriod::from_date(run.created_date.date_naive()));
+        Ok(RemittanceSummary::from_records(pay_period, self.run_records(run), rates))
+    }
+
+    pub fn journal_for_run(
+        &self,
+        run_id: u32,
+        accounts: &AccountCodes,
+    ) -> Result<Vec<JournalLine>, PayrollError> {
+        let run = self.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        if run.status != RunStatus::Approved {
+            return Err(PayrollError::RunNotApproved(run_id));
+        }
+        Ok(journal_entries(run, &self.run_records(run), accounts))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, sender, options)))]
+    pub fn deliver_payslips(
+        &mut self,
+        run_id: u32,
+        sender: &dyn PayslipSender,
+        options: &PayslipOptions,
+    ) -> Result<&[PayslipDelivery], PayrollError> {
+        let run = self.get_run(run_id).ok_or(PayrollError::RunNotFound(run_id))?;
+        if run.status != RunStatus::Approved {
+            return Err(PayrollError::RunNotApproved(run_id));
+        }
+
+        let already_sent: Vec<String> = run
+            .deliveries
+            .iter()
+            .filter(|delivery| matches!(delivery.status, DeliveryStatus::Sent(_)))
+            .map(|delivery| delivery.employee_id.clone())
+            .collect();
+        let deliveries: Vec<PayslipDelivery> = self
+            .run_records(run)
+            .into_iter()
+            .filter(|record| !already_sent.iter().any(|id| id == record.employee.as_employee().employee_id()))
+            .map(|record| deliver(record, sender, options))
+            .collect();
+
+        let run = self.runs.iter_mut().find(|run| run.run_id == run_id).unwrap();
+        for delivery in deliveries {
+            match run
+                .deliveries
+                .iter_mut()
+                .find(|existing| existing.employee_id == delivery.employee_id)
+            {
+                Some(existing) => *existing = delivery,
+                None => run.deliveries.push(delivery),
+            }
+        }
+        Ok(&run.deliveries)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    pub fn close_period(&mut self, period: &str) {
+        if !self.is_period_closed(period) {
+            self.closed_periods.push(period.trim().to_string());
+        }
+    }
+
+    /// Reopens a closed period for `principal`, who needs the admin scope: authenticate them with
+    /// [`ApiAuthConfig::authenticate`](crate::auth::ApiAuthConfig::authenticate).
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, principal), fields(principal = %principal.name()), err(Display)))]
+    pub fn reopen_period(&mut self, period: &str, principal: &Principal) -> Result<(), PayrollError> {
+        if principal.require(Scope::Admin).is_err() {
+            return Err(PayrollError::PermissionDenied("reopening a pay period".to_string()));
+        }
+        let position = self
+            .closed_periods
+            .iter()
+            .position(|closed| Self::same_period(closed, period))
+            .ok_or_else(|| PayrollError::PeriodNotClosed(period.trim().to_string()))?;
+        self.closed_periods.remove(position);
+        Ok(())
+    }
+
+    pub fn closed_periods(&self) -> &[String] {
+        &self.closed_periods
+    }
+
+    pub fn is_period_closed(&self, period: &str) -> bool {
+        self.closed_periods
+            .iter()
+            .any(|closed| Self::same_period(closed, period))
+    }
+
+    fn ensure_period_open(&self, period: &str) -> Result<(), PayrollError> {
+        if self.is_period_closed(period) {
+            Err(PayrollError::PeriodClosed(period.trim().to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn same_period(a: &str, b: &str) -> bool {
+        PayPeriod::same_period(a, b)
+    }
+
+    pub fn get_payroll_records(&self) -> &[PayrollData] {
+        &self.payroll_records
+    }
+
+    /// Changes the `index`th stored record in place, e.g. to adjust it right after processing. Like
+    /// `edit_record`, it refuses voided records and records of closed periods.
+    pub(crate) fn update_record<R>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut PayrollData) -> R,
+    ) -> Result<R, PayrollError> {
+        let record = self
+            .payroll_records
+            .get(index)
+            .ok_or(PayrollError::RecordNotFound(index))?;
+        self.ensure_period_open(&record.pay_period)?;
+        if record.voided {
+            return Err(PayrollError::RecordVoided(index));
+        }
+        self.payroll_records.update(index, f).ok_or(PayrollError::RecordNotFound(index))
+    }
+
+    /// The id a record had before it was moved to `record_id`, e.g. by pseudonymizing its employee.
+    pub(crate) fn renamed_from(&self, record_id: &str) -> Option<&str> {
+        self.payroll_records.renamed_from(record_id)
+    }
+
+    /// The last payroll ledger entry the data file was saved with.
+    pub fn ledger_head(&self) -> Option<LedgerHead> {
+        self.ledger_head.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Set through `&self` while saving, like the revision.
+    pub(crate) fn set_ledger_head(&self, head: Option<LedgerHead>) {
+        *self.ledger_head.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = head;
+    }
+
+    /// The employees and periods whose totals changed since the last call, so results cached for
+    /// them outside the payroll can be dropped.
+    pub fn take_record_changes(&mut self) -> BTreeSet<RecordKey> {
+        self.payroll_records.take_changes()
+    }
+
+    pub fn into_payroll_records(self) -> Vec<PayrollData> {
+        self.payroll_records.into_vec()
+    }
+
+    pub fn query(&self) -> PayrollQuery<'_> {
+        PayrollQuery::new(self)
+    }
+
+    /// Distribution of gross and net over the records `filter` matches, e.g.
+    /// `payroll.statistics(&payroll.query().period("2024-09"))`.
+    pub fn statistics(&self, filter: &PayrollQuery<'_>) -> PayrollStatistics {
+        let records: Vec<&PayrollData> = self
+            .payroll_records
+            .iter()
+            .filter(|record| filter.matches(record))
+            .collect();
+        PayrollStatistics::from_records(&records)
+    }
+
+    /// Self-check of the calculation settings and of every record that is not voided; see
+    /// `invariants` for what is checked.
+    pub fn check_invariants(&self) -> Vec<Violation> {
+        let mut violations = invariants::check_settings(&self.settings);
+        for record in self.payroll_records.iter().filter(|record| !record.voided) {
+            violations.extend(invariants::check_record(record));
+        }
+        violations
+    }
+
+    /// Recomputes every record that is not voided with the calculation context it was stored with,
+    /// reporting the amounts that come out differently.
+    pub fn verify(&self) -> Verification {
+        let mut verification = Verification::default();
+        for (index, record) in self.payroll_records.iter().enumerate().filter(|(_, record)| !record.voided) {
+            if record.context.is_none() {
+                verification.unversioned += 1;
+                continue;
+            }
+            verification.checked += 1;
+            verification.mismatches.extend(verify::verify_record(index, record));
+        }
+        verification
+    }
+
+    pub fn search(&self, query: &str) -> SearchResults<'_> {
+        search(self, query)
+    }
+
+    /// Registered employees plus employees that only appear in payroll records (latest record wins).
+    pub fn known_employees(&self) -> Vec<&EmployeeData> {
+        let mut known: Vec<&EmployeeData> = self.employees.iter().collect();
+        for record in self.payroll_records.iter().rev() {
+            let employee_id = record.employee.as_employee().employee_id();
+            if !known.iter().any(|employee| employee.as_employee().employee_id() == employee_id) {
+                known.push(&record.employee);
+            }
+        }
+        known
+    }
+
+    pub fn match_employee_id(&self, input: &str) -> EmployeeIdMatch<'_> {
+        match_employee_id(&self.known_employees(), input)
+    }
+
+    pub fn get_period_records(&self, pay_period: &str) -> Vec<&PayrollData> {
+        self.query().period(pay_period).run().records
+    }
+
+    pub fn get_employee_payroll(&self, employee_id: &str) -> Vec<&PayrollData> {
+        self.query().employee(employee_id).include_voided(true).run().records
+    }
 }
 
-pub struct PayrollPresentation;
-
-impl PayrollPresentation {
-    pub fn print_payroll_summary(payroll_data: &PayrollData) {
-        let employee = payroll_data.employee.as_employee();
-        println!("=== Payroll Summary ===");
-        println!("Employee ID: {}", employee.employee_id());
-        println!("Employee Type: {}", employee.employee_type());
-        println!("Pay Period: {}", payroll_data.pay_period);
-        println!("Processed Date: {}", payroll_data.processed_date.format("%Y-%m-%d %H:%M:%S"));
-        println!("Work Hours: {}", employee.work_hour());
-        println!("Gross Salary: Rp {:.2}", payroll_data.gross_salary);
-        println!("Deductions: Rp {:.2}", payroll_data.deductions);
-        println!("Net Salary: Rp {:.2}", payroll_data.net_salary);
-        println!("{}", "-".repeat(40));
-    }
-
-    pub fn print_all_payrolls(payroll_records: &[PayrollData]) {
-        println!("=== ALL PAYROLL RECORDS ===\n");
-        let mut total_gross = 0.0;
-        let mut total_net = 0.0;
-
-        for record in payroll_records {
-            Self::print_payroll_summary(record);
-            total_gross += record.gross_salary;
-            total_net += record.net_salary;
-            println!();
-        }
-
-        println!("=== TOTAL SUMMARY ===");
-        println!("Total Employees: {}", payroll_records.len());
-        println!("Total Gross Payroll: Rp {:.2}", total_gross);
-        println!("Total Net Payroll: Rp {:.2}", total_net);
-        println!("Total Deductions: Rp {:.2}", total_gross - total_net);
-    }
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+
+    fn employee(employee_id: &str, base_salary: f64) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            employee_id.to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            base_salary,
+        ))
+    }
+
+    fn payroll() -> Payroll {
+        let mut payroll = Payroll::new();
+        payroll.add_employee(employee("E1", 9_000_000.0));
+        payroll.add_employee(employee("E2", 12_000_000.0));
+        payroll
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn records_keep_their_id_when_edited() {
+        let mut payroll = payroll();
+        let processed = payroll.process_payroll(employee("E1", 9_000_000.0), "2026-01".to_string()).unwrap();
+        let index = payroll.find_record_index("E1", "2026-01").unwrap();
+
+        let edited = payroll.edit_record(index, employee("E1", 10_000_000.0)).unwrap();
+        assert_eq!(edited.record_id, processed.record_id);
+        assert_eq!(edited.gross_salary, 10_000_000.0);
+        let found = payroll.find_record_by_id(&processed.record_id).unwrap();
+        assert_eq!(found.gross_salary, 10_000_000.0);
+
+        payroll.void_record(index).unwrap();
+        assert!(matches!(
+            payroll.edit_record(index, employee("E1", 11_000_000.0)),
+            Err(PayrollError::RecordVoided(_))
+        ));
+    }
+
+    #[test]
+    fn an_employee_is_processed_once_per_period() {
+        let mut payroll = payroll();
+        payroll.process_payroll(employee("E1", 9_000_000.0), "2026-01".to_string()).unwrap();
+        assert!(matches!(
+            payroll.process_payroll(employee("E1", 9_500_000.0), "January 2026".to_string()),
+            Err(PayrollError::AlreadyProcessed(employee_id, _)) if employee_id == "E1"
+        ));
+        assert_eq!(payroll.get_payroll_records().len(), 1);
+
+        payroll.void_record(0).unwrap();
+        payroll.process_payroll(employee("E1", 9_500_000.0), "2026-01".to_string()).unwrap();
+        assert_eq!(payroll.find_record_index("E1", "2026-01"), Some(1));
+    }
+
+    #[test]
+    fn records_are_only_updated_while_active_and_open() {
+        let mut payroll = payroll();
+        payroll.process_payroll(employee("E1", 9_000_000.0), "2026-01".to_string()).unwrap();
+        payroll.process_payroll(employee("E2", 12_000_000.0), "2026-01".to_string()).unwrap();
+        payroll.update_record(0, |record| record.net_salary += 1.0).unwrap();
+
+        payroll.void_record(0).unwrap();
+        assert!(matches!(payroll.update_record(0, |_| ()), Err(PayrollError::RecordVoided(0))));
+        payroll.close_period("2026-01");
+        assert!(matches!(payroll.update_record(1, |_| ()), Err(PayrollError::PeriodClosed(_))));
+        assert!(matches!(payroll.update_record(2, |_| ()), Err(PayrollError::RecordNotFound(2))));
+    }
+
+    #[test]
+    fn ids_are_never_given_out_twice() {
+        let mut payroll = payroll();
+        let first = payroll.process_payroll(employee("E1", 9_000_000.0), "2026-01".to_string()).unwrap();
+        payroll.void_record(0).unwrap();
+        let again = payroll.process_payroll(employee("E1", 9_000_000.0), "2026-01".to_string()).unwrap();
+        assert_ne!(first.record_id, again.record_id);
+    }
+
+    #[test]
+    fn batches_store_nothing_new_when_run_again() {
+        let mut payroll = payroll();
+        let employees = vec![employee("E1", 9_000_000.0), employee("E2", 12_000_000.0)];
+        assert_eq!(payroll.process_batch(employees.clone(), "2026-01").unwrap().len(), 2);
+        assert_eq!(payroll.process_batch(employees, "2026-01").unwrap().len(), 0);
+        assert_eq!(payroll.get_payroll_records().len(), 2);
+
+        let mut changed = employee("E1", 9_000_000.0);
+        if let EmployeeData::Fulltime(emp) = &mut changed {
+            emp.work_hour = 180.0;
+        }
+        assert!(matches!(
+            payroll.process_batch(vec![changed], "2026-01"),
+            Err(PayrollError::AlreadyProcessed(..))
+        ));
+    }
+
+    #[test]
+    fn records_kept_through_a_purge_keep_their_ids_and_runs() {
+        let mut payroll = payroll();
+        let old = payroll.process_payroll(employee("E1", 9_000_000.0), "2019-12".to_string()).unwrap();
+        let kept = payroll.process_payroll(employee("E2", 12_000_000.0), "2026-01".to_string()).unwrap();
+        payroll.runs.push(PayrollRun {
+            run_id: 1,
+            pay_period: "2026-01".to_string(),
+            record_indices: vec![1],
+            status: RunStatus::Draft,
+            created_date: Utc::now(),
+            approved_date: None,
+            deliveries: Vec::new(),
+            disbursement: None,
+            held: Vec::new(),
+        });
+
+        let report = payroll.purge_records(5, date(2026, 3, 1), &BpjsRates::default());
+        assert_eq!(report.cutoff, PayPeriod { year: 2021, month: 3 });
+        assert_eq!(report.summaries.len(), 1);
+        assert!(payroll.find_record_by_id(&old.record_id).is_none());
+        assert_eq!(payroll.find_record_by_id(&kept.record_id).unwrap().gross_salary, 12_000_000.0);
+        assert_eq!(payroll.runs[0].record_indices, [0]);
+        assert!(payroll.period_summary(PayPeriod { year: 2019, month: 12 }).is_some());
+        assert!(matches!(
+            payroll.process_payroll(employee("E1", 9_000_000.0), "2019-12".to_string()),
+            Err(PayrollError::PeriodClosed(_))
+        ));
+
+        let later = payroll.process_payroll(employee("E1", 9_000_000.0), "2026-02".to_string()).unwrap();
+        assert_ne!(later.record_id, old.record_id);
+        assert_ne!(later.record_id, kept.record_id);
+    }
+}
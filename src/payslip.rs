@@ -0,0 +1,416 @@
+use crate::auth::constant_time_eq;
+use crate::disbursement::allocate;
+use crate::employee::Employee;
+use crate::payroll::PayrollData;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Starts every verification code, naming its layout.
+pub const VERIFICATION_CODE_PREFIX: &str = "EMPSLIP1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayslipSeal {
+    pub hash: String,
+    pub signature: Option<String>,
+}
+
+impl PayslipSeal {
+    pub fn for_record(record: &PayrollData, signing_key: Option<&str>) -> Self {
+        let hash = record_hash(record);
+        let signature = signing_key.map(|key| sign(&hash, key));
+        Self { hash, signature }
+    }
+
+    pub fn verify(&self, record: &PayrollData, signing_key: Option<&str>) -> bool {
+        if !constant_time_eq(self.hash.as_bytes(), record_hash(record).as_bytes()) {
+            return false;
+        }
+        match (&self.signature, signing_key) {
+            (Some(signature), Some(key)) => constant_time_eq(signature.as_bytes(), sign(&self.hash, key).as_bytes()),
+            (None, _) => true,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// What a payslip's QR code holds, `EMPSLIP1:<record id>:<hash>[:<signature>]`, so whoever is
+/// shown the payslip can have it checked against the payroll history with `verify-payslip`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationCode {
+    pub record_id: String,
+    pub hash: String,
+    pub signature: Option<String>,
+}
+
+impl VerificationCode {
+    pub fn new(record: &PayrollData, seal: &PayslipSeal) -> Self {
+        Self {
+            record_id: record.record_id.clone(),
+            hash: seal.hash.clone(),
+            signature: seal.signature.clone(),
+        }
+    }
+}
+
+impl fmt::Display for VerificationCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", VERIFICATION_CODE_PREFIX, self.record_id, self.hash)?;
+        match &self.signature {
+            Some(signature) => write!(f, ":{}", signature),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Also accepts a bare verification hash, as printed on payslips, for codes typed in by hand.
+impl FromStr for VerificationCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a payslip verification code", s.trim());
+        let is_digest = |value: &str| value.len() == 64 && value.bytes().all(|byte| byte.is_ascii_hexdigit());
+        let trimmed = s.trim();
+        if is_digest(trimmed) {
+            return Ok(Self {
+                record_id: String::new(),
+                hash: trimmed.to_ascii_lowercase(),
+                signature: None,
+            });
+        }
+        let mut parts = trimmed.split(':');
+        if parts.next() != Some(VERIFICATION_CODE_PREFIX) {
+            return Err(invalid());
+        }
+        let record_id = parts.next().ok_or_else(invalid)?.to_string();
+        let hash = parts.next().filter(|hash| is_digest(hash)).ok_or_else(invalid)?;
+        let signature = match parts.next() {
+            Some(signature) if is_digest(signature) => Some(signature.to_ascii_lowercase()),
+            Some(_) => return Err(invalid()),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            record_id,
+            hash: hash.to_ascii_lowercase(),
+            signature,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayslipVerdict {
+    /// The payslip matches the stored record, and its signature the signing key.
+    Authentic,
+    /// The payslip matches the stored record; its signature was not checked for lack of a key.
+    SignatureNotChecked,
+    /// The signature was not made with the signing key, although the hash matches.
+    BadSignature,
+    /// The record has been changed since the payslip was issued, or the code was altered.
+    Altered,
+    /// The record was voided, so the payslip is no longer valid.
+    Voided,
+}
+
+impl PayslipVerdict {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, PayslipVerdict::Authentic | PayslipVerdict::SignatureNotChecked)
+    }
+}
+
+impl fmt::Display for PayslipVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PayslipVerdict::Authentic => "authentic",
+            PayslipVerdict::SignatureNotChecked => "matches the payroll history (signature not checked)",
+            PayslipVerdict::BadSignature => "signature does not match",
+            PayslipVerdict::Altered => "does not match the stored record",
+            PayslipVerdict::Voided => "record voided",
+        })
+    }
+}
+
+/// Looks the payslip `code` was scanned from up in `records`, by record ID or, for codes without
+/// one, by hash. `None` when no record matches at all.
+pub fn verify_payslip<'a>(
+    records: &'a [PayrollData],
+    code: &VerificationCode,
+    signing_key: Option<&str>,
+) -> Option<(&'a PayrollData, PayslipVerdict)> {
+    let record = match code.record_id.is_empty() {
+        false => records.iter().find(|record| record.record_id == code.record_id)?,
+        true => records.iter().find(|record| record_hash(record) == code.hash)?,
+    };
+    let seal = PayslipSeal {
+        hash: code.hash.clone(),
+        signature: code.signature.clone(),
+    };
+    let verdict = if record.voided {
+        PayslipVerdict::Voided
+    } else if seal.hash != record_hash(record) {
+        PayslipVerdict::Altered
+    } else if seal.signature.is_some() && signing_key.is_none() {
+        PayslipVerdict::SignatureNotChecked
+    } else if !seal.verify(record, signing_key) {
+        PayslipVerdict::BadSignature
+    } else {
+        PayslipVerdict::Authentic
+    };
+    Some((record, verdict))
+}
+
+/// The QR code of `code` as a square of modules, row by row, `true` for dark ones.
+#[cfg(feature = "qr")]
+pub fn qr_modules(code: &VerificationCode) -> (usize, Vec<bool>) {
+    let qr = qrcode::QrCode::with_error_correction_level(code.to_string(), qrcode::EcLevel::M)
+        .expect("a verification code fits in a QR code");
+    let modules = qr.to_colors().into_iter().map(|color| color == qrcode::Color::Dark).collect();
+    (qr.width(), modules)
+}
+
+/// The QR code of `code` as an inline SVG image, with the four-module quiet zone scanners need.
+#[cfg(feature = "qr")]
+pub fn qr_svg(code: &VerificationCode) -> String {
+    let (width, modules) = qr_modules(code);
+    let mut path = String::new();
+    for (index, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+        path.push_str(&format!("M{} {}h1v1h-1z", index % width + 4, index / width + 4));
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" width=\"160\" height=\"160\" \
+         shape-rendering=\"crispEdges\"><rect width=\"{size}\" height=\"{size}\" fill=\"#fff\"/>\
+         <path fill=\"#000\" d=\"{path}\"/></svg>",
+        size = width + 8,
+        path = path
+    )
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn record_hash(record: &PayrollData) -> String {
+    let bytes = serde_json::to_vec(record).expect("payroll record serializes");
+    to_hex(&Sha256::digest(bytes))
+}
+
+fn sign(hash: &str, key: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(hash.as_bytes());
+    to_hex(&mac.finalize().into_bytes())
+}
+
+pub fn payslip_password(employee: &dyn Employee) -> Option<String> {
+    employee
+        .profile()
+        .date_of_birth
+        .map(|date| date.format("%d%m%Y").to_string())
+}
+
+pub fn payslip_lines(record: &PayrollData) -> Vec<(String, String)> {
+    let employee = record.employee.as_employee();
+    let breakdown = record.breakdown();
+    let money = |amount: f64| format!("Rp {:.2}", amount);
+    let mut lines = vec![
+        ("Employee".to_string(), display_name(employee).to_string()),
+        ("Employee ID".to_string(), employee.employee_id().to_string()),
+        ("Employee Type".to_string(), employee.employee_type().to_string()),
+        ("Pay Period".to_string(), record.pay_period.clone()),
+        ("Work Hours".to_string(), employee.work_hour().to_string()),
+    ];
+    for component in &record.extra_earnings {
+        lines.push((component.name.clone(), money(component.amount)));
+    }
+    lines.extend([
+        ("Gross Salary".to_string(), money(record.gross_salary)),
+        (record.tax_name().to_string(), money(breakdown.tax)),
+        ("BPJS Kesehatan".to_string(), money(breakdown.bpjs_kesehatan)),
+        ("BPJS Ketenagakerjaan".to_string(), money(breakdown.bpjs_ketenagakerjaan)),
+    ]);
+    for component in &record.extra_deductions {
+        lines.push((component.name.clone(), money(component.amount)));
+    }
+    lines.extend([
+        ("Total Deductions".to_string(), money(record.deductions)),
+        ("Net Salary".to_string(), money(record.net_salary)),
+    ]);
+    lines
+}
+
+fn display_name(employee: &dyn Employee) -> &str {
+    if employee.profile().name.is_empty() {
+        employee.employee_id()
+    } else {
+        &employee.profile().name
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+pub fn payslip_file_name(record: &PayrollData, extension: &str) -> String {
+    let employee_id = record.employee.as_employee().employee_id();
+    let period: String = record
+        .pay_period
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("payslip_{}_{}.{}", employee_id, period, extension)
+}
+
+pub fn render_html(record: &PayrollData, seal: &PayslipSeal) -> String {
+    let employee = record.employee.as_employee();
+    let breakdown = record.breakdown();
+    let name = display_name(employee);
+    let row = |label: &str, amount: f64| {
+        format!(
+            "<tr><td>{}</td><td style=\"text-align:right\">Rp {:.2}</td></tr>\n",
+            label, amount
+        )
+    };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Payslip</title></head>\n<body>\n");
+    html.push_str(&format!("<h2>Payslip - {}</h2>\n", escape_html(&record.pay_period)));
+    html.push_str(&format!(
+        "<p>Employee: {} ({})<br>Type: {}<br>Work Hours: {}<br>Processed: {}</p>\n",
+        escape_html(name),
+        escape_html(employee.employee_id()),
+        employee.employee_type(),
+        employee.work_hour(),
+        record.processed_date.format("%Y-%m-%d")
+    ));
+    html.push_str("<table>\n");
+    for component in &record.extra_earnings {
+        html.push_str(&row(&escape_html(&component.name), component.amount));
+    }
+    html.push_str(&row("Gross Salary", record.gross_salary));
+    html.push_str(&row(record.tax_name(), breakdown.tax));
+    html.push_str(&row("BPJS Kesehatan", breakdown.bpjs_kesehatan));
+    html.push_str(&row("BPJS Ketenagakerjaan", breakdown.bpjs_ketenagakerjaan));
+    for component in &record.extra_deductions {
+        html.push_str(&row(&escape_html(&component.name), component.amount));
+    }
+    html.push_str(&row("Total Deductions", record.deductions));
+    html.push_str(&row("<strong>Net Salary</strong>", record.net_salary));
+    if let Ok(allocations) = allocate(&employee.profile().salary_split, record.net_salary) {
+        for (split, amount) in allocations {
+            let label = format!("Paid to {} {}", escape_html(&split.bank), split.masked_account());
+            html.push_str(&row(&label, amount));
+        }
+    }
+    html.push_str("</table>\n");
+    html.push_str(&format!("<p><small>Verification hash: {}", seal.hash));
+    if let Some(signature) = &seal.signature {
+        html.push_str(&format!("<br>Signature: {}", signature));
+    }
+    html.push_str("</small></p>\n");
+    #[cfg(feature = "qr")]
+    html.push_str(&format!(
+        "<p>{}<br><small>Scan to verify this payslip</small></p>\n",
+        qr_svg(&VerificationCode::new(record, seal))
+    ));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::EmployeeData;
+
+    const KEY: &str = "signing-key";
+
+    fn record(record_id: &str) -> PayrollData {
+        let employee = FulltimeEmployee::new("E1".to_string(), 173.0, 0.0, AllowancePeriod::Monthly, 9_000_000.0);
+        let mut record = PayrollData::new(EmployeeData::Fulltime(employee), "2026-01".to_string());
+        record.record_id = record_id.to_string();
+        record
+    }
+
+    fn code(record: &PayrollData, signing_key: Option<&str>) -> VerificationCode {
+        VerificationCode::new(record, &PayslipSeal::for_record(record, signing_key))
+    }
+
+    #[test]
+    fn seals_fail_once_the_record_changes_or_under_another_key() {
+        let mut record = record("E1-2026-01-1");
+        let seal = PayslipSeal::for_record(&record, Some(KEY));
+        assert!(seal.verify(&record, Some(KEY)));
+        assert!(!seal.verify(&record, Some("other-key")));
+        assert!(!seal.verify(&record, None));
+        assert!(PayslipSeal::for_record(&record, None).verify(&record, Some(KEY)));
+
+        record.net_salary += 1.0;
+        assert!(!seal.verify(&record, Some(KEY)));
+    }
+
+    #[test]
+    fn verification_codes_read_back_what_they_print() {
+        let record = record("E1-2026-01-1");
+        for signing_key in [None, Some(KEY)] {
+            let code = code(&record, signing_key);
+            assert_eq!(code.to_string().parse::<VerificationCode>().unwrap(), code);
+        }
+        let printed = code(&record, None).to_string();
+        assert_eq!(printed, format!("EMPSLIP1:E1-2026-01-1:{}", record_hash(&record)));
+
+        let bare: VerificationCode = record_hash(&record).to_ascii_uppercase().parse().unwrap();
+        assert_eq!(bare.record_id, "");
+        assert_eq!(bare.hash, record_hash(&record));
+
+        let extra = format!("{}:{}:extra", printed, record_hash(&record));
+        for invalid in ["", "EMPSLIP1:E1", "EMPSLIP2:E1:00", &format!("{}:zz", printed), &extra] {
+            assert!(invalid.parse::<VerificationCode>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn scanned_codes_are_checked_against_the_stored_records() {
+        let mut records = vec![record("E1-2026-01-1"), record("E1-2026-02-2")];
+        records[1].pay_period = "2026-02".to_string();
+        let signed = code(&records[0], Some(KEY));
+        let verdict = |records: &[PayrollData], code: &VerificationCode, key| {
+            verify_payslip(records, code, key).map(|(record, verdict)| (record.record_id.clone(), verdict))
+        };
+
+        assert_eq!(
+            verdict(&records, &signed, Some(KEY)),
+            Some(("E1-2026-01-1".to_string(), PayslipVerdict::Authentic))
+        );
+        assert_eq!(verdict(&records, &signed, None).unwrap().1, PayslipVerdict::SignatureNotChecked);
+        assert_eq!(verdict(&records, &signed, Some("other-key")).unwrap().1, PayslipVerdict::BadSignature);
+
+        let by_hash: VerificationCode = record_hash(&records[1]).parse().unwrap();
+        assert_eq!(verdict(&records, &by_hash, None), Some(("E1-2026-02-2".to_string(), PayslipVerdict::Authentic)));
+
+        let mut unknown = signed.clone();
+        unknown.record_id = "E9-2026-01-7".to_string();
+        assert_eq!(verdict(&records, &unknown, Some(KEY)), None);
+
+        records[0].gross_salary += 1_000_000.0;
+        assert_eq!(verdict(&records, &signed, Some(KEY)).unwrap().1, PayslipVerdict::Altered);
+        records[0].voided = true;
+        assert_eq!(verdict(&records, &signed, Some(KEY)).unwrap().1, PayslipVerdict::Voided);
+        assert!(!PayslipVerdict::Voided.is_valid());
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn qr_codes_are_square() {
+        let (width, modules) = qr_modules(&code(&record("E1-2026-01-1"), Some(KEY)));
+        assert_eq!(modules.len(), width * width);
+        assert!(modules.iter().any(|dark| *dark));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::payslip::{payslip_lines, to_hex, PayslipSeal};
+use rand_core::{OsRng, RngCore};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use lopdf::content::{Content, Operation};
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{dictionary, Document, EncryptionState, EncryptionVersion, Object, Permissions, Stream, StringFormat};
+
+/// Renders a payslip, encrypted with AES-256 when `user_password` is given. The owner password
+/// grants every permission, so a blank one is refused; without one a random password is used.
+pub fn render_payslip_pdf(
+    record: &PayrollData,
+    seal: &PayslipSeal,
+    user_password: Option<&str>,
+    owner_password: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+
+    let mut operations = vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), 16.into()]),
+        Operation::new("Td", vec![50.into(), 780.into()]),
+        Operation::new("Tj", vec![Object::string_literal(format!("Payslip - {}", record.pay_period))]),
+        Operation::new("Tf", vec!["F1".into(), 11.into()]),
+        Operation::new("TL", vec![18.into()]),
+        Operation::new("T*", vec![]),
+    ];
+    for (label, value) in payslip_lines(record) {
+        operations.push(Operation::new("T*", vec![]));
+        operations.push(Operation::new(
+            "Tj",
+            vec![Object::string_literal(format!("{:<24} {}", format!("{}:", label), value))],
+        ));
+    }
+    operations.push(Operation::new("Tf", vec!["F1".into(), 7.into()]));
+    operations.push(Operation::new("T*", vec![]));
+    operations.push(Operation::new("T*", vec![]));
+    operations.push(Operation::new(
+        "Tj",
+        vec![Object::string_literal(format!("Verification hash: {}", seal.hash))],
+    ));
+    if let Some(signature) = &seal.signature {
+        operations.push(Operation::new("T*", vec![]));
+        operations.push(Operation::new(
+            "Tj",
+            vec![Object::string_literal(format!("Signature: {}", signature))],
+        ));
+    }
+    operations.push(Operation::new("ET", vec![]));
+    #[cfg(feature = "qr")]
+    operations.extend(qr_operations(&crate::payslip::VerificationCode::new(record, seal)));
+
+    let content = Content { operations };
+    let content_id = doc.add_object(Stream::new(
+        dictionary! {},
+        content.encode().map_err(|err| err.to_string())?,
+    ));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    if let Some(user_password) = user_password {
+        let owner_password = match owner_password {
+            Some(password) if password.trim().is_empty() => return Err(PayrollError::BlankOwnerPassword.to_string()),
+            Some(password) => password.to_string(),
+            None => random_password(),
+        };
+        let id = Object::String(seal.hash.as_bytes()[..16].to_vec(), StringFormat::Hexadecimal);
+        doc.trailer.set("ID", Object::Array(vec![id.clone(), id]));
+        // AES-256 (PDF 2.0 security handler) with a fresh file key per payslip.
+        let mut file_encryption_key = [0u8; 32];
+        OsRng.fill_bytes(&mut file_encryption_key);
+        let filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+        let version = EncryptionVersion::V5 {
+            encrypt_metadata: true,
+            crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+            file_encryption_key: &file_encryption_key,
+            stream_filter: b"StdCF".to_vec(),
+            string_filter: b"StdCF".to_vec(),
+            owner_password: &owner_password,
+            user_password,
+            permissions: Permissions::PRINTABLE,
+        };
+        let state = EncryptionState::try_from(version).map_err(|err| err.to_string())?;
+        doc.encrypt(&state).map_err(|err| err.to_string())?;
+        doc.version = "2.0".to_string();
+    }
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+/// 32 random bytes from the OS, hex-encoded.
+fn random_password() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Draws the payslip's QR code in the bottom right corner of the page, 120 points wide with its
+/// quiet zone.
+#[cfg(feature = "qr")]
+fn qr_operations(code: &crate::payslip::VerificationCode) -> Vec<Operation> {
+    const SIZE: f32 = 120.0;
+    const LEFT: f32 = 425.0;
+    const BOTTOM: f32 = 50.0;
+    let (width, modules) = crate::payslip::qr_modules(code);
+    let module = SIZE / (width + 8) as f32;
+    let mut operations = vec![Operation::new("rg", vec![0.into(), 0.into(), 0.into()])];
+    for (index, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+        let x = LEFT + (index % width + 4) as f32 * module;
+        let y = BOTTOM + SIZE - (index / width + 5) as f32 * module;
+        operations.push(Operation::new("re", vec![x.into(), y.into(), module.into(), module.into()]));
+    }
+    operations.push(Operation::new("f", vec![]));
+    operations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::EmployeeData;
+    use chrono::NaiveDate;
+
+    const DATE_OF_BIRTH: &str = "14021990";
+
+    fn payslip(owner_password: Option<&str>) -> Result<Document, String> {
+        let mut employee = FulltimeEmployee::new("E1".to_string(), 173.0, 0.0, AllowancePeriod::Monthly, 9_000_000.0);
+        employee.profile.date_of_birth = NaiveDate::from_ymd_opt(1990, 2, 14);
+        let record = PayrollData::new(EmployeeData::Fulltime(employee), "2026-01".to_string());
+        let seal = PayslipSeal::for_record(&record, None);
+        let bytes = render_payslip_pdf(&record, &seal, Some(DATE_OF_BIRTH), owner_password)?;
+        Document::load_mem(&bytes).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn an_encrypted_payslip_does_not_open_with_an_empty_password() {
+        for owner_password in [None, Some("owner-secret")] {
+            let doc = payslip(owner_password).unwrap();
+            assert!(doc.is_encrypted());
+            assert!(doc.authenticate_user_password("").is_err());
+            assert!(doc.authenticate_owner_password("").is_err());
+            assert!(doc.authenticate_user_password(DATE_OF_BIRTH).is_ok());
+        }
+        let doc = payslip(Some("owner-secret")).unwrap();
+        assert!(doc.authenticate_owner_password("owner-secret").is_ok());
+    }
+
+    #[test]
+    fn a_blank_owner_password_is_refused() {
+        for blank in ["", "   "] {
+            assert_eq!(payslip(Some(blank)).unwrap_err(), PayrollError::BlankOwnerPassword.to_string());
+        }
+    }
+}
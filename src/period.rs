@@ -0,0 +1,125 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayPeriod {
+    pub year: i32,
+    pub month: u32,
+}
+
+impl PayPeriod {
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        if (1..=12).contains(&month) {
+            Some(Self { year, month })
+        } else {
+            None
+        }
+    }
+
+    pub fn from_date(date: NaiveDate) -> Self {
+        Self {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        if self.month == 1 {
+            Self { year: self.year - 1, month: 12 }
+        } else {
+            Self { year: self.year, month: self.month - 1 }
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        if self.month == 12 {
+            Self { year: self.year + 1, month: 1 }
+        } else {
+            Self { year: self.year, month: self.month + 1 }
+        }
+    }
+
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("valid pay period")
+    }
+
+    pub fn month_name(&self) -> &'static str {
+        MONTH_NAMES[(self.month - 1) as usize]
+    }
+
+    pub fn same_period(a: &str, b: &str) -> bool {
+        match (a.parse::<PayPeriod>(), b.parse::<PayPeriod>()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a.trim().eq_ignore_ascii_case(b.trim()),
+        }
+    }
+
+    fn parse_month(s: &str) -> Option<u32> {
+        let s = s.to_lowercase();
+        if s.len() < 3 {
+            return None;
+        }
+        MONTH_NAMES
+            .iter()
+            .position(|name| name.to_lowercase().starts_with(&s))
+            .map(|index| index as u32 + 1)
+    }
+}
+
+impl fmt::Display for PayPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.month_name(), self.year)
+    }
+}
+
+impl FromStr for PayPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Unrecognized pay period '{}'", s);
+        let trimmed = s.trim();
+
+        if let Some((year, month)) = trimmed.split_once('-') {
+            let year = year.trim().parse::<i32>().map_err(|_| invalid())?;
+            let month = month.trim().parse::<u32>().map_err(|_| invalid())?;
+            return PayPeriod::new(year, month).ok_or_else(invalid);
+        }
+
+        if let Some((month, year)) = trimmed.split_once('/') {
+            let month = month.trim().parse::<u32>().map_err(|_| invalid())?;
+            let year = year.trim().parse::<i32>().map_err(|_| invalid())?;
+            return PayPeriod::new(year, month).ok_or_else(invalid);
+        }
+
+        let mut parts = trimmed.split_whitespace();
+        let month = parts.next().and_then(Self::parse_month).ok_or_else(invalid)?;
+        let year = parts
+            .next()
+            .and_then(|year| year.parse::<i32>().ok())
+            .ok_or_else(invalid)?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        let year = if year < 100 { 2000 + year } else { year };
+        PayPeriod::new(year, month).ok_or_else(invalid)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::error::StorageError;
+use crate::migration::{migrate, CURRENT_SCHEMA_VERSION};
+use crate::payroll::Payroll;
+use crate::storage::{RecordCallback, Storage};
+use serde_json::{Map, Value};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use std::sync::atomic::Ordering;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+use tokio_stream::StreamExt;
+
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("migrations/postgres");
+
+/// Saves wait for each other on a row lock, so a few connections are enough.
+const MAX_CONNECTIONS: u32 = 5;
+
+/// The runtime the blocking [`Storage`] calls run their queries on. It lives for the whole process,
+/// so dropping a storage from async code does not drop a runtime there.
+fn runtime() -> Result<&'static Runtime, StorageError> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Keeps the payroll in a PostgreSQL database that several servers and users can share. Employees
+/// and payroll records get a row each, the rest of the payroll is one JSON document, and every row
+/// holds its data as JSON at the document's schema version, so loading goes through the same
+/// migrations as a data file. Saves are checked against the revision the payroll was loaded at,
+/// like [`JsonFileStorage`](crate::storage::JsonFileStorage), under a row lock instead of a lock file.
+/// There is no payroll ledger: the database keeps its own history.
+///
+/// The calls block; from async code, run them on a blocking thread.
+pub struct PostgresStorage {
+    pool: PgPool,
+}
+
+impl PostgresStorage {
+    /// Connects to the database at `url` and applies any schema migrations it has not had yet.
+    pub fn connect(url: &str) -> Result<Self, StorageError> {
+        runtime()?.block_on(async {
+            let pool = PgPoolOptions::new().max_connections(MAX_CONNECTIONS).connect(url).await?;
+            MIGRATOR.run(&pool).await.map_err(sqlx::Error::from)?;
+            Ok(Self { pool })
+        })
+    }
+
+    /// The revision of the stored payroll; 0 before the first save.
+    pub fn revision(&self) -> Result<u64, StorageError> {
+        runtime()?.block_on(async {
+            let revision: i64 = sqlx::query_scalar("SELECT revision FROM payroll_state")
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(revision as u64)
+        })
+    }
+
+    /// Reads the state, employees and records from one snapshot of the database and puts them back
+    /// together into a payroll document.
+    async fn load_document(&self) -> Result<Option<Value>, StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        let state = sqlx::query("SELECT revision, schema_version, document FROM payroll_state")
+            .fetch_one(&mut *tx)
+            .await?;
+        let revision: i64 = state.try_get("revision")?;
+        if revision == 0 {
+            return Ok(None);
+        }
+        let schema_version: i32 = state.try_get("schema_version")?;
+        let mut document: Map<String, Value> = serde_json::from_value(state.try_get("document")?)?;
+        let employees: Vec<Value> = sqlx::query_scalar("SELECT data FROM employees ORDER BY position")
+            .fetch_all(&mut *tx)
+            .await?;
+        let records: Vec<Value> = sqlx::query_scalar("SELECT data FROM payroll_records ORDER BY position")
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        document.insert("schema_version".to_string(), Value::from(schema_version));
+        document.insert("revision".to_string(), Value::from(revision));
+        document.insert("employees".to_string(), Value::Array(employees));
+        document.insert("payroll_records".to_string(), Value::Array(records));
+        Ok(Some(Value::Object(document)))
+    }
+}
+
+impl Storage for PostgresStorage {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err(Display)))]
+    fn load(&self) -> Result<Payroll, StorageError> {
+        match runtime()?.block_on(self.load_document())? {
+            Some(document) => Ok(serde_json::from_value(migrate(document)?)?),
+            None => Ok(Payroll::new()),
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(records = payroll.get_payroll_records().len()), err(Display))
+    )]
+    fn save(&self, payroll: &Payroll) -> Result<(), StorageError> {
+        self.save_with(payroll, &mut || Ok(()))
+    }
+
+    /// Refuses with [`StorageError::Conflict`] when someone else saved after `payroll` was loaded.
+    /// Rows whose data is unchanged are left alone, so saving a long history mostly touches the new
+    /// records.
+    fn save_with(
+        &self,
+        payroll: &Payroll,
+        before_write: &mut dyn FnMut() -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let loaded = payroll.revision.load(Ordering::SeqCst);
+        let mut document = match serde_json::to_value(payroll)? {
+            Value::Object(document) => document,
+            _ => Map::new(),
+        };
+        document.remove("revision");
+        document.remove("schema_version");
+        let employees = match document.remove("employees") {
+            Some(Value::Array(employees)) => employees,
+            _ => Vec::new(),
+        };
+        let records = match document.remove("payroll_records") {
+            Some(Value::Array(records)) => records,
+            _ => Vec::new(),
+        };
+        let employee_ids: Vec<String> = payroll
+            .employees
+            .iter()
+            .map(|employee| employee.as_employee().employee_id().to_string())
+            .collect();
+        let record_employee_ids: Vec<String> = payroll
+            .get_payroll_records()
+            .iter()
+            .map(|record| record.employee.as_employee().employee_id().to_string())
+            .collect();
+
+        runtime()?.block_on(async {
+            let mut tx = self.pool.begin().await?;
+            let stored: i64 = sqlx::query_scalar("SELECT revision FROM payroll_state FOR UPDATE")
+                .fetch_one(&mut *tx)
+                .await?;
+            if stored as u64 != loaded {
+                return Err(StorageError::Conflict(stored as u64, loaded));
+            }
+            before_write()?;
+
+            sqlx::query(
+                "INSERT INTO employees (position, employee_id, data) \
+                 SELECT * FROM UNNEST($1::INTEGER[], $2::TEXT[], $3::JSONB[]) \
+                 ON CONFLICT (position) DO UPDATE SET employee_id = EXCLUDED.employee_id, data = EXCLUDED.data \
+                 WHERE employees.data IS DISTINCT FROM EXCLUDED.data",
+            )
+            .bind((0..employees.len() as i32).collect::<Vec<_>>())
+            .bind(employee_ids)
+            .bind(employees.as_slice())
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM employees WHERE position >= $1")
+                .bind(employees.len() as i32)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO payroll_records (position, record_id, employee_id, pay_period, voided, data) \
+                 SELECT * FROM UNNEST($1::INTEGER[], $2::TEXT[], $3::TEXT[], $4::TEXT[], $5::BOOLEAN[], $6::JSONB[]) \
+                 ON CONFLICT (position) DO UPDATE SET record_id = EXCLUDED.record_id, \
+                 employee_id = EXCLUDED.employee_id, pay_period = EXCLUDED.pay_period, voided = EXCLUDED.voided, \
+                 data = EXCLUDED.data \
+                 WHERE payroll_records.data IS DISTINCT FROM EXCLUDED.data",
+            )
+            .bind((0..records.len() as i32).collect::<Vec<_>>())
+            .bind(payroll.get_payroll_records().iter().map(|record| record.record_id.clone()).collect::<Vec<_>>())
+            .bind(record_employee_ids)
+            .bind(payroll.get_payroll_records().iter().map(|record| record.pay_period.clone()).collect::<Vec<_>>())
+            .bind(payroll.get_payroll_records().iter().map(|record| record.voided).collect::<Vec<_>>())
+            .bind(records.as_slice())
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM payroll_records WHERE position >= $1")
+                .bind(records.len() as i32)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "UPDATE payroll_state SET revision = $1, schema_version = $2, document = $3, saved_at = now()",
+            )
+            .bind(loaded as i64 + 1)
+            .bind(payroll.schema_version as i32)
+            .bind(Value::Object(document))
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        })?;
+        payroll.revision.store(loaded + 1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Streams the records from the database, unless they were stored at an older schema version
+    /// and have to be migrated with the rest of the payroll first.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err(Display)))]
+    fn for_each_record(&self, f: &mut RecordCallback) -> Result<usize, StorageError> {
+        let runtime = runtime()?;
+        let schema_version: i32 = runtime.block_on(
+            sqlx::query_scalar("SELECT schema_version FROM payroll_state").fetch_one(&self.pool),
+        )?;
+        if schema_version as u32 != CURRENT_SCHEMA_VERSION {
+            let payroll = self.load()?;
+            let records = payroll.into_payroll_records();
+            let count = records.len();
+            for record in records {
+                f(record)?;
+            }
+            return Ok(count);
+        }
+        runtime.block_on(async {
+            let mut rows = sqlx::query_scalar::<_, Value>("SELECT data FROM payroll_records ORDER BY position")
+                .fetch(&self.pool);
+            let mut count = 0;
+            while let Some(data) = rows.next().await {
+                f(serde_json::from_value(data?)?)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+}
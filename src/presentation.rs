@@ -0,0 +1,1442 @@
+use crate::budget::BudgetVariance;
+use crate::calc::CalculationSettings;
+use crate::compensation::{SalaryChangeRequest, SalaryHistoryEntry};
+use crate::context::CALCULATION_VERSION;
+use crate::disbursement::{allocate, BankTransferBatch, Disbursement, EWallet, TransferStatus, EWALLET_BALANCE_LIMIT};
+use crate::equity::{EquityGrant, VestingEvent};
+use crate::expense::ExpenseClaim;
+use crate::insurance::{InsuranceEnrollment, InsurancePlan};
+use crate::export::register::{PayrollRegister, RegisterRow};
+use crate::hris::SyncReport;
+use crate::invariants::Violation;
+use crate::natura::NaturaItem;
+use crate::onboarding::OnboardingChecklist;
+use crate::overtime::OvertimeRequest;
+use crate::payroll::{EmployeeData, Payroll, PayrollData};
+use crate::payslip::PayslipVerdict;
+use crate::period::PayPeriod;
+use crate::read_model::DepartmentMonth;
+use crate::remittance::RemittanceSummary;
+use crate::report::{EmployeeTrend, MonthlyCost, PayrollTotals, PeriodComparison};
+use crate::retention::PurgeReport;
+use crate::review::{BonusRun, ReviewCycle};
+use crate::rules::PayrollComponent;
+use crate::self_service::YearToDate;
+use crate::severance::TerminationRecord;
+use crate::simulation::SimulationResult;
+use crate::statistics::PayrollStatistics;
+use crate::timesheet::Timesheet;
+use crate::travel::TravelRecord;
+use crate::verify::Verification;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::borrow::Borrow;
+use std::fmt;
+use std::io::{self, Write};
+
+pub struct PayrollPresentation;
+
+impl PayrollPresentation {
+    pub fn write_payroll_summary<W: Write + ?Sized>(out: &mut W, payroll_data: &PayrollData) -> io::Result<()> {
+        let employee = payroll_data.employee.as_employee();
+        writeln!(out, "=== Payroll Summary ===")?;
+        writeln!(out, "Employee ID: {}", employee.employee_id())?;
+        writeln!(out, "Employee Type: {}", employee.employee_type())?;
+        writeln!(out, "Pay Period: {}", payroll_data.pay_period)?;
+        if !payroll_data.record_id.is_empty() {
+            writeln!(out, "Record ID: {}", payroll_data.record_id)?;
+        }
+        if payroll_data.voided {
+            writeln!(out, "Status: VOID")?;
+        }
+        writeln!(out, "Processed Date: {}", payroll_data.processed_date.format("%Y-%m-%d %H:%M:%S"))?;
+        writeln!(out, "Work Hours: {}", employee.work_hour())?;
+        if let Some(check) = payroll_data.overtime.filter(|check| check.excess_hours > 0.0) {
+            let action = if check.excluded { "excluded from pay" } else { "paid, flagged for review" };
+            writeln!(out, "Overtime Beyond Policy: {} hours ({})", check.excess_hours, action)?;
+        }
+        for component in &payroll_data.extra_earnings {
+            Self::write_amount(out, &format!("  + {}", component.name), component.amount)?;
+        }
+        Self::write_amount(out, "Gross Salary", payroll_data.gross_salary)?;
+        for component in &payroll_data.extra_deductions {
+            Self::write_amount(out, &format!("  - {}", component.name), component.amount)?;
+        }
+        Self::write_amount(out, "Deductions", payroll_data.deductions)?;
+        Self::write_amount(out, "Net Salary", payroll_data.net_salary)?;
+        let splits = &employee.profile().salary_split;
+        if !splits.is_empty() {
+            match allocate(splits, payroll_data.net_salary) {
+                Ok(allocations) => {
+                    for (split, amount) in allocations {
+                        Self::write_amount(out, &format!("  > {} {}", split.bank, split.masked_account()), amount)?;
+                    }
+                }
+                Err(err) => writeln!(out, "Payment Split: {}", err)?,
+            }
+        }
+        if let Some(context) = &payroll_data.context {
+            writeln!(out, "Tax Table: {} (engine {})", context.tax_table, context.engine_version)?;
+        }
+        writeln!(out, "{}", "-".repeat(40))
+    }
+
+    pub fn write_all_payrolls<W, I>(out: &mut W, payroll_records: I) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+        I: IntoIterator,
+        I::Item: Borrow<PayrollData>,
+    {
+        writeln!(out, "=== ALL PAYROLL RECORDS ===\n")?;
+        let mut totals = PayrollTotals::default();
+
+        for record in payroll_records {
+            let record = record.borrow();
+            Self::write_payroll_summary(out, record)?;
+            totals.add(record);
+            writeln!(out)?;
+        }
+
+        Self::write_total_summary(out, &totals)
+    }
+
+    pub fn write_total_summary<W: Write + ?Sized>(out: &mut W, totals: &PayrollTotals) -> io::Result<()> {
+        writeln!(out, "=== TOTAL SUMMARY ===")?;
+        writeln!(out, "Total Employees: {}", totals.employees)?;
+        Self::write_amount(out, "Total Gross Payroll", totals.gross)?;
+        Self::write_amount(out, "Total Net Payroll", totals.net)?;
+        Self::write_amount(out, "Total Deductions", totals.gross - totals.net)
+    }
+
+    fn write_amount<W: Write + ?Sized>(out: &mut W, label: &str, amount: f64) -> io::Result<()> {
+        writeln!(out, "{:<22} Rp {:>16.2}", format!("{}:", label), amount)
+    }
+
+    pub fn write_simulation<W: Write + ?Sized>(out: &mut W, result: &SimulationResult) -> io::Result<()> {
+        writeln!(out, "=== Salary Simulation ===")?;
+        writeln!(out, "Employee ID: {}", result.employee_id)?;
+        writeln!(out, "{:<12} {:>18} {:>18} {:>18}", "", "Current", "Simulated", "Delta")?;
+        let rows = [
+            ("Gross", result.current.gross, result.simulated.gross, result.gross_delta()),
+            ("Tax", result.current.tax, result.simulated.tax, result.tax_delta()),
+            ("Deductions", result.current.deductions, result.simulated.deductions, result.deductions_delta()),
+            ("Net", result.current.net, result.simulated.net, result.net_delta()),
+        ];
+        for (label, current, simulated, delta) in rows {
+            writeln!(out, "{:<12} {:>18.2} {:>18.2} {:>+18.2}", label, current, simulated, delta)?;
+        }
+        writeln!(out, "{}", "-".repeat(70))
+    }
+
+    pub fn write_period_comparison<W: Write + ?Sized>(out: &mut W, comparison: &PeriodComparison) -> io::Result<()> {
+        writeln!(out, "=== Payroll Comparison: {} vs {} ===", comparison.current_period, comparison.previous_period)?;
+        writeln!(
+            out,
+            "{:<12} {:>16} {:>16} {:>16}  Reasons",
+            "Employee", "Gross Change", "Deduct Change", "Net Change"
+        )?;
+
+        let mut changed = 0;
+        for diff in comparison.employees.iter().filter(|diff| diff.has_changes()) {
+            changed += 1;
+            let reasons = if diff.reasons.is_empty() {
+                "-".to_string()
+            } else {
+                diff.reasons.iter().map(|r| r.to_string()).collect::<Vec<_>>().join("; ")
+            };
+            writeln!(
+                out,
+                "{:<12} {:>+16.2} {:>+16.2} {:>+16.2}  {}",
+                diff.employee_id,
+                diff.gross_change(),
+                diff.deduction_change(),
+                diff.net_change(),
+                reasons
+            )?;
+        }
+
+        writeln!(
+            out,
+            "{} of {} employees changed",
+            changed,
+            comparison.employees.len()
+        )?;
+        writeln!(out, "{}", "-".repeat(70))
+    }
+
+    pub fn write_employee_history<W: Write + ?Sized>(
+        out: &mut W,
+        employee_id: &str,
+        records: &[&PayrollData],
+    ) -> io::Result<()> {
+        let mut rows = records.to_vec();
+        rows.sort_by_key(|record| record.pay_period.parse::<PayPeriod>().ok());
+        writeln!(out, "=== Payroll History: {} ===", employee_id)?;
+        writeln!(
+            out,
+            "{:<16} {:>7} {:>14} {:>14} {:>14} {:>14}",
+            "Period", "Hours", "Gross", "Deductions", "Tax", "Net"
+        )?;
+        for record in rows {
+            writeln!(
+                out,
+                "{:<16} {:>7} {:>14.2} {:>14.2} {:>14.2} {:>14.2}{}",
+                record.pay_period,
+                record.employee.as_employee().work_hour(),
+                record.gross_salary,
+                record.deductions,
+                record.breakdown().tax,
+                record.net_salary,
+                if record.voided { "  VOID" } else { "" }
+            )?;
+        }
+
+        let Some(trend) = EmployeeTrend::from_records(records) else {
+            return writeln!(out, "{}", "-".repeat(84));
+        };
+        writeln!(out)?;
+        writeln!(out, "Periods Paid: {}", trend.periods)?;
+        Self::write_amount(out, "Average Net", trend.average_net)?;
+        Self::write_amount(out, &format!("YTD Tax ({})", trend.latest_period.year), trend.ytd_tax)?;
+        match trend.net_change {
+            Some(change) => writeln!(
+                out,
+                "{:<22} Rp {:>+16.2} ({:+.1}%)",
+                "Net vs Prev. Month:",
+                change,
+                if trend.latest_net - change != 0.0 { change / (trend.latest_net - change) * 100.0 } else { 0.0 }
+            )?,
+            None => writeln!(out, "Net vs Prev. Month:    no record for {}", trend.latest_period.previous())?,
+        }
+        writeln!(out, "{}", "-".repeat(84))
+    }
+
+    pub fn write_payroll_register<W: Write + ?Sized>(out: &mut W, register: &PayrollRegister) -> io::Result<()> {
+        let header = register.header();
+        let widths: Vec<usize> = header
+            .iter()
+            .enumerate()
+            .map(|(col, title)| match col {
+                0 => title.len().max(register.rows.iter().map(|row| row.employee_id.len()).max().unwrap_or(0)),
+                1 => 20,
+                2 => 10,
+                _ => title.len().max(14),
+            })
+            .collect();
+        let rule = "-".repeat(widths.iter().sum::<usize>() + widths.len() - 1);
+        let write_row = |out: &mut W, row: &RegisterRow| {
+            let mut name = row.name.clone();
+            if name.chars().count() > widths[1] {
+                name = name.chars().take(widths[1] - 1).chain(['~']).collect();
+            }
+            let mut line = format!(
+                "{:<w0$} {:<w1$} {:>w2$}",
+                row.employee_id,
+                name,
+                row.work_hours,
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2]
+            );
+            let amounts = row
+                .earnings
+                .iter()
+                .chain(std::iter::once(&row.gross))
+                .chain(&row.deductions)
+                .chain([&row.total_deductions, &row.net]);
+            for (amount, width) in amounts.zip(&widths[3..]) {
+                line.push_str(&format!(" {:>width$.2}", amount, width = width));
+            }
+            writeln!(out, "{}", line)
+        };
+
+        writeln!(out, "=== Payroll Register: {} ===", register.title)?;
+        let titles: Vec<String> = header
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(col, (title, width))| match col {
+                0 | 1 => format!("{:<width$}", title, width = width),
+                _ => format!("{:>width$}", title, width = width),
+            })
+            .collect();
+        writeln!(out, "{}", titles.join(" "))?;
+        writeln!(out, "{}", rule)?;
+        for row in &register.rows {
+            write_row(out, row)?;
+        }
+        writeln!(out, "{}", rule)?;
+        write_row(out, &register.totals())
+    }
+
+    pub fn write_remittance_summary<W: Write + ?Sized>(out: &mut W, summary: &RemittanceSummary) -> io::Result<()> {
+        writeln!(out, "=== Remittance Summary: {} ===", summary.pay_period)?;
+        writeln!(out, "Employees: {}", summary.employees)?;
+        writeln!(
+            out,
+            "{:<24} {:<22} {:>14} {:>14} {:>14}  {:<10}",
+            "Item", "Payee", "Employee", "Employer", "Total", "Due"
+        )?;
+        for line in &summary.lines {
+            writeln!(
+                out,
+                "{:<24} {:<22} {:>14.2} {:>14.2} {:>14.2}  {}",
+                line.item,
+                line.payee,
+                line.employee_share,
+                line.employer_share,
+                line.total(),
+                line.due_date.format("%Y-%m-%d")
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(104))?;
+        for (payee, amount, due) in summary.by_payee() {
+            writeln!(out, "{:<22} Rp {:>16.2}  due {}", format!("{}:", payee), amount, due.format("%Y-%m-%d"))?;
+        }
+        Self::write_amount(out, "Total to Remit", summary.total())
+    }
+
+    pub fn write_cost_trend<W: Write + ?Sized>(out: &mut W, months: &[MonthlyCost]) -> io::Result<()> {
+        let (Some(first), Some(last)) = (months.first(), months.last()) else {
+            return Ok(());
+        };
+        writeln!(out, "=== Headcount and Cost Trend: {} - {} ===", first.period, last.period)?;
+        writeln!(
+            out,
+            "{:<16} {:>9} {:>16} {:>16} {:>16}",
+            "Period", "Headcount", "Total Gross", "Employer Cost", "Average Gross"
+        )?;
+        for month in months {
+            writeln!(
+                out,
+                "{:<16} {:>9} {:>16.2} {:>16.2} {:>16.2}",
+                month.period.to_string(),
+                month.headcount,
+                month.gross,
+                month.employer_cost,
+                month.average_gross()
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(77))?;
+        Self::write_amount(out, "Total Gross", months.iter().map(|month| month.gross).sum())?;
+        Self::write_amount(out, "Total Employer Cost", months.iter().map(|month| month.employer_cost).sum())?;
+        let mut paid = months.iter().filter(|month| month.headcount > 0);
+        let (Some(start), Some(end)) = (paid.next(), paid.next_back()) else {
+            return Ok(());
+        };
+        writeln!(
+            out,
+            "Headcount Change:      {:+} ({} in {} -> {} in {})",
+            end.headcount as i64 - start.headcount as i64,
+            start.headcount,
+            start.period,
+            end.headcount,
+            end.period
+        )
+    }
+
+    pub fn write_department_totals<W: Write + ?Sized>(out: &mut W, months: &[DepartmentMonth]) -> io::Result<()> {
+        let (Some(first), Some(last)) = (months.first(), months.last()) else {
+            return writeln!(out, "No payroll records in this range");
+        };
+        writeln!(out, "=== Department Totals: {} - {} ===", first.pay_period, last.pay_period)?;
+        writeln!(
+            out,
+            "{:<8} {:<16} {:>9} {:>16} {:>16} {:>16}",
+            "Period", "Department", "Employees", "Total Gross", "Deductions", "Total Net"
+        )?;
+        for month in months {
+            writeln!(
+                out,
+                "{:<8} {:<16} {:>9} {:>16.2} {:>16.2} {:>16.2}",
+                month.pay_period,
+                month.department.as_deref().unwrap_or("-"),
+                month.employees,
+                month.gross,
+                month.deductions,
+                month.net
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(86))?;
+        Self::write_amount(out, "Total Gross", months.iter().map(|month| month.gross).sum())?;
+        Self::write_amount(out, "Total Net", months.iter().map(|month| month.net).sum())
+    }
+
+    pub fn write_budget_variance<W: Write + ?Sized>(out: &mut W, rows: &[BudgetVariance]) -> io::Result<()> {
+        writeln!(out, "=== Budget vs Actual ===")?;
+        writeln!(
+            out,
+            "{:<20} {:<16} {:>16} {:>16} {:>16} {:>8}",
+            "Department", "Period", "Budget", "Actual", "Variance", "%"
+        )?;
+        for row in rows {
+            writeln!(
+                out,
+                "{:<20} {:<16} {:>16.2} {:>16.2} {:>+16.2} {:>8}{}",
+                row.department,
+                row.period.to_string(),
+                row.budget,
+                row.actual,
+                row.variance(),
+                row.variance_percent().map(|percent| format!("{:+.1}%", percent)).unwrap_or_default(),
+                if row.over_budget() { "  OVER" } else { "" }
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(99))?;
+        Self::write_amount(out, "Total Budget", rows.iter().map(|row| row.budget).sum())?;
+        Self::write_amount(out, "Total Actual", rows.iter().map(|row| row.actual).sum())?;
+        let over: Vec<String> = rows
+            .iter()
+            .filter(|row| row.over_budget())
+            .map(|row| format!("{} {}", row.department, row.period))
+            .collect();
+        if over.is_empty() {
+            writeln!(out, "All departments within budget")
+        } else {
+            writeln!(out, "Over Budget: {}", over.join(", "))
+        }
+    }
+
+    pub fn write_violations<W: Write + ?Sized>(out: &mut W, violations: &[Violation]) -> io::Result<()> {
+        if violations.is_empty() {
+            return writeln!(out, "All invariants hold.");
+        }
+        writeln!(out, "=== Invariant Violations ===")?;
+        for violation in violations {
+            writeln!(out, "- {}", violation)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_verification<W: Write + ?Sized>(out: &mut W, verification: &Verification) -> io::Result<()> {
+        writeln!(out, "Records Recomputed: {}", verification.checked)?;
+        if verification.unversioned > 0 {
+            writeln!(out, "Skipped (no calculation context): {}", verification.unversioned)?;
+        }
+        if verification.mismatches.is_empty() {
+            return writeln!(out, "All recomputed amounts match.");
+        }
+        writeln!(out, "=== Mismatches ===")?;
+        for mismatch in &verification.mismatches {
+            let cause = if mismatch.is_version_change() {
+                format!("calculated by version {}, now {}", mismatch.calculation_version, CALCULATION_VERSION)
+            } else {
+                "same calculation version: data changed or calculation changed silently".to_string()
+            };
+            writeln!(
+                out,
+                "- #{} {} {}: {} stored Rp {:.2}, recomputed Rp {:.2} ({})",
+                mismatch.index + 1,
+                mismatch.employee_id,
+                mismatch.pay_period,
+                mismatch.field,
+                mismatch.stored,
+                mismatch.recomputed,
+                cause
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_purge<W: Write + ?Sized>(out: &mut W, report: &PurgeReport) -> io::Result<()> {
+        writeln!(out, "Keeping pay periods from {} on.", report.cutoff)?;
+        if report.is_empty() {
+            return writeln!(out, "Nothing to purge.");
+        }
+        if !report.summaries.is_empty() {
+            writeln!(
+                out,
+                "{:<10} {:>8} {:>10} {:>16} {:>16} {:>16}",
+                "Period", "Records", "Employees", "Gross", "Net", "Employer Cost"
+            )?;
+            for summary in &report.summaries {
+                writeln!(
+                    out,
+                    "{:<10} {:>8} {:>10} {:>16.2} {:>16.2} {:>16.2}",
+                    summary.pay_period,
+                    summary.records,
+                    summary.employees,
+                    summary.gross,
+                    summary.net,
+                    summary.employer_cost
+                )?;
+            }
+        }
+        writeln!(out, "Payroll Records: {}", report.records())?;
+        writeln!(out, "Payroll Runs: {}", report.runs)?;
+        writeln!(out, "Timesheets, Attendance, Overtime, Travel and Expenses: {}", report.other_entries)
+    }
+
+    /// What a scanned payslip was issued for, to compare with the paper, and whether it holds.
+    pub fn write_payslip_verification<W: Write + ?Sized>(
+        out: &mut W,
+        record: &PayrollData,
+        verdict: PayslipVerdict,
+    ) -> io::Result<()> {
+        let employee = record.employee.as_employee();
+        writeln!(out, "=== Payslip Verification ===")?;
+        writeln!(out, "Employee:   {} ({})", employee.profile().name, employee.employee_id())?;
+        writeln!(out, "Pay Period: {}", record.pay_period)?;
+        writeln!(out, "Net Salary: Rp {:.2}", record.net_salary)?;
+        writeln!(out, "Processed:  {}", record.processed_date.format("%Y-%m-%d"))?;
+        writeln!(out, "Result:     {}", verdict)
+    }
+
+    pub fn write_statistics<W: Write + ?Sized>(
+        out: &mut W,
+        scope: &str,
+        statistics: &PayrollStatistics,
+    ) -> io::Result<()> {
+        writeln!(out, "=== Payroll Statistics: {} ===", scope)?;
+        writeln!(out, "Records: {} ({} employees)", statistics.records, statistics.employees)?;
+        let (Some(gross), Some(net)) = (statistics.gross, statistics.net) else {
+            return writeln!(out, "No payroll records match.");
+        };
+        writeln!(out, "{:<16} {:>16} {:>16}", "", "Gross", "Net")?;
+        let rows = [
+            ("Minimum", gross.min, net.min),
+            ("10th Percentile", gross.p10, net.p10),
+            ("25th Percentile", gross.p25, net.p25),
+            ("Median", gross.median, net.median),
+            ("Mean", gross.mean, net.mean),
+            ("75th Percentile", gross.p75, net.p75),
+            ("90th Percentile", gross.p90, net.p90),
+            ("Maximum", gross.max, net.max),
+            ("Std. Deviation", gross.std_dev, net.std_dev),
+        ];
+        for (label, gross, net) in rows {
+            writeln!(out, "{:<16} {:>16.2} {:>16.2}", label, gross, net)?;
+        }
+        writeln!(out, "{}", "-".repeat(50))?;
+        if let Some(ratio) = gross.p90_p10_ratio() {
+            writeln!(out, "Gross P90/P10: {:.2}x", ratio)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_year_to_date<W: Write + ?Sized>(out: &mut W, ytd: &YearToDate) -> io::Result<()> {
+        writeln!(out, "=== Year to Date: {} ===", ytd.year)?;
+        writeln!(out, "Periods Paid: {}", ytd.periods)?;
+        Self::write_amount(out, "Gross", ytd.gross)?;
+        Self::write_amount(out, "Tax (PPh 21)", ytd.tax)?;
+        Self::write_amount(out, "BPJS", ytd.bpjs)?;
+        Self::write_amount(out, "Deductions", ytd.deductions)?;
+        Self::write_amount(out, "Net", ytd.net)
+    }
+
+    pub fn write_bank_transfers<W: Write + ?Sized>(out: &mut W, batch: &BankTransferBatch) -> io::Result<()> {
+        writeln!(out, "=== Bank Transfers: {} ===", batch.reference)?;
+        writeln!(out, "{:<10} {:<10} {:<18} {:>20}", "Employee", "Bank", "Account", "Amount")?;
+        for transfer in &batch.transfers {
+            writeln!(
+                out,
+                "{:<10} {:<10} {:<18} {:>20.2}",
+                transfer.employee_id, transfer.bank, transfer.account_number, transfer.amount
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(61))?;
+        for (bank, count, amount) in batch.by_bank() {
+            Self::write_amount(out, &format!("{} ({} transfers)", bank, count), amount)?;
+        }
+        Self::write_amount(out, "Total", batch.total())?;
+        if !batch.problems.is_empty() {
+            writeln!(out, "Not paid ({}):", batch.problems.len())?;
+            for (employee_id, problem) in &batch.problems {
+                writeln!(out, "  {}: {}", employee_id, problem)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_ewallet_transfers<W: Write + ?Sized>(
+        out: &mut W,
+        batch: &BankTransferBatch,
+        wallet: EWallet,
+    ) -> io::Result<()> {
+        writeln!(out, "=== {} Payouts: {} ===", wallet.as_str(), batch.reference)?;
+        writeln!(out, "{:<10} {:<16} {:>20}", "Employee", "Phone", "Amount")?;
+        let (mut total, mut over_limit) = (0.0, 0);
+        for transfer in batch.ewallet_transfers(wallet) {
+            let over = transfer.amount > EWALLET_BALANCE_LIMIT;
+            writeln!(
+                out,
+                "{:<10} {:<16} {:>20.0}{}",
+                transfer.employee_id,
+                transfer.account_number,
+                transfer.amount.round(),
+                if over { " *" } else { "" }
+            )?;
+            total += transfer.amount.round();
+            over_limit += usize::from(over);
+        }
+        writeln!(out, "{}", "-".repeat(48))?;
+        Self::write_amount(out, "Total", total)?;
+        if over_limit > 0 {
+            writeln!(
+                out,
+                "* {} payouts exceed the Rp {:.0} wallet balance limit and may be rejected",
+                over_limit, EWALLET_BALANCE_LIMIT
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_disbursement<W: Write + ?Sized>(
+        out: &mut W,
+        run_id: u32,
+        disbursement: &Disbursement,
+    ) -> io::Result<()> {
+        writeln!(out, "=== Disbursement of Run #{} ===", run_id)?;
+        writeln!(out, "Provider: {}", disbursement.provider)?;
+        writeln!(out, "Batch: {}", disbursement.batch_id)?;
+        writeln!(out, "Submitted: {}", disbursement.submitted.format("%Y-%m-%d %H:%M:%S"))?;
+        writeln!(out, "Status: {:?}", disbursement.status)?;
+        writeln!(out, "{:<10} {:<10} {:<16} {:>18}  Result", "Employee", "Bank", "Account", "Amount")?;
+        for confirmation in &disbursement.confirmations {
+            let result = match &confirmation.status {
+                TransferStatus::Pending => "pending".to_string(),
+                TransferStatus::Paid(at) => format!("paid {}", at.format("%Y-%m-%d %H:%M")),
+                TransferStatus::Rejected(reason) => format!("rejected: {}", reason),
+            };
+            writeln!(
+                out,
+                "{:<10} {:<10} {:<16} {:>18.2}  {}",
+                confirmation.employee_id, confirmation.bank, confirmation.account_number, confirmation.amount, result
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(70))?;
+        writeln!(
+            out,
+            "Paid: {}  Pending: {}  Rejected: {}",
+            disbursement.count(|status| matches!(status, TransferStatus::Paid(_))),
+            disbursement.count(|status| *status == TransferStatus::Pending),
+            disbursement.count(|status| matches!(status, TransferStatus::Rejected(_)))
+        )?;
+        Self::write_amount(out, "Paid Total", disbursement.paid_total())
+    }
+
+    pub fn write_onboarding<W: Write + ?Sized>(out: &mut W, checklists: &[&OnboardingChecklist]) -> io::Result<()> {
+        for checklist in checklists {
+            let status = if checklist.is_complete() { "ready for payroll" } else { "held from payroll" };
+            writeln!(out, "{} ({}, since {})", checklist.employee_id, status, checklist.created.format("%Y-%m-%d"))?;
+            for item in &checklist.items {
+                let mark = if item.completed.is_some() { "x" } else { " " };
+                let optional = if item.task.mandatory() { "" } else { " (optional)" };
+                writeln!(out, "  [{}] {:<10} {}{}", mark, item.task.as_str(), item.task, optional)?;
+            }
+        }
+        writeln!(out, "{}", "-".repeat(50))
+    }
+
+    /// Each employee's self-service PIN status, as of `now`.
+    pub fn write_pin_statuses<W: Write + ?Sized>(out: &mut W, payroll: &Payroll, now: DateTime<Utc>) -> io::Result<()> {
+        writeln!(out, "=== Employee PINs ===")?;
+        writeln!(out, "{:<10} {:<24} {:<30} {:>8}", "ID", "Name", "PIN", "Failures")?;
+        for employee in &payroll.employees {
+            let employee = employee.as_employee();
+            let credential = payroll.credential(employee.employee_id());
+            writeln!(
+                out,
+                "{:<10} {:<24} {:<30} {:>8}",
+                employee.employee_id(),
+                employee.profile().name.chars().take(24).collect::<String>(),
+                credential.map_or_else(|| "none".to_string(), |credential| credential.status(now).to_string()),
+                credential.map_or(0, |credential| credential.failed_attempts)
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(75))
+    }
+
+    pub fn write_sync_report<W: Write + ?Sized>(out: &mut W, report: &SyncReport) -> io::Result<()> {
+        writeln!(out, "=== Employee Sync: {} ===", report.source)?;
+        for (label, ids) in [
+            ("Created", &report.created),
+            ("Updated", &report.updated),
+            ("Deactivated", &report.deactivated),
+        ] {
+            match ids.is_empty() {
+                true => writeln!(out, "{:<12} {:>4}", label, 0)?,
+                false => writeln!(out, "{:<12} {:>4}  {}", label, ids.len(), ids.join(", "))?,
+            }
+        }
+        writeln!(out, "{:<12} {:>4}", "Unchanged", report.unchanged)?;
+        for (employee_id, reason) in &report.skipped {
+            writeln!(out, "Skipped {}: {}", employee_id, reason)?;
+        }
+        if !report.missing.is_empty() {
+            writeln!(
+                out,
+                "Not listed by the source (still active): {}",
+                report.missing.join(", ")
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(50))
+    }
+
+    /// The reporting lines as an indented tree, starting from employees without a manager.
+    pub fn write_org_chart<W: Write + ?Sized>(out: &mut W, payroll: &Payroll) -> io::Result<()> {
+        fn write_node<W: Write + ?Sized>(
+            out: &mut W,
+            payroll: &Payroll,
+            employee: &EmployeeData,
+            depth: usize,
+            visited: &mut Vec<String>,
+        ) -> io::Result<()> {
+            let emp = employee.as_employee();
+            let profile = emp.profile();
+            let title = [profile.grade.as_deref(), profile.department.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+            let indent = if depth == 0 { String::new() } else { format!("{}- ", "    ".repeat(depth - 1)) };
+            match title.as_str() {
+                "" => writeln!(out, "{}{} {}", indent, emp.employee_id(), profile.name)?,
+                title => writeln!(out, "{}{} {} ({})", indent, emp.employee_id(), profile.name, title)?,
+            }
+            visited.push(emp.employee_id().to_string());
+            for report in payroll.direct_reports(emp.employee_id()) {
+                if !visited.iter().any(|id| id == report.as_employee().employee_id()) {
+                    write_node(out, payroll, report, depth + 1, visited)?;
+                }
+            }
+            Ok(())
+        }
+
+        writeln!(out, "=== Organization Chart ===")?;
+        let mut visited = Vec::new();
+        for root in payroll.org_roots() {
+            write_node(out, payroll, root, 0, &mut visited)?;
+        }
+        writeln!(out, "{}", "-".repeat(50))
+    }
+
+    pub fn write_review_cycles<W: Write + ?Sized>(out: &mut W, cycles: &[ReviewCycle]) -> io::Result<()> {
+        writeln!(out, "{:<20} {:<8} {:>8} {:>10} Started", "Cycle", "Status", "Reviews", "Avg Score")?;
+        for cycle in cycles {
+            writeln!(
+                out,
+                "{:<20} {:<8} {:>8} {:>10} {}",
+                cycle.name,
+                if cycle.closed { "closed" } else { "open" },
+                cycle.reviews.len(),
+                cycle
+                    .average_score()
+                    .map_or_else(|| "-".to_string(), |score| format!("{:.2}", score)),
+                cycle.created.format("%Y-%m-%d")
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(62))
+    }
+
+    pub fn write_bonus_run<W: Write + ?Sized>(out: &mut W, run: &BonusRun) -> io::Result<()> {
+        writeln!(out, "=== Bonus Run: {} (target {} months) ===", run.cycle, run.target_months)?;
+        writeln!(
+            out,
+            "{:<10} {:<24} {:>6} {:>10} {:>16} {:>16}",
+            "ID", "Name", "Score", "Multiplier", "Monthly Base", "Bonus"
+        )?;
+        for line in &run.lines {
+            writeln!(
+                out,
+                "{:<10} {:<24} {:>6.2} {:>9.2}x {:>16.2} {:>16.2}",
+                line.employee_id,
+                line.name.chars().take(24).collect::<String>(),
+                line.score,
+                line.multiplier,
+                line.monthly_base,
+                line.bonus
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(87))?;
+        Self::write_amount(out, "Total Bonus", run.total())?;
+        if !run.unreviewed.is_empty() {
+            writeln!(out, "Not reviewed (no bonus): {}", run.unreviewed.join(", "))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_terminations<W: Write + ?Sized>(out: &mut W, terminations: &[&TerminationRecord]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:<12} {:<10} {:<10} {:>5} {:>16} {:>14}",
+            "Employee", "Reason", "Hired", "Last Day", "Years", "Severance", "Final Tax"
+        )?;
+        for termination in terminations {
+            writeln!(
+                out,
+                "{:<10} {:<12} {:<10} {:<10} {:>5} {:>16.2} {:>14.2}",
+                termination.employee_id,
+                termination.reason.as_str(),
+                termination.hired,
+                termination.last_day,
+                termination.years_of_service,
+                termination.severance,
+                termination.tax
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(87))?;
+        Self::write_amount(out, "Total Severance", terminations.iter().map(|t| t.severance).sum())?;
+        Self::write_amount(out, "Total Final Tax", terminations.iter().map(|t| t.tax).sum())
+    }
+
+    /// Each employee's attendance in `period`, with the allowances and penalties it earns under the
+    /// current policy.
+    pub fn write_attendance_summary<W: Write + ?Sized>(
+        out: &mut W,
+        payroll: &Payroll,
+        period: &PayPeriod,
+    ) -> io::Result<()> {
+        writeln!(out, "=== Attendance: {} ===", period)?;
+        writeln!(
+            out,
+            "{:<10} {:<24} {:>5} {:>5} {:>6} {:>16} {:>16}",
+            "Employee", "Name", "Days", "Late", "Early", "Allowances", "Penalties"
+        )?;
+        for employee in &payroll.employees {
+            let emp = employee.as_employee();
+            let summary = payroll.attendance_summary(emp.employee_id(), period);
+            if summary.days == 0 {
+                continue;
+            }
+            let policy = payroll.attendance.policy();
+            let total = |components: Vec<PayrollComponent>| components.iter().fold(0.0, |sum, c| sum + c.amount);
+            writeln!(
+                out,
+                "{:<10} {:<24} {:>5} {:>5} {:>6} {:>16.2} {:>16.2}",
+                emp.employee_id(),
+                emp.profile().name.chars().take(24).collect::<String>(),
+                summary.days,
+                summary.late,
+                summary.early_leaves,
+                total(policy.allowances(&summary)),
+                total(policy.penalties(&summary))
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(89))
+    }
+
+    pub fn write_overtime_requests<W: Write + ?Sized>(out: &mut W, requests: &[&OvertimeRequest]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<16} {:>7} {:<24} {:<10} Status",
+            "#", "Employee", "Period", "Hours", "Reason", "Decided By"
+        )?;
+        for request in requests {
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<16} {:>7.1} {:<24} {:<10} {}",
+                request.id,
+                request.employee_id,
+                request.period.to_string(),
+                request.hours,
+                request.reason.chars().take(24).collect::<String>(),
+                request.decided_by.as_deref().unwrap_or("-"),
+                request.status
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(90))
+    }
+
+    pub fn write_travel<W: Write + ?Sized>(out: &mut W, records: &[&TravelRecord]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<20} {:<13} {:<10} {:>4} {:<16} {:>14} Taxable",
+            "#", "Employee", "Destination", "Tier", "Start", "Days", "Paid In", "Per Diem"
+        )?;
+        for record in records {
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<20} {:<13} {:<10} {:>4} {:<16} {:>14.2} {:.2}",
+                record.id,
+                record.employee_id,
+                record.destination.chars().take(20).collect::<String>(),
+                record.tier.as_str(),
+                record.start.format("%Y-%m-%d").to_string(),
+                record.days,
+                record.period.to_string(),
+                record.amount(),
+                record.taxable_amount()
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(110))
+    }
+
+    /// Grants with the units vested by `on`.
+    pub fn write_equity_grants<W: Write + ?Sized>(
+        out: &mut W,
+        grants: &[&EquityGrant],
+        on: NaiveDate,
+    ) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<6} {:>9} {:>9} {:>12} {:>12} {:<10} {:<12} Forfeited",
+            "#", "Employee", "Kind", "Units", "Vested", "Strike", "Fair Value", "Granted", "Vesting"
+        )?;
+        for grant in grants {
+            let schedule = grant.schedule;
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<6} {:>9} {:>9} {:>12.2} {:>12.2} {:<10} {:<12} {}",
+                grant.id,
+                grant.employee_id,
+                grant.kind.to_string(),
+                grant.units,
+                grant.vested_units(on),
+                grant.strike_price,
+                grant.fair_value,
+                grant.granted.format("%Y-%m-%d").to_string(),
+                format!("{}/{}/{}m", schedule.cliff_months, schedule.every_months, schedule.total_months),
+                grant.forfeited.map_or_else(|| "-".to_string(), |date| date.format("%Y-%m-%d").to_string())
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(105))
+    }
+
+    /// Vestings of a pay period with the benefit-in-kind income each one creates.
+    pub fn write_vesting<W: Write + ?Sized>(out: &mut W, events: &[(&EquityGrant, VestingEvent)]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:>4} {:<6} {:<10} {:>9} {:>16}",
+            "Employee", "#", "Kind", "Date", "Units", "Income"
+        )?;
+        let mut total = 0.0;
+        for (grant, event) in events {
+            let income = grant.value_of(event.units);
+            total += income;
+            writeln!(
+                out,
+                "{:<10} {:>4} {:<6} {:<10} {:>9} {:>16.2}",
+                grant.employee_id,
+                grant.id,
+                grant.kind.to_string(),
+                event.date.format("%Y-%m-%d").to_string(),
+                event.units,
+                income
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(60))?;
+        Self::write_amount(out, "Total Vesting Income", total)
+    }
+
+    pub fn write_insurance_plans<W: Write + ?Sized>(out: &mut W, plans: &[InsurancePlan]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:<24} {:<20} {:<16} {:>14} {:>14}",
+            "Code", "Plan", "Insurer", "Tier", "Employee", "Employer"
+        )?;
+        for plan in plans {
+            for tier in &plan.tiers {
+                writeln!(
+                    out,
+                    "{:<10} {:<24} {:<20} {:<16} {:>14.2} {:>14.2}",
+                    plan.code,
+                    plan.name.chars().take(24).collect::<String>(),
+                    plan.insurer.chars().take(20).collect::<String>(),
+                    tier.name,
+                    tier.employee_premium,
+                    tier.employer_premium
+                )?;
+            }
+        }
+        writeln!(out, "{}", "-".repeat(103))
+    }
+
+    pub fn write_insurance_enrollments<W: Write + ?Sized>(
+        out: &mut W,
+        enrollments: &[&InsuranceEnrollment],
+    ) -> io::Result<()> {
+        writeln!(out, "{:<10} {:<10} {:<16} {:<16} Until", "Employee", "Plan", "Tier", "From")?;
+        for enrollment in enrollments {
+            writeln!(
+                out,
+                "{:<10} {:<10} {:<16} {:<16} {}",
+                enrollment.employee_id,
+                enrollment.plan,
+                enrollment.tier,
+                enrollment.start.to_string(),
+                enrollment.end.map_or_else(|| "-".to_string(), |end| end.to_string())
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(70))
+    }
+
+    pub fn write_natura<W: Write + ?Sized>(out: &mut W, items: &[&NaturaItem]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<8} {:>14} {:<16} {:<16} Description",
+            "#", "Employee", "Kind", "Monthly Value", "From", "Until"
+        )?;
+        for item in items {
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<8} {:>14.2} {:<16} {:<16} {}",
+                item.id,
+                item.employee_id,
+                item.kind.as_str(),
+                item.monthly_value,
+                item.start.to_string(),
+                item.end.map_or_else(|| "-".to_string(), |end| end.to_string()),
+                item.description
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(98))
+    }
+
+    pub fn write_expense_claims<W: Write + ?Sized>(out: &mut W, claims: &[&ExpenseClaim]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<16} {:<16} {:>14} {:>14} Description",
+            "#", "Employee", "Category", "Period", "Claimed", "Paid"
+        )?;
+        for claim in claims {
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<16} {:<16} {:>14.2} {:>14.2} {}",
+                claim.id,
+                claim.employee_id,
+                claim.category.chars().take(16).collect::<String>(),
+                claim.period.to_string(),
+                claim.amount,
+                claim.paid,
+                claim.description
+            )?;
+            if let Some(note) = &claim.note {
+                writeln!(out, "     {}", note)?;
+            }
+        }
+        writeln!(out, "{}", "-".repeat(100))
+    }
+
+    /// Records whose overtime went beyond the cap or the approved hours.
+    pub fn write_overtime_exceptions<W: Write + ?Sized>(out: &mut W, records: &[&PayrollData]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:>8} {:>8} {:>6} {:>6} {:>7} Action",
+            "Employee", "Overtime", "Approved", "Cap", "Paid", "Excess"
+        )?;
+        let hours = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |value| format!("{:.1}", value));
+        for record in records {
+            let Some(check) = record.overtime else {
+                continue;
+            };
+            writeln!(
+                out,
+                "{:<10} {:>8.1} {:>8} {:>6} {:>6.1} {:>7.1} {}",
+                record.employee.as_employee().employee_id(),
+                check.worked_hours,
+                hours(check.approved_hours),
+                hours(check.cap),
+                check.paid_hours,
+                check.excess_hours,
+                if check.excluded { "excluded" } else { "flagged" }
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(60))
+    }
+
+    pub fn write_timesheets<W: Write + ?Sized>(out: &mut W, timesheets: &[&Timesheet]) -> io::Result<()> {
+        writeln!(out, "{:<10} {:<16} {:>8} {:<10} Status", "Employee", "Period", "Hours", "Decided By")?;
+        for timesheet in timesheets {
+            writeln!(
+                out,
+                "{:<10} {:<16} {:>8.1} {:<10} {}",
+                timesheet.employee_id,
+                timesheet.period.to_string(),
+                timesheet.hours,
+                timesheet.decided_by.as_deref().unwrap_or("-"),
+                timesheet.status
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(64))
+    }
+
+    pub fn write_salary_changes<W: Write + ?Sized>(out: &mut W, requests: &[&SalaryChangeRequest]) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:>4} {:<10} {:<8} {:>16} {:<14} {:<16} Status",
+            "#", "Employee", "Grade", "New Rate", "Effective", "Approver"
+        )?;
+        for request in requests {
+            writeln!(
+                out,
+                "{:>4} {:<10} {:<8} {:>16.2} {:<14} {:<16} {}",
+                request.id,
+                request.employee_id,
+                request.grade.as_deref().unwrap_or("-"),
+                request.rate,
+                request.effective.to_string(),
+                request.approver.chars().take(16).collect::<String>(),
+                request.status
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(80))
+    }
+
+    pub fn write_salary_history<W: Write + ?Sized>(
+        out: &mut W,
+        employee_id: &str,
+        history: &[&SalaryHistoryEntry],
+    ) -> io::Result<()> {
+        writeln!(out, "=== Salary History: {} ===", employee_id)?;
+        writeln!(
+            out,
+            "{:<14} {:>16} {:>16} {:>8} {:<8} {:>8} Approved By",
+            "Effective", "Previous", "New", "Change", "Grade", "Request"
+        )?;
+        for entry in history {
+            writeln!(
+                out,
+                "{:<14} {:>16.2} {:>16.2} {:>8} {:<8} {:>8} {}",
+                entry.effective.to_string(),
+                entry.previous_rate,
+                entry.rate,
+                entry
+                    .change_percent()
+                    .map_or_else(|| "-".to_string(), |percent| format!("{:+.1}%", percent)),
+                entry.grade.as_deref().unwrap_or("-"),
+                format!("#{}", entry.request_id),
+                entry.approved_by
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(80))
+    }
+
+    /// Employees whose probation review is due, with the days left (negative once overdue).
+    pub fn write_probation_reviews<W: Write + ?Sized>(
+        out: &mut W,
+        employees: &[&EmployeeData],
+        today: NaiveDate,
+    ) -> io::Result<()> {
+        writeln!(out, "=== Probation Reviews Due ===")?;
+        writeln!(out, "{:<10} {:<24} {:<12} {:>10} {:>18}", "ID", "Name", "Ends", "Days Left", "Base Rate")?;
+        for employee in employees {
+            let emp = employee.as_employee();
+            let end = emp.profile().probation_end;
+            writeln!(
+                out,
+                "{:<10} {:<24} {:<12} {:>10} {:>18.2}",
+                emp.employee_id(),
+                emp.profile().name.chars().take(24).collect::<String>(),
+                end.map_or_else(|| "-".to_string(), |end| end.format("%Y-%m-%d").to_string()),
+                end.map_or_else(|| "-".to_string(), |end| (end - today).num_days().to_string()),
+                employee.base_rate()
+            )?;
+        }
+        writeln!(out, "{}", "-".repeat(78))
+    }
+
+    pub fn write_employee_detail<W: Write + ?Sized>(
+        out: &mut W,
+        employee: &EmployeeData,
+        preview: &PayrollData,
+    ) -> io::Result<()> {
+        let field = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        let emp = employee.as_employee();
+        let profile = emp.profile();
+        writeln!(out, "=== Employee {} ===", emp.employee_id())?;
+        writeln!(out, "Name: {}", profile.name)?;
+        writeln!(out, "Employee Type: {}", emp.employee_type())?;
+        writeln!(out, "Status: {}", profile.status.as_str())?;
+        if let Some(end) = profile.probation_end {
+            writeln!(out, "Probation Ends: {}", end.format("%Y-%m-%d"))?;
+        }
+        writeln!(out, "Department: {}", field(&profile.department))?;
+        writeln!(out, "Grade: {}", field(&profile.grade))?;
+        writeln!(out, "NIK: {}", field(&profile.nik))?;
+        writeln!(out, "NPWP: {}", field(&profile.npwp))?;
+        writeln!(out, "BPJS Ketenagakerjaan No.: {}", field(&profile.bpjs_tk_number))?;
+        writeln!(out, "Email: {}", field(&profile.email))?;
+        writeln!(
+            out,
+            "Date of Birth: {}",
+            field(&profile.date_of_birth.map(|date| date.format("%Y-%m-%d").to_string()))
+        )?;
+        writeln!(out, "Work Hours: {}", emp.work_hour())?;
+        match employee {
+            EmployeeData::Fulltime(fulltime) => {
+                Self::write_amount(out, "Base Salary", fulltime.base_salary)?;
+                writeln!(
+                    out,
+                    "PTKP Status: {}",
+                    fulltime.ptkp_status.map(|status| status.code()).unwrap_or("-")
+                )?;
+                if let Some(regime) = &profile.tax_regime {
+                    writeln!(out, "Tax Regime: {}", regime)?;
+                }
+            }
+            EmployeeData::Contract(contract) => {
+                Self::write_amount(out, "Hourly Rate", contract.hourly_rate)?;
+                if let Some(residency) = &profile.non_resident {
+                    writeln!(out, "Tax Residence: {} (PPh 26 at {}%)", residency.country, residency.rate * 100.0)?;
+                }
+            }
+        }
+        Self::write_amount(out, &format!("Allowance ({})", emp.periode_tunjangan().as_str()), emp.tunjangan())?;
+
+        let breakdown = preview.breakdown();
+        writeln!(out, "\n--- Preview for {} (not saved) ---", preview.pay_period)?;
+        for component in &preview.extra_earnings {
+            Self::write_amount(out, &format!("  + {}", component.name), component.amount)?;
+        }
+        Self::write_amount(out, "Gross Salary", preview.gross_salary)?;
+        Self::write_amount(out, &format!("  Tax ({})", preview.tax_name()), breakdown.tax)?;
+        Self::write_amount(out, "  BPJS Kesehatan", breakdown.bpjs_kesehatan)?;
+        Self::write_amount(out, "  BPJS TK", breakdown.bpjs_ketenagakerjaan)?;
+        for component in &preview.extra_deductions {
+            Self::write_amount(out, &format!("  - {}", component.name), component.amount)?;
+        }
+        Self::write_amount(out, "Deductions", preview.deductions)?;
+        Self::write_amount(out, "Net Salary", preview.net_salary)?;
+        writeln!(out, "{}", "-".repeat(40))
+    }
+
+    pub fn write_employee_list<W: Write + ?Sized>(
+        out: &mut W,
+        employees: &[&EmployeeData],
+        settings: &CalculationSettings,
+    ) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:<24} {:<9} {:<10} {:<16} {:>16}",
+            "ID", "Name", "Type", "Status", "Department", "Monthly Gross"
+        )?;
+        for employee in employees {
+            let kind = match employee {
+                EmployeeData::Fulltime(_) => "Fulltime",
+                EmployeeData::Contract(_) => "Contract",
+            };
+            let employee = employee.as_employee();
+            let profile = employee.profile();
+            writeln!(
+                out,
+                "{:<10} {:<24} {:<9} {:<10} {:<16} {:>16.2}",
+                employee.employee_id(),
+                profile.name.chars().take(24).collect::<String>(),
+                kind,
+                profile.status.as_str(),
+                profile.department.as_deref().unwrap_or("-"),
+                employee.gross_with(settings)
+            )?;
+        }
+        writeln!(out, "{} employees", employees.len())?;
+        writeln!(out, "{}", "-".repeat(90))
+    }
+
+    pub fn print_payroll_summary(payroll_data: &PayrollData) {
+        Self::print(|out| Self::write_payroll_summary(out, payroll_data));
+    }
+
+    pub fn print_all_payrolls<I>(payroll_records: I)
+    where
+        I: IntoIterator,
+        I::Item: Borrow<PayrollData>,
+    {
+        Self::print(|out| Self::write_all_payrolls(out, payroll_records));
+    }
+
+    pub fn print_total_summary(totals: &PayrollTotals) {
+        Self::print(|out| Self::write_total_summary(out, totals));
+    }
+
+    pub fn print_simulation(result: &SimulationResult) {
+        Self::print(|out| Self::write_simulation(out, result));
+    }
+
+    pub fn print_period_comparison(comparison: &PeriodComparison) {
+        Self::print(|out| Self::write_period_comparison(out, comparison));
+    }
+
+    pub fn print_employee_history(employee_id: &str, records: &[&PayrollData]) {
+        Self::print(|out| Self::write_employee_history(out, employee_id, records));
+    }
+
+    pub fn print_payroll_register(register: &PayrollRegister) {
+        Self::print(|out| Self::write_payroll_register(out, register));
+    }
+
+    pub fn print_remittance_summary(summary: &RemittanceSummary) {
+        Self::print(|out| Self::write_remittance_summary(out, summary));
+    }
+
+    pub fn print_cost_trend(months: &[MonthlyCost]) {
+        Self::print(|out| Self::write_cost_trend(out, months));
+    }
+
+    pub fn print_department_totals(months: &[DepartmentMonth]) {
+        Self::print(|out| Self::write_department_totals(out, months));
+    }
+
+    pub fn print_budget_variance(rows: &[BudgetVariance]) {
+        Self::print(|out| Self::write_budget_variance(out, rows));
+    }
+
+    pub fn print_violations(violations: &[Violation]) {
+        Self::print(|out| Self::write_violations(out, violations));
+    }
+
+    pub fn print_verification(verification: &Verification) {
+        Self::print(|out| Self::write_verification(out, verification));
+    }
+
+    pub fn print_purge(report: &PurgeReport) {
+        Self::print(|out| Self::write_purge(out, report));
+    }
+
+    pub fn print_payslip_verification(record: &PayrollData, verdict: PayslipVerdict) {
+        Self::print(|out| Self::write_payslip_verification(out, record, verdict));
+    }
+
+    pub fn print_statistics(scope: &str, statistics: &PayrollStatistics) {
+        Self::print(|out| Self::write_statistics(out, scope, statistics));
+    }
+
+    pub fn print_year_to_date(ytd: &YearToDate) {
+        Self::print(|out| Self::write_year_to_date(out, ytd));
+    }
+
+    pub fn print_bank_transfers(batch: &BankTransferBatch) {
+        Self::print(|out| Self::write_bank_transfers(out, batch));
+    }
+
+    pub fn print_ewallet_transfers(batch: &BankTransferBatch, wallet: EWallet) {
+        Self::print(|out| Self::write_ewallet_transfers(out, batch, wallet));
+    }
+
+    pub fn print_disbursement(run_id: u32, disbursement: &Disbursement) {
+        Self::print(|out| Self::write_disbursement(out, run_id, disbursement));
+    }
+
+    pub fn print_org_chart(payroll: &Payroll) {
+        Self::print(|out| Self::write_org_chart(out, payroll));
+    }
+
+    pub fn print_review_cycles(cycles: &[ReviewCycle]) {
+        Self::print(|out| Self::write_review_cycles(out, cycles));
+    }
+
+    pub fn print_bonus_run(run: &BonusRun) {
+        Self::print(|out| Self::write_bonus_run(out, run));
+    }
+
+    pub fn print_attendance_summary(payroll: &Payroll, period: &PayPeriod) {
+        Self::print(|out| Self::write_attendance_summary(out, payroll, period));
+    }
+
+    pub fn print_equity_grants(grants: &[&EquityGrant], on: NaiveDate) {
+        Self::print(|out| Self::write_equity_grants(out, grants, on));
+    }
+
+    pub fn print_vesting(events: &[(&EquityGrant, VestingEvent)]) {
+        Self::print(|out| Self::write_vesting(out, events));
+    }
+
+    pub fn print_insurance_plans(plans: &[InsurancePlan]) {
+        Self::print(|out| Self::write_insurance_plans(out, plans));
+    }
+
+    pub fn print_insurance_enrollments(enrollments: &[&InsuranceEnrollment]) {
+        Self::print(|out| Self::write_insurance_enrollments(out, enrollments));
+    }
+
+    pub fn print_terminations(terminations: &[&TerminationRecord]) {
+        Self::print(|out| Self::write_terminations(out, terminations));
+    }
+
+    pub fn print_natura(items: &[&NaturaItem]) {
+        Self::print(|out| Self::write_natura(out, items));
+    }
+
+    pub fn print_expense_claims(claims: &[&ExpenseClaim]) {
+        Self::print(|out| Self::write_expense_claims(out, claims));
+    }
+
+    pub fn print_travel(records: &[&TravelRecord]) {
+        Self::print(|out| Self::write_travel(out, records));
+    }
+
+    pub fn print_overtime_requests(requests: &[&OvertimeRequest]) {
+        Self::print(|out| Self::write_overtime_requests(out, requests));
+    }
+
+    pub fn print_overtime_exceptions(records: &[&PayrollData]) {
+        Self::print(|out| Self::write_overtime_exceptions(out, records));
+    }
+
+    pub fn print_timesheets(timesheets: &[&Timesheet]) {
+        Self::print(|out| Self::write_timesheets(out, timesheets));
+    }
+
+    pub fn print_salary_changes(requests: &[&SalaryChangeRequest]) {
+        Self::print(|out| Self::write_salary_changes(out, requests));
+    }
+
+    pub fn print_salary_history(employee_id: &str, history: &[&SalaryHistoryEntry]) {
+        Self::print(|out| Self::write_salary_history(out, employee_id, history));
+    }
+
+    pub fn print_probation_reviews(employees: &[&EmployeeData], today: NaiveDate) {
+        Self::print(|out| Self::write_probation_reviews(out, employees, today));
+    }
+
+    pub fn print_onboarding(checklists: &[&OnboardingChecklist]) {
+        Self::print(|out| Self::write_onboarding(out, checklists));
+    }
+
+    pub fn print_pin_statuses(payroll: &Payroll, now: DateTime<Utc>) {
+        Self::print(|out| Self::write_pin_statuses(out, payroll, now));
+    }
+
+    pub fn print_sync_report(report: &SyncReport) {
+        Self::print(|out| Self::write_sync_report(out, report));
+    }
+
+    pub fn print_employee_detail(employee: &EmployeeData, preview: &PayrollData) {
+        Self::print(|out| Self::write_employee_detail(out, employee, preview));
+    }
+
+    pub fn print_employee_list(employees: &[&EmployeeData], settings: &CalculationSettings) {
+        Self::print(|out| Self::write_employee_list(out, employees, settings));
+    }
+
+    pub fn render(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut buffer = Vec::new();
+        write(&mut buffer).expect("writing to a Vec cannot fail");
+        String::from_utf8(buffer).expect("presentation output is UTF-8")
+    }
+
+    fn print(write: impl FnOnce(&mut io::StdoutLock<'static>) -> io::Result<()>) {
+        write(&mut io::stdout().lock()).expect("failed to write to stdout");
+    }
+}
+
+impl fmt::Display for PayrollData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&PayrollPresentation::render(|out| {
+            PayrollPresentation::write_payroll_summary(out, self)
+        }))
+    }
+}
+
+impl fmt::Display for PayrollTotals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&PayrollPresentation::render(|out| {
+            PayrollPresentation::write_total_summary(out, self)
+        }))
+    }
+}
+
+impl fmt::Display for SimulationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&PayrollPresentation::render(|out| {
+            PayrollPresentation::write_simulation(out, self)
+        }))
+    }
+}
+
+impl fmt::Display for PeriodComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&PayrollPresentation::render(|out| {
+            PayrollPresentation::write_period_comparison(out, self)
+        }))
+    }
+}
@@ -0,0 +1,168 @@
+use crate::attendance::AttendanceEntry;
+use crate::compensation::{SalaryChangeRequest, SalaryHistoryEntry};
+use crate::credentials::EmployeeCredential;
+use crate::delivery::PayslipDelivery;
+use crate::disbursement::TransferConfirmation;
+use crate::equity::EquityGrant;
+use crate::expense::ExpenseClaim;
+use crate::insurance::InsuranceEnrollment;
+use crate::natura::NaturaItem;
+use crate::onboarding::OnboardingChecklist;
+use crate::overtime::OvertimeRequest;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::payslip::to_hex;
+use crate::review::PerformanceReview;
+use crate::severance::TerminationRecord;
+use crate::timesheet::Timesheet;
+use crate::travel::TravelRecord;
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Employee IDs given to pseudonymized employees start with this.
+pub const PSEUDONYM_PREFIX: &str = "anon-";
+
+/// Years a terminated employee's identity is kept when none are configured: as long as UU KUP
+/// requires the books payroll taxes were withheld by to be kept.
+pub const DEFAULT_RETENTION_YEARS: u32 = 10;
+
+/// Identifies the layout of [`SubjectData`] documents.
+pub const SUBJECT_DATA_FORMAT: &str = "employee-management/subject-data/v1";
+
+/// The self-service sign-in of an employee, without the PIN hash and salt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialSummary {
+    pub updated: DateTime<Utc>,
+    pub must_change: bool,
+    pub failed_attempts: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl From<&EmployeeCredential> for CredentialSummary {
+    fn from(credential: &EmployeeCredential) -> Self {
+        Self {
+            updated: credential.updated,
+            must_change: credential.must_change,
+            failed_attempts: credential.failed_attempts,
+            locked_until: credential.locked_until,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CycleReview {
+    pub cycle: String,
+    pub review: PerformanceReview,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunDelivery {
+    pub run_id: u32,
+    pub pay_period: String,
+    pub delivery: PayslipDelivery,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunTransfer {
+    pub run_id: u32,
+    pub pay_period: String,
+    pub provider: String,
+    pub transfer: TransferConfirmation,
+}
+
+/// Everything a payroll holds on one employee, for answering a data-subject access request. Each
+/// list is in the order the payroll keeps it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubjectData {
+    pub format: String,
+    pub exported: DateTime<Utc>,
+    pub employee: EmployeeData,
+    pub self_service: Option<CredentialSummary>,
+    pub onboarding: Option<OnboardingChecklist>,
+    pub payroll_records: Vec<PayrollData>,
+    pub payslip_deliveries: Vec<RunDelivery>,
+    pub transfers: Vec<RunTransfer>,
+    pub salary_changes: Vec<SalaryChangeRequest>,
+    pub salary_history: Vec<SalaryHistoryEntry>,
+    pub reviews: Vec<CycleReview>,
+    pub timesheets: Vec<Timesheet>,
+    pub overtime_requests: Vec<OvertimeRequest>,
+    pub attendance: Vec<AttendanceEntry>,
+    pub travel: Vec<TravelRecord>,
+    pub expense_claims: Vec<ExpenseClaim>,
+    pub insurance_enrollments: Vec<InsuranceEnrollment>,
+    pub equity_grants: Vec<EquityGrant>,
+    pub natura: Vec<NaturaItem>,
+    pub terminations: Vec<TerminationRecord>,
+}
+
+/// What pseudonymizing an employee changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pseudonymization {
+    pub employee_id: String,
+    pub pseudonym: String,
+    pub payroll_records: usize,
+    /// Entries of other kinds (timesheets, claims, reviews, run deliveries...) now under the pseudonym.
+    pub other_entries: usize,
+}
+
+/// A fresh pseudonym: 16 bytes from the OS random number generator. It is random rather than
+/// derived from the employee ID, so it cannot be traced back by hashing candidate IDs.
+pub fn new_pseudonym() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", PSEUDONYM_PREFIX, to_hex(&bytes))
+}
+
+/// An employee ID being replaced by a pseudonym wherever an entry names it.
+#[derive(Debug, Clone, Copy)]
+pub struct Renaming<'a> {
+    pub employee_id: &'a str,
+    pub pseudonym: &'a str,
+}
+
+impl Renaming<'_> {
+    /// Renames `id` if it is the employee's: 1 when it was, so renamed entries can be counted.
+    pub fn id(&self, id: &mut String) -> usize {
+        match id == self.employee_id {
+            true => {
+                *id = self.pseudonym.to_string();
+                1
+            }
+            false => 0,
+        }
+    }
+
+    pub fn option(&self, id: &mut Option<String>) -> usize {
+        id.as_mut().map_or(0, |id| self.id(id))
+    }
+}
+
+pub fn is_pseudonym(employee_id: &str) -> bool {
+    employee_id.starts_with(PSEUDONYM_PREFIX)
+}
+
+/// The first day an employee whose employment ended on `last_day` may be pseudonymized.
+pub fn retention_ends(last_day: NaiveDate, years: u32) -> NaiveDate {
+    last_day
+        .checked_add_months(Months::new(years.saturating_mul(12)))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+/// Replaces the employee's ID with `pseudonym` and drops everything identifying them: name, tax
+/// and BPJS numbers, e-mail, date of birth and bank accounts. Department, grade and pay stay, so
+/// the employee still counts towards totals and averages.
+pub fn scrub_employee(employee: &mut EmployeeData, pseudonym: &str) {
+    match employee {
+        EmployeeData::Fulltime(emp) => emp.employee_id = pseudonym.to_string(),
+        EmployeeData::Contract(emp) => emp.employee_id = pseudonym.to_string(),
+    }
+    let profile = employee.profile_mut();
+    profile.name = pseudonym.to_string();
+    profile.nik = None;
+    profile.npwp = None;
+    profile.bpjs_tk_number = None;
+    profile.email = None;
+    profile.date_of_birth = None;
+    profile.salary_split.clear();
+}
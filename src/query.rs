@@ -0,0 +1,324 @@
+use crate::employee::EmployeeStatus;
+use crate::payroll::{EmployeeData, Payroll, PayrollData};
+use crate::period::PayPeriod;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmployeeKind {
+    Fulltime,
+    Contract,
+}
+
+impl EmployeeKind {
+    pub fn of(employee: &EmployeeData) -> Self {
+        match employee {
+            EmployeeData::Fulltime(_) => EmployeeKind::Fulltime,
+            EmployeeData::Contract(_) => EmployeeKind::Contract,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmployeeKind::Fulltime => "fulltime",
+            EmployeeKind::Contract => "contract",
+        }
+    }
+}
+
+impl FromStr for EmployeeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fulltime" => Ok(EmployeeKind::Fulltime),
+            "contract" => Ok(EmployeeKind::Contract),
+            _ => Err(format!("Unknown employee type '{}'", s.trim())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    ProcessedDate,
+    PayPeriod,
+    Gross,
+    Net,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<'a> {
+    pub records: Vec<&'a PayrollData>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl Page<'_> {
+    pub fn next_offset(&self) -> Option<usize> {
+        let next = self.offset + self.records.len();
+        if self.limit.is_some() && next < self.total {
+            Some(next)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PayrollQuery<'a> {
+    payroll: &'a Payroll,
+    employee_id: Option<String>,
+    period: Option<String>,
+    from: Option<PayPeriod>,
+    to: Option<PayPeriod>,
+    kind: Option<EmployeeKind>,
+    include_voided: bool,
+    sort: Option<(SortKey, SortOrder)>,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl<'a> PayrollQuery<'a> {
+    pub fn new(payroll: &'a Payroll) -> Self {
+        Self {
+            payroll,
+            employee_id: None,
+            period: None,
+            from: None,
+            to: None,
+            kind: None,
+            include_voided: false,
+            sort: None,
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    pub fn employee(mut self, employee_id: &str) -> Self {
+        self.employee_id = Some(employee_id.trim().to_string());
+        self
+    }
+
+    pub fn period(mut self, pay_period: &str) -> Self {
+        self.period = Some(pay_period.trim().to_string());
+        self
+    }
+
+    pub fn from_period(mut self, period: PayPeriod) -> Self {
+        self.from = Some(period);
+        self
+    }
+
+    pub fn to_period(mut self, period: PayPeriod) -> Self {
+        self.to = Some(period);
+        self
+    }
+
+    pub fn employee_kind(mut self, kind: EmployeeKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn include_voided(mut self, include_voided: bool) -> Self {
+        self.include_voided = include_voided;
+        self
+    }
+
+    pub fn sort_by(mut self, key: SortKey, order: SortOrder) -> Self {
+        self.sort = Some((key, order));
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, record: &PayrollData) -> bool {
+        if record.voided && !self.include_voided {
+            return false;
+        }
+        if let Some(employee_id) = &self.employee_id {
+            if record.employee.as_employee().employee_id() != employee_id {
+                return false;
+            }
+        }
+        if let Some(kind) = self.kind {
+            if EmployeeKind::of(&record.employee) != kind {
+                return false;
+            }
+        }
+        if let Some(period) = &self.period {
+            if !PayPeriod::same_period(&record.pay_period, period) {
+                return false;
+            }
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let period = match record.pay_period.parse::<PayPeriod>() {
+                Ok(period) => period,
+                Err(_) => return false,
+            };
+            if self.from.is_some_and(|from| period < from) || self.to.is_some_and(|to| period > to) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn count(&self) -> usize {
+        self.payroll
+            .get_payroll_records()
+            .iter()
+            .filter(|record| self.matches(record))
+            .count()
+    }
+
+    pub fn run(&self) -> Page<'a> {
+        let mut records: Vec<&'a PayrollData> = self
+            .payroll
+            .get_payroll_records()
+            .iter()
+            .filter(|record| self.matches(record))
+            .collect();
+        if let Some((key, order)) = self.sort {
+            records.sort_by(|a, b| {
+                let ordering = compare(a, b, key);
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
+        }
+
+        let total = records.len();
+        let records = records
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        Page {
+            records,
+            total,
+            offset: self.offset,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Filters the employee registry. Salary bounds apply to the monthly gross under the current settings.
+#[derive(Debug, Clone)]
+pub struct EmployeeQuery<'a> {
+    payroll: &'a Payroll,
+    kind: Option<EmployeeKind>,
+    status: Option<EmployeeStatus>,
+    department: Option<String>,
+    manager: Option<String>,
+    min_salary: Option<f64>,
+    max_salary: Option<f64>,
+}
+
+impl<'a> EmployeeQuery<'a> {
+    pub fn new(payroll: &'a Payroll) -> Self {
+        Self {
+            payroll,
+            kind: None,
+            status: None,
+            department: None,
+            manager: None,
+            min_salary: None,
+            max_salary: None,
+        }
+    }
+
+    pub fn employee_kind(mut self, kind: EmployeeKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn status(mut self, status: EmployeeStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn department(mut self, department: &str) -> Self {
+        self.department = Some(department.trim().to_string());
+        self
+    }
+
+    /// Direct reports of `manager_id`.
+    pub fn manager(mut self, manager_id: &str) -> Self {
+        self.manager = Some(manager_id.trim().to_string());
+        self
+    }
+
+    pub fn min_salary(mut self, amount: f64) -> Self {
+        self.min_salary = Some(amount);
+        self
+    }
+
+    pub fn max_salary(mut self, amount: f64) -> Self {
+        self.max_salary = Some(amount);
+        self
+    }
+
+    pub fn salary(&self, employee: &EmployeeData) -> f64 {
+        employee.as_employee().gross_with(&self.payroll.settings)
+    }
+
+    pub fn matches(&self, employee: &EmployeeData) -> bool {
+        let profile = employee.as_employee().profile();
+        if self.kind.is_some_and(|kind| EmployeeKind::of(employee) != kind) {
+            return false;
+        }
+        if self.status.is_some_and(|status| profile.status != status) {
+            return false;
+        }
+        if let Some(department) = &self.department {
+            if !profile
+                .department
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(department))
+            {
+                return false;
+            }
+        }
+        if self.manager.is_some() && profile.manager_id != self.manager {
+            return false;
+        }
+        let salary = self.salary(employee);
+        !(self.min_salary.is_some_and(|min| salary < min) || self.max_salary.is_some_and(|max| salary > max))
+    }
+
+    pub fn run(&self) -> Vec<&'a EmployeeData> {
+        let mut employees: Vec<&'a EmployeeData> =
+            self.payroll.employees.iter().filter(|employee| self.matches(employee)).collect();
+        employees.sort_by(|a, b| a.as_employee().employee_id().cmp(b.as_employee().employee_id()));
+        employees
+    }
+}
+
+fn compare(a: &PayrollData, b: &PayrollData, key: SortKey) -> Ordering {
+    match key {
+        SortKey::ProcessedDate => a.processed_date.cmp(&b.processed_date),
+        SortKey::PayPeriod => {
+            match (a.pay_period.parse::<PayPeriod>(), b.pay_period.parse::<PayPeriod>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.pay_period.cmp(&b.pay_period),
+            }
+        }
+        SortKey::Gross => a.gross_salary.total_cmp(&b.gross_salary),
+        SortKey::Net => a.net_salary.total_cmp(&b.net_salary),
+    }
+}
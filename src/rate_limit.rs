@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Buckets are pruned once this many clients have one, dropping those that have refilled.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Limits each client to `requests_per_minute`, allowing bursts of up to `burst` requests. A token
+/// bucket per client refills steadily, so a client that stays under the rate is never refused.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `requests_per_minute`, or `None` for 0 (no limit). A `burst` of 0 allows
+    /// a second's worth of requests at once.
+    pub fn new(requests_per_minute: u32, burst: u32) -> Option<Self> {
+        if requests_per_minute == 0 {
+            return None;
+        }
+        let per_second = f64::from(requests_per_minute) / 60.0;
+        let burst = match burst {
+            0 => per_second.ceil(),
+            burst => f64::from(burst),
+        };
+        Some(Self {
+            per_second,
+            burst,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Takes a request from `client`'s allowance at `now`, or tells how long until one is left.
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD && !buckets.contains_key(client) {
+            buckets.retain(|_, bucket| self.refilled(bucket, now) < self.burst);
+        }
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_second).min(self.burst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_rate_means_no_limit() {
+        assert!(RateLimiter::new(0, 10).is_none());
+    }
+
+    #[test]
+    fn a_burst_is_allowed_and_then_refills_steadily() {
+        let limiter = RateLimiter::new(60, 3).unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("ci", start).is_ok());
+        }
+        assert_eq!(limiter.check("ci", start), Err(Duration::from_secs(1)));
+
+        assert!(limiter.check("ci", start + Duration::from_millis(500)).is_err());
+        assert!(limiter.check("ci", start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.check("ci", start + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn clients_have_their_own_allowance() {
+        let limiter = RateLimiter::new(60, 1).unwrap();
+        let now = Instant::now();
+        assert!(limiter.check("ci", now).is_ok());
+        assert!(limiter.check("ci", now).is_err());
+        assert!(limiter.check("reports", now).is_ok());
+    }
+
+    #[test]
+    fn the_default_burst_is_a_seconds_worth() {
+        let limiter = RateLimiter::new(120, 0).unwrap();
+        let now = Instant::now();
+        assert!(limiter.check("ci", now).is_ok());
+        assert!(limiter.check("ci", now).is_ok());
+        assert!(limiter.check("ci", now).is_err());
+    }
+
+    #[test]
+    fn a_client_under_the_rate_is_never_refused() {
+        let limiter = RateLimiter::new(60, 1).unwrap();
+        let start = Instant::now();
+        for second in 0..100 {
+            assert!(limiter.check("ci", start + Duration::from_secs(second)).is_ok());
+        }
+    }
+}
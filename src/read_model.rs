@@ -0,0 +1,136 @@
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::report::PeriodReport;
+use crate::retention::DepartmentTotals;
+use crate::self_service::YearToDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Totals of one department's active records in one pay period.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DepartmentMonth {
+    /// `YYYY-MM`.
+    pub pay_period: String,
+    pub department: Option<String>,
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+impl DepartmentMonth {
+    pub fn new(pay_period: &str, department: Option<String>) -> Self {
+        Self {
+            pay_period: pay_period.to_string(),
+            department,
+            ..Self::default()
+        }
+    }
+
+    /// Adds the department's share of a purged period's summary.
+    pub fn add(&mut self, totals: &DepartmentTotals) {
+        self.employees += totals.employees;
+        self.gross += totals.gross;
+        self.deductions += totals.deductions;
+        self.net += totals.net;
+    }
+}
+
+/// Report totals kept apart from the payroll records they are derived from: each stored record is
+/// folded in once, so reports over long histories do not go through every record again. They are
+/// never saved; a loaded payroll builds them on its first report.
+#[derive(Debug, Default)]
+pub struct ReadModels {
+    /// How many of the stored records are folded in; any after them are new.
+    applied: usize,
+    year_to_date: HashMap<(String, i32), YearToDate>,
+    departments: BTreeMap<(PayPeriod, Option<String>), DepartmentMonth>,
+}
+
+impl ReadModels {
+    /// Folds in the records stored since the last call. Records changed in place are not noticed,
+    /// so whoever changes them has to [`reset`](Self::reset) first.
+    pub fn catch_up(&mut self, records: &[PayrollData]) {
+        if records.len() < self.applied {
+            self.reset();
+        }
+        for record in &records[self.applied..] {
+            self.apply(record);
+        }
+        self.applied = records.len();
+    }
+
+    /// Forgets every total; the next catch-up starts over from the first record.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn apply(&mut self, record: &PayrollData) {
+        if record.voided {
+            return;
+        }
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee = record.employee.as_employee();
+        let breakdown = record.breakdown();
+        let ytd = self
+            .year_to_date
+            .entry((employee.employee_id().to_string(), period.year))
+            .or_insert_with(|| YearToDate {
+                year: period.year,
+                ..YearToDate::default()
+            });
+        ytd.periods += 1;
+        ytd.gross += record.gross_salary;
+        ytd.tax += breakdown.tax;
+        ytd.bpjs += breakdown.bpjs_kesehatan + breakdown.bpjs_ketenagakerjaan;
+        ytd.deductions += record.deductions;
+        ytd.net += record.net_salary;
+
+        let department = employee.profile().department.clone();
+        let month = self
+            .departments
+            .entry((period, department.clone()))
+            .or_insert_with(|| DepartmentMonth::new(&format!("{}-{:02}", period.year, period.month), department));
+        month.employees += 1;
+        month.gross += record.gross_salary;
+        month.deductions += record.deductions;
+        month.net += record.net_salary;
+    }
+
+    pub fn year_to_date(&self, employee_id: &str, year: i32) -> YearToDate {
+        self.year_to_date
+            .get(&(employee_id.to_string(), year))
+            .copied()
+            .unwrap_or(YearToDate {
+                year,
+                ..YearToDate::default()
+            })
+    }
+
+    /// Department totals of the pay periods `from` to `to`, by period and then department.
+    pub fn departments(&self, from: PayPeriod, to: PayPeriod) -> impl Iterator<Item = &DepartmentMonth> {
+        self.departments
+            .range((from, None)..)
+            .take_while(move |((period, _), _)| *period <= to)
+            .map(|(_, month)| month)
+    }
+
+    /// The period's totals across departments.
+    pub fn period_report(&self, period: PayPeriod) -> PeriodReport {
+        let mut report = PeriodReport {
+            pay_period: format!("{}-{:02}", period.year, period.month),
+            ..PeriodReport::default()
+        };
+        for month in self.departments(period, period) {
+            report.employees += month.employees;
+            report.gross += month.gross;
+            report.deductions += month.deductions;
+            report.net += month.net;
+        }
+        report
+    }
+}
@@ -0,0 +1,154 @@
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::read_model::ReadModels;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Deref;
+use std::sync::{Mutex, MutexGuard};
+
+/// An employee and pay period whose totals a record counts towards.
+pub type RecordKey = (String, PayPeriod);
+
+/// The stored payroll records. Reading goes through the slice they deref to; every change goes
+/// through the methods here, which keep the report read models in step and note the employees and
+/// periods whose totals changed, for caches kept outside the payroll (see
+/// [`take_changes`](Self::take_changes)).
+///
+/// Saved as the plain list of records.
+#[derive(Debug, Default)]
+pub struct PayrollRecords {
+    records: Vec<PayrollData>,
+    read_models: Mutex<ReadModels>,
+    changed: BTreeSet<RecordKey>,
+    /// The id each record moved to a new id had before, for the payroll ledger.
+    renamed: BTreeMap<String, String>,
+}
+
+impl PayrollRecords {
+    /// Stores a new record; the read models fold it in on the next report.
+    pub fn push(&mut self, record: PayrollData) {
+        self.changed.extend(key(&record));
+        self.records.push(record);
+    }
+
+    /// Changes the `index`th record in place, or returns `None` when there is none.
+    pub fn update<R>(&mut self, index: usize, f: impl FnOnce(&mut PayrollData) -> R) -> Option<R> {
+        let record = self.records.get_mut(index)?;
+        let record_id = record.record_id.clone();
+        self.changed.extend(key(record));
+        let result = f(record);
+        self.changed.extend(key(record));
+        note_rename(&mut self.renamed, record_id, record);
+        self.reset_read_models();
+        Some(result)
+    }
+
+    /// Changes every record in place.
+    pub fn update_all(&mut self, mut f: impl FnMut(&mut PayrollData)) {
+        for record in &mut self.records {
+            let record_id = record.record_id.clone();
+            self.changed.extend(key(record));
+            f(record);
+            self.changed.extend(key(record));
+            note_rename(&mut self.renamed, record_id, record);
+        }
+        self.reset_read_models();
+    }
+
+    /// Drops the records after the first `len`.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.records.len() {
+            return;
+        }
+        self.changed.extend(self.records[len..].iter().filter_map(key));
+        self.records.truncate(len);
+        self.reset_read_models();
+    }
+
+    /// Drops the records `keep` returns false for.
+    pub fn retain(&mut self, mut keep: impl FnMut(&PayrollData) -> bool) {
+        let changed = &mut self.changed;
+        let before = self.records.len();
+        self.records.retain(|record| {
+            let kept = keep(record);
+            if !kept {
+                changed.extend(key(record));
+            }
+            kept
+        });
+        if self.records.len() != before {
+            self.reset_read_models();
+        }
+    }
+
+    /// The employees and periods whose totals changed since the last call, so results cached for
+    /// them elsewhere can be dropped.
+    pub fn take_changes(&mut self) -> BTreeSet<RecordKey> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// The id the record now at `record_id` had before it was moved there.
+    pub fn renamed_from(&self, record_id: &str) -> Option<&str> {
+        self.renamed.get(record_id).map(String::as_str)
+    }
+
+    /// The read models, caught up with the records.
+    pub fn read_models(&self) -> MutexGuard<'_, ReadModels> {
+        let mut models = self.read_models.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        models.catch_up(&self.records);
+        models
+    }
+
+    pub fn into_vec(self) -> Vec<PayrollData> {
+        self.records
+    }
+
+    /// Has the read models rebuilt on the next report, after records changed in place.
+    fn reset_read_models(&mut self) {
+        self.read_models
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .reset();
+    }
+}
+
+fn note_rename(renamed: &mut BTreeMap<String, String>, previous: String, record: &PayrollData) {
+    if previous.is_empty() || previous == record.record_id {
+        return;
+    }
+    renamed.insert(record.record_id.clone(), previous);
+}
+
+fn key(record: &PayrollData) -> Option<RecordKey> {
+    let period = record.pay_period.parse::<PayPeriod>().ok()?;
+    Some((record.employee.as_employee().employee_id().to_string(), period))
+}
+
+impl Deref for PayrollRecords {
+    type Target = [PayrollData];
+
+    fn deref(&self) -> &[PayrollData] {
+        &self.records
+    }
+}
+
+impl From<Vec<PayrollData>> for PayrollRecords {
+    fn from(records: Vec<PayrollData>) -> Self {
+        Self {
+            records,
+            ..Self::default()
+        }
+    }
+}
+
+impl Serialize for PayrollRecords {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.records.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PayrollRecords {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,186 @@
+use crate::attendance::ALLOWANCE_TAX;
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::equity::EQUITY_TAX;
+use crate::export::write_csv_row;
+use crate::insurance::INSURANCE_DUE_DAY;
+use crate::natura::NATURA_TAX;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::severance::SEVERANCE_TAX;
+use crate::travel::PER_DIEM_TAX;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::io::{self, Write};
+
+pub const DJP: &str = "DJP (tax office)";
+pub const BPJS_KESEHATAN: &str = "BPJS Kesehatan";
+pub const BPJS_KETENAGAKERJAAN: &str = "BPJS Ketenagakerjaan";
+/// Days of the month after the pay period by which withheld tax and BPJS contributions are due.
+pub const TAX_DUE_DAY: u32 = 15;
+pub const BPJS_KESEHATAN_DUE_DAY: u32 = 10;
+pub const BPJS_KETENAGAKERJAAN_DUE_DAY: u32 = 15;
+/// Deduction lines withholding PPh 21 on income paid outside the salary calculation.
+pub const EXTRA_TAX_LINES: [&str; 5] = [ALLOWANCE_TAX, PER_DIEM_TAX, EQUITY_TAX, NATURA_TAX, SEVERANCE_TAX];
+
+/// One amount the company has to pay over for a pay period, split into the part withheld from
+/// employees and the part the company contributes on top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemittanceLine {
+    pub item: String,
+    pub payee: String,
+    pub employee_share: f64,
+    pub employer_share: f64,
+    pub due_date: NaiveDate,
+}
+
+impl RemittanceLine {
+    pub fn total(&self) -> f64 {
+        self.employee_share + self.employer_share
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemittanceSummary {
+    pub pay_period: PayPeriod,
+    pub employees: usize,
+    pub lines: Vec<RemittanceLine>,
+}
+
+/// `day` of the month after `period`, moved to the next Monday when it falls on a weekend.
+pub fn due_date(period: PayPeriod, day: u32) -> NaiveDate {
+    let next = period.next();
+    let date = NaiveDate::from_ymd_opt(next.year, next.month, day).unwrap_or_else(|| next.first_day());
+    match date.weekday() {
+        Weekday::Sat => date + chrono::Duration::days(2),
+        Weekday::Sun => date + chrono::Duration::days(1),
+        _ => date,
+    }
+}
+
+impl RemittanceSummary {
+    /// Totals PPh 21, BPJS Kesehatan, each BPJS Ketenagakerjaan program and each insurance plan's
+    /// premiums over the non-voided `records`. PPh 21 and BPJS Ketenagakerjaan are due on the 15th
+    /// of the next month, BPJS Kesehatan and insurance premiums on the 10th.
+    pub fn from_records<'a>(
+        pay_period: PayPeriod,
+        records: impl IntoIterator<Item = &'a PayrollData>,
+        rates: &BpjsRates,
+    ) -> Self {
+        let mut employees = 0;
+        let mut tax = 0.0;
+        let mut pph26 = 0.0;
+        let mut kesehatan = (0.0, 0.0);
+        let mut programs = BpjsContribution::default();
+        let mut premiums: Vec<(&str, &str, f64, f64)> = Vec::new();
+        for record in records.into_iter().filter(|record| !record.voided) {
+            employees += 1;
+            let breakdown = record.breakdown();
+            let withheld = breakdown.tax
+                + record
+                    .extra_deductions
+                    .iter()
+                    .filter(|deduction| EXTRA_TAX_LINES.contains(&deduction.name.as_str()))
+                    .map(|deduction| deduction.amount)
+                    .sum::<f64>();
+            match record.employee.as_employee().profile().non_resident {
+                Some(_) => pph26 += withheld,
+                None => tax += withheld,
+            }
+            kesehatan.0 += breakdown.bpjs_kesehatan;
+            kesehatan.1 += BpjsContribution::kesehatan_employer(record, rates);
+            if let Some(contribution) = BpjsContribution::for_record(record, rates) {
+                programs.wage_base += contribution.wage_base;
+                programs.jht_employee += contribution.jht_employee;
+                programs.jht_employer += contribution.jht_employer;
+                programs.jp_employee += contribution.jp_employee;
+                programs.jp_employer += contribution.jp_employer;
+                programs.jkk += contribution.jkk;
+                programs.jkm += contribution.jkm;
+            }
+            for premium in &record.insurance {
+                match premiums
+                    .iter_mut()
+                    .find(|(insurer, plan, _, _)| *insurer == premium.insurer && *plan == premium.plan)
+                {
+                    Some((_, _, employee_share, employer_share)) => {
+                        *employee_share += premium.employee_share;
+                        *employer_share += premium.employer_share;
+                    }
+                    None => premiums.push((
+                        &premium.insurer,
+                        &premium.plan,
+                        premium.employee_share,
+                        premium.employer_share,
+                    )),
+                }
+            }
+        }
+
+        let line = |item: &str, payee: &str, employee_share, employer_share, day| RemittanceLine {
+            item: item.to_string(),
+            payee: payee.to_string(),
+            employee_share,
+            employer_share,
+            due_date: due_date(pay_period, day),
+        };
+        let tk_day = BPJS_KETENAGAKERJAAN_DUE_DAY;
+        let mut lines = vec![
+            line("PPh 21", DJP, tax, 0.0, TAX_DUE_DAY),
+            line("BPJS Kesehatan", BPJS_KESEHATAN, kesehatan.0, kesehatan.1, BPJS_KESEHATAN_DUE_DAY),
+            line("JHT (old-age savings)", BPJS_KETENAGAKERJAAN, programs.jht_employee, programs.jht_employer, tk_day),
+            line("JP (pension)", BPJS_KETENAGAKERJAAN, programs.jp_employee, programs.jp_employer, tk_day),
+            line("JKK (work accident)", BPJS_KETENAGAKERJAAN, 0.0, programs.jkk, tk_day),
+            line("JKM (death benefit)", BPJS_KETENAGAKERJAAN, 0.0, programs.jkm, tk_day),
+        ];
+        if pph26 > 0.0 {
+            lines.insert(1, line("PPh 26", DJP, pph26, 0.0, TAX_DUE_DAY));
+        }
+        for (insurer, plan, employee_share, employer_share) in premiums {
+            lines.push(line(plan, insurer, employee_share, employer_share, INSURANCE_DUE_DAY));
+        }
+        Self {
+            pay_period,
+            employees,
+            lines,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.lines.iter().map(RemittanceLine::total).sum()
+    }
+
+    /// Totals per payee, in the order the payees first appear, with the earliest due date of each.
+    pub fn by_payee(&self) -> Vec<(&str, f64, NaiveDate)> {
+        let mut payees: Vec<(&str, f64, NaiveDate)> = Vec::new();
+        for line in &self.lines {
+            match payees.iter_mut().find(|(payee, _, _)| *payee == line.payee) {
+                Some((_, amount, due)) => {
+                    *amount += line.total();
+                    *due = (*due).min(line.due_date);
+                }
+                None => payees.push((&line.payee, line.total(), line.due_date)),
+            }
+        }
+        payees
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_csv_row(
+            &mut writer,
+            &["Item", "Payee", "Employee Share", "Employer Share", "Total", "Due Date"].map(String::from),
+        )?;
+        for line in &self.lines {
+            write_csv_row(
+                &mut writer,
+                &[
+                    line.item.clone(),
+                    line.payee.clone(),
+                    format!("{:.2}", line.employee_share),
+                    format!("{:.2}", line.employer_share),
+                    format!("{:.2}", line.total()),
+                    line.due_date.format("%Y-%m-%d").to_string(),
+                ],
+            )?;
+        }
+        writer.flush()
+    }
+}
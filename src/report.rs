@@ -0,0 +1,350 @@
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::period::PayPeriod;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeReason {
+    NewInPeriod,
+    MissingFromPeriod,
+    EmployeeTypeChanged,
+    HoursChanged { from: f64, to: f64 },
+    AllowanceChanged { from: f64, to: f64 },
+    AllowancePeriodChanged,
+    SalaryRevision { from: f64, to: f64 },
+    PtkpStatusChanged,
+}
+
+impl fmt::Display for ChangeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeReason::NewInPeriod => write!(f, "no record in previous period"),
+            ChangeReason::MissingFromPeriod => write!(f, "no record in current period"),
+            ChangeReason::EmployeeTypeChanged => write!(f, "employee type changed"),
+            ChangeReason::HoursChanged { from, to } => {
+                write!(f, "hours changed {} -> {}", from, to)
+            }
+            ChangeReason::AllowanceChanged { from, to } => {
+                write!(f, "allowance changed {:.2} -> {:.2}", from, to)
+            }
+            ChangeReason::AllowancePeriodChanged => write!(f, "allowance period changed"),
+            ChangeReason::SalaryRevision { from, to } => {
+                write!(f, "salary revision {:.2} -> {:.2}", from, to)
+            }
+            ChangeReason::PtkpStatusChanged => write!(f, "PTKP status changed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmployeePeriodDiff {
+    pub employee_id: String,
+    pub previous: Option<PayrollData>,
+    pub current: Option<PayrollData>,
+    pub reasons: Vec<ChangeReason>,
+}
+
+impl EmployeePeriodDiff {
+    pub fn gross_change(&self) -> f64 {
+        Self::amount(&self.current, |r| r.gross_salary) - Self::amount(&self.previous, |r| r.gross_salary)
+    }
+
+    pub fn deduction_change(&self) -> f64 {
+        Self::amount(&self.current, |r| r.deductions) - Self::amount(&self.previous, |r| r.deductions)
+    }
+
+    pub fn net_change(&self) -> f64 {
+        Self::amount(&self.current, |r| r.net_salary) - Self::amount(&self.previous, |r| r.net_salary)
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.reasons.is_empty()
+            || self.gross_change().abs() > 0.005
+            || self.net_change().abs() > 0.005
+    }
+
+    fn amount(record: &Option<PayrollData>, field: impl Fn(&PayrollData) -> f64) -> f64 {
+        record.as_ref().map(field).unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PeriodComparison {
+    pub previous_period: String,
+    pub current_period: String,
+    pub employees: Vec<EmployeePeriodDiff>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PayrollTotals {
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+impl PayrollTotals {
+    pub fn add(&mut self, record: &PayrollData) {
+        if record.voided {
+            return;
+        }
+        self.employees += 1;
+        self.gross += record.gross_salary;
+        self.deductions += record.deductions;
+        self.net += record.net_salary;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmployeeTrend {
+    pub periods: usize,
+    pub average_net: f64,
+    pub latest_period: PayPeriod,
+    pub latest_net: f64,
+    pub ytd_tax: f64,
+    pub net_change: Option<f64>,
+}
+
+impl EmployeeTrend {
+    /// Trend figures over one employee's records. Voided records and unparseable periods are
+    /// ignored; a later record for the same period replaces an earlier one.
+    pub fn from_records(records: &[&PayrollData]) -> Option<Self> {
+        let mut by_period: BTreeMap<PayPeriod, &PayrollData> = BTreeMap::new();
+        for record in records.iter().filter(|record| !record.voided) {
+            if let Ok(period) = record.pay_period.parse::<PayPeriod>() {
+                by_period.insert(period, record);
+            }
+        }
+        let (&latest_period, latest) = by_period.last_key_value()?;
+
+        Some(Self {
+            periods: by_period.len(),
+            average_net: by_period.values().map(|record| record.net_salary).sum::<f64>() / by_period.len() as f64,
+            latest_period,
+            latest_net: latest.net_salary,
+            ytd_tax: by_period
+                .iter()
+                .filter(|(period, _)| period.year == latest_period.year)
+                .map(|(_, record)| record.breakdown().tax)
+                .sum(),
+            net_change: by_period
+                .get(&latest_period.previous())
+                .map(|previous| latest.net_salary - previous.net_salary),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonthlyCost {
+    pub period: PayPeriod,
+    pub headcount: usize,
+    pub gross: f64,
+    pub employer_cost: f64,
+}
+
+impl MonthlyCost {
+    pub fn average_gross(&self) -> f64 {
+        if self.headcount == 0 {
+            0.0
+        } else {
+            self.gross / self.headcount as f64
+        }
+    }
+}
+
+pub struct PayrollReport;
+
+impl PayrollReport {
+    pub fn compare_periods(
+        records: &[PayrollData],
+        previous_period: &str,
+        current_period: &str,
+    ) -> PeriodComparison {
+        let previous = Self::latest_records(records, previous_period);
+        let current = Self::latest_records(records, current_period);
+
+        let mut employee_ids: Vec<String> = previous
+            .iter()
+            .chain(current.iter())
+            .map(|record| record.employee.as_employee().employee_id().to_string())
+            .collect();
+        employee_ids.sort();
+        employee_ids.dedup();
+
+        let employees = employee_ids
+            .into_iter()
+            .map(|employee_id| {
+                let find = |list: &[&PayrollData]| {
+                    list.iter()
+                        .find(|r| r.employee.as_employee().employee_id() == employee_id)
+                        .map(|r| (*r).clone())
+                };
+                let previous = find(&previous);
+                let current = find(&current);
+                let reasons = Self::explain(previous.as_ref(), current.as_ref());
+                EmployeePeriodDiff {
+                    employee_id,
+                    previous,
+                    current,
+                    reasons,
+                }
+            })
+            .collect();
+
+        PeriodComparison {
+            previous_period: previous_period.to_string(),
+            current_period: current_period.to_string(),
+            employees,
+        }
+    }
+
+    /// Headcount, gross and employer cost for every month from `from` to `to`, including months
+    /// without records. Voided records are ignored; a later record for the same employee and month
+    /// replaces an earlier one.
+    pub fn cost_trend(
+        records: &[PayrollData],
+        from: PayPeriod,
+        to: PayPeriod,
+        rates: &BpjsRates,
+    ) -> Vec<MonthlyCost> {
+        let mut latest: BTreeMap<(PayPeriod, &str), &PayrollData> = BTreeMap::new();
+        for record in records.iter().filter(|record| !record.voided) {
+            match record.pay_period.parse::<PayPeriod>() {
+                Ok(period) if period >= from && period <= to => {
+                    latest.insert((period, record.employee.as_employee().employee_id()), record);
+                }
+                _ => {}
+            }
+        }
+
+        let mut months = Vec::new();
+        let mut period = from;
+        while period <= to {
+            let mut month = MonthlyCost {
+                period,
+                headcount: 0,
+                gross: 0.0,
+                employer_cost: 0.0,
+            };
+            for (_, record) in latest.range((period, "")..).take_while(|((key, _), _)| *key == period) {
+                month.headcount += 1;
+                month.gross += record.gross_salary;
+                month.employer_cost += BpjsContribution::employer_cost(record, rates);
+            }
+            months.push(month);
+            period = period.next();
+        }
+        months
+    }
+
+    pub fn compare_with_previous(records: &[PayrollData], current_period: &str) -> Option<PeriodComparison> {
+        let previous = current_period.parse::<PayPeriod>().ok()?.previous();
+        Some(Self::compare_periods(records, &previous.to_string(), current_period))
+    }
+
+    fn latest_records<'a>(records: &'a [PayrollData], period: &str) -> Vec<&'a PayrollData> {
+        let mut latest: Vec<&PayrollData> = Vec::new();
+        for record in records
+            .iter()
+            .filter(|r| !r.voided && PayPeriod::same_period(&r.pay_period, period))
+        {
+            let employee_id = record.employee.as_employee().employee_id();
+            match latest
+                .iter_mut()
+                .find(|r| r.employee.as_employee().employee_id() == employee_id)
+            {
+                Some(existing) => *existing = record,
+                None => latest.push(record),
+            }
+        }
+        latest
+    }
+
+    fn explain(previous: Option<&PayrollData>, current: Option<&PayrollData>) -> Vec<ChangeReason> {
+        let (previous, current) = match (previous, current) {
+            (None, Some(_)) => return vec![ChangeReason::NewInPeriod],
+            (Some(_), None) => return vec![ChangeReason::MissingFromPeriod],
+            (Some(previous), Some(current)) => (&previous.employee, &current.employee),
+            (None, None) => return Vec::new(),
+        };
+
+        let mut reasons = Vec::new();
+        let (prev_emp, curr_emp) = (previous.as_employee(), current.as_employee());
+
+        if (prev_emp.work_hour() - curr_emp.work_hour()).abs() > f64::EPSILON {
+            reasons.push(ChangeReason::HoursChanged {
+                from: prev_emp.work_hour(),
+                to: curr_emp.work_hour(),
+            });
+        }
+        if (prev_emp.tunjangan() - curr_emp.tunjangan()).abs() > f64::EPSILON {
+            reasons.push(ChangeReason::AllowanceChanged {
+                from: prev_emp.tunjangan(),
+                to: curr_emp.tunjangan(),
+            });
+        }
+        if std::mem::discriminant(prev_emp.periode_tunjangan())
+            != std::mem::discriminant(curr_emp.periode_tunjangan())
+        {
+            reasons.push(ChangeReason::AllowancePeriodChanged);
+        }
+
+        match (previous, current) {
+            (EmployeeData::Fulltime(prev), EmployeeData::Fulltime(curr)) => {
+                if (prev.base_salary - curr.base_salary).abs() > f64::EPSILON {
+                    reasons.push(ChangeReason::SalaryRevision {
+                        from: prev.base_salary,
+                        to: curr.base_salary,
+                    });
+                }
+                if prev.ptkp_status != curr.ptkp_status {
+                    reasons.push(ChangeReason::PtkpStatusChanged);
+                }
+            }
+            (EmployeeData::Contract(prev), EmployeeData::Contract(curr)) => {
+                if (prev.hourly_rate - curr.hourly_rate).abs() > f64::EPSILON {
+                    reasons.push(ChangeReason::SalaryRevision {
+                        from: prev.hourly_rate,
+                        to: curr.hourly_rate,
+                    });
+                }
+            }
+            _ => reasons.push(ChangeReason::EmployeeTypeChanged),
+        }
+
+        reasons
+    }
+}
+
+/// Totals of a pay period's active payroll records.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PeriodReport {
+    pub pay_period: String,
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+impl PeriodReport {
+    pub fn for_period(records: &[PayrollData], period: PayPeriod) -> Self {
+        let mut report = PeriodReport {
+            pay_period: format!("{}-{:02}", period.year, period.month),
+            ..PeriodReport::default()
+        };
+        for record in records.iter().filter(|record| !record.voided) {
+            if record.pay_period.parse::<PayPeriod>() != Ok(period) {
+                continue;
+            }
+            report.employees += 1;
+            report.gross += record.gross_salary;
+            report.deductions += record.deductions;
+            report.net += record.net_salary;
+        }
+        report
+    }
+}
@@ -0,0 +1,184 @@
+use crate::bpjs::{BpjsContribution, BpjsRates};
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::report::{MonthlyCost, PeriodReport};
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// Years detailed payroll records are kept when none are configured.
+pub const DEFAULT_PURGE_YEARS: u32 = 10;
+
+/// One department's share of a [`PeriodSummary`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepartmentTotals {
+    pub department: Option<String>,
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+/// What a pay period's active records added up to, kept in their place once they are purged so
+/// reports and cost trends still cover the period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    /// `YYYY-MM`.
+    pub pay_period: String,
+    /// Detailed records purged, voided ones included.
+    pub records: usize,
+    pub employees: usize,
+    pub gross: f64,
+    pub deductions: f64,
+    pub net: f64,
+    /// Gross pay plus employer contributions and premiums, as in the cost trend.
+    pub employer_cost: f64,
+    pub departments: Vec<DepartmentTotals>,
+    pub purged: DateTime<Utc>,
+}
+
+impl PeriodSummary {
+    /// Sums up the records of `period` among `records`; the others are ignored.
+    pub fn summarize(records: &[PayrollData], period: PayPeriod, rates: &BpjsRates, now: DateTime<Utc>) -> Self {
+        let report = PeriodReport::for_period(records, period);
+        let mut summary = Self {
+            pay_period: report.pay_period,
+            records: 0,
+            employees: report.employees,
+            gross: report.gross,
+            deductions: report.deductions,
+            net: report.net,
+            employer_cost: 0.0,
+            departments: Vec::new(),
+            purged: now,
+        };
+        for record in records.iter().filter(|record| record.pay_period.parse::<PayPeriod>() == Ok(period)) {
+            summary.records += 1;
+            if record.voided {
+                continue;
+            }
+            summary.employer_cost += BpjsContribution::employer_cost(record, rates);
+            summary.add_department(&DepartmentTotals {
+                department: record.employee.as_employee().profile().department.clone(),
+                employees: 1,
+                gross: record.gross_salary,
+                deductions: record.deductions,
+                net: record.net_salary,
+            });
+        }
+        summary
+    }
+
+    /// Adds the totals of `other`, a later purge of the same period (after it was reopened).
+    pub fn merge(&mut self, other: &PeriodSummary) {
+        self.records += other.records;
+        self.employees += other.employees;
+        self.gross += other.gross;
+        self.deductions += other.deductions;
+        self.net += other.net;
+        self.employer_cost += other.employer_cost;
+        for totals in &other.departments {
+            self.add_department(totals);
+        }
+        self.purged = other.purged;
+    }
+
+    fn add_department(&mut self, totals: &DepartmentTotals) {
+        match self.departments.binary_search_by(|existing| existing.department.cmp(&totals.department)) {
+            Ok(index) => {
+                let existing = &mut self.departments[index];
+                existing.employees += totals.employees;
+                existing.gross += totals.gross;
+                existing.deductions += totals.deductions;
+                existing.net += totals.net;
+            }
+            Err(index) => self.departments.insert(index, totals.clone()),
+        }
+    }
+
+    pub fn period(&self) -> Option<PayPeriod> {
+        self.pay_period.parse().ok()
+    }
+
+    pub fn report(&self) -> PeriodReport {
+        PeriodReport {
+            pay_period: self.pay_period.clone(),
+            employees: self.employees,
+            gross: self.gross,
+            deductions: self.deductions,
+            net: self.net,
+        }
+    }
+
+    pub fn monthly_cost(&self, period: PayPeriod) -> MonthlyCost {
+        MonthlyCost {
+            period,
+            headcount: self.employees,
+            gross: self.gross,
+            employer_cost: self.employer_cost,
+        }
+    }
+}
+
+/// The summaries of every purged pay period, in period order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PeriodSummaries {
+    summaries: Vec<PeriodSummary>,
+}
+
+impl PeriodSummaries {
+    pub fn get(&self, period: PayPeriod) -> Option<&PeriodSummary> {
+        self.summaries.iter().find(|summary| summary.period() == Some(period))
+    }
+
+    /// Keeps a purged period's summary, adding it to the one kept for the period by an earlier purge.
+    pub(crate) fn add(&mut self, summary: &PeriodSummary) {
+        match self.summaries.iter_mut().find(|existing| existing.pay_period == summary.pay_period) {
+            Some(existing) => existing.merge(summary),
+            None => {
+                self.summaries.push(summary.clone());
+                self.summaries.sort_by(|a, b| a.pay_period.cmp(&b.pay_period));
+            }
+        }
+    }
+}
+
+impl Deref for PeriodSummaries {
+    type Target = [PeriodSummary];
+
+    fn deref(&self) -> &[PeriodSummary] {
+        &self.summaries
+    }
+}
+
+/// What a purge removes, or removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurgeReport {
+    /// The first pay period kept; everything before it goes.
+    pub cutoff: PayPeriod,
+    /// One per period whose records are purged.
+    pub summaries: Vec<PeriodSummary>,
+    pub runs: usize,
+    /// Timesheets, attendance entries, overtime requests, travel and expense claims of the periods.
+    pub other_entries: usize,
+}
+
+impl PurgeReport {
+    pub fn records(&self) -> usize {
+        self.summaries.iter().map(|summary| summary.records).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summaries.is_empty() && self.runs == 0 && self.other_entries == 0
+    }
+}
+
+/// The first pay period kept on `today` when records are kept for `years`: periods that ended
+/// more than `years` ago are purged.
+pub fn purge_cutoff(today: NaiveDate, years: u32) -> PayPeriod {
+    let date = today
+        .checked_sub_months(Months::new(years.saturating_mul(12)))
+        .unwrap_or(NaiveDate::MIN);
+    PayPeriod::from_date(date)
+}
@@ -0,0 +1,258 @@
+use crate::error::PayrollError;
+use crate::export::write_csv_row;
+use crate::privacy::Renaming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::ops::Deref;
+
+pub const MIN_REVIEW_SCORE: f64 = 1.0;
+pub const MAX_REVIEW_SCORE: f64 = 5.0;
+
+/// Scores at or above `min_score` earn `multiplier` times the target bonus.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BonusBand {
+    pub min_score: f64,
+    pub multiplier: f64,
+}
+
+impl BonusBand {
+    pub const fn new(min_score: f64, multiplier: f64) -> Self {
+        Self { min_score, multiplier }
+    }
+}
+
+pub const DEFAULT_BONUS_BANDS: [BonusBand; 5] = [
+    BonusBand::new(1.0, 0.0),
+    BonusBand::new(2.0, 0.5),
+    BonusBand::new(3.0, 1.0),
+    BonusBand::new(4.0, 1.25),
+    BonusBand::new(4.5, 1.5),
+];
+
+/// How review scores turn into bonuses (`[bonus]` in the configuration).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BonusPolicy {
+    /// Bonus at multiplier 1, in months of base pay.
+    pub target_months: f64,
+    pub bands: Vec<BonusBand>,
+}
+
+impl Default for BonusPolicy {
+    fn default() -> Self {
+        Self {
+            target_months: 1.0,
+            bands: DEFAULT_BONUS_BANDS.to_vec(),
+        }
+    }
+}
+
+impl BonusPolicy {
+    /// The multiplier of the highest band `score` reaches, or 0 below every band.
+    pub fn multiplier(&self, score: f64) -> f64 {
+        self.bands
+            .iter()
+            .filter(|band| score >= band.min_score)
+            .max_by(|a, b| a.min_score.total_cmp(&b.min_score))
+            .map_or(0.0, |band| band.multiplier)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.target_months.is_finite() || self.target_months < 0.0 {
+            return Err("bonus.target_months must not be negative".to_string());
+        }
+        if self.bands.is_empty() {
+            return Err("bonus.bands needs at least one band".to_string());
+        }
+        for band in &self.bands {
+            if validate_score(band.min_score).is_err() {
+                return Err(format!(
+                    "bonus band min_score {} is outside {} to {}",
+                    band.min_score, MIN_REVIEW_SCORE, MAX_REVIEW_SCORE
+                ));
+            }
+            if !band.multiplier.is_finite() || band.multiplier < 0.0 {
+                return Err(format!("bonus band multiplier {} must not be negative", band.multiplier));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_score(score: f64) -> Result<(), String> {
+    if !(MIN_REVIEW_SCORE..=MAX_REVIEW_SCORE).contains(&score) {
+        return Err(format!("scores run from {} to {}", MIN_REVIEW_SCORE, MAX_REVIEW_SCORE));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceReview {
+    pub employee_id: String,
+    pub score: f64,
+    pub reviewer: Option<String>,
+    pub recorded: DateTime<Utc>,
+}
+
+/// One round of performance reviews, e.g. the 2026 annual review. Closing it freezes the scores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewCycle {
+    pub name: String,
+    pub created: DateTime<Utc>,
+    pub closed: bool,
+    pub reviews: Vec<PerformanceReview>,
+}
+
+impl ReviewCycle {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            created: Utc::now(),
+            closed: false,
+            reviews: Vec::new(),
+        }
+    }
+
+    pub fn review(&self, employee_id: &str) -> Option<&PerformanceReview> {
+        self.reviews.iter().find(|review| review.employee_id == employee_id)
+    }
+
+    /// Records or replaces an employee's score.
+    pub fn record(&mut self, employee_id: &str, score: f64, reviewer: Option<String>) {
+        let review = PerformanceReview {
+            employee_id: employee_id.to_string(),
+            score,
+            reviewer,
+            recorded: Utc::now(),
+        };
+        match self.reviews.iter_mut().find(|existing| existing.employee_id == employee_id) {
+            Some(existing) => *existing = review,
+            None => self.reviews.push(review),
+        }
+    }
+
+    pub fn average_score(&self) -> Option<f64> {
+        (!self.reviews.is_empty())
+            .then(|| self.reviews.iter().fold(0.0, |sum, review| sum + review.score) / self.reviews.len() as f64)
+    }
+}
+
+/// Every review cycle, open or closed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReviewCycles {
+    cycles: Vec<ReviewCycle>,
+}
+
+impl ReviewCycles {
+    pub fn get(&self, name: &str) -> Option<&ReviewCycle> {
+        self.cycles.iter().find(|cycle| cycle.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub(crate) fn start(&mut self, name: &str) -> Result<&ReviewCycle, PayrollError> {
+        if self.get(name).is_some() {
+            return Err(PayrollError::ReviewCycleExists(name.trim().to_string()));
+        }
+        self.cycles.push(ReviewCycle::new(name));
+        Ok(self.cycles.last().expect("cycle was just pushed"))
+    }
+
+    fn open_mut(&mut self, name: &str) -> Result<&mut ReviewCycle, PayrollError> {
+        let cycle = self
+            .cycles
+            .iter_mut()
+            .find(|cycle| cycle.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| PayrollError::ReviewCycleNotFound(name.trim().to_string()))?;
+        if cycle.closed {
+            return Err(PayrollError::ReviewCycleClosed(cycle.name.clone()));
+        }
+        Ok(cycle)
+    }
+
+    /// Records (or replaces) an employee's score in an open cycle.
+    pub(crate) fn record(
+        &mut self,
+        cycle: &str,
+        employee_id: &str,
+        score: f64,
+        reviewer: Option<String>,
+    ) -> Result<(), PayrollError> {
+        validate_score(score).map_err(PayrollError::InvalidScore)?;
+        self.open_mut(cycle)?.record(employee_id, score, reviewer);
+        Ok(())
+    }
+
+    /// Freezes a cycle's scores.
+    pub(crate) fn close(&mut self, name: &str) -> Result<(), PayrollError> {
+        self.open_mut(name)?.closed = true;
+        Ok(())
+    }
+
+    /// Moves the employee's reviews, and the ones they gave, to their pseudonym. Returns how many
+    /// were theirs.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        let mut renamed = 0;
+        for review in self.cycles.iter_mut().flat_map(|cycle| cycle.reviews.iter_mut()) {
+            renamed += renaming.id(&mut review.employee_id);
+            renaming.option(&mut review.reviewer);
+        }
+        renamed
+    }
+}
+
+impl Deref for ReviewCycles {
+    type Target = [ReviewCycle];
+
+    fn deref(&self) -> &[ReviewCycle] {
+        &self.cycles
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BonusLine {
+    pub employee_id: String,
+    pub name: String,
+    pub score: f64,
+    pub multiplier: f64,
+    /// Monthly base pay the target is measured in.
+    pub monthly_base: f64,
+    pub bonus: f64,
+}
+
+/// Bonuses computed from a review cycle: monthly base × target months × the score's multiplier.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BonusRun {
+    pub cycle: String,
+    pub target_months: f64,
+    pub lines: Vec<BonusLine>,
+    /// Active employees without a score in the cycle; they get no bonus.
+    pub unreviewed: Vec<String>,
+}
+
+impl BonusRun {
+    pub fn total(&self) -> f64 {
+        self.lines.iter().fold(0.0, |sum, line| sum + line.bonus)
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_csv_row(
+            &mut writer,
+            &["Employee ID", "Name", "Score", "Multiplier", "Monthly Base", "Bonus"].map(String::from),
+        )?;
+        for line in &self.lines {
+            write_csv_row(
+                &mut writer,
+                &[
+                    line.employee_id.clone(),
+                    line.name.clone(),
+                    line.score.to_string(),
+                    line.multiplier.to_string(),
+                    format!("{:.2}", line.monthly_base),
+                    format!("{:.2}", line.bonus),
+                ],
+            )?;
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::calc::CalculationSettings;
+use crate::period::PayPeriod;
+use crate::tax::{self, TaxRegime};
+use serde::{Deserialize, Serialize};
+
+/// The tax table and BPJS rates that were in force up to and including `until`, before the ones
+/// configured now (`[[rule_history]]` in the configuration).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveRules {
+    #[serde(with = "period_code")]
+    pub until: PayPeriod,
+    pub tax_table: String,
+    pub bpjs_kesehatan_rate: f64,
+    pub bpjs_ketenagakerjaan_rate: f64,
+}
+
+impl EffectiveRules {
+    /// `current` with these rules in place of its tax table, found among `regimes`, and BPJS rates.
+    /// Overtime rules are not dated and stay as they are.
+    pub fn apply(&self, current: &CalculationSettings, regimes: &[TaxRegime]) -> CalculationSettings {
+        CalculationSettings {
+            tax_table: tax::find_regime(regimes, &self.tax_table).unwrap_or(current.tax_table),
+            bpjs_kesehatan_rate: self.bpjs_kesehatan_rate,
+            bpjs_ketenagakerjaan_rate: self.bpjs_ketenagakerjaan_rate,
+            ..*current
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, rate) in [
+            ("bpjs_kesehatan_rate", self.bpjs_kesehatan_rate),
+            ("bpjs_ketenagakerjaan_rate", self.bpjs_ketenagakerjaan_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("rule_history.{} until {} must be between 0 and 1", name, self.until));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The settings in force in `period`: the earliest of the `history` rules that were still in force
+/// then, or `current` when the period comes after all of them.
+pub fn settings_in_force(
+    history: &[EffectiveRules],
+    period: PayPeriod,
+    current: &CalculationSettings,
+    regimes: &[TaxRegime],
+) -> CalculationSettings {
+    history
+        .iter()
+        .filter(|rules| period <= rules.until)
+        .min_by_key(|rules| rules.until)
+        .map_or(*current, |rules| rules.apply(current, regimes))
+}
+
+mod period_code {
+    use crate::period::PayPeriod;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(period: &PayPeriod, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}-{:02}", period.year, period.month))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PayPeriod, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::employee::Employee;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub trait EarningRule: Send + Sync {
+    fn name(&self) -> &str;
+    fn amount(&self, employee: &dyn Employee, pay_period: &str) -> f64;
+}
+
+pub trait DeductionRule: Send + Sync {
+    fn name(&self) -> &str;
+    fn amount(&self, employee: &dyn Employee, pay_period: &str) -> f64;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayrollComponent {
+    pub name: String,
+    pub amount: f64,
+}
+
+#[derive(Default)]
+pub struct RuleSet {
+    earnings: Vec<Box<dyn EarningRule>>,
+    deductions: Vec<Box<dyn DeductionRule>>,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_earning(&mut self, rule: Box<dyn EarningRule>) {
+        self.earnings.push(rule);
+    }
+
+    pub fn add_deduction(&mut self, rule: Box<dyn DeductionRule>) {
+        self.deductions.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.earnings.is_empty() && self.deductions.is_empty()
+    }
+
+    pub fn earnings_for(&self, employee: &dyn Employee, pay_period: &str) -> Vec<PayrollComponent> {
+        self.earnings
+            .iter()
+            .map(|rule| PayrollComponent {
+                name: rule.name().to_string(),
+                amount: rule.amount(employee, pay_period),
+            })
+            .filter(|component| component.amount != 0.0)
+            .collect()
+    }
+
+    pub fn deductions_for(&self, employee: &dyn Employee, pay_period: &str) -> Vec<PayrollComponent> {
+        self.deductions
+            .iter()
+            .map(|rule| PayrollComponent {
+                name: rule.name().to_string(),
+                amount: rule.amount(employee, pay_period),
+            })
+            .filter(|component| component.amount != 0.0)
+            .collect()
+    }
+}
+
+impl fmt::Debug for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuleSet")
+            .field("earnings", &self.earnings.iter().map(|rule| rule.name()).collect::<Vec<_>>())
+            .field("deductions", &self.deductions.iter().map(|rule| rule.name()).collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// A cron-like `minute hour day-of-month month day-of-week` schedule, e.g. `0 8 25 * *` for 08:00
+/// on the 25th. Fields accept `*`, numbers, ranges, lists and `/step`; day-of-month also accepts
+/// `L` for the last day of the month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    last_day: bool,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| format!("bad step in '{}'", part))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(format!("bad step in '{}'", part));
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = start.parse::<u32>().map_err(|_| format!("bad range '{}'", range))?;
+            let end = end.parse::<u32>().map_err(|_| format!("bad range '{}'", range))?;
+            (start, end)
+        } else {
+            let value = range.parse::<u32>().map_err(|_| format!("bad value '{}'", range))?;
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("'{}' is outside {}-{}", part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
+
+impl Schedule {
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0 || (self.last_day && date.day() == last_day_of_month(date));
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        // As in cron, a restricted day-of-month and day-of-week match when either one does.
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    pub fn matches(&self, time: NaiveDateTime) -> bool {
+        self.matches_date(time.date())
+            && self.hours & (1 << time.hour()) != 0
+            && self.minutes & (1 << time.minute()) != 0
+    }
+
+    /// The first minute strictly after `after` that the schedule fires, if any within eight years.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_date(date) {
+                let from = if date == start.date() { start.time() } else { NaiveTime::MIN };
+                for hour in from.hour()..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let first_minute = if hour == from.hour() { from.minute() } else { 0 };
+                    if let Some(minute) = (first_minute..60).find(|minute| self.minutes & (1 << minute) != 0) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| format!("Unrecognized schedule '{}': {}", s.trim(), reason);
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(invalid("expected 'minute hour day month weekday'".to_string()));
+        };
+
+        let (day, last_day) = match day.split(',').partition::<Vec<_>, _>(|part| part.eq_ignore_ascii_case("L")) {
+            (last, rest) if rest.is_empty() && !last.is_empty() => (None, true),
+            (last, rest) => (Some(rest.join(",")), !last.is_empty()),
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).map_err(invalid)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minute, 0, 59).map_err(invalid)?,
+            hours: parse_field(hour, 0, 23).map_err(invalid)?,
+            days: match &day {
+                Some(day) => parse_field(day, 1, 31).map_err(invalid)?,
+                None => 0,
+            },
+            last_day,
+            months: parse_field(month, 1, 12).map_err(invalid)?,
+            weekdays,
+            any_day: day.as_deref() == Some("*"),
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.expression)
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::access_log::AccessLogEntry;
+use crate::accounting::JournalLine;
+use crate::export::audit_bundle::AuditBundleManifest;
+use crate::ledger::LedgerEntry;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::self_service::YearToDate;
+use crate::webhook::WebhookEvent;
+use schemars::{schema_for, Schema};
+
+/// Names the schemas are published under, in the order [`schemas`] returns them.
+pub const SCHEMA_NAMES: [&str; 9] = [
+    "employee",
+    "payroll-record",
+    "ledger-entry",
+    "audit-bundle-manifest",
+    "journal-line",
+    "webhook-event",
+    "year-to-date",
+    "period-report",
+    "access-log-entry",
+];
+
+/// The JSON Schema (draft 2020-12) of `name`, one of [`SCHEMA_NAMES`].
+pub fn schema(name: &str) -> Option<Schema> {
+    let mut schema = match name {
+        "employee" => schema_for!(EmployeeData),
+        "payroll-record" => schema_for!(PayrollData),
+        "ledger-entry" => schema_for!(LedgerEntry),
+        "audit-bundle-manifest" => schema_for!(AuditBundleManifest),
+        "journal-line" => schema_for!(JournalLine),
+        "webhook-event" => schema_for!(WebhookEvent),
+        "year-to-date" => schema_for!(YearToDate),
+        "period-report" => schema_for!(crate::report::PeriodReport),
+        "access-log-entry" => schema_for!(AccessLogEntry),
+        _ => return None,
+    };
+    schema.insert("$id".to_string(), format!("urn:employee-management:schema:{}", name).into());
+    Some(schema)
+}
+
+/// Every published schema with its name: employees and payroll records as in data files and the
+/// REST API, the ledger and audit bundle files, journal exports, webhook payloads, API reports and
+/// the server's access log.
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    SCHEMA_NAMES
+        .iter()
+        .map(|name| (*name, schema(name).expect("every listed schema exists")))
+        .collect()
+}
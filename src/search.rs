@@ -0,0 +1,231 @@
+use crate::payroll::{EmployeeData, Payroll, PayrollData};
+use crate::period::PayPeriod;
+
+#[derive(Debug, Clone)]
+pub struct EmployeeMatch<'a> {
+    pub employee: &'a EmployeeData,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordMatch<'a> {
+    pub index: usize,
+    pub record: &'a PayrollData,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults<'a> {
+    pub employees: Vec<EmployeeMatch<'a>>,
+    pub records: Vec<RecordMatch<'a>>,
+}
+
+impl SearchResults<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.employees.is_empty() && self.records.is_empty()
+    }
+}
+
+pub fn search<'a>(payroll: &'a Payroll, query: &str) -> SearchResults<'a> {
+    let tokens = tokenize(query);
+    if tokens.is_empty() {
+        return SearchResults::default();
+    }
+
+    let mut employees: Vec<EmployeeMatch> = payroll
+        .employees
+        .iter()
+        .filter_map(|employee| {
+            let fields = employee_fields(employee);
+            score_tokens(&tokens, &fields, None).map(|score| EmployeeMatch { employee, score })
+        })
+        .collect();
+    employees.sort_by_key(|found| std::cmp::Reverse(found.score));
+
+    let mut records: Vec<RecordMatch> = payroll
+        .get_payroll_records()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, record)| {
+            let mut fields = employee_fields(&record.employee);
+            fields.push(normalize(&record.pay_period));
+            let period = record.pay_period.parse::<PayPeriod>().ok();
+            score_tokens(&tokens, &fields, period).map(|score| RecordMatch { index, record, score })
+        })
+        .collect();
+    records.sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+
+    SearchResults { employees, records }
+}
+
+#[derive(Debug, Clone)]
+pub enum EmployeeIdMatch<'a> {
+    Exact(&'a EmployeeData),
+    Completed(&'a EmployeeData),
+    Ambiguous(Vec<&'a EmployeeData>),
+    Unknown(Vec<&'a EmployeeData>),
+}
+
+/// Resolves a typed employee ID: exact (case-insensitive) matches and unique prefixes resolve to the
+/// employee, several prefix matches are ambiguous, and anything else returns close IDs and names.
+pub fn match_employee_id<'a>(employees: &[&'a EmployeeData], input: &str) -> EmployeeIdMatch<'a> {
+    let typed = input.trim();
+    let input = typed.to_lowercase();
+    let id = |employee: &EmployeeData| employee.as_employee().employee_id().to_lowercase();
+
+    if let Some(employee) = employees.iter().find(|employee| employee.as_employee().employee_id() == typed) {
+        return EmployeeIdMatch::Exact(employee);
+    }
+    if let Some(employee) = employees.iter().find(|employee| id(employee) == input) {
+        return EmployeeIdMatch::Completed(employee);
+    }
+    if !input.is_empty() {
+        let prefixed: Vec<&EmployeeData> =
+            employees.iter().copied().filter(|employee| id(employee).starts_with(&input)).collect();
+        match prefixed.len() {
+            0 => {}
+            1 => return EmployeeIdMatch::Completed(prefixed[0]),
+            _ => return EmployeeIdMatch::Ambiguous(prefixed),
+        }
+    }
+
+    let tokens = tokenize(&input);
+    let mut suggestions: Vec<(u32, &EmployeeData)> = employees
+        .iter()
+        .filter_map(|employee| {
+            let candidate = id(employee);
+            let close = within_one_edit(&input, &candidate) || is_transposition(&input, &candidate);
+            let score = if !input.is_empty() && close {
+                100
+            } else {
+                score_tokens(&tokens, &employee_fields(employee), None).unwrap_or(0)
+            };
+            (score > 0).then_some((score, *employee))
+        })
+        .collect();
+    suggestions.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    EmployeeIdMatch::Unknown(suggestions.into_iter().take(5).map(|(_, employee)| employee).collect())
+}
+
+fn employee_fields(employee: &EmployeeData) -> Vec<String> {
+    let employee = employee.as_employee();
+    vec![normalize(employee.employee_id()), normalize(&employee.profile().name)]
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect()
+}
+
+fn tokenize(query: &str) -> Vec<String> {
+    normalize(&query.replace(['/', '-', '_'], " "))
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+fn score_tokens(tokens: &[String], fields: &[String], period: Option<PayPeriod>) -> Option<u32> {
+    tokens.iter().try_fold(0, |total, token| {
+        let text_score = fields.iter().map(|field| score_field(token, field)).max().unwrap_or(0);
+        let period_score = period.map(|period| score_period(token, period)).unwrap_or(0);
+        match text_score.max(period_score) {
+            0 => None,
+            score => Some(total + score),
+        }
+    })
+}
+
+fn score_field(token: &str, field: &str) -> u32 {
+    if field.is_empty() {
+        return 0;
+    }
+    if field == token {
+        return 100;
+    }
+    if field.starts_with(token) {
+        return 60;
+    }
+    if field.split_whitespace().any(|word| word.starts_with(token)) {
+        return 50;
+    }
+    if field.contains(token) {
+        return 40;
+    }
+    if token.chars().any(|c| c.is_ascii_digit()) {
+        return 0;
+    }
+    if token.chars().count() >= 4
+        && field.split_whitespace().any(|word| within_one_edit(token, word))
+    {
+        return 20;
+    }
+    if token.chars().count() >= 3 && is_subsequence(token, field) {
+        return 10;
+    }
+    0
+}
+
+fn score_period(token: &str, period: PayPeriod) -> u32 {
+    if token.chars().all(|c| c.is_ascii_digit()) {
+        let year = period.year.to_string();
+        return match token.len() {
+            4 if token == year => 80,
+            2 if year.ends_with(token) => 70,
+            1 | 2 if token.parse::<u32>() == Ok(period.month) => 50,
+            _ => 0,
+        };
+    }
+    let month = period.month_name().to_lowercase();
+    if token.len() >= 3 && month.starts_with(token) {
+        80
+    } else {
+        0
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > 1 {
+        return false;
+    }
+    let (mut i, mut j, mut edits) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        edits += 1;
+        if edits > 1 {
+            return false;
+        }
+        match a.len().cmp(&b.len()) {
+            std::cmp::Ordering::Greater => i += 1,
+            std::cmp::Ordering::Less => j += 1,
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    edits + (a.len() - i) + (b.len() - j) <= 1
+}
+
+fn is_transposition(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len() != b.len() {
+        return false;
+    }
+    let differences: Vec<usize> = (0..a.len()).filter(|&i| a[i] != b[i]).collect();
+    matches!(differences[..], [i, j] if j == i + 1 && a[i] == b[j] && a[j] == b[i])
+}
@@ -0,0 +1,72 @@
+use crate::payroll::{EmployeeData, Payroll, PayrollData};
+use crate::period::PayPeriod;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct YearToDate {
+    pub year: i32,
+    pub periods: usize,
+    pub gross: f64,
+    pub tax: f64,
+    pub bpjs: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+/// A signed-in employee's view of the payroll. It can only be obtained with the employee's PIN
+/// (`Payroll::sign_in`) and never exposes another employee's data.
+#[derive(Debug, Clone)]
+pub struct SelfService<'a> {
+    payroll: &'a Payroll,
+    employee_id: String,
+}
+
+impl<'a> SelfService<'a> {
+    pub(crate) fn new(payroll: &'a Payroll, employee_id: &str) -> Self {
+        Self {
+            payroll,
+            employee_id: employee_id.to_string(),
+        }
+    }
+
+    pub fn employee_id(&self) -> &str {
+        &self.employee_id
+    }
+
+    /// Whether the employee signed in with a PIN issued by an administrator.
+    pub fn must_change_pin(&self) -> bool {
+        self.payroll
+            .credential(&self.employee_id)
+            .is_some_and(|credential| credential.must_change)
+    }
+
+    pub fn employee(&self) -> Option<&'a EmployeeData> {
+        self.payroll.get_employee(&self.employee_id)
+    }
+
+    /// The employee's non-voided records, oldest period first.
+    pub fn payslips(&self) -> Vec<&'a PayrollData> {
+        let mut records: Vec<&'a PayrollData> = self
+            .payroll
+            .get_payroll_records()
+            .iter()
+            .filter(|record| !record.voided && record.employee.as_employee().employee_id() == self.employee_id)
+            .collect();
+        records.sort_by_key(|record| record.pay_period.parse::<PayPeriod>().ok());
+        records
+    }
+
+    /// The latest non-voided record for `pay_period`.
+    pub fn payslip(&self, pay_period: &str) -> Option<&'a PayrollData> {
+        self.payslips()
+            .into_iter()
+            .rev()
+            .find(|record| PayPeriod::same_period(&record.pay_period, pay_period))
+    }
+
+    pub fn year_to_date(&self, year: i32) -> YearToDate {
+        self.payroll.year_to_date(&self.employee_id, year)
+    }
+}
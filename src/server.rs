@@ -0,0 +1,1764 @@
+use crate::access_log::{AccessLog, AccessLogEntry};
+use crate::auth::{ApiAuthConfig, Principal, Scope};
+use crate::cache::CacheKey;
+use crate::calendar::{self, payroll_calendar, write_ics};
+use crate::error::{AuthError, PayrollError, StorageError};
+use crate::metrics::Metrics;
+use crate::notify::{notify_all, NotifierConfig, RunSummary};
+use crate::payroll::{EmployeeData, PayrollData, PayrollRun};
+use crate::payslip::{render_html, PayslipSeal};
+use crate::rate_limit::RateLimiter;
+use crate::read_model::DepartmentMonth;
+use crate::period::PayPeriod;
+use crate::report::PeriodReport;
+use crate::schedule::Schedule;
+use crate::self_service::{SelfService, YearToDate};
+use crate::shared::SharedPayroll;
+use crate::storage::Storage;
+use crate::webhook::{
+    deliver_webhook, HttpWebhookTransport, RetryPolicy, Webhook, WebhookEvent, WebhookEventKind,
+    WebhookOutcome, WebhookRegistry, WebhookTransport,
+};
+use axum::extract::{ConnectInfo, MatchedPath, Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use chrono::Datelike;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+pub struct ServerState {
+    payroll: SharedPayroll,
+    storage: Option<Arc<dyn Storage + Send + Sync>>,
+    webhooks: Arc<RwLock<WebhookRegistry>>,
+    transport: Arc<dyn WebhookTransport>,
+    retry: RetryPolicy,
+    notifiers: Arc<Vec<NotifierConfig>>,
+    metrics: Arc<Metrics>,
+    pay_day: u32,
+    auth: Arc<ApiAuthConfig>,
+    limiter: Option<Arc<RateLimiter>>,
+    access_log: Option<Arc<AccessLog>>,
+    #[cfg(feature = "redis")]
+    cache: Option<crate::cache::RedisCache>,
+}
+
+impl ServerState {
+    pub fn new(payroll: SharedPayroll) -> Self {
+        Self {
+            payroll,
+            storage: None,
+            webhooks: Arc::new(RwLock::new(WebhookRegistry::new())),
+            transport: Arc::new(HttpWebhookTransport),
+            retry: RetryPolicy::default(),
+            notifiers: Arc::new(Vec::new()),
+            metrics: Arc::new(Metrics::new()),
+            pay_day: calendar::DEFAULT_PAY_DAY,
+            auth: Arc::new(ApiAuthConfig::default()),
+            limiter: None,
+            access_log: None,
+            #[cfg(feature = "redis")]
+            cache: None,
+        }
+    }
+
+    pub fn with_notifiers(mut self, notifiers: Vec<NotifierConfig>) -> Self {
+        self.notifiers = Arc::new(notifiers);
+        self
+    }
+
+    /// The day of the month `/calendar.ics` shows salaries paid on.
+    pub fn with_pay_day(mut self, pay_day: u32) -> Self {
+        self.pay_day = pay_day;
+        self
+    }
+
+    /// Requires API keys or tokens with the scope each endpoint needs, once `auth` configures any.
+    pub fn with_auth(mut self, auth: ApiAuthConfig) -> Self {
+        self.auth = Arc::new(auth);
+        self
+    }
+
+    /// Refuses requests with 429 once their client, an API key or else an address, exceeds `limiter`.
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(Arc::new(limiter));
+        self
+    }
+
+    /// Records every API request in `log`.
+    pub fn with_access_log(mut self, log: AccessLog) -> Self {
+        self.access_log = Some(Arc::new(log));
+        self
+    }
+
+    /// Saves the payroll to `storage` after every change, e.g. a
+    /// [`JsonFileStorage`](crate::storage::JsonFileStorage) or, shared between several servers, a
+    /// `PostgresStorage`.
+    pub fn with_storage(mut self, storage: impl Storage + Send + Sync + 'static) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+
+    /// Caches year-to-date totals and period reports in Redis.
+    #[cfg(feature = "redis")]
+    pub fn with_cache(mut self, cache: crate::cache::RedisCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn WebhookTransport>, retry: RetryPolicy) -> Self {
+        self.transport = transport;
+        self.retry = retry;
+        self
+    }
+
+    pub fn payroll(&self) -> &SharedPayroll {
+        &self.payroll
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Saves the payroll after a change, first dropping the cached results of the records it
+    /// changed.
+    pub(crate) async fn persist(&self) -> Result<(), ApiError> {
+        self.drop_changed_results().await;
+        let storage = match &self.storage {
+            Some(storage) => Arc::clone(storage),
+            None => return Ok(()),
+        };
+        let payroll = self.payroll.clone();
+        let result = tokio::task::spawn_blocking(move || payroll.read(|payroll| storage.save(payroll)))
+            .await
+            .map_err(|err| ApiError::internal(err.to_string()))?;
+        if result.is_err() {
+            self.metrics.storage_failed();
+        }
+        result.map_err(ApiError::from)
+    }
+
+    fn revision(&self) -> u64 {
+        self.payroll.read(|payroll| payroll.revision.load(Ordering::SeqCst))
+    }
+
+    /// The result cached for `key` at `revision`, when there is a cache and it has one.
+    async fn cached<T: DeserializeOwned>(&self, key: &CacheKey, revision: u64) -> Option<T> {
+        #[cfg(feature = "redis")]
+        if let Some(cache) = &self.cache {
+            match cache.get(key, revision).await {
+                Ok(Some(value)) => {
+                    self.metrics.cache_hit();
+                    return Some(value);
+                }
+                Ok(None) => self.metrics.cache_missed(),
+                Err(err) => self.cache_failed(err),
+            }
+        }
+        #[cfg(not(feature = "redis"))]
+        let _ = (key, revision);
+        None
+    }
+
+    async fn cache<T: Serialize>(&self, key: &CacheKey, revision: u64, value: &T) {
+        #[cfg(feature = "redis")]
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.put(key, revision, value).await {
+                self.cache_failed(err);
+            }
+        }
+        #[cfg(not(feature = "redis"))]
+        let _ = (key, revision, value);
+    }
+
+    /// Drops the cached results of the employees and periods whose records changed since the last
+    /// call. The payroll notes every change to its records, the same way its own read models learn
+    /// of them, so no handler has to say what it changed.
+    async fn drop_changed_results(&self) {
+        let changes = self.payroll.write(|payroll| payroll.take_record_changes());
+        #[cfg(feature = "redis")]
+        if let Some(cache) = &self.cache {
+            if let Err(err) = cache.records_changed(&changes).await {
+                self.cache_failed(err);
+            }
+        }
+        #[cfg(not(feature = "redis"))]
+        let _ = changes;
+    }
+
+    #[cfg(feature = "redis")]
+    fn cache_failed(&self, err: redis::RedisError) {
+        self.metrics.cache_failed();
+        eprintln!("cache unavailable: {}", err);
+    }
+
+    pub(crate) fn notify(&self, kind: WebhookEventKind, data: serde_json::Value) {
+        let hooks = self
+            .webhooks
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .subscribers(kind);
+        if hooks.is_empty() {
+            return;
+        }
+        let event = WebhookEvent::new(kind, data);
+        for hook in hooks {
+            let event = event.clone();
+            let transport = Arc::clone(&self.transport);
+            let retry = self.retry;
+            let metrics = Arc::clone(&self.metrics);
+            tokio::task::spawn_blocking(move || {
+                if let WebhookOutcome::Failed { attempts, error } =
+                    deliver_webhook(&hook, &event, transport.as_ref(), &retry)
+                {
+                    metrics.webhook_failed();
+                    eprintln!(
+                        "webhook {} ({}) failed after {} attempts: {}",
+                        hook.id, hook.url, attempts, error
+                    );
+                }
+            });
+        }
+    }
+}
+
+impl ServerState {
+    fn announce_run(&self, run_id: u32) {
+        if self.notifiers.is_empty() {
+            return;
+        }
+        let summary = match self.payroll.read(|payroll| RunSummary::for_run(payroll, run_id)) {
+            Ok(summary) => summary,
+            Err(_) => return,
+        };
+        let notifiers = Arc::clone(&self.notifiers);
+        tokio::task::spawn_blocking(move || {
+            for (channel, result) in notify_all(&notifiers, &summary) {
+                if let Err(err) = result {
+                    eprintln!("run summary not posted to {}: {}", channel, err);
+                }
+            }
+        });
+    }
+
+    async fn create_scheduled_run(&self, period: PayPeriod) {
+        let pay_period = period.to_string();
+        let exists = self.payroll.read(|payroll| {
+            payroll
+                .runs
+                .iter()
+                .any(|run| PayPeriod::same_period(&run.pay_period, &pay_period))
+        });
+        if exists {
+            return;
+        }
+        let run = match self.payroll.create_run(&pay_period) {
+            Ok(run) => run,
+            Err(err) => {
+                eprintln!("scheduled draft run for {} not created: {}", pay_period, err);
+                return;
+            }
+        };
+        if let Err(err) = self.persist().await {
+            eprintln!("scheduled draft run #{} not saved: {}", run.run_id, err.message);
+        }
+        println!("scheduled draft run #{} created for {}", run.run_id, pay_period);
+        self.announce_run(run.run_id);
+    }
+}
+
+/// Creates a draft run for the current month each time `schedule` fires (server local time) and
+/// posts its summary to the run notifiers. Periods that already have a run are left alone.
+pub async fn run_schedule(state: ServerState, schedule: Schedule) {
+    let mut next = schedule.next_after(chrono::Local::now().naive_local());
+    while let Some(due) = next {
+        let now = chrono::Local::now().naive_local();
+        if now >= due {
+            state.create_scheduled_run(PayPeriod::from_date(due.date())).await;
+            next = schedule.next_after(now);
+            continue;
+        }
+        // Wake at least once a minute so clock changes do not push a run back.
+        let wait = (due - now).to_std().unwrap_or_default().min(Duration::from_secs(60));
+        tokio::time::sleep(wait).await;
+    }
+    eprintln!("schedule '{}' never fires; no draft runs will be created", schedule);
+}
+
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<PayrollError> for ApiError {
+    fn from(err: PayrollError) -> Self {
+        let status = match err {
+            PayrollError::RecordNotFound(_) | PayrollError::RunNotFound(_) | PayrollError::EmployeeNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            PayrollError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            PayrollError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            PayrollError::AccountLocked(_) => StatusCode::TOO_MANY_REQUESTS,
+            PayrollError::PinChangeRequired => StatusCode::FORBIDDEN,
+            PayrollError::InvalidPin(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::CONFLICT,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(err: AuthError) -> Self {
+        let status = match err {
+            AuthError::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::Conflict(..) => Self::new(StatusCode::CONFLICT, err.to_string()),
+            _ => Self::internal(err.to_string()),
+        }
+    }
+}
+
+/// The body of every error response.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ErrorBody { error: self.message })).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct RecordFilter {
+    pub employee_id: Option<String>,
+    pub pay_period: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct YearQuery {
+    pub year: Option<i32>,
+}
+
+/// Pay periods, e.g. "2026-01"; see [`calendar::default_range`] for the defaults.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct CalendarQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Pay periods, e.g. "2026-01". `to` defaults to the current month and `from` to January of its year.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct PeriodRangeQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChangePinRequest {
+    pub new_pin: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct RemoveOptions {
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProcessRequest {
+    pub employee_id: Option<String>,
+    pub employee: Option<EmployeeData>,
+    pub pay_period: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RunRequest {
+    pub pay_period: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookRequest {
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+}
+
+/// An employee's place in the reporting lines, by employee ID.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportingLines {
+    pub employee_id: String,
+    /// Manager first, then their manager, up to the top.
+    pub reporting_chain: Vec<String>,
+    pub direct_reports: Vec<String>,
+    /// Direct and indirect reports.
+    pub all_reports: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IndexedRecord {
+    pub index: usize,
+    #[serde(flatten)]
+    pub record: PayrollData,
+}
+
+/// What every routed request passes through: authentication for the scope its routes need, the
+/// rate limit and the access log.
+#[derive(Clone)]
+struct Gate {
+    state: ServerState,
+    /// `None` for routes clients do not authenticate to with an API key, such as self-service.
+    scope: Option<Scope>,
+}
+
+impl Gate {
+    /// The client a request was made by, and the rate limit bucket it counts against.
+    fn identify(&self, request: &Request, remote: Option<IpAddr>) -> Result<(Principal, String), (AuthError, String)> {
+        let remote = remote.map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+        let scope = match self.scope {
+            Some(scope) => scope,
+            None => {
+                let employee_id = sign_in_headers(request.headers()).0.trim();
+                let name = match employee_id {
+                    "" => "anonymous".to_string(),
+                    employee_id => format!("employee:{}", employee_id),
+                };
+                return Ok((Principal::new(name, Scope::Read), remote));
+            }
+        };
+        let auth = &self.state.auth;
+        if !auth.is_enabled() {
+            return Ok((Principal::anonymous(), remote));
+        }
+        let headers = request.headers();
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        let credential = header(header::AUTHORIZATION.as_str())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| header("x-api-key"))
+            .unwrap_or_default();
+        match auth.authenticate(credential, chrono::Utc::now()) {
+            Ok(principal) => match principal.require(scope) {
+                Ok(()) => {
+                    let bucket = principal.name().to_string();
+                    Ok((principal, bucket))
+                }
+                Err(err) => Err((err, principal.name().to_string())),
+            },
+            // Failed attempts count against the address, so guessing keys is limited too.
+            Err(err) => Err((err, remote)),
+        }
+    }
+}
+
+/// Lets the request through when its `Authorization: Bearer` or `X-Api-Key` credential grants the
+/// gate's scope and its client is within the rate limit, records the [`Principal`] in the request
+/// extensions and writes the outcome to the access log. Every client is let in while no keys or
+/// tokens are configured.
+async fn gate(State(gate): State<Gate>, mut request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let remote = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    let method = request.method().to_string();
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path().to_string(), ToString::to_string);
+    let employee_id = requested_employee(&gate.state, &request);
+
+    let identified = gate.identify(&request, remote);
+    let (client, bucket) = match &identified {
+        Ok((principal, bucket)) => (principal.name().to_string(), bucket.clone()),
+        Err((AuthError::InsufficientScope(_), name)) => (name.clone(), name.clone()),
+        Err((_, remote)) => ("unauthenticated".to_string(), remote.clone()),
+    };
+    let limited = gate
+        .state
+        .limiter
+        .as_ref()
+        .and_then(|limiter| limiter.check(&bucket, started).err());
+    let response = match (limited, identified) {
+        (Some(retry_after), _) => {
+            gate.state.metrics.request_rate_limited();
+            let message = format!("rate limit exceeded; retry in {} s", retry_after.as_secs() + 1);
+            let mut response = ApiError::new(StatusCode::TOO_MANY_REQUESTS, message).into_response();
+            response.headers_mut().insert(header::RETRY_AFTER, (retry_after.as_secs() + 1).into());
+            response
+        }
+        (None, Ok((principal, _))) => {
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        (None, Err((err, _))) => {
+            let unauthenticated = !matches!(err, AuthError::InsufficientScope(_));
+            let mut response = ApiError::from(err).into_response();
+            if unauthenticated {
+                response
+                    .headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+            }
+            response
+        }
+    };
+
+    if let Some(log) = &gate.state.access_log {
+        let entry = AccessLogEntry {
+            timestamp: chrono::Utc::now(),
+            client,
+            remote_addr: remote.map(|ip| ip.to_string()),
+            method,
+            path,
+            employee_id,
+            status: response.status().as_u16(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        if let Err(err) = log.append(&entry) {
+            gate.state.metrics.access_log_failed();
+            eprintln!("access log {} not written: {}", log.path().display(), err);
+        }
+    }
+    response
+}
+
+/// The employee whose data `request` reads or changes, as far as its route, query or self-service
+/// sign-in tell.
+fn requested_employee(state: &ServerState, request: &Request) -> Option<String> {
+    let path = request.uri().path();
+    if path.starts_with("/me/") {
+        let employee_id = sign_in_headers(request.headers()).0.trim();
+        return (!employee_id.is_empty()).then(|| employee_id.to_string());
+    }
+    if let Some(route) = request.extensions().get::<MatchedPath>() {
+        let param = |name: &str| {
+            route
+                .as_str()
+                .split('/')
+                .zip(path.split('/'))
+                .find(|(segment, _)| segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) == Some(name))
+                .map(|(_, value)| value.to_string())
+        };
+        if let Some(employee_id) = param("employee_id") {
+            return Some(employee_id);
+        }
+        if let Some(index) = param("index").and_then(|index| index.parse::<usize>().ok()) {
+            return state.payroll.read(|payroll| {
+                let record = payroll.get_payroll_records().get(index)?;
+                Some(record.employee.as_employee().employee_id().to_string())
+            });
+        }
+    }
+    Query::<RecordFilter>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(filter)| filter.employee_id)
+}
+
+pub fn router(state: ServerState) -> Router {
+    let guard = |scope| {
+        let state = state.clone();
+        middleware::from_fn_with_state(Gate { state, scope }, gate)
+    };
+
+    #[cfg(feature = "graphql")]
+    let graphql = Router::new()
+        .route("/graphql", post(crate::graphql::graphql_handler))
+        .with_state(crate::graphql::schema(state.payroll.clone()))
+        .route_layer(guard(Some(Scope::Read)));
+
+    // gRPC methods that change data check for their scope themselves.
+    #[cfg(feature = "grpc")]
+    let grpc = crate::grpc::routes(state.clone()).route_layer(guard(Some(Scope::Read)));
+
+    let read = Router::new()
+        .route("/employees", get(list_employees))
+        .route("/employees/{employee_id}", get(get_employee))
+        .route("/employees/{employee_id}/reports", get(reporting_lines))
+        .route("/payrolls", get(list_records))
+        .route("/payrolls/{index}/payslip", get(payslip))
+        .route("/reports/departments", get(department_totals))
+        .route("/reports/{pay_period}", get(period_report))
+        .route("/calendar.ics", get(payroll_calendar_ics))
+        .route("/runs/{run_id}", get(get_run))
+        .route("/metrics", get(metrics))
+        .route_layer(guard(Some(Scope::Read)));
+    let process = Router::new()
+        .route("/payrolls", post(process_payroll))
+        .route("/runs", post(create_run))
+        .route_layer(guard(Some(Scope::PayrollProcess)));
+    let admin = Router::new()
+        .route("/employees", post(add_employee))
+        .route("/employees/{employee_id}", delete(remove_employee))
+        .route("/employees/{employee_id}/archive", post(archive_employee))
+        .route("/runs/{run_id}/approve", post(approve_run))
+        .route("/webhooks", get(list_webhooks).post(register_webhook))
+        .route("/webhooks/{id}", delete(remove_webhook))
+        .route_layer(guard(Some(Scope::Admin)));
+    // Employees sign in to self-service with their own ID and PIN instead.
+    let self_service = Router::new()
+        .route("/me/payslips", get(own_payslips))
+        .route("/me/payslips/{pay_period}", get(own_payslip))
+        .route("/me/ytd", get(own_year_to_date))
+        .route("/me/pin", post(change_own_pin))
+        .route_layer(guard(None));
+
+    let router = read.merge(process).merge(admin).merge(self_service).with_state(state);
+
+    #[cfg(feature = "graphql")]
+    let router = router.merge(graphql);
+    #[cfg(feature = "grpc")]
+    let router = router.merge(grpc);
+    #[cfg(feature = "schema")]
+    let router = router.merge(
+        Router::new()
+            .route("/schemas", get(list_schemas))
+            .route("/schemas/{name}", get(get_schema)),
+    );
+    #[cfg(feature = "openapi")]
+    let router = router.route("/openapi.json", get(openapi_json));
+
+    router
+}
+
+pub async fn serve(addr: SocketAddr, state: ServerState) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(state).into_make_service_with_connect_info::<SocketAddr>()).await
+}
+
+/// The OpenAPI 3.1 document of the REST API, for generating clients. Operations list the scope
+/// they need as the role of their security requirement. The GraphQL and gRPC APIs are described
+/// by their own schemas.
+#[cfg(feature = "openapi")]
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+    use utoipa::OpenApi;
+
+    #[derive(OpenApi)]
+    #[openapi(
+        info(description = "Employees, payroll processing, payroll runs and employee self-service."),
+        paths(
+            list_employees,
+            add_employee,
+            get_employee,
+            remove_employee,
+            archive_employee,
+            reporting_lines,
+            list_records,
+            process_payroll,
+            payslip,
+            period_report,
+            department_totals,
+            payroll_calendar_ics,
+            create_run,
+            get_run,
+            approve_run,
+            own_payslips,
+            own_payslip,
+            own_year_to_date,
+            change_own_pin,
+            list_webhooks,
+            register_webhook,
+            remove_webhook,
+            metrics,
+        ),
+        tags(
+            (name = "employees", description = "Employee records and reporting lines"),
+            (name = "payroll", description = "Payroll processing and payslips"),
+            (name = "runs", description = "Payroll runs and their approval"),
+            (name = "reports", description = "Period reports and the payroll calendar"),
+            (name = "self-service", description = "Signed in with the X-Employee-Id and X-Employee-Pin headers"),
+            (name = "webhooks", description = "Event notifications"),
+            (name = "operations", description = "Metrics and published schemas"),
+        )
+    )]
+    struct ApiDoc;
+
+    let mut doc = ApiDoc::openapi();
+    doc.info.license = None;
+    #[cfg(feature = "schema")]
+    {
+        #[derive(OpenApi)]
+        #[openapi(paths(list_schemas, get_schema))]
+        struct SchemaDoc;
+        doc.merge(SchemaDoc::openapi());
+    }
+    let components = doc.components.get_or_insert_with(Default::default);
+    let bearer = HttpBuilder::new()
+        .scheme(HttpAuthScheme::Bearer)
+        .description(Some("An API key, or a JWT signed with the configured secret"))
+        .build();
+    components.add_security_scheme("bearer", SecurityScheme::Http(bearer));
+    for (name, header, description) in [
+        ("api_key", "X-Api-Key", "An API key, instead of the Authorization header"),
+        ("employee_id", "X-Employee-Id", "The signed-in employee's ID"),
+        ("employee_pin", "X-Employee-Pin", "The signed-in employee's PIN"),
+    ] {
+        let key = ApiKey::Header(ApiKeyValue::with_description(header, description));
+        components.add_security_scheme(name, SecurityScheme::ApiKey(key));
+    }
+    doc
+}
+
+#[cfg(feature = "openapi")]
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(openapi())
+}
+
+/// Lists the published JSON Schemas.
+#[cfg(feature = "schema")]
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/schemas",
+        tag = "operations",
+        responses(
+            (status = 200, description = "Schema names", body = Vec<String>),
+        )
+    )
+)]
+async fn list_schemas() -> Json<Vec<&'static str>> {
+    Json(crate::schema::SCHEMA_NAMES.to_vec())
+}
+
+/// Gets a JSON Schema.
+#[cfg(feature = "schema")]
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/schemas/{name}",
+        tag = "operations",
+        params(
+            ("name" = String, Path, description = "Schema name, e.g. payroll-record"),
+        ),
+        responses(
+            (status = 200, description = "JSON Schema", content_type = "application/schema+json", body = Object),
+            (status = 404, description = "No schema with that name", body = ErrorBody),
+        )
+    )
+)]
+async fn get_schema(Path(name): Path<String>) -> Result<Response, ApiError> {
+    let name = name.strip_suffix(".json").unwrap_or(&name);
+    let schema = crate::schema::schema(name)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("no schema named '{}'", name)))?;
+    Ok(([(header::CONTENT_TYPE, "application/schema+json")], Json(schema)).into_response())
+}
+
+/// Gets Prometheus metrics.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/metrics",
+        tag = "operations",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        responses(
+            (status = 200, description = "Prometheus text format", content_type = "text/plain", body = String),
+        )
+    )
+)]
+async fn metrics(State(state): State<ServerState>) -> impl IntoResponse {
+    let (employees, records) = state
+        .payroll
+        .read(|payroll| (payroll.employees.len(), payroll.get_payroll_records().len()));
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(employees, records),
+    )
+}
+
+/// Lists employees.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/employees",
+        tag = "employees",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        responses(
+            (status = 200, description = "Employees, archived ones included", body = Vec<EmployeeData>),
+        )
+    )
+)]
+async fn list_employees(State(state): State<ServerState>) -> Json<Vec<EmployeeData>> {
+    Json(state.payroll.read(|payroll| payroll.employees.clone()))
+}
+
+/// Gets an employee.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/employees/{employee_id}",
+        tag = "employees",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            ("employee_id" = String, Path, description = "Employee ID"),
+        ),
+        responses(
+            (status = 200, description = "The employee", body = EmployeeData),
+            (status = 404, description = "Employee not found", body = ErrorBody),
+        )
+    )
+)]
+async fn get_employee(
+    State(state): State<ServerState>,
+    Path(employee_id): Path<String>,
+) -> Result<Json<EmployeeData>, ApiError> {
+    state
+        .payroll
+        .get_employee(&employee_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("employee {} not found", employee_id)))
+}
+
+/// Adds an employee.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/employees",
+        tag = "employees",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        request_body = EmployeeData,
+        responses(
+            (status = 201, description = "The employee", body = EmployeeData),
+            (status = 409, description = "An employee with this ID already exists", body = ErrorBody),
+        )
+    )
+)]
+async fn add_employee(
+    State(state): State<ServerState>,
+    Json(employee): Json<EmployeeData>,
+) -> Result<(StatusCode, Json<EmployeeData>), ApiError> {
+    let employee_id = employee.as_employee().employee_id().to_string();
+    let added = state.payroll.write(|payroll| {
+        let added = payroll.get_employee(&employee_id).is_none();
+        if added {
+            payroll.add_employee(employee.clone());
+        }
+        added
+    });
+    if !added {
+        return Err(ApiError::new(StatusCode::CONFLICT, format!("employee {} already exists", employee_id)));
+    }
+    state.persist().await?;
+    state.notify(WebhookEventKind::EmployeeAdded, serde_json::to_value(&employee).unwrap_or_default());
+    Ok((StatusCode::CREATED, Json(employee)))
+}
+
+/// Removes an employee; one with payroll records only with `force`.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        delete,
+        path = "/employees/{employee_id}",
+        tag = "employees",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        params(
+            ("employee_id" = String, Path, description = "Employee ID"),
+            RemoveOptions,
+        ),
+        responses(
+            (status = 204, description = "Removed"),
+            (status = 404, description = "Employee not found", body = ErrorBody),
+            (status = 409, description = "The employee has payroll records", body = ErrorBody),
+        )
+    )
+)]
+async fn remove_employee(
+    State(state): State<ServerState>,
+    Path(employee_id): Path<String>,
+    Query(options): Query<RemoveOptions>,
+) -> Result<StatusCode, ApiError> {
+    state.payroll.remove_employee(&employee_id, options.force)?;
+    state.persist().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Archives an employee who has left.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/employees/{employee_id}/archive",
+        tag = "employees",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        params(
+            ("employee_id" = String, Path, description = "Employee ID"),
+        ),
+        responses(
+            (status = 200, description = "The employee", body = EmployeeData),
+            (status = 404, description = "Employee not found", body = ErrorBody),
+        )
+    )
+)]
+async fn archive_employee(
+    State(state): State<ServerState>,
+    Path(employee_id): Path<String>,
+) -> Result<Json<EmployeeData>, ApiError> {
+    let employee = state.payroll.archive_employee(&employee_id)?;
+    state.persist().await?;
+    Ok(Json(employee))
+}
+
+/// Gets an employee's manager chain and reports.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/employees/{employee_id}/reports",
+        tag = "employees",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            ("employee_id" = String, Path, description = "Employee ID"),
+        ),
+        responses(
+            (status = 200, description = "Reporting lines", body = ReportingLines),
+            (status = 404, description = "Employee not found", body = ErrorBody),
+        )
+    )
+)]
+async fn reporting_lines(
+    State(state): State<ServerState>,
+    Path(employee_id): Path<String>,
+) -> Result<Json<ReportingLines>, ApiError> {
+    let ids = |employees: Vec<&EmployeeData>| -> Vec<String> {
+        employees
+            .into_iter()
+            .map(|employee| employee.as_employee().employee_id().to_string())
+            .collect()
+    };
+    state
+        .payroll
+        .read(|payroll| {
+            payroll.get_employee(&employee_id)?;
+            Some(ReportingLines {
+                employee_id: employee_id.clone(),
+                reporting_chain: ids(payroll.reporting_chain(&employee_id)),
+                direct_reports: ids(payroll.direct_reports(&employee_id)),
+                all_reports: ids(payroll.all_reports(&employee_id)),
+            })
+        })
+        .map(Json)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("employee {} not found", employee_id)))
+}
+
+/// Lists payroll records.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/payrolls",
+        tag = "payroll",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            RecordFilter,
+        ),
+        responses(
+            (status = 200, description = "Payroll records with their index", body = Vec<IndexedRecord>),
+        )
+    )
+)]
+async fn list_records(
+    State(state): State<ServerState>,
+    Query(filter): Query<RecordFilter>,
+) -> Json<Vec<IndexedRecord>> {
+    let records = state.payroll.read(|payroll| {
+        payroll
+            .get_payroll_records()
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| {
+                filter
+                    .employee_id
+                    .as_deref()
+                    .is_none_or(|id| record.employee.as_employee().employee_id() == id)
+            })
+            .filter(|(_, record)| {
+                filter
+                    .pay_period
+                    .as_deref()
+                    .is_none_or(|period| crate::period::PayPeriod::same_period(&record.pay_period, period))
+            })
+            .map(|(index, record)| IndexedRecord {
+                index,
+                record: record.clone(),
+            })
+            .collect()
+    });
+    Json(records)
+}
+
+/// Processes payroll for an employee, given by ID or in full.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/payrolls",
+        tag = "payroll",
+        security(("bearer" = ["payroll-process"]), ("api_key" = ["payroll-process"])),
+        request_body = ProcessRequest,
+        responses(
+            (status = 201, description = "The payroll record", body = PayrollData),
+            (status = 400, description = "Neither employee nor employee_id given", body = ErrorBody),
+            (status = 404, description = "Employee not found", body = ErrorBody),
+            (status = 409, description = "The payroll could not be processed", body = ErrorBody),
+        )
+    )
+)]
+async fn process_payroll(
+    State(state): State<ServerState>,
+    Json(request): Json<ProcessRequest>,
+) -> Result<(StatusCode, Json<PayrollData>), ApiError> {
+    let employee = match (request.employee, request.employee_id) {
+        (Some(employee), _) => employee,
+        (None, Some(employee_id)) => state.payroll.get_employee(&employee_id).ok_or_else(|| {
+            ApiError::new(StatusCode::NOT_FOUND, format!("employee {} not found", employee_id))
+        })?,
+        (None, None) => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "either employee or employee_id is required",
+            ))
+        }
+    };
+    let started = Instant::now();
+    let record = match state.payroll.process_payroll(employee, request.pay_period) {
+        Ok(record) => record,
+        Err(err) => {
+            state.metrics.processing_failed();
+            return Err(err.into());
+        }
+    };
+    state.persist().await?;
+    state.metrics.record_processed(started.elapsed());
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+/// Renders the payslip of a payroll record.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/payrolls/{index}/payslip",
+        tag = "payroll",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            ("index" = usize, Path, description = "Record index, as listed by `/payrolls`"),
+        ),
+        responses(
+            (status = 200, description = "Payslip page", content_type = "text/html", body = String),
+            (status = 404, description = "Record not found", body = ErrorBody),
+        )
+    )
+)]
+async fn payslip(
+    State(state): State<ServerState>,
+    Path(index): Path<usize>,
+) -> Result<Html<String>, ApiError> {
+    let record = state
+        .payroll
+        .read(|payroll| payroll.get_payroll_records().get(index).cloned())
+        .ok_or(PayrollError::RecordNotFound(index))?;
+    let seal = PayslipSeal::for_record(&record, None);
+    let html = render_html(&record, &seal);
+    state.notify(
+        WebhookEventKind::PayslipGenerated,
+        serde_json::json!({
+            "index": index,
+            "employee_id": record.employee.as_employee().employee_id(),
+            "pay_period": record.pay_period,
+            "hash": seal.hash,
+        }),
+    );
+    Ok(Html(html))
+}
+
+fn sign_in_headers(headers: &HeaderMap) -> (&str, &str) {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    (header("x-employee-id"), header("x-employee-pin"))
+}
+
+/// Runs `view` for the employee signed in with the `X-Employee-Id` and `X-Employee-Pin` headers.
+/// The PIN is checked against a copy of the credential on a blocking thread, so Argon2 neither
+/// holds the payroll lock nor an async worker; the lock is only written to count the attempt. The
+/// data file is saved when a lockout starts, so it holds across restarts.
+async fn with_self_service<T>(
+    state: &ServerState,
+    headers: &HeaderMap,
+    view: impl FnOnce(&SelfService) -> T,
+) -> Result<T, ApiError> {
+    let (employee_id, pin) = sign_in_headers(headers);
+    let employee_id = employee_id.trim();
+    let credential = state
+        .payroll
+        .read(|payroll| payroll.credential(employee_id).cloned())
+        .ok_or(PayrollError::InvalidCredentials)?;
+    if let Some(until) = credential.locked_at(chrono::Utc::now()) {
+        return Err(PayrollError::AccountLocked(until).into());
+    }
+    let (checked, pin) = (credential.clone(), pin.to_string());
+    let verified = tokio::task::spawn_blocking(move || checked.verify(&pin))
+        .await
+        .map_err(|err| ApiError::internal(err.to_string()))?;
+    let (signed_in, locked) = state.payroll.write(|payroll| {
+        let signed_in = payroll.record_sign_in(&credential, verified);
+        let locked = payroll.credential(employee_id).and_then(|credential| credential.locked_at(chrono::Utc::now()));
+        (signed_in, locked)
+    });
+    if locked.is_some() {
+        state.persist().await?;
+    }
+    signed_in?;
+    Ok(state.payroll.read(|payroll| {
+        let session = SelfService::new(payroll, employee_id);
+        match session.must_change_pin() {
+            true => Err(PayrollError::PinChangeRequired),
+            false => Ok(view(&session)),
+        }
+    })?)
+}
+
+/// Lists the signed-in employee's payroll records.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/me/payslips",
+        tag = "self-service",
+        security(("employee_id" = [], "employee_pin" = [])),
+        responses(
+            (status = 200, description = "The employee's payroll records", body = Vec<PayrollData>),
+            (status = 401, description = "Wrong employee ID or PIN", body = ErrorBody),
+            (status = 403, description = "The PIN has to be changed first", body = ErrorBody),
+            (status = 429, description = "Locked after too many failed sign-ins", body = ErrorBody),
+        )
+    )
+)]
+async fn own_payslips(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PayrollData>>, ApiError> {
+    with_self_service(&state, &headers, |session| session.payslips().into_iter().cloned().collect())
+        .await
+        .map(Json)
+}
+
+/// Renders the signed-in employee's payslip for a pay period.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/me/payslips/{pay_period}",
+        tag = "self-service",
+        security(("employee_id" = [], "employee_pin" = [])),
+        params(
+            ("pay_period" = String, Path, description = "Pay period, e.g. 2026-01"),
+        ),
+        responses(
+            (status = 200, description = "Payslip page", content_type = "text/html", body = String),
+            (status = 404, description = "No payslip for the period", body = ErrorBody),
+            (status = 401, description = "Wrong employee ID or PIN", body = ErrorBody),
+            (status = 403, description = "The PIN has to be changed first", body = ErrorBody),
+            (status = 429, description = "Locked after too many failed sign-ins", body = ErrorBody),
+        )
+    )
+)]
+async fn own_payslip(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Path(pay_period): Path<String>,
+) -> Result<Html<String>, ApiError> {
+    let record = with_self_service(&state, &headers, |session| session.payslip(&pay_period).cloned())
+        .await?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("no payslip for {}", pay_period)))?;
+    let seal = PayslipSeal::for_record(&record, None);
+    Ok(Html(render_html(&record, &seal)))
+}
+
+/// Gets the signed-in employee's year-to-date totals.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/me/ytd",
+        tag = "self-service",
+        security(("employee_id" = [], "employee_pin" = [])),
+        params(
+            YearQuery,
+        ),
+        responses(
+            (status = 200, description = "Year-to-date totals", body = YearToDate),
+            (status = 401, description = "Wrong employee ID or PIN", body = ErrorBody),
+            (status = 403, description = "The PIN has to be changed first", body = ErrorBody),
+            (status = 429, description = "Locked after too many failed sign-ins", body = ErrorBody),
+        )
+    )
+)]
+async fn own_year_to_date(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Query(query): Query<YearQuery>,
+) -> Result<Json<YearToDate>, ApiError> {
+    let year = query.year.unwrap_or_else(|| chrono::Local::now().year());
+    let key = CacheKey::YearToDate {
+        employee_id: sign_in_headers(&headers).0.trim().to_string(),
+        year,
+    };
+    let revision = state.revision();
+    // The employee still signs in for a cached answer.
+    let cached = state.cached(&key, revision).await;
+    let ytd = with_self_service(&state, &headers, |session| {
+        cached.unwrap_or_else(|| session.year_to_date(year))
+    })
+    .await?;
+    if cached.is_none() {
+        state.cache(&key, revision, &ytd).await;
+    }
+    Ok(Json(ytd))
+}
+
+/// Gets the payroll totals of a pay period.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/reports/{pay_period}",
+        tag = "reports",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            ("pay_period" = String, Path, description = "Pay period, e.g. 2026-01"),
+        ),
+        responses(
+            (status = 200, description = "Period totals", body = PeriodReport),
+            (status = 400, description = "Invalid pay period", body = ErrorBody),
+        )
+    )
+)]
+async fn period_report(
+    State(state): State<ServerState>,
+    Path(pay_period): Path<String>,
+) -> Result<Json<PeriodReport>, ApiError> {
+    let period = pay_period
+        .parse::<PayPeriod>()
+        .map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, err.to_string()))?;
+    let key = CacheKey::PeriodReport(period);
+    let revision = state.revision();
+    if let Some(report) = state.cached(&key, revision).await {
+        return Ok(Json(report));
+    }
+    let report = state
+        .payroll
+        .read(|payroll| payroll.period_report(period));
+    state.cache(&key, revision, &report).await;
+    Ok(Json(report))
+}
+
+/// Gets headcount and totals per department and pay period.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/reports/departments",
+        tag = "reports",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            PeriodRangeQuery,
+        ),
+        responses(
+            (status = 200, description = "Department totals, by period and then department", body = [DepartmentMonth]),
+            (status = 400, description = "Invalid range", body = ErrorBody),
+        )
+    )
+)]
+async fn department_totals(
+    State(state): State<ServerState>,
+    Query(query): Query<PeriodRangeQuery>,
+) -> Result<Json<Vec<DepartmentMonth>>, ApiError> {
+    let bad_request = |message: String| ApiError::new(StatusCode::BAD_REQUEST, message);
+    let to = match query.to {
+        Some(to) => to.parse::<PayPeriod>().map_err(bad_request)?,
+        None => PayPeriod::from_date(chrono::Local::now().date_naive()),
+    };
+    let from = match query.from {
+        Some(from) => from.parse::<PayPeriod>().map_err(bad_request)?,
+        None => PayPeriod { month: 1, ..to },
+    };
+    if from > to {
+        return Err(bad_request(format!("{} is after {}", from, to)));
+    }
+    Ok(Json(state.payroll.read(|payroll| payroll.department_totals(from, to))))
+}
+
+/// Most months one calendar request covers.
+const MAX_CALENDAR_MONTHS: i32 = 120;
+
+/// Gets pay dates and PPh 21 and BPJS deadlines as an iCalendar file.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/calendar.ics",
+        tag = "reports",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            CalendarQuery,
+        ),
+        responses(
+            (status = 200, description = "iCalendar file", content_type = "text/calendar", body = String),
+            (status = 400, description = "Invalid range", body = ErrorBody),
+        )
+    )
+)]
+async fn payroll_calendar_ics(
+    State(state): State<ServerState>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Response, ApiError> {
+    let bad_request = |message: String| ApiError::new(StatusCode::BAD_REQUEST, message);
+    let (default_from, default_to) = calendar::default_range(chrono::Local::now().date_naive());
+    let parse = |value: Option<String>, default| match value {
+        Some(value) => value.parse::<PayPeriod>().map_err(bad_request),
+        None => Ok(default),
+    };
+    let from = parse(query.from, default_from)?;
+    let to = parse(query.to, default_to)?;
+    let months = (to.year - from.year) * 12 + to.month as i32 - from.month as i32 + 1;
+    if !(1..=MAX_CALENDAR_MONTHS).contains(&months) {
+        return Err(bad_request(format!(
+            "the calendar must cover 1 to {} months, from {} to {}",
+            MAX_CALENDAR_MONTHS, from, to
+        )));
+    }
+    let mut ics = Vec::new();
+    write_ics(&payroll_calendar(from, to, state.pay_day), chrono::Utc::now(), &mut ics)
+        .map_err(|err| ApiError::internal(err.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], ics).into_response())
+}
+
+/// Changes the signed-in employee's PIN.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/me/pin",
+        tag = "self-service",
+        request_body = ChangePinRequest,
+        security(("employee_id" = [], "employee_pin" = [])),
+        responses(
+            (status = 204, description = "Changed"),
+            (status = 400, description = "The new PIN is not allowed", body = ErrorBody),
+            (status = 401, description = "Wrong employee ID or PIN", body = ErrorBody),
+            (status = 429, description = "Locked after too many failed sign-ins", body = ErrorBody),
+        )
+    )
+)]
+async fn change_own_pin(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(request): Json<ChangePinRequest>,
+) -> Result<StatusCode, ApiError> {
+    let (employee_id, pin) = sign_in_headers(&headers);
+    let result = state
+        .payroll
+        .write(|payroll| payroll.change_pin(employee_id, pin, &request.new_pin));
+    state.persist().await?;
+    result?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates a draft payroll run for a pay period.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/runs",
+        tag = "runs",
+        security(("bearer" = ["payroll-process"]), ("api_key" = ["payroll-process"])),
+        request_body = RunRequest,
+        responses(
+            (status = 201, description = "The run", body = PayrollRun),
+            (status = 409, description = "The run could not be created", body = ErrorBody),
+        )
+    )
+)]
+async fn create_run(
+    State(state): State<ServerState>,
+    Json(request): Json<RunRequest>,
+) -> Result<(StatusCode, Json<PayrollRun>), ApiError> {
+    let run = state.payroll.create_run(&request.pay_period)?;
+    state.persist().await?;
+    Ok((StatusCode::CREATED, Json(run)))
+}
+
+/// Gets a payroll run.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/runs/{run_id}",
+        tag = "runs",
+        security(("bearer" = ["read"]), ("api_key" = ["read"])),
+        params(
+            ("run_id" = u32, Path, description = "Run ID"),
+        ),
+        responses(
+            (status = 200, description = "The run", body = PayrollRun),
+            (status = 404, description = "Run not found", body = ErrorBody),
+        )
+    )
+)]
+async fn get_run(
+    State(state): State<ServerState>,
+    Path(run_id): Path<u32>,
+) -> Result<Json<PayrollRun>, ApiError> {
+    state
+        .payroll
+        .read(|payroll| payroll.get_run(run_id).cloned())
+        .map(Json)
+        .ok_or_else(|| PayrollError::RunNotFound(run_id).into())
+}
+
+/// Approves a draft payroll run.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/runs/{run_id}/approve",
+        tag = "runs",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        params(
+            ("run_id" = u32, Path, description = "Run ID"),
+        ),
+        responses(
+            (status = 200, description = "The run", body = PayrollRun),
+            (status = 404, description = "Run not found", body = ErrorBody),
+            (status = 409, description = "The run is already approved", body = ErrorBody),
+        )
+    )
+)]
+async fn approve_run(
+    State(state): State<ServerState>,
+    Path(run_id): Path<u32>,
+) -> Result<Json<PayrollRun>, ApiError> {
+    let run = state.payroll.approve_run(run_id)?;
+    state.persist().await?;
+    state.metrics.run_approved();
+    let (headcount, total_net) = state.payroll.read(|payroll| {
+        let records = payroll.run_records(&run);
+        (records.len(), records.iter().map(|record| record.net_salary).sum::<f64>())
+    });
+    state.notify(
+        WebhookEventKind::RunCompleted,
+        serde_json::json!({
+            "run_id": run.run_id,
+            "pay_period": run.pay_period,
+            "headcount": headcount,
+            "total_net": total_net,
+        }),
+    );
+    state.announce_run(run.run_id);
+    Ok(Json(run))
+}
+
+/// Lists webhooks.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/webhooks",
+        tag = "webhooks",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        responses(
+            (status = 200, description = "Registered webhooks; secrets are not shown", body = Vec<Webhook>),
+        )
+    )
+)]
+async fn list_webhooks(State(state): State<ServerState>) -> Json<Vec<Webhook>> {
+    let webhooks = state.webhooks.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    Json(webhooks.list().to_vec())
+}
+
+/// Registers a webhook.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        post,
+        path = "/webhooks",
+        tag = "webhooks",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        request_body = WebhookRequest,
+        responses(
+            (status = 201, description = "The webhook", body = Webhook),
+            (status = 400, description = "The URL is not http or https", body = ErrorBody),
+        )
+    )
+)]
+async fn register_webhook(
+    State(state): State<ServerState>,
+    Json(request): Json<WebhookRequest>,
+) -> Result<(StatusCode, Json<Webhook>), ApiError> {
+    if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "webhook url must be http or https"));
+    }
+    let hook = state
+        .webhooks
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .register(request.url, request.secret, request.events);
+    Ok((StatusCode::CREATED, Json(hook)))
+}
+
+/// Removes a webhook.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        delete,
+        path = "/webhooks/{id}",
+        tag = "webhooks",
+        security(("bearer" = ["admin"]), ("api_key" = ["admin"])),
+        params(
+            ("id" = u32, Path, description = "Webhook ID"),
+        ),
+        responses(
+            (status = 204, description = "Removed"),
+            (status = 404, description = "Webhook not found"),
+        )
+    )
+)]
+async fn remove_webhook(State(state): State<ServerState>, Path(id): Path<u32>) -> StatusCode {
+    let removed = state
+        .webhooks
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(id);
+    if removed {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{key_sha256, ApiKey};
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    fn employee(employee_id: &str) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            employee_id.to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            10_000_000.0,
+        ))
+    }
+
+    fn key(name: &str, scope: Scope) -> ApiKey {
+        ApiKey {
+            name: name.to_string(),
+            key_sha256: key_sha256(&format!("{}-key", name)),
+            scope,
+        }
+    }
+
+    fn secured() -> ServerState {
+        ServerState::new(SharedPayroll::default()).with_auth(ApiAuthConfig {
+            keys: vec![key("reader", Scope::Read), key("clerk", Scope::PayrollProcess), key("admin", Scope::Admin)],
+            jwt: None,
+        })
+    }
+
+    /// Sends a request through the router, as from 192.0.2.1, with the given headers and JSON body.
+    async fn send(
+        state: &ServerState,
+        method: &str,
+        uri: &str,
+        headers: &[(&str, &str)],
+        body: Option<EmployeeData>,
+    ) -> Response {
+        let mut request = axum::http::Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let body = match body {
+            Some(body) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(serde_json::to_vec(&body).unwrap())
+            }
+            None => Body::empty(),
+        };
+        let mut request = request.body(body).unwrap();
+        request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))));
+        router(state.clone()).oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_need_a_credential_once_keys_are_configured() {
+        let open = ServerState::new(SharedPayroll::default());
+        assert_eq!(send(&open, "GET", "/employees", &[], None).await.status(), StatusCode::OK);
+
+        let state = secured();
+        let response = send(&state, "GET", "/employees", &[], None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let guessed = send(&state, "GET", "/employees", &[("authorization", "Bearer guess")], None).await;
+        assert_eq!(guessed.status(), StatusCode::UNAUTHORIZED);
+
+        let bearer = send(&state, "GET", "/employees", &[("authorization", "Bearer reader-key")], None).await;
+        assert_eq!(bearer.status(), StatusCode::OK);
+        let api_key = send(&state, "GET", "/employees", &[("x-api-key", "reader-key")], None).await;
+        assert_eq!(api_key.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn each_endpoint_needs_its_scope() {
+        let state = secured();
+        let add = |name: &'static str| {
+            let state = state.clone();
+            async move {
+                let key = format!("{}-key", name);
+                send(&state, "POST", "/employees", &[("x-api-key", key.as_str())], Some(employee(name))).await.status()
+            }
+        };
+        assert_eq!(add("reader").await, StatusCode::FORBIDDEN);
+        assert_eq!(add("clerk").await, StatusCode::FORBIDDEN);
+        assert_eq!(add("admin").await, StatusCode::CREATED);
+        assert!(state.payroll().get_employee("admin").is_some());
+        assert!(state.payroll().get_employee("reader").is_none());
+
+        let runs = send(&state, "GET", "/runs/1", &[("x-api-key", "clerk-key")], None).await;
+        assert_eq!(runs.status(), StatusCode::NOT_FOUND, "payroll-process includes read");
+    }
+
+    #[tokio::test]
+    async fn adding_an_employee_twice_is_a_conflict() {
+        let state = ServerState::new(SharedPayroll::default());
+        assert_eq!(send(&state, "POST", "/employees", &[], Some(employee("E1"))).await.status(), StatusCode::CREATED);
+
+        let mut changed = employee("E1");
+        let EmployeeData::Fulltime(fulltime) = &mut changed else { unreachable!() };
+        fulltime.base_salary = 1.0;
+        assert_eq!(send(&state, "POST", "/employees", &[], Some(changed)).await.status(), StatusCode::CONFLICT);
+        let EmployeeData::Fulltime(kept) = state.payroll().get_employee("E1").unwrap() else { unreachable!() };
+        assert_eq!(kept.base_salary, 10_000_000.0);
+    }
+
+    #[tokio::test]
+    async fn clients_over_the_rate_limit_are_refused_with_retry_after() {
+        let state = secured().with_rate_limit(RateLimiter::new(60, 2).unwrap());
+        for _ in 0..2 {
+            let response = send(&state, "GET", "/employees", &[("x-api-key", "reader-key")], None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let limited = send(&state, "GET", "/employees", &[("x-api-key", "reader-key")], None).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key(header::RETRY_AFTER));
+        assert!(state.metrics().render(0, 0).contains("payroll_rate_limited_total 1\n"));
+
+        let other = send(&state, "GET", "/employees", &[("x-api-key", "admin-key")], None).await;
+        assert_eq!(other.status(), StatusCode::OK, "each key has its own allowance");
+        for _ in 0..2 {
+            send(&state, "GET", "/employees", &[("x-api-key", "guess")], None).await;
+        }
+        let guessing = send(&state, "GET", "/employees", &[("x-api-key", "another-guess")], None).await;
+        assert_eq!(guessing.status(), StatusCode::TOO_MANY_REQUESTS, "refused keys count against the address");
+    }
+
+    #[tokio::test]
+    async fn every_request_is_written_to_the_access_log() {
+        struct TempLog(std::path::PathBuf);
+
+        impl Drop for TempLog {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        let log = TempLog(std::env::temp_dir().join(format!("employee-access-{}.jsonl", std::process::id())));
+        let _ = std::fs::remove_file(&log.0);
+        let state = secured().with_access_log(AccessLog::open(&log.0).unwrap());
+        state.payroll().add_employee(employee("E1"));
+        send(&state, "GET", "/employees/E1", &[("x-api-key", "reader-key")], None).await;
+        send(&state, "GET", "/payrolls?employee_id=E1", &[("x-api-key", "reader-key")], None).await;
+        send(&state, "POST", "/employees", &[("x-api-key", "reader-key")], Some(employee("E2"))).await;
+        send(&state, "GET", "/employees", &[], None).await;
+
+        let entries: Vec<AccessLogEntry> = std::fs::read_to_string(&log.0)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let summary: Vec<(&str, &str, &str, Option<&str>, u16)> = entries
+            .iter()
+            .map(|entry| {
+                let employee_id = entry.employee_id.as_deref();
+                (entry.client.as_str(), entry.method.as_str(), entry.path.as_str(), employee_id, entry.status)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("reader", "GET", "/employees/E1", Some("E1"), 200),
+                ("reader", "GET", "/payrolls?employee_id=E1", Some("E1"), 200),
+                ("reader", "POST", "/employees", None, 403),
+                ("unauthenticated", "GET", "/employees", None, 401),
+            ]
+        );
+        assert!(entries.iter().all(|entry| entry.remote_addr.as_deref() == Some("192.0.2.1")));
+    }
+}
@@ -0,0 +1,477 @@
+use crate::batch::{split_csv_line, RejectedRow};
+use crate::calc::{CalculationSettings, TaxBracket};
+use crate::config::parse_number;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::ops::Deref;
+use std::str::FromStr;
+
+pub const SEVERANCE_PAY: &str = "Severance Pay (Pesangon)";
+pub const SERVICE_PAY: &str = "Long Service Pay (UPMK)";
+pub const COMPENSATION_PAY: &str = "Compensation of Rights (UPH)";
+pub const CONTRACT_COMPENSATION: &str = "Contract Compensation";
+/// The deduction line withholding the final PPh 21 on severance.
+pub const SEVERANCE_TAX: &str = "Final PPh 21 on Severance";
+
+/// Final PPh 21 on severance (PP 68/2009), charged on each layer of the total separately.
+pub const SEVERANCE_TAX_BRACKETS: [TaxBracket; 4] = [
+    TaxBracket { up_to: Some(50_000_000.0), rate: 0.0 },
+    TaxBracket { up_to: Some(100_000_000.0), rate: 0.05 },
+    TaxBracket { up_to: Some(500_000_000.0), rate: 0.15 },
+    TaxBracket { up_to: None, rate: 0.25 },
+];
+
+/// Why employment ends, which sets the severance due to permanent employees (PP 35/2021).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationReason {
+    /// Redundancy to prevent losses.
+    Layoff,
+    /// Redundancy because the company is making losses.
+    LayoffLoss,
+    Retirement,
+    Resignation,
+}
+
+impl TerminationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TerminationReason::Layoff => "layoff",
+            TerminationReason::LayoffLoss => "layoff_loss",
+            TerminationReason::Retirement => "retirement",
+            TerminationReason::Resignation => "resignation",
+        }
+    }
+
+    /// The multiple of the severance pay table due.
+    pub fn severance_factor(&self) -> f64 {
+        match self {
+            TerminationReason::Layoff => 1.0,
+            TerminationReason::LayoffLoss => 0.5,
+            TerminationReason::Retirement => 1.75,
+            TerminationReason::Resignation => 0.0,
+        }
+    }
+
+    /// The multiple of the long service pay table due.
+    pub fn service_pay_factor(&self) -> f64 {
+        match self {
+            TerminationReason::Resignation => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TerminationReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "layoff" => Ok(TerminationReason::Layoff),
+            "layoff_loss" => Ok(TerminationReason::LayoffLoss),
+            "retirement" => Ok(TerminationReason::Retirement),
+            "resignation" => Ok(TerminationReason::Resignation),
+            _ => Err(format!(
+                "Unknown termination reason '{}' (expected layoff, layoff_loss, retirement or resignation)",
+                s
+            )),
+        }
+    }
+}
+
+/// Months of wages of severance pay for `years` completed years of service, before the reason's factor.
+pub fn severance_months(years: u32) -> f64 {
+    (years + 1).min(9) as f64
+}
+
+/// Months of wages of long service pay for `years` completed years of service.
+pub fn service_pay_months(years: u32) -> f64 {
+    match years {
+        0..=2 => 0.0,
+        3..=5 => 2.0,
+        6..=8 => 3.0,
+        9..=11 => 4.0,
+        12..=14 => 5.0,
+        15..=17 => 6.0,
+        18..=20 => 7.0,
+        21..=23 => 8.0,
+        _ => 10.0,
+    }
+}
+
+pub fn severance_tax(amount: f64) -> f64 {
+    let mut tax = 0.0;
+    let mut lower = 0.0;
+    for bracket in &SEVERANCE_TAX_BRACKETS {
+        let upper = bracket.up_to.unwrap_or(f64::INFINITY).min(amount);
+        if upper > lower {
+            tax += (upper - lower) * bracket.rate;
+        }
+        lower = bracket.up_to.unwrap_or(f64::INFINITY);
+    }
+    tax
+}
+
+fn completed_months(from: NaiveDate, to: NaiveDate) -> u32 {
+    let months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    let months = if to.day() < from.day() { months - 1 } else { months };
+    months.max(0) as u32
+}
+
+/// One employee to terminate: their hire date, last day and reason, the hours of their final month
+/// (their usual hours when left out) and any compensation of rights such as untaken leave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Termination {
+    pub employee_id: String,
+    pub hired: NaiveDate,
+    pub last_day: NaiveDate,
+    pub reason: TerminationReason,
+    pub work_hours: Option<f64>,
+    pub compensation: f64,
+}
+
+impl Termination {
+    /// The pay period of the last day, which the final payroll record is processed for.
+    pub fn period(&self) -> PayPeriod {
+        PayPeriod::from_date(self.last_day)
+    }
+
+    pub fn years_of_service(&self) -> u32 {
+        completed_months(self.hired, self.last_day) / 12
+    }
+}
+
+/// What a termination pays on top of the final month's pay. Permanent employees get severance and
+/// long service pay in months of wages by years of service; contract employees get one month's
+/// wages per year worked, prorated by month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Severance {
+    pub years_of_service: u32,
+    /// Base salary plus fixed allowances; hourly rate over the standard month for contract employees.
+    pub monthly_wage: f64,
+    pub components: Vec<PayrollComponent>,
+    pub tax: f64,
+}
+
+impl Severance {
+    pub fn calculate(employee: &EmployeeData, termination: &Termination, settings: &CalculationSettings) -> Self {
+        let earnings = employee.as_employee().earning_breakdown_with(settings);
+        let base = match employee {
+            EmployeeData::Fulltime(_) => earnings.base,
+            EmployeeData::Contract(emp) => emp.hourly_rate * settings.standard_monthly_hours,
+        };
+        let monthly_wage = base + earnings.allowance;
+        let years = termination.years_of_service();
+        let reason = termination.reason;
+        let mut pay = match employee {
+            EmployeeData::Fulltime(_) => vec![
+                (SEVERANCE_PAY, severance_months(years) * reason.severance_factor() * monthly_wage),
+                (SERVICE_PAY, service_pay_months(years) * reason.service_pay_factor() * monthly_wage),
+            ],
+            EmployeeData::Contract(_) => {
+                let months = completed_months(termination.hired, termination.last_day);
+                vec![(CONTRACT_COMPENSATION, months as f64 / 12.0 * monthly_wage)]
+            }
+        };
+        pay.push((COMPENSATION_PAY, termination.compensation));
+        let components: Vec<PayrollComponent> = pay
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0.0)
+            .map(|(name, amount)| PayrollComponent {
+                name: name.to_string(),
+                amount,
+            })
+            .collect();
+        let tax = severance_tax(components.iter().map(|component| component.amount).sum());
+        Self {
+            years_of_service: years,
+            monthly_wage,
+            components,
+            tax,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.components.iter().map(|component| component.amount).sum()
+    }
+
+    /// Adds the severance to a final payroll record, with the final tax on it.
+    pub fn apply(&self, record: &mut PayrollData) {
+        for component in &self.components {
+            record.gross_salary += component.amount;
+            record.extra_earnings.push(component.clone());
+        }
+        if self.tax > 0.0 {
+            record.deductions += self.tax;
+            record.extra_deductions.push(PayrollComponent {
+                name: SEVERANCE_TAX.to_string(),
+                amount: self.tax,
+            });
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+}
+
+/// A processed termination, kept with the payroll data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminationRecord {
+    pub employee_id: String,
+    pub hired: NaiveDate,
+    pub last_day: NaiveDate,
+    pub reason: TerminationReason,
+    pub years_of_service: u32,
+    pub severance: f64,
+    pub tax: f64,
+    pub processed: DateTime<Utc>,
+}
+
+/// Every termination processed, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Terminations {
+    records: Vec<TerminationRecord>,
+}
+
+impl Terminations {
+    /// The day the employee's latest termination took effect, if they were terminated.
+    pub fn last_day(&self, employee_id: &str) -> Option<NaiveDate> {
+        self.records
+            .iter()
+            .filter(|termination| termination.employee_id == employee_id)
+            .map(|termination| termination.last_day)
+            .max()
+    }
+
+    pub(crate) fn push(&mut self, record: TerminationRecord) {
+        self.records.push(record);
+    }
+
+    /// Moves the employee's terminations to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.records.iter_mut().map(|record| renaming.id(&mut record.employee_id)).sum()
+    }
+}
+
+impl Deref for Terminations {
+    type Target = [TerminationRecord];
+
+    fn deref(&self) -> &[TerminationRecord] {
+        &self.records
+    }
+}
+
+/// A termination read from a CSV, with the line it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminationRow {
+    pub line: usize,
+    pub termination: Termination,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TerminationOutcome {
+    pub records: Vec<PayrollData>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+/// Reads `employee_id,hired,last_day,reason[,work_hours][,compensation]` rows (header required,
+/// columns in any order, dates as YYYY-MM-DD, amounts following `locale`). Bad rows are returned as
+/// rejected.
+pub fn read_terminations_csv<R: BufRead>(
+    reader: R,
+    locale: &str,
+) -> io::Result<(Vec<TerminationRow>, Vec<RejectedRow>)> {
+    let mut lines = reader.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, header)) => split_csv_line(header?.trim_start_matches('\u{feff}')),
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let find = |names: &[&str]| {
+        header.iter().position(|name| {
+            let name = name.to_lowercase().replace([' ', '-'], "_");
+            names.contains(&name.as_str())
+        })
+    };
+    let missing = |name: &str| io::Error::new(io::ErrorKind::InvalidData, format!("missing {} column", name));
+    let employee_column = find(&["employee_id", "id"]).ok_or_else(|| missing("employee_id"))?;
+    let hired_column = find(&["hired", "hire_date"]).ok_or_else(|| missing("hired"))?;
+    let last_day_column = find(&["last_day", "terminated"]).ok_or_else(|| missing("last_day"))?;
+    let reason_column = find(&["reason"]).ok_or_else(|| missing("reason"))?;
+    let hours_column = find(&["work_hours", "hours"]);
+    let compensation_column = find(&["compensation"]);
+
+    let mut terminations = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, line) in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(&line);
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| fields.get(column))
+                .filter(|value| !value.is_empty())
+        };
+        let date = |name: &str, column: usize| {
+            field(Some(column))
+                .ok_or(format!("missing {}", name))?
+                .parse::<NaiveDate>()
+                .map_err(|_| format!("{} must be YYYY-MM-DD", name))
+        };
+        let amount = |name: &str, column: Option<usize>| match field(column) {
+            None => Ok(None),
+            Some(value) => match parse_number(value, locale) {
+                Ok(amount) if amount >= 0.0 => Ok(Some(amount)),
+                Ok(_) => Err(format!("{} must not be negative", name)),
+                Err(err) => Err(format!("{}: {}", name, err)),
+            },
+        };
+        let parsed = field(Some(employee_column))
+            .ok_or_else(|| "missing employee_id".to_string())
+            .and_then(|employee_id| {
+                let hired = date("hired", hired_column)?;
+                let last_day = date("last_day", last_day_column)?;
+                if last_day < hired {
+                    return Err("last_day is before hired".to_string());
+                }
+                Ok(Termination {
+                    employee_id: employee_id.clone(),
+                    hired,
+                    last_day,
+                    reason: field(Some(reason_column)).ok_or("missing reason")?.parse()?,
+                    work_hours: amount("work_hours", hours_column)?,
+                    compensation: amount("compensation", compensation_column)?.unwrap_or(0.0),
+                })
+            });
+        match parsed {
+            Ok(termination) => terminations.push(TerminationRow {
+                line: index + 1,
+                termination,
+            }),
+            Err(reason) => rejected.push(RejectedRow {
+                line: index + 1,
+                employee_id: fields.get(employee_column).cloned().unwrap_or_default(),
+                reason,
+            }),
+        }
+    }
+    Ok((terminations, rejected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::{ContractEmployee, FulltimeEmployee};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn termination(hired: NaiveDate, last_day: NaiveDate, reason: TerminationReason) -> Termination {
+        Termination {
+            employee_id: "E1".to_string(),
+            hired,
+            last_day,
+            reason,
+            work_hours: None,
+            compensation: 0.0,
+        }
+    }
+
+    fn fulltime(base_salary: f64, allowance: f64) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            "E1".to_string(),
+            173.0,
+            allowance,
+            AllowancePeriod::Monthly,
+            base_salary,
+        ))
+    }
+
+    #[test]
+    fn severance_months_follow_years_of_service_up_to_nine() {
+        let months: Vec<f64> = [0, 1, 2, 7, 8, 9, 30].into_iter().map(severance_months).collect();
+        assert_eq!(months, [1.0, 2.0, 3.0, 8.0, 9.0, 9.0, 9.0]);
+    }
+
+    #[test]
+    fn service_pay_starts_after_three_years_and_tops_out_at_ten_months() {
+        let months: Vec<f64> = [0, 2, 3, 5, 6, 9, 12, 15, 18, 21, 23, 24].into_iter().map(service_pay_months).collect();
+        assert_eq!(months, [0.0, 0.0, 2.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 8.0, 10.0]);
+    }
+
+    #[test]
+    fn severance_tax_charges_each_layer_at_its_rate() {
+        assert_eq!(severance_tax(50_000_000.0), 0.0);
+        assert_eq!(severance_tax(100_000_000.0), 2_500_000.0);
+        assert_eq!(severance_tax(500_000_000.0), 62_500_000.0);
+        assert_eq!(severance_tax(600_000_000.0), 87_500_000.0);
+    }
+
+    #[test]
+    fn years_of_service_count_completed_years_only() {
+        let reason = TerminationReason::Layoff;
+        assert_eq!(termination(date(2016, 3, 15), date(2026, 3, 14), reason).years_of_service(), 9);
+        assert_eq!(termination(date(2016, 3, 15), date(2026, 3, 15), reason).years_of_service(), 10);
+        assert_eq!(termination(date(2026, 3, 15), date(2026, 1, 1), reason).years_of_service(), 0);
+    }
+
+    #[test]
+    fn permanent_employees_get_severance_and_service_pay_by_reason() {
+        let employee = fulltime(9_000_000.0, 1_000_000.0);
+        let settings = CalculationSettings::DEFAULT;
+        let hired = date(2016, 1, 1);
+        let last_day = date(2026, 1, 31);
+
+        let layoff = Severance::calculate(&employee, &termination(hired, last_day, TerminationReason::Layoff), &settings);
+        assert_eq!(layoff.years_of_service, 10);
+        assert_eq!(layoff.monthly_wage, 10_000_000.0);
+        assert_eq!(layoff.components[0].name, SEVERANCE_PAY);
+        assert_eq!(layoff.components[0].amount, 90_000_000.0);
+        assert_eq!(layoff.components[1].name, SERVICE_PAY);
+        assert_eq!(layoff.components[1].amount, 40_000_000.0);
+        assert_eq!(layoff.total(), 130_000_000.0);
+        assert_eq!(layoff.tax, 7_000_000.0);
+
+        let loss = termination(hired, last_day, TerminationReason::LayoffLoss);
+        assert_eq!(Severance::calculate(&employee, &loss, &settings).total(), 85_000_000.0);
+        let retirement = termination(hired, last_day, TerminationReason::Retirement);
+        assert_eq!(Severance::calculate(&employee, &retirement, &settings).total(), 197_500_000.0);
+
+        let mut resignation = termination(hired, last_day, TerminationReason::Resignation);
+        resignation.compensation = 2_000_000.0;
+        let resignation = Severance::calculate(&employee, &resignation, &settings);
+        assert_eq!(resignation.components.len(), 1);
+        assert_eq!(resignation.components[0].name, COMPENSATION_PAY);
+        assert_eq!(resignation.tax, 0.0);
+    }
+
+    #[test]
+    fn contract_employees_get_a_month_per_year_prorated_by_month() {
+        let employee = EmployeeData::Contract(ContractEmployee::new(
+            "C1".to_string(),
+            120.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            50_000.0,
+        ));
+        let ended = termination(date(2025, 1, 1), date(2026, 7, 1), TerminationReason::Resignation);
+        let severance = Severance::calculate(&employee, &ended, &CalculationSettings::DEFAULT);
+        assert_eq!(severance.monthly_wage, 8_650_000.0);
+        assert_eq!(severance.components[0].name, CONTRACT_COMPENSATION);
+        assert_eq!(severance.total(), 12_975_000.0);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::auth::Principal;
+use crate::bpjs::BpjsRates;
+use crate::error::PayrollError;
+use crate::payroll::{EmployeeData, Payroll, PayrollData, PayrollRun};
+use crate::period::PayPeriod;
+use crate::report::{MonthlyCost, PeriodComparison};
+use crate::rules::{DeductionRule, EarningRule};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[derive(Debug, Clone, Default)]
+pub struct SharedPayroll {
+    inner: Arc<RwLock<Payroll>>,
+}
+
+impl SharedPayroll {
+    pub fn new(payroll: Payroll) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(payroll)),
+        }
+    }
+
+    pub fn read<R>(&self, f: impl FnOnce(&Payroll) -> R) -> R {
+        f(&self.read_guard())
+    }
+
+    pub fn write<R>(&self, f: impl FnOnce(&mut Payroll) -> R) -> R {
+        f(&mut self.write_guard())
+    }
+
+    pub fn add_employee(&self, employee: EmployeeData) {
+        self.write(|payroll| payroll.add_employee(employee))
+    }
+
+    pub fn register_earning_rule(&self, rule: Box<dyn EarningRule>) {
+        self.write(|payroll| payroll.register_earning_rule(rule))
+    }
+
+    pub fn register_deduction_rule(&self, rule: Box<dyn DeductionRule>) {
+        self.write(|payroll| payroll.register_deduction_rule(rule))
+    }
+
+    pub fn get_employee(&self, employee_id: &str) -> Option<EmployeeData> {
+        self.read(|payroll| payroll.get_employee(employee_id).cloned())
+    }
+
+    pub fn remove_employee(&self, employee_id: &str, force: bool) -> Result<EmployeeData, PayrollError> {
+        self.write(|payroll| payroll.remove_employee(employee_id, force))
+    }
+
+    pub fn archive_employee(&self, employee_id: &str) -> Result<EmployeeData, PayrollError> {
+        self.write(|payroll| payroll.archive_employee(employee_id).cloned())
+    }
+
+    pub fn process_payroll(
+        &self,
+        employee: EmployeeData,
+        pay_period: String,
+    ) -> Result<PayrollData, PayrollError> {
+        self.write(|payroll| payroll.process_payroll(employee, pay_period))
+    }
+
+    pub fn process_batch(
+        &self,
+        employees: Vec<EmployeeData>,
+        pay_period: &str,
+    ) -> Result<Vec<PayrollData>, PayrollError> {
+        self.write(|payroll| payroll.process_batch(employees, pay_period))
+    }
+
+    pub fn void_record(&self, index: usize) -> Result<(), PayrollError> {
+        self.write(|payroll| payroll.void_record(index))
+    }
+
+    pub fn edit_record(&self, index: usize, employee: EmployeeData) -> Result<PayrollData, PayrollError> {
+        self.write(|payroll| payroll.edit_record(index, employee).cloned())
+    }
+
+    pub fn get_payroll_records(&self) -> Vec<PayrollData> {
+        self.read(|payroll| payroll.get_payroll_records().to_vec())
+    }
+
+    pub fn get_employee_payroll(&self, employee_id: &str) -> Vec<PayrollData> {
+        self.read(|payroll| {
+            payroll
+                .get_employee_payroll(employee_id)
+                .into_iter()
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn get_period_records(&self, pay_period: &str) -> Vec<PayrollData> {
+        self.read(|payroll| {
+            payroll
+                .get_period_records(pay_period)
+                .into_iter()
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn compare_periods(&self, previous_period: &str, current_period: &str) -> PeriodComparison {
+        self.read(|payroll| payroll.compare_periods(previous_period, current_period))
+    }
+
+    pub fn cost_trend(&self, from: PayPeriod, to: PayPeriod, rates: &BpjsRates) -> Vec<MonthlyCost> {
+        self.read(|payroll| payroll.cost_trend(from, to, rates))
+    }
+
+    pub fn create_run(&self, pay_period: &str) -> Result<PayrollRun, PayrollError> {
+        self.write(|payroll| payroll.create_run(pay_period).cloned())
+    }
+
+    pub fn approve_run(&self, run_id: u32) -> Result<PayrollRun, PayrollError> {
+        self.write(|payroll| payroll.approve_run(run_id).cloned())
+    }
+
+    pub fn close_period(&self, period: &str) {
+        self.write(|payroll| payroll.close_period(period))
+    }
+
+    pub fn reopen_period(&self, period: &str, principal: &Principal) -> Result<(), PayrollError> {
+        self.write(|payroll| payroll.reopen_period(period, principal))
+    }
+
+    pub fn is_period_closed(&self, period: &str) -> bool {
+        self.read(|payroll| payroll.is_period_closed(period))
+    }
+
+    fn read_guard(&self) -> RwLockReadGuard<'_, Payroll> {
+        self.inner.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write_guard(&self) -> RwLockWriteGuard<'_, Payroll> {
+        self.inner.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl From<Payroll> for SharedPayroll {
+    fn from(payroll: Payroll) -> Self {
+        Self::new(payroll)
+    }
+}
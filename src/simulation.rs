@@ -0,0 +1,176 @@
+use crate::employee::Employee;
+use crate::payroll::EmployeeData;
+use crate::tax::PtkpStatus;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default)]
+pub struct SalaryScenario {
+    pub raise_to: Option<f64>,
+    pub additional_allowance: Option<f64>,
+    pub ptkp_status: Option<PtkpStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SalaryFigures {
+    pub gross: f64,
+    pub tax: f64,
+    pub deductions: f64,
+    pub net: f64,
+}
+
+impl SalaryFigures {
+    pub fn from_employee(employee: &dyn Employee) -> Self {
+        Self {
+            gross: employee.calculate_gross(),
+            tax: employee.calculate_tax(),
+            deductions: employee.calculate_deduction(),
+            net: employee.calculate_net(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub employee_id: String,
+    pub current: SalaryFigures,
+    pub simulated: SalaryFigures,
+}
+
+impl SimulationResult {
+    pub fn gross_delta(&self) -> f64 {
+        self.simulated.gross - self.current.gross
+    }
+
+    pub fn tax_delta(&self) -> f64 {
+        self.simulated.tax - self.current.tax
+    }
+
+    pub fn deductions_delta(&self) -> f64 {
+        self.simulated.deductions - self.current.deductions
+    }
+
+    pub fn net_delta(&self) -> f64 {
+        self.simulated.net - self.current.net
+    }
+}
+
+pub struct SalarySimulator;
+
+impl SalarySimulator {
+    pub fn apply(employee: &EmployeeData, scenario: &SalaryScenario) -> EmployeeData {
+        let mut simulated = employee.clone();
+        match &mut simulated {
+            EmployeeData::Fulltime(emp) => {
+                if let Some(base_salary) = scenario.raise_to {
+                    emp.base_salary = base_salary;
+                }
+                if let Some(allowance) = scenario.additional_allowance {
+                    emp.tunjangan += allowance;
+                }
+                if let Some(status) = scenario.ptkp_status {
+                    emp.ptkp_status = Some(status);
+                }
+            }
+            EmployeeData::Contract(emp) => {
+                if let Some(hourly_rate) = scenario.raise_to {
+                    emp.hourly_rate = hourly_rate;
+                }
+                if let Some(allowance) = scenario.additional_allowance {
+                    emp.tunjangan += allowance;
+                }
+            }
+        }
+        simulated
+    }
+
+    pub fn simulate(employee: &EmployeeData, scenario: &SalaryScenario) -> SimulationResult {
+        let simulated = Self::apply(employee, scenario);
+        SimulationResult {
+            employee_id: employee.as_employee().employee_id().to_string(),
+            current: SalaryFigures::from_employee(employee.as_employee()),
+            simulated: SalaryFigures::from_employee(simulated.as_employee()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::{ContractEmployee, FulltimeEmployee};
+
+    fn fulltime(base_salary: f64) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            "E1".to_string(),
+            173.0,
+            500_000.0,
+            AllowancePeriod::Monthly,
+            base_salary,
+        ))
+    }
+
+    #[test]
+    fn an_empty_scenario_changes_nothing() {
+        let result = SalarySimulator::simulate(&fulltime(10_000_000.0), &SalaryScenario::default());
+        assert_eq!(result.employee_id, "E1");
+        assert_eq!(result.current, result.simulated);
+        assert_eq!(result.net_delta(), 0.0);
+    }
+
+    #[test]
+    fn a_raise_is_figured_as_if_it_were_the_salary() {
+        let employee = fulltime(10_000_000.0);
+        let scenario = SalaryScenario {
+            raise_to: Some(12_000_000.0),
+            additional_allowance: Some(250_000.0),
+            ..SalaryScenario::default()
+        };
+        let result = SalarySimulator::simulate(&employee, &scenario);
+
+        let raised =
+            FulltimeEmployee::new("E1".to_string(), 173.0, 750_000.0, AllowancePeriod::Monthly, 12_000_000.0);
+        assert_eq!(result.current, SalaryFigures::from_employee(employee.as_employee()));
+        assert_eq!(result.simulated, SalaryFigures::from_employee(&raised));
+        assert_eq!(result.gross_delta(), result.simulated.gross - result.current.gross);
+        assert!(result.gross_delta() > 0.0);
+        assert!(result.tax_delta() >= 0.0);
+        assert_eq!(result.net_delta(), result.simulated.net - result.current.net);
+        let EmployeeData::Fulltime(original) = &employee else { unreachable!() };
+        assert_eq!(original.base_salary, 10_000_000.0);
+    }
+
+    #[test]
+    fn more_dependants_lower_the_tax() {
+        let scenario = SalaryScenario {
+            ptkp_status: Some(PtkpStatus::K3),
+            ..SalaryScenario::default()
+        };
+        let result = SalarySimulator::simulate(&fulltime(25_000_000.0), &scenario);
+        assert_eq!(result.gross_delta(), 0.0);
+        assert!(result.tax_delta() < 0.0);
+        let EmployeeData::Fulltime(simulated) = SalarySimulator::apply(&fulltime(25_000_000.0), &scenario) else {
+            unreachable!()
+        };
+        assert_eq!(simulated.ptkp_status, Some(PtkpStatus::K3));
+    }
+
+    #[test]
+    fn a_contract_raise_sets_the_hourly_rate() {
+        let employee = EmployeeData::Contract(ContractEmployee::new(
+            "C1".to_string(),
+            100.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            50_000.0,
+        ));
+        let scenario = SalaryScenario {
+            raise_to: Some(60_000.0),
+            ptkp_status: Some(PtkpStatus::K1),
+            ..SalaryScenario::default()
+        };
+        let EmployeeData::Contract(simulated) = SalarySimulator::apply(&employee, &scenario) else { unreachable!() };
+        assert_eq!(simulated.hourly_rate, 60_000.0);
+        let result = SalarySimulator::simulate(&employee, &scenario);
+        assert!(result.gross_delta() > 0.0);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::error::StorageError;
+use crate::payroll::Payroll;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use std::io::Write;
+
+pub const MAGIC: &[u8] = b"EMPSNAP1";
+
+pub fn is_snapshot(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// MessagePack rather than a positional format such as bincode: records leave out empty optional
+/// fields, which only a self-describing format can read back.
+pub fn encode(payroll: &Payroll) -> Result<Vec<u8>, StorageError> {
+    let packed = rmp_serde::to_vec_named(payroll).map_err(|err| StorageError::Snapshot(err.to_string()))?;
+    let mut encoder = DeflateEncoder::new(MAGIC.to_vec(), Compression::default());
+    encoder.write_all(&packed)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decodes a snapshot into the same document a JSON data file parses to, so it goes through the
+/// same schema migrations.
+pub fn decode(bytes: &[u8]) -> Result<Value, StorageError> {
+    if !is_snapshot(bytes) {
+        return Err(StorageError::Snapshot("not a snapshot data file".to_string()));
+    }
+    rmp_serde::from_read(DeflateDecoder::new(&bytes[MAGIC.len()..]))
+        .map_err(|err| StorageError::Snapshot(err.to_string()))
+}
@@ -0,0 +1,78 @@
+use crate::payroll::PayrollData;
+
+/// Summary statistics over a set of amounts. Percentiles interpolate linearly between the two
+/// nearest values, so the median of an even count is the mean of the middle pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+impl Distribution {
+    pub fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count as f64;
+        Some(Self {
+            count,
+            min: values[0],
+            max: values[count - 1],
+            mean,
+            std_dev: variance.sqrt(),
+            p10: percentile(&values, 10.0),
+            p25: percentile(&values, 25.0),
+            median: percentile(&values, 50.0),
+            p75: percentile(&values, 75.0),
+            p90: percentile(&values, 90.0),
+        })
+    }
+
+    /// The spread between the 90th and 10th percentile as a multiple of the 10th, if it is non-zero.
+    pub fn p90_p10_ratio(&self) -> Option<f64> {
+        (self.p10 != 0.0).then(|| self.p90 / self.p10)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PayrollStatistics {
+    pub records: usize,
+    pub employees: usize,
+    pub gross: Option<Distribution>,
+    pub net: Option<Distribution>,
+}
+
+impl PayrollStatistics {
+    pub fn from_records(records: &[&PayrollData]) -> Self {
+        let mut employees: Vec<&str> = records
+            .iter()
+            .map(|record| record.employee.as_employee().employee_id())
+            .collect();
+        employees.sort_unstable();
+        employees.dedup();
+        Self {
+            records: records.len(),
+            employees: employees.len(),
+            gross: Distribution::from_values(records.iter().map(|record| record.gross_salary).collect()),
+            net: Distribution::from_values(records.iter().map(|record| record.net_salary).collect()),
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use crate::error::StorageError;
+use crate::ledger::PayrollLedger;
+use crate::migration::{migrate, CURRENT_SCHEMA_VERSION};
+use crate::payroll::{Payroll, PayrollData};
+use crate::transaction::Transaction;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+pub type RecordCallback<'a> = dyn FnMut(PayrollData) -> Result<(), StorageError> + 'a;
+
+pub trait Storage {
+    fn load(&self) -> Result<Payroll, StorageError>;
+    fn save(&self, payroll: &Payroll) -> Result<(), StorageError>;
+
+    /// Saves `payroll`, running `before_write` first once no one else can save, e.g. to check the
+    /// data against something else kept beside it. If it fails, nothing is saved.
+    fn save_with(
+        &self,
+        payroll: &Payroll,
+        before_write: &mut dyn FnMut() -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        before_write()?;
+        self.save(payroll)
+    }
+
+    /// Loads the payroll for a change that is saved all at once or not at all; see [`Transaction`].
+    fn transaction(&self) -> Result<Transaction<'_, Self>, StorageError>
+    where
+        Self: Sized,
+    {
+        Transaction::begin(self)
+    }
+
+    fn for_each_record(&self, f: &mut RecordCallback) -> Result<usize, StorageError> {
+        let payroll = self.load()?;
+        let records = payroll.into_payroll_records();
+        let count = records.len();
+        for record in records {
+            f(record)?;
+        }
+        Ok(count)
+    }
+}
+
+/// How a data file is written. Loading tells the formats apart by themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    /// Compressed MessagePack, much smaller and quicker to save for long histories (`snapshot`
+    /// feature).
+    Snapshot,
+}
+
+impl DataFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataFormat::Json => "json",
+            DataFormat::Snapshot => "snapshot",
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DataFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(DataFormat::Json),
+            "snapshot" | "binary" => Ok(DataFormat::Snapshot),
+            _ => Err(format!("Unknown data format '{}' (expected json or snapshot)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JsonFileStorage {
+    path: PathBuf,
+    /// The format to save in; by default the one the file is already in, JSON for a new file.
+    format: Option<DataFormat>,
+    /// Where each save logs the record changes it contains.
+    ledger: Option<PayrollLedger>,
+    #[cfg(feature = "encryption")]
+    key: Option<crate::encryption::EncryptionKey>,
+}
+
+impl JsonFileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: None,
+            ledger: None,
+            #[cfg(feature = "encryption")]
+            key: None,
+        }
+    }
+
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Appends the record changes of each save to `ledger` (usually [`PayrollLedger::beside`] the
+    /// file) while the file is locked, so the ledger holds them exactly when the file does.
+    pub fn with_ledger(mut self, ledger: PayrollLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn ledger(&self) -> Option<&PayrollLedger> {
+        self.ledger.as_ref()
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption(mut self, key: crate::encryption::EncryptionKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn decode(&self, bytes: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+        #[cfg(feature = "encryption")]
+        if crate::encryption::is_encrypted(&bytes) {
+            let key = self.key.as_ref().ok_or(StorageError::EncryptedFile)?;
+            return crate::encryption::decrypt(&bytes, key);
+        }
+        #[cfg(not(feature = "encryption"))]
+        if bytes.starts_with(ENCRYPTED_MAGIC) {
+            return Err(StorageError::EncryptedFile);
+        }
+        #[cfg(not(feature = "snapshot"))]
+        if bytes.starts_with(SNAPSHOT_MAGIC) {
+            return Err(StorageError::SnapshotFile);
+        }
+        Ok(bytes)
+    }
+
+    fn encode(&self, bytes: Vec<u8>) -> Result<Vec<u8>, StorageError> {
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.key {
+            return crate::encryption::encrypt(&bytes, key);
+        }
+        Ok(bytes)
+    }
+
+    /// The revision and format of the data file on disk; 0 and none before the first save.
+    fn stored_state(&self) -> Result<(u64, Option<DataFormat>), StorageError> {
+        #[derive(Deserialize)]
+        struct Stored {
+            #[serde(default)]
+            revision: u64,
+        }
+
+        if !self.path.exists() {
+            return Ok((0, None));
+        }
+        let bytes = self.decode(fs::read(&self.path)?)?;
+        #[cfg(feature = "snapshot")]
+        if crate::snapshot::is_snapshot(&bytes) {
+            let document = crate::snapshot::decode(&bytes)?;
+            let revision = document.get("revision").and_then(serde_json::Value::as_u64).unwrap_or(0);
+            return Ok((revision, Some(DataFormat::Snapshot)));
+        }
+        Ok((serde_json::from_slice::<Stored>(&bytes)?.revision, Some(DataFormat::Json)))
+    }
+
+    /// Takes an exclusive lock on `<data>.lock`, held until the returned file is dropped, so only one
+    /// process at a time checks and writes the data file. The data file itself is replaced on each
+    /// save, so it cannot hold the lock.
+    fn lock(&self) -> Result<File, StorageError> {
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("lock"))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    fn write(&self, payroll: &Payroll, format: DataFormat) -> Result<(), StorageError> {
+        let bytes = match format {
+            DataFormat::Json => serde_json::to_vec_pretty(payroll)?,
+            #[cfg(feature = "snapshot")]
+            DataFormat::Snapshot => crate::snapshot::encode(payroll)?,
+            #[cfg(not(feature = "snapshot"))]
+            DataFormat::Snapshot => return Err(StorageError::SnapshotFile),
+        };
+        let bytes = self.encode(bytes)?;
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+const ENCRYPTED_MAGIC: &[u8] = b"EMPENC1";
+const SNAPSHOT_MAGIC: &[u8] = b"EMPSNAP1";
+
+impl Storage for JsonFileStorage {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), fields(path = %self.path.display()), err(Display)))]
+    fn load(&self) -> Result<Payroll, StorageError> {
+        if !self.path.exists() {
+            return Ok(Payroll::new());
+        }
+        let bytes = self.decode(fs::read(&self.path)?)?;
+        #[cfg(feature = "snapshot")]
+        if crate::snapshot::is_snapshot(&bytes) {
+            return Ok(serde_json::from_value(migrate(crate::snapshot::decode(&bytes)?)?)?);
+        }
+        let document = migrate(serde_json::from_slice(&bytes)?)?;
+        Ok(serde_json::from_value(document)?)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(path = %self.path.display(), records = payroll.get_payroll_records().len()), err(Display))
+    )]
+    fn save(&self, payroll: &Payroll) -> Result<(), StorageError> {
+        self.save_with(payroll, &mut || Ok(()))
+    }
+
+    /// Refuses with [`StorageError::Conflict`] when the file was saved by another process after
+    /// `payroll` was loaded from it, rather than overwriting those changes. With a ledger, the record
+    /// changes are appended to it before the file is written, and cut off again if writing fails.
+    fn save_with(
+        &self,
+        payroll: &Payroll,
+        before_write: &mut dyn FnMut() -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let _lock = self.lock()?;
+        let loaded = payroll.revision.load(Ordering::SeqCst);
+        let (stored, stored_format) = self.stored_state()?;
+        if stored != loaded {
+            return Err(StorageError::Conflict(stored, loaded));
+        }
+        before_write()?;
+        let appended = self.ledger.as_ref().map(|ledger| ledger.record(payroll)).transpose()?;
+        payroll.revision.store(loaded + 1, Ordering::SeqCst);
+        let written = self.write(payroll, self.format.or(stored_format).unwrap_or(DataFormat::Json));
+        if written.is_err() {
+            payroll.revision.store(loaded, Ordering::SeqCst);
+            if let (Some(ledger), Some(appended)) = (&self.ledger, appended) {
+                ledger.roll_back(payroll, appended)?;
+            }
+        }
+        written
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(path = %self.path.display()), err(Display)))]
+    fn for_each_record(&self, f: &mut RecordCallback) -> Result<usize, StorageError> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let head = reader.fill_buf()?;
+        if head.starts_with(ENCRYPTED_MAGIC) || head.starts_with(SNAPSHOT_MAGIC) {
+            return self.for_each_loaded_record(f);
+        }
+
+        let mut sink = RecordSink {
+            callback: f,
+            count: 0,
+            error: None,
+            needs_migration: false,
+        };
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = deserializer.deserialize_map(PayrollVisitor {
+            sink: &mut sink,
+            schema_version: None,
+        });
+        if sink.needs_migration {
+            trace_event!(debug, "data file needs migration, streaming from a full load");
+            return self.for_each_loaded_record(sink.callback);
+        }
+        if let Some(err) = sink.error {
+            return Err(err);
+        }
+        result?;
+        deserializer.end()?;
+        Ok(sink.count)
+    }
+}
+
+impl JsonFileStorage {
+    fn for_each_loaded_record(&self, f: &mut RecordCallback) -> Result<usize, StorageError> {
+        let payroll = self.load()?;
+        let records = payroll.into_payroll_records();
+        let count = records.len();
+        for record in records {
+            f(record)?;
+        }
+        Ok(count)
+    }
+}
+
+struct RecordSink<'a, 'f> {
+    callback: &'a mut RecordCallback<'f>,
+    count: usize,
+    error: Option<StorageError>,
+    needs_migration: bool,
+}
+
+struct PayrollVisitor<'s, 'a, 'f> {
+    sink: &'s mut RecordSink<'a, 'f>,
+    schema_version: Option<u32>,
+}
+
+impl<'de> Visitor<'de> for PayrollVisitor<'_, '_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a payroll object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "schema_version" {
+                self.schema_version = Some(map.next_value()?);
+            } else if key == "payroll_records" {
+                if self.schema_version != Some(CURRENT_SCHEMA_VERSION) {
+                    self.sink.needs_migration = true;
+                    return Err(de::Error::custom("data file needs migration"));
+                }
+                map.next_value_seed(RecordsVisitor(&mut *self.sink))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct RecordsVisitor<'s, 'a, 'f>(&'s mut RecordSink<'a, 'f>);
+
+impl<'de> DeserializeSeed<'de> for RecordsVisitor<'_, '_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RecordsVisitor<'_, '_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of payroll records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(record) = seq.next_element::<PayrollData>()? {
+            if let Err(err) = (self.0.callback)(record) {
+                self.0.error = Some(err);
+                return Err(de::Error::custom("record stream aborted"));
+            }
+            self.0.count += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::AllowancePeriod;
+    use crate::employee::FulltimeEmployee;
+    use crate::payroll::EmployeeData;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// A data file under the temp directory, removed with its lock file when dropped.
+    struct TempData(JsonFileStorage);
+
+    impl TempData {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("employee-storage-{}-{}.json", std::process::id(), name));
+            let _ = fs::remove_file(&path);
+            Self(JsonFileStorage::new(path))
+        }
+    }
+
+    impl Drop for TempData {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(self.0.path());
+            let _ = fs::remove_file(self.0.path().with_extension("lock"));
+        }
+    }
+
+    fn employee(employee_id: &str) -> EmployeeData {
+        EmployeeData::Fulltime(FulltimeEmployee::new(
+            employee_id.to_string(),
+            173.0,
+            0.0,
+            AllowancePeriod::Monthly,
+            10_000_000.0,
+        ))
+    }
+
+    #[test]
+    fn a_save_over_newer_data_is_refused() {
+        let data = TempData::new("conflict");
+        let mut first = data.0.load().unwrap();
+        let mut second = data.0.load().unwrap();
+
+        first.add_employee(employee("E1"));
+        data.0.save(&first).unwrap();
+        second.add_employee(employee("E2"));
+        assert!(matches!(data.0.save(&second), Err(StorageError::Conflict(1, 0))));
+
+        let mut reloaded = data.0.load().unwrap();
+        assert!(reloaded.get_employee("E1").is_some());
+        assert!(reloaded.get_employee("E2").is_none());
+        reloaded.add_employee(employee("E2"));
+        data.0.save(&reloaded).unwrap();
+        data.0.save(&reloaded).unwrap();
+        assert_eq!(data.0.load().unwrap().known_employees().len(), 2);
+    }
+
+    #[test]
+    fn a_failed_check_before_writing_saves_nothing() {
+        let data = TempData::new("before-write");
+        let mut payroll = data.0.load().unwrap();
+        payroll.add_employee(employee("E1"));
+        let refused = data.0.save_with(&payroll, &mut || Err(StorageError::Conflict(0, 0)));
+        assert!(refused.is_err());
+        assert!(!data.0.path().exists());
+
+        data.0.save(&payroll).unwrap();
+        assert!(data.0.load().unwrap().get_employee("E1").is_some());
+    }
+
+    #[test]
+    fn a_save_waits_for_the_lock() {
+        let data = TempData::new("lock");
+        let lock = data.0.lock().unwrap();
+        let (saved, waited) = mpsc::channel();
+        let storage = data.0.clone();
+        let saver = thread::spawn(move || {
+            let mut payroll = storage.load().unwrap();
+            payroll.add_employee(employee("E1"));
+            storage.save(&payroll).unwrap();
+            saved.send(()).unwrap();
+        });
+
+        assert!(waited.recv_timeout(Duration::from_millis(200)).is_err(), "saved while the file was locked");
+        drop(lock);
+        waited.recv_timeout(Duration::from_secs(10)).unwrap();
+        saver.join().unwrap();
+        assert!(data.0.load().unwrap().get_employee("E1").is_some());
+    }
+
+    #[test]
+    fn concurrent_writers_that_retry_on_conflict_keep_every_change() {
+        let data = TempData::new("writers");
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let storage = data.0.clone();
+                thread::spawn(move || loop {
+                    let mut payroll = storage.load().unwrap();
+                    payroll.add_employee(employee(&format!("E{}", writer)));
+                    match storage.save(&payroll) {
+                        Ok(()) => break,
+                        Err(StorageError::Conflict(..)) => continue,
+                        Err(err) => panic!("{}", err),
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(data.0.load().unwrap().known_employees().len(), 4);
+    }
+}
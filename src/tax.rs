@@ -1,3 +1,19 @@
+use crate::calc::{self, CalculationSettings};
+use crate::employee::EmployeeProfile;
+use crate::invariants;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+pub use crate::calc::{
+    PtkpStatus, TaxBracket, TaxRounding, TaxTable, CONTRACT_TAX_RATE, CONTRACT_TAX_TABLE_ID, FULLTIME_TAX_BRACKETS,
+    FULLTIME_TAX_TABLE_ID, MAX_TAX_BRACKETS, MAX_TAX_TABLE_ID_LEN,
+};
+
+/// PPh 26 withheld from non-residents' gross pay where no tax treaty lowers it.
+pub const PPH26_RATE: f64 = 0.20;
+pub const PPH26_TAX_TABLE_ID: &str = "pph26";
+
 pub trait Tax {
     fn calculate_tax(&self, gross_salary: f64) -> f64;
 }
@@ -7,15 +23,7 @@ pub struct FulltimeTax;
 
 impl Tax for FulltimeTax {
     fn calculate_tax(&self, gross_salary: f64) -> f64 {
-        if gross_salary <= 54_000_000.0 {
-            gross_salary * 0.05
-        } else if gross_salary <= 250_000_000.0 {
-            gross_salary * 0.15
-        } else if gross_salary <= 500_000_000.0 {
-            gross_salary * 0.25
-        } else {
-            gross_salary * 0.30
-        }
+        calc::bracket_tax(gross_salary, &FULLTIME_TAX_BRACKETS)
     }
 }
 
@@ -24,6 +32,147 @@ pub struct ContractTax;
 
 impl Tax for ContractTax {
     fn calculate_tax(&self, gross_salary: f64) -> f64 {
-        gross_salary * 0.025
+        calc::contract_tax(gross_salary)
+    }
+}
+
+/// A named bracket table that full-time employees' tax is charged on. Besides the
+/// built-in tables, regimes can be defined in the configuration (`[[tax.regimes]]`), e.g. another
+/// country's rates or a table to simulate with, and chosen for the company or for one employee.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxRegime {
+    pub id: String,
+    pub brackets: Vec<TaxBracket>,
+}
+
+impl TaxRegime {
+    /// The regime as settings carry it; `None` when it is too large (see [`TaxTable::new`]).
+    pub fn table(&self) -> Option<TaxTable> {
+        TaxTable::new(&self.id, &self.brackets)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("tax regimes need an id".to_string());
+        }
+        if self.table().is_none() {
+            return Err(format!(
+                "tax regime {} needs an id of at most {} bytes and at most {} brackets",
+                self.id, MAX_TAX_TABLE_ID_LEN, MAX_TAX_BRACKETS
+            ));
+        }
+        match invariants::check_brackets(&format!("tax regime {}", self.id), &self.brackets).first() {
+            Some(violation) => Err(violation.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Tax for TaxRegime {
+    fn calculate_tax(&self, gross_salary: f64) -> f64 {
+        calc::bracket_tax(gross_salary, &self.brackets)
+    }
+}
+
+/// The built-in table or the one among `regimes` that `id` names.
+pub fn find_regime(regimes: &[TaxRegime], id: &str) -> Option<TaxTable> {
+    let id = id.trim();
+    calc::fulltime_tax_table(id).or_else(|| {
+        regimes
+            .iter()
+            .find(|regime| regime.id.eq_ignore_ascii_case(id))
+            .and_then(TaxRegime::table)
+    })
+}
+
+/// `settings` with the employee's own tax regime, when they have one among `regimes`.
+pub fn settings_for(
+    profile: &EmployeeProfile,
+    settings: &CalculationSettings,
+    regimes: &[TaxRegime],
+) -> CalculationSettings {
+    match profile.tax_regime.as_deref().and_then(|id| find_regime(regimes, id)) {
+        Some(tax_table) => CalculationSettings {
+            tax_table,
+            ..*settings
+        },
+        None => *settings,
+    }
+}
+
+/// PPh 26 on a non-resident's pay: a flat `rate` of gross, with no PTKP allowance.
+#[derive(Debug, Clone)]
+pub struct Pph26Tax {
+    pub rate: f64,
+}
+
+impl Tax for Pph26Tax {
+    fn calculate_tax(&self, gross_salary: f64) -> f64 {
+        gross_salary * self.rate
+    }
+}
+
+/// Marks an employee as not resident in Indonesia for tax. `country` is where they are resident
+/// and `rate` the PPh 26 rate resolved for it when it was set, so records recompute the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NonResidency {
+    pub country: String,
+    pub rate: f64,
+}
+
+impl NonResidency {
+    pub fn tax(&self) -> Pph26Tax {
+        Pph26Tax { rate: self.rate }
     }
-}
\ No newline at end of file
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.country.len() != 2 || !self.country.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(format!("'{}' is not a two-letter country code", self.country));
+        }
+        if !(0.0..=1.0).contains(&self.rate) {
+            return Err("the PPh 26 rate must be between 0 and 1".to_string());
+        }
+        if self.country == "ID" {
+            return Err("employees resident in Indonesia are taxed under PPh 21".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TaxRounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaxRounding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "exact" | "none" => Ok(TaxRounding::Exact),
+            "djp" => Ok(TaxRounding::Djp),
+            _ => Err(format!("Unknown tax rounding '{}' (expected djp or exact)", s)),
+        }
+    }
+}
+
+impl FromStr for PtkpStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().replace('/', "").as_str() {
+            "TK0" => Ok(PtkpStatus::TK0),
+            "TK1" => Ok(PtkpStatus::TK1),
+            "TK2" => Ok(PtkpStatus::TK2),
+            "TK3" => Ok(PtkpStatus::TK3),
+            "K0" => Ok(PtkpStatus::K0),
+            "K1" => Ok(PtkpStatus::K1),
+            "K2" => Ok(PtkpStatus::K2),
+            "K3" => Ok(PtkpStatus::K3),
+            _ => Err(format!("Unknown PTKP status '{}'", s)),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use anstyle::{AnsiColor, Style};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub const HEADING: Style = Style::new().bold();
+pub const SUCCESS: Style = AnsiColor::Green.on_default();
+pub const WARNING: Style = AnsiColor::Yellow.on_default();
+pub const ERROR: Style = AnsiColor::Red.on_default().bold();
+
+pub struct Styled<T> {
+    style: Style,
+    value: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{:#}", self.style, self.value, self.style)
+    }
+}
+
+pub fn styled<T: fmt::Display>(style: Style, value: T) -> Styled<T> {
+    Styled { style, value }
+}
+
+pub fn heading<T: fmt::Display>(value: T) -> Styled<T> {
+    styled(HEADING, value)
+}
+
+pub fn success<T: fmt::Display>(value: T) -> Styled<T> {
+    styled(SUCCESS, value)
+}
+
+pub fn warning<T: fmt::Display>(value: T) -> Styled<T> {
+    styled(WARNING, value)
+}
+
+pub fn error<T: fmt::Display>(value: T) -> Styled<T> {
+    styled(ERROR, value)
+}
+
+/// Disables styling for everything written through `anstream`. Colors are already dropped when
+/// stdout is not a terminal or `NO_COLOR` is set.
+pub fn disable_color() {
+    anstream::ColorChoice::Never.write_global();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    Trace,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    }
+}
+
+pub fn is_quiet() -> bool {
+    verbosity() == Verbosity::Quiet
+}
+
+/// `println!` for banners, headings and confirmations; suppressed by `--quiet` so only results
+/// and errors remain.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::term::is_quiet() {
+            anstream::println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use status;
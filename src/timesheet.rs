@@ -0,0 +1,181 @@
+use crate::error::PayrollError;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// More hours than a month has cannot be real, so submissions above this are refused.
+pub const MAX_TIMESHEET_HOURS: f64 = 744.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimesheetStatus {
+    Submitted,
+    Approved,
+    Rejected(String),
+}
+
+impl fmt::Display for TimesheetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimesheetStatus::Submitted => write!(f, "submitted"),
+            TimesheetStatus::Approved => write!(f, "approved"),
+            TimesheetStatus::Rejected(reason) => write!(f, "rejected: {}", reason),
+        }
+    }
+}
+
+/// A contract employee's hours for one pay period. Once a manager above them in the reporting
+/// chain approves it, its hours replace whatever was entered when the period is processed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timesheet {
+    pub employee_id: String,
+    pub period: PayPeriod,
+    pub hours: f64,
+    pub submitted: DateTime<Utc>,
+    pub status: TimesheetStatus,
+    /// The manager who approved or rejected it.
+    pub decided_by: Option<String>,
+    pub decided: Option<DateTime<Utc>>,
+}
+
+impl Timesheet {
+    pub fn new(employee_id: &str, period: PayPeriod, hours: f64) -> Result<Self, String> {
+        validate_hours(hours)?;
+        Ok(Self {
+            employee_id: employee_id.to_string(),
+            period,
+            hours,
+            submitted: Utc::now(),
+            status: TimesheetStatus::Submitted,
+            decided_by: None,
+            decided: None,
+        })
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.status == TimesheetStatus::Approved
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == TimesheetStatus::Submitted
+    }
+}
+
+/// Every timesheet submitted, at most one per employee and pay period.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timesheets {
+    timesheets: Vec<Timesheet>,
+}
+
+impl Timesheets {
+    pub fn get(&self, employee_id: &str, period: &PayPeriod) -> Option<&Timesheet> {
+        self.timesheets
+            .iter()
+            .find(|timesheet| timesheet.employee_id == employee_id && timesheet.period == *period)
+    }
+
+    /// Timesheets waiting for approval, oldest submission first.
+    pub fn pending(&self) -> Vec<&Timesheet> {
+        let mut pending: Vec<&Timesheet> = self.timesheets.iter().filter(|timesheet| timesheet.is_pending()).collect();
+        pending.sort_by_key(|timesheet| timesheet.submitted);
+        pending
+    }
+
+    /// The hours of the employee's approved timesheet for `period`, if there is one.
+    pub fn approved_hours(&self, employee_id: &str, period: &PayPeriod) -> Option<f64> {
+        self.get(employee_id, period)
+            .filter(|timesheet| timesheet.is_approved())
+            .map(|timesheet| timesheet.hours)
+    }
+
+    /// Refuses a period whose timesheet was submitted but not approved.
+    pub fn ensure_approved(&self, employee_id: &str, pay_period: &str) -> Result<(), PayrollError> {
+        let Ok(period) = pay_period.parse::<PayPeriod>() else {
+            return Ok(());
+        };
+        match self.get(employee_id, &period) {
+            Some(timesheet) if !timesheet.is_approved() => {
+                Err(PayrollError::TimesheetNotApproved(employee_id.to_string(), period.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Stores a submission, replacing an earlier one for the period that has not been approved.
+    pub(crate) fn submit(&mut self, timesheet: Timesheet) -> Result<&Timesheet, PayrollError> {
+        let (employee_id, period) = (timesheet.employee_id.clone(), timesheet.period);
+        if self.get(&employee_id, &period).is_some_and(Timesheet::is_approved) {
+            return Err(PayrollError::TimesheetApproved(employee_id, period.to_string()));
+        }
+        self.timesheets
+            .retain(|existing| existing.employee_id != employee_id || existing.period != period);
+        self.timesheets.push(timesheet);
+        Ok(self.timesheets.last().expect("timesheet was just pushed"))
+    }
+
+    /// Approves or rejects a submitted timesheet on behalf of `approver_id`.
+    pub(crate) fn decide(
+        &mut self,
+        employee_id: &str,
+        period: &PayPeriod,
+        approver_id: &str,
+        status: TimesheetStatus,
+    ) -> Result<&Timesheet, PayrollError> {
+        let timesheet = self
+            .timesheets
+            .iter_mut()
+            .find(|timesheet| timesheet.employee_id == employee_id && timesheet.period == *period)
+            .ok_or_else(|| PayrollError::TimesheetNotFound(employee_id.to_string(), period.to_string()))?;
+        match &timesheet.status {
+            TimesheetStatus::Submitted => {}
+            TimesheetStatus::Approved => {
+                return Err(PayrollError::TimesheetApproved(employee_id.to_string(), period.to_string()))
+            }
+            TimesheetStatus::Rejected(_) => {
+                return Err(PayrollError::InvalidTimesheet(format!(
+                    "the {} timesheet of {} was rejected and has to be resubmitted",
+                    period, employee_id
+                )))
+            }
+        }
+        timesheet.status = status;
+        timesheet.decided_by = Some(approver_id.to_string());
+        timesheet.decided = Some(Utc::now());
+        Ok(timesheet)
+    }
+
+    /// Drops the timesheets of periods before `cutoff`.
+    pub(crate) fn purge_before(&mut self, cutoff: PayPeriod) {
+        self.timesheets.retain(|timesheet| timesheet.period >= cutoff);
+    }
+
+    /// Moves the employee's timesheets, and the ones they decided, to their pseudonym. Returns how
+    /// many were theirs.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        let mut renamed = 0;
+        for timesheet in &mut self.timesheets {
+            renamed += renaming.id(&mut timesheet.employee_id);
+            renaming.option(&mut timesheet.decided_by);
+        }
+        renamed
+    }
+}
+
+impl Deref for Timesheets {
+    type Target = [Timesheet];
+
+    fn deref(&self) -> &[Timesheet] {
+        &self.timesheets
+    }
+}
+
+pub fn validate_hours(hours: f64) -> Result<(), String> {
+    if !hours.is_finite() || !(0.0..=MAX_TIMESHEET_HOURS).contains(&hours) {
+        return Err(format!("hours must be between 0 and {}", MAX_TIMESHEET_HOURS));
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::error::StorageError;
+use crate::payroll::Payroll;
+use crate::storage::Storage;
+
+/// A unit of work on stored payroll data, e.g. a run that processes dozens of records and changes
+/// their status. Changes are made to the loaded payroll; nothing is written until
+/// [`commit`](Self::commit), which saves them, and appends them to the payroll ledger when the
+/// storage keeps one, all at once. Dropping the transaction instead, as on an error part way
+/// through, leaves the data file and the ledger as they were.
+pub struct Transaction<'a, S: Storage + ?Sized> {
+    storage: &'a S,
+    payroll: Payroll,
+}
+
+impl<'a, S: Storage + ?Sized> Transaction<'a, S> {
+    /// Loads the payroll from `storage` to work on.
+    pub fn begin(storage: &'a S) -> Result<Self, StorageError> {
+        Ok(Self {
+            storage,
+            payroll: storage.load()?,
+        })
+    }
+
+    pub fn payroll(&self) -> &Payroll {
+        &self.payroll
+    }
+
+    pub fn payroll_mut(&mut self) -> &mut Payroll {
+        &mut self.payroll
+    }
+
+    /// Saves the payroll; if saving fails, neither the data file nor the ledger keeps any of it.
+    pub fn commit(self) -> Result<Payroll, StorageError> {
+        self.storage.save(&self.payroll)?;
+        Ok(self.payroll)
+    }
+
+    /// Discards the changes; the same as dropping the transaction.
+    pub fn rollback(self) {}
+}
@@ -0,0 +1,271 @@
+use crate::error::PayrollError;
+use crate::payroll::PayrollData;
+use crate::period::PayPeriod;
+use crate::privacy::Renaming;
+use crate::rules::PayrollComponent;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// The deduction line withholding PPh 21 on the taxable part of per diems.
+pub const PER_DIEM_TAX: &str = "PPh 21 on Per Diem";
+/// Longest trip one travel record may cover.
+pub const MAX_TRAVEL_DAYS: u32 = 366;
+
+/// How far a business trip goes, which sets the per-diem rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DestinationTier {
+    /// Within the home city or province.
+    Local,
+    Domestic,
+    International,
+}
+
+impl DestinationTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DestinationTier::Local => "local",
+            DestinationTier::Domestic => "domestic",
+            DestinationTier::International => "international",
+        }
+    }
+}
+
+impl fmt::Display for DestinationTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DestinationTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "local" => Ok(DestinationTier::Local),
+            "domestic" => Ok(DestinationTier::Domestic),
+            "international" => Ok(DestinationTier::International),
+            _ => Err(format!(
+                "Unknown destination tier '{}' (expected local, domestic or international)",
+                s
+            )),
+        }
+    }
+}
+
+/// The daily per diem for one destination tier. Up to `non_taxable` a day is treated as a
+/// reimbursement; the rest is taxable income.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerDiemRate {
+    pub daily: f64,
+    pub non_taxable: f64,
+}
+
+impl PerDiemRate {
+    /// A rate paid in full as a reimbursement.
+    pub const fn non_taxable(daily: f64) -> Self {
+        Self {
+            daily,
+            non_taxable: daily,
+        }
+    }
+}
+
+/// Per-diem rates by destination tier (`[travel]` in the configuration).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerDiemRates {
+    pub local: PerDiemRate,
+    pub domestic: PerDiemRate,
+    pub international: PerDiemRate,
+}
+
+impl Default for PerDiemRates {
+    fn default() -> Self {
+        Self {
+            local: PerDiemRate::non_taxable(150_000.0),
+            domestic: PerDiemRate::non_taxable(400_000.0),
+            international: PerDiemRate::non_taxable(1_500_000.0),
+        }
+    }
+}
+
+impl PerDiemRates {
+    pub fn rate(&self, tier: DestinationTier) -> PerDiemRate {
+        match tier {
+            DestinationTier::Local => self.local,
+            DestinationTier::Domestic => self.domestic,
+            DestinationTier::International => self.international,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for tier in [DestinationTier::Local, DestinationTier::Domestic, DestinationTier::International] {
+            let rate = self.rate(tier);
+            if !rate.daily.is_finite() || rate.daily < 0.0 {
+                return Err(format!("travel.{}.daily must not be negative", tier));
+            }
+            if !rate.non_taxable.is_finite() || rate.non_taxable < 0.0 {
+                return Err(format!("travel.{}.non_taxable must not be negative", tier));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A business trip, paid as a per diem in `period`. The rate in force when the trip was recorded
+/// is kept with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TravelRecord {
+    pub id: u32,
+    pub employee_id: String,
+    pub destination: String,
+    pub tier: DestinationTier,
+    pub start: NaiveDate,
+    pub days: u32,
+    pub period: PayPeriod,
+    pub rate: PerDiemRate,
+    pub recorded: DateTime<Utc>,
+}
+
+impl TravelRecord {
+    pub fn amount(&self) -> f64 {
+        self.days as f64 * self.rate.daily
+    }
+
+    /// The part of [`amount`](Self::amount) above the non-taxable daily limit.
+    pub fn taxable_amount(&self) -> f64 {
+        self.days as f64 * (self.rate.daily - self.rate.non_taxable).max(0.0)
+    }
+
+    pub fn earning(&self) -> PayrollComponent {
+        PayrollComponent {
+            name: format!("Per Diem ({})", self.destination),
+            amount: self.amount(),
+        }
+    }
+}
+
+/// Every business trip recorded, with the per-diem rates new trips are paid at.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TravelLog {
+    records: Vec<TravelRecord>,
+    #[serde(skip)]
+    rates: PerDiemRates,
+}
+
+impl TravelLog {
+    pub fn rates(&self) -> &PerDiemRates {
+        &self.rates
+    }
+
+    pub fn set_rates(&mut self, rates: PerDiemRates) {
+        self.rates = rates;
+    }
+
+    pub fn get(&self, id: u32) -> Option<&TravelRecord> {
+        self.records.iter().find(|record| record.id == id)
+    }
+
+    /// Stores a trip, paid at the current rate of its tier.
+    pub(crate) fn record(
+        &mut self,
+        employee_id: &str,
+        destination: &str,
+        tier: DestinationTier,
+        start: NaiveDate,
+        days: u32,
+        period: PayPeriod,
+    ) -> Result<&TravelRecord, PayrollError> {
+        if destination.trim().is_empty() {
+            return Err(PayrollError::InvalidTravel("destination is required".to_string()));
+        }
+        if !(1..=MAX_TRAVEL_DAYS).contains(&days) {
+            return Err(PayrollError::InvalidTravel(format!("days must be between 1 and {}", MAX_TRAVEL_DAYS)));
+        }
+        let id = self.records.iter().map(|record| record.id).max().unwrap_or(0) + 1;
+        self.records.push(TravelRecord {
+            id,
+            employee_id: employee_id.to_string(),
+            destination: destination.trim().to_string(),
+            tier,
+            start,
+            days,
+            period,
+            rate: self.rates.rate(tier),
+            recorded: Utc::now(),
+        });
+        Ok(self.records.last().expect("travel record was just pushed"))
+    }
+
+    pub(crate) fn cancel(&mut self, id: u32) -> Result<TravelRecord, PayrollError> {
+        let index = self
+            .records
+            .iter()
+            .position(|record| record.id == id)
+            .ok_or(PayrollError::TravelNotFound(id))?;
+        Ok(self.records.remove(index))
+    }
+
+    /// Travel paid in `period`, or all travel, by start date.
+    pub fn in_period(&self, period: Option<&PayPeriod>) -> Vec<&TravelRecord> {
+        let mut records: Vec<&TravelRecord> = self
+            .records
+            .iter()
+            .filter(|record| period.is_none_or(|period| record.period == *period))
+            .collect();
+        records.sort_by_key(|record| (record.start, record.id));
+        records
+    }
+
+    /// Adds the per diems of the record's period to its earnings, with PPh 21 on their taxable part.
+    pub fn apply(&self, record: &mut PayrollData) {
+        let Ok(period) = record.pay_period.parse::<PayPeriod>() else {
+            return;
+        };
+        let employee_id = record.employee.as_employee().employee_id().to_string();
+        let trips: Vec<&TravelRecord> = self
+            .records
+            .iter()
+            .filter(|trip| trip.employee_id == employee_id && trip.period == period)
+            .collect();
+        if trips.is_empty() {
+            return;
+        }
+        for trip in &trips {
+            record.gross_salary += trip.amount();
+            record.extra_earnings.push(trip.earning());
+        }
+        let tax = record.add_taxable_income(trips.iter().map(|trip| trip.taxable_amount()).sum());
+        if tax > 0.0 {
+            record.deductions += tax;
+            record.extra_deductions.push(PayrollComponent {
+                name: PER_DIEM_TAX.to_string(),
+                amount: tax,
+            });
+        }
+        record.net_salary = record.gross_salary - record.deductions;
+    }
+
+    /// Drops the trips paid in periods before `cutoff`.
+    pub(crate) fn purge_before(&mut self, cutoff: PayPeriod) {
+        self.records.retain(|record| record.period >= cutoff);
+    }
+
+    /// Moves the employee's trips to their pseudonym. Returns how many there were.
+    pub(crate) fn pseudonymize(&mut self, renaming: &Renaming) -> usize {
+        self.records.iter_mut().map(|record| renaming.id(&mut record.employee_id)).sum()
+    }
+}
+
+impl Deref for TravelLog {
+    type Target = [TravelRecord];
+
+    fn deref(&self) -> &[TravelRecord] {
+        &self.records
+    }
+}
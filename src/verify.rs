@@ -0,0 +1,66 @@
+use crate::context::CALCULATION_VERSION;
+use crate::payroll::PayrollData;
+
+/// Stored and recomputed amounts closer than this match.
+const TOLERANCE: f64 = 0.01;
+
+/// A stored amount that recomputing its record does not reproduce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordMismatch {
+    /// Position of the record in the payroll's records.
+    pub index: usize,
+    pub employee_id: String,
+    pub pay_period: String,
+    pub field: &'static str,
+    pub stored: f64,
+    pub recomputed: f64,
+    /// The calculation version the record was calculated with.
+    pub calculation_version: u32,
+}
+
+impl RecordMismatch {
+    /// Whether the record was calculated by another version of the calculation, which explains the
+    /// difference; otherwise the data was changed or the calculation changed without a new version.
+    pub fn is_version_change(&self) -> bool {
+        self.calculation_version != CALCULATION_VERSION
+    }
+}
+
+/// The result of recomputing every stored record.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Verification {
+    pub checked: usize,
+    /// Records without a calculation context, which cannot be recomputed as they were calculated.
+    pub unversioned: usize,
+    pub mismatches: Vec<RecordMismatch>,
+}
+
+/// Recomputes a record from its employee snapshot with the settings in its calculation context, plus
+/// its stored extra lines, and compares gross, deductions and net with the stored amounts.
+pub fn verify_record(index: usize, record: &PayrollData) -> Vec<RecordMismatch> {
+    let Some(context) = &record.context else {
+        return Vec::new();
+    };
+    let settings = context.settings();
+    let employee = record.employee.as_employee();
+    let gross = employee.gross_with(&settings) + record.extra_earnings.iter().map(|c| c.amount).sum::<f64>();
+    let deductions = employee.deduction_breakdown_with(&settings).total()
+        + record.extra_deductions.iter().map(|c| c.amount).sum::<f64>();
+    [
+        ("gross", record.gross_salary, gross),
+        ("deductions", record.deductions, deductions),
+        ("net", record.net_salary, gross - deductions),
+    ]
+    .into_iter()
+    .filter(|(_, stored, recomputed)| (stored - recomputed).abs() > TOLERANCE)
+    .map(|(field, stored, recomputed)| RecordMismatch {
+        index,
+        employee_id: employee.employee_id().to_string(),
+        pay_period: record.pay_period.clone(),
+        field,
+        stored,
+        recomputed,
+        calculation_version: context.calculation_version,
+    })
+    .collect()
+}
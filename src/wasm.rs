@@ -0,0 +1,46 @@
+use crate::context::CalculationContext;
+use crate::employee::DeductionBreakdown;
+use crate::payroll::{EmployeeData, PayrollData};
+use crate::simulation::SalaryFigures;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SalaryCalculation {
+    pub employee_id: String,
+    pub figures: SalaryFigures,
+    pub breakdown: DeductionBreakdown,
+    pub context: CalculationContext,
+}
+
+impl SalaryCalculation {
+    pub fn for_employee(employee: &EmployeeData) -> Self {
+        let emp_ref = employee.as_employee();
+        Self {
+            employee_id: emp_ref.employee_id().to_string(),
+            figures: SalaryFigures::from_employee(emp_ref),
+            breakdown: emp_ref.deduction_breakdown(),
+            context: CalculationContext::for_employee(employee),
+        }
+    }
+}
+
+fn parse_employee(employee_json: &str) -> Result<EmployeeData, JsError> {
+    serde_json::from_str(employee_json).map_err(|err| JsError::new(&format!("invalid employee: {}", err)))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+#[wasm_bindgen(js_name = calculatePayroll)]
+pub fn calculate_payroll(employee_json: &str) -> Result<String, JsError> {
+    let employee = parse_employee(employee_json)?;
+    to_json(&SalaryCalculation::for_employee(&employee))
+}
+
+#[wasm_bindgen(js_name = processPayroll)]
+pub fn process_payroll(employee_json: &str, pay_period: &str) -> Result<String, JsError> {
+    let employee = parse_employee(employee_json)?;
+    to_json(&PayrollData::new(employee, pay_period.to_string()))
+}
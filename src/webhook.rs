@@ -0,0 +1,185 @@
+use crate::payslip::to_hex;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum WebhookEventKind {
+    #[serde(rename = "employee.added")]
+    EmployeeAdded,
+    #[serde(rename = "run.completed")]
+    RunCompleted,
+    #[serde(rename = "payslip.generated")]
+    PayslipGenerated,
+}
+
+impl WebhookEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEventKind::EmployeeAdded => "employee.added",
+            WebhookEventKind::RunCompleted => "run.completed",
+            WebhookEventKind::PayslipGenerated => "payslip.generated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookEvent {
+    pub event: WebhookEventKind,
+    pub timestamp: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event: WebhookEventKind, data: serde_json::Value) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now(),
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Webhook {
+    pub id: u32,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, kind: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WebhookRegistry {
+    hooks: Vec<Webhook>,
+    next_id: u32,
+}
+
+impl WebhookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, url: String, secret: String, events: Vec<WebhookEventKind>) -> Webhook {
+        self.next_id += 1;
+        let hook = Webhook {
+            id: self.next_id,
+            url,
+            secret,
+            events,
+        };
+        self.hooks.push(hook.clone());
+        hook
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|hook| hook.id != id);
+        self.hooks.len() != before
+    }
+
+    pub fn list(&self) -> &[Webhook] {
+        &self.hooks
+    }
+
+    pub fn subscribers(&self, kind: WebhookEventKind) -> Vec<Webhook> {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.subscribes_to(kind))
+            .cloned()
+            .collect()
+    }
+}
+
+pub trait WebhookTransport: Send + Sync {
+    fn post(&self, url: &str, headers: &[(&str, String)], body: &[u8]) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookOutcome {
+    Delivered { attempts: u32 },
+    Failed { attempts: u32, error: String },
+}
+
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+pub fn deliver_webhook(
+    hook: &Webhook,
+    event: &WebhookEvent,
+    transport: &dyn WebhookTransport,
+    policy: &RetryPolicy,
+) -> WebhookOutcome {
+    let body = serde_json::to_vec(event).expect("webhook event serializes");
+    let headers = [
+        ("Content-Type", "application/json".to_string()),
+        ("X-Payroll-Event", event.event.name().to_string()),
+        ("X-Payroll-Timestamp", event.timestamp.to_rfc3339()),
+        ("X-Payroll-Signature", webhook_signature(&hook.secret, &body)),
+    ];
+
+    let mut backoff = policy.initial_backoff;
+    let mut last_error = String::new();
+    for attempt in 1..=policy.attempts.max(1) {
+        match transport.post(&hook.url, &headers, &body) {
+            Ok(()) => return WebhookOutcome::Delivered { attempts: attempt },
+            Err(err) => last_error = err,
+        }
+        if attempt < policy.attempts {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    WebhookOutcome::Failed {
+        attempts: policy.attempts.max(1),
+        error: last_error,
+    }
+}
+
+#[cfg(feature = "notifications")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HttpWebhookTransport;
+
+#[cfg(feature = "notifications")]
+impl WebhookTransport for HttpWebhookTransport {
+    fn post(&self, url: &str, headers: &[(&str, String)], body: &[u8]) -> Result<(), String> {
+        let mut request = ureq::post(url).timeout(Duration::from_secs(10));
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        request.send_bytes(body).map(|_| ()).map_err(|err| err.to_string())
+    }
+}